@@ -3,10 +3,37 @@
 extern crate quick_xml;
 extern crate test;
 
-use quick_xml::events::Event;
-use quick_xml::Reader;
+use quick_xml::escape::escape;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::{Reader, Writer};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use test::Bencher;
 
+/// Allocator, that counts allocations and reallocations of buffers
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
 #[bench]
 fn bench_quick_xml_normal(b: &mut Bencher) {
     let src: &[u8] = include_bytes!("../tests/sample_rss.xml");
@@ -234,3 +261,394 @@ fn bench_quick_xml_one_cdata_event_trimmed(b: &mut Bencher) {
         assert_eq!(nbtxt, 518);
     })
 }
+
+/// Document with 1000 elements with 10 attributes each
+fn attributes_heavy_document() -> Vec<u8> {
+    let mut src = String::from("<root>");
+    for i in 0..1000 {
+        src.push_str(&format!("<item id='{}'", i));
+        for j in 0..9 {
+            src.push_str(&format!(r#" attribute{}="value {}""#, j, j));
+        }
+        src.push_str("/>");
+    }
+    src.push_str("</root>");
+    src.into_bytes()
+}
+
+/// Inspects attributes of only one element in a thousand, so other tags are never parsed
+#[bench]
+fn bench_quick_xml_attributes_lazy(b: &mut Bencher) {
+    let src = attributes_heavy_document();
+    b.iter(|| {
+        let mut r = Reader::from_reader(src.as_slice());
+        let mut buf = Vec::new();
+        let mut count = test::black_box(0);
+        let mut found = 0;
+        loop {
+            match r.read_event(&mut buf) {
+                Ok(Event::Empty(ref e)) => {
+                    if count == 500 {
+                        let id = e.attributes().next().unwrap().unwrap();
+                        if &*id.value == b"500" {
+                            found += 1;
+                        }
+                    }
+                    count += 1;
+                }
+                Ok(Event::Eof) => break,
+                _ => (),
+            }
+            buf.clear();
+        }
+        assert_eq!(found, 1);
+    });
+}
+
+#[bench]
+fn bench_quick_xml_attributes_checked(b: &mut Bencher) {
+    let src = attributes_heavy_document();
+    b.iter(|| {
+        let mut r = Reader::from_reader(src.as_slice());
+        let mut buf = Vec::new();
+        let mut count = test::black_box(0);
+        loop {
+            match r.read_event(&mut buf) {
+                Ok(Event::Empty(ref e)) => {
+                    for attr in e.attributes() {
+                        attr.unwrap();
+                        count += 1;
+                    }
+                }
+                Ok(Event::Eof) => break,
+                _ => (),
+            }
+            buf.clear();
+        }
+        assert_eq!(count, 10000);
+    });
+}
+
+#[bench]
+fn bench_quick_xml_attributes_unchecked(b: &mut Bencher) {
+    let src = attributes_heavy_document();
+    b.iter(|| {
+        let mut r = Reader::from_reader(src.as_slice());
+        let mut buf = Vec::new();
+        let mut count = test::black_box(0);
+        loop {
+            match r.read_event(&mut buf) {
+                Ok(Event::Empty(ref e)) => {
+                    let mut attributes = e.attributes();
+                    attributes.with_checks(false);
+                    for attr in attributes {
+                        attr.unwrap();
+                        count += 1;
+                    }
+                }
+                Ok(Event::Eof) => break,
+                _ => (),
+            }
+            buf.clear();
+        }
+        assert_eq!(count, 10000);
+    });
+}
+
+/// Multi-megabyte document, made of copies of `sample_rss.xml` inside one root element
+fn large_document() -> Vec<u8> {
+    let sample: &[u8] = include_bytes!("../tests/sample_rss.xml");
+    // Skip the xml declaration, which is allowed only at the start of the document
+    let body = &sample[sample.iter().position(|&b| b == b'>').unwrap() + 1..];
+    let mut src = b"<root>".to_vec();
+    for _ in 0..20 {
+        src.extend_from_slice(body);
+    }
+    src.extend_from_slice(b"</root>");
+    src
+}
+
+/// Reads a multi-megabyte document and unescapes all its text and attribute values
+#[bench]
+fn bench_quick_xml_large_document(b: &mut Bencher) {
+    let src = large_document();
+    b.bytes = src.len() as u64;
+    b.iter(|| {
+        let mut r = Reader::from_reader(src.as_slice());
+        r.check_end_names(false).check_comments(false);
+        let mut buf = Vec::new();
+        let mut count = test::black_box(0);
+        loop {
+            match r.read_event(&mut buf) {
+                Ok(Event::Start(ref e)) | Ok(Event::Empty(ref e)) => {
+                    for attr in e.attributes() {
+                        count += attr.unwrap().unescaped_value().unwrap().len();
+                    }
+                }
+                Ok(Event::Text(ref e)) => count += e.unescaped().unwrap().len(),
+                Ok(Event::Eof) => break,
+                _ => (),
+            }
+            buf.clear();
+        }
+        assert!(count > 0);
+    });
+}
+
+/// Escapes a multi-megabyte document, which contains a lot of markup characters
+#[bench]
+fn bench_escape_large_document(b: &mut Bencher) {
+    let src = large_document();
+    b.bytes = src.len() as u64;
+    b.iter(|| {
+        let escaped = escape(&src);
+        assert!(escaped.len() > src.len());
+    });
+}
+
+/// Escapes multi-megabyte text, where special characters are rare
+#[bench]
+fn bench_escape_large_text(b: &mut Bencher) {
+    let mut src = Vec::new();
+    while src.len() < 4 << 20 {
+        src.extend_from_slice(b"Lorem ipsum dolor sit amet, consectetur adipiscing elit, sed do ");
+        src.extend_from_slice(b"eiusmod tempor incididunt ut labore et dolore magna aliqua & co. ");
+    }
+    b.bytes = src.len() as u64;
+    b.iter(|| {
+        let escaped = escape(&src);
+        assert!(escaped.len() > src.len());
+    });
+}
+
+const ATTRIBUTES: [(&str, &str); 16] = [
+    ("id", "element-0001"),
+    ("class", "item item-large"),
+    ("x", "125.5"),
+    ("y", "-17.25"),
+    ("width", "300"),
+    ("height", "200"),
+    ("fill", "#ff8800"),
+    ("stroke", "#000000"),
+    ("stroke-width", "2"),
+    ("opacity", "0.75"),
+    ("transform", "rotate(45)"),
+    ("title", "A large element"),
+    ("lang", "en"),
+    ("hidden", "false"),
+    ("tabindex", "-1"),
+    ("data-value", "some value & more"),
+];
+
+/// Builds 10k start tags with many attributes and returns the number of allocations made
+fn build_start_tags(capacity: Option<usize>) -> usize {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    for _ in 0..10_000 {
+        let mut tag = match capacity {
+            Some(capacity) => BytesStart::with_capacity("element", capacity),
+            None => BytesStart::owned_name("element"),
+        };
+        for &attr in ATTRIBUTES.iter() {
+            tag.push_attribute(attr);
+        }
+        test::black_box(&tag);
+    }
+    ALLOCATIONS.load(Ordering::Relaxed) - before
+}
+
+/// Pushes attributes to start tags, growing the content for each attribute
+#[bench]
+fn bench_push_attributes(b: &mut Bencher) {
+    b.iter(|| {
+        let allocations = build_start_tags(None);
+        assert!(allocations > 50_000, "{} allocations", allocations);
+    });
+}
+
+/// Pushes attributes to start tags with reserved capacity, which allocates one buffer per tag
+#[bench]
+fn bench_push_attributes_with_capacity(b: &mut Bencher) {
+    b.iter(|| {
+        let allocations = build_start_tags(Some(512));
+        // Escaping `&` in one of the values allocates too
+        assert_eq!(allocations, 20_000);
+    });
+}
+
+/// SVG-like document with 1000 shapes with many short attributes each
+fn svg_document() -> Vec<u8> {
+    let mut src = String::from(r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 100 100">"#);
+    for i in 0..1000 {
+        src.push_str(&format!(
+            r##"<rect id="r{}" x="{}" y="{}" width="10" height="20" rx="2" ry="2" fill="#ff8800" stroke="#000" stroke-width="1" opacity="0.5"/>"##,
+            i,
+            i % 100,
+            i / 10
+        ));
+    }
+    src.push_str("</svg>");
+    src.into_bytes()
+}
+
+/// Parses the SVG document and writes each tag as a new tag with the attributes
+/// copied, returning the number of allocations made
+fn rewrite_svg(src: &[u8], with_capacity: bool) -> usize {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    let mut r = Reader::from_reader(src);
+    let mut w = Writer::new(Vec::with_capacity(src.len()));
+    let mut buf = Vec::new();
+    loop {
+        match r.read_event(&mut buf).unwrap() {
+            Event::Empty(ref e) => {
+                let mut tag = if with_capacity {
+                    BytesStart::with_attributes_capacity(e.name(), e.len() - e.name().len())
+                } else {
+                    BytesStart::owned_name(e.name().as_ref())
+                };
+                tag.extend_attributes(e.attributes().with_checks(false).map(|a| a.unwrap()));
+                w.write_event(Event::Empty(tag)).unwrap();
+            }
+            Event::Eof => break,
+            e => w.write_event(e).unwrap(),
+        }
+        buf.clear();
+    }
+    assert_eq!(w.into_inner().len(), src.len());
+    ALLOCATIONS.load(Ordering::Relaxed) - before
+}
+
+/// Rewrites an attribute-heavy document, growing the content of each tag for each attribute
+#[bench]
+fn bench_rewrite_svg(b: &mut Bencher) {
+    let src = svg_document();
+    b.iter(|| {
+        let allocations = rewrite_svg(&src, false);
+        assert!(allocations > 3_000, "{} allocations", allocations);
+    });
+}
+
+/// Rewrites an attribute-heavy document with the content of each tag allocated once
+#[bench]
+fn bench_rewrite_svg_with_attributes_capacity(b: &mut Bencher) {
+    let src = svg_document();
+    b.iter(|| {
+        let allocations = rewrite_svg(&src, true);
+        assert!(allocations < 1_100, "{} allocations", allocations);
+    });
+}
+
+/// Document of about 50 MB, made of copies of `sample_rss.xml` inside one root element
+fn huge_document() -> Vec<u8> {
+    let sample: &[u8] = include_bytes!("../tests/sample_rss.xml");
+    let body = &sample[sample.iter().position(|&b| b == b'>').unwrap() + 1..];
+    let mut src = b"<root>".to_vec();
+    while src.len() < 50_000_000 {
+        src.extend_from_slice(body);
+    }
+    src.extend_from_slice(b"</root>");
+    src
+}
+
+/// Reads a 50 MB in-memory document, copying the events into a buffer
+#[bench]
+fn bench_quick_xml_huge_document_buffered(b: &mut Bencher) {
+    let src = huge_document();
+    b.bytes = src.len() as u64;
+    b.iter(|| {
+        let mut r = Reader::from_bytes(&src);
+        r.check_end_names(false);
+        let mut buf = Vec::new();
+        let mut count = test::black_box(0);
+        loop {
+            match r.read_event(&mut buf) {
+                Ok(Event::Start(ref e)) | Ok(Event::Empty(ref e)) => count += e.len(),
+                Ok(Event::Text(ref e)) => count += e.len(),
+                Ok(Event::Eof) => break,
+                _ => (),
+            }
+            buf.clear();
+        }
+        assert!(count > 0);
+    });
+}
+
+/// Reads a 50 MB in-memory document with events, borrowing the input
+#[bench]
+fn bench_quick_xml_huge_document_unbuffered(b: &mut Bencher) {
+    let src = huge_document();
+    b.bytes = src.len() as u64;
+    b.iter(|| {
+        let mut r = Reader::from_bytes(&src);
+        r.check_end_names(false);
+        let mut count = test::black_box(0);
+        loop {
+            match r.read_event_unbuffered() {
+                Ok(Event::Start(ref e)) | Ok(Event::Empty(ref e)) => count += e.len(),
+                Ok(Event::Text(ref e)) => count += e.len(),
+                Ok(Event::Eof) => break,
+                _ => (),
+            }
+        }
+        assert!(count > 0);
+    });
+}
+
+/// Document of `<record>` elements, every 20th of which is modified, and the others are
+/// passed through unchanged
+fn pass_through_document() -> Vec<u8> {
+    let sample: &[u8] = include_bytes!("../tests/sample_rss.xml");
+    let body = &sample[sample.iter().position(|&b| b == b'>').unwrap() + 1..];
+    let mut src = b"<root>".to_vec();
+    for i in 0..200 {
+        src.extend_from_slice(format!(r#"<record id="{}">"#, i).as_bytes());
+        src.extend_from_slice(body);
+        src.extend_from_slice(b"</record>");
+    }
+    src.extend_from_slice(b"</root>");
+    src
+}
+
+/// Filters the document, copying unchanged records either event by event or as raw bytes
+fn filter_items(src: &[u8], raw: bool) -> usize {
+    let mut r = Reader::from_reader(src);
+    r.check_end_names(false);
+    let mut w = Writer::new(Vec::with_capacity(src.len()));
+    let mut buf = Vec::new();
+    let mut count = 0;
+    loop {
+        match r.read_event(&mut buf).unwrap() {
+            Event::Start(ref e) if e.name().as_ref() == b"record" => {
+                count += 1;
+                if count % 20 == 0 {
+                    let mut start = e.to_owned();
+                    start.push_attribute(("modified", "true"));
+                    w.write_event(Event::Start(start)).unwrap();
+                } else if raw {
+                    w.copy_subtree_raw(&mut r, e).unwrap();
+                } else {
+                    w.write_event(Event::Start(e.to_borrowed())).unwrap();
+                }
+            }
+            Event::Eof => break,
+            e => w.write_event(e).unwrap(),
+        }
+        buf.clear();
+    }
+    w.into_inner().len()
+}
+
+/// Passes 95% of a document through, copying the records event by event
+#[bench]
+fn bench_pass_through_events(b: &mut Bencher) {
+    let src = pass_through_document();
+    b.bytes = src.len() as u64;
+    b.iter(|| assert!(filter_items(&src, false) > src.len()));
+}
+
+/// Passes 95% of a document through, copying the raw bytes of the unchanged records
+#[bench]
+fn bench_pass_through_raw(b: &mut Bencher) {
+    let src = pass_through_document();
+    b.bytes = src.len() as u64;
+    b.iter(|| assert!(filter_items(&src, true) > src.len()));
+}
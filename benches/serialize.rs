@@ -0,0 +1,132 @@
+#![cfg(feature = "serialize")]
+#![feature(test)]
+
+extern crate quick_xml;
+extern crate serde;
+extern crate test;
+
+use quick_xml::se::to_writer;
+use serde::Serialize;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use test::Bencher;
+
+/// Allocator, that counts allocations, to check how many of them the serializer makes
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+#[derive(Serialize)]
+struct Point {
+    id: u32,
+    x: f64,
+    y: f64,
+    label: &'static str,
+    note: &'static str,
+}
+
+#[derive(Serialize)]
+struct Points {
+    point: Vec<Point>,
+}
+
+fn points() -> Points {
+    Points {
+        point: (0..100_000)
+            .map(|i| Point {
+                id: i,
+                x: f64::from(i) / 3.0,
+                y: -f64::from(i),
+                label: "plain text",
+                note: "needs <escaping> & more",
+            })
+            .collect(),
+    }
+}
+
+/// Serializes 100k small structs and checks the number of allocations made
+#[bench]
+fn bench_serialize_small_structs(b: &mut Bencher) {
+    let points = points();
+    let mut buffer = Vec::with_capacity(16 << 20);
+    b.iter(|| {
+        buffer.clear();
+        let before = ALLOCATIONS.load(Ordering::Relaxed);
+        to_writer(&mut buffer, &points).unwrap();
+        let allocations = ALLOCATIONS.load(Ordering::Relaxed) - before;
+        // Buffers are reused between structs, so the number of allocations does not depend
+        // on the number of structs. Before that it was about 17 allocations per struct
+        assert!(allocations < 100, "{} allocations", allocations);
+    });
+}
+
+/// Serializes a map with 1M entries and checks the number of allocations made
+#[bench]
+fn bench_serialize_large_map(b: &mut Bencher) {
+    let map: BTreeMap<String, u32> = (0..1_000_000).map(|i| (format!("key{}", i), i)).collect();
+    let mut buffer = Vec::with_capacity(32 << 20);
+    b.iter(|| {
+        buffer.clear();
+        let before = ALLOCATIONS.load(Ordering::Relaxed);
+        to_writer(&mut buffer, &map).unwrap();
+        let allocations = ALLOCATIONS.load(Ordering::Relaxed) - before;
+        // Keys are written into a buffer, reused between entries, so the number of
+        // allocations does not depend on the number of entries
+        assert!(allocations < 100, "{} allocations", allocations);
+    });
+}
+
+#[derive(Serialize)]
+struct Node {
+    id: u32,
+    name: &'static str,
+    node: Vec<Node>,
+}
+
+/// Chain of nodes with one child on each level, which ends with `leaves` nodes on the
+/// last level
+fn chain(depth: u32, leaves: u32) -> Node {
+    Node {
+        id: depth,
+        name: "node",
+        node: match depth {
+            0 => Vec::new(),
+            1 => (0..leaves).map(|i| chain(0, i)).collect(),
+            _ => vec![chain(depth - 1, leaves)],
+        },
+    }
+}
+
+/// Serializes 6 levels of nested structs with 100k structs on the last level. Elements
+/// of each struct are written directly into the output, so the content of the last level
+/// is not copied once for each level above it
+#[bench]
+fn bench_serialize_nested_structs(b: &mut Bencher) {
+    let chain = chain(6, 100_000);
+    let mut buffer = Vec::with_capacity(8 << 20);
+    b.iter(|| {
+        buffer.clear();
+        to_writer(&mut buffer, &chain).unwrap();
+        assert!(buffer.len() > 1 << 20);
+    });
+}
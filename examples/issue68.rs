@@ -85,7 +85,7 @@ fn parse_report(xml_data: &str) -> Vec<Resource> {
         match reader.read_namespaced_event(&mut buf, &mut ns_buffer) {
             Ok((namespace_value, Event::Start(e))) => {
                 let namespace_value = namespace_value.unwrap_or_default();
-                match (depth, state, namespace_value, e.local_name()) {
+                match (depth, state, namespace_value, e.local_name().as_ref()) {
                     (0, State::Root, b"DAV:", b"multistatus") => state = State::MultiStatus,
                     (1, State::MultiStatus, b"DAV:", b"response") => {
                         state = State::Response;
@@ -101,7 +101,7 @@ fn parse_report(xml_data: &str) -> Vec<Resource> {
             Ok((namespace_value, Event::End(e))) => {
                 let namespace_value = namespace_value.unwrap_or_default();
                 let local_name = e.local_name();
-                match (depth, state, &*namespace_value, local_name) {
+                match (depth, state, &*namespace_value, local_name.as_ref()) {
                     (1, State::MultiStatus, b"DAV:", b"multistatus") => state = State::Root,
                     (2, State::MultiStatus, b"DAV:", b"multistatus") => state = State::MultiStatus,
                     _ => {}
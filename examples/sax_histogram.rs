@@ -0,0 +1,49 @@
+extern crate quick_xml;
+
+use quick_xml::sax::{run, Attribute, Handler, Name};
+use quick_xml::Reader;
+use std::ops::ControlFlow;
+
+/// Counts elements at each nesting depth
+#[derive(Default)]
+struct DepthHistogram {
+    depth: usize,
+    counts: Vec<usize>,
+}
+
+impl Handler for DepthHistogram {
+    fn start_element(&mut self, _name: &Name, _attributes: &[Attribute]) -> ControlFlow<()> {
+        if self.counts.len() <= self.depth {
+            self.counts.push(0);
+        }
+        self.counts[self.depth] += 1;
+        self.depth += 1;
+        ControlFlow::Continue(())
+    }
+
+    fn end_element(&mut self, _name: &Name) -> ControlFlow<()> {
+        self.depth -= 1;
+        ControlFlow::Continue(())
+    }
+}
+
+fn main() {
+    let xml = r#"<library>
+        <shelf id="1">
+            <book><title>First</title><author>A</author></book>
+            <book><title>Second</title></book>
+        </shelf>
+        <shelf id="2"/>
+    </library>"#;
+
+    let mut reader = Reader::from_str(xml);
+    let mut histogram = DepthHistogram::default();
+    if let Err(e) = run(&mut reader, &mut histogram) {
+        panic!("Error at position {}: {:?}", reader.buffer_position(), e);
+    }
+
+    for (depth, count) in histogram.counts.iter().enumerate() {
+        println!("{:>2} {:>3} {}", depth, count, "#".repeat(*count));
+    }
+    assert_eq!(histogram.counts, vec![1, 2, 2, 3]);
+}
@@ -0,0 +1,97 @@
+//! Benchmarks of the paths users care about. Run a part of them with a filter:
+//!
+//! ```text
+//! cargo bench --bench suite -- parse/
+//! cargo bench --bench suite -- serde/deserialize
+//! ```
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use perf::{
+    count_events, deep, escape, read_attributes, rss, rss_feed, svg, text, unescape,
+    wide_attributes, Rss, Titles,
+};
+use quick_xml::de::from_slice;
+use quick_xml::se::to_writer;
+
+/// Event parsing throughput on large documents of different shapes
+fn parse(c: &mut Criterion) {
+    let documents = vec![
+        ("rss", rss(5_000)),
+        ("svg", svg(5_000)),
+        ("deep", deep(500, 100)),
+        ("wide_attributes", wide_attributes(5_000, 40)),
+    ];
+    let mut group = c.benchmark_group("parse");
+    for (name, doc) in &documents {
+        // The buffer outlives the iterations, so only the first one grows it
+        let mut buf = Vec::with_capacity(4096);
+        group.throughput(Throughput::Bytes(doc.len() as u64));
+        group.bench_function(*name, |b| b.iter(|| count_events(black_box(doc), &mut buf)));
+    }
+    group.finish();
+}
+
+/// Reading and unescaping all attributes of attribute-heavy documents
+fn attributes(c: &mut Criterion) {
+    let documents = vec![
+        ("svg", svg(5_000)),
+        ("wide_attributes", wide_attributes(5_000, 40)),
+    ];
+    let mut group = c.benchmark_group("attributes");
+    for (name, doc) in &documents {
+        let (mut buf, mut value) = (Vec::with_capacity(4096), Vec::with_capacity(256));
+        group.throughput(Throughput::Bytes(doc.len() as u64));
+        group.bench_function(*name, |b| {
+            b.iter(|| read_attributes(black_box(doc), &mut buf, &mut value))
+        });
+    }
+    group.finish();
+}
+
+/// Serializing nested structs and deserializing them, all fields or only a few
+fn serde_structs(c: &mut Criterion) {
+    const ITEMS: usize = 1_000;
+
+    let feed = rss_feed(ITEMS);
+    let doc = rss(ITEMS);
+    let mut group = c.benchmark_group("serde");
+    group.throughput(Throughput::Elements(ITEMS as u64));
+
+    let mut out = Vec::with_capacity(doc.len() * 2);
+    group.bench_function("serialize", |b| {
+        b.iter(|| {
+            out.clear();
+            to_writer(&mut out, black_box(&feed)).unwrap();
+            out.len()
+        })
+    });
+    group.bench_function("deserialize", |b| {
+        b.iter(|| from_slice::<Rss>(black_box(&doc)).unwrap())
+    });
+    group.bench_function("deserialize_skipping", |b| {
+        b.iter(|| from_slice::<Titles>(black_box(&doc)).unwrap())
+    });
+    group.finish();
+}
+
+/// Escaping and unescaping of a text with many special characters
+fn escaping(c: &mut Criterion) {
+    let raw = text(1 << 20);
+    let mut escaped = Vec::with_capacity(raw.len() * 2);
+    escape(raw.as_bytes(), &mut escaped);
+    let mut out = Vec::with_capacity(raw.len() * 2);
+
+    let mut group = c.benchmark_group("escape");
+    group.throughput(Throughput::Bytes(raw.len() as u64));
+    group.bench_function("escape", |b| {
+        b.iter(|| escape(black_box(raw.as_bytes()), &mut out))
+    });
+    group.throughput(Throughput::Bytes(escaped.len() as u64));
+    group.bench_function("unescape", |b| {
+        b.iter(|| unescape(black_box(&escaped), &mut out))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, parse, attributes, serde_structs, escaping);
+criterion_main!(benches);
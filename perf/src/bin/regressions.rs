@@ -0,0 +1,210 @@
+//! Compares the last run of the benchmark suite with a saved baseline and fails, if any
+//! benchmark became slower by more than a threshold, 10% by default.
+//!
+//! ```text
+//! cargo bench --bench suite -- --save-baseline before
+//! # change the library
+//! cargo bench --bench suite -- --baseline before
+//! cargo run --release --bin regressions -- before [threshold in percents]
+//! ```
+//!
+//! Results are read from the criterion output directory: `$CRITERION_HOME`,
+//! `$CARGO_TARGET_DIR/criterion` or `target/criterion`.
+
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process;
+
+const DEFAULT_THRESHOLD: f64 = 10.0;
+
+/// Mean times of one benchmark in the baseline and in the last run, in nanoseconds
+#[derive(Debug, PartialEq)]
+struct Change {
+    name: String,
+    baseline: f64,
+    current: f64,
+}
+
+impl Change {
+    fn percent(&self) -> f64 {
+        (self.current - self.baseline) / self.baseline * 100.0
+    }
+}
+
+/// Formats nanoseconds with a unit, suitable for the time
+struct Time(f64);
+
+impl fmt::Display for Time {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (value, unit) = match self.0 {
+            t if t >= 1e9 => (t / 1e9, "s"),
+            t if t >= 1e6 => (t / 1e6, "ms"),
+            t if t >= 1e3 => (t / 1e3, "µs"),
+            t => (t, "ns"),
+        };
+        write!(f, "{:.2} {}", value, unit)
+    }
+}
+
+fn usage() -> ! {
+    eprintln!("usage: regressions <baseline> [threshold in percents]");
+    process::exit(2);
+}
+
+fn main() {
+    let mut args = env::args().skip(1);
+    let baseline = args.next().unwrap_or_else(|| usage());
+    let threshold = match args.next() {
+        Some(t) => t.parse().unwrap_or_else(|_| usage()),
+        None => DEFAULT_THRESHOLD,
+    };
+    let dir = match (
+        env::var_os("CRITERION_HOME"),
+        env::var_os("CARGO_TARGET_DIR"),
+    ) {
+        (Some(home), _) => PathBuf::from(home),
+        (None, Some(target)) => Path::new(&target).join("criterion"),
+        (None, None) => PathBuf::from("target/criterion"),
+    };
+
+    let mut changes = Vec::new();
+    if let Err(e) = collect(&dir, &dir, &baseline, &mut changes) {
+        eprintln!("cannot read results from {}: {}", dir.display(), e);
+        process::exit(2);
+    }
+    if changes.is_empty() {
+        eprintln!(
+            "no results to compare with the baseline `{}` in {}",
+            baseline,
+            dir.display()
+        );
+        process::exit(2);
+    }
+    changes.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let mut regressions = 0;
+    for change in &changes {
+        let regressed = change.percent() > threshold;
+        if regressed {
+            regressions += 1;
+        }
+        println!(
+            "{:<40} {:>12} {:>12} {:>+8.1}%{}",
+            change.name,
+            Time(change.baseline).to_string(),
+            Time(change.current).to_string(),
+            change.percent(),
+            if regressed { "  REGRESSION" } else { "" }
+        );
+    }
+    if regressions > 0 {
+        eprintln!(
+            "\n{} of {} benchmarks are more than {}% slower than `{}`",
+            regressions,
+            changes.len(),
+            threshold,
+            baseline
+        );
+        process::exit(1);
+    }
+}
+
+/// Finds benchmarks in `dir`, which have results of both the `baseline` and the last run
+fn collect(root: &Path, dir: &Path, baseline: &str, changes: &mut Vec<Change>) -> io::Result<()> {
+    let saved = dir.join(baseline).join("estimates.json");
+    let last = dir.join("new").join("estimates.json");
+    if saved.is_file() && last.is_file() {
+        let name = dir.strip_prefix(root).unwrap_or(dir);
+        changes.push(Change {
+            name: name.to_string_lossy().replace('\\', "/"),
+            baseline: read_mean(&saved)?,
+            current: read_mean(&last)?,
+        });
+        return Ok(());
+    }
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect(root, &path, baseline, changes)?;
+        }
+    }
+    Ok(())
+}
+
+fn read_mean(path: &Path) -> io::Result<f64> {
+    let json = fs::read_to_string(path)?;
+    mean(&json).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("no mean time in {}", path.display()),
+        )
+    })
+}
+
+/// Extracts the point estimate of the mean time from the `estimates.json` of criterion
+fn mean(json: &str) -> Option<f64> {
+    const POINT_ESTIMATE: &str = "\"point_estimate\"";
+
+    let mean = &json[json.find("\"mean\"")?..];
+    let value = &mean[mean.find(POINT_ESTIMATE)? + POINT_ESTIMATE.len()..];
+    let value = value.trim_start().strip_prefix(':')?.trim_start();
+    let end = value
+        .find(|c: char| c == ',' || c == '}' || c.is_whitespace())
+        .unwrap_or(value.len());
+    value[..end].parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_mean() {
+        let json = r#"{"mean":{"confidence_interval":{"confidence_level":0.95,"lower_bound":1.5,"upper_bound":2.5},"point_estimate":2.0,"standard_error":0.1},"median":{"confidence_interval":{"confidence_level":0.95,"lower_bound":1.0,"upper_bound":3.0},"point_estimate":1.75,"standard_error":0.2}}"#;
+        assert_eq!(mean(json), Some(2.0));
+        assert_eq!(mean(r#"{"mean": {"point_estimate": 1e3 }}"#), Some(1000.0));
+        assert_eq!(mean(r#"{"median":{"point_estimate":1.75}}"#), None);
+    }
+
+    #[test]
+    fn compare_with_baseline() {
+        let root = env::temp_dir().join(format!("regressions-{}", process::id()));
+        let write = |path: &str, mean: f64| {
+            let path = root.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            let json = format!(r#"{{"mean":{{"point_estimate":{}}}}}"#, mean);
+            fs::write(path, json).unwrap();
+        };
+        write("parse/rss/before/estimates.json", 100.0);
+        write("parse/rss/new/estimates.json", 120.0);
+        write("escape/before/estimates.json", 50.0);
+        write("escape/new/estimates.json", 45.0);
+        write("unescape/new/estimates.json", 45.0);
+
+        let mut changes = Vec::new();
+        collect(&root, &root, "before", &mut changes).unwrap();
+        changes.sort_by(|a, b| a.name.cmp(&b.name));
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(
+            changes,
+            vec![
+                Change {
+                    name: "escape".to_string(),
+                    baseline: 50.0,
+                    current: 45.0,
+                },
+                Change {
+                    name: "parse/rss".to_string(),
+                    baseline: 100.0,
+                    current: 120.0,
+                },
+            ]
+        );
+        assert_eq!(changes[0].percent(), -10.0);
+        assert_eq!(changes[1].percent(), 20.0);
+    }
+}
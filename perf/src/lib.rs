@@ -0,0 +1,344 @@
+//! Fixtures and workloads of the quick-xml benchmark suite.
+//!
+//! Documents are generated instead of being checked in, so they can be scaled, and the
+//! generators are deterministic, so results of different runs can be compared with each
+//! other. Workloads take the buffers they need from the caller, so a benchmark can
+//! allocate them once and measure only the work done by the library.
+
+use quick_xml::escape::{escape_into, unescape_into};
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+
+/// Generates an RSS feed with `items` items. Each item has a few children, escaped
+/// text, an attribute and a CDATA section
+pub fn rss(items: usize) -> Vec<u8> {
+    let mut doc = String::with_capacity(300 + items * 420);
+    doc.push_str("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+    doc.push_str("<rss version=\"2.0\">\n<channel>\n");
+    doc.push_str("<title>Synthetic feed</title>\n");
+    doc.push_str("<link>https://example.com/</link>\n");
+    doc.push_str("<description>News &amp; updates</description>\n");
+    for i in 0..items {
+        write!(
+            doc,
+            "<item>\n\
+             <title>Item {} &lt;{}&gt;</title>\n\
+             <link>https://example.com/items/{}</link>\n\
+             <guid isPermaLink=\"false\">item-{}</guid>\n\
+             <category>category {}</category>\n\
+             <category>tag {}</category>\n\
+             <pubDate>Mon, {:02} Jan 2018 10:{:02}:00 GMT</pubDate>\n\
+             <description><![CDATA[<p>Description of the item {}</p>]]></description>\n\
+             </item>\n",
+            i,
+            i % 7,
+            i,
+            i,
+            i % 5,
+            i % 11,
+            i % 28 + 1,
+            i % 60,
+            i
+        )
+        .unwrap();
+    }
+    doc.push_str("</channel>\n</rss>\n");
+    doc.into_bytes()
+}
+
+/// Generates an SVG-like drawing with `groups` groups of shapes. Most of the markup
+/// consists of attributes with short values, and there is little text
+pub fn svg(groups: usize) -> Vec<u8> {
+    let mut doc = String::with_capacity(200 + groups * 700);
+    doc.push_str(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" version=\"1.1\" \
+         width=\"1000\" height=\"1000\" viewBox=\"0 0 1000 1000\">\n",
+    );
+    for i in 0..groups {
+        let (x, y) = (i * 37 % 1000, i * 91 % 1000);
+        write!(
+            doc,
+            "<g id=\"layer-{}\" transform=\"translate({} {})\" fill=\"#{:06x}\" \
+             stroke=\"black\" stroke-width=\"2\">\n\
+             <path d=\"M {} {} L {} {} L {} {} Z\" fill-opacity=\"0.5\" \
+             stroke-linejoin=\"round\"/>\n\
+             <circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"none\" stroke-dasharray=\"4 2\"/>\n\
+             <rect x=\"{}\" y=\"{}\" width=\"40\" height=\"20\" rx=\"3\" ry=\"3\" \
+             style=\"fill:#eee;stroke:#333\"/>\n\
+             <text x=\"{}\" y=\"{}\" font-family=\"Verdana\" font-size=\"12\">\
+             Shape {} &amp; label</text>\n\
+             </g>\n",
+            i,
+            x,
+            y,
+            i * 2_654_435 % 0x0100_0000,
+            x,
+            y,
+            x + 10,
+            y + 25,
+            x + 20,
+            y,
+            x,
+            y,
+            i % 50 + 5,
+            x,
+            y,
+            x,
+            y + 30,
+            i
+        )
+        .unwrap();
+    }
+    doc.push_str("</svg>\n");
+    doc.into_bytes()
+}
+
+/// Generates `trees` trees of elements, nested `depth` levels deep, with a text in the
+/// innermost element of each tree
+pub fn deep(depth: usize, trees: usize) -> Vec<u8> {
+    let mut doc = String::with_capacity(20 + trees * depth * 40);
+    doc.push_str("<root>\n");
+    for i in 0..trees {
+        for level in 0..depth {
+            write!(doc, "<node level=\"{}\">", level).unwrap();
+        }
+        write!(doc, "leaf {}", i).unwrap();
+        for _ in 0..depth {
+            doc.push_str("</node>");
+        }
+        doc.push('\n');
+    }
+    doc.push_str("</root>\n");
+    doc.into_bytes()
+}
+
+/// Generates `elements` empty elements with `attributes` attributes each. Every third
+/// value contains a character reference
+pub fn wide_attributes(elements: usize, attributes: usize) -> Vec<u8> {
+    let mut doc = String::with_capacity(20 + elements * (attributes * 24 + 10));
+    doc.push_str("<root>\n");
+    for i in 0..elements {
+        doc.push_str("<element");
+        for a in 0..attributes {
+            if a % 3 == 0 {
+                write!(doc, " attribute{}=\"value {} &amp; {}\"", a, i, a).unwrap();
+            } else {
+                write!(doc, " attribute{}=\"value {}\"", a, a).unwrap();
+            }
+        }
+        doc.push_str("/>\n");
+    }
+    doc.push_str("</root>\n");
+    doc.into_bytes()
+}
+
+/// Generates a text of about `len` bytes, where about every tenth character should be
+/// escaped
+pub fn text(len: usize) -> String {
+    const WORDS: [&str; 8] = [
+        "quick",
+        "<xml>",
+        "reader",
+        "&",
+        "writer",
+        "\"quoted\"",
+        "text",
+        "'apostrophe'",
+    ];
+    let mut text = String::with_capacity(len + 16);
+    let mut i = 0;
+    while text.len() < len {
+        text.push_str(WORDS[i % WORDS.len()]);
+        text.push(' ');
+        i += 1;
+    }
+    text
+}
+
+/// Reads all events of `doc` into `buf` and returns the number of events
+pub fn count_events(doc: &[u8], buf: &mut Vec<u8>) -> usize {
+    let mut reader = Reader::from_reader(doc);
+    reader.trim_text(true);
+    let mut count = 0;
+    loop {
+        match reader.read_event(buf) {
+            Ok(Event::Eof) => break,
+            Ok(_) => count += 1,
+            Err(e) => panic!("error at position {}: {:?}", reader.buffer_position(), e),
+        }
+        buf.clear();
+    }
+    count
+}
+
+/// Reads all attributes of `doc`, unescaping their values into `value`, and returns
+/// the total length of the unescaped values
+pub fn read_attributes(doc: &[u8], buf: &mut Vec<u8>, value: &mut Vec<u8>) -> usize {
+    let mut reader = Reader::from_reader(doc);
+    reader.trim_text(true);
+    let mut len = 0;
+    loop {
+        match reader.read_event(buf) {
+            Ok(Event::Start(ref e)) | Ok(Event::Empty(ref e)) => {
+                for attribute in e.attributes() {
+                    let attribute = attribute.unwrap();
+                    value.clear();
+                    unescape_into(&attribute.value, value).unwrap();
+                    len += value.len();
+                }
+            }
+            Ok(Event::Eof) => break,
+            Ok(_) => {}
+            Err(e) => panic!("error at position {}: {:?}", reader.buffer_position(), e),
+        }
+        buf.clear();
+    }
+    len
+}
+
+/// Escapes `text` into `out` and returns the length of the escaped text
+pub fn escape(text: &[u8], out: &mut Vec<u8>) -> usize {
+    out.clear();
+    escape_into(text, out);
+    out.len()
+}
+
+/// Unescapes `text` into `out` and returns the length of the unescaped text
+pub fn unescape(text: &[u8], out: &mut Vec<u8>) -> usize {
+    out.clear();
+    unescape_into(text, out).unwrap();
+    out.len()
+}
+
+/// An RSS feed, as generated by [`rss`](fn.rss.html)
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename = "rss")]
+pub struct Rss {
+    pub version: String,
+    pub channel: Channel,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Channel {
+    pub title: String,
+    pub link: String,
+    pub description: String,
+    #[serde(default)]
+    pub item: Vec<Item>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Item {
+    pub title: String,
+    pub link: String,
+    pub guid: Guid,
+    #[serde(default)]
+    pub category: Vec<String>,
+    #[serde(rename = "pubDate")]
+    pub pub_date: String,
+    pub description: String,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Guid {
+    #[serde(rename = "isPermaLink")]
+    pub is_perma_link: bool,
+    #[serde(rename = "$value")]
+    pub value: String,
+}
+
+/// Only the titles of an RSS feed. Deserializing it skips everything else
+#[derive(Debug, PartialEq, Deserialize)]
+pub struct Titles {
+    pub channel: ChannelTitles,
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+pub struct ChannelTitles {
+    pub title: String,
+    #[serde(default)]
+    pub item: Vec<ItemTitle>,
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+pub struct ItemTitle {
+    pub title: String,
+}
+
+/// Builds the same feed, as [`rss`](fn.rss.html) generates, with `items` items
+pub fn rss_feed(items: usize) -> Rss {
+    Rss {
+        version: "2.0".to_string(),
+        channel: Channel {
+            title: "Synthetic feed".to_string(),
+            link: "https://example.com/".to_string(),
+            description: "News & updates".to_string(),
+            item: (0..items)
+                .map(|i| Item {
+                    title: format!("Item {} <{}>", i, i % 7),
+                    link: format!("https://example.com/items/{}", i),
+                    guid: Guid {
+                        is_perma_link: false,
+                        value: format!("item-{}", i),
+                    },
+                    category: vec![format!("category {}", i % 5), format!("tag {}", i % 11)],
+                    pub_date: format!("Mon, {:02} Jan 2018 10:{:02}:00 GMT", i % 28 + 1, i % 60),
+                    description: format!("<p>Description of the item {}</p>", i),
+                })
+                .collect(),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quick_xml::de::from_slice;
+    use quick_xml::se::to_writer;
+
+    #[test]
+    fn fixtures_are_well_formed() {
+        let mut buf = Vec::new();
+        assert_eq!(count_events(&rss(10), &mut buf), 3 + 3 * 3 + 10 * 23 + 2);
+        assert_eq!(count_events(&svg(10), &mut buf), 2 + 10 * 8);
+        assert_eq!(count_events(&deep(20, 10), &mut buf), 2 + 10 * 41);
+        assert_eq!(count_events(&wide_attributes(10, 20), &mut buf), 2 + 10);
+    }
+
+    #[test]
+    fn read_attributes_unescapes() {
+        let (mut buf, mut value) = (Vec::new(), Vec::new());
+        let doc = wide_attributes(1, 3);
+        assert_eq!(
+            read_attributes(&doc, &mut buf, &mut value),
+            "value 0 & 0".len() + 2 * "value 1".len()
+        );
+    }
+
+    #[test]
+    fn escape_round_trip() {
+        let text = text(1000);
+        let (mut escaped, mut unescaped) = (Vec::new(), Vec::new());
+        escape(text.as_bytes(), &mut escaped);
+        unescape(&escaped, &mut unescaped);
+        assert_eq!(unescaped, text.as_bytes());
+    }
+
+    #[test]
+    fn rss_deserializes() {
+        let feed: Rss = from_slice(&rss(10)).unwrap();
+        assert_eq!(feed, rss_feed(10));
+
+        let titles: Titles = from_slice(&rss(10)).unwrap();
+        assert_eq!(titles.channel.item.len(), 10);
+        assert_eq!(titles.channel.item[3].title, "Item 3 <3>");
+
+        // Sequences of strings are serialized as one text, so skip them when reading back
+        let mut out = Vec::new();
+        to_writer(&mut out, &feed).unwrap();
+        let serialized: Titles = from_slice(&out).unwrap();
+        assert_eq!(serialized, titles);
+    }
+}
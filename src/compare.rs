@@ -0,0 +1,488 @@
+//! Structural comparison of XML documents, mostly useful in tests.
+//!
+//! [`semantic_eq`] parses both documents and compares their logical structure instead of
+//! the text, so the order of attributes, the quotes around attribute values, the way the
+//! characters are escaped and whether a text is in a CDATA section do not matter.
+//!
+//! Compared are:
+//! - names of elements and attributes, as written, namespace prefixes are not resolved;
+//! - sets of attributes, including namespace declarations, with unescaped values;
+//! - unescaped texts, where adjacent texts and CDATA sections are joined;
+//! - comments, processing instructions and doctypes.
+//!
+//! The XML declaration is ignored.
+//!
+//! [`semantic_eq`]: fn.semantic_eq.html
+
+use std::collections::HashMap;
+use std::fmt;
+use std::io::BufRead;
+
+use errors::Result;
+use events::{BytesStart, Event};
+use reader::Reader;
+
+/// Defines which differences are ignored by [`semantic_eq`].
+///
+/// [`semantic_eq`]: fn.semantic_eq.html
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CompareOptions {
+    /// Comments are skipped, so texts around them are joined
+    pub ignore_comments: bool,
+    /// Texts, which consist only of whitespace, are skipped, for example, the indentation
+    pub ignore_whitespace_text: bool,
+    /// `<a/>` is equal to `<a></a>`
+    pub empty_equals_expanded: bool,
+}
+
+/// The first difference between two documents, found by [`semantic_eq`].
+///
+/// [`semantic_eq`]: fn.semantic_eq.html
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Difference {
+    /// Path to the difference, like `/root/items/item[2]/@id`. Elements are numbered among
+    /// the siblings with the same name starting from 1, the first one is written without
+    /// a number. Texts are written as `text()`, comments as `comment()` and processing
+    /// instructions as `processing-instruction()`
+    pub path: String,
+    /// Description of the first document at the path
+    pub left: String,
+    /// Description of the second document at the path
+    pub right: String,
+}
+
+impl fmt::Display for Difference {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {} != {}", self.path, self.left, self.right)
+    }
+}
+
+impl std::error::Error for Difference {}
+
+/// Compares the logical structure of two XML documents. See the
+/// [module documentation](index.html) for what is compared.
+///
+/// Returns the first [`Difference`] if the documents are not equal. An invalid document
+/// is a difference too, described by the error.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate quick_xml;
+/// # fn main() {
+/// use quick_xml::compare::{semantic_eq, CompareOptions};
+///
+/// let options = CompareOptions {
+///     ignore_whitespace_text: true,
+///     empty_equals_expanded: true,
+///     ..CompareOptions::default()
+/// };
+/// assert_eq!(
+///     semantic_eq(
+///         r#"<items><item id="1" type='a'/></items>"#,
+///         "<items>\n  <item type=\"a\" id=\"1\"></item>\n</items>",
+///         options,
+///     ),
+///     Ok(())
+/// );
+///
+/// let difference = semantic_eq(
+///     r#"<items><item id="1"/><item id="2"/></items>"#,
+///     r#"<items><item id="1"/><item id="3"/></items>"#,
+///     options,
+/// )
+/// .unwrap_err();
+/// assert_eq!(difference.path, "/items/item[2]/@id");
+/// assert_eq!(difference.to_string(), r#"/items/item[2]/@id: "2" != "3""#);
+/// # }
+/// ```
+///
+/// [`Difference`]: struct.Difference.html
+pub fn semantic_eq(
+    a: &str,
+    b: &str,
+    options: CompareOptions,
+) -> std::result::Result<(), Difference> {
+    let mut left = Items::new(a.as_bytes(), options);
+    let mut right = Items::new(b.as_bytes(), options);
+    let mut path = Path::default();
+    loop {
+        let (l, r) = match (left.next(), right.next()) {
+            (Ok(l), Ok(r)) => (l, r),
+            (l, r) => {
+                let segment = match l {
+                    Ok(ref l) => path.segment(l),
+                    Err(_) => String::new(),
+                };
+                return Err(path.difference(segment, describe(&l), describe(&r)));
+            }
+        };
+        if l != r {
+            let segment = path.segment(&l);
+            match (l, r) {
+                (
+                    Item::Start {
+                        name: ref l_name,
+                        attributes: ref l_attrs,
+                        empty: l_empty,
+                    },
+                    Item::Start {
+                        name: ref r_name,
+                        attributes: ref r_attrs,
+                        empty: r_empty,
+                    },
+                ) if l_name == r_name && l_empty == r_empty => {
+                    return Err(compare_attributes(&path, segment, l_attrs, r_attrs));
+                }
+                (l, r) => {
+                    return Err(path.difference(segment, l.to_string(), r.to_string()));
+                }
+            }
+        }
+        match l {
+            Item::Start {
+                name, empty: false, ..
+            } => path.push(name),
+            // Only counts the element
+            Item::Start { name, .. } => {
+                path.push(name);
+                path.pop();
+            }
+            Item::End(_) => path.pop(),
+            Item::Eof => return Ok(()),
+            _ => {}
+        }
+    }
+}
+
+fn describe(item: &Result<Item>) -> String {
+    match item {
+        Ok(item) => item.to_string(),
+        Err(e) => format!("invalid XML ({})", e),
+    }
+}
+
+/// Returns the first difference of attributes of the elements with the same name
+fn compare_attributes(
+    path: &Path,
+    segment: String,
+    left: &[(String, String)],
+    right: &[(String, String)],
+) -> Difference {
+    let mut left = left.iter().peekable();
+    let mut right = right.iter().peekable();
+    let (key, l, r) = loop {
+        match (left.peek(), right.peek()) {
+            (Some((lk, lv)), Some((rk, rv))) if lk == rk => {
+                if lv != rv {
+                    break (lk, format!("{:?}", lv), format!("{:?}", rv));
+                }
+                left.next();
+                right.next();
+            }
+            (Some((lk, lv)), Some((rk, _))) if lk < rk => {
+                break (lk, format!("{:?}", lv), "no attribute".to_string())
+            }
+            (Some((lk, lv)), None) => break (lk, format!("{:?}", lv), "no attribute".to_string()),
+            (_, Some((rk, rv))) => break (rk, "no attribute".to_string(), format!("{:?}", rv)),
+            (None, None) => unreachable!("attributes are different"),
+        }
+    };
+    path.difference(format!("{}/@{}", segment, key), l, r)
+}
+
+/// A part of a document, which is compared as a whole
+#[derive(Debug, PartialEq)]
+pub(crate) enum Item {
+    Start {
+        name: String,
+        /// Attributes, sorted by the name
+        attributes: Vec<(String, String)>,
+        /// Whether the element is written as `<name/>`
+        empty: bool,
+    },
+    End(String),
+    Text(String),
+    Comment(String),
+    PI(String),
+    DocType(String),
+    Eof,
+}
+
+impl fmt::Display for Item {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Item::Start {
+                name, empty: true, ..
+            } => write!(f, "empty element <{}>", name),
+            Item::Start { name, .. } => write!(f, "element <{}>", name),
+            Item::End(name) => write!(f, "end of element <{}>", name),
+            Item::Text(text) => write!(f, "text {:?}", text),
+            Item::Comment(text) => write!(f, "comment {:?}", text),
+            Item::PI(text) => write!(f, "processing instruction {:?}", text),
+            Item::DocType(text) => write!(f, "doctype {:?}", text),
+            Item::Eof => f.write_str("end of document"),
+        }
+    }
+}
+
+/// Reads the items of a document
+pub(crate) struct Items<R: BufRead> {
+    reader: Reader<R>,
+    buf: Vec<u8>,
+    options: CompareOptions,
+    /// An item, read after a text
+    next: Option<Item>,
+}
+
+impl<R: BufRead> Items<R> {
+    pub(crate) fn new(input: R, options: CompareOptions) -> Items<R> {
+        let mut reader = Reader::from_reader(input);
+        reader.expand_empty_elements(options.empty_equals_expanded);
+        Items {
+            reader,
+            buf: Vec::new(),
+            options,
+            next: None,
+        }
+    }
+
+    pub(crate) fn next(&mut self) -> Result<Item> {
+        if let Some(item) = self.next.take() {
+            return Ok(item);
+        }
+        let mut text = String::new();
+        let item = loop {
+            self.buf.clear();
+            break match self.reader.read_event(&mut self.buf)? {
+                Event::Text(e) | Event::CData(e) => {
+                    text.push_str(&e.unescape_and_decode(&self.reader)?);
+                    continue;
+                }
+                Event::Comment(_) if self.options.ignore_comments => continue,
+                Event::Decl(_) => continue,
+                Event::Start(e) => start(&self.reader, &e, false)?,
+                Event::Empty(e) => start(&self.reader, &e, true)?,
+                Event::End(e) => Item::End(self.reader.decode_cow(e.name().as_ref())?.into_owned()),
+                Event::Comment(e) => Item::Comment(self.reader.decode_cow(&e)?.into_owned()),
+                Event::PI(e) => Item::PI(self.reader.decode_cow(&e)?.into_owned()),
+                Event::DocType(e) => Item::DocType(self.reader.decode_cow(&e)?.trim().to_string()),
+                Event::Eof => Item::Eof,
+            };
+        };
+        if text.is_empty() || self.options.ignore_whitespace_text && text.trim().is_empty() {
+            return Ok(item);
+        }
+        self.next = Some(item);
+        Ok(Item::Text(text))
+    }
+}
+
+fn start<R: BufRead>(reader: &Reader<R>, e: &BytesStart, empty: bool) -> Result<Item> {
+    let mut attributes = Vec::new();
+    for attr in e.attributes() {
+        let attr = attr?;
+        attributes.push((
+            reader.decode_cow(attr.key.as_ref())?.into_owned(),
+            attr.unescape_and_decode_value(reader)?,
+        ));
+    }
+    attributes.sort();
+    Ok(Item::Start {
+        name: reader.decode_cow(e.name().as_ref())?.into_owned(),
+        attributes,
+        empty,
+    })
+}
+
+/// Path to the currently compared item
+#[derive(Default)]
+struct Path {
+    /// Path of the current element
+    path: String,
+    /// Lengths of `path` before each opened element
+    lengths: Vec<usize>,
+    /// Number of the children with each name for each opened element and for the document
+    counts: Vec<HashMap<String, usize>>,
+}
+
+impl Path {
+    /// Returns the last part of the path to the `item`, which is the next child of the current
+    /// element, or an empty string if the item has no own path
+    fn segment(&self, item: &Item) -> String {
+        match item {
+            Item::Start { name, .. } => {
+                let count = self
+                    .counts
+                    .last()
+                    .and_then(|counts| counts.get(name))
+                    .map_or(0, |count| *count);
+                match count {
+                    0 => format!("/{}", name),
+                    _ => format!("/{}[{}]", name, count + 1),
+                }
+            }
+            Item::Text(_) => "/text()".to_string(),
+            Item::Comment(_) => "/comment()".to_string(),
+            Item::PI(_) => "/processing-instruction()".to_string(),
+            Item::End(_) | Item::DocType(_) | Item::Eof => String::new(),
+        }
+    }
+
+    fn push(&mut self, name: String) {
+        let segment = self.segment(&Item::Start {
+            name: name.clone(),
+            attributes: Vec::new(),
+            empty: false,
+        });
+        if self.counts.is_empty() {
+            self.counts.push(HashMap::new());
+        }
+        if let Some(counts) = self.counts.last_mut() {
+            *counts.entry(name).or_insert(0) += 1;
+        }
+        self.lengths.push(self.path.len());
+        self.path.push_str(&segment);
+        self.counts.push(HashMap::new());
+    }
+
+    fn pop(&mut self) {
+        if let Some(len) = self.lengths.pop() {
+            self.path.truncate(len);
+            self.counts.pop();
+        }
+    }
+
+    fn difference(&self, segment: String, left: String, right: String) -> Difference {
+        let mut path = format!("{}{}", self.path, segment);
+        if path.is_empty() {
+            path.push('/');
+        }
+        Difference { path, left, right }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL: CompareOptions = CompareOptions {
+        ignore_comments: true,
+        ignore_whitespace_text: true,
+        empty_equals_expanded: true,
+    };
+
+    fn difference(a: &str, b: &str, options: CompareOptions) -> String {
+        semantic_eq(a, b, options).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn equal() {
+        let options = CompareOptions::default();
+        assert_eq!(semantic_eq("<a/>", "<a/>", options), Ok(()));
+        assert_eq!(
+            semantic_eq(
+                r#"<?xml version="1.0"?><a x="1" y='&lt;'>a &amp; b<![CDATA[ <c> ]]></a>"#,
+                r#"<?xml version='1.0' encoding='utf-8'?><a y="&#60;" x='1'><![CDATA[a & b]]> &lt;c&gt; </a>"#,
+                options,
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            semantic_eq(
+                "<a>\n  <!-- c -->\n  <b/>\n  <c>text</c>\n</a>",
+                "<a><b></b><c>text<!-- other --></c></a>",
+                ALL
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn options() {
+        let options = CompareOptions::default();
+        assert_eq!(
+            difference("<a/>", "<a></a>", options),
+            "/a: empty element <a> != element <a>"
+        );
+        assert_eq!(
+            difference("<a><b/></a>", "<a>\n  <b/>\n</a>", options),
+            r#"/a/b: empty element <b> != text "\n  ""#
+        );
+        assert_eq!(
+            difference("<a><!--x--><b/></a>", "<a><b/></a>", options),
+            r#"/a/comment(): comment "x" != empty element <b>"#
+        );
+        assert_eq!(
+            semantic_eq("<a><!--x--><b/></a>", "<a>\n  <b></b>\n</a>", ALL,),
+            Ok(())
+        );
+        // Texts are joined, but not trimmed
+        assert_eq!(
+            difference("<a>x<!--c-->y</a>", "<a> xy</a>", ALL),
+            r#"/a/text(): text "xy" != text " xy""#
+        );
+    }
+
+    #[test]
+    fn paths() {
+        let left =
+            r#"<root><items><item id="1"/><item id="2"/><other/><item id="3"/></items></root>"#;
+        assert_eq!(
+            difference(
+                left,
+                r#"<root><items><item id="1"/><item id="2"/><other/><item id="4"/></items></root>"#,
+                ALL
+            ),
+            r#"/root/items/item[3]/@id: "3" != "4""#
+        );
+        assert_eq!(
+            difference(
+                left,
+                r#"<root><items><item id="1"/><item/></items></root>"#,
+                ALL
+            ),
+            r#"/root/items/item[2]/@id: "2" != no attribute"#
+        );
+        assert_eq!(
+            difference(
+                left,
+                r#"<root><items><item id="1" x=""/></items></root>"#,
+                ALL
+            ),
+            r#"/root/items/item/@x: no attribute != """#
+        );
+        assert_eq!(
+            difference(
+                left,
+                r#"<root><items><item id="1"/><item id="2"/><item id="3"/></items></root>"#,
+                ALL
+            ),
+            "/root/items/other: element <other> != element <item>"
+        );
+        assert_eq!(
+            difference(
+                "<root><a>x</a><a>y</a></root>",
+                "<root><a>x</a><a>z</a></root>",
+                ALL
+            ),
+            r#"/root/a[2]/text(): text "y" != text "z""#
+        );
+        assert_eq!(
+            difference("<root><a/></root>", "<root><a/><b/></root>", ALL),
+            "/root: end of element <root> != element <b>"
+        );
+        assert_eq!(
+            difference("<root/>", "<other/>", ALL),
+            "/root: element <root> != element <other>"
+        );
+    }
+
+    #[test]
+    fn invalid() {
+        assert_eq!(
+            difference("<a></a>", "<a></b>", ALL),
+            "/a: end of element <a> != invalid XML (Expecting </a> found </b>)"
+        );
+    }
+}
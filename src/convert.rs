@@ -0,0 +1,459 @@
+//! Conversion between XML documents and `serde_json` values.
+//!
+//! [`xml_to_json`] and [`json_to_xml`] use the following conventions:
+//!
+//! |XML                               |Value                                      |
+//! |----------------------------------|-------------------------------------------|
+//! |`<e/>`, `<e></e>`                 |`null`                                     |
+//! |`<e>text</e>`                     |`"text"`                                   |
+//! |`<e a="1">text</e>`               |`{"@a": "1", "#text": "text"}`             |
+//! |`<e><x>1</x><y/></e>`             |`{"x": "1", "y": null}`                    |
+//! |`<e><x>1</x><x>2</x></e>`         |`{"x": ["1", "2"]}`                        |
+//!
+//! Attributes are prefixed with `@`, the text content of an element with attributes or
+//! child elements is stored under `#text`, and repeated elements are collected into arrays.
+//! With [`ScalarPolicy::Parse`] texts and attribute values, which look like booleans or
+//! numbers, are converted to them.
+//!
+//! The conversion is lossy:
+//! - the name of the root element is not stored, it is given to `json_to_xml` instead;
+//! - comments, processing instructions, the declaration and the doctype are dropped;
+//! - texts are trimmed, and all texts and CDATA sections of an element are concatenated,
+//!   so the position of the text among child elements is lost;
+//! - elements with different names are grouped, so `<a/><b/><a/>` is written back as
+//!   `<a/><a/><b/>`, and an element repeated once is not an array;
+//! - `<e/>` and `<e></e>` are the same, and an empty array writes nothing.
+//!
+//! Objects are `serde_json` maps, which sort their keys, unless the `preserve_order`
+//! feature of `serde_json` is enabled, so attributes and child elements may be written
+//! back in another order.
+//!
+//! This module requires the `json` feature.
+//!
+//! # Examples
+//!
+//! ```edition2018
+//! use quick_xml::convert::{json_to_xml, xml_to_json};
+//! use quick_xml::{Reader, Writer};
+//! use serde_json::json;
+//!
+//! let mut reader = Reader::from_str(r#"<book id="1"><tag>xml</tag><tag>json</tag></book>"#);
+//! let value = xml_to_json(&mut reader).unwrap();
+//! assert_eq!(value, json!({ "@id": "1", "tag": ["xml", "json"] }));
+//!
+//! let mut writer = Writer::new(Vec::new());
+//! json_to_xml(&value, &mut writer, "book").unwrap();
+//! assert_eq!(
+//!     writer.into_inner(),
+//!     br#"<book id="1"><tag>xml</tag><tag>json</tag></book>"#.as_ref()
+//! );
+//! ```
+//!
+//! [`xml_to_json`]: fn.xml_to_json.html
+//! [`json_to_xml`]: fn.json_to_xml.html
+//! [`ScalarPolicy::Parse`]: enum.ScalarPolicy.html#variant.Parse
+
+use crate::{
+    errors::serialize::DeError,
+    events::{BytesEnd, BytesStart, BytesText, Event},
+    reader::Reader,
+    writer::{Sink, Writer},
+};
+use serde_json::{Map, Number, Value};
+use std::io::BufRead;
+
+/// Prefix of the keys of attributes
+pub const ATTRIBUTE_PREFIX: &str = "@";
+/// Key of the text content of an element with attributes or child elements
+pub const TEXT_KEY: &str = "#text";
+
+/// Defines whether [`xml_to_json_with_scalars`] converts texts and attribute values,
+/// which look like booleans or numbers.
+///
+/// [`xml_to_json_with_scalars`]: fn.xml_to_json_with_scalars.html
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ScalarPolicy {
+    /// All texts and attribute values are kept as strings. This is the default
+    #[default]
+    Strings,
+    /// `true` and `false` are converted to booleans, and texts which parse as integers
+    /// or finite floating point numbers, to numbers. Other values are kept as strings.
+    /// Note, that numbers are written back in the Rust formatting, so `1.50` becomes `1.5`
+    Parse,
+}
+
+impl ScalarPolicy {
+    fn convert(self, text: String) -> Value {
+        if self == ScalarPolicy::Strings {
+            return Value::String(text);
+        }
+        match text.as_str() {
+            "true" => return Value::Bool(true),
+            "false" => return Value::Bool(false),
+            _ => {}
+        }
+        if let Ok(i) = text.parse::<i64>() {
+            return Value::Number(i.into());
+        }
+        // Rust also parses `inf` and `NaN`, which are not numbers in JSON
+        match text.parse::<f64>() {
+            Ok(f) if text.bytes().any(|b| b.is_ascii_digit()) => match Number::from_f64(f) {
+                Some(n) => Value::Number(n),
+                None => Value::String(text),
+            },
+            _ => Value::String(text),
+        }
+    }
+}
+
+/// An element, which end tag is not read yet
+struct Frame {
+    name: String,
+    attributes: Vec<(String, Value)>,
+    children: Vec<(String, Value)>,
+    text: String,
+}
+
+impl Frame {
+    fn into_value(self, scalars: ScalarPolicy) -> Value {
+        let text = self.text.trim();
+        if self.attributes.is_empty() && self.children.is_empty() {
+            return if text.is_empty() {
+                Value::Null
+            } else {
+                scalars.convert(text.to_string())
+            };
+        }
+        let mut object: Map<String, Value> = self.attributes.into_iter().collect();
+        object.extend(self.children);
+        if !text.is_empty() {
+            object.insert(TEXT_KEY.to_string(), scalars.convert(text.to_string()));
+        }
+        Value::Object(object)
+    }
+
+    /// Adds a child element, collecting elements with the same name into an array
+    fn push_child(&mut self, name: String, value: Value) {
+        match self.children.iter_mut().find(|(key, _)| *key == name) {
+            Some((_, Value::Array(items))) => items.push(value),
+            Some((_, existing)) => {
+                let first = std::mem::replace(existing, Value::Null);
+                *existing = Value::Array(vec![first, value]);
+            }
+            None => self.children.push((name, value)),
+        }
+    }
+}
+
+/// Reads the next element from the `reader` and converts its content, keeping all texts
+/// and attribute values as strings. See the [module documentation](index.html) for the
+/// conventions.
+pub fn xml_to_json<B: BufRead>(reader: &mut Reader<B>) -> Result<Value, DeError> {
+    xml_to_json_with_scalars(reader, ScalarPolicy::Strings)
+}
+
+/// Reads the next element from the `reader` and converts its content, converting texts and
+/// attribute values according to the `scalars` policy. See the [module documentation](index.html)
+/// for the conventions.
+///
+/// Events preceding the element are skipped, reading stops at its end tag.
+pub fn xml_to_json_with_scalars<B: BufRead>(
+    reader: &mut Reader<B>,
+    scalars: ScalarPolicy,
+) -> Result<Value, DeError> {
+    let mut buf = Vec::new();
+    let mut stack: Vec<Frame> = Vec::new();
+    loop {
+        let (frame, empty) = match reader.read_event(&mut buf)? {
+            Event::Start(ref e) => (start_frame(e, reader, scalars)?, false),
+            Event::Empty(ref e) => (start_frame(e, reader, scalars)?, true),
+            Event::End(_) => match stack.pop() {
+                Some(frame) => (frame, true),
+                None => return Err(DeError::End),
+            },
+            Event::Text(ref e) | Event::CData(ref e) => {
+                if let Some(frame) = stack.last_mut() {
+                    frame.text.push_str(&e.unescape_and_decode(reader)?);
+                }
+                buf.clear();
+                continue;
+            }
+            Event::Eof => return Err(DeError::Eof),
+            _ => {
+                buf.clear();
+                continue;
+            }
+        };
+        if !empty {
+            stack.push(frame);
+        } else {
+            let name = frame.name.clone();
+            let value = frame.into_value(scalars);
+            match stack.last_mut() {
+                Some(parent) => parent.push_child(name, value),
+                None => return Ok(value),
+            }
+        }
+        buf.clear();
+    }
+}
+
+fn start_frame<B: BufRead>(
+    e: &BytesStart,
+    reader: &Reader<B>,
+    scalars: ScalarPolicy,
+) -> Result<Frame, DeError> {
+    let mut attributes = Vec::new();
+    for attr in e.attributes() {
+        let attr = attr?;
+        let key = format!(
+            "{}{}",
+            ATTRIBUTE_PREFIX,
+            reader.decode_cow(attr.key.as_ref())?
+        );
+        attributes.push((
+            key,
+            scalars.convert(attr.unescape_and_decode_value(reader)?),
+        ));
+    }
+    Ok(Frame {
+        name: reader.decode_cow(e.name().as_ref())?.into_owned(),
+        attributes,
+        children: Vec::new(),
+        text: String::new(),
+    })
+}
+
+/// Writes the `value` as the content of the element `root_name`. See the
+/// [module documentation](index.html) for the conventions.
+///
+/// Keys of objects starting with `@` are written as attributes and `#text` as the text
+/// of the element. Arrays are written as repeated elements, and `null` as an empty element.
+///
+/// Returns [`DeError::Unsupported`] if an attribute or a `#text` is an array or an object.
+///
+/// [`DeError::Unsupported`]: ../de/enum.DeError.html#variant.Unsupported
+pub fn json_to_xml<W: Sink>(
+    value: &Value,
+    writer: &mut Writer<W>,
+    root_name: &str,
+) -> Result<(), DeError> {
+    let name = root_name.as_bytes();
+    match value {
+        Value::Null => writer.write_event(Event::Empty(BytesStart::borrowed_name(name)))?,
+        Value::Array(items) => {
+            for item in items {
+                json_to_xml(item, writer, root_name)?;
+            }
+        }
+        Value::Object(entries) => {
+            let mut start = BytesStart::borrowed_name(name);
+            for (key, value) in entries {
+                if let Some(key) = key.strip_prefix(ATTRIBUTE_PREFIX) {
+                    let value = scalar_to_string(value, "attribute must be a scalar value")?;
+                    start.push_attribute((key, value.as_str()));
+                }
+            }
+            let mut content = entries
+                .iter()
+                .filter(|(key, _)| !key.starts_with(ATTRIBUTE_PREFIX))
+                .peekable();
+            if content.peek().is_none() {
+                writer.write_event(Event::Empty(start))?;
+                return Ok(());
+            }
+            writer.write_event(Event::Start(start))?;
+            for (key, value) in content {
+                if key == TEXT_KEY {
+                    let text = scalar_to_string(value, "#text must be a scalar value")?;
+                    writer.write_event(Event::Text(BytesText::from_plain_str(&text)))?;
+                } else {
+                    json_to_xml(value, writer, key)?;
+                }
+            }
+            writer.write_event(Event::End(BytesEnd::borrowed(name)))?;
+        }
+        scalar => {
+            let text = scalar_to_string(scalar, "")?;
+            writer.write_event(Event::Start(BytesStart::borrowed_name(name)))?;
+            writer.write_event(Event::Text(BytesText::from_plain_str(&text)))?;
+            writer.write_event(Event::End(BytesEnd::borrowed(name)))?;
+        }
+    }
+    Ok(())
+}
+
+fn scalar_to_string(value: &Value, error: &'static str) -> Result<String, DeError> {
+    match value {
+        Value::Null => Ok(String::new()),
+        Value::Bool(b) => Ok(b.to_string()),
+        Value::Number(n) => Ok(n.to_string()),
+        Value::String(s) => Ok(s.clone()),
+        Value::Array(_) | Value::Object(_) => Err(DeError::Unsupported(error)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn string(s: &str) -> Value {
+        Value::String(s.to_string())
+    }
+
+    fn object(entries: Vec<(&str, Value)>) -> Value {
+        Value::Object(
+            entries
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), v))
+                .collect(),
+        )
+    }
+
+    fn convert(xml: &str, scalars: ScalarPolicy) -> Value {
+        let mut reader = Reader::from_str(xml);
+        xml_to_json_with_scalars(&mut reader, scalars).unwrap()
+    }
+
+    fn write(value: &Value, root: &str) -> String {
+        let mut writer = Writer::new(Vec::new());
+        json_to_xml(value, &mut writer, root).unwrap();
+        String::from_utf8(writer.into_inner()).unwrap()
+    }
+
+    const DOCUMENT: &str = r#"<?xml version="1.0"?>
+<!-- catalog -->
+<catalog updated="2021-03-01" count="3">
+    <book id="1" available="true">
+        <title>XML &amp; JSON</title>
+        <price>12.50</price>
+        <tag>xml</tag>
+        <tag>json</tag>
+    </book>
+    <book id="2">
+        <title><![CDATA[<Streaming>]]></title>
+        <tag/>
+    </book>
+    <note lang="en">Prices <b>may</b> change</note>
+    <empty></empty>
+</catalog>"#;
+
+    #[test]
+    fn strings() {
+        let value = convert(DOCUMENT, ScalarPolicy::Strings);
+        assert_eq!(
+            value,
+            object(vec![
+                ("@updated", string("2021-03-01")),
+                ("@count", string("3")),
+                (
+                    "book",
+                    Value::Array(vec![
+                        object(vec![
+                            ("@id", string("1")),
+                            ("@available", string("true")),
+                            ("title", string("XML & JSON")),
+                            ("price", string("12.50")),
+                            ("tag", Value::Array(vec![string("xml"), string("json")])),
+                        ]),
+                        object(vec![
+                            ("@id", string("2")),
+                            ("title", string("<Streaming>")),
+                            ("tag", Value::Null),
+                        ]),
+                    ])
+                ),
+                (
+                    "note",
+                    object(vec![
+                        ("@lang", string("en")),
+                        ("b", string("may")),
+                        ("#text", string("Prices  change")),
+                    ])
+                ),
+                ("empty", Value::Null),
+            ])
+        );
+    }
+
+    #[test]
+    fn parse_scalars() {
+        let value = convert(
+            r#"<r i="-3" f="1e3" b="false" s="inf"><n>12.50</n><n>1</n><n>NaN</n><n>true</n></r>"#,
+            ScalarPolicy::Parse,
+        );
+        assert_eq!(
+            value,
+            object(vec![
+                ("@i", Value::from(-3)),
+                ("@f", Value::from(1000.0)),
+                ("@b", Value::Bool(false)),
+                ("@s", string("inf")),
+                (
+                    "n",
+                    Value::Array(vec![
+                        Value::from(12.5),
+                        Value::from(1),
+                        string("NaN"),
+                        Value::Bool(true),
+                    ])
+                ),
+            ])
+        );
+        assert_eq!(
+            write(&value, "r"),
+            r#"<r b="false" f="1000.0" i="-3" s="inf"><n>12.5</n><n>1</n><n>NaN</n><n>true</n></r>"#
+        );
+    }
+
+    #[test]
+    fn roundtrip() {
+        let value = convert(DOCUMENT, ScalarPolicy::Strings);
+        let xml = write(&value, "catalog");
+        // Texts are trimmed, keys are sorted, so the text of `note` is moved
+        // before its child, and `<empty></empty>` is written as `<empty/>`
+        assert_eq!(
+            xml,
+            r#"<catalog count="3" updated="2021-03-01">"#.to_string()
+                + r#"<book available="true" id="1"><price>12.50</price><tag>xml</tag><tag>json</tag><title>XML &amp; JSON</title></book>"#
+                + r#"<book id="2"><tag/><title>&lt;Streaming&gt;</title></book>"#
+                + r#"<empty/>"#
+                + r#"<note lang="en">Prices  change<b>may</b></note>"#
+                + r#"</catalog>"#
+        );
+        assert_eq!(convert(&xml, ScalarPolicy::Strings), value);
+    }
+
+    #[test]
+    fn lossy_grouping() {
+        let value = convert("<r><a>1</a><b/><a>2</a></r>", ScalarPolicy::Strings);
+        assert_eq!(
+            value,
+            object(vec![
+                ("a", Value::Array(vec![string("1"), string("2")])),
+                ("b", Value::Null),
+            ])
+        );
+        assert_eq!(write(&value, "r"), "<r><a>1</a><a>2</a><b/></r>");
+    }
+
+    #[test]
+    fn scalar_root() {
+        assert_eq!(
+            convert("<r> text </r>", ScalarPolicy::Strings),
+            string("text")
+        );
+        assert_eq!(write(&string("a < b"), "r"), "<r>a &lt; b</r>");
+        assert_eq!(write(&Value::Null, "r"), "<r/>");
+    }
+
+    #[test]
+    fn invalid_attribute() {
+        let value = object(vec![("@a", Value::Array(vec![]))]);
+        let mut writer = Writer::new(Vec::new());
+        match json_to_xml(&value, &mut writer, "r") {
+            Err(DeError::Unsupported(_)) => {}
+            x => panic!("Expected `Err(Unsupported(_))`, but got `{:?}`", x),
+        }
+    }
+}
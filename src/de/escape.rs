@@ -13,23 +13,24 @@ use std::borrow::Cow;
 /// when converting to float, we don't expect any escapable character
 /// anyway
 #[derive(Clone)]
-pub(crate) struct EscapedDeserializer {
+pub(crate) struct EscapedDeserializer<'a> {
     decoder: Decoder,
-    /// Possible escaped value of text/CDATA or attribute value
-    escaped_value: Vec<u8>,
+    /// Possible escaped value of text/CDATA or attribute value, borrowed from the input,
+    /// if the deserializer borrows it
+    escaped_value: Cow<'a, [u8]>,
     /// If `true`, value requires unescaping before using
     escaped: bool,
 }
 
-impl EscapedDeserializer {
-    pub fn new(escaped_value: Vec<u8>, decoder: Decoder, escaped: bool) -> Self {
+impl<'a> EscapedDeserializer<'a> {
+    pub fn new(escaped_value: Cow<'a, [u8]>, decoder: Decoder, escaped: bool) -> Self {
         EscapedDeserializer {
             decoder,
             escaped_value,
             escaped,
         }
     }
-    fn unescaped(&self) -> Result<Cow<[u8]>, DeError> {
+    fn unescaped(&self) -> Result<Cow<'_, [u8]>, DeError> {
         if self.escaped {
             unescape(&self.escaped_value).map_err(|e| DeError::Xml(Error::EscapeError(e)))
         } else {
@@ -44,18 +45,19 @@ macro_rules! deserialize_num {
         where
             V: Visitor<'de>,
         {
+            let unescaped = self.unescaped()?;
             #[cfg(not(feature = "encoding"))]
-            let value = self.decoder.decode(&self.escaped_value)?.parse()?;
+            let value = self.decoder.decode(&unescaped)?.parse()?;
 
             #[cfg(feature = "encoding")]
-            let value = self.decoder.decode(&self.escaped_value).parse()?;
+            let value = self.decoder.decode(&unescaped).parse()?;
 
             visitor.$visit(value)
         }
     };
 }
 
-impl<'de> serde::Deserializer<'de> for EscapedDeserializer {
+impl<'de> serde::Deserializer<'de> for EscapedDeserializer<'de> {
     type Error = DeError;
 
     fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
@@ -69,6 +71,18 @@ impl<'de> serde::Deserializer<'de> for EscapedDeserializer {
     where
         V: Visitor<'de>,
     {
+        if !self.escaped {
+            if let Cow::Borrowed(value) = self.escaped_value {
+                #[cfg(not(feature = "encoding"))]
+                return visitor.visit_borrowed_str(self.decoder.decode(value)?);
+
+                #[cfg(feature = "encoding")]
+                return match self.decoder.decode(value) {
+                    Cow::Borrowed(value) => visitor.visit_borrowed_str(value),
+                    Cow::Owned(value) => visitor.visit_string(value),
+                };
+            }
+        }
         let unescaped = self.unescaped()?;
         #[cfg(not(feature = "encoding"))]
         let value = self.decoder.decode(&unescaped)?;
@@ -104,10 +118,10 @@ impl<'de> serde::Deserializer<'de> for EscapedDeserializer {
     where
         V: Visitor<'de>,
     {
+        let unescaped = self.unescaped()?;
         #[cfg(feature = "encoding")]
         {
-            #[cfg(feature = "encoding")]
-            let value = self.decoder.decode(&self.escaped_value);
+            let value = self.decoder.decode(&unescaped);
 
             match value.as_ref() {
                 "true" | "1" | "True" | "TRUE" | "t" | "Yes" | "YES" | "yes" | "y" => {
@@ -122,7 +136,7 @@ impl<'de> serde::Deserializer<'de> for EscapedDeserializer {
 
         #[cfg(not(feature = "encoding"))]
         {
-            match &*self.escaped_value {
+            match &*unescaped {
                 b"true" | b"1" | b"True" | b"TRUE" | b"t" | b"Yes" | b"YES" | b"yes" | b"y" => {
                     visitor.visit_bool(true)
                 }
@@ -201,7 +215,7 @@ impl<'de> serde::Deserializer<'de> for EscapedDeserializer {
     }
 }
 
-impl<'de> de::EnumAccess<'de> for EscapedDeserializer {
+impl<'de> de::EnumAccess<'de> for EscapedDeserializer<'de> {
     type Error = DeError;
     type Variant = Self;
 
@@ -214,7 +228,7 @@ impl<'de> de::EnumAccess<'de> for EscapedDeserializer {
     }
 }
 
-impl<'de> de::VariantAccess<'de> for EscapedDeserializer {
+impl<'de> de::VariantAccess<'de> for EscapedDeserializer<'de> {
     type Error = DeError;
 
     fn unit_variant(self) -> Result<(), DeError> {
@@ -1,77 +1,182 @@
 //! Serde `Deserializer` module
 
 use crate::{
-    de::{escape::EscapedDeserializer, Deserializer, INNER_VALUE, UNFLATTEN_PREFIX},
-    errors::serialize::DeError,
-    events::{attributes::Attribute, BytesStart, Event},
+    de::{
+        escape::EscapedDeserializer, Deserializer, XmlRead, ATTRIBUTE_PREFIX, ELEMENT_NAME,
+        INNER_VALUE, UNFLATTEN_PREFIX,
+    },
+    errors::{serialize::DeError, Error},
+    escape::normalize_attribute_value,
+    events::{
+        attributes::{AttrQuote, Attributes},
+        BytesStart, Event,
+    },
 };
-use serde::de::{self, DeserializeSeed, IntoDeserializer};
-use std::io::BufRead;
+use serde::de::{self, DeserializeSeed, IntoDeserializer, Visitor};
+use serde::forward_to_deserialize_any;
+use std::borrow::Cow;
+use std::ops::Range;
 
-enum MapValue {
+enum MapValue<'a> {
     Empty,
-    Attribute { value: Vec<u8> },
-    Nested,
+    Attribute {
+        /// Value of the attribute, borrowed from the input, if the deserializer borrows it
+        value: Cow<'a, [u8]>,
+        /// Position of the value in the document
+        position: usize,
+        /// Position of the name in the tag
+        key: Range<usize>,
+    },
+    /// An attribute without a value, like `<option selected>`, read in the HTML mode
+    Valueless,
+    /// A child element with the name, reported in the errors of its value
+    Nested(Vec<u8>),
     InnerValue,
+    /// Local name of the element for the `$name` field
+    Name(Vec<u8>),
 }
 
 /// A deserializer for `Attributes`
-pub(crate) struct MapAccess<'a, R: BufRead> {
+pub(crate) struct MapAccess<'de, 'a, R: XmlRead<'de>> {
     /// Tag -- owner of attributes
-    start: BytesStart<'static>,
-    de: &'a mut Deserializer<R>,
+    start: BytesStart<'de>,
+    de: &'a mut Deserializer<'de, R>,
     /// Position in flat byte slice of all attributes from which next
     /// attribute should be parsed. This field is required because we
     /// do not store reference to `Attributes` itself but instead create
     /// a new object on each advance of `Attributes` iterator, so we need
     /// to restore last position before advance.
     position: usize,
-    value: MapValue,
+    /// Position of the content of the tag, after `<`, in the document
+    start_position: usize,
+    value: MapValue<'de>,
+    /// Whether the `$name` field should be returned before the attributes
+    name_field: bool,
+    /// Fields of the struct, if some of them have the `$attr:` prefix, so the attributes
+    /// are returned with the prefix for them
+    attribute_fields: Option<&'static [&'static str]>,
 }
 
-impl<'a, R: BufRead> MapAccess<'a, R> {
+impl<'de, 'a, R: XmlRead<'de>> MapAccess<'de, 'a, R> {
     /// Create a new MapAccess
-    pub fn new(de: &'a mut Deserializer<R>, start: BytesStart<'static>) -> Result<Self, DeError> {
+    pub fn new(
+        de: &'a mut Deserializer<'de, R>,
+        start: BytesStart<'de>,
+        name_field: bool,
+        fields: &'static [&'static str],
+    ) -> Result<Self, DeError> {
         let position = start.attributes().position;
+        // The tag is the last event read by the deserializer
+        let start_position = de.position;
         Ok(MapAccess {
             de,
             start,
             position,
+            start_position,
             value: MapValue::Empty,
+            name_field,
+            attribute_fields: if fields.iter().any(|f| f.starts_with(ATTRIBUTE_PREFIX)) {
+                Some(fields)
+            } else {
+                None
+            },
         })
     }
 
-    fn next_attr(&mut self) -> Result<Option<Attribute>, DeError> {
-        let mut attributes = self.start.attributes();
-        attributes.position = self.position;
-        let next_att = attributes.next();
-        self.position = attributes.position;
-        Ok(next_att.transpose()?)
+    fn next_attr(&mut self) -> Result<Option<Attr<'de>>, DeError> {
+        let lenient = self.de.reader.reader().lenient_attributes();
+        let position = &mut self.position;
+        match self.start.borrowed_buf() {
+            // Values are borrowed from the input, if the tag is
+            Some(buf) => read_attr(buf, position, self.start_position, lenient),
+            None => {
+                let attr = read_attr(&self.start, position, self.start_position, lenient)?;
+                Ok(attr.map(|attr| Attr {
+                    value: Cow::Owned(attr.value.into_owned()),
+                    ..attr
+                }))
+            }
+        }
     }
 }
 
-impl<'a, 'de, R: BufRead> de::MapAccess<'de> for MapAccess<'a, R> {
+/// Reads the next attribute from `buf`, the content of a tag at `start_position` in
+/// the document, starting at `position` in `buf`, and advances the `position`
+fn read_attr<'a>(
+    buf: &'a [u8],
+    position: &mut usize,
+    start_position: usize,
+    lenient: bool,
+) -> Result<Option<Attr<'a>>, DeError> {
+    let mut attributes = if lenient {
+        Attributes::html(buf, *position)
+    } else {
+        Attributes::new(buf, *position)
+    }
+    .with_spans();
+    let next_att = attributes.next().transpose()?;
+    *position = attributes.position();
+    Ok(next_att.map(|a| Attr {
+        key: a.attribute.key.to_vec(),
+        key_span: a.key_span,
+        valueless: a.quote == AttrQuote::Unquoted && a.value_span.is_empty(),
+        position: start_position + a.value_span.start,
+        value: a.attribute.value,
+    }))
+}
+
+/// An attribute, read by `MapAccess::next_attr`
+struct Attr<'a> {
+    key: Vec<u8>,
+    /// Position of the key in the tag
+    key_span: Range<usize>,
+    value: Cow<'a, [u8]>,
+    /// Position of the value in the document
+    position: usize,
+    /// Whether the attribute has no value, like `<option selected>`
+    valueless: bool,
+}
+
+impl<'de, 'a, R: XmlRead<'de>> de::MapAccess<'de> for MapAccess<'de, 'a, R> {
     type Error = DeError;
 
     fn next_key_seed<K: DeserializeSeed<'de>>(
         &mut self,
         seed: K,
     ) -> Result<Option<K::Value>, Self::Error> {
-        let attr_key_val = self
-            .next_attr()?
-            .map(|a| (a.key.to_owned(), a.value.into_owned()));
-        let decoder = self.de.reader.decoder();
+        if self.name_field {
+            self.name_field = false;
+            self.value = MapValue::Name(self.start.local_name().to_vec());
+            return seed.deserialize(ELEMENT_NAME.into_deserializer()).map(Some);
+        }
+        let attr = self.next_attr()?;
+        let decoder = self.de.reader.reader().decoder();
         let has_value_field = self.de.has_value_field;
         let has_unflatten_field = self.de.has_unflatten_field;
-        if let Some((key, value)) = attr_key_val {
+        if let Some(attr) = attr {
             // try getting map from attributes (key= "value")
-            self.value = MapValue::Attribute { value };
-            seed.deserialize(EscapedDeserializer::new(key, decoder, false))
+            self.value = if attr.valueless {
+                MapValue::Valueless
+            } else {
+                MapValue::Attribute {
+                    position: attr.position,
+                    value: attr.value,
+                    key: attr.key_span,
+                }
+            };
+            let mut key = self.de.field_name(attr.key);
+            if let Some(fields) = self.attribute_fields {
+                let prefixed = format!("{}{}", ATTRIBUTE_PREFIX, String::from_utf8_lossy(&key));
+                if fields.contains(&prefixed.as_str()) {
+                    key = prefixed.into_bytes();
+                }
+            }
+            seed.deserialize(EscapedDeserializer::new(Cow::Owned(key), decoder, false))
                 .map(Some)
         } else {
             // try getting from events (<key>value</key>)
             match self.de.peek()? {
-                Some(Event::Text(_)) => {
+                Some(Event::Text(_)) | Some(Event::CData(_)) => {
                     self.value = MapValue::InnerValue;
                     seed.deserialize(INNER_VALUE.into_deserializer()).map(Some)
                 }
@@ -96,16 +201,17 @@ impl<'a, 'de, R: BufRead> de::MapAccess<'de> for MapAccess<'a, R> {
                     seed.deserialize(INNER_VALUE.into_deserializer()).map(Some)
                 }
                 Some(Event::Start(e)) if has_unflatten_field => {
-                    self.value = MapValue::InnerValue;
+                    self.value = MapValue::Nested(e.local_name().to_vec());
                     let key = format!("{}{}", UNFLATTEN_PREFIX, String::from_utf8(e.local_name().to_vec())
                                       .expect("$unflatten= did not contain valid Rust identifier"));
                     seed.deserialize(key.into_deserializer()).map(Some)
                 }
                 Some(Event::Start(e)) => {
-                    let name = e.local_name().to_owned();
+                    let name = e.local_name().to_vec();
+                    self.value = MapValue::Nested(name.clone());
+                    let name = self.de.field_name(name);
 
-                    self.value = MapValue::Nested;
-                    seed.deserialize(EscapedDeserializer::new(name, decoder, false))
+                    seed.deserialize(EscapedDeserializer::new(Cow::Owned(name), decoder, false))
                         .map(Some)
                 }
                 _ => Ok(None),
@@ -118,13 +224,69 @@ impl<'a, 'de, R: BufRead> de::MapAccess<'de> for MapAccess<'a, R> {
         seed: K,
     ) -> Result<K::Value, Self::Error> {
         match std::mem::replace(&mut self.value, MapValue::Empty) {
-            MapValue::Attribute { value } => seed.deserialize(EscapedDeserializer::new(
+            MapValue::Attribute {
                 value,
-                self.de.reader.decoder(),
-                true,
+                position,
+                key,
+            } => {
+                let html = self.de.reader.reader().html_entities();
+                let normalized =
+                    normalize_attribute_value(&value, html, Some(&self.de.entities))
+                        .map_err(|e| DeError::Xml(Error::EscapeError(e.with_offset(position))))?;
+                // The value stays borrowed from the input, if it has nothing to normalize
+                let value = match normalized {
+                    Cow::Borrowed(_) => value,
+                    Cow::Owned(normalized) => Cow::Owned(normalized),
+                };
+                seed.deserialize(EscapedDeserializer::new(
+                    value,
+                    self.de.reader.reader().decoder(),
+                    false,
+                ))
+                .map_err(|e| e.in_field(&self.start[key], position))
+            }
+            MapValue::Valueless => seed.deserialize(ValuelessDeserializer),
+            MapValue::Name(name) => seed.deserialize(EscapedDeserializer::new(
+                Cow::Owned(name),
+                self.de.reader.reader().decoder(),
+                false,
             )),
-            MapValue::Nested | MapValue::InnerValue => seed.deserialize(&mut *self.de),
+            // The value is read from the events, so the error is after the last one.
+            // Errors of the content are reported by the element, that contains it
+            MapValue::Nested(name) => seed
+                .deserialize(&mut *self.de)
+                .map_err(|e| e.in_field(&name, self.de.position)),
+            MapValue::InnerValue => seed.deserialize(&mut *self.de),
             MapValue::Empty => Err(DeError::EndOfAttributes),
         }
     }
 }
+
+/// A deserializer for an attribute without a value, like `<option selected>`, which is
+/// `true` as a boolean and an empty string otherwise
+struct ValuelessDeserializer;
+
+impl<'de> de::Deserializer<'de> for ValuelessDeserializer {
+    type Error = DeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        visitor.visit_str("")
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        visitor.visit_bool(true)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        visitor.visit_unit()
+    }
+
+    forward_to_deserialize_any! {
+        i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf
+        unit_struct newtype_struct seq tuple tuple_struct map struct enum identifier ignored_any
+    }
+}
@@ -114,32 +114,168 @@ mod var;
 
 pub use crate::errors::serialize::DeError;
 use crate::{
-    events::{BytesStart, BytesText, Event},
-    Reader,
+    errors::Error,
+    escape::do_unescape_with_html,
+    events::{BytesEnd, BytesStart, BytesText, Event},
+    name::QName,
+    reader::is_whitespace,
+    Reader, ReaderConfig,
 };
-use serde::de::{self, DeserializeOwned};
+use serde::de::{self, Deserialize, DeserializeOwned};
 use serde::serde_if_integer128;
+use std::borrow::Cow;
+use std::collections::HashMap;
 use std::io::BufRead;
+use std::marker::PhantomData;
+use std::ops::Range;
 
 pub(crate) const INNER_VALUE: &str = "$value";
 pub(crate) const UNFLATTEN_PREFIX: &str = "$unflatten=";
+pub(crate) const ATTRIBUTE_PREFIX: &str = "$attr:";
+pub(crate) const ELEMENT_NAME: &str = "$name";
+
+/// A naming convention of XML names, which are converted to `snake_case` names of
+/// the fields by [`Deserializer::rename_fields`].
+///
+/// [`Deserializer::rename_fields`]: struct.Deserializer.html#method.rename_fields
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Convention {
+    /// `kebab-case` names: each `-` is replaced with `_`, so `user-name` matches
+    /// the `user_name` field
+    KebabToSnake,
+    /// `camelCase` and `PascalCase` names: `_` is inserted before each uppercase ASCII
+    /// letter, except the first one, and all letters are lowercased, so `userName` and
+    /// `UserName` match the `user_name` field
+    CamelToSnake,
+}
+
+impl Convention {
+    fn apply(self, name: &[u8]) -> Vec<u8> {
+        match self {
+            Convention::KebabToSnake => name
+                .iter()
+                .map(|&b| if b == b'-' { b'_' } else { b })
+                .collect(),
+            Convention::CamelToSnake => {
+                let mut result = Vec::with_capacity(name.len() + 4);
+                for (i, &b) in name.iter().enumerate() {
+                    if b.is_ascii_uppercase() && i > 0 {
+                        result.push(b'_');
+                    }
+                    result.push(b.to_ascii_lowercase());
+                }
+                result
+            }
+        }
+    }
+}
 
 /// An xml deserializer
-pub struct Deserializer<R: BufRead> {
-    reader: Reader<R>,
-    peek: Option<Event<'static>>,
+///
+/// A struct field renamed to `$name` gets the local name of the element, from which
+/// the struct is deserialized, so differently named elements can share one type:
+///
+/// ```edition2018
+/// use quick_xml::de::from_str;
+/// use serde::Deserialize;
+///
+/// #[derive(Debug, Deserialize, PartialEq)]
+/// struct Shape {
+///     #[serde(rename = "$name")]
+///     kind: String,
+///     size: u32,
+/// }
+///
+/// let shape: Shape = from_str(r#"<circle size="3"/>"#).unwrap();
+/// assert_eq!(shape, Shape { kind: "circle".into(), size: 3 });
+/// ```
+///
+/// A struct field renamed to `$value` gets the text content of the element, and other
+/// fields get its attributes. The text can be parsed as any primitive type and can be
+/// written as a CDATA section. An element without text has no `$value`, so make the field
+/// `#[serde(default)]`, if the text can be empty. The serializer writes such structs back
+/// in the same form:
+///
+/// ```edition2018
+/// use quick_xml::{de::from_str, se::to_string};
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Debug, Deserialize, PartialEq, Serialize)]
+/// #[serde(rename = "measurement")]
+/// struct Measurement {
+///     unit: String,
+///     #[serde(rename = "$value")]
+///     value: f64,
+/// }
+///
+/// let xml = r#"<measurement unit="kg">42.5</measurement>"#;
+/// let measurement: Measurement = from_str(xml).unwrap();
+/// assert_eq!(measurement, Measurement { unit: "kg".into(), value: 42.5 });
+/// assert_eq!(to_string(&measurement).unwrap(), xml);
+/// ```
+///
+/// A deserializer created by [`from_str`] or [`Deserializer::from_str`] borrows strings and
+/// bytes from the input, so `&str` fields can be deserialized without copying, if they
+/// have nothing to unescape. Values with escaped characters are unescaped into new strings,
+/// so use `Cow<str>` fields for such values:
+///
+/// ```edition2018
+/// use quick_xml::de::from_str;
+/// use serde::Deserialize;
+/// use std::borrow::Cow;
+///
+/// #[derive(Debug, Deserialize, PartialEq)]
+/// struct Book<'a> {
+///     title: &'a str,
+///     #[serde(borrow)]
+///     author: Cow<'a, str>,
+/// }
+///
+/// let xml = "<book title='Dune'><author>Frank &amp; Co</author></book>";
+/// let book: Book = from_str(xml).unwrap();
+/// assert_eq!(book.title, "Dune");
+/// assert_eq!(book.author, "Frank & Co");
+/// ```
+///
+/// [`from_str`]: fn.from_str.html
+/// [`Deserializer::from_str`]: #method.from_str
+pub struct Deserializer<'de, R: XmlRead<'de>> {
+    reader: R,
+    peek: Option<Event<'de>>,
     has_value_field: bool,
     has_unflatten_field: bool,
+    /// Names of the structs' elements that are currently being deserialized
+    path: Vec<String>,
+    /// Values of the entities, that are not predefined
+    entities: HashMap<Vec<u8>, Vec<u8>>,
+    /// Position in the document of the content of the last event read from the reader,
+    /// used to report positions of unescaping errors in the document
+    position: usize,
+    /// Convention of the names of attributes and elements, converted to the names of fields
+    convention: Option<Convention>,
+    /// Namespace prefix with a colon, removed from the names of attributes
+    namespace_prefix: Option<Vec<u8>>,
+    /// Number of the opened elements of the deserialized element, created by
+    /// [`Deserializer::from_element`], so nothing is read after its end tag
+    depth: Option<usize>,
+    /// End tag of the empty element, created by [`Deserializer::from_empty_element`],
+    /// returned after its start tag
+    empty_end: Option<BytesEnd<'static>>,
 }
 
 /// Deserialize an instance of type T from a string of XML text.
-pub fn from_str<T: DeserializeOwned>(s: &str) -> Result<T, DeError> {
-    from_reader(s.as_bytes())
+///
+/// Strings are borrowed from `s`, if they have nothing to unescape.
+pub fn from_str<'de, T: Deserialize<'de>>(s: &'de str) -> Result<T, DeError> {
+    from_slice(s.as_bytes())
 }
 
 /// Deserialize an instance of type T from bytes of XML text.
-pub fn from_slice<T: DeserializeOwned>(b: &[u8]) -> Result<T, DeError> {
-    from_reader(b)
+///
+/// Strings and bytes are borrowed from `b`, if they have nothing to unescape.
+pub fn from_slice<'de, T: Deserialize<'de>>(b: &'de [u8]) -> Result<T, DeError> {
+    let mut de = Deserializer::from_slice(b);
+    T::deserialize(&mut de)
 }
 
 /// Deserialize from a reader
@@ -148,15 +284,414 @@ pub fn from_reader<R: BufRead, T: DeserializeOwned>(reader: R) -> Result<T, DeEr
     T::deserialize(&mut de)
 }
 
-impl<R: BufRead> Deserializer<R> {
+/// Deserialize an instance of type T from a string of XML text, read with the options
+/// of the `config`, except that empty elements are always expanded, as the deserializer
+/// requires.
+///
+/// Unlike [`from_str`], texts, which contain only whitespaces, are not skipped, unless
+/// the `config` drops or trims them.
+///
+/// # Examples
+///
+/// ```edition2018
+/// use quick_xml::de::from_str_with_config;
+/// use quick_xml::{DeError, Error, ReaderConfig};
+/// use serde::Deserialize;
+///
+/// #[derive(Debug, Deserialize, PartialEq)]
+/// struct Item {
+///     id: u32,
+/// }
+///
+/// let config = ReaderConfig::secure().trim_text(true);
+/// let item: Item = from_str_with_config(r#"<item id="1"> </item>"#, &config).unwrap();
+/// assert_eq!(item, Item { id: 1 });
+///
+/// let xml = r#"<!DOCTYPE item><item id="1"/>"#;
+/// match from_str_with_config::<Item>(xml, &config) {
+///     Err(DeError::Xml(Error::ForbiddenDocType(0))) => {}
+///     x => panic!("Expected `ForbiddenDocType`, found {:?}", x),
+/// }
+/// ```
+///
+/// [`from_str`]: fn.from_str.html
+pub fn from_str_with_config<'de, T: Deserialize<'de>>(
+    s: &'de str,
+    config: &ReaderConfig,
+) -> Result<T, DeError> {
+    let mut reader = config.build_from_str(s);
+    reader.expand_empty_elements(true);
+    let mut de = Deserializer::from_slice_reader(reader);
+    T::deserialize(&mut de)
+}
+
+/// Deserialize from a reader with the options of the `config`, except that empty elements
+/// are always expanded, as the deserializer requires. See [`from_str_with_config`].
+///
+/// [`from_str_with_config`]: fn.from_str_with_config.html
+pub fn from_reader_with_config<R: BufRead, T: DeserializeOwned>(
+    reader: R,
+    config: &ReaderConfig,
+) -> Result<T, DeError> {
+    let mut reader = config.build_from_reader(reader);
+    reader.expand_empty_elements(true);
+    let mut de = Deserializer::new(reader);
+    T::deserialize(&mut de)
+}
+
+/// Deserializes an instance of type T from each element named `element_name` read from
+/// `reader`, one at a time, so documents with many such elements, like a huge
+/// `<records><record/>...</records>` feed, can be processed in constant memory.
+///
+/// Elements with other names, like the wrapper element, are entered and their texts are
+/// skipped, so matching elements are found at any depth. Elements are matched by their
+/// qualified or local name. The iterator ends at the end of the document or after the
+/// first error.
+///
+/// # Examples
+///
+/// ```edition2018
+/// use quick_xml::de::iter_from_reader;
+/// use serde::Deserialize;
+///
+/// #[derive(Debug, Deserialize, PartialEq)]
+/// struct Record {
+///     id: u32,
+///     name: String,
+/// }
+///
+/// let xml = r#"<records>
+///     <record id="1"><name>first</name></record>
+///     <record id="2"><name>second</name></record>
+/// </records>"#;
+/// let records: Vec<Record> = iter_from_reader(xml.as_bytes(), "record")
+///     .collect::<Result<_, _>>()
+///     .unwrap();
+/// assert_eq!(records[1], Record { id: 2, name: "second".into() });
+/// ```
+pub fn iter_from_reader<R: BufRead, T: DeserializeOwned>(
+    reader: R,
+    element_name: &str,
+) -> ElementIter<R, T> {
+    ElementIter {
+        de: Deserializer::from_reader(reader),
+        name: element_name.as_bytes().to_vec(),
+        done: false,
+        item: PhantomData,
+    }
+}
+
+/// An iterator over the deserialized elements of a document.
+///
+/// This struct is created by the [`iter_from_reader`] function.
+///
+/// [`iter_from_reader`]: fn.iter_from_reader.html
+pub struct ElementIter<R: BufRead, T> {
+    de: Deserializer<'static, IoReader<R>>,
+    /// Name of the deserialized elements
+    name: Vec<u8>,
+    /// Whether the end of the document or an error is reached
+    done: bool,
+    item: PhantomData<T>,
+}
+
+impl<R: BufRead, T: DeserializeOwned> ElementIter<R, T> {
+    /// Skips events up to the next element to deserialize, returns `false` at the end of
+    /// the document
+    fn find_next(&mut self) -> Result<bool, DeError> {
+        let name = &self.name;
+        let found = find_element(&mut self.de, |e| {
+            if has_name(e, name) {
+                Some(())
+            } else {
+                None
+            }
+        })?;
+        Ok(found.is_some())
+    }
+}
+
+/// Whether the element `e` has the qualified or local `name`
+fn has_name(e: &BytesStart, name: &[u8]) -> bool {
+    e.name().as_ref() == name || e.local_name().as_ref() == name
+}
+
+/// Skips events up to the next start tag, for which `find` returns `Some`, and returns
+/// that result, or `None` at the end of the document. The start tag is not consumed
+fn find_element<'de, R, T, F>(de: &mut Deserializer<'de, R>, find: F) -> Result<Option<T>, DeError>
+where
+    R: XmlRead<'de>,
+    F: Fn(&BytesStart) -> Option<T>,
+{
+    loop {
+        match de.peek()? {
+            Some(Event::Start(e)) => {
+                if let Some(found) = find(e) {
+                    return Ok(Some(found));
+                }
+            }
+            None | Some(Event::Eof) => return Ok(None),
+            _ => {}
+        }
+        de.next()?;
+    }
+}
+
+/// A function, which deserializes an element, found by [`from_reader_seq_dispatch`],
+/// from the deserializer, positioned at its start tag.
+///
+/// [`from_reader_seq_dispatch`]: fn.from_reader_seq_dispatch.html
+pub type DispatchFn<R, T> = fn(&mut Deserializer<'static, IoReader<R>>) -> Result<T, DeError>;
+
+/// Deserializes elements of different types from `reader`, one at a time, choosing the
+/// function to deserialize each element by its name in `dispatch`. This serves documents
+/// with open content models, where elements of several kinds are mixed in any order,
+/// and collects them, for example, into a `Vec` of an enum with a variant for each kind.
+///
+/// Elements with names, not listed in `dispatch`, like the wrapper element, are entered
+/// and their texts are skipped, as by [`iter_from_reader`], so elements of unknown kinds
+/// are ignored. Elements are matched by their qualified or local name. The iterator ends
+/// at the end of the document or after the first error.
+///
+/// # Examples
+///
+/// ```edition2018
+/// use quick_xml::de::from_reader_seq_dispatch;
+/// use serde::Deserialize;
+///
+/// #[derive(Debug, Deserialize, PartialEq)]
+/// struct Text {
+///     #[serde(rename = "$value")]
+///     content: String,
+/// }
+///
+/// #[derive(Debug, Deserialize, PartialEq)]
+/// struct Image {
+///     src: String,
+/// }
+///
+/// #[derive(Debug, PartialEq)]
+/// enum Block {
+///     Text(Text),
+///     Image(Image),
+/// }
+///
+/// let xml = r#"<page>
+///     <text>Hello</text>
+///     <image src="a.png"/>
+///     <video src="b.mp4"/>
+/// </page>"#;
+/// let blocks: Vec<Block> = from_reader_seq_dispatch(
+///     xml.as_bytes(),
+///     &[
+///         ("text", |de| Text::deserialize(de).map(Block::Text)),
+///         ("image", |de| Image::deserialize(de).map(Block::Image)),
+///     ],
+/// )
+/// .collect::<Result<_, _>>()
+/// .unwrap();
+/// assert_eq!(
+///     blocks,
+///     vec![
+///         Block::Text(Text { content: "Hello".into() }),
+///         Block::Image(Image { src: "a.png".into() }),
+///     ]
+/// );
+/// ```
+///
+/// [`iter_from_reader`]: fn.iter_from_reader.html
+pub fn from_reader_seq_dispatch<'a, R: BufRead, T>(
+    reader: R,
+    dispatch: &'a [(&'a str, DispatchFn<R, T>)],
+) -> DispatchIter<'a, R, T> {
+    DispatchIter {
+        de: Deserializer::from_reader(reader),
+        dispatch,
+        done: false,
+    }
+}
+
+/// An iterator over the elements of a document, deserialized by the functions, chosen
+/// by the names of the elements.
+///
+/// This struct is created by the [`from_reader_seq_dispatch`] function.
+///
+/// [`from_reader_seq_dispatch`]: fn.from_reader_seq_dispatch.html
+pub struct DispatchIter<'a, R: BufRead, T> {
+    de: Deserializer<'static, IoReader<R>>,
+    /// Names of the deserialized elements and the functions, which deserialize them
+    dispatch: &'a [(&'a str, DispatchFn<R, T>)],
+    /// Whether the end of the document or an error is reached
+    done: bool,
+}
+
+impl<'a, R: BufRead, T> Iterator for DispatchIter<'a, R, T> {
+    type Item = Result<T, DeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let dispatch = self.dispatch;
+        let found = find_element(&mut self.de, |e| {
+            dispatch
+                .iter()
+                .find(|(name, _)| has_name(e, name.as_bytes()))
+                .map(|(_, deserialize)| *deserialize)
+        });
+        let result = match found {
+            Ok(Some(deserialize)) => deserialize(&mut self.de),
+            Ok(None) => {
+                self.done = true;
+                return None;
+            }
+            Err(e) => Err(e),
+        };
+        self.done = result.is_err();
+        Some(result)
+    }
+}
+
+impl<R: BufRead, T: DeserializeOwned> Iterator for ElementIter<R, T> {
+    type Item = Result<T, DeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let result = match self.find_next() {
+            Ok(true) => T::deserialize(&mut self.de),
+            Ok(false) => {
+                self.done = true;
+                return None;
+            }
+            Err(e) => Err(e),
+        };
+        self.done = result.is_err();
+        Some(result)
+    }
+}
+
+impl<'de, R: BufRead> Deserializer<'de, IoReader<R>> {
     /// Get a new deserializer
     pub fn new(reader: Reader<R>) -> Self {
-        Deserializer {
-            reader,
-            peek: None,
-            has_value_field: false,
-            has_unflatten_field: false,
-        }
+        Self::from_source(IoReader::new(reader))
+    }
+
+    /// Creates a deserializer of the element, which start tag `start` was just read by
+    /// the `reader`, so a part of a large document can be deserialized.
+    ///
+    /// Only the element is deserialized: the deserializer returns the end of the document
+    /// after its end tag, and doesn't read the events after it, even on errors, so the reader,
+    /// returned by [`into_inner`], can be used to continue reading the document. The reader
+    /// is positioned after the end tag, if the element has been deserialized successfully.
+    ///
+    /// The reader is configured to expand empty elements, as the deserializer requires,
+    /// so `start` should be read as a [`Start`] event. Use [`from_empty_element`] for
+    /// an [`Empty`] event, read before that.
+    ///
+    /// # Examples
+    ///
+    /// ```edition2018
+    /// use quick_xml::de::Deserializer;
+    /// use quick_xml::events::Event;
+    /// use quick_xml::Reader;
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Debug, Deserialize, PartialEq)]
+    /// struct Record {
+    ///     id: u32,
+    /// }
+    ///
+    /// let xml = r#"<feed><meta/><record id="1"/><record id="2"/></feed>"#;
+    /// let mut reader = Reader::from_str(xml);
+    /// reader.expand_empty_elements(true).trim_text(true);
+    /// let mut buf = Vec::new();
+    /// let start = loop {
+    ///     match reader.read_event(&mut buf).unwrap() {
+    ///         Event::Start(e) if e.name().as_ref() == b"record" => break e.into_owned(),
+    ///         _ => {}
+    ///     }
+    /// };
+    ///
+    /// let mut de = Deserializer::from_element(reader, &start);
+    /// assert_eq!(Record::deserialize(&mut de).unwrap(), Record { id: 1 });
+    ///
+    /// // The second record is read by the reader
+    /// let mut reader = de.into_inner();
+    /// match reader.read_event(&mut buf).unwrap() {
+    ///     Event::Start(e) => assert_eq!(&*e, br#"record id="2""#.as_ref()),
+    ///     e => panic!("Expected `Start`, found {:?}", e),
+    /// }
+    /// ```
+    ///
+    /// [`into_inner`]: #method.into_inner
+    /// [`from_empty_element`]: #method.from_empty_element
+    /// [`Start`]: ../events/enum.Event.html#variant.Start
+    /// [`Empty`]: ../events/enum.Event.html#variant.Empty
+    pub fn from_element(reader: Reader<R>, start: &BytesStart) -> Self {
+        let mut reader = reader;
+        reader.expand_empty_elements(true);
+        let position = reader.buffer_position().saturating_sub(start.len() + 1);
+        let mut de = Self::new(reader);
+        de.peek = Some(Event::Start(start.to_owned()));
+        de.position = position;
+        de.depth = Some(1);
+        de
+    }
+
+    /// Creates a deserializer of the empty element `<.../>`, which was just read by
+    /// the `reader` as an [`Empty`] event, like [`from_element`] does for elements, read
+    /// as [`Start`] events.
+    ///
+    /// The deserializer returns the start and the end tags of the element, and nothing
+    /// is read from the `reader`.
+    ///
+    /// # Examples
+    ///
+    /// ```edition2018
+    /// use quick_xml::de::Deserializer;
+    /// use quick_xml::events::Event;
+    /// use quick_xml::Reader;
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Debug, Deserialize, PartialEq)]
+    /// struct Record {
+    ///     id: u32,
+    /// }
+    ///
+    /// let mut reader = Reader::from_str(r#"<record id="1"/><tail/>"#);
+    /// reader.trim_text(true);
+    /// let mut buf = Vec::new();
+    /// let start = match reader.read_event(&mut buf).unwrap() {
+    ///     Event::Empty(e) => e.into_owned(),
+    ///     e => panic!("Expected `Empty`, found {:?}", e),
+    /// };
+    ///
+    /// let mut de = Deserializer::from_empty_element(reader, &start);
+    /// assert_eq!(Record::deserialize(&mut de).unwrap(), Record { id: 1 });
+    ///
+    /// let mut reader = de.into_inner();
+    /// match reader.read_event(&mut buf).unwrap() {
+    ///     Event::Start(e) => assert_eq!(e.name().as_ref(), b"tail"),
+    ///     e => panic!("Expected `Start`, found {:?}", e),
+    /// }
+    /// ```
+    ///
+    /// [`from_element`]: #method.from_element
+    /// [`Start`]: ../events/enum.Event.html#variant.Start
+    /// [`Empty`]: ../events/enum.Event.html#variant.Empty
+    pub fn from_empty_element(reader: Reader<R>, start: &BytesStart) -> Self {
+        let mut reader = reader;
+        reader.expand_empty_elements(true);
+        // `/>`
+        let position = reader.buffer_position().saturating_sub(start.len() + 2);
+        let mut de = Self::new(reader);
+        de.peek = Some(Event::Start(start.to_owned()));
+        de.empty_end = Some(BytesEnd::owned(start.name().as_ref().to_vec()));
+        de.position = position;
+        de.depth = Some(0);
+        de
     }
 
     /// Get a new deserializer from a regular BufRead
@@ -165,40 +700,313 @@ impl<R: BufRead> Deserializer<R> {
         reader
             .expand_empty_elements(true)
             .check_end_names(true)
-            .trim_text(true);
+            .drop_whitespace_only_text(true);
         Self::new(reader)
     }
+}
+
+impl<'de> Deserializer<'de, SliceReader<'de>> {
+    /// Get a new deserializer from a string, which borrows strings from it
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: &'de str) -> Self {
+        Self::from_slice(s.as_bytes())
+    }
+
+    /// Get a new deserializer from bytes, which borrows strings and bytes from them
+    pub fn from_slice(b: &'de [u8]) -> Self {
+        let mut reader = Reader::from_bytes(b);
+        reader
+            .expand_empty_elements(true)
+            .check_end_names(true)
+            .drop_whitespace_only_text(true);
+        Self::from_slice_reader(reader)
+    }
+
+    /// Get a new deserializer, which borrows strings from the input of the `reader`
+    pub fn from_slice_reader(reader: Reader<&'de [u8]>) -> Self {
+        Self::from_source(SliceReader { reader })
+    }
+}
+
+impl<'de, R: XmlRead<'de>> Deserializer<'de, R> {
+    fn from_source(reader: R) -> Self {
+        Deserializer {
+            reader,
+            peek: None,
+            has_value_field: false,
+            has_unflatten_field: false,
+            path: Vec::new(),
+            entities: HashMap::new(),
+            position: 0,
+            convention: None,
+            namespace_prefix: None,
+            depth: None,
+            empty_end: None,
+        }
+    }
+
+    /// Returns the underlying reader.
+    ///
+    /// An event, which has been read by the deserializer, but has not been deserialized yet,
+    /// is lost.
+    pub fn into_inner(self) -> Reader<R::Input> {
+        self.reader.into_reader()
+    }
+
+    /// Sets values of the entities, that are not predefined, for example, declared in the DTD.
+    /// They are used to unescape texts and attribute values.
+    ///
+    /// Values are inserted as is, they are not scanned for other entities. The predefined XML
+    /// entities and character references are always resolved first and cannot be overridden.
+    /// HTML named entities, if enabled, are resolved before the custom ones too.
+    ///
+    /// # Examples
+    ///
+    /// ```edition2018
+    /// # use std::collections::HashMap;
+    /// use quick_xml::de::Deserializer;
+    /// use serde::Deserialize;
+    ///
+    /// let mut entities = HashMap::new();
+    /// entities.insert("product".to_string(), "quick-xml".to_string());
+    ///
+    /// let mut de = Deserializer::from_reader("<name>&product; &lt;3</name>".as_bytes());
+    /// de.custom_entities(entities);
+    /// assert_eq!(String::deserialize(&mut de).unwrap(), "quick-xml <3");
+    /// ```
+    pub fn custom_entities(&mut self, entities: HashMap<String, String>) -> &mut Self {
+        self.entities = entities
+            .into_iter()
+            .map(|(name, value)| (name.into_bytes(), value.into_bytes()))
+            .collect();
+        self
+    }
+
+    /// Changes whether HTML5 named character references, such as `&nbsp;`, are resolved
+    /// in texts and attribute values. See [`Reader::resolve_html_entities`] for details.
+    ///
+    /// (`true` by default)
+    ///
+    /// [`Reader::resolve_html_entities`]: ../struct.Reader.html#method.resolve_html_entities
+    #[cfg(feature = "escape-html")]
+    pub fn resolve_html_entities(&mut self, val: bool) -> &mut Self {
+        self.reader.reader_mut().resolve_html_entities(val);
+        self
+    }
+
+    /// Sets the naming convention of attributes and elements, which names are converted
+    /// to match the names of the struct fields, so `#[serde(rename)]` is not needed on each
+    /// field. Names are converted before they are given to serde, so explicit renames
+    /// must use the converted names.
+    ///
+    /// (names are not converted by default)
+    ///
+    /// # Examples
+    ///
+    /// ```edition2018
+    /// use quick_xml::de::{Convention, Deserializer};
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Debug, Deserialize, PartialEq)]
+    /// struct User {
+    ///     user_id: u32,
+    ///     user_name: String,
+    /// }
+    ///
+    /// let xml = "<user user-id='1'><user-name>Alice</user-name></user>";
+    /// let mut de = Deserializer::from_reader(xml.as_bytes());
+    /// de.rename_fields(Convention::KebabToSnake);
+    /// assert_eq!(
+    ///     User::deserialize(&mut de).unwrap(),
+    ///     User { user_id: 1, user_name: "Alice".into() }
+    /// );
+    /// ```
+    pub fn rename_fields(&mut self, convention: Convention) -> &mut Self {
+        self.convention = Some(convention);
+        self
+    }
+
+    /// Sets the namespace prefix, which is removed from the names of attributes, so
+    /// `xsi:type` matches the `type` field if the prefix is `xsi`. Names of elements are
+    /// always matched without their prefixes. The prefix is removed before the naming
+    /// convention, if any, is applied.
+    ///
+    /// (no prefix is removed by default)
+    pub fn strip_namespace_prefix(&mut self, prefix: &str) -> &mut Self {
+        let mut prefix = prefix.as_bytes().to_vec();
+        prefix.push(b':');
+        self.namespace_prefix = Some(prefix);
+        self
+    }
+
+    /// Changes whether the prolog and the epilog of the document are checked.
+    /// See [`Reader::strict_prolog`] for details.
+    ///
+    /// (`false` by default)
+    ///
+    /// # Examples
+    ///
+    /// ```edition2018
+    /// use quick_xml::de::{DeError, Deserializer};
+    /// use quick_xml::Error;
+    /// use serde::Deserialize;
+    ///
+    /// let xml = "<!DOCTYPE name><?xml version='1.0'?><name>quick-xml</name>";
+    /// let mut de = Deserializer::from_reader(xml.as_bytes());
+    /// de.strict_prolog(true);
+    /// match String::deserialize(&mut de) {
+    ///     Err(DeError::Xml(Error::InvalidProlog(position, _))) => assert_eq!(position, 15),
+    ///     e => panic!("Expected `InvalidProlog`, found {:?}", e),
+    /// }
+    ///
+    /// let xml = "<?xml version='1.0'?><!DOCTYPE name><name>quick-xml</name>";
+    /// let mut de = Deserializer::from_reader(xml.as_bytes());
+    /// de.strict_prolog(true);
+    /// assert_eq!(String::deserialize(&mut de).unwrap(), "quick-xml");
+    /// assert!(de.has_declaration());
+    /// assert!(de.has_doctype());
+    /// ```
+    ///
+    /// [`Reader::strict_prolog`]: ../struct.Reader.html#method.strict_prolog
+    pub fn strict_prolog(&mut self, val: bool) -> &mut Self {
+        self.reader.reader_mut().strict_prolog(val);
+        self
+    }
+
+    /// Returns `true` if an XML declaration `<?xml ...?>` has been read.
+    pub fn has_declaration(&self) -> bool {
+        self.reader.reader().has_declaration()
+    }
+
+    /// Returns `true` if a DOCTYPE declaration has been read.
+    pub fn has_doctype(&self) -> bool {
+        self.reader.reader().has_doctype()
+    }
+
+    /// Converts the name of an attribute or an element to the name of a field
+    fn field_name(&self, name: Vec<u8>) -> Vec<u8> {
+        let name = match self.namespace_prefix {
+            Some(ref prefix) if name.starts_with(prefix) => name[prefix.len()..].to_vec(),
+            _ => name,
+        };
+        match self.convention {
+            Some(convention) => convention.apply(&name),
+            None => name,
+        }
+    }
+
+    /// Unescapes `raw`, the content of the last read event, using the custom entities and
+    /// settings of the reader
+    fn unescape<'b>(&self, raw: &'b [u8]) -> Result<Cow<'b, [u8]>, DeError> {
+        let html = self.reader.reader().html_entities();
+        do_unescape_with_html(raw, Some(&self.entities), html)
+            .map_err(|e| self.in_document(Error::EscapeError(e)))
+    }
+
+    /// Decodes and unescapes `text`, the content of the last read event. The string is
+    /// borrowed from the input, if `text` is borrowed and has nothing to unescape
+    fn decode_text(&self, text: Cow<'de, [u8]>) -> Result<Cow<'de, str>, DeError> {
+        let decoder = self.reader.reader().decoder();
+        let decoded = match text {
+            #[cfg(not(feature = "encoding"))]
+            Cow::Borrowed(bytes) => Cow::Borrowed(decoder.decode(bytes)?),
+            #[cfg(feature = "encoding")]
+            Cow::Borrowed(bytes) => decoder.decode(bytes),
+            #[cfg(not(feature = "encoding"))]
+            Cow::Owned(bytes) => Cow::Owned(
+                String::from_utf8(bytes).map_err(|e| DeError::Xml(Error::Utf8(e.utf8_error())))?,
+            ),
+            #[cfg(feature = "encoding")]
+            Cow::Owned(bytes) => Cow::Owned(decoder.decode(&bytes).into_owned()),
+        };
+        let unescaped = match self.unescape(decoded.as_bytes())? {
+            Cow::Borrowed(_) => None,
+            Cow::Owned(unescaped) => Some(unescaped),
+        };
+        match unescaped {
+            Some(unescaped) => String::from_utf8(unescaped)
+                .map(Cow::Owned)
+                .map_err(|e| DeError::Xml(Error::Utf8(e.utf8_error()))),
+            None => Ok(decoded),
+        }
+    }
+
+    /// Translates positions of an unescaping error in the content of the last read event
+    /// to positions in the document
+    fn in_document(&self, error: Error) -> DeError {
+        match error {
+            Error::EscapeError(e) => DeError::Xml(Error::EscapeError(e.with_offset(self.position))),
+            e => DeError::Xml(e),
+        }
+    }
 
-    fn peek(&mut self) -> Result<Option<&Event<'static>>, DeError> {
+    fn peek(&mut self) -> Result<Option<&Event<'de>>, DeError> {
         if self.peek.is_none() {
-            self.peek = Some(self.next(&mut Vec::new())?);
+            self.peek = Some(self.next()?);
         }
         Ok(self.peek.as_ref())
     }
 
-    fn next<'a>(&mut self, buf: &'a mut Vec<u8>) -> Result<Event<'static>, DeError> {
+    fn next(&mut self) -> Result<Event<'de>, DeError> {
         if let Some(e) = self.peek.take() {
             return Ok(e);
         }
+        if let Some(e) = self.empty_end.take() {
+            return Ok(Event::End(e));
+        }
+        if self.depth == Some(0) {
+            return Ok(Event::Eof);
+        }
         loop {
-            let e = self.reader.read_event(buf)?;
+            let position = self.reader.reader().buffer_position();
+            let (e, raw) = self.reader.next()?;
+            // Leading whitespaces are removed if the reader trims texts, and trimmed
+            // texts never start with a whitespace
+            let text_position = match e {
+                Event::Text(ref t) if matches!(t.first(), Some(&b) if !is_whitespace(b)) => {
+                    position + raw.iter().position(|&b| !is_whitespace(b)).unwrap_or(0)
+                }
+                _ => position,
+            };
             match e {
                 Event::Start(_) | Event::End(_) | Event::Text(_) | Event::Eof | Event::CData(_) => {
-                    return Ok(e.into_owned())
+                    if let Some(depth) = self.depth.as_mut() {
+                        match e {
+                            Event::Start(_) => *depth += 1,
+                            Event::End(_) => *depth = depth.saturating_sub(1),
+                            _ => {}
+                        }
+                    }
+                    self.position = self.content_position(&e, text_position);
+                    return Ok(e);
                 }
-                _ => buf.clear(),
+                _ => {}
             }
         }
     }
 
-    fn next_start(&mut self, buf: &mut Vec<u8>) -> Result<Option<BytesStart<'static>>, DeError> {
+    /// Returns the position in the document of the content of the event `e`, which is
+    /// at `text_position` for texts
+    fn content_position(&self, e: &Event, text_position: usize) -> usize {
+        let markup_start = self.reader.reader().markup_start();
+        match e {
+            Event::Text(_) => text_position,
+            // `<`. Unquoted attribute values are quoted in the event in the lenient mode,
+            // so the length of the event cannot be used
+            Event::Start(_) => markup_start + 1,
+            // `<![CDATA[`
+            Event::CData(_) => markup_start + 9,
+            _ => self.position,
+        }
+    }
+
+    fn next_start(&mut self) -> Result<Option<BytesStart<'de>>, DeError> {
         loop {
-            let e = self.next(buf)?;
-            match e {
+            match self.next()? {
                 Event::Start(e) => return Ok(Some(e)),
                 Event::End(_) => return Err(DeError::End),
                 Event::Eof => return Ok(None),
-                _ => buf.clear(), // ignore texts
+                _ => {} // ignore texts
             }
         }
     }
@@ -210,26 +1018,11 @@ impl<R: BufRead> Deserializer<R> {
     /// |`<tag ...>text</tag>`|`text`     |Complete tag consumed       |
     /// |`<tag/>`             |empty slice|Virtual end tag not consumed|
     /// |`</tag>`             |empty slice|Not consumed                |
-    fn next_text<'a>(&mut self) -> Result<BytesText<'static>, DeError> {
-        match self.next(&mut Vec::new())? {
+    fn next_text(&mut self) -> Result<BytesText<'de>, DeError> {
+        match self.next()? {
             Event::Text(e) | Event::CData(e) => Ok(e),
             Event::Eof => Err(DeError::Eof),
-            Event::Start(e) => {
-                // allow one nested level
-                let inner = self.next(&mut Vec::new())?;
-                let t = match inner {
-                    Event::Text(t) | Event::CData(t) => t,
-                    Event::Start(_) => return Err(DeError::Start),
-                    Event::End(end) if end.name() == e.name() => {
-                        return Ok(BytesText::from_escaped(&[] as &[u8]));
-                    }
-                    Event::End(_) => return Err(DeError::End),
-                    Event::Eof => return Err(DeError::Eof),
-                    _ => unreachable!(),
-                };
-                self.read_to_end(e.name())?;
-                Ok(t)
-            }
+            Event::Start(e) => self.read_text_content(e.name()),
             Event::End(e) => {
                 self.peek = Some(Event::End(e));
                 Ok(BytesText::from_escaped(&[] as &[u8]))
@@ -238,14 +1031,163 @@ impl<R: BufRead> Deserializer<R> {
         }
     }
 
-    fn read_to_end(&mut self, name: &[u8]) -> Result<(), DeError> {
-        let mut buf = Vec::new();
-        match self.next(&mut buf)? {
-            Event::Start(e) => self.reader.read_to_end(e.name(), &mut Vec::new())?,
+    /// Reads texts and CDATA sections up to the end of the element `name`, which start tag
+    /// was just read, and concatenates them. One nested level is allowed: the content after
+    /// a nested element is skipped.
+    ///
+    /// CDATA sections are taken verbatim. If the reader trims texts, only the whitespaces
+    /// at the start and at the end of the whole content are trimmed, so whitespaces between
+    /// texts and CDATA sections are kept. If the reader drops whitespace-only texts, such texts
+    /// at the start and at the end of the content are dropped.
+    fn read_text_content(&mut self, name: QName) -> Result<BytesText<'de>, DeError> {
+        let (trim_start, trim_end) = self.reader.reader().trims_text();
+        let drop_whitespaces = self.reader.reader().drops_whitespace_only_text();
+        self.reader.reader_mut().trim_text(false);
+        let pieces = self.read_text_pieces(name);
+        self.reader
+            .reader_mut()
+            .trim_text(trim_start)
+            .trim_text_end(trim_end)
+            .drop_whitespace_only_text(drop_whitespaces);
+        let mut pieces = pieces?;
+
+        if drop_whitespaces {
+            let is_formatting =
+                |piece: &TextPiece| !piece.cdata && piece.text.iter().all(|&b| is_whitespace(b));
+            while matches!(pieces.last(), Some(piece) if is_formatting(piece)) {
+                pieces.pop();
+            }
+            let leading = pieces
+                .iter()
+                .take_while(|piece| is_formatting(piece))
+                .count();
+            pieces.drain(..leading);
+        }
+
+        if trim_start {
+            for piece in pieces.iter_mut() {
+                if piece.cdata {
+                    break;
+                }
+                let content = &piece.text[piece.range.clone()];
+                piece.range.start += content
+                    .iter()
+                    .position(|&b| !is_whitespace(b))
+                    .unwrap_or(content.len());
+                if !piece.range.is_empty() {
+                    break;
+                }
+            }
+        }
+        if trim_end {
+            for piece in pieces.iter_mut().rev() {
+                if piece.cdata {
+                    break;
+                }
+                let content = &piece.text[piece.range.clone()];
+                piece.range.end = piece.range.start
+                    + content
+                        .iter()
+                        .rposition(|&b| !is_whitespace(b))
+                        .map_or(0, |p| p + 1);
+                if !piece.range.is_empty() {
+                    break;
+                }
+            }
+        }
+        pieces.retain(|piece| !piece.range.is_empty());
+
+        // Keep the position of the text for unescaping errors
+        if let Some(first) = pieces.first() {
+            self.position = first.position + first.range.start;
+        }
+        match pieces.len() {
+            0 => Ok(BytesText::from_escaped(&[] as &[u8])),
+            1 if pieces[0].range == (0..pieces[0].text.len()) => Ok(pieces.remove(0).text),
+            _ => {
+                let mut content = Vec::new();
+                for piece in &pieces {
+                    let text = &piece.text[piece.range.clone()];
+                    // Report unescaping errors here, as positions in the concatenated
+                    // content do not correspond to positions in the document
+                    if !piece.cdata {
+                        self.position = piece.position + piece.range.start;
+                        self.unescape(text)?;
+                    }
+                    content.extend_from_slice(text);
+                }
+                Ok(BytesText::from_escaped(content))
+            }
+        }
+    }
+
+    /// Reads texts and CDATA sections up to the end of the element `name` without trimming
+    fn read_text_pieces(&mut self, name: QName) -> Result<Vec<TextPiece<'de>>, DeError> {
+        let mut pieces = Vec::new();
+        loop {
+            let (text, cdata) = match self.next()? {
+                Event::Text(t) => (t, false),
+                Event::CData(t) => (t, true),
+                Event::End(ref end) if end.name() == name => return Ok(pieces),
+                Event::Start(_) if pieces.is_empty() => return Err(DeError::Start),
+                Event::Start(e) => {
+                    self.skip_to_end(e.name())?;
+                    self.skip_to_end(name)?;
+                    return Ok(pieces);
+                }
+                Event::End(_) => return Err(DeError::End),
+                Event::Eof => return Err(DeError::Eof),
+                _ => unreachable!(),
+            };
+            pieces.push(TextPiece {
+                range: 0..text.len(),
+                text,
+                cdata,
+                position: self.position,
+            });
+        }
+    }
+
+    fn read_to_end(&mut self, name: QName) -> Result<(), DeError> {
+        match self.next()? {
+            Event::Start(e) => self.skip_to_end(e.name())?,
             Event::End(e) if e.name() == name => return Ok(()),
-            _ => buf.clear(),
+            _ => {}
+        }
+        self.skip_to_end(name)
+    }
+
+    /// Reads the events up to the end tag of the element `name` with the reader, skipping
+    /// the nested elements
+    fn skip_to_end(&mut self, name: QName) -> Result<(), DeError> {
+        self.reader
+            .reader_mut()
+            .read_to_end(name, &mut Vec::new())?;
+        if let Some(depth) = self.depth.as_mut() {
+            *depth = depth.saturating_sub(1);
         }
-        Ok(self.reader.read_to_end(name, &mut buf)?)
+        Ok(())
+    }
+}
+
+/// A text or a CDATA section of an element, read by `Deserializer::read_text_pieces`
+struct TextPiece<'a> {
+    text: BytesText<'a>,
+    /// The part of the `text`, which is kept after trimming
+    range: Range<usize>,
+    cdata: bool,
+    /// The position of the `text` in the document
+    position: usize,
+}
+
+/// Removes whitespaces at the start and at the end of the `text`. Whitespaces around
+/// scalar values, like numbers, are not a part of the value, even if texts are not trimmed
+pub(crate) fn trim_whitespaces(text: &[u8]) -> &[u8] {
+    let start = text.iter().position(|&b| !is_whitespace(b));
+    let end = text.iter().rposition(|&b| !is_whitespace(b));
+    match (start, end) {
+        (Some(start), Some(end)) => &text[start..=end],
+        _ => &[],
     }
 }
 
@@ -255,17 +1197,25 @@ macro_rules! deserialize_type {
             let txt = self.next_text()?;
 
             #[cfg(not(feature = "encoding"))]
-            let value = self.reader.decode(&*txt)?.parse()?;
+            let value = self
+                .reader
+                .reader()
+                .decode(trim_whitespaces(&txt))?
+                .parse()?;
 
             #[cfg(feature = "encoding")]
-            let value = self.reader.decode(&*txt).parse()?;
+            let value = self
+                .reader
+                .reader()
+                .decode(trim_whitespaces(&txt))
+                .parse()?;
 
             visitor.$visit(value)
         }
     };
 }
 
-impl<'de, 'a, R: BufRead> de::Deserializer<'de> for &'a mut Deserializer<R> {
+impl<'de, 'a, R: XmlRead<'de>> de::Deserializer<'de> for &'a mut Deserializer<'de, R> {
     type Error = DeError;
 
     fn deserialize_struct<V: de::Visitor<'de>>(
@@ -274,15 +1224,29 @@ impl<'de, 'a, R: BufRead> de::Deserializer<'de> for &'a mut Deserializer<R> {
         fields: &'static [&'static str],
         visitor: V,
     ) -> Result<V::Value, DeError> {
-        if let Some(e) = self.next_start(&mut Vec::new())? {
+        if let Some(e) = self.next_start()? {
             let name = e.name().to_vec();
             self.has_value_field = fields.contains(&INNER_VALUE);
             self.has_unflatten_field = fields.iter().any(|elem| elem.starts_with(UNFLATTEN_PREFIX));
-            let map = map::MapAccess::new(self, e)?;
-            let value = visitor.visit_map(map)?;
+            self.path.push(String::from_utf8_lossy(&name).into_owned());
+            let name_field = fields.contains(&ELEMENT_NAME);
+            let result = map::MapAccess::new(self, e, name_field, fields)
+                .and_then(|map| visitor.visit_map(map));
+            let result = match result {
+                // Only the innermost struct knows where the field was expected
+                Err(DeError::MissingField { field, ref path }) if path.is_empty() => {
+                    Err(DeError::MissingField {
+                        field,
+                        path: self.path.join("/"),
+                    })
+                }
+                result => result,
+            };
+            self.path.pop();
+            let value = result?;
             self.has_value_field = false;
             self.has_unflatten_field = false;
-            self.read_to_end(&name)?;
+            self.read_to_end(QName(&name))?;
             Ok(value)
         } else {
             Err(DeError::Start)
@@ -311,7 +1275,7 @@ impl<'de, 'a, R: BufRead> de::Deserializer<'de> for &'a mut Deserializer<R> {
         #[cfg(feature = "encoding")]
         {
             #[cfg(feature = "encoding")]
-            let value = self.reader.decode(&*txt);
+            let value = self.reader.reader().decode(trim_whitespaces(&txt));
 
             match value.as_ref() {
                 "true" | "1" | "True" | "TRUE" | "t" | "Yes" | "YES" | "yes" | "y" => {
@@ -326,21 +1290,26 @@ impl<'de, 'a, R: BufRead> de::Deserializer<'de> for &'a mut Deserializer<R> {
 
         #[cfg(not(feature = "encoding"))]
         {
-            match txt.as_ref() {
+            match trim_whitespaces(&txt) {
                 b"true" | b"1" | b"True" | b"TRUE" | b"t" | b"Yes" | b"YES" | b"yes" | b"y" => {
                     visitor.visit_bool(true)
                 }
                 b"false" | b"0" | b"False" | b"FALSE" | b"f" | b"No" | b"NO" | b"no" | b"n" => {
                     visitor.visit_bool(false)
                 }
-                e => Err(DeError::InvalidBoolean(self.reader.decode(e)?.into())),
+                e => Err(DeError::InvalidBoolean(
+                    self.reader.reader().decode(e)?.into(),
+                )),
             }
         }
     }
 
     fn deserialize_string<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
-        let value = self.next_text()?.unescape_and_decode(&self.reader)?;
-        visitor.visit_string(value)
+        let text = self.next_text()?.into_inner();
+        match self.decode_text(text)? {
+            Cow::Borrowed(value) => visitor.visit_borrowed_str(value),
+            Cow::Owned(value) => visitor.visit_string(value),
+        }
     }
 
     fn deserialize_char<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
@@ -352,9 +1321,10 @@ impl<'de, 'a, R: BufRead> de::Deserializer<'de> for &'a mut Deserializer<R> {
     }
 
     fn deserialize_bytes<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
-        let text = self.next_text()?;
-        let value = text.escaped();
-        visitor.visit_bytes(value)
+        match self.next_text()?.into_inner() {
+            Cow::Borrowed(value) => visitor.visit_borrowed_bytes(value),
+            Cow::Owned(value) => visitor.visit_bytes(&value),
+        }
     }
 
     fn deserialize_byte_buf<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
@@ -364,8 +1334,7 @@ impl<'de, 'a, R: BufRead> de::Deserializer<'de> for &'a mut Deserializer<R> {
     }
 
     fn deserialize_unit<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
-        let mut buf = Vec::new();
-        match self.next(&mut buf)? {
+        match self.next()? {
             Event::Start(s) => {
                 self.read_to_end(s.name())?;
                 visitor.visit_unit()
@@ -410,9 +1379,37 @@ impl<'de, 'a, R: BufRead> de::Deserializer<'de> for &'a mut Deserializer<R> {
     fn deserialize_enum<V: de::Visitor<'de>>(
         self,
         _name: &'static str,
-        _variants: &'static [&'static str],
+        variants: &'static [&'static str],
         visitor: V,
     ) -> Result<V::Value, DeError> {
+        let decoder = self.reader.reader().decoder();
+        let wrapper = match self.peek()? {
+            Some(Event::Start(e)) => {
+                #[cfg(not(feature = "encoding"))]
+                let name = decoder.decode(e.name().into_inner())?;
+                #[cfg(feature = "encoding")]
+                let name = decoder.decode(e.name().into_inner());
+                if variants.contains(&&*name) {
+                    None
+                } else {
+                    Some((name.to_string(), e.name().to_vec()))
+                }
+            }
+            _ => None,
+        };
+        // An element, which is not a variant itself, but contains one (a choice group):
+        // <shape><circle r="1"/></shape>
+        // or the name of a unit variant as a text: <status>active</status>
+        if let Some((name, tag)) = wrapper {
+            self.next()?;
+            match self.peek()? {
+                Some(Event::Start(_)) | Some(Event::Text(_)) | Some(Event::CData(_)) => {}
+                _ => return Err(de::Error::unknown_variant(&name, variants)),
+            }
+            let value = visitor.visit_enum(var::EnumAccess::new(self))?;
+            self.read_to_end(QName(&tag))?;
+            return Ok(value);
+        }
         let value = visitor.visit_enum(var::EnumAccess::new(self))?;
         Ok(value)
     }
@@ -438,7 +1435,7 @@ impl<'de, 'a, R: BufRead> de::Deserializer<'de> for &'a mut Deserializer<R> {
     }
 
     fn deserialize_ignored_any<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
-        match self.next(&mut Vec::new())? {
+        match self.next()? {
             Event::Start(e) => self.read_to_end(e.name())?,
             Event::End(_) => return Err(DeError::End),
             _ => (),
@@ -455,6 +1452,103 @@ impl<'de, 'a, R: BufRead> de::Deserializer<'de> for &'a mut Deserializer<R> {
     }
 }
 
+/// A source of events for the [`Deserializer`]: [`IoReader`] copies events from any
+/// buffered input, and [`SliceReader`] borrows them from a slice, so deserialized strings
+/// can borrow the input too.
+///
+/// [`Deserializer`]: struct.Deserializer.html
+/// [`IoReader`]: struct.IoReader.html
+/// [`SliceReader`]: struct.SliceReader.html
+pub trait XmlRead<'de> {
+    /// The input of the reader
+    type Input: BufRead;
+
+    /// Reads the next event. The event is returned with the raw bytes, from which it
+    /// has been read, or the rest of the input, starting at the same position
+    fn next(&mut self) -> Result<(Event<'de>, &[u8]), Error>;
+
+    /// Returns the reader of the events
+    fn reader(&self) -> &Reader<Self::Input>;
+
+    /// Returns the reader of the events to change its settings
+    fn reader_mut(&mut self) -> &mut Reader<Self::Input>;
+
+    /// Consumes this source and returns the reader of the events
+    fn into_reader(self) -> Reader<Self::Input>;
+}
+
+/// A source of events, read from a buffered input and copied, created by
+/// [`Deserializer::from_reader`]
+///
+/// [`Deserializer::from_reader`]: struct.Deserializer.html#method.from_reader
+pub struct IoReader<R: BufRead> {
+    reader: Reader<R>,
+    buf: Vec<u8>,
+}
+
+impl<R: BufRead> IoReader<R> {
+    fn new(reader: Reader<R>) -> Self {
+        IoReader {
+            reader,
+            buf: Vec::new(),
+        }
+    }
+}
+
+impl<'de, R: BufRead> XmlRead<'de> for IoReader<R> {
+    type Input = R;
+
+    fn next(&mut self) -> Result<(Event<'de>, &[u8]), Error> {
+        self.buf.clear();
+        let event = self.reader.read_event(&mut self.buf)?.into_owned();
+        Ok((event, &self.buf))
+    }
+
+    fn reader(&self) -> &Reader<R> {
+        &self.reader
+    }
+
+    fn reader_mut(&mut self) -> &mut Reader<R> {
+        &mut self.reader
+    }
+
+    fn into_reader(self) -> Reader<R> {
+        self.reader
+    }
+}
+
+/// A source of events, which borrow the input slice, created by
+/// [`Deserializer::from_str`]
+///
+/// [`Deserializer::from_str`]: struct.Deserializer.html#method.from_str
+pub struct SliceReader<'de> {
+    reader: Reader<&'de [u8]>,
+}
+
+impl<'de> XmlRead<'de> for SliceReader<'de> {
+    type Input = &'de [u8];
+
+    fn next(&mut self) -> Result<(Event<'de>, &[u8]), Error> {
+        // Transcoded input is not available, so positions of errors in trimmed texts of
+        // such input do not count the trimmed whitespaces
+        let input = self.reader.remaining().unwrap_or(&[]);
+        let event = self.reader.read_event_unbuffered()?;
+        Ok((event, input))
+    }
+
+    fn reader(&self) -> &Reader<&'de [u8]> {
+        &self.reader
+    }
+
+    fn reader_mut(&mut self) -> &mut Reader<&'de [u8]> {
+        &mut self.reader
+    }
+
+    fn into_reader(self) -> Reader<&'de [u8]> {
+        self.reader
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -484,26 +1578,172 @@ mod tests {
     }
 
     #[test]
-    fn multiple_roots_attributes() {
-        let s = r##"
-	    <item name="hello" source="world.rs" />
-	    <item name="hello" source="world.rs" />
-	"##;
-
-        let item: Vec<Item> = from_str(s).unwrap();
-
+    fn normalized_attributes() {
+        let item: Item =
+            from_str("<item name='hello\r\n\tworld' source='a&#x20;&#9;&lt;b'/>").unwrap();
         assert_eq!(
             item,
-            vec![
-                Item {
-                    name: "hello".to_string(),
-                    source: "world.rs".to_string(),
-                },
-                Item {
-                    name: "hello".to_string(),
-                    source: "world.rs".to_string(),
-                },
-            ]
+            Item {
+                name: "hello  world".to_string(),
+                source: "a \t<b".to_string(),
+            }
+        );
+
+        // Raw whitespaces are normalized to spaces, but character references are kept
+        let raw: Item = from_str("<item name='a\nb' source='a\n\rb'/>").unwrap();
+        let escaped: Item = from_str("<item name='a&#x20;b' source='a&#32;&#x20;b'/>").unwrap();
+        assert_eq!(raw, escaped);
+        let escaped: Item = from_str("<item name='a&#xA;b' source='a&#10;b'/>").unwrap();
+        assert_eq!(escaped.name, "a\nb");
+        assert_eq!(escaped.source, "a\nb");
+    }
+
+    #[test]
+    fn valueless_attributes() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Option {
+            selected: bool,
+            disabled: std::option::Option<String>,
+            label: String,
+            value: String,
+            id: String,
+            #[serde(default)]
+            hidden: bool,
+        }
+
+        let xml = "<option selected value='a b' disabled id=x label></option>";
+        let mut reader = Reader::from_str(xml);
+        reader
+            .expand_empty_elements(true)
+            .trim_text(true)
+            .lenient_attribute_values(true);
+        let mut de = Deserializer::new(reader);
+        let option = Option::deserialize(&mut de).unwrap();
+        assert_eq!(
+            option,
+            Option {
+                selected: true,
+                disabled: Some(String::new()),
+                label: String::new(),
+                value: "a b".to_string(),
+                id: "x".to_string(),
+                hidden: false,
+            }
+        );
+
+        // Attributes without values are errors in the strict mode
+        let mut reader = Reader::from_str("<option selected value='a'></option>");
+        reader.expand_empty_elements(true).trim_text(true);
+        let mut de = Deserializer::new(reader);
+        match Option::deserialize(&mut de) {
+            Err(DeError::Xml(Error::NoEqAfterName(_))) => {}
+            e => panic!("Expected `NoEqAfterName`, found {:?}", e),
+        }
+    }
+
+    /// A custom deserialization module, which reads a date in the `YYYY-MM-DD` format
+    mod date {
+        use serde::de::{self, Deserializer, Visitor};
+        use std::fmt;
+
+        pub fn deserialize<'de, D>(deserializer: D) -> Result<(u16, u8, u8), D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            struct DateVisitor;
+
+            impl<'de> Visitor<'de> for DateVisitor {
+                type Value = (u16, u8, u8);
+
+                fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                    f.write_str("a date in the YYYY-MM-DD format")
+                }
+
+                fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                    let mut parts = v.trim().splitn(3, '-');
+                    let mut next = || parts.next().ok_or_else(|| E::custom("missing date part"));
+                    let y = next()?.parse().map_err(E::custom)?;
+                    let m = next()?.parse().map_err(E::custom)?;
+                    let d = next()?.parse().map_err(E::custom)?;
+                    Ok((y, m, d))
+                }
+            }
+
+            deserializer.deserialize_str(DateVisitor)
+        }
+    }
+
+    #[test]
+    fn custom_module_for_attribute() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Event {
+            #[serde(with = "date")]
+            date: (u16, u8, u8),
+            #[serde(with = "date")]
+            deadline: (u16, u8, u8),
+        }
+
+        let event: Event =
+            from_str("<event date='2021-06-02'><deadline>2021-07-01</deadline></event>").unwrap();
+        assert_eq!(
+            event,
+            Event {
+                date: (2021, 6, 2),
+                deadline: (2021, 7, 1),
+            }
+        );
+
+        // Attribute values are unescaped and normalized before passing them to the module
+        let event: Event =
+            from_str("<event date='&#50;021-06-02&#x0A;' deadline='\t2021-07-01'/>").unwrap();
+        assert_eq!(
+            event,
+            Event {
+                date: (2021, 6, 2),
+                deadline: (2021, 7, 1),
+            }
+        );
+    }
+
+    #[test]
+    fn escaped_scalar_attributes() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Scalars {
+            number: u32,
+            flag: bool,
+        }
+
+        let scalars: Scalars = from_str("<scalars number='&#52;2' flag='&#x74;rue'/>").unwrap();
+        assert_eq!(
+            scalars,
+            Scalars {
+                number: 42,
+                flag: true,
+            }
+        );
+    }
+
+    #[test]
+    fn multiple_roots_attributes() {
+        let s = r##"
+	    <item name="hello" source="world.rs" />
+	    <item name="hello" source="world.rs" />
+	"##;
+
+        let item: Vec<Item> = from_str(s).unwrap();
+
+        assert_eq!(
+            item,
+            vec![
+                Item {
+                    name: "hello".to_string(),
+                    source: "world.rs".to_string(),
+                },
+                Item {
+                    name: "hello".to_string(),
+                    source: "world.rs".to_string(),
+                },
+            ]
         );
     }
 
@@ -730,6 +1970,77 @@ mod tests {
         assert_eq!(data, Tuple(42.0, "answer".into()));
     }
 
+    /// Repeated elements with a scalar content deserialized as a sequence of their texts
+    mod seq_of_scalars {
+        use super::*;
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Strings {
+            tag: Vec<String>,
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Integers {
+            tag: Vec<i32>,
+            other: String,
+        }
+
+        #[test]
+        fn strings() {
+            let data: Vec<String> = from_str("<tag>a</tag><tag/><tag>&lt;c&gt;</tag>").unwrap();
+            assert_eq!(data, vec!["a", "", "<c>"]);
+        }
+
+        #[test]
+        fn integers() {
+            let data: Vec<i32> = from_str("<tag>1</tag><tag>-2</tag><tag> 3 </tag>").unwrap();
+            assert_eq!(data, vec![1, -2, 3]);
+        }
+
+        #[test]
+        fn strings_field() {
+            let data: Strings =
+                from_str(r#"<root><tag id="1">a</tag><tag><![CDATA[b]]></tag></root>"#).unwrap();
+            assert_eq!(
+                data,
+                Strings {
+                    tag: vec!["a".into(), "b".into()]
+                }
+            );
+        }
+
+        #[test]
+        fn integers_field() {
+            let data: Integers =
+                from_str("<root><tag>1</tag><tag>2</tag><other>x</other></root>").unwrap();
+            assert_eq!(
+                data,
+                Integers {
+                    tag: vec![1, 2],
+                    other: "x".into(),
+                }
+            );
+        }
+
+        /// Text between the elements is not an item of the sequence
+        #[test]
+        fn text_between_items() {
+            let data: Vec<i32> = from_str("<tag>1</tag><tag>2</tag>text<tag>3</tag>").unwrap();
+            assert_eq!(data, vec![1, 2, 3]);
+
+            let data: Integers =
+                from_str("<root><tag>1</tag>text<tag>2</tag><![CDATA[x]]><other>x</other></root>")
+                    .unwrap();
+            assert_eq!(
+                data,
+                Integers {
+                    tag: vec![1, 2],
+                    other: "x".into(),
+                }
+            );
+        }
+    }
+
     mod struct_ {
         use super::*;
 
@@ -808,6 +2119,788 @@ mod tests {
         }
     }
 
+    mod missing_field {
+        use super::*;
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Root {
+            item: Item,
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Item {
+            #[serde(rename = "$attr:id")]
+            id: u32,
+            #[serde(rename = "$unflatten=name")]
+            name: String,
+        }
+
+        fn message(xml: &str) -> String {
+            match from_str::<Root>(xml) {
+                Err(e @ DeError::MissingField { .. }) => e.to_string(),
+                x => panic!("Expected `Err(MissingField {{ .. }})`, but got `{:?}`", x),
+            }
+        }
+
+        #[test]
+        fn attribute() {
+            assert_eq!(
+                message("<root><item><name>n</name></item></root>"),
+                "missing attribute `id` in `root/item`"
+            );
+        }
+
+        #[test]
+        fn element() {
+            assert_eq!(
+                message(r#"<root><item id="1"/></root>"#),
+                "missing element `name` in `root/item`"
+            );
+        }
+
+        #[test]
+        fn outer() {
+            assert_eq!(message("<root/>"), "missing element `item` in `root`");
+        }
+
+        /// `$attr:` fields are read only from attributes, other fields from both
+        #[test]
+        fn attribute_only() {
+            #[derive(Debug, Deserialize, PartialEq, serde::Serialize)]
+            struct Mixed {
+                #[serde(rename = "$attr:id")]
+                id: u32,
+                name: String,
+            }
+
+            let mixed = Mixed {
+                id: 1,
+                name: "n".into(),
+            };
+            assert_eq!(from_str::<Mixed>(r#"<m id="1" name="n"/>"#).unwrap(), mixed);
+            assert_eq!(
+                from_str::<Mixed>(r#"<m id="1"><name>n</name></m>"#).unwrap(),
+                mixed
+            );
+            match from_str::<Mixed>(r#"<m name="n"><id>1</id></m>"#) {
+                Err(e @ DeError::MissingField { .. }) => {
+                    assert_eq!(e.to_string(), "missing attribute `id` in `m`")
+                }
+                x => panic!("Expected `Err(MissingField {{ .. }})`, but got `{:?}`", x),
+            }
+            assert_eq!(
+                crate::se::to_string(&mixed).unwrap(),
+                r#"<Mixed id="1" name="n"/>"#
+            );
+        }
+    }
+
+    mod invalid_field {
+        use super::*;
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Root {
+            item: Item,
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Item {
+            id: u32,
+            #[serde(rename = "$unflatten=flag")]
+            flag: bool,
+        }
+
+        fn error(xml: &str) -> (String, usize, String) {
+            match from_str::<Root>(xml) {
+                Err(e) => match e {
+                    DeError::InvalidField {
+                        ref field,
+                        position,
+                        ..
+                    } => (field.clone(), position, e.to_string()),
+                    e => panic!("Expected `InvalidField {{ .. }}`, but got `{:?}`", e),
+                },
+                x => panic!("Expected `Err(InvalidField {{ .. }})`, but got `{:?}`", x),
+            }
+        }
+
+        #[test]
+        fn attribute() {
+            let xml = r#"<root><item id="abc"><flag>true</flag></item></root>"#;
+            let (field, position, message) = error(xml);
+            assert_eq!(field, "id");
+            assert_eq!(position, xml.find("abc").unwrap());
+            assert_eq!(
+                message,
+                "invalid value of `id` at position 16: invalid digit found in string"
+            );
+        }
+
+        #[test]
+        fn element() {
+            let xml = r#"<root><item id="1"><flag>maybe</flag></item></root>"#;
+            let (field, position, message) = error(xml);
+            assert_eq!(field, "flag");
+            assert_eq!(position, xml.find("maybe").unwrap());
+            assert!(message.starts_with("invalid value of `flag` at position 25: "));
+        }
+    }
+
+    #[test]
+    fn custom_entities() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        enum Kind {
+            Sample,
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Item {
+            title: String,
+            text: String,
+            kind: Kind,
+        }
+
+        let mut entities = HashMap::new();
+        entities.insert("name".to_string(), "Sample".to_string());
+        entities.insert("lt".to_string(), "overridden".to_string());
+
+        let xml = "<item title='&name;&#32;&lt;1&gt;'><text>&name;&amp;&lt;</text>\
+                   <kind>&name;</kind></item>";
+        let mut de = Deserializer::from_reader(xml.as_bytes());
+        de.custom_entities(entities);
+        assert_eq!(
+            Item::deserialize(&mut de).unwrap(),
+            Item {
+                title: "Sample <1>".into(),
+                text: "Sample&<".into(),
+                kind: Kind::Sample,
+            }
+        );
+
+        match from_str::<Item>(xml) {
+            Err(DeError::Xml(Error::EscapeError(_))) => {}
+            x => panic!("Expected `Err(Xml(EscapeError(_)))`, but got `{:?}`", x),
+        }
+    }
+
+    #[cfg(feature = "escape-html")]
+    #[test]
+    fn html_entities() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Item {
+            title: String,
+            #[serde(rename = "$value")]
+            text: String,
+        }
+
+        let xml = "<item title='&copy; 2022'>a&nbsp;&mdash;&nbsp;b</item>";
+        let item: Item = from_str(xml).unwrap();
+        assert_eq!(
+            item,
+            Item {
+                title: "\u{A9} 2022".into(),
+                text: "a\u{A0}\u{2014}\u{A0}b".into(),
+            }
+        );
+
+        let mut de = Deserializer::from_reader(xml.as_bytes());
+        de.resolve_html_entities(false);
+        match Item::deserialize(&mut de) {
+            Err(DeError::Xml(Error::EscapeError(_))) => {}
+            x => panic!("Expected `Err(Xml(EscapeError(_)))`, but got `{:?}`", x),
+        }
+    }
+
+    #[test]
+    fn escape_error_positions() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Item {
+            title: String,
+            text: String,
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Value {
+            #[serde(rename = "$value")]
+            text: String,
+        }
+
+        fn error_at<T: DeserializeOwned + std::fmt::Debug>(xml: &str) -> &str {
+            match from_str::<T>(xml) {
+                Err(DeError::Xml(Error::EscapeError(e))) => &xml[e.range()],
+                x => panic!("Expected `Err(Xml(EscapeError(_)))`, but got `{:?}`", x),
+            }
+        }
+
+        assert_eq!(
+            error_at::<Item>("<item title='a &bad; b'><text/></item>"),
+            "&bad;"
+        );
+        assert_eq!(
+            error_at::<Item>("<item title='&amp;' other=\"&amp;\" text='&#xZ;'/>"),
+            "&#xZ;"
+        );
+        assert_eq!(
+            error_at::<Item>("<item title='a'>\n  <text>\n  x &bad; y\n</text>\n</item>"),
+            "&bad;"
+        );
+        assert_eq!(
+            error_at::<Value>("<item>\n  <!-- &lt; -->\n  &lt;a&gt; &bad</item>"),
+            "&bad"
+        );
+    }
+
+    /// Deserializes `xml` with the reader, which trims texts
+    fn from_trimmed<T: DeserializeOwned>(xml: &str) -> Result<T, DeError> {
+        let mut reader = Reader::from_str(xml);
+        reader
+            .expand_empty_elements(true)
+            .check_end_names(true)
+            .trim_text(true);
+        T::deserialize(&mut Deserializer::new(reader))
+    }
+
+    #[test]
+    fn cdata_is_not_trimmed() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Item {
+            x: String,
+        }
+
+        assert_eq!(
+            from_trimmed::<String>("<x> <![CDATA[  keep me  ]]> </x>").unwrap(),
+            "  keep me  "
+        );
+        assert_eq!(
+            from_trimmed::<Item>("<item><x> <![CDATA[  keep me  ]]> </x></item>").unwrap(),
+            Item {
+                x: "  keep me  ".into()
+            }
+        );
+        // Whitespaces between texts and CDATA sections are kept
+        assert_eq!(
+            from_trimmed::<String>("<x>\n  a &lt; <![CDATA[<b>]]> <!-- c --> d\n</x>").unwrap(),
+            "a < <b>  d"
+        );
+        assert_eq!(
+            from_trimmed::<String>("<x><![CDATA[ a ]]><![CDATA[ b ]]></x>").unwrap(),
+            " a  b "
+        );
+        // Texts of the nested elements are not included
+        assert_eq!(
+            from_trimmed::<String>("<x> text <nested>skipped</nested> skipped </x>").unwrap(),
+            "text"
+        );
+        assert_eq!(
+            from_trimmed::<Item>("<item><x>  text  </x></item>").unwrap(),
+            Item { x: "text".into() }
+        );
+    }
+
+    /// By default only whitespace-only texts are dropped, so whitespaces in mixed content
+    /// are kept, but scalar values are still read without surrounding whitespaces
+    #[test]
+    fn whitespaces_in_mixed_text() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Doc {
+            p: String,
+            count: u32,
+        }
+
+        let doc: Doc =
+            from_str("<doc>\n  <p>hello <b>world</b></p>\n  <count> 2 </count>\n</doc>").unwrap();
+        assert_eq!(
+            doc,
+            Doc {
+                p: "hello ".into(),
+                count: 2,
+            }
+        );
+        assert_eq!(from_str::<String>("<p>  </p>").unwrap(), "");
+    }
+
+    #[test]
+    fn escape_error_in_mixed_content() {
+        match from_str::<String>("<x>  <![CDATA[&]]> &bad;</x>") {
+            Err(DeError::Xml(Error::EscapeError(e))) => assert_eq!(e.range(), 19..24),
+            x => panic!("Expected `Err(Xml(EscapeError(_)))`, but got `{:?}`", x),
+        }
+    }
+
+    #[test]
+    fn rename_fields() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Address {
+            street_name: String,
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct User {
+            user_id: u32,
+            display_name: String,
+            home_address: Address,
+            #[serde(rename = "e_mail")]
+            email: String,
+        }
+
+        let expected = User {
+            user_id: 1,
+            display_name: "Alice".into(),
+            home_address: Address {
+                street_name: "Main".into(),
+            },
+            email: "alice@example.com".into(),
+        };
+
+        let xml = "<user user-id='1'><display-name>Alice</display-name>\
+                   <home-address><street-name>Main</street-name></home-address>\
+                   <e-mail>alice@example.com</e-mail></user>";
+        let mut de = Deserializer::from_reader(xml.as_bytes());
+        de.rename_fields(Convention::KebabToSnake);
+        assert_eq!(User::deserialize(&mut de).unwrap(), expected);
+
+        let xml = "<User UserId='1'><displayName>Alice</displayName>\
+                   <homeAddress><StreetName>Main</StreetName></homeAddress>\
+                   <eMail>alice@example.com</eMail></User>";
+        let mut de = Deserializer::from_reader(xml.as_bytes());
+        de.rename_fields(Convention::CamelToSnake);
+        assert_eq!(User::deserialize(&mut de).unwrap(), expected);
+
+        // Without a convention names are not converted
+        match from_str::<User>(xml) {
+            Err(DeError::MissingField { field, .. }) => assert_eq!(field, "user_id"),
+            x => panic!("Expected `Err(MissingField {{ .. }})`, but got `{:?}`", x),
+        }
+    }
+
+    #[test]
+    fn strip_namespace_prefix() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Item {
+            #[serde(rename = "type")]
+            type_: String,
+            item_id: u32,
+            #[serde(rename = "other:lang")]
+            lang: String,
+            name: String,
+        }
+
+        let xml = "<x:item xmlns:x='urn:x' x:type='book' x:item-id='2' other:lang='en'>\
+                   <x:name>Book</x:name></x:item>";
+        let mut de = Deserializer::from_reader(xml.as_bytes());
+        de.strip_namespace_prefix("x")
+            .rename_fields(Convention::KebabToSnake);
+        assert_eq!(
+            Item::deserialize(&mut de).unwrap(),
+            Item {
+                type_: "book".into(),
+                item_id: 2,
+                lang: "en".into(),
+                name: "Book".into(),
+            }
+        );
+    }
+
+    #[test]
+    fn element_name() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Shape {
+            #[serde(rename = "$name")]
+            kind: String,
+            size: u32,
+            color: Option<String>,
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Drawing {
+            #[serde(rename = "$value")]
+            shapes: Vec<Shape>,
+        }
+
+        assert_eq!(
+            from_str::<Shape>(r#"<circle size="3"/>"#).unwrap(),
+            Shape {
+                kind: "circle".into(),
+                size: 3,
+                color: None,
+            }
+        );
+        assert_eq!(
+            from_str::<Shape>(r#"<svg:square size="4"><color>red</color></svg:square>"#).unwrap(),
+            Shape {
+                kind: "square".into(),
+                size: 4,
+                color: Some("red".into()),
+            }
+        );
+        assert_eq!(
+            from_str::<Drawing>(
+                r#"<drawing><circle size="1"/><square size="2"/><circle size="3"/></drawing>"#
+            )
+            .unwrap(),
+            Drawing {
+                shapes: vec![
+                    Shape {
+                        kind: "circle".into(),
+                        size: 1,
+                        color: None,
+                    },
+                    Shape {
+                        kind: "square".into(),
+                        size: 2,
+                        color: None,
+                    },
+                    Shape {
+                        kind: "circle".into(),
+                        size: 3,
+                        color: None,
+                    },
+                ],
+            }
+        );
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Record {
+        id: u64,
+        name: String,
+    }
+
+    #[test]
+    fn iter_elements() {
+        let xml = r#"<?xml version="1.0"?>
+            <feed>
+                <title>Records</title>
+                <records>
+                    <record id="1"><name>first</name></record>
+                    text between
+                    <r:record id="2"><name>second</name></r:record>
+                    <record id="3"><name>third</name></record>
+                </records>
+            </feed>"#;
+        let records: Vec<Record> = iter_from_reader(xml.as_bytes(), "record")
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(
+            records,
+            vec![
+                Record {
+                    id: 1,
+                    name: "first".into(),
+                },
+                Record {
+                    id: 2,
+                    name: "second".into(),
+                },
+                Record {
+                    id: 3,
+                    name: "third".into(),
+                },
+            ]
+        );
+
+        let mut iter = iter_from_reader::<_, Record>("<records/>".as_bytes(), "record");
+        assert!(iter.next().is_none());
+
+        // The iterator ends after an error
+        let xml =
+            r#"<records><record id="x"><name/></record><record id="2"><name/></record></records>"#;
+        let mut iter = iter_from_reader::<_, Record>(xml.as_bytes(), "record");
+        assert!(iter.next().unwrap().is_err());
+        assert!(iter.next().is_none());
+
+        let mut iter = iter_from_reader::<_, Record>("<records><record>".as_bytes(), "record");
+        assert!(iter.next().unwrap().is_err());
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    fn seq_dispatch() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct A {
+            id: u32,
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct B {
+            #[serde(rename = "$value")]
+            text: String,
+        }
+
+        #[derive(Debug, PartialEq)]
+        enum Node {
+            A(A),
+            B(B),
+        }
+
+        const DISPATCH: &[(&str, DispatchFn<&[u8], Node>)] = &[
+            ("a", |de| A::deserialize(de).map(Node::A)),
+            ("b", |de| B::deserialize(de).map(Node::B)),
+        ];
+
+        let xml = r#"<root>
+                <a id="1"/>
+                <c><b>nested</b></c>
+                text between
+                <x:b>second</x:b>
+                <a id="2"></a>
+            </root>"#;
+        let nodes: Vec<Node> = from_reader_seq_dispatch(xml.as_bytes(), DISPATCH)
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(
+            nodes,
+            vec![
+                Node::A(A { id: 1 }),
+                Node::B(B {
+                    text: "nested".into(),
+                }),
+                Node::B(B {
+                    text: "second".into(),
+                }),
+                Node::A(A { id: 2 }),
+            ]
+        );
+
+        let mut iter = from_reader_seq_dispatch("<root/>".as_bytes(), DISPATCH);
+        assert!(iter.next().is_none());
+
+        // The iterator ends after an error
+        let xml = r#"<root><a id="x"/><b>text</b></root>"#;
+        let mut iter = from_reader_seq_dispatch(xml.as_bytes(), DISPATCH);
+        assert!(iter.next().unwrap().is_err());
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    fn from_element() {
+        let xml = r#"<feed>
+                <records>
+                    <record id="1"><name>first</name></record>
+                    <record id="2"><name>second</name></record>
+                    <record id="3"><name>third</name><extra><name>skipped</name></extra></record>
+                    <record id="4"><name>fourth</name></record>
+                    <record id="x"><name>invalid</name></record>
+                    <record id="6"><name>sixth</name></record>
+                </records>
+            </feed>"#;
+
+        /// Reads the start tag of the `n`th next record
+        fn nth_record(reader: &mut Reader<&[u8]>, n: usize) -> BytesStart<'static> {
+            let mut buf = Vec::new();
+            let mut count = 0;
+            loop {
+                match reader.read_event(&mut buf).unwrap() {
+                    Event::Start(e) if e.name().as_ref() == b"record" => {
+                        count += 1;
+                        if count == n {
+                            return e.into_owned();
+                        }
+                        reader.read_to_end(e.name(), &mut Vec::new()).unwrap();
+                    }
+                    Event::Eof => panic!("the record is not found"),
+                    _ => {}
+                }
+                buf.clear();
+            }
+        }
+
+        let mut reader = Reader::from_str(xml);
+        reader.trim_text(true);
+
+        let start = nth_record(&mut reader, 3);
+        let mut de = Deserializer::from_element(reader, &start);
+        let third = Record::deserialize(&mut de).unwrap();
+        assert_eq!(third.name, "third");
+        // Nothing is read after the element
+        assert!(matches!(de.next().unwrap(), Event::Eof));
+
+        let mut reader = de.into_inner();
+        let start = nth_record(&mut reader, 1);
+        let mut de = Deserializer::from_element(reader, &start);
+        let fourth = Record::deserialize(&mut de).unwrap();
+        assert_eq!(
+            fourth,
+            Record {
+                id: 4,
+                name: "fourth".into(),
+            }
+        );
+
+        // Errors don't consume the events after the element
+        let mut reader = de.into_inner();
+        let start = nth_record(&mut reader, 1);
+        let mut de = Deserializer::from_element(reader, &start);
+        assert!(Record::deserialize(&mut de).is_err());
+        let mut reader = de.into_inner();
+        reader.read_to_end(b"record", &mut Vec::new()).unwrap();
+        let start = nth_record(&mut reader, 1);
+        let mut de = Deserializer::from_element(reader, &start);
+        assert_eq!(Record::deserialize(&mut de).unwrap().id, 6);
+
+        // Sequences end at the end of the element
+        let mut reader = Reader::from_str("<a><b>1</b><b>2</b></a><b>3</b>");
+        reader.trim_text(true);
+        reader.read_event(&mut Vec::new()).unwrap();
+        let start = BytesStart::borrowed_name(b"a");
+        let mut de = Deserializer::from_element(reader, &start);
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct A {
+            b: Vec<u32>,
+        }
+        assert_eq!(A::deserialize(&mut de).unwrap(), A { b: vec![1, 2] });
+        let mut reader = de.into_inner();
+        match reader.read_event(&mut Vec::new()).unwrap() {
+            Event::Start(e) => assert_eq!(e.name().as_ref(), b"b"),
+            e => panic!("Expected `Start`, found {:?}", e),
+        }
+
+        // Empty elements, read without expanding them
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Id {
+            id: u32,
+        }
+        let mut reader =
+            Reader::from_str(r#"<r><record id="1"/><record id="2"><x/></record></r><tail/>"#);
+        reader.trim_text(true);
+        let mut buf = Vec::new();
+        reader.read_event(&mut buf).unwrap();
+        let start = match reader.read_event(&mut buf).unwrap() {
+            Event::Empty(e) => e.into_owned(),
+            e => panic!("Expected `Empty`, found {:?}", e),
+        };
+        let mut de = Deserializer::from_empty_element(reader, &start);
+        assert_eq!(Id::deserialize(&mut de).unwrap(), Id { id: 1 });
+        assert!(matches!(de.next().unwrap(), Event::Eof));
+        let mut reader = de.into_inner();
+        let start = match reader.read_event(&mut buf).unwrap() {
+            Event::Start(e) => e.into_owned(),
+            e => panic!("Expected `Start`, found {:?}", e),
+        };
+        let mut de = Deserializer::from_element(reader, &start);
+        assert_eq!(Id::deserialize(&mut de).unwrap(), Id { id: 2 });
+        let mut reader = de.into_inner();
+        assert!(matches!(
+            reader.read_event(&mut buf).unwrap(),
+            Event::End(_)
+        ));
+        match reader.read_event(&mut buf).unwrap() {
+            Event::Start(e) => assert_eq!(e.name().as_ref(), b"tail"),
+            e => panic!("Expected `Start`, found {:?}", e),
+        }
+    }
+
+    #[test]
+    fn borrowed_strings() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Item<'a> {
+            id: &'a str,
+            name: &'a str,
+            #[serde(borrow)]
+            note: Cow<'a, str>,
+        }
+
+        let xml = r#"<item id="1"><name>first</name><note>plain</note></item>"#;
+        let item: Item = from_str(xml).unwrap();
+        assert_eq!(item.id, "1");
+        assert_eq!(item.name, "first");
+        assert!(matches!(item.note, Cow::Borrowed("plain")));
+
+        // Escaped values are unescaped into new strings
+        let xml = r#"<item id="1"><name>first</name><note>a &amp; b</note></item>"#;
+        let item: Item = from_str(xml).unwrap();
+        assert!(matches!(item.note, Cow::Owned(ref note) if note == "a & b"));
+
+        // ...so they cannot be deserialized as `&str`
+        let xml = r#"<item id="1"><name>a &amp; b</name><note/></item>"#;
+        match from_str::<Item>(xml) {
+            Err(DeError::InvalidField { source, .. }) => match *source {
+                DeError::Custom(_) => {}
+                e => panic!("Expected `Custom`, found {:?}", e),
+            },
+            e => panic!("Expected `InvalidField`, found {:?}", e),
+        }
+        let xml = r#"<item id="&#49;"><name>first</name><note/></item>"#;
+        match from_str::<Item>(xml) {
+            Err(DeError::InvalidField { source, .. }) => match *source {
+                DeError::Custom(_) => {}
+                e => panic!("Expected `Custom`, found {:?}", e),
+            },
+            e => panic!("Expected `InvalidField`, found {:?}", e),
+        }
+    }
+
+    #[test]
+    fn with_config() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Tree {
+            #[serde(default)]
+            node: Vec<Tree>,
+        }
+
+        let config = ReaderConfig::secure().trim_text(true).max_depth(3);
+        let xml = "<root>\n  <node><node/></node>\n  <node/>\n</root>";
+        let tree: Tree = from_reader_with_config(xml.as_bytes(), &config).unwrap();
+        assert_eq!(tree.node.len(), 2);
+        assert_eq!(tree.node[0].node.len(), 1);
+
+        let xml = "<root><node><node><node/></node></node></root>";
+        match from_str_with_config::<Tree>(xml, &config) {
+            Err(DeError::Xml(Error::TooDeep(3))) => {}
+            e => panic!("Expected `TooDeep(3)`, found {:?}", e),
+        }
+    }
+
+    #[test]
+    fn iter_elements_streaming() {
+        use std::cell::Cell;
+        use std::io::{BufReader, Read};
+        use std::rc::Rc;
+
+        /// An endless `<records>` document, which counts the generated bytes
+        struct Feed {
+            next_id: u64,
+            pending: Vec<u8>,
+            generated: Rc<Cell<usize>>,
+        }
+
+        impl Read for Feed {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                if self.pending.is_empty() {
+                    self.pending = format!(
+                        r#"<record id="{0}"><name>record {0}</name></record>"#,
+                        self.next_id
+                    )
+                    .into_bytes();
+                    self.next_id += 1;
+                }
+                let len = buf.len().min(self.pending.len());
+                buf[..len].copy_from_slice(&self.pending[..len]);
+                self.pending.drain(..len);
+                self.generated.set(self.generated.get() + len);
+                Ok(len)
+            }
+        }
+
+        let generated = Rc::new(Cell::new(0));
+        let feed = Feed {
+            next_id: 0,
+            pending: b"<records>".to_vec(),
+            generated: generated.clone(),
+        };
+        let mut iter = iter_from_reader::<_, Record>(BufReader::new(feed), "record");
+        for id in 0..10_000 {
+            let record = iter.next().unwrap().unwrap();
+            assert_eq!(record.id, id);
+            assert_eq!(record.name, format!("record {}", id));
+        }
+        // Only a few records are read ahead
+        let consumed = (0..10_000)
+            .map(|id| format!(r#"<record id="{0}"><name>record {0}</name></record>"#, id).len())
+            .sum::<usize>();
+        assert!(generated.get() < consumed + 8 * 1024);
+    }
+
     mod flatten_struct {
         use super::*;
 
@@ -1002,6 +3095,146 @@ mod tests {
             }
         }
 
+        /// Variant is selected by the name of a child element (`xsd:choice`)
+        mod choice {
+            use super::*;
+
+            #[derive(Debug, Deserialize, PartialEq)]
+            #[serde(rename_all = "lowercase")]
+            enum Shape {
+                Circle { r: f64 },
+                Square { side: f64 },
+            }
+
+            #[derive(Debug, Deserialize, PartialEq)]
+            struct Drawing {
+                name: String,
+                shape: Shape,
+            }
+
+            #[test]
+            fn root() {
+                let data: Shape = from_str(r#"<shape><circle r="1"/></shape>"#).unwrap();
+                assert_eq!(data, Shape::Circle { r: 1.0 });
+
+                let data: Shape = from_str(r#"<shape><square side="2"/></shape>"#).unwrap();
+                assert_eq!(data, Shape::Square { side: 2.0 });
+            }
+
+            #[test]
+            fn field() {
+                let data: Drawing = from_str(
+                    r#"<drawing name="sketch"><shape><square side="2"/></shape></drawing>"#,
+                )
+                .unwrap();
+                assert_eq!(
+                    data,
+                    Drawing {
+                        name: "sketch".into(),
+                        shape: Shape::Square { side: 2.0 },
+                    }
+                );
+            }
+
+            #[test]
+            fn unknown() {
+                match from_str::<Shape>(r#"<shape><triangle/></shape>"#) {
+                    Err(DeError::Custom(e)) => assert!(e.contains("triangle"), "{}", e),
+                    e => panic!("Expected `Err(Custom(_))`, but found {:?}", e),
+                }
+                match from_str::<Shape>(r#"<shape/>"#) {
+                    Err(DeError::Custom(e)) => assert!(e.contains("shape"), "{}", e),
+                    e => panic!("Expected `Err(Custom(_))`, but found {:?}", e),
+                }
+            }
+        }
+
+        /// Unit variant is selected by the text of an element
+        mod text {
+            use super::*;
+
+            #[derive(Debug, Deserialize, PartialEq)]
+            #[serde(rename_all = "lowercase")]
+            enum Status {
+                Active,
+                Inactive,
+                Pending,
+            }
+
+            #[derive(Debug, Deserialize, PartialEq)]
+            struct Account {
+                status: Status,
+                name: String,
+            }
+
+            #[test]
+            fn root() {
+                let data: Status = from_str("<status>active</status>").unwrap();
+                assert_eq!(data, Status::Active);
+
+                let data: Status = from_str("<status> inactive </status>").unwrap();
+                assert_eq!(data, Status::Inactive);
+            }
+
+            #[test]
+            fn field() {
+                let data: Account =
+                    from_str("<account><status>pending</status><name>x</name></account>").unwrap();
+                assert_eq!(
+                    data,
+                    Account {
+                        status: Status::Pending,
+                        name: "x".into(),
+                    }
+                );
+            }
+
+            #[test]
+            fn attribute() {
+                let data: Account = from_str(r#"<account status="inactive" name="x"/>"#).unwrap();
+                assert_eq!(
+                    data,
+                    Account {
+                        status: Status::Inactive,
+                        name: "x".into(),
+                    }
+                );
+            }
+
+            /// Both the text and the empty element select the variant
+            #[test]
+            fn value() {
+                #[derive(Debug, Deserialize, PartialEq)]
+                struct Value {
+                    #[serde(rename = "$value")]
+                    status: Status,
+                }
+
+                let data: Value = from_str("<value>pending</value>").unwrap();
+                assert_eq!(data.status, Status::Pending);
+
+                let data: Value = from_str("<value><pending/></value>").unwrap();
+                assert_eq!(data.status, Status::Pending);
+
+                let data: Status = from_str("<active/>").unwrap();
+                assert_eq!(data, Status::Active);
+
+                let data: Status = from_str("<status><active/></status>").unwrap();
+                assert_eq!(data, Status::Active);
+            }
+
+            #[test]
+            fn unknown() {
+                match from_str::<Status>("<status>Active</status>") {
+                    Err(DeError::Custom(e)) => assert_eq!(
+                        e,
+                        "unknown variant `Active`, expected one of `active`, `inactive`, `pending`"
+                    ),
+                    e => panic!("Expected `Err(Custom(_))`, but found {:?}", e),
+                }
+            }
+        }
+
         mod internally_tagged {
             use super::*;
 
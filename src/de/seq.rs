@@ -1,10 +1,9 @@
-use crate::de::{DeError, Deserializer};
+use crate::de::{DeError, Deserializer, XmlRead};
 use crate::{
     events::{BytesStart, Event},
     reader::Decoder,
 };
 use serde::de;
-use std::io::BufRead;
 
 #[derive(Debug)]
 enum Names {
@@ -15,36 +14,43 @@ enum Names {
 impl Names {
     fn is_valid(&self, decoder: Decoder, start: &BytesStart) -> Result<bool, DeError> {
         #[cfg(not(feature = "encoding"))]
-        let name = decoder.decode(start.name())?;
+        let name = decoder.decode(start.name().into_inner())?;
         #[cfg(feature = "encoding")]
-        let name = decoder.decode(start.name());
+        let name = decoder.decode(start.name().into_inner());
         let res = match self {
             Names::Unknown => true,
             Names::Peek(n) => &**n == &*name,
         };
         Ok(res)
     }
+
+    fn is_named(&self) -> bool {
+        match self {
+            Names::Unknown => false,
+            Names::Peek(_) => true,
+        }
+    }
 }
 
 /// A SeqAccess
-pub struct SeqAccess<'a, R: BufRead> {
-    de: &'a mut Deserializer<R>,
+pub struct SeqAccess<'de, 'a, R: XmlRead<'de>> {
+    de: &'a mut Deserializer<'de, R>,
     max_size: Option<usize>,
     names: Names,
 }
 
-impl<'a, R: BufRead> SeqAccess<'a, R> {
+impl<'de, 'a, R: XmlRead<'de>> SeqAccess<'de, 'a, R> {
     /// Get a new SeqAccess
-    pub fn new(de: &'a mut Deserializer<R>, max_size: Option<usize>) -> Result<Self, DeError> {
-        let decoder = de.reader.decoder();
+    pub fn new(de: &'a mut Deserializer<'de, R>, max_size: Option<usize>) -> Result<Self, DeError> {
+        let decoder = de.reader.reader().decoder();
         let names = if de.has_value_field {
             Names::Unknown
         } else {
             if let Some(Event::Start(e)) = de.peek()? {
                 #[cfg(not(feature = "encoding"))]
-                let name = decoder.decode(e.name())?.to_owned();
+                let name = decoder.decode(e.name().into_inner())?.to_owned();
                 #[cfg(feature = "encoding")]
-                let name = decoder.decode(e.name()).into_owned();
+                let name = decoder.decode(e.name().into_inner()).into_owned();
                 Names::Peek(name)
             } else {
                 Names::Unknown
@@ -58,7 +64,7 @@ impl<'a, R: BufRead> SeqAccess<'a, R> {
     }
 }
 
-impl<'de, 'a, R: 'a + BufRead> de::SeqAccess<'de> for SeqAccess<'a, R> {
+impl<'de, 'a, R: 'a + XmlRead<'de>> de::SeqAccess<'de> for SeqAccess<'de, 'a, R> {
     type Error = DeError;
 
     fn size_hint(&self) -> Option<usize> {
@@ -75,11 +81,17 @@ impl<'de, 'a, R: 'a + BufRead> de::SeqAccess<'de> for SeqAccess<'a, R> {
             }
             *s -= 1;
         }
-        let decoder = self.de.reader.decoder();
-        match self.de.peek()? {
-            None | Some(Event::Eof) | Some(Event::End(_)) => Ok(None),
-            Some(Event::Start(e)) if !self.names.is_valid(decoder, e)? => Ok(None),
-            _ => seed.deserialize(&mut *self.de).map(Some),
+        let decoder = self.de.reader.reader().decoder();
+        loop {
+            match self.de.peek()? {
+                None | Some(Event::Eof) | Some(Event::End(_)) => return Ok(None),
+                Some(Event::Start(e)) if !self.names.is_valid(decoder, e)? => return Ok(None),
+                // Text between repeated elements is not one of them, so it is skipped
+                Some(Event::Text(_)) | Some(Event::CData(_)) if self.names.is_named() => {
+                    self.de.next()?;
+                }
+                _ => return seed.deserialize(&mut *self.de).map(Some),
+            }
         }
     }
 }
@@ -1,51 +1,57 @@
 use crate::{
-    de::{escape::EscapedDeserializer, Deserializer},
+    de::{escape::EscapedDeserializer, trim_whitespaces, Deserializer, XmlRead},
     errors::serialize::DeError,
     events::Event,
 };
 use serde::de::{self, Deserializer as SerdeDeserializer};
-use std::io::BufRead;
+use std::borrow::Cow;
 
 /// An enum access
-pub struct EnumAccess<'a, R: BufRead> {
-    de: &'a mut Deserializer<R>,
+pub struct EnumAccess<'de, 'a, R: XmlRead<'de>> {
+    de: &'a mut Deserializer<'de, R>,
 }
 
-impl<'a, R: BufRead> EnumAccess<'a, R> {
-    pub fn new(de: &'a mut Deserializer<R>) -> Self {
+impl<'de, 'a, R: XmlRead<'de>> EnumAccess<'de, 'a, R> {
+    pub fn new(de: &'a mut Deserializer<'de, R>) -> Self {
         EnumAccess { de }
     }
 }
 
-impl<'de, 'a, R: 'a + BufRead> de::EnumAccess<'de> for EnumAccess<'a, R> {
+impl<'de, 'a, R: 'a + XmlRead<'de>> de::EnumAccess<'de> for EnumAccess<'de, 'a, R> {
     type Error = DeError;
-    type Variant = VariantAccess<'a, R>;
+    type Variant = VariantAccess<'de, 'a, R>;
 
     fn variant_seed<V: de::DeserializeSeed<'de>>(
         self,
         seed: V,
-    ) -> Result<(V::Value, VariantAccess<'a, R>), DeError> {
-        let decoder = self.de.reader.decoder();
-        let de = match self.de.peek()? {
-            Some(Event::Text(t)) => EscapedDeserializer::new(t.to_vec(), decoder, true),
-            Some(Event::Start(e)) => EscapedDeserializer::new(e.name().to_vec(), decoder, false),
-            Some(e) => return Err(DeError::InvalidEnum(e.to_owned())),
+    ) -> Result<(V::Value, VariantAccess<'de, 'a, R>), DeError> {
+        let decoder = self.de.reader.reader().decoder();
+        let (name, escaped) = match self.de.peek()? {
+            Some(Event::Text(t)) => (trim_whitespaces(t).to_vec(), true),
+            Some(Event::Start(e)) => (e.name().to_vec(), false),
+            Some(e) => return Err(DeError::InvalidEnum(e.clone().into_owned())),
             None => return Err(DeError::Eof),
         };
+        let name = if escaped {
+            self.de.unescape(&name)?.into_owned()
+        } else {
+            name
+        };
+        let de = EscapedDeserializer::new(Cow::Owned(name), decoder, false);
         let name = seed.deserialize(de)?;
         Ok((name, VariantAccess { de: self.de }))
     }
 }
 
-pub struct VariantAccess<'a, R: BufRead> {
-    de: &'a mut Deserializer<R>,
+pub struct VariantAccess<'de, 'a, R: XmlRead<'de>> {
+    de: &'a mut Deserializer<'de, R>,
 }
 
-impl<'de, 'a, R: BufRead> de::VariantAccess<'de> for VariantAccess<'a, R> {
+impl<'de, 'a, R: XmlRead<'de>> de::VariantAccess<'de> for VariantAccess<'de, 'a, R> {
     type Error = DeError;
 
     fn unit_variant(self) -> Result<(), DeError> {
-        match self.de.next(&mut Vec::new())? {
+        match self.de.next()? {
             Event::Start(e) => self.de.read_to_end(e.name()),
             Event::Text(_) => Ok(()),
             _ => unreachable!(),
@@ -0,0 +1,254 @@
+//! Hashing of the logical content of XML documents, for example, to detect changes.
+//!
+//! [`hash_events`] feeds the same canonical parts of a document, which are compared by
+//! [`semantic_eq`], into a [`Hasher`], so documents, which are equal for [`semantic_eq`]
+//! with the same [`CompareOptions`], have equal hashes: the order of attributes, the quotes
+//! around attribute values, the way the characters are escaped and whether a text is in
+//! a CDATA section do not change the hash.
+//!
+//! [`hash_events`]: fn.hash_events.html
+//! [`semantic_eq`]: ../compare/fn.semantic_eq.html
+//! [`Hasher`]: https://doc.rust-lang.org/std/hash/trait.Hasher.html
+//! [`CompareOptions`]: ../compare/struct.CompareOptions.html
+
+use std::hash::Hasher;
+use std::io::BufRead;
+
+use compare::{CompareOptions, Item, Items};
+use errors::Result;
+
+/// Feeds the logical content of the XML document read from `input` into the `hasher`.
+/// `options` define which differences are ignored, as for [`semantic_eq`].
+///
+/// Each part of the document is written with a tag of its kind, and each name, value and
+/// text is prefixed with its length, so different structures, like a text split into two
+/// texts by a comment and a single text, are not fed as the same bytes.
+///
+/// Returns an error if the document is not valid. The `hasher` may be partially fed then.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate quick_xml;
+/// # fn main() {
+/// use std::collections::hash_map::DefaultHasher;
+/// use std::hash::Hasher;
+/// use quick_xml::compare::CompareOptions;
+/// use quick_xml::digest::hash_events;
+///
+/// fn hash(xml: &str) -> u64 {
+///     let options = CompareOptions {
+///         ignore_whitespace_text: true,
+///         ..CompareOptions::default()
+///     };
+///     let mut hasher = DefaultHasher::new();
+///     hash_events(xml.as_bytes(), &mut hasher, options).unwrap();
+///     hasher.finish()
+/// }
+///
+/// assert_eq!(
+///     hash(r#"<items><item id="1" type='a'>a &amp; b</item></items>"#),
+///     hash("<items>\n  <item type=\"a\" id=\"1\"><![CDATA[a & b]]></item>\n</items>"),
+/// );
+/// assert_ne!(hash("<item>text</item>"), hash("<item>test</item>"));
+/// # }
+/// ```
+///
+/// [`semantic_eq`]: ../compare/fn.semantic_eq.html
+pub fn hash_events<R: BufRead, H: Hasher>(
+    input: R,
+    hasher: &mut H,
+    options: CompareOptions,
+) -> Result<()> {
+    let mut items = Items::new(input, options);
+    loop {
+        let item = items.next()?;
+        hash_item(&item, hasher);
+        if item == Item::Eof {
+            return Ok(());
+        }
+    }
+}
+
+fn hash_item<H: Hasher>(item: &Item, hasher: &mut H) {
+    match item {
+        Item::Start {
+            name,
+            attributes,
+            empty,
+        } => {
+            hasher.write_u8(if *empty { 1 } else { 0 });
+            hash_str(name, hasher);
+            hasher.write_u64(attributes.len() as u64);
+            for (key, value) in attributes {
+                hash_str(key, hasher);
+                hash_str(value, hasher);
+            }
+        }
+        Item::End(name) => {
+            hasher.write_u8(2);
+            hash_str(name, hasher);
+        }
+        Item::Text(text) => {
+            hasher.write_u8(3);
+            hash_str(text, hasher);
+        }
+        Item::Comment(text) => {
+            hasher.write_u8(4);
+            hash_str(text, hasher);
+        }
+        Item::PI(text) => {
+            hasher.write_u8(5);
+            hash_str(text, hasher);
+        }
+        Item::DocType(text) => {
+            hasher.write_u8(6);
+            hash_str(text, hasher);
+        }
+        Item::Eof => hasher.write_u8(7),
+    }
+}
+
+/// Writes the length of `s` followed by its bytes
+fn hash_str<H: Hasher>(s: &str, hasher: &mut H) {
+    hasher.write_u64(s.len() as u64);
+    hasher.write(s.as_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use compare::semantic_eq;
+    use std::collections::hash_map::DefaultHasher;
+
+    const ALL: CompareOptions = CompareOptions {
+        ignore_comments: true,
+        ignore_whitespace_text: true,
+        empty_equals_expanded: true,
+    };
+
+    fn hash(xml: &str, options: CompareOptions) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        hash_events(xml.as_bytes(), &mut hasher, options).unwrap();
+        hasher.finish()
+    }
+
+    /// Records the bytes fed to the hasher
+    #[derive(Default)]
+    struct Recorder(Vec<u8>);
+
+    impl Hasher for Recorder {
+        fn finish(&self) -> u64 {
+            0
+        }
+
+        fn write(&mut self, bytes: &[u8]) {
+            self.0.extend_from_slice(bytes);
+        }
+    }
+
+    fn fed(xml: &str, options: CompareOptions) -> Vec<u8> {
+        let mut recorder = Recorder::default();
+        hash_events(xml.as_bytes(), &mut recorder, options).unwrap();
+        recorder.0
+    }
+
+    #[test]
+    fn reordered_attributes() {
+        let options = CompareOptions::default();
+        assert_eq!(
+            hash(r#"<a x="1" y='2' z="&lt;"/>"#, options),
+            hash(r#"<a z='&#60;' y="2" x="1"/>"#, options)
+        );
+        assert_ne!(
+            hash(r#"<a x="1" y="2"/>"#, options),
+            hash(r#"<a x="2" y="1"/>"#, options)
+        );
+    }
+
+    #[test]
+    fn whitespace() {
+        let compact = "<root><item>text</item><item/></root>";
+        let indented = "<root>\n  <item>text</item>\n  <item></item>\n</root>\n";
+        assert_eq!(hash(compact, ALL), hash(indented, ALL));
+        assert_ne!(
+            hash(compact, CompareOptions::default()),
+            hash(indented, CompareOptions::default())
+        );
+        // Whitespace inside of texts is significant
+        assert_ne!(
+            hash("<a>some text</a>", ALL),
+            hash("<a>some  text</a>", ALL)
+        );
+    }
+
+    #[test]
+    fn single_character() {
+        let xml = r#"<root a="value"><item>text</item><!--comment--><?pi?></root>"#;
+        let changed = [
+            r#"<root b="value"><item>text</item><!--comment--><?pi?></root>"#,
+            r#"<root a="valud"><item>text</item><!--comment--><?pi?></root>"#,
+            r#"<root a="value"><iten>text</iten><!--comment--><?pi?></root>"#,
+            r#"<root a="value"><item>test</item><!--comment--><?pi?></root>"#,
+            r#"<root a="value"><item>text </item><!--comment--><?pi?></root>"#,
+            r#"<root a="value"><item>text</item><!--commend--><?pi?></root>"#,
+            r#"<root a="value"><item>text</item><!--comment--><?pj?></root>"#,
+        ];
+        let options = CompareOptions::default();
+        for changed in changed.iter() {
+            assert_ne!(hash(xml, options), hash(changed, options), "{}", changed);
+        }
+    }
+
+    #[test]
+    fn framing() {
+        let options = CompareOptions::default();
+        // The same characters in different parts of the document
+        assert_ne!(fed("<a>bc</a>", options), fed("<ab>c</ab>", options));
+        assert_ne!(fed("<a x='12'/>", options), fed("<a x1='2'/>", options));
+        assert_ne!(fed("<a>b<!---->c</a>", options), fed("<a>bc</a>", options));
+        assert_ne!(fed("<a/>", options), fed("<a></a>", options));
+        assert_eq!(fed("<a/>", ALL), fed("<a></a>", ALL));
+    }
+
+    #[test]
+    fn agrees_with_semantic_eq() {
+        let documents = [
+            "<root/>",
+            "<root></root>",
+            "<root>\n</root>",
+            "<root><!-- comment --></root>",
+            "<root a='1' b='2'/>",
+            r#"<root b="2" a="1"></root>"#,
+            "<root>a<![CDATA[b]]></root>",
+            "<root>ab</root>",
+            "<root>a<!---->b</root>",
+            "<?xml version='1.0'?><root/>",
+            "<!DOCTYPE root><root/>",
+            "<root><?pi?></root>",
+        ];
+        let options = [
+            CompareOptions::default(),
+            ALL,
+            CompareOptions {
+                ignore_comments: true,
+                ..CompareOptions::default()
+            },
+        ];
+        for &options in options.iter() {
+            for a in documents.iter() {
+                for b in documents.iter() {
+                    let equal = semantic_eq(a, b, options).is_ok();
+                    let same_hash = hash(a, options) == hash(b, options);
+                    assert_eq!(equal, same_hash, "{} and {} with {:?}", a, b, options);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn invalid() {
+        let mut hasher = DefaultHasher::new();
+        assert!(hash_events("<a></b>".as_bytes(), &mut hasher, ALL).is_err());
+    }
+}
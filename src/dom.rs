@@ -0,0 +1,425 @@
+//! A lightweight tree of elements for small documents, which need random access.
+//!
+//! An [`Element`] is built from the events of a [`Reader`] and written back as events to
+//! a [`Writer`], so the parsing and the escaping rules are the ones of the event API.
+//! Names, attribute values and texts are stored unescaped and decoded.
+//!
+//! # Examples
+//!
+//! ```rust
+//! # extern crate quick_xml;
+//! # fn main() {
+//! use quick_xml::dom::Element;
+//! use quick_xml::{Reader, Writer};
+//!
+//! let mut reader = Reader::from_str(r#"<config><server port="80"/></config>"#);
+//! let mut root = Element::parse(&mut reader, 1024).unwrap();
+//!
+//! root.child_mut("server").unwrap().set_attr("port", "8080");
+//! root.push_child(Element::new("log").with_text("a & b"));
+//!
+//! let mut writer = Writer::new(Vec::new());
+//! root.write(&mut writer).unwrap();
+//! assert_eq!(
+//!     String::from_utf8(writer.into_inner()).unwrap(),
+//!     r#"<config><server port="8080"/><log>a &amp; b</log></config>"#
+//! );
+//! # }
+//! ```
+//!
+//! [`Element`]: struct.Element.html
+//! [`Reader`]: ../reader/struct.Reader.html
+//! [`Writer`]: ../writer/struct.Writer.html
+
+use std::io::BufRead;
+
+use errors::{Error, Result};
+use events::{BytesEnd, BytesStart, BytesText, Event};
+use reader::Reader;
+use writer::{Sink, Writer};
+
+/// A child of an [`Element`].
+///
+/// [`Element`]: struct.Element.html
+#[derive(Clone, Debug, PartialEq)]
+pub enum Node {
+    /// A nested element
+    Element(Element),
+    /// An unescaped text
+    Text(String),
+    /// Content of a `<![CDATA[...]]>` section
+    CData(String),
+    /// Content of a `<!--...-->` comment
+    Comment(String),
+    /// Content of a `<?...?>` processing instruction
+    PI(String),
+}
+
+impl From<Element> for Node {
+    fn from(element: Element) -> Node {
+        Node::Element(element)
+    }
+}
+
+/// An element with its attributes and children, in document order.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Element {
+    /// Qualified name of the element
+    pub name: String,
+    /// Unescaped attributes, in document order
+    pub attributes: Vec<(String, String)>,
+    /// Nested elements, texts, CDATA sections, comments and processing instructions
+    pub children: Vec<Node>,
+}
+
+impl Element {
+    /// Creates an element without attributes and children
+    pub fn new<N: Into<String>>(name: N) -> Element {
+        Element {
+            name: name.into(),
+            attributes: Vec::new(),
+            children: Vec::new(),
+        }
+    }
+
+    /// Consumes the element and returns it with the attribute set to `value`
+    pub fn with_attr<N: Into<String>, V: Into<String>>(mut self, name: N, value: V) -> Element {
+        self.set_attr(name, value);
+        self
+    }
+
+    /// Consumes the element and returns it with the node appended to the children
+    pub fn with_child<N: Into<Node>>(mut self, node: N) -> Element {
+        self.children.push(node.into());
+        self
+    }
+
+    /// Consumes the element and returns it with the text appended to the children
+    pub fn with_text<T: Into<String>>(mut self, text: T) -> Element {
+        self.children.push(Node::Text(text.into()));
+        self
+    }
+
+    /// Reads the next element from the `reader` with all its content.
+    ///
+    /// The declaration, comments, processing instructions, doctype and texts preceding
+    /// the element are skipped. Reading stops at the end of the element, so the rest of
+    /// the document can still be read from the `reader`.
+    ///
+    /// Returns [`Error::TooLarge`] if more than `max_size` bytes are read before the
+    /// end of the element, which limits the memory used for untrusted documents, and
+    /// [`Error::UnexpectedEof`] if the document ends before.
+    ///
+    /// Texts are stored as the `reader` returns them, so configure it with
+    /// [`Reader::trim_text`] to drop the indentation.
+    ///
+    /// [`Error::TooLarge`]: ../enum.Error.html#variant.TooLarge
+    /// [`Error::UnexpectedEof`]: ../enum.Error.html#variant.UnexpectedEof
+    /// [`Reader::trim_text`]: ../reader/struct.Reader.html#method.trim_text
+    pub fn parse<B: BufRead>(reader: &mut Reader<B>, max_size: usize) -> Result<Element> {
+        let start = reader.buffer_position();
+        let mut buf = Vec::new();
+        // Elements, which end tag is not read yet. The last one is the current element
+        let mut stack: Vec<Element> = Vec::new();
+        loop {
+            let event = reader.read_event(&mut buf)?;
+            if reader.buffer_position() - start > max_size {
+                return Err(Error::TooLarge(max_size));
+            }
+            let node = match event {
+                Event::Start(ref e) => {
+                    stack.push(Element::from_start(e, reader)?);
+                    None
+                }
+                Event::Empty(ref e) => Some(Node::Element(Element::from_start(e, reader)?)),
+                Event::End(_) => stack.pop().map(Node::Element),
+                Event::Text(ref e) if !stack.is_empty() && !e.is_empty() => {
+                    Some(Node::Text(e.unescape_and_decode(reader)?))
+                }
+                Event::CData(ref e) if !stack.is_empty() => {
+                    Some(Node::CData(e.unescape_and_decode(reader)?))
+                }
+                Event::Comment(ref e) if !stack.is_empty() => {
+                    Some(Node::Comment(reader.decode_cow(e)?.into_owned()))
+                }
+                Event::PI(ref e) if !stack.is_empty() => {
+                    Some(Node::PI(reader.decode_cow(e)?.into_owned()))
+                }
+                Event::Eof => return Err(Error::UnexpectedEof("Element".to_string())),
+                _ => None,
+            };
+            match (node, stack.last_mut()) {
+                (Some(node), Some(parent)) => parent.children.push(node),
+                (Some(Node::Element(root)), None) => return Ok(root),
+                _ => {}
+            }
+            buf.clear();
+        }
+    }
+
+    /// Creates an element without children from a start tag
+    fn from_start<B: BufRead>(e: &BytesStart, reader: &Reader<B>) -> Result<Element> {
+        let mut element = Element::new(reader.decode_cow(e.name().as_ref())?.into_owned());
+        for attr in e.attributes() {
+            let attr = attr?;
+            let value = attr.unescape_and_decode_value(reader)?;
+            element
+                .attributes
+                .push((reader.decode_cow(attr.key.as_ref())?.into_owned(), value));
+        }
+        Ok(element)
+    }
+
+    /// Returns the value of the attribute with the given qualified name
+    pub fn attr(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Sets the value of the attribute, adding it after the existing attributes if
+    /// the element does not have it yet
+    pub fn set_attr<N: Into<String>, V: Into<String>>(&mut self, name: N, value: V) {
+        let name = name.into();
+        let value = value.into();
+        match self.attributes.iter_mut().find(|(key, _)| *key == name) {
+            Some(attr) => attr.1 = value,
+            None => self.attributes.push((name, value)),
+        }
+    }
+
+    /// Removes the attribute, returning its value if the element had it
+    pub fn remove_attr(&mut self, name: &str) -> Option<String> {
+        let index = self.attributes.iter().position(|(key, _)| key == name)?;
+        Some(self.attributes.remove(index).1)
+    }
+
+    /// Returns an iterator over the nested elements
+    pub fn elements(&self) -> impl Iterator<Item = &Element> {
+        self.children.iter().filter_map(|node| match node {
+            Node::Element(e) => Some(e),
+            _ => None,
+        })
+    }
+
+    /// Returns an iterator over the nested elements with the given qualified name
+    pub fn children_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Element> {
+        self.elements().filter(move |e| e.name == name)
+    }
+
+    /// Returns the first nested element with the given qualified name
+    pub fn child(&self, name: &str) -> Option<&Element> {
+        self.elements().find(|e| e.name == name)
+    }
+
+    /// Returns the first nested element with the given qualified name for modification
+    pub fn child_mut(&mut self, name: &str) -> Option<&mut Element> {
+        self.children.iter_mut().find_map(|node| match node {
+            Node::Element(e) if e.name == name => Some(e),
+            _ => None,
+        })
+    }
+
+    /// Appends a node to the children
+    pub fn push_child<N: Into<Node>>(&mut self, node: N) {
+        self.children.push(node.into());
+    }
+
+    /// Inserts a node into the children at position `index`, shifting all nodes after it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index > children.len()`.
+    pub fn insert_child<N: Into<Node>>(&mut self, index: usize, node: N) {
+        self.children.insert(index, node.into());
+    }
+
+    /// Returns the concatenated texts and CDATA sections of this element, without the
+    /// content of the nested elements
+    pub fn text(&self) -> String {
+        let mut text = String::new();
+        for node in &self.children {
+            match node {
+                Node::Text(t) | Node::CData(t) => text.push_str(t),
+                _ => {}
+            }
+        }
+        text
+    }
+
+    /// Writes the element with its content as events. An element without children is
+    /// written as an `Event::Empty`.
+    ///
+    /// A CDATA section, which contains `]]>`, is split into adjacent sections between
+    /// `]]` and `>`, and a comment, which contains `--` or ends with `-`, is rejected with
+    /// [`Error::InvalidComment`], as in [`Writer::write_comment`].
+    ///
+    /// [`Error::InvalidComment`]: ../enum.Error.html#variant.InvalidComment
+    /// [`Writer::write_comment`]: ../writer/struct.Writer.html#method.write_comment
+    pub fn write<W: Sink>(&self, writer: &mut Writer<W>) -> Result<()> {
+        let mut start = BytesStart::borrowed_name(self.name.as_bytes());
+        for (key, value) in &self.attributes {
+            start.push_attribute((key.as_str(), value.as_str()));
+        }
+        if self.children.is_empty() {
+            return writer.write_event(Event::Empty(start));
+        }
+        writer.write_event(Event::Start(start))?;
+        for node in &self.children {
+            match node {
+                Node::Element(e) => e.write(writer)?,
+                Node::Text(t) => writer.write_event(Event::Text(BytesText::from_plain_str(t)))?,
+                Node::CData(t) => {
+                    let mut rest = t.as_str();
+                    while let Some(i) = rest.find("]]>") {
+                        writer.write_cdata(&rest[..i + 2])?;
+                        rest = &rest[i + 2..];
+                    }
+                    writer.write_cdata(rest)?;
+                }
+                Node::Comment(t) => writer.write_comment(t)?,
+                Node::PI(t) => {
+                    writer.write_event(Event::PI(BytesText::from_escaped_str(t.as_str())))?
+                }
+            }
+        }
+        writer.write_event(Event::End(BytesEnd::borrowed(self.name.as_bytes())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DOCUMENT: &str = r#"<?xml version="1.0"?>
+<!-- servers -->
+<config version="1">
+    <server name="alpha" port="80"/>
+    <server name="beta" port="443">primary &amp; backup</server>
+    <log><![CDATA[<raw>]]></log>
+</config>"#;
+
+    fn parse(xml: &str) -> Element {
+        let mut reader = Reader::from_str(xml);
+        reader.trim_text(true);
+        Element::parse(&mut reader, xml.len()).unwrap()
+    }
+
+    fn write(element: &Element) -> String {
+        let mut writer = Writer::new(Vec::new());
+        element.write(&mut writer).unwrap();
+        String::from_utf8(writer.into_inner()).unwrap()
+    }
+
+    #[test]
+    fn parse_and_navigate() {
+        let root = parse(DOCUMENT);
+        assert_eq!(root.name, "config");
+        assert_eq!(root.attr("version"), Some("1"));
+        assert_eq!(root.attr("missing"), None);
+
+        let names: Vec<_> = root
+            .children_named("server")
+            .map(|e| e.attr("name").unwrap())
+            .collect();
+        assert_eq!(names, vec!["alpha", "beta"]);
+        assert_eq!(root.elements().count(), 3);
+
+        let beta = root.children_named("server").nth(1).unwrap();
+        assert_eq!(beta.text(), "primary & backup");
+        assert_eq!(root.child("log").unwrap().text(), "<raw>");
+        assert_eq!(root.child("server").unwrap().text(), "");
+    }
+
+    #[test]
+    fn mutate_write_and_reparse() {
+        let mut root = parse(DOCUMENT);
+        root.child_mut("server").unwrap().set_attr("port", "8080");
+        root.insert_child(
+            0,
+            Element::new("admin")
+                .with_attr("email", "root@example.com")
+                .with_text("Tom & Jerry"),
+        );
+        assert_eq!(root.remove_attr("version"), Some("1".to_string()));
+
+        let xml = write(&root);
+        assert_eq!(
+            xml,
+            "<config>\
+                <admin email=\"root@example.com\">Tom &amp; Jerry</admin>\
+                <server name=\"alpha\" port=\"8080\"/>\
+                <server name=\"beta\" port=\"443\">primary &amp; backup</server>\
+                <log><![CDATA[<raw>]]></log>\
+            </config>"
+        );
+
+        let reparsed = parse(&xml);
+        assert_eq!(reparsed, root);
+        assert_eq!(reparsed.child("server").unwrap().attr("port"), Some("8080"));
+        assert_eq!(reparsed.child("admin").unwrap().text(), "Tom & Jerry");
+    }
+
+    #[test]
+    fn keeps_comments_and_processing_instructions() {
+        let xml = "<root><!-- note --><?target data?>text</root>";
+        let root = parse(xml);
+        assert_eq!(
+            root.children,
+            vec![
+                Node::Comment(" note ".to_string()),
+                Node::PI("target data".to_string()),
+                Node::Text("text".to_string()),
+            ]
+        );
+        assert_eq!(write(&root), xml);
+    }
+
+    #[test]
+    fn split_cdata() {
+        let root = Element::new("r").with_child(Node::CData("a]]>b]]>".to_string()));
+        let xml = write(&root);
+        assert_eq!(xml, "<r><![CDATA[a]]]]><![CDATA[>b]]]]><![CDATA[>]]></r>");
+        assert_eq!(parse(&xml).text(), "a]]>b]]>");
+    }
+
+    #[test]
+    fn invalid_comments() {
+        for comment in &["a--b", "a-"] {
+            let root = Element::new("r").with_child(Node::Comment(comment.to_string()));
+            let mut writer = Writer::new(Vec::new());
+            match root.write(&mut writer) {
+                Err(Error::InvalidComment(c)) => assert_eq!(&c, comment),
+                x => panic!("Expected `Err(InvalidComment(_))`, but got `{:?}`", x),
+            }
+        }
+    }
+
+    #[test]
+    fn stops_after_element() {
+        let mut reader = Reader::from_str("<a/><b><c/></b>");
+        assert_eq!(Element::parse(&mut reader, 100).unwrap(), Element::new("a"));
+        let b = Element::parse(&mut reader, 100).unwrap();
+        assert_eq!(b, Element::new("b").with_child(Element::new("c")));
+    }
+
+    #[test]
+    fn size_limit() {
+        let mut reader = Reader::from_str("<root><child/><child/><child/></root>");
+        match Element::parse(&mut reader, 16) {
+            Err(Error::TooLarge(16)) => {}
+            x => panic!("Expected `Err(TooLarge(16))`, but got `{:?}`", x),
+        }
+    }
+
+    #[test]
+    fn unclosed() {
+        let mut reader = Reader::from_str("<root><child>");
+        reader.check_end_names(false);
+        match Element::parse(&mut reader, 100) {
+            Err(Error::UnexpectedEof(_)) => {}
+            x => panic!("Expected `Err(UnexpectedEof(_))`, but got `{:?}`", x),
+        }
+    }
+}
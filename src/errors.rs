@@ -7,6 +7,8 @@ pub enum Error {
     Io(::std::io::Error),
     /// Utf8 error
     Utf8(::std::str::Utf8Error),
+    /// Formatting error of a `std::fmt::Write` destination
+    Fmt(::std::fmt::Error),
     /// Unexpected End of File
     UnexpectedEof(String),
     /// End event mismatch
@@ -16,6 +18,8 @@ pub enum Error {
         /// Found end event
         found: String,
     },
+    /// The document was finished while the element with this name was not closed
+    UnclosedElement(String),
     /// Unexpected token
     UnexpectedToken(String),
     /// Unexpected <!>
@@ -26,6 +30,8 @@ pub enum Error {
     XmlDeclWithoutVersion(Option<String>),
     /// Attribute Name contains quote
     NameWithQuote(usize),
+    /// Attribute name contains a character, which is not allowed in XML names
+    InvalidAttributeName(usize),
     /// Attribute key not followed by with `=`
     NoEqAfterName(usize),
     /// Attribute value not quoted
@@ -34,6 +40,29 @@ pub enum Error {
     DuplicatedAttribute(usize, usize),
     /// Escape error
     EscapeError(::escape::EscapeError),
+    /// Document type declaration cannot be written
+    InvalidDocType(String),
+    /// More bytes than the given limit were read while building a tree of elements
+    TooLarge(usize),
+    /// Path given to `Reader::select` is malformed
+    InvalidPath(String),
+    /// Text of a comment contains `--` or ends with `-`
+    InvalidComment(String),
+    /// Text of a CDATA section contains `]]>`
+    InvalidCData(String),
+    /// Bytes are malformed in the encoding with this name
+    NonDecodable(&'static str),
+    /// A declaration, a text or an element at this position is not allowed at its place
+    /// before or after the root element, when `Reader::strict_prolog` is enabled
+    InvalidProlog(usize, &'static str),
+    /// A text or an attribute value contains this control character, which is not allowed
+    /// in XML 1.0 documents, and `Writer::control_char_policy` rejects it
+    ControlCharacter(u8),
+    /// An element is nested deeper than this limit, set by `Reader::max_depth`
+    TooDeep(usize),
+    /// A DOCTYPE declaration at this position is rejected, because `Reader::forbid_doctype`
+    /// is enabled
+    ForbiddenDocType(usize),
 }
 
 impl From<::std::io::Error> for Error {
@@ -52,6 +81,14 @@ impl From<::std::str::Utf8Error> for Error {
     }
 }
 
+impl From<::std::fmt::Error> for Error {
+    /// Creates a new `Error::Fmt` from the given error
+    #[inline]
+    fn from(error: ::std::fmt::Error) -> Error {
+        Error::Fmt(error)
+    }
+}
+
 /// A specialized `Result` type where the error is hard-wired to [`Error`].
 ///
 /// [`Error`]: enum.Error.html
@@ -62,14 +99,16 @@ impl std::fmt::Display for Error {
         match self {
             Error::Io(e) => write!(f, "I/O error: {}", e),
             Error::Utf8(e) => write!(f, "UTF8 error: {}", e),
+            Error::Fmt(e) => write!(f, "formatting error: {}", e),
             Error::UnexpectedEof(e) => write!(f, "Unexpected EOF during reading {}.", e),
             Error::EndEventMismatch { expected, found } => {
                 write!(f, "Expecting </{}> found </{}>", expected, found)
             }
+            Error::UnclosedElement(e) => write!(f, "Element <{}> is not closed", e),
             Error::UnexpectedToken(e) => write!(f, "Unexpected token '{}'", e),
             Error::UnexpectedBang => write!(
                 f,
-                "Only Comment, CDATA and DOCTYPE nodes can start with a '!'"
+                "Only Comment, CDATA, DOCTYPE, INCLUDE and IGNORE nodes can start with a '!'"
             ),
             Error::TextNotFound => write!(f, "Cannot read text, expecting Event::Text"),
             Error::XmlDeclWithoutVersion(e) => write!(
@@ -83,6 +122,12 @@ impl std::fmt::Display for Error {
                  Attribute key cannot contain quote.",
                 e
             ),
+            Error::InvalidAttributeName(e) => write!(
+                f,
+                "error while parsing attribute at position {}: \
+                 Attribute key contains an invalid character.",
+                e
+            ),
             Error::NoEqAfterName(e) => write!(
                 f,
                 "error while parsing attribute at position {}: \
@@ -102,6 +147,22 @@ impl std::fmt::Display for Error {
                 pos1, pos2
             ),
             Error::EscapeError(e) => write!(f, "{}", e),
+            Error::InvalidDocType(e) => write!(f, "Invalid DOCTYPE: {}", e),
+            Error::TooLarge(limit) => write!(f, "Document is larger than {} bytes", limit),
+            Error::InvalidPath(e) => write!(f, "Invalid path: {}", e),
+            Error::InvalidComment(e) => {
+                write!(f, "Comment cannot contain '--' or end with '-': {:?}", e)
+            }
+            Error::InvalidCData(e) => write!(f, "CDATA section cannot contain ']]>': {:?}", e),
+            Error::NonDecodable(e) => write!(f, "Malformed input, cannot decode it as {}", e),
+            Error::InvalidProlog(position, e) => write!(f, "error at position {}: {}", position, e),
+            Error::ControlCharacter(b) => {
+                write!(f, "Control character U+{:04X} is not allowed in XML 1.0", b)
+            }
+            Error::TooDeep(limit) => write!(f, "Elements are nested deeper than {}", limit),
+            Error::ForbiddenDocType(position) => {
+                write!(f, "error at position {}: DOCTYPE is not allowed", position)
+            }
         }
     }
 }
@@ -111,6 +172,7 @@ impl std::error::Error for Error {
         match self {
             Error::Io(e) => Some(e),
             Error::Utf8(e) => Some(e),
+            Error::Fmt(e) => Some(e),
             Error::EscapeError(e) => Some(e),
             _ => None,
         }
@@ -153,6 +215,49 @@ pub mod serialize {
         End,
         /// Unsupported operation
         Unsupported(&'static str),
+        /// A required field of a struct was not found in the XML.
+        ///
+        /// Fields with the `$attr:` prefix are read only from attributes and `$value` only
+        /// from the element content, so they are reported as missing attributes and text.
+        /// Other fields are reported as missing child elements: plain fields can be read
+        /// from attributes too, but serde doesn't tell the type of a missing field.
+        MissingField {
+            /// Name of the field as declared in the struct
+            field: &'static str,
+            /// `/`-separated names of the elements enclosing the missing field,
+            /// starting from the root, or an empty string if unknown
+            path: String,
+        },
+        /// The value of an attribute or a child element, read into a struct field, is invalid
+        InvalidField {
+            /// Name of the attribute or the element in the document
+            field: String,
+            /// Position in the document of the attribute value or, for elements, of
+            /// the last event read before the error
+            position: usize,
+            /// The error of the value
+            source: Box<DeError>,
+        },
+    }
+
+    impl DeError {
+        /// Adds the `field` name and the `position` in the document to an error of the value
+        /// of an attribute or an element. Only errors, which do not know their position,
+        /// are changed, and errors of nested fields keep the innermost field
+        pub(crate) fn in_field(self, field: &[u8], position: usize) -> DeError {
+            match self {
+                DeError::Custom(_)
+                | DeError::Int(_)
+                | DeError::Float(_)
+                | DeError::InvalidBoolean(_)
+                | DeError::InvalidUnit(_) => DeError::InvalidField {
+                    field: String::from_utf8_lossy(field).into_owned(),
+                    position,
+                    source: Box::new(self),
+                },
+                e => e,
+            }
+        }
     }
 
     impl fmt::Display for DeError {
@@ -177,6 +282,30 @@ pub mod serialize {
                 DeError::Start => write!(f, "Expecting Start event"),
                 DeError::End => write!(f, "Expecting End event"),
                 DeError::Unsupported(s) => write!(f, "Unsupported operation {}", s),
+                DeError::MissingField { field, path } => {
+                    if let Some(name) = field.strip_prefix(crate::de::ATTRIBUTE_PREFIX) {
+                        write!(f, "missing attribute `{}`", name)?;
+                    } else if *field == crate::de::INNER_VALUE {
+                        write!(f, "missing text content")?;
+                    } else {
+                        let name = field.strip_prefix(crate::de::UNFLATTEN_PREFIX);
+                        write!(f, "missing element `{}`", name.unwrap_or(field))?;
+                    }
+                    if path.is_empty() {
+                        Ok(())
+                    } else {
+                        write!(f, " in `{}`", path)
+                    }
+                }
+                DeError::InvalidField {
+                    field,
+                    position,
+                    source,
+                } => write!(
+                    f,
+                    "invalid value of `{}` at position {}: {}",
+                    field, position, source
+                ),
             }
         }
     }
@@ -187,6 +316,7 @@ pub mod serialize {
                 DeError::Int(e) => Some(e),
                 DeError::Float(e) => Some(e),
                 DeError::Xml(e) => Some(e),
+                DeError::InvalidField { source, .. } => Some(source.as_ref()),
                 _ => None,
             }
         }
@@ -196,6 +326,13 @@ pub mod serialize {
         fn custom<T: fmt::Display>(msg: T) -> Self {
             DeError::Custom(msg.to_string())
         }
+
+        fn missing_field(field: &'static str) -> Self {
+            DeError::MissingField {
+                field,
+                path: String::new(),
+            }
+        }
     }
 
     impl serde::ser::Error for DeError {
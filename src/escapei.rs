@@ -3,14 +3,21 @@
 use memchr;
 use std::borrow::Cow;
 use std::collections::HashMap;
+use std::iter::Peekable;
 use std::ops::Range;
 
 /// Error for XML escape/unescqpe.
+///
+/// All ranges are byte ranges of the offending reference in the unescaped input, including
+/// the leading `&` and the trailing `;`, if any. Use [`with_offset`] to translate them to
+/// positions in a bigger document.
+///
+/// [`with_offset`]: #method.with_offset
 #[derive(Debug)]
 pub enum EscapeError {
     /// Entity with Null character
     EntityWithNull(::std::ops::Range<usize>),
-    /// Unrecognized escape symbol
+    /// Unrecognized escape symbol, with the name of the entity
     UnrecognizedSymbol(
         ::std::ops::Range<usize>,
         ::std::result::Result<String, ::std::string::FromUtf8Error>,
@@ -18,15 +25,60 @@ pub enum EscapeError {
     /// Cannot find `;` after `&`
     UnterminatedEntity(::std::ops::Range<usize>),
     /// Cannot convert Hexa to utf8
-    TooLongHexadecimal,
+    TooLongHexadecimal(::std::ops::Range<usize>),
     /// Character is not a valid hexadecimal value
-    InvalidHexadecimal(char),
+    InvalidHexadecimal(::std::ops::Range<usize>, char),
     /// Cannot convert decimal to hexa
-    TooLongDecimal,
+    TooLongDecimal(::std::ops::Range<usize>),
     /// Character is not a valid decimal value
-    InvalidDecimal(char),
+    InvalidDecimal(::std::ops::Range<usize>, char),
     /// Not a valid unicode codepoint
-    InvalidCodepoint(u32),
+    InvalidCodepoint(::std::ops::Range<usize>, u32),
+}
+
+impl EscapeError {
+    /// Returns the byte range of the offending reference in the input
+    pub fn range(&self) -> Range<usize> {
+        match self {
+            EscapeError::EntityWithNull(r)
+            | EscapeError::UnrecognizedSymbol(r, _)
+            | EscapeError::UnterminatedEntity(r)
+            | EscapeError::TooLongHexadecimal(r)
+            | EscapeError::InvalidHexadecimal(r, _)
+            | EscapeError::TooLongDecimal(r)
+            | EscapeError::InvalidDecimal(r, _)
+            | EscapeError::InvalidCodepoint(r, _) => r.clone(),
+        }
+    }
+
+    /// Shifts the range of the offending reference by `offset` bytes. Useful when the
+    /// unescaped input is a part of a bigger document, starting at `offset`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use quick_xml::escape::unescape;
+    ///
+    /// let xml = b"<a>text &unknown;</a>";
+    /// let error = unescape(&xml[3..17]).unwrap_err().with_offset(3);
+    /// assert_eq!(&xml[error.range()], b"&unknown;");
+    /// ```
+    pub fn with_offset(mut self, offset: usize) -> Self {
+        match &mut self {
+            EscapeError::EntityWithNull(r)
+            | EscapeError::UnrecognizedSymbol(r, _)
+            | EscapeError::UnterminatedEntity(r)
+            | EscapeError::TooLongHexadecimal(r)
+            | EscapeError::InvalidHexadecimal(r, _)
+            | EscapeError::TooLongDecimal(r)
+            | EscapeError::InvalidDecimal(r, _)
+            | EscapeError::InvalidCodepoint(r, _) => {
+                r.start += offset;
+                r.end += offset;
+            }
+        }
+        self
+    }
 }
 
 impl std::fmt::Display for EscapeError {
@@ -47,13 +99,31 @@ impl std::fmt::Display for EscapeError {
                 "Error while escaping character at range {:?}: Cannot find ';' after '&'",
                 e
             ),
-            EscapeError::TooLongHexadecimal => write!(f, "Cannot convert hexadecimal to utf8"),
-            EscapeError::InvalidHexadecimal(e) => {
-                write!(f, "'{}' is not a valid hexadecimal character", e)
-            }
-            EscapeError::TooLongDecimal => write!(f, "Cannot convert decimal to utf8"),
-            EscapeError::InvalidDecimal(e) => write!(f, "'{}' is not a valid decimal character", e),
-            EscapeError::InvalidCodepoint(n) => write!(f, "'{}' is not a valid codepoint", n),
+            EscapeError::TooLongHexadecimal(e) => write!(
+                f,
+                "Error while escaping character at range {:?}: Cannot convert hexadecimal to utf8",
+                e
+            ),
+            EscapeError::InvalidHexadecimal(e, c) => write!(
+                f,
+                "Error while escaping character at range {:?}: '{}' is not a valid hexadecimal character",
+                e, c
+            ),
+            EscapeError::TooLongDecimal(e) => write!(
+                f,
+                "Error while escaping character at range {:?}: Cannot convert decimal to utf8",
+                e
+            ),
+            EscapeError::InvalidDecimal(e, c) => write!(
+                f,
+                "Error while escaping character at range {:?}: '{}' is not a valid decimal character",
+                e, c
+            ),
+            EscapeError::InvalidCodepoint(e, n) => write!(
+                f,
+                "Error while escaping character at range {:?}: '{}' is not a valid codepoint",
+                e, n
+            ),
         }
     }
 }
@@ -63,32 +133,63 @@ impl std::error::Error for EscapeError {}
 /// Escapes a `&[u8]` and replaces all xml special characters (<, >, &, ', ") with their
 /// corresponding xml escaped value.
 pub fn escape(raw: &[u8]) -> Cow<[u8]> {
-    fn to_escape(b: u8) -> bool {
-        match b {
-            b'<' | b'>' | b'\'' | b'&' | b'"' => true,
-            _ => false,
-        }
-    }
+    _escape(raw, special_chars(raw))
+}
+
+/// Escapes a `&[u8]` as [`escape`] does, and also replaces tab, line feed and carriage return
+/// characters with character references (`&#9;`, `&#10;` and `&#13;`), as they should be in
+/// attribute values.
+///
+/// When an attribute value is read, each whitespace character, written as is, is replaced by
+/// a space, as required by the [XML specification], so a multi-line value would not survive
+/// a round-trip. Characters, written as character references, are kept.
+///
+/// # Examples
+///
+/// ```
+/// use quick_xml::escape::escape_attribute;
+///
+/// assert_eq!(&*escape_attribute(b"a <b>\n\tc"), b"a &lt;b&gt;&#10;&#9;c");
+/// ```
+///
+/// [`escape`]: fn.escape.html
+/// [XML specification]: https://www.w3.org/TR/xml/#AVNormalize
+pub fn escape_attribute<'a>(raw: &'a [u8]) -> Cow<'a, [u8]> {
+    _escape(
+        raw,
+        Merge::new(
+            special_chars(raw),
+            memchr::memchr3_iter(b'\t', b'\n', b'\r', raw),
+        ),
+    )
+}
 
+/// Escapes `<`, `>` and `"` characters, and the `&` characters which do not start an entity
+/// or a character reference.
+///
+/// Unlike [`escape`], this function can be applied to content which is already (possibly
+/// partially) escaped: existing references such as `&amp;` or `&#x30;` are kept untouched.
+///
+/// [`escape`]: fn.escape.html
+pub(crate) fn escape_unescaped(raw: &[u8]) -> Cow<'_, [u8]> {
     let mut escaped = None;
-    let mut bytes = raw.iter();
     let mut pos = 0;
-    while let Some(i) = bytes.position(|&b| to_escape(b)) {
-        if escaped.is_none() {
-            escaped = Some(Vec::with_capacity(raw.len()));
-        }
-        let escaped = escaped.as_mut().expect("initialized");
-        let new_pos = pos + i;
-        escaped.extend_from_slice(&raw[pos..new_pos]);
-        match raw[new_pos] {
-            b'<' => escaped.extend_from_slice(b"&lt;"),
-            b'>' => escaped.extend_from_slice(b"&gt;"),
-            b'\'' => escaped.extend_from_slice(b"&apos;"),
-            b'&' => escaped.extend_from_slice(b"&amp;"),
-            b'"' => escaped.extend_from_slice(b"&quot;"),
-            _ => unreachable!("Only '<', '>','\', '&' and '\"' are escaped"),
-        }
-        pos = new_pos + 1;
+    let positions = Merge::new(
+        memchr::memchr3_iter(b'<', b'>', b'&', raw),
+        memchr::memchr_iter(b'"', raw),
+    );
+    for i in positions {
+        let replacement: &[u8] = match raw[i] {
+            b'<' => b"&lt;",
+            b'>' => b"&gt;",
+            b'"' => b"&quot;",
+            b'&' if !starts_with_reference(&raw[i..]) => b"&amp;",
+            _ => continue,
+        };
+        let escaped = escaped.get_or_insert_with(|| Vec::with_capacity(raw.len()));
+        escaped.extend_from_slice(&raw[pos..i]);
+        escaped.extend_from_slice(replacement);
+        pos = i + 1;
     }
 
     if let Some(mut escaped) = escaped {
@@ -101,12 +202,573 @@ pub fn escape(raw: &[u8]) -> Cow<[u8]> {
     }
 }
 
+/// Escapes the `&` characters which do not start an entity or a character reference, so
+/// that `raw` with bare ampersands, as is common in HTML, can be unescaped.
+pub(crate) fn escape_bare_ampersands(raw: &[u8]) -> Cow<'_, [u8]> {
+    _escape(
+        raw,
+        memchr::memchr_iter(b'&', raw).filter(|&i| !starts_with_reference(&raw[i..])),
+    )
+}
+
+/// Checks if `raw`, which starts with a `&`, starts with a well-formed entity reference
+/// (`&name;`) or character reference (`&#123;` or `&#x7B;`).
+fn starts_with_reference(raw: &[u8]) -> bool {
+    let end = match memchr::memchr(b';', raw) {
+        Some(end) => end,
+        None => return false,
+    };
+    match &raw[1..end] {
+        [] => false,
+        [b'#', b'x', hex @ ..] => !hex.is_empty() && hex.iter().all(u8::is_ascii_hexdigit),
+        [b'#', dec @ ..] => !dec.is_empty() && dec.iter().all(u8::is_ascii_digit),
+        [first, rest @ ..] => {
+            (first.is_ascii_alphabetic() || *first == b'_' || *first == b':')
+                && rest
+                    .iter()
+                    .all(|b| b.is_ascii_alphanumeric() || b"_:.-".contains(b))
+        }
+    }
+}
+
+/// Escapes bytes of `raw` at the given increasing `positions` with their corresponding
+/// xml escaped value.
+fn _escape<'a, I: Iterator<Item = usize>>(raw: &'a [u8], mut positions: I) -> Cow<'a, [u8]> {
+    match positions.next() {
+        Some(first) => {
+            let mut escaped = Vec::with_capacity(raw.len() + 8);
+            escape_positions_into(raw, &mut escaped, Some(first).into_iter().chain(positions));
+            Cow::Owned(escaped)
+        }
+        None => Cow::Borrowed(raw),
+    }
+}
+
+/// Appends `raw` to `out`, replacing bytes at the given increasing `positions` with their
+/// corresponding xml escaped value. Returns `true` if any byte was replaced.
+fn escape_positions_into<I: Iterator<Item = usize>>(
+    raw: &[u8],
+    out: &mut Vec<u8>,
+    positions: I,
+) -> bool {
+    let mut pos = 0;
+    let mut changed = false;
+    for i in positions {
+        out.extend_from_slice(&raw[pos..i]);
+        out.extend_from_slice(entity(raw[i]).as_bytes());
+        pos = i + 1;
+        changed = true;
+    }
+    out.extend_from_slice(&raw[pos..]);
+    changed
+}
+
+/// Returns positions of all xml special characters (<, >, &, ', ") in `raw`, in increasing
+/// order.
+///
+/// `memchr` searches for at most three bytes at once, so quotes are searched by a separate
+/// iterator. Merging both iterators, instead of restarting searches after each match, keeps
+/// the scan linear whatever the distribution of special characters is.
+fn special_chars<'a>(raw: &'a [u8]) -> Merge<memchr::Memchr3<'a>, memchr::Memchr2<'a>> {
+    Merge::new(
+        memchr::memchr3_iter(b'<', b'>', b'&', raw),
+        memchr::memchr2_iter(b'\'', b'"', raw),
+    )
+}
+
+/// Merges two iterators over increasing positions into one iterator over increasing positions
+struct Merge<A: Iterator<Item = usize>, B: Iterator<Item = usize>> {
+    a: Peekable<A>,
+    b: Peekable<B>,
+}
+
+impl<A: Iterator<Item = usize>, B: Iterator<Item = usize>> Merge<A, B> {
+    fn new(a: A, b: B) -> Self {
+        Merge {
+            a: a.peekable(),
+            b: b.peekable(),
+        }
+    }
+}
+
+impl<A: Iterator<Item = usize>, B: Iterator<Item = usize>> Iterator for Merge<A, B> {
+    type Item = usize;
+
+    #[inline]
+    fn next(&mut self) -> Option<usize> {
+        match (self.a.peek(), self.b.peek()) {
+            (Some(a), Some(b)) if a < b => self.a.next(),
+            (Some(_), Some(_)) | (None, _) => self.b.next(),
+            (Some(_), None) => self.a.next(),
+        }
+    }
+}
+
+/// Returns the predefined entity reference for one of the xml special characters, or
+/// the character reference for one of the whitespace characters, escaped in attribute values.
+fn entity(b: u8) -> &'static str {
+    match b {
+        b'<' => "&lt;",
+        b'>' => "&gt;",
+        b'\'' => "&apos;",
+        b'&' => "&amp;",
+        b'"' => "&quot;",
+        b'\t' => "&#9;",
+        b'\n' => "&#10;",
+        b'\r' => "&#13;",
+        _ => unreachable!("Only '<', '>','\\', '&', '\"' and whitespaces are escaped"),
+    }
+}
+
+/// Escapes a `&str` and replaces all xml special characters (<, >, &, ', ") with their
+/// corresponding xml escaped value.
+///
+/// This is the same as [`escape`], but for strings. No allocation is made if `raw` does not
+/// contain any special characters.
+///
+/// [`escape`]: fn.escape.html
+pub fn escape_str(raw: &str) -> Cow<'_, str> {
+    if needs_escaping(raw.as_bytes()) {
+        let mut escaped = String::with_capacity(raw.len());
+        escape_str_into(raw, &mut escaped);
+        Cow::Owned(escaped)
+    } else {
+        Cow::Borrowed(raw)
+    }
+}
+
+/// Escapes a `&str` as [`escape_str`] does, but appends the result to `out` instead of
+/// returning it.
+///
+/// [`escape_str`]: fn.escape_str.html
+pub fn escape_str_into(raw: &str, out: &mut String) {
+    let mut pos = 0;
+    // Special characters are ASCII, so their positions are always on char boundaries
+    for i in special_chars(raw.as_bytes()) {
+        out.push_str(&raw[pos..i]);
+        out.push_str(entity(raw.as_bytes()[i]));
+        pos = i + 1;
+    }
+    out.push_str(&raw[pos..]);
+}
+
+/// Escapes a `&[u8]` as [`escape`] does, but appends the result to `out` instead of
+/// returning it, so the same buffer can be reused for many values.
+///
+/// Returns `true` if any character was escaped, i.e. if the appended bytes differ from `raw`.
+/// Use [`needs_escaping`] to check that beforehand and borrow `raw` instead of copying it.
+///
+/// # Examples
+///
+/// ```
+/// use quick_xml::escape::{escape_into, needs_escaping};
+///
+/// let mut buffer = Vec::new();
+/// for raw in &["a < b", "plain"] {
+///     buffer.clear();
+///     let escaped: &[u8] = if needs_escaping(raw.as_bytes()) {
+///         escape_into(raw.as_bytes(), &mut buffer);
+///         &buffer
+///     } else {
+///         raw.as_bytes()
+///     };
+///     assert!(!escaped.contains(&b'<'));
+/// }
+///
+/// assert!(escape_into(b"a & b", &mut buffer));
+/// assert!(!escape_into(b", c", &mut buffer));
+/// assert_eq!(buffer, b"a &amp; b, c");
+/// ```
+///
+/// [`escape`]: fn.escape.html
+/// [`needs_escaping`]: fn.needs_escaping.html
+pub fn escape_into(raw: &[u8], out: &mut Vec<u8>) -> bool {
+    escape_positions_into(raw, out, special_chars(raw))
+}
+
+/// Returns `true` if `raw` contains any of the xml special characters (<, >, &, ', "), that is
+/// if [`escape`] would return an owned value.
+///
+/// [`escape`]: fn.escape.html
+pub fn needs_escaping(raw: &[u8]) -> bool {
+    special_chars(raw).next().is_some()
+}
+
+fn is_special(b: u8) -> bool {
+    matches!(b, b'<' | b'>' | b'\'' | b'&' | b'"')
+}
+
+/// Defines which characters are escaped by [`escape_with_level`].
+///
+/// [`escape_with_level`]: fn.escape_with_level.html
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EscapeLevel {
+    /// Only `&` and `<` are escaped, which is the minimum required for well-formed
+    /// text content. The same as [`partial_escape`].
+    ///
+    /// [`partial_escape`]: fn.partial_escape.html
+    Minimal,
+    /// All xml special characters (<, >, &, ', ") are escaped. The same as [`escape`].
+    ///
+    /// [`escape`]: fn.escape.html
+    #[default]
+    Standard,
+    /// All xml special characters are escaped, and all non-ASCII characters are written
+    /// as hexadecimal character references, such as `&#x444;`. Bytes which are not valid
+    /// UTF-8 are kept as is.
+    Aggressive,
+}
+
+/// Escapes only `&` and `<` characters of a `&[u8]`, which is the minimum required for
+/// well-formed text content. Attribute values need the quote character to be escaped too.
+pub fn partial_escape<'a>(raw: &'a [u8]) -> Cow<'a, [u8]> {
+    escape_with_level(raw, EscapeLevel::Minimal)
+}
+
+/// Escapes characters of a `&[u8]` selected by the `level`. No allocation is made if `raw`
+/// does not contain such characters.
+///
+/// # Examples
+///
+/// ```
+/// use quick_xml::escape::{escape_with_level, EscapeLevel};
+///
+/// let raw = "'a' > \u{444}".as_bytes();
+/// assert_eq!(&*escape_with_level(raw, EscapeLevel::Minimal), raw);
+/// assert_eq!(&*escape_with_level(raw, EscapeLevel::Standard), "&apos;a&apos; &gt; \u{444}".as_bytes());
+/// assert_eq!(&*escape_with_level(raw, EscapeLevel::Aggressive), b"&apos;a&apos; &gt; &#x444;");
+/// ```
+pub fn escape_with_level<'a>(raw: &'a [u8], level: EscapeLevel) -> Cow<'a, [u8]> {
+    if needs_escaping_with_level(raw, level) {
+        let mut escaped = Vec::with_capacity(raw.len());
+        escape_with_level_into(raw, &mut escaped, level);
+        Cow::Owned(escaped)
+    } else {
+        Cow::Borrowed(raw)
+    }
+}
+
+/// Returns `true` if [`escape_with_level`] would return an owned value.
+///
+/// [`escape_with_level`]: fn.escape_with_level.html
+pub(crate) fn needs_escaping_with_level(raw: &[u8], level: EscapeLevel) -> bool {
+    match level {
+        EscapeLevel::Minimal => memchr::memchr2(b'&', b'<', raw).is_some(),
+        EscapeLevel::Standard => needs_escaping(raw),
+        EscapeLevel::Aggressive => raw.iter().any(|&b| !b.is_ascii() || is_special(b)),
+    }
+}
+
+/// Escapes `raw` as [`escape_with_level`] does, but appends the result to `out`.
+/// Returns `true` if any character was escaped.
+///
+/// [`escape_with_level`]: fn.escape_with_level.html
+pub(crate) fn escape_with_level_into(raw: &[u8], out: &mut Vec<u8>, level: EscapeLevel) -> bool {
+    match level {
+        EscapeLevel::Minimal => {
+            escape_positions_into(raw, out, memchr::memchr2_iter(b'&', b'<', raw))
+        }
+        EscapeLevel::Standard => escape_into(raw, out),
+        EscapeLevel::Aggressive => {
+            escape_chars_into(raw, out, is_special, CharRefFormat::Hexadecimal)
+        }
+    }
+}
+
+/// Escapes the ASCII characters of `raw` for which `escape_ascii` returns `true` with their
+/// corresponding xml escaped value and all non-ASCII characters with character references
+/// in the `format`. Every non-ASCII byte needs to be examined, so this is a plain byte loop.
+fn escape_chars_into<F: Fn(u8) -> bool>(
+    raw: &[u8],
+    out: &mut Vec<u8>,
+    escape_ascii: F,
+    format: CharRefFormat,
+) -> bool {
+    let mut pos = 0;
+    let mut changed = false;
+    let mut i = 0;
+    while i < raw.len() {
+        let b = raw[i];
+        if b.is_ascii() && escape_ascii(b) {
+            out.extend_from_slice(&raw[pos..i]);
+            out.extend_from_slice(entity(b).as_bytes());
+            i += 1;
+            pos = i;
+            changed = true;
+        } else if !b.is_ascii() {
+            let end = raw.len().min(i + 4);
+            let valid = match std::str::from_utf8(&raw[i..end]) {
+                Ok(valid) => valid,
+                Err(e) => std::str::from_utf8(&raw[i..i + e.valid_up_to()]).expect("valid"),
+            };
+            match valid.chars().next() {
+                Some(ch) => {
+                    out.extend_from_slice(&raw[pos..i]);
+                    push_char_ref(out, ch, format);
+                    i += ch.len_utf8();
+                    pos = i;
+                    changed = true;
+                }
+                // Not a valid UTF-8, keep as is
+                None => i += 1,
+            }
+        } else {
+            i += 1;
+        }
+    }
+    out.extend_from_slice(&raw[pos..]);
+    changed
+}
+
+fn push_char_ref(out: &mut Vec<u8>, ch: char, format: CharRefFormat) {
+    use std::io::Write;
+    let result = match format {
+        CharRefFormat::Decimal => write!(out, "&#{};", u32::from(ch)),
+        CharRefFormat::Hexadecimal => write!(out, "&#x{:X};", u32::from(ch)),
+    };
+    result.expect("writing to a Vec never fails");
+}
+
+/// Returns `true` if `b` is a C0 control character, which is not allowed in XML 1.0
+/// documents, even as a character reference. Tab, line feed and carriage return are allowed
+pub(crate) fn is_control_char(b: u8) -> bool {
+    b < 0x20 && b != b'\t' && b != b'\n' && b != b'\r'
+}
+
+/// Replaces the control characters of `raw`, not allowed in XML 1.0 documents, with
+/// character references in the `format`, as XML 1.1 allows. NUL is not allowed even in
+/// XML 1.1, so it should be rejected before
+pub(crate) fn escape_control_chars<'a>(raw: &'a [u8], format: CharRefFormat) -> Cow<'a, [u8]> {
+    if !raw.iter().any(|&b| is_control_char(b)) {
+        return Cow::Borrowed(raw);
+    }
+    let mut escaped = Vec::with_capacity(raw.len() + 16);
+    for &b in raw {
+        if is_control_char(b) {
+            push_char_ref(&mut escaped, char::from(b), format);
+        } else {
+            escaped.push(b);
+        }
+    }
+    Cow::Owned(escaped)
+}
+
+/// Format of the character references, which replace escaped non-ASCII characters
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CharRefFormat {
+    /// Decimal character references, such as `&#233;`
+    #[default]
+    Decimal,
+    /// Hexadecimal character references, such as `&#xE9;`
+    Hexadecimal,
+}
+
+/// Replaces all non-ASCII characters of a `&[u8]` with character references in the `format`,
+/// so the result is pure ASCII if `raw` is valid UTF-8. Bytes which are not valid UTF-8 are
+/// kept as is. No allocation is made if `raw` is ASCII.
+///
+/// Other characters are kept untouched, so this can be applied to already escaped content.
+///
+/// # Examples
+///
+/// ```
+/// use quick_xml::escape::{escape_non_ascii, CharRefFormat};
+///
+/// let raw = "caf\u{E9} &amp; \u{1F600}".as_bytes();
+/// assert_eq!(&*escape_non_ascii(raw, CharRefFormat::Decimal), b"caf&#233; &amp; &#128512;");
+/// assert_eq!(&*escape_non_ascii(raw, CharRefFormat::Hexadecimal), b"caf&#xE9; &amp; &#x1F600;");
+/// ```
+pub fn escape_non_ascii<'a>(raw: &'a [u8], format: CharRefFormat) -> Cow<'a, [u8]> {
+    if raw.is_ascii() {
+        return Cow::Borrowed(raw);
+    }
+    let mut escaped = Vec::with_capacity(raw.len() + 16);
+    if escape_chars_into(raw, &mut escaped, |_| false, format) {
+        Cow::Owned(escaped)
+    } else {
+        Cow::Borrowed(raw)
+    }
+}
+
+/// Escapes `raw` as [`escape_with_level`] does, but also replaces all non-ASCII characters
+/// with character references in the `format`, and appends the result to `out`.
+/// Returns `true` if any character was escaped.
+///
+/// [`escape_with_level`]: fn.escape_with_level.html
+#[cfg(feature = "serialize")]
+pub(crate) fn escape_with_char_refs_into(
+    raw: &[u8],
+    out: &mut Vec<u8>,
+    level: EscapeLevel,
+    format: CharRefFormat,
+) -> bool {
+    match level {
+        EscapeLevel::Minimal => escape_chars_into(raw, out, |b| b == b'&' || b == b'<', format),
+        EscapeLevel::Standard | EscapeLevel::Aggressive => {
+            escape_chars_into(raw, out, is_special, format)
+        }
+    }
+}
+
+/// Escapes the characters of an already escaped attribute value, which are not escaped in
+/// texts: `"` characters, which are kept by the [`EscapeLevel::Minimal`] level, if `quotes`
+/// is `true`, and whitespace characters, as [`escape_attribute`] does, if `whitespaces`
+/// is `true`.
+///
+/// [`EscapeLevel::Minimal`]: enum.EscapeLevel.html#variant.Minimal
+/// [`escape_attribute`]: fn.escape_attribute.html
+#[cfg(feature = "serialize")]
+pub(crate) fn escape_attribute_chars<'a>(
+    raw: &'a [u8],
+    quotes: bool,
+    whitespaces: bool,
+) -> Cow<'a, [u8]> {
+    let positions = Merge::new(
+        memchr::memchr3_iter(b'\t', b'\n', b'\r', raw),
+        memchr::memchr_iter(b'"', raw),
+    );
+    _escape(
+        raw,
+        positions.filter(|&i| if raw[i] == b'"' { quotes } else { whitespaces }),
+    )
+}
+
 /// Unescape a `&[u8]` and replaces all xml escaped characters ('&...;') into their corresponding
 /// value
 pub fn unescape(raw: &[u8]) -> Result<Cow<[u8]>, EscapeError> {
     do_unescape(raw, None)
 }
 
+/// Unescapes a `&[u8]` as [`unescape`] does, but appends the result to `out` instead of
+/// returning it, so the same buffer can be reused for many values.
+///
+/// Returns `true` if any reference was replaced, i.e. if the appended bytes differ from `raw`.
+/// If an error is returned, `out` may contain a part of the unescaped value.
+///
+/// # Examples
+///
+/// ```
+/// use quick_xml::escape::unescape_into;
+///
+/// let mut buffer = Vec::new();
+/// assert!(unescape_into(b"a &lt; b", &mut buffer).unwrap());
+/// assert!(!unescape_into(b", c", &mut buffer).unwrap());
+/// assert_eq!(buffer, b"a < b, c");
+/// ```
+///
+/// [`unescape`]: fn.unescape.html
+pub fn unescape_into(raw: &[u8], out: &mut Vec<u8>) -> Result<bool, EscapeError> {
+    unescape_into_with_resolver(raw, out, cfg!(feature = "escape-html"), |_| None)
+}
+
+/// Unescapes a `&[u8]` as [`unescape`] does, but never fails: references which cannot be
+/// resolved, such as unknown entities or a `&` without the closing `;`, are kept as is and
+/// reported to `on_error`. Useful to recover data from malformed documents.
+///
+/// # Examples
+///
+/// ```
+/// use quick_xml::escape::unescape_lossy;
+///
+/// let raw = b"&lt;&unknown;&#xFFFFFFF; &am";
+/// let mut errors = Vec::new();
+/// let unescaped = unescape_lossy(raw, |e| errors.push(e.range()));
+/// assert_eq!(&*unescaped, b"<&unknown;&#xFFFFFFF; &am");
+/// assert_eq!(errors, vec![4..13, 13..24, 25..28]);
+/// ```
+///
+/// [`unescape`]: fn.unescape.html
+pub fn unescape_lossy<'a, F: FnMut(EscapeError)>(raw: &'a [u8], mut on_error: F) -> Cow<'a, [u8]> {
+    if memchr::memchr(b'&', raw).is_none() {
+        return Cow::Borrowed(raw);
+    }
+    let html = cfg!(feature = "escape-html");
+    let mut unescaped = Vec::with_capacity(raw.len());
+    let mut last_end = 0;
+    let mut iter = memchr::memchr2_iter(b'&', b';', raw).peekable();
+    while let Some(start) = iter.by_ref().find(|p| raw[*p] == b'&') {
+        match iter.peek() {
+            Some(&end) if raw[end] == b';' => {
+                iter.next();
+                unescaped.extend_from_slice(&raw[last_end..start]);
+                if let Err(e) = push_reference(&mut unescaped, raw, start..end, html, &mut |_| None)
+                {
+                    unescaped.extend_from_slice(&raw[start..=end]);
+                    on_error(e);
+                }
+                last_end = end + 1;
+            }
+            // The `&` and the text after it are copied as is with the following text
+            next => {
+                let end = next.copied().unwrap_or(raw.len());
+                on_error(EscapeError::UnterminatedEntity(start..end));
+            }
+        }
+    }
+    unescaped.extend_from_slice(&raw[last_end..]);
+    Cow::Owned(unescaped)
+}
+
+/// Unescape a `&str` and replaces all xml escaped characters ('&...;') into their corresponding
+/// value.
+///
+/// This is the same as [`unescape`], but for strings. No allocation is made if `raw` does not
+/// contain any escaped characters.
+///
+/// [`unescape`]: fn.unescape.html
+pub fn unescape_str(raw: &str) -> Result<Cow<'_, str>, EscapeError> {
+    Ok(match do_unescape(raw.as_bytes(), None)? {
+        Cow::Borrowed(_) => Cow::Borrowed(raw),
+        Cow::Owned(unescaped) => {
+            Cow::Owned(String::from_utf8(unescaped).expect("predefined entities are valid UTF-8"))
+        }
+    })
+}
+
+/// Unescape a `&str` and replaces all xml escaped characters ('&...;') into their corresponding
+/// value, using `resolve_entity` to get values of the entities, that are not predefined.
+///
+/// `resolve_entity` receives the name of an entity without `&` and `;` and returns its value,
+/// or `None` if the entity is unknown, which results in an error. The returned value is inserted
+/// as is, it is not scanned for other entities.
+///
+/// The predefined XML entities (`&lt;`, `&gt;`, `&amp;`, `&apos;` and `&quot;`) and character
+/// references (`&#...;`) are always resolved first and cannot be overridden. With the
+/// `escape-html` feature HTML named entities are resolved before calling `resolve_entity` too.
+///
+/// # Examples
+///
+/// ```
+/// use quick_xml::escape::unescape_str_with;
+///
+/// let unescaped = unescape_str_with("&product; &lt;&version;&gt;", |entity| match entity {
+///     "product" => Some("quick-xml"),
+///     "version" => Some("&amp;"),
+///     _ => None,
+/// });
+/// assert_eq!(unescaped.unwrap(), "quick-xml <&amp;>");
+/// assert!(unescape_str_with("&unknown;", |_| None).is_err());
+/// ```
+pub fn unescape_str_with<'a, 'e, F>(
+    raw: &'a str,
+    mut resolve_entity: F,
+) -> Result<Cow<'a, str>, EscapeError>
+where
+    F: FnMut(&str) -> Option<&'e str>,
+{
+    let unescaped =
+        unescape_with_resolver(raw.as_bytes(), cfg!(feature = "escape-html"), |name| {
+            // `raw` is a string, so the entity name is always valid UTF-8
+            resolve_entity(std::str::from_utf8(name).ok()?).map(str::as_bytes)
+        })?;
+    Ok(match unescaped {
+        Cow::Borrowed(_) => Cow::Borrowed(raw),
+        Cow::Owned(unescaped) => {
+            Cow::Owned(String::from_utf8(unescaped).expect("entities values are valid UTF-8"))
+        }
+    })
+}
+
 /// Unescape a `&[u8]` and replaces all xml escaped characters ('&...;') into their corresponding
 /// value, using a dictionnary of custom entities.
 ///
@@ -130,51 +792,163 @@ pub fn do_unescape<'a>(
     raw: &'a [u8],
     custom_entities: Option<&HashMap<Vec<u8>, Vec<u8>>>,
 ) -> Result<Cow<'a, [u8]>, EscapeError> {
-    let mut unescaped = None;
+    do_unescape_with_html(raw, custom_entities, cfg!(feature = "escape-html"))
+}
+
+/// The same as [`do_unescape`], but HTML named entities are resolved only if `html` is `true`.
+/// They are resolved after the predefined XML entities and character references, but before
+/// the custom entities.
+///
+/// [`do_unescape`]: fn.do_unescape.html
+pub(crate) fn do_unescape_with_html<'a>(
+    raw: &'a [u8],
+    custom_entities: Option<&HashMap<Vec<u8>, Vec<u8>>>,
+    html: bool,
+) -> Result<Cow<'a, [u8]>, EscapeError> {
+    unescape_with_resolver(raw, html, |name| {
+        custom_entities
+            .and_then(|hm| hm.get(name))
+            .map(Vec::as_slice)
+    })
+}
+
+/// Unescapes `raw`, using `resolve_entity` to get values of the entities, that are neither
+/// predefined XML entities nor, if `html` is `true`, HTML named entities.
+pub(crate) fn unescape_with_resolver<'a, 'e, F>(
+    raw: &'a [u8],
+    html: bool,
+    resolve_entity: F,
+) -> Result<Cow<'a, [u8]>, EscapeError>
+where
+    F: FnMut(&[u8]) -> Option<&'e [u8]>,
+{
+    if memchr::memchr(b'&', raw).is_none() {
+        return Ok(Cow::Borrowed(raw));
+    }
+    let mut unescaped = Vec::with_capacity(raw.len());
+    unescape_into_with_resolver(raw, &mut unescaped, html, resolve_entity)?;
+    Ok(Cow::Owned(unescaped))
+}
+
+/// Appends unescaped `raw` to `out`, resolving entities as [`unescape_with_resolver`] does.
+/// Returns `true` if any reference was replaced.
+///
+/// [`unescape_with_resolver`]: fn.unescape_with_resolver.html
+fn unescape_into_with_resolver<'e, F>(
+    raw: &[u8],
+    out: &mut Vec<u8>,
+    html: bool,
+    mut resolve_entity: F,
+) -> Result<bool, EscapeError>
+where
+    F: FnMut(&[u8]) -> Option<&'e [u8]>,
+{
     let mut last_end = 0;
     let mut iter = memchr::memchr2_iter(b'&', b';', raw);
     while let Some(start) = iter.by_ref().find(|p| raw[*p] == b'&') {
         match iter.next() {
             Some(end) if raw[end] == b';' => {
                 // append valid data
-                if unescaped.is_none() {
-                    unescaped = Some(Vec::with_capacity(raw.len()));
-                }
-                let unescaped = unescaped.as_mut().expect("initialized");
-                unescaped.extend_from_slice(&raw[last_end..start]);
-
-                // search for character correctness
-                let pat = &raw[start + 1..end];
-                if let Some(s) = named_entity(pat) {
-                    unescaped.extend_from_slice(s.as_bytes());
-                } else if pat.starts_with(b"#") {
-                    push_utf8(unescaped, parse_number(&pat[1..], start..end)?);
-                } else if let Some(value) = custom_entities.and_then(|hm| hm.get(pat)) {
-                    unescaped.extend_from_slice(&value);
-                } else {
-                    return Err(EscapeError::UnrecognizedSymbol(
-                        start + 1..end,
-                        String::from_utf8(pat.to_vec()),
-                    ));
-                }
+                out.extend_from_slice(&raw[last_end..start]);
+                push_reference(out, raw, start..end, html, &mut resolve_entity)?;
 
                 last_end = end + 1;
             }
             _ => return Err(EscapeError::UnterminatedEntity(start..raw.len())),
         }
     }
+    out.extend_from_slice(&raw[last_end..]);
+    Ok(last_end != 0)
+}
 
-    if let Some(mut unescaped) = unescaped {
-        if let Some(raw) = raw.get(last_end..) {
-            unescaped.extend_from_slice(raw);
-        }
-        Ok(Cow::Owned(unescaped))
+/// Appends the value of the reference `&...;`, which starts and ends at the `range` of `raw`,
+/// to `out`. The `range` includes `&`, but not `;`.
+/// HTML named entities are resolved only if `html` is `true`, other entities are resolved
+/// by `resolve_entity`.
+fn push_reference<'e, F>(
+    out: &mut Vec<u8>,
+    raw: &[u8],
+    range: Range<usize>,
+    html: bool,
+    resolve_entity: &mut F,
+) -> Result<(), EscapeError>
+where
+    F: FnMut(&[u8]) -> Option<&'e [u8]>,
+{
+    // search for character correctness
+    let pat = &raw[range.start + 1..range.end];
+    if let Some(s) = named_entity(pat) {
+        out.extend_from_slice(s.as_bytes());
+    } else if pat.starts_with(b"#") {
+        push_utf8(out, parse_number(&pat[1..], range.start..range.end + 1)?);
+    } else if let Some(s) = Some(pat).filter(|_| html).and_then(html_entity) {
+        out.extend_from_slice(s.as_bytes());
+    } else if let Some(value) = resolve_entity(pat) {
+        out.extend_from_slice(value);
     } else {
-        Ok(Cow::Borrowed(raw))
+        return Err(EscapeError::UnrecognizedSymbol(
+            range.start..range.end + 1,
+            String::from_utf8(pat.to_vec()),
+        ));
     }
+    Ok(())
 }
 
-#[cfg(not(feature = "escape-html"))]
+/// Normalizes a raw attribute value as required by the [XML specification] for attributes of
+/// the `CDATA` type: references are replaced by their values, and each whitespace character
+/// (`\t`, `\n`, `\r` and the `\r\n` line end) is replaced by a space. Whitespaces produced by
+/// character references, such as `&#xA;`, are kept as is.
+///
+/// HTML named entities are resolved only if `html` is `true`.
+///
+/// [XML specification]: https://www.w3.org/TR/xml/#AVNormalize
+pub(crate) fn normalize_attribute_value<'a>(
+    raw: &'a [u8],
+    html: bool,
+    custom_entities: Option<&HashMap<Vec<u8>, Vec<u8>>>,
+) -> Result<Cow<'a, [u8]>, EscapeError> {
+    let mut resolve_entity = |name: &[u8]| {
+        custom_entities
+            .and_then(|hm| hm.get(name))
+            .map(Vec::as_slice)
+    };
+    if !raw
+        .iter()
+        .any(|b| matches!(b, b'&' | b'\t' | b'\n' | b'\r'))
+    {
+        return Ok(Cow::Borrowed(raw));
+    }
+
+    let mut normalized = Vec::with_capacity(raw.len());
+    let mut pos = 0;
+    while pos < raw.len() {
+        match raw[pos] {
+            b'\r' if raw.get(pos + 1) == Some(&b'\n') => {
+                normalized.push(b' ');
+                pos += 1;
+            }
+            b'\t' | b'\n' | b'\r' => normalized.push(b' '),
+            b'&' => match memchr::memchr(b';', &raw[pos..]) {
+                Some(len) => {
+                    push_reference(
+                        &mut normalized,
+                        raw,
+                        pos..pos + len,
+                        html,
+                        &mut resolve_entity,
+                    )?;
+                    pos += len;
+                }
+                None => return Err(EscapeError::UnterminatedEntity(pos..raw.len())),
+            },
+            b => normalized.push(b),
+        }
+        pos += 1;
+    }
+    Ok(Cow::Owned(normalized))
+}
+
+/// Resolves the predefined XML entities
 const fn named_entity(name: &[u8]) -> Option<&str> {
     let s = match name {
         b"lt" => "<",
@@ -182,22 +956,32 @@ const fn named_entity(name: &[u8]) -> Option<&str> {
         b"amp" => "&",
         b"apos" => "'",
         b"quot" => "\"",
-        _ => return None
+        _ => return None,
     };
     Some(s)
 }
+
+/// Resolves the HTML5 [named character references], except the legacy ones, that
+/// can be used without the trailing `;`.
+///
+/// The table is generated from the [`entities.json`] of the specification by the ignored
+/// test in `tests/html_entities.rs`, which describes how to run it: names which resolve
+/// to the same string are grouped in one arm and arms are ordered by the code points of
+/// the string.
+///
+/// [named character references]: https://html.spec.whatwg.org/multipage/named-characters.html
+/// [`entities.json`]: https://html.spec.whatwg.org/entities.json
 #[cfg(feature = "escape-html")]
-const fn named_entity(name: &[u8]) -> Option<&str> {
-    // imported from https://dev.w3.org/html5/html-author/charref
+const fn html_entity(name: &[u8]) -> Option<&str> {
     let s = match name {
         b"Tab" => "\u{09}",
         b"NewLine" => "\u{0A}",
         b"excl" => "\u{21}",
-        b"quot" | b"QUOT" => "\u{22}",
+        b"QUOT" | b"quot" => "\u{22}",
         b"num" => "\u{23}",
         b"dollar" => "\u{24}",
         b"percnt" => "\u{25}",
-        b"amp" | b"AMP" => "\u{26}",
+        b"AMP" | b"amp" => "\u{26}",
         b"apos" => "\u{27}",
         b"lpar" => "\u{28}",
         b"rpar" => "\u{29}",
@@ -208,20 +992,24 @@ const fn named_entity(name: &[u8]) -> Option<&str> {
         b"sol" => "\u{2F}",
         b"colon" => "\u{3A}",
         b"semi" => "\u{3B}",
-        b"lt" | b"LT" => "\u{3C}",
+        b"LT" | b"lt" => "\u{3C}",
+        b"nvlt" => "\u{3C}\u{20D2}",
         b"equals" => "\u{3D}",
-        b"gt" | b"GT" => "\u{3E}",
+        b"bne" => "\u{3D}\u{20E5}",
+        b"GT" | b"gt" => "\u{3E}",
+        b"nvgt" => "\u{3E}\u{20D2}",
         b"quest" => "\u{3F}",
         b"commat" => "\u{40}",
-        b"lsqb" | b"lbrack" => "\u{5B}",
+        b"lbrack" | b"lsqb" => "\u{5B}",
         b"bsol" => "\u{5C}",
-        b"rsqb" | b"rbrack" => "\u{5D}",
+        b"rbrack" | b"rsqb" => "\u{5D}",
         b"Hat" => "\u{5E}",
-        b"lowbar" => "\u{5F}",
-        b"grave" | b"DiacriticalGrave" => "\u{60}",
-        b"lcub" | b"lbrace" => "\u{7B}",
+        b"lowbar" | b"UnderBar" => "\u{5F}",
+        b"DiacriticalGrave" | b"grave" => "\u{60}",
+        b"fjlig" => "\u{66}\u{6A}",
+        b"lbrace" | b"lcub" => "\u{7B}",
         b"verbar" | b"vert" | b"VerticalLine" => "\u{7C}",
-        b"rcub" | b"rbrace" => "\u{7D}",
+        b"rbrace" | b"rcub" => "\u{7D}",
         b"nbsp" | b"NonBreakingSpace" => "\u{A0}",
         b"iexcl" => "\u{A1}",
         b"cent" => "\u{A2}",
@@ -230,22 +1018,22 @@ const fn named_entity(name: &[u8]) -> Option<&str> {
         b"yen" => "\u{A5}",
         b"brvbar" => "\u{A6}",
         b"sect" => "\u{A7}",
-        b"Dot" | b"die" | b"DoubleDot" | b"uml" => "\u{A8}",
-        b"copy" | b"COPY" => "\u{A9}",
+        b"die" | b"Dot" | b"DoubleDot" | b"uml" => "\u{A8}",
+        b"COPY" | b"copy" => "\u{A9}",
         b"ordf" => "\u{AA}",
         b"laquo" => "\u{AB}",
         b"not" => "\u{AC}",
         b"shy" => "\u{AD}",
-        b"reg" | b"circledR" | b"REG" => "\u{AE}",
-        b"macr" | b"OverBar" | b"strns" => "\u{AF}",
+        b"circledR" | b"REG" | b"reg" => "\u{AE}",
+        b"macr" | b"strns" => "\u{AF}",
         b"deg" => "\u{B0}",
-        b"plusmn" | b"pm" | b"PlusMinus" => "\u{B1}",
+        b"PlusMinus" | b"plusmn" | b"pm" => "\u{B1}",
         b"sup2" => "\u{B2}",
         b"sup3" => "\u{B3}",
         b"acute" | b"DiacriticalAcute" => "\u{B4}",
         b"micro" => "\u{B5}",
         b"para" => "\u{B6}",
-        b"middot" | b"centerdot" | b"CenterDot" => "\u{B7}",
+        b"CenterDot" | b"centerdot" | b"middot" => "\u{B7}",
         b"cedil" | b"Cedilla" => "\u{B8}",
         b"sup1" => "\u{B9}",
         b"ordm" => "\u{BA}",
@@ -259,7 +1047,7 @@ const fn named_entity(name: &[u8]) -> Option<&str> {
         b"Acirc" => "\u{C2}",
         b"Atilde" => "\u{C3}",
         b"Auml" => "\u{C4}",
-        b"Aring" => "\u{C5}",
+        b"angst" | b"Aring" => "\u{C5}",
         b"AElig" => "\u{C6}",
         b"Ccedil" => "\u{C7}",
         b"Egrave" => "\u{C8}",
@@ -309,7 +1097,7 @@ const fn named_entity(name: &[u8]) -> Option<&str> {
         b"ocirc" => "\u{F4}",
         b"otilde" => "\u{F5}",
         b"ouml" => "\u{F6}",
-        b"divide" | b"div" => "\u{F7}",
+        b"div" | b"divide" => "\u{F7}",
         b"oslash" => "\u{F8}",
         b"ugrave" => "\u{F9}",
         b"uacute" => "\u{FA}",
@@ -318,291 +1106,290 @@ const fn named_entity(name: &[u8]) -> Option<&str> {
         b"yacute" => "\u{FD}",
         b"thorn" => "\u{FE}",
         b"yuml" => "\u{FF}",
-        b"Amacr" => "\u{10}",
-        b"amacr" => "\u{10}",
-        b"Abreve" => "\u{10}",
-        b"abreve" => "\u{10}",
-        b"Aogon" => "\u{10}",
-        b"aogon" => "\u{10}",
-        b"Cacute" => "\u{10}",
-        b"cacute" => "\u{10}",
-        b"Ccirc" => "\u{10}",
-        b"ccirc" => "\u{10}",
-        b"Cdot" => "\u{10}",
-        b"cdot" => "\u{10}",
-        b"Ccaron" => "\u{10}",
-        b"ccaron" => "\u{10}",
-        b"Dcaron" => "\u{10}",
-        b"dcaron" => "\u{10}",
-        b"Dstrok" => "\u{11}",
-        b"dstrok" => "\u{11}",
-        b"Emacr" => "\u{11}",
-        b"emacr" => "\u{11}",
-        b"Edot" => "\u{11}",
-        b"edot" => "\u{11}",
-        b"Eogon" => "\u{11}",
-        b"eogon" => "\u{11}",
-        b"Ecaron" => "\u{11}",
-        b"ecaron" => "\u{11}",
-        b"Gcirc" => "\u{11}",
-        b"gcirc" => "\u{11}",
-        b"Gbreve" => "\u{11}",
-        b"gbreve" => "\u{11}",
-        b"Gdot" => "\u{12}",
-        b"gdot" => "\u{12}",
-        b"Gcedil" => "\u{12}",
-        b"Hcirc" => "\u{12}",
-        b"hcirc" => "\u{12}",
-        b"Hstrok" => "\u{12}",
-        b"hstrok" => "\u{12}",
-        b"Itilde" => "\u{12}",
-        b"itilde" => "\u{12}",
-        b"Imacr" => "\u{12}",
-        b"imacr" => "\u{12}",
-        b"Iogon" => "\u{12}",
-        b"iogon" => "\u{12}",
-        b"Idot" => "\u{13}",
-        b"imath" | b"inodot" => "\u{13}",
-        b"IJlig" => "\u{13}",
-        b"ijlig" => "\u{13}",
-        b"Jcirc" => "\u{13}",
-        b"jcirc" => "\u{13}",
-        b"Kcedil" => "\u{13}",
-        b"kcedil" => "\u{13}",
-        b"kgreen" => "\u{13}",
-        b"Lacute" => "\u{13}",
-        b"lacute" => "\u{13}",
-        b"Lcedil" => "\u{13}",
-        b"lcedil" => "\u{13}",
-        b"Lcaron" => "\u{13}",
-        b"lcaron" => "\u{13}",
-        b"Lmidot" => "\u{13}",
-        b"lmidot" => "\u{14}",
-        b"Lstrok" => "\u{14}",
-        b"lstrok" => "\u{14}",
-        b"Nacute" => "\u{14}",
-        b"nacute" => "\u{14}",
-        b"Ncedil" => "\u{14}",
-        b"ncedil" => "\u{14}",
-        b"Ncaron" => "\u{14}",
-        b"ncaron" => "\u{14}",
-        b"napos" => "\u{14}",
-        b"ENG" => "\u{14}",
-        b"eng" => "\u{14}",
-        b"Omacr" => "\u{14}",
-        b"omacr" => "\u{14}",
-        b"Odblac" => "\u{15}",
-        b"odblac" => "\u{15}",
-        b"OElig" => "\u{15}",
-        b"oelig" => "\u{15}",
-        b"Racute" => "\u{15}",
-        b"racute" => "\u{15}",
-        b"Rcedil" => "\u{15}",
-        b"rcedil" => "\u{15}",
-        b"Rcaron" => "\u{15}",
-        b"rcaron" => "\u{15}",
-        b"Sacute" => "\u{15}",
-        b"sacute" => "\u{15}",
-        b"Scirc" => "\u{15}",
-        b"scirc" => "\u{15}",
-        b"Scedil" => "\u{15}",
-        b"scedil" => "\u{15}",
-        b"Scaron" => "\u{16}",
-        b"scaron" => "\u{16}",
-        b"Tcedil" => "\u{16}",
-        b"tcedil" => "\u{16}",
-        b"Tcaron" => "\u{16}",
-        b"tcaron" => "\u{16}",
-        b"Tstrok" => "\u{16}",
-        b"tstrok" => "\u{16}",
-        b"Utilde" => "\u{16}",
-        b"utilde" => "\u{16}",
-        b"Umacr" => "\u{16}",
-        b"umacr" => "\u{16}",
-        b"Ubreve" => "\u{16}",
-        b"ubreve" => "\u{16}",
-        b"Uring" => "\u{16}",
-        b"uring" => "\u{16}",
-        b"Udblac" => "\u{17}",
-        b"udblac" => "\u{17}",
-        b"Uogon" => "\u{17}",
-        b"uogon" => "\u{17}",
-        b"Wcirc" => "\u{17}",
-        b"wcirc" => "\u{17}",
-        b"Ycirc" => "\u{17}",
-        b"ycirc" => "\u{17}",
-        b"Yuml" => "\u{17}",
-        b"Zacute" => "\u{17}",
-        b"zacute" => "\u{17}",
-        b"Zdot" => "\u{17}",
-        b"zdot" => "\u{17}",
-        b"Zcaron" => "\u{17}",
-        b"zcaron" => "\u{17}",
-        b"fnof" => "\u{19}",
-        b"imped" => "\u{1B}",
-        b"gacute" => "\u{1F}",
-        b"jmath" => "\u{23}",
-        b"circ" => "\u{2C}",
-        b"caron" | b"Hacek" => "\u{2C}",
-        b"breve" | b"Breve" => "\u{2D}",
-        b"dot" | b"DiacriticalDot" => "\u{2D}",
-        b"ring" => "\u{2D}",
-        b"ogon" => "\u{2D}",
-        b"tilde" | b"DiacriticalTilde" => "\u{2D}",
-        b"dblac" | b"DiacriticalDoubleAcute" => "\u{2D}",
-        b"DownBreve" => "\u{31}",
-        b"UnderBar" => "\u{33}",
-        b"Alpha" => "\u{39}",
-        b"Beta" => "\u{39}",
-        b"Gamma" => "\u{39}",
-        b"Delta" => "\u{39}",
-        b"Epsilon" => "\u{39}",
-        b"Zeta" => "\u{39}",
-        b"Eta" => "\u{39}",
-        b"Theta" => "\u{39}",
-        b"Iota" => "\u{39}",
-        b"Kappa" => "\u{39}",
-        b"Lambda" => "\u{39}",
-        b"Mu" => "\u{39}",
-        b"Nu" => "\u{39}",
-        b"Xi" => "\u{39}",
-        b"Omicron" => "\u{39}",
-        b"Pi" => "\u{3A}",
-        b"Rho" => "\u{3A}",
-        b"Sigma" => "\u{3A}",
-        b"Tau" => "\u{3A}",
-        b"Upsilon" => "\u{3A}",
-        b"Phi" => "\u{3A}",
-        b"Chi" => "\u{3A}",
-        b"Psi" => "\u{3A}",
-        b"Omega" => "\u{3A}",
-        b"alpha" => "\u{3B}",
-        b"beta" => "\u{3B}",
-        b"gamma" => "\u{3B}",
-        b"delta" => "\u{3B}",
-        b"epsiv" | b"varepsilon" | b"epsilon" => "\u{3B}",
-        b"zeta" => "\u{3B}",
-        b"eta" => "\u{3B}",
-        b"theta" => "\u{3B}",
-        b"iota" => "\u{3B}",
-        b"kappa" => "\u{3B}",
-        b"lambda" => "\u{3B}",
-        b"mu" => "\u{3B}",
-        b"nu" => "\u{3B}",
-        b"xi" => "\u{3B}",
-        b"omicron" => "\u{3B}",
-        b"pi" => "\u{3C}",
-        b"rho" => "\u{3C}",
-        b"sigmav" | b"varsigma" | b"sigmaf" => "\u{3C}",
-        b"sigma" => "\u{3C}",
-        b"tau" => "\u{3C}",
-        b"upsi" | b"upsilon" => "\u{3C}",
-        b"phi" | b"phiv" | b"varphi" => "\u{3C}",
-        b"chi" => "\u{3C}",
-        b"psi" => "\u{3C}",
-        b"omega" => "\u{3C}",
-        b"thetav" | b"vartheta" | b"thetasym" => "\u{3D}",
-        b"Upsi" | b"upsih" => "\u{3D}",
-        b"straightphi" => "\u{3D}",
-        b"piv" | b"varpi" => "\u{3D}",
-        b"Gammad" => "\u{3D}",
-        b"gammad" | b"digamma" => "\u{3D}",
-        b"kappav" | b"varkappa" => "\u{3F}",
-        b"rhov" | b"varrho" => "\u{3F}",
-        b"epsi" | b"straightepsilon" => "\u{3F}",
-        b"bepsi" | b"backepsilon" => "\u{3F}",
-        b"IOcy" => "\u{40}",
-        b"DJcy" => "\u{40}",
-        b"GJcy" => "\u{40}",
-        b"Jukcy" => "\u{40}",
-        b"DScy" => "\u{40}",
-        b"Iukcy" => "\u{40}",
-        b"YIcy" => "\u{40}",
-        b"Jsercy" => "\u{40}",
-        b"LJcy" => "\u{40}",
-        b"NJcy" => "\u{40}",
-        b"TSHcy" => "\u{40}",
-        b"KJcy" => "\u{40}",
-        b"Ubrcy" => "\u{40}",
-        b"DZcy" => "\u{40}",
-        b"Acy" => "\u{41}",
-        b"Bcy" => "\u{41}",
-        b"Vcy" => "\u{41}",
-        b"Gcy" => "\u{41}",
-        b"Dcy" => "\u{41}",
-        b"IEcy" => "\u{41}",
-        b"ZHcy" => "\u{41}",
-        b"Zcy" => "\u{41}",
-        b"Icy" => "\u{41}",
-        b"Jcy" => "\u{41}",
-        b"Kcy" => "\u{41}",
-        b"Lcy" => "\u{41}",
-        b"Mcy" => "\u{41}",
-        b"Ncy" => "\u{41}",
-        b"Ocy" => "\u{41}",
-        b"Pcy" => "\u{41}",
-        b"Rcy" => "\u{42}",
-        b"Scy" => "\u{42}",
-        b"Tcy" => "\u{42}",
-        b"Ucy" => "\u{42}",
-        b"Fcy" => "\u{42}",
-        b"KHcy" => "\u{42}",
-        b"TScy" => "\u{42}",
-        b"CHcy" => "\u{42}",
-        b"SHcy" => "\u{42}",
-        b"SHCHcy" => "\u{42}",
-        b"HARDcy" => "\u{42}",
-        b"Ycy" => "\u{42}",
-        b"SOFTcy" => "\u{42}",
-        b"Ecy" => "\u{42}",
-        b"YUcy" => "\u{42}",
-        b"YAcy" => "\u{42}",
-        b"acy" => "\u{43}",
-        b"bcy" => "\u{43}",
-        b"vcy" => "\u{43}",
-        b"gcy" => "\u{43}",
-        b"dcy" => "\u{43}",
-        b"iecy" => "\u{43}",
-        b"zhcy" => "\u{43}",
-        b"zcy" => "\u{43}",
-        b"icy" => "\u{43}",
-        b"jcy" => "\u{43}",
-        b"kcy" => "\u{43}",
-        b"lcy" => "\u{43}",
-        b"mcy" => "\u{43}",
-        b"ncy" => "\u{43}",
-        b"ocy" => "\u{43}",
-        b"pcy" => "\u{43}",
-        b"rcy" => "\u{44}",
-        b"scy" => "\u{44}",
-        b"tcy" => "\u{44}",
-        b"ucy" => "\u{44}",
-        b"fcy" => "\u{44}",
-        b"khcy" => "\u{44}",
-        b"tscy" => "\u{44}",
-        b"chcy" => "\u{44}",
-        b"shcy" => "\u{44}",
-        b"shchcy" => "\u{44}",
-        b"hardcy" => "\u{44}",
-        b"ycy" => "\u{44}",
-        b"softcy" => "\u{44}",
-        b"ecy" => "\u{44}",
-        b"yucy" => "\u{44}",
-        b"yacy" => "\u{44}",
-        b"iocy" => "\u{45}",
-        b"djcy" => "\u{45}",
-        b"gjcy" => "\u{45}",
-        b"jukcy" => "\u{45}",
-        b"dscy" => "\u{45}",
-        b"iukcy" => "\u{45}",
-        b"yicy" => "\u{45}",
-        b"jsercy" => "\u{45}",
-        b"ljcy" => "\u{45}",
-        b"njcy" => "\u{45}",
-        b"tshcy" => "\u{45}",
-        b"kjcy" => "\u{45}",
-        b"ubrcy" => "\u{45}",
-        b"dzcy" => "\u{45}",
+        b"Amacr" => "\u{100}",
+        b"amacr" => "\u{101}",
+        b"Abreve" => "\u{102}",
+        b"abreve" => "\u{103}",
+        b"Aogon" => "\u{104}",
+        b"aogon" => "\u{105}",
+        b"Cacute" => "\u{106}",
+        b"cacute" => "\u{107}",
+        b"Ccirc" => "\u{108}",
+        b"ccirc" => "\u{109}",
+        b"Cdot" => "\u{10A}",
+        b"cdot" => "\u{10B}",
+        b"Ccaron" => "\u{10C}",
+        b"ccaron" => "\u{10D}",
+        b"Dcaron" => "\u{10E}",
+        b"dcaron" => "\u{10F}",
+        b"Dstrok" => "\u{110}",
+        b"dstrok" => "\u{111}",
+        b"Emacr" => "\u{112}",
+        b"emacr" => "\u{113}",
+        b"Edot" => "\u{116}",
+        b"edot" => "\u{117}",
+        b"Eogon" => "\u{118}",
+        b"eogon" => "\u{119}",
+        b"Ecaron" => "\u{11A}",
+        b"ecaron" => "\u{11B}",
+        b"Gcirc" => "\u{11C}",
+        b"gcirc" => "\u{11D}",
+        b"Gbreve" => "\u{11E}",
+        b"gbreve" => "\u{11F}",
+        b"Gdot" => "\u{120}",
+        b"gdot" => "\u{121}",
+        b"Gcedil" => "\u{122}",
+        b"Hcirc" => "\u{124}",
+        b"hcirc" => "\u{125}",
+        b"Hstrok" => "\u{126}",
+        b"hstrok" => "\u{127}",
+        b"Itilde" => "\u{128}",
+        b"itilde" => "\u{129}",
+        b"Imacr" => "\u{12A}",
+        b"imacr" => "\u{12B}",
+        b"Iogon" => "\u{12E}",
+        b"iogon" => "\u{12F}",
+        b"Idot" => "\u{130}",
+        b"imath" | b"inodot" => "\u{131}",
+        b"IJlig" => "\u{132}",
+        b"ijlig" => "\u{133}",
+        b"Jcirc" => "\u{134}",
+        b"jcirc" => "\u{135}",
+        b"Kcedil" => "\u{136}",
+        b"kcedil" => "\u{137}",
+        b"kgreen" => "\u{138}",
+        b"Lacute" => "\u{139}",
+        b"lacute" => "\u{13A}",
+        b"Lcedil" => "\u{13B}",
+        b"lcedil" => "\u{13C}",
+        b"Lcaron" => "\u{13D}",
+        b"lcaron" => "\u{13E}",
+        b"Lmidot" => "\u{13F}",
+        b"lmidot" => "\u{140}",
+        b"Lstrok" => "\u{141}",
+        b"lstrok" => "\u{142}",
+        b"Nacute" => "\u{143}",
+        b"nacute" => "\u{144}",
+        b"Ncedil" => "\u{145}",
+        b"ncedil" => "\u{146}",
+        b"Ncaron" => "\u{147}",
+        b"ncaron" => "\u{148}",
+        b"napos" => "\u{149}",
+        b"ENG" => "\u{14A}",
+        b"eng" => "\u{14B}",
+        b"Omacr" => "\u{14C}",
+        b"omacr" => "\u{14D}",
+        b"Odblac" => "\u{150}",
+        b"odblac" => "\u{151}",
+        b"OElig" => "\u{152}",
+        b"oelig" => "\u{153}",
+        b"Racute" => "\u{154}",
+        b"racute" => "\u{155}",
+        b"Rcedil" => "\u{156}",
+        b"rcedil" => "\u{157}",
+        b"Rcaron" => "\u{158}",
+        b"rcaron" => "\u{159}",
+        b"Sacute" => "\u{15A}",
+        b"sacute" => "\u{15B}",
+        b"Scirc" => "\u{15C}",
+        b"scirc" => "\u{15D}",
+        b"Scedil" => "\u{15E}",
+        b"scedil" => "\u{15F}",
+        b"Scaron" => "\u{160}",
+        b"scaron" => "\u{161}",
+        b"Tcedil" => "\u{162}",
+        b"tcedil" => "\u{163}",
+        b"Tcaron" => "\u{164}",
+        b"tcaron" => "\u{165}",
+        b"Tstrok" => "\u{166}",
+        b"tstrok" => "\u{167}",
+        b"Utilde" => "\u{168}",
+        b"utilde" => "\u{169}",
+        b"Umacr" => "\u{16A}",
+        b"umacr" => "\u{16B}",
+        b"Ubreve" => "\u{16C}",
+        b"ubreve" => "\u{16D}",
+        b"Uring" => "\u{16E}",
+        b"uring" => "\u{16F}",
+        b"Udblac" => "\u{170}",
+        b"udblac" => "\u{171}",
+        b"Uogon" => "\u{172}",
+        b"uogon" => "\u{173}",
+        b"Wcirc" => "\u{174}",
+        b"wcirc" => "\u{175}",
+        b"Ycirc" => "\u{176}",
+        b"ycirc" => "\u{177}",
+        b"Yuml" => "\u{178}",
+        b"Zacute" => "\u{179}",
+        b"zacute" => "\u{17A}",
+        b"Zdot" => "\u{17B}",
+        b"zdot" => "\u{17C}",
+        b"Zcaron" => "\u{17D}",
+        b"zcaron" => "\u{17E}",
+        b"fnof" => "\u{192}",
+        b"imped" => "\u{1B5}",
+        b"gacute" => "\u{1F5}",
+        b"jmath" => "\u{237}",
+        b"circ" => "\u{2C6}",
+        b"caron" | b"Hacek" => "\u{2C7}",
+        b"Breve" | b"breve" => "\u{2D8}",
+        b"DiacriticalDot" | b"dot" => "\u{2D9}",
+        b"ring" => "\u{2DA}",
+        b"ogon" => "\u{2DB}",
+        b"DiacriticalTilde" | b"tilde" => "\u{2DC}",
+        b"dblac" | b"DiacriticalDoubleAcute" => "\u{2DD}",
+        b"DownBreve" => "\u{311}",
+        b"Alpha" => "\u{391}",
+        b"Beta" => "\u{392}",
+        b"Gamma" => "\u{393}",
+        b"Delta" => "\u{394}",
+        b"Epsilon" => "\u{395}",
+        b"Zeta" => "\u{396}",
+        b"Eta" => "\u{397}",
+        b"Theta" => "\u{398}",
+        b"Iota" => "\u{399}",
+        b"Kappa" => "\u{39A}",
+        b"Lambda" => "\u{39B}",
+        b"Mu" => "\u{39C}",
+        b"Nu" => "\u{39D}",
+        b"Xi" => "\u{39E}",
+        b"Omicron" => "\u{39F}",
+        b"Pi" => "\u{3A0}",
+        b"Rho" => "\u{3A1}",
+        b"Sigma" => "\u{3A3}",
+        b"Tau" => "\u{3A4}",
+        b"Upsilon" => "\u{3A5}",
+        b"Phi" => "\u{3A6}",
+        b"Chi" => "\u{3A7}",
+        b"Psi" => "\u{3A8}",
+        b"ohm" | b"Omega" => "\u{3A9}",
+        b"alpha" => "\u{3B1}",
+        b"beta" => "\u{3B2}",
+        b"gamma" => "\u{3B3}",
+        b"delta" => "\u{3B4}",
+        b"epsi" | b"epsilon" => "\u{3B5}",
+        b"zeta" => "\u{3B6}",
+        b"eta" => "\u{3B7}",
+        b"theta" => "\u{3B8}",
+        b"iota" => "\u{3B9}",
+        b"kappa" => "\u{3BA}",
+        b"lambda" => "\u{3BB}",
+        b"mu" => "\u{3BC}",
+        b"nu" => "\u{3BD}",
+        b"xi" => "\u{3BE}",
+        b"omicron" => "\u{3BF}",
+        b"pi" => "\u{3C0}",
+        b"rho" => "\u{3C1}",
+        b"sigmaf" | b"sigmav" | b"varsigma" => "\u{3C2}",
+        b"sigma" => "\u{3C3}",
+        b"tau" => "\u{3C4}",
+        b"upsi" | b"upsilon" => "\u{3C5}",
+        b"phi" => "\u{3C6}",
+        b"chi" => "\u{3C7}",
+        b"psi" => "\u{3C8}",
+        b"omega" => "\u{3C9}",
+        b"thetasym" | b"thetav" | b"vartheta" => "\u{3D1}",
+        b"Upsi" | b"upsih" => "\u{3D2}",
+        b"phiv" | b"straightphi" | b"varphi" => "\u{3D5}",
+        b"piv" | b"varpi" => "\u{3D6}",
+        b"Gammad" => "\u{3DC}",
+        b"digamma" | b"gammad" => "\u{3DD}",
+        b"kappav" | b"varkappa" => "\u{3F0}",
+        b"rhov" | b"varrho" => "\u{3F1}",
+        b"epsiv" | b"straightepsilon" | b"varepsilon" => "\u{3F5}",
+        b"backepsilon" | b"bepsi" => "\u{3F6}",
+        b"IOcy" => "\u{401}",
+        b"DJcy" => "\u{402}",
+        b"GJcy" => "\u{403}",
+        b"Jukcy" => "\u{404}",
+        b"DScy" => "\u{405}",
+        b"Iukcy" => "\u{406}",
+        b"YIcy" => "\u{407}",
+        b"Jsercy" => "\u{408}",
+        b"LJcy" => "\u{409}",
+        b"NJcy" => "\u{40A}",
+        b"TSHcy" => "\u{40B}",
+        b"KJcy" => "\u{40C}",
+        b"Ubrcy" => "\u{40E}",
+        b"DZcy" => "\u{40F}",
+        b"Acy" => "\u{410}",
+        b"Bcy" => "\u{411}",
+        b"Vcy" => "\u{412}",
+        b"Gcy" => "\u{413}",
+        b"Dcy" => "\u{414}",
+        b"IEcy" => "\u{415}",
+        b"ZHcy" => "\u{416}",
+        b"Zcy" => "\u{417}",
+        b"Icy" => "\u{418}",
+        b"Jcy" => "\u{419}",
+        b"Kcy" => "\u{41A}",
+        b"Lcy" => "\u{41B}",
+        b"Mcy" => "\u{41C}",
+        b"Ncy" => "\u{41D}",
+        b"Ocy" => "\u{41E}",
+        b"Pcy" => "\u{41F}",
+        b"Rcy" => "\u{420}",
+        b"Scy" => "\u{421}",
+        b"Tcy" => "\u{422}",
+        b"Ucy" => "\u{423}",
+        b"Fcy" => "\u{424}",
+        b"KHcy" => "\u{425}",
+        b"TScy" => "\u{426}",
+        b"CHcy" => "\u{427}",
+        b"SHcy" => "\u{428}",
+        b"SHCHcy" => "\u{429}",
+        b"HARDcy" => "\u{42A}",
+        b"Ycy" => "\u{42B}",
+        b"SOFTcy" => "\u{42C}",
+        b"Ecy" => "\u{42D}",
+        b"YUcy" => "\u{42E}",
+        b"YAcy" => "\u{42F}",
+        b"acy" => "\u{430}",
+        b"bcy" => "\u{431}",
+        b"vcy" => "\u{432}",
+        b"gcy" => "\u{433}",
+        b"dcy" => "\u{434}",
+        b"iecy" => "\u{435}",
+        b"zhcy" => "\u{436}",
+        b"zcy" => "\u{437}",
+        b"icy" => "\u{438}",
+        b"jcy" => "\u{439}",
+        b"kcy" => "\u{43A}",
+        b"lcy" => "\u{43B}",
+        b"mcy" => "\u{43C}",
+        b"ncy" => "\u{43D}",
+        b"ocy" => "\u{43E}",
+        b"pcy" => "\u{43F}",
+        b"rcy" => "\u{440}",
+        b"scy" => "\u{441}",
+        b"tcy" => "\u{442}",
+        b"ucy" => "\u{443}",
+        b"fcy" => "\u{444}",
+        b"khcy" => "\u{445}",
+        b"tscy" => "\u{446}",
+        b"chcy" => "\u{447}",
+        b"shcy" => "\u{448}",
+        b"shchcy" => "\u{449}",
+        b"hardcy" => "\u{44A}",
+        b"ycy" => "\u{44B}",
+        b"softcy" => "\u{44C}",
+        b"ecy" => "\u{44D}",
+        b"yucy" => "\u{44E}",
+        b"yacy" => "\u{44F}",
+        b"iocy" => "\u{451}",
+        b"djcy" => "\u{452}",
+        b"gjcy" => "\u{453}",
+        b"jukcy" => "\u{454}",
+        b"dscy" => "\u{455}",
+        b"iukcy" => "\u{456}",
+        b"yicy" => "\u{457}",
+        b"jsercy" => "\u{458}",
+        b"ljcy" => "\u{459}",
+        b"njcy" => "\u{45A}",
+        b"tshcy" => "\u{45B}",
+        b"kjcy" => "\u{45C}",
+        b"ubrcy" => "\u{45E}",
+        b"dzcy" => "\u{45F}",
         b"ensp" => "\u{2002}",
         b"emsp" => "\u{2003}",
         b"emsp13" => "\u{2004}",
@@ -611,26 +1398,26 @@ const fn named_entity(name: &[u8]) -> Option<&str> {
         b"puncsp" => "\u{2008}",
         b"thinsp" | b"ThinSpace" => "\u{2009}",
         b"hairsp" | b"VeryThinSpace" => "\u{200A}",
-        b"ZeroWidthSpace"
-        | b"NegativeVeryThinSpace"
+        b"NegativeMediumSpace"
+        | b"NegativeThickSpace"
         | b"NegativeThinSpace"
-        | b"NegativeMediumSpace"
-        | b"NegativeThickSpace" => "\u{200B}",
+        | b"NegativeVeryThinSpace"
+        | b"ZeroWidthSpace" => "\u{200B}",
         b"zwnj" => "\u{200C}",
         b"zwj" => "\u{200D}",
         b"lrm" => "\u{200E}",
         b"rlm" => "\u{200F}",
-        b"hyphen" | b"dash" => "\u{2010}",
+        b"dash" | b"hyphen" => "\u{2010}",
         b"ndash" => "\u{2013}",
         b"mdash" => "\u{2014}",
         b"horbar" => "\u{2015}",
         b"Verbar" | b"Vert" => "\u{2016}",
         b"lsquo" | b"OpenCurlyQuote" => "\u{2018}",
-        b"rsquo" | b"rsquor" | b"CloseCurlyQuote" => "\u{2019}",
+        b"CloseCurlyQuote" | b"rsquo" | b"rsquor" => "\u{2019}",
         b"lsquor" | b"sbquo" => "\u{201A}",
         b"ldquo" | b"OpenCurlyDoubleQuote" => "\u{201C}",
-        b"rdquo" | b"rdquor" | b"CloseCurlyDoubleQuote" => "\u{201D}",
-        b"ldquor" | b"bdquo" => "\u{201E}",
+        b"CloseCurlyDoubleQuote" | b"rdquo" | b"rdquor" => "\u{201D}",
+        b"bdquo" | b"ldquor" => "\u{201E}",
         b"dagger" => "\u{2020}",
         b"Dagger" | b"ddagger" => "\u{2021}",
         b"bull" | b"bullet" => "\u{2022}",
@@ -641,67 +1428,66 @@ const fn named_entity(name: &[u8]) -> Option<&str> {
         b"prime" => "\u{2032}",
         b"Prime" => "\u{2033}",
         b"tprime" => "\u{2034}",
-        b"bprime" | b"backprime" => "\u{2035}",
+        b"backprime" | b"bprime" => "\u{2035}",
         b"lsaquo" => "\u{2039}",
         b"rsaquo" => "\u{203A}",
-        b"oline" => "\u{203E}",
+        b"oline" | b"OverBar" => "\u{203E}",
         b"caret" => "\u{2041}",
         b"hybull" => "\u{2043}",
         b"frasl" => "\u{2044}",
         b"bsemi" => "\u{204F}",
         b"qprime" => "\u{2057}",
         b"MediumSpace" => "\u{205F}",
+        b"ThickSpace" => "\u{205F}\u{200A}",
         b"NoBreak" => "\u{2060}",
-        b"ApplyFunction" | b"af" => "\u{2061}",
+        b"af" | b"ApplyFunction" => "\u{2061}",
         b"InvisibleTimes" | b"it" => "\u{2062}",
-        b"InvisibleComma" | b"ic" => "\u{2063}",
+        b"ic" | b"InvisibleComma" => "\u{2063}",
         b"euro" => "\u{20AC}",
         b"tdot" | b"TripleDot" => "\u{20DB}",
         b"DotDot" => "\u{20DC}",
-        b"Copf" | b"complexes" => "\u{2102}",
+        b"complexes" | b"Copf" => "\u{2102}",
         b"incare" => "\u{2105}",
         b"gscr" => "\u{210A}",
         b"hamilt" | b"HilbertSpace" | b"Hscr" => "\u{210B}",
         b"Hfr" | b"Poincareplane" => "\u{210C}",
-        b"quaternions" | b"Hopf" => "\u{210D}",
+        b"Hopf" | b"quaternions" => "\u{210D}",
         b"planckh" => "\u{210E}",
-        b"planck" | b"hbar" | b"plankv" | b"hslash" => "\u{210F}",
-        b"Iscr" | b"imagline" => "\u{2110}",
-        b"image" | b"Im" | b"imagpart" | b"Ifr" => "\u{2111}",
-        b"Lscr" | b"lagran" | b"Laplacetrf" => "\u{2112}",
+        b"hbar" | b"hslash" | b"planck" | b"plankv" => "\u{210F}",
+        b"imagline" | b"Iscr" => "\u{2110}",
+        b"Ifr" | b"Im" | b"image" | b"imagpart" => "\u{2111}",
+        b"lagran" | b"Laplacetrf" | b"Lscr" => "\u{2112}",
         b"ell" => "\u{2113}",
-        b"Nopf" | b"naturals" => "\u{2115}",
+        b"naturals" | b"Nopf" => "\u{2115}",
         b"numero" => "\u{2116}",
         b"copysr" => "\u{2117}",
         b"weierp" | b"wp" => "\u{2118}",
         b"Popf" | b"primes" => "\u{2119}",
-        b"rationals" | b"Qopf" => "\u{211A}",
-        b"Rscr" | b"realine" => "\u{211B}",
-        b"real" | b"Re" | b"realpart" | b"Rfr" => "\u{211C}",
+        b"Qopf" | b"rationals" => "\u{211A}",
+        b"realine" | b"Rscr" => "\u{211B}",
+        b"Re" | b"real" | b"realpart" | b"Rfr" => "\u{211C}",
         b"reals" | b"Ropf" => "\u{211D}",
         b"rx" => "\u{211E}",
-        b"trade" | b"TRADE" => "\u{2122}",
+        b"TRADE" | b"trade" => "\u{2122}",
         b"integers" | b"Zopf" => "\u{2124}",
-        b"ohm" => "\u{2126}",
         b"mho" => "\u{2127}",
-        b"Zfr" | b"zeetrf" => "\u{2128}",
+        b"zeetrf" | b"Zfr" => "\u{2128}",
         b"iiota" => "\u{2129}",
-        b"angst" => "\u{212B}",
         b"bernou" | b"Bernoullis" | b"Bscr" => "\u{212C}",
-        b"Cfr" | b"Cayleys" => "\u{212D}",
+        b"Cayleys" | b"Cfr" => "\u{212D}",
         b"escr" => "\u{212F}",
         b"Escr" | b"expectation" => "\u{2130}",
-        b"Fscr" | b"Fouriertrf" => "\u{2131}",
-        b"phmmat" | b"Mellintrf" | b"Mscr" => "\u{2133}",
+        b"Fouriertrf" | b"Fscr" => "\u{2131}",
+        b"Mellintrf" | b"Mscr" | b"phmmat" => "\u{2133}",
         b"order" | b"orderof" | b"oscr" => "\u{2134}",
         b"alefsym" | b"aleph" => "\u{2135}",
         b"beth" => "\u{2136}",
         b"gimel" => "\u{2137}",
         b"daleth" => "\u{2138}",
         b"CapitalDifferentialD" | b"DD" => "\u{2145}",
-        b"DifferentialD" | b"dd" => "\u{2146}",
-        b"ExponentialE" | b"exponentiale" | b"ee" => "\u{2147}",
-        b"ImaginaryI" | b"ii" => "\u{2148}",
+        b"dd" | b"DifferentialD" => "\u{2146}",
+        b"ee" | b"ExponentialE" | b"exponentiale" => "\u{2147}",
+        b"ii" | b"ImaginaryI" => "\u{2148}",
         b"frac13" => "\u{2153}",
         b"frac23" => "\u{2154}",
         b"frac15" => "\u{2155}",
@@ -714,19 +1500,20 @@ const fn named_entity(name: &[u8]) -> Option<&str> {
         b"frac38" => "\u{215C}",
         b"frac58" => "\u{215D}",
         b"frac78" => "\u{215E}",
-        b"larr" | b"leftarrow" | b"LeftArrow" | b"slarr" | b"ShortLeftArrow" => "\u{2190}",
-        b"uarr" | b"uparrow" | b"UpArrow" | b"ShortUpArrow" => "\u{2191}",
-        b"rarr" | b"rightarrow" | b"RightArrow" | b"srarr" | b"ShortRightArrow" => "\u{2192}",
-        b"darr" | b"downarrow" | b"DownArrow" | b"ShortDownArrow" => "\u{2193}",
-        b"harr" | b"leftrightarrow" | b"LeftRightArrow" => "\u{2194}",
-        b"varr" | b"updownarrow" | b"UpDownArrow" => "\u{2195}",
-        b"nwarr" | b"UpperLeftArrow" | b"nwarrow" => "\u{2196}",
-        b"nearr" | b"UpperRightArrow" | b"nearrow" => "\u{2197}",
-        b"searr" | b"searrow" | b"LowerRightArrow" => "\u{2198}",
-        b"swarr" | b"swarrow" | b"LowerLeftArrow" => "\u{2199}",
+        b"larr" | b"LeftArrow" | b"leftarrow" | b"ShortLeftArrow" | b"slarr" => "\u{2190}",
+        b"ShortUpArrow" | b"uarr" | b"UpArrow" | b"uparrow" => "\u{2191}",
+        b"rarr" | b"RightArrow" | b"rightarrow" | b"ShortRightArrow" | b"srarr" => "\u{2192}",
+        b"darr" | b"DownArrow" | b"downarrow" | b"ShortDownArrow" => "\u{2193}",
+        b"harr" | b"LeftRightArrow" | b"leftrightarrow" => "\u{2194}",
+        b"UpDownArrow" | b"updownarrow" | b"varr" => "\u{2195}",
+        b"nwarr" | b"nwarrow" | b"UpperLeftArrow" => "\u{2196}",
+        b"nearr" | b"nearrow" | b"UpperRightArrow" => "\u{2197}",
+        b"LowerRightArrow" | b"searr" | b"searrow" => "\u{2198}",
+        b"LowerLeftArrow" | b"swarr" | b"swarrow" => "\u{2199}",
         b"nlarr" | b"nleftarrow" => "\u{219A}",
         b"nrarr" | b"nrightarrow" => "\u{219B}",
         b"rarrw" | b"rightsquigarrow" => "\u{219D}",
+        b"nrarrw" => "\u{219D}\u{338}",
         b"Larr" | b"twoheadleftarrow" => "\u{219E}",
         b"Uarr" => "\u{219F}",
         b"Rarr" | b"twoheadrightarrow" => "\u{21A0}",
@@ -734,50 +1521,50 @@ const fn named_entity(name: &[u8]) -> Option<&str> {
         b"larrtl" | b"leftarrowtail" => "\u{21A2}",
         b"rarrtl" | b"rightarrowtail" => "\u{21A3}",
         b"LeftTeeArrow" | b"mapstoleft" => "\u{21A4}",
-        b"UpTeeArrow" | b"mapstoup" => "\u{21A5}",
-        b"map" | b"RightTeeArrow" | b"mapsto" => "\u{21A6}",
+        b"mapstoup" | b"UpTeeArrow" => "\u{21A5}",
+        b"map" | b"mapsto" | b"RightTeeArrow" => "\u{21A6}",
         b"DownTeeArrow" | b"mapstodown" => "\u{21A7}",
-        b"larrhk" | b"hookleftarrow" => "\u{21A9}",
-        b"rarrhk" | b"hookrightarrow" => "\u{21AA}",
+        b"hookleftarrow" | b"larrhk" => "\u{21A9}",
+        b"hookrightarrow" | b"rarrhk" => "\u{21AA}",
         b"larrlp" | b"looparrowleft" => "\u{21AB}",
-        b"rarrlp" | b"looparrowright" => "\u{21AC}",
+        b"looparrowright" | b"rarrlp" => "\u{21AC}",
         b"harrw" | b"leftrightsquigarrow" => "\u{21AD}",
         b"nharr" | b"nleftrightarrow" => "\u{21AE}",
-        b"lsh" | b"Lsh" => "\u{21B0}",
-        b"rsh" | b"Rsh" => "\u{21B1}",
+        b"Lsh" | b"lsh" => "\u{21B0}",
+        b"Rsh" | b"rsh" => "\u{21B1}",
         b"ldsh" => "\u{21B2}",
         b"rdsh" => "\u{21B3}",
         b"crarr" => "\u{21B5}",
         b"cularr" | b"curvearrowleft" => "\u{21B6}",
         b"curarr" | b"curvearrowright" => "\u{21B7}",
-        b"olarr" | b"circlearrowleft" => "\u{21BA}",
-        b"orarr" | b"circlearrowright" => "\u{21BB}",
-        b"lharu" | b"LeftVector" | b"leftharpoonup" => "\u{21BC}",
-        b"lhard" | b"leftharpoondown" | b"DownLeftVector" => "\u{21BD}",
-        b"uharr" | b"upharpoonright" | b"RightUpVector" => "\u{21BE}",
-        b"uharl" | b"upharpoonleft" | b"LeftUpVector" => "\u{21BF}",
-        b"rharu" | b"RightVector" | b"rightharpoonup" => "\u{21C0}",
-        b"rhard" | b"rightharpoondown" | b"DownRightVector" => "\u{21C1}",
-        b"dharr" | b"RightDownVector" | b"downharpoonright" => "\u{21C2}",
-        b"dharl" | b"LeftDownVector" | b"downharpoonleft" => "\u{21C3}",
-        b"rlarr" | b"rightleftarrows" | b"RightArrowLeftArrow" => "\u{21C4}",
+        b"circlearrowleft" | b"olarr" => "\u{21BA}",
+        b"circlearrowright" | b"orarr" => "\u{21BB}",
+        b"leftharpoonup" | b"LeftVector" | b"lharu" => "\u{21BC}",
+        b"DownLeftVector" | b"leftharpoondown" | b"lhard" => "\u{21BD}",
+        b"RightUpVector" | b"uharr" | b"upharpoonright" => "\u{21BE}",
+        b"LeftUpVector" | b"uharl" | b"upharpoonleft" => "\u{21BF}",
+        b"rharu" | b"rightharpoonup" | b"RightVector" => "\u{21C0}",
+        b"DownRightVector" | b"rhard" | b"rightharpoondown" => "\u{21C1}",
+        b"dharr" | b"downharpoonright" | b"RightDownVector" => "\u{21C2}",
+        b"dharl" | b"downharpoonleft" | b"LeftDownVector" => "\u{21C3}",
+        b"RightArrowLeftArrow" | b"rightleftarrows" | b"rlarr" => "\u{21C4}",
         b"udarr" | b"UpArrowDownArrow" => "\u{21C5}",
-        b"lrarr" | b"leftrightarrows" | b"LeftArrowRightArrow" => "\u{21C6}",
-        b"llarr" | b"leftleftarrows" => "\u{21C7}",
-        b"uuarr" | b"upuparrows" => "\u{21C8}",
-        b"rrarr" | b"rightrightarrows" => "\u{21C9}",
+        b"LeftArrowRightArrow" | b"leftrightarrows" | b"lrarr" => "\u{21C6}",
+        b"leftleftarrows" | b"llarr" => "\u{21C7}",
+        b"upuparrows" | b"uuarr" => "\u{21C8}",
+        b"rightrightarrows" | b"rrarr" => "\u{21C9}",
         b"ddarr" | b"downdownarrows" => "\u{21CA}",
-        b"lrhar" | b"ReverseEquilibrium" | b"leftrightharpoons" => "\u{21CB}",
-        b"rlhar" | b"rightleftharpoons" | b"Equilibrium" => "\u{21CC}",
+        b"leftrightharpoons" | b"lrhar" | b"ReverseEquilibrium" => "\u{21CB}",
+        b"Equilibrium" | b"rightleftharpoons" | b"rlhar" => "\u{21CC}",
         b"nlArr" | b"nLeftarrow" => "\u{21CD}",
         b"nhArr" | b"nLeftrightarrow" => "\u{21CE}",
         b"nrArr" | b"nRightarrow" => "\u{21CF}",
-        b"lArr" | b"Leftarrow" | b"DoubleLeftArrow" => "\u{21D0}",
-        b"uArr" | b"Uparrow" | b"DoubleUpArrow" => "\u{21D1}",
-        b"rArr" | b"Rightarrow" | b"Implies" | b"DoubleRightArrow" => "\u{21D2}",
-        b"dArr" | b"Downarrow" | b"DoubleDownArrow" => "\u{21D3}",
-        b"hArr" | b"Leftrightarrow" | b"DoubleLeftRightArrow" | b"iff" => "\u{21D4}",
-        b"vArr" | b"Updownarrow" | b"DoubleUpDownArrow" => "\u{21D5}",
+        b"DoubleLeftArrow" | b"lArr" | b"Leftarrow" => "\u{21D0}",
+        b"DoubleUpArrow" | b"uArr" | b"Uparrow" => "\u{21D1}",
+        b"DoubleRightArrow" | b"Implies" | b"rArr" | b"Rightarrow" => "\u{21D2}",
+        b"dArr" | b"DoubleDownArrow" | b"Downarrow" => "\u{21D3}",
+        b"DoubleLeftRightArrow" | b"hArr" | b"iff" | b"Leftrightarrow" => "\u{21D4}",
+        b"DoubleUpDownArrow" | b"Updownarrow" | b"vArr" => "\u{21D5}",
         b"nwArr" => "\u{21D6}",
         b"neArr" => "\u{21D7}",
         b"seArr" => "\u{21D8}",
@@ -787,166 +1574,197 @@ const fn named_entity(name: &[u8]) -> Option<&str> {
         b"zigrarr" => "\u{21DD}",
         b"larrb" | b"LeftArrowBar" => "\u{21E4}",
         b"rarrb" | b"RightArrowBar" => "\u{21E5}",
-        b"duarr" | b"DownArrowUpArrow" => "\u{21F5}",
+        b"DownArrowUpArrow" | b"duarr" => "\u{21F5}",
         b"loarr" => "\u{21FD}",
         b"roarr" => "\u{21FE}",
         b"hoarr" => "\u{21FF}",
-        b"forall" | b"ForAll" => "\u{2200}",
+        b"ForAll" | b"forall" => "\u{2200}",
         b"comp" | b"complement" => "\u{2201}",
         b"part" | b"PartialD" => "\u{2202}",
+        b"npart" => "\u{2202}\u{338}",
         b"exist" | b"Exists" => "\u{2203}",
-        b"nexist" | b"NotExists" | b"nexists" => "\u{2204}",
+        b"nexist" | b"nexists" | b"NotExists" => "\u{2204}",
         b"empty" | b"emptyset" | b"emptyv" | b"varnothing" => "\u{2205}",
-        b"nabla" | b"Del" => "\u{2207}",
-        b"isin" | b"isinv" | b"Element" | b"in" => "\u{2208}",
-        b"notin" | b"NotElement" | b"notinva" => "\u{2209}",
-        b"niv" | b"ReverseElement" | b"ni" | b"SuchThat" => "\u{220B}",
+        b"Del" | b"nabla" => "\u{2207}",
+        b"Element" | b"in" | b"isin" | b"isinv" => "\u{2208}",
+        b"NotElement" | b"notin" | b"notinva" => "\u{2209}",
+        b"ni" | b"niv" | b"ReverseElement" | b"SuchThat" => "\u{220B}",
         b"notni" | b"notniva" | b"NotReverseElement" => "\u{220C}",
         b"prod" | b"Product" => "\u{220F}",
         b"coprod" | b"Coproduct" => "\u{2210}",
-        b"sum" | b"Sum" => "\u{2211}",
+        b"Sum" | b"sum" => "\u{2211}",
         b"minus" => "\u{2212}",
-        b"mnplus" | b"mp" | b"MinusPlus" => "\u{2213}",
-        b"plusdo" | b"dotplus" => "\u{2214}",
-        b"setmn" | b"setminus" | b"Backslash" | b"ssetmn" | b"smallsetminus" => "\u{2216}",
+        b"MinusPlus" | b"mnplus" | b"mp" => "\u{2213}",
+        b"dotplus" | b"plusdo" => "\u{2214}",
+        b"Backslash" | b"setminus" | b"setmn" | b"smallsetminus" | b"ssetmn" => "\u{2216}",
         b"lowast" => "\u{2217}",
         b"compfn" | b"SmallCircle" => "\u{2218}",
         b"radic" | b"Sqrt" => "\u{221A}",
-        b"prop" | b"propto" | b"Proportional" | b"vprop" | b"varpropto" => "\u{221D}",
+        b"prop" | b"Proportional" | b"propto" | b"varpropto" | b"vprop" => "\u{221D}",
         b"infin" => "\u{221E}",
         b"angrt" => "\u{221F}",
         b"ang" | b"angle" => "\u{2220}",
+        b"nang" => "\u{2220}\u{20D2}",
         b"angmsd" | b"measuredangle" => "\u{2221}",
         b"angsph" => "\u{2222}",
-        b"mid" | b"VerticalBar" | b"smid" | b"shortmid" => "\u{2223}",
-        b"nmid" | b"NotVerticalBar" | b"nsmid" | b"nshortmid" => "\u{2224}",
-        b"par" | b"parallel" | b"DoubleVerticalBar" | b"spar" | b"shortparallel" => "\u{2225}",
-        b"npar"
-        | b"nparallel"
-        | b"NotDoubleVerticalBar"
-        | b"nspar"
-        | b"nshortparallel" => "\u{2226}",
+        b"mid" | b"shortmid" | b"smid" | b"VerticalBar" => "\u{2223}",
+        b"nmid" | b"NotVerticalBar" | b"nshortmid" | b"nsmid" => "\u{2224}",
+        b"DoubleVerticalBar" | b"par" | b"parallel" | b"shortparallel" | b"spar" => "\u{2225}",
+        b"NotDoubleVerticalBar" | b"npar" | b"nparallel" | b"nshortparallel" | b"nspar" => {
+            "\u{2226}"
+        }
         b"and" | b"wedge" => "\u{2227}",
         b"or" | b"vee" => "\u{2228}",
         b"cap" => "\u{2229}",
+        b"caps" => "\u{2229}\u{FE00}",
         b"cup" => "\u{222A}",
+        b"cups" => "\u{222A}\u{FE00}",
         b"int" | b"Integral" => "\u{222B}",
         b"Int" => "\u{222C}",
-        b"tint" | b"iiint" => "\u{222D}",
-        b"conint" | b"oint" | b"ContourIntegral" => "\u{222E}",
+        b"iiint" | b"tint" => "\u{222D}",
+        b"conint" | b"ContourIntegral" | b"oint" => "\u{222E}",
         b"Conint" | b"DoubleContourIntegral" => "\u{222F}",
         b"Cconint" => "\u{2230}",
         b"cwint" => "\u{2231}",
-        b"cwconint" | b"ClockwiseContourIntegral" => "\u{2232}",
+        b"ClockwiseContourIntegral" | b"cwconint" => "\u{2232}",
         b"awconint" | b"CounterClockwiseContourIntegral" => "\u{2233}",
-        b"there4" | b"therefore" | b"Therefore" => "\u{2234}",
-        b"becaus" | b"because" | b"Because" => "\u{2235}",
+        b"there4" | b"Therefore" | b"therefore" => "\u{2234}",
+        b"becaus" | b"Because" | b"because" => "\u{2235}",
         b"ratio" => "\u{2236}",
         b"Colon" | b"Proportion" => "\u{2237}",
-        b"minusd" | b"dotminus" => "\u{2238}",
+        b"dotminus" | b"minusd" => "\u{2238}",
         b"mDDot" => "\u{223A}",
         b"homtht" => "\u{223B}",
-        b"sim" | b"Tilde" | b"thksim" | b"thicksim" => "\u{223C}",
-        b"bsim" | b"backsim" => "\u{223D}",
+        b"sim" | b"thicksim" | b"thksim" | b"Tilde" => "\u{223C}",
+        b"nvsim" => "\u{223C}\u{20D2}",
+        b"backsim" | b"bsim" => "\u{223D}",
+        b"race" => "\u{223D}\u{331}",
         b"ac" | b"mstpos" => "\u{223E}",
+        b"acE" => "\u{223E}\u{333}",
         b"acd" => "\u{223F}",
-        b"wreath" | b"VerticalTilde" | b"wr" => "\u{2240}",
-        b"nsim" | b"NotTilde" => "\u{2241}",
-        b"esim" | b"EqualTilde" | b"eqsim" => "\u{2242}",
-        b"sime" | b"TildeEqual" | b"simeq" => "\u{2243}",
-        b"nsime" | b"nsimeq" | b"NotTildeEqual" => "\u{2244}",
+        b"VerticalTilde" | b"wr" | b"wreath" => "\u{2240}",
+        b"NotTilde" | b"nsim" => "\u{2241}",
+        b"eqsim" | b"EqualTilde" | b"esim" => "\u{2242}",
+        b"nesim" | b"NotEqualTilde" => "\u{2242}\u{338}",
+        b"sime" | b"simeq" | b"TildeEqual" => "\u{2243}",
+        b"NotTildeEqual" | b"nsime" | b"nsimeq" => "\u{2244}",
         b"cong" | b"TildeFullEqual" => "\u{2245}",
         b"simne" => "\u{2246}",
         b"ncong" | b"NotTildeFullEqual" => "\u{2247}",
-        b"asymp" | b"ap" | b"TildeTilde" | b"approx" | b"thkap" | b"thickapprox" => "\u{2248}",
-        b"nap" | b"NotTildeTilde" | b"napprox" => "\u{2249}",
+        b"ap" | b"approx" | b"asymp" | b"thickapprox" | b"thkap" | b"TildeTilde" => "\u{2248}",
+        b"nap" | b"napprox" | b"NotTildeTilde" => "\u{2249}",
         b"ape" | b"approxeq" => "\u{224A}",
         b"apid" => "\u{224B}",
-        b"bcong" | b"backcong" => "\u{224C}",
+        b"napid" => "\u{224B}\u{338}",
+        b"backcong" | b"bcong" => "\u{224C}",
         b"asympeq" | b"CupCap" => "\u{224D}",
-        b"bump" | b"HumpDownHump" | b"Bumpeq" => "\u{224E}",
-        b"bumpe" | b"HumpEqual" | b"bumpeq" => "\u{224F}",
-        b"esdot" | b"DotEqual" | b"doteq" => "\u{2250}",
-        b"eDot" | b"doteqdot" => "\u{2251}",
+        b"nvap" => "\u{224D}\u{20D2}",
+        b"bump" | b"Bumpeq" | b"HumpDownHump" => "\u{224E}",
+        b"nbump" | b"NotHumpDownHump" => "\u{224E}\u{338}",
+        b"bumpe" | b"bumpeq" | b"HumpEqual" => "\u{224F}",
+        b"nbumpe" | b"NotHumpEqual" => "\u{224F}\u{338}",
+        b"doteq" | b"DotEqual" | b"esdot" => "\u{2250}",
+        b"nedot" => "\u{2250}\u{338}",
+        b"doteqdot" | b"eDot" => "\u{2251}",
         b"efDot" | b"fallingdotseq" => "\u{2252}",
         b"erDot" | b"risingdotseq" => "\u{2253}",
-        b"colone" | b"coloneq" | b"Assign" => "\u{2254}",
+        b"Assign" | b"colone" | b"coloneq" => "\u{2254}",
         b"ecolon" | b"eqcolon" => "\u{2255}",
         b"ecir" | b"eqcirc" => "\u{2256}",
-        b"cire" | b"circeq" => "\u{2257}",
+        b"circeq" | b"cire" => "\u{2257}",
         b"wedgeq" => "\u{2259}",
         b"veeeq" => "\u{225A}",
-        b"trie" | b"triangleq" => "\u{225C}",
+        b"triangleq" | b"trie" => "\u{225C}",
         b"equest" | b"questeq" => "\u{225F}",
         b"ne" | b"NotEqual" => "\u{2260}",
-        b"equiv" | b"Congruent" => "\u{2261}",
+        b"Congruent" | b"equiv" => "\u{2261}",
+        b"bnequiv" => "\u{2261}\u{20E5}",
         b"nequiv" | b"NotCongruent" => "\u{2262}",
         b"le" | b"leq" => "\u{2264}",
-        b"ge" | b"GreaterEqual" | b"geq" => "\u{2265}",
-        b"lE" | b"LessFullEqual" | b"leqq" => "\u{2266}",
-        b"gE" | b"GreaterFullEqual" | b"geqq" => "\u{2267}",
+        b"nvle" => "\u{2264}\u{20D2}",
+        b"ge" | b"geq" | b"GreaterEqual" => "\u{2265}",
+        b"nvge" => "\u{2265}\u{20D2}",
+        b"lE" | b"leqq" | b"LessFullEqual" => "\u{2266}",
+        b"nlE" | b"nleqq" => "\u{2266}\u{338}",
+        b"gE" | b"geqq" | b"GreaterFullEqual" => "\u{2267}",
+        b"ngE" | b"ngeqq" | b"NotGreaterFullEqual" => "\u{2267}\u{338}",
         b"lnE" | b"lneqq" => "\u{2268}",
+        b"lvertneqq" | b"lvnE" => "\u{2268}\u{FE00}",
         b"gnE" | b"gneqq" => "\u{2269}",
-        b"Lt" | b"NestedLessLess" | b"ll" => "\u{226A}",
-        b"Gt" | b"NestedGreaterGreater" | b"gg" => "\u{226B}",
-        b"twixt" | b"between" => "\u{226C}",
+        b"gvertneqq" | b"gvnE" => "\u{2269}\u{FE00}",
+        b"ll" | b"Lt" | b"NestedLessLess" => "\u{226A}",
+        b"nLtv" | b"NotLessLess" => "\u{226A}\u{338}",
+        b"nLt" => "\u{226A}\u{20D2}",
+        b"gg" | b"Gt" | b"NestedGreaterGreater" => "\u{226B}",
+        b"nGtv" | b"NotGreaterGreater" => "\u{226B}\u{338}",
+        b"nGt" => "\u{226B}\u{20D2}",
+        b"between" | b"twixt" => "\u{226C}",
         b"NotCupCap" => "\u{226D}",
-        b"nlt" | b"NotLess" | b"nless" => "\u{226E}",
-        b"ngt" | b"NotGreater" | b"ngtr" => "\u{226F}",
-        b"nle" | b"NotLessEqual" | b"nleq" => "\u{2270}",
-        b"nge" | b"NotGreaterEqual" | b"ngeq" => "\u{2271}",
-        b"lsim" | b"LessTilde" | b"lesssim" => "\u{2272}",
-        b"gsim" | b"gtrsim" | b"GreaterTilde" => "\u{2273}",
+        b"nless" | b"nlt" | b"NotLess" => "\u{226E}",
+        b"ngt" | b"ngtr" | b"NotGreater" => "\u{226F}",
+        b"nle" | b"nleq" | b"NotLessEqual" => "\u{2270}",
+        b"nge" | b"ngeq" | b"NotGreaterEqual" => "\u{2271}",
+        b"lesssim" | b"LessTilde" | b"lsim" => "\u{2272}",
+        b"GreaterTilde" | b"gsim" | b"gtrsim" => "\u{2273}",
         b"nlsim" | b"NotLessTilde" => "\u{2274}",
         b"ngsim" | b"NotGreaterTilde" => "\u{2275}",
-        b"lg" | b"lessgtr" | b"LessGreater" => "\u{2276}",
-        b"gl" | b"gtrless" | b"GreaterLess" => "\u{2277}",
-        b"ntlg" | b"NotLessGreater" => "\u{2278}",
-        b"ntgl" | b"NotGreaterLess" => "\u{2279}",
-        b"pr" | b"Precedes" | b"prec" => "\u{227A}",
-        b"sc" | b"Succeeds" | b"succ" => "\u{227B}",
-        b"prcue" | b"PrecedesSlantEqual" | b"preccurlyeq" => "\u{227C}",
-        b"sccue" | b"SucceedsSlantEqual" | b"succcurlyeq" => "\u{227D}",
-        b"prsim" | b"precsim" | b"PrecedesTilde" => "\u{227E}",
-        b"scsim" | b"succsim" | b"SucceedsTilde" => "\u{227F}",
-        b"npr" | b"nprec" | b"NotPrecedes" => "\u{2280}",
-        b"nsc" | b"nsucc" | b"NotSucceeds" => "\u{2281}",
+        b"LessGreater" | b"lessgtr" | b"lg" => "\u{2276}",
+        b"gl" | b"GreaterLess" | b"gtrless" => "\u{2277}",
+        b"NotLessGreater" | b"ntlg" => "\u{2278}",
+        b"NotGreaterLess" | b"ntgl" => "\u{2279}",
+        b"pr" | b"prec" | b"Precedes" => "\u{227A}",
+        b"sc" | b"succ" | b"Succeeds" => "\u{227B}",
+        b"prcue" | b"preccurlyeq" | b"PrecedesSlantEqual" => "\u{227C}",
+        b"sccue" | b"succcurlyeq" | b"SucceedsSlantEqual" => "\u{227D}",
+        b"PrecedesTilde" | b"precsim" | b"prsim" => "\u{227E}",
+        b"scsim" | b"SucceedsTilde" | b"succsim" => "\u{227F}",
+        b"NotSucceedsTilde" => "\u{227F}\u{338}",
+        b"NotPrecedes" | b"npr" | b"nprec" => "\u{2280}",
+        b"NotSucceeds" | b"nsc" | b"nsucc" => "\u{2281}",
         b"sub" | b"subset" => "\u{2282}",
-        b"sup" | b"supset" | b"Superset" => "\u{2283}",
+        b"NotSubset" | b"nsubset" | b"vnsub" => "\u{2282}\u{20D2}",
+        b"sup" | b"Superset" | b"supset" => "\u{2283}",
+        b"NotSuperset" | b"nsupset" | b"vnsup" => "\u{2283}\u{20D2}",
         b"nsub" => "\u{2284}",
         b"nsup" => "\u{2285}",
-        b"sube" | b"SubsetEqual" | b"subseteq" => "\u{2286}",
-        b"supe" | b"supseteq" | b"SupersetEqual" => "\u{2287}",
-        b"nsube" | b"nsubseteq" | b"NotSubsetEqual" => "\u{2288}",
-        b"nsupe" | b"nsupseteq" | b"NotSupersetEqual" => "\u{2289}",
+        b"sube" | b"subseteq" | b"SubsetEqual" => "\u{2286}",
+        b"supe" | b"SupersetEqual" | b"supseteq" => "\u{2287}",
+        b"NotSubsetEqual" | b"nsube" | b"nsubseteq" => "\u{2288}",
+        b"NotSupersetEqual" | b"nsupe" | b"nsupseteq" => "\u{2289}",
         b"subne" | b"subsetneq" => "\u{228A}",
+        b"varsubsetneq" | b"vsubne" => "\u{228A}\u{FE00}",
         b"supne" | b"supsetneq" => "\u{228B}",
+        b"varsupsetneq" | b"vsupne" => "\u{228B}\u{FE00}",
         b"cupdot" => "\u{228D}",
-        b"uplus" | b"UnionPlus" => "\u{228E}",
-        b"sqsub" | b"SquareSubset" | b"sqsubset" => "\u{228F}",
-        b"sqsup" | b"SquareSuperset" | b"sqsupset" => "\u{2290}",
-        b"sqsube" | b"SquareSubsetEqual" | b"sqsubseteq" => "\u{2291}",
-        b"sqsupe" | b"SquareSupersetEqual" | b"sqsupseteq" => "\u{2292}",
+        b"UnionPlus" | b"uplus" => "\u{228E}",
+        b"sqsub" | b"sqsubset" | b"SquareSubset" => "\u{228F}",
+        b"NotSquareSubset" => "\u{228F}\u{338}",
+        b"sqsup" | b"sqsupset" | b"SquareSuperset" => "\u{2290}",
+        b"NotSquareSuperset" => "\u{2290}\u{338}",
+        b"sqsube" | b"sqsubseteq" | b"SquareSubsetEqual" => "\u{2291}",
+        b"sqsupe" | b"sqsupseteq" | b"SquareSupersetEqual" => "\u{2292}",
         b"sqcap" | b"SquareIntersection" => "\u{2293}",
+        b"sqcaps" => "\u{2293}\u{FE00}",
         b"sqcup" | b"SquareUnion" => "\u{2294}",
-        b"oplus" | b"CirclePlus" => "\u{2295}",
-        b"ominus" | b"CircleMinus" => "\u{2296}",
-        b"otimes" | b"CircleTimes" => "\u{2297}",
+        b"sqcups" => "\u{2294}\u{FE00}",
+        b"CirclePlus" | b"oplus" => "\u{2295}",
+        b"CircleMinus" | b"ominus" => "\u{2296}",
+        b"CircleTimes" | b"otimes" => "\u{2297}",
         b"osol" => "\u{2298}",
-        b"odot" | b"CircleDot" => "\u{2299}",
-        b"ocir" | b"circledcirc" => "\u{229A}",
-        b"oast" | b"circledast" => "\u{229B}",
-        b"odash" | b"circleddash" => "\u{229D}",
-        b"plusb" | b"boxplus" => "\u{229E}",
-        b"minusb" | b"boxminus" => "\u{229F}",
-        b"timesb" | b"boxtimes" => "\u{22A0}",
-        b"sdotb" | b"dotsquare" => "\u{22A1}",
-        b"vdash" | b"RightTee" => "\u{22A2}",
+        b"CircleDot" | b"odot" => "\u{2299}",
+        b"circledcirc" | b"ocir" => "\u{229A}",
+        b"circledast" | b"oast" => "\u{229B}",
+        b"circleddash" | b"odash" => "\u{229D}",
+        b"boxplus" | b"plusb" => "\u{229E}",
+        b"boxminus" | b"minusb" => "\u{229F}",
+        b"boxtimes" | b"timesb" => "\u{22A0}",
+        b"dotsquare" | b"sdotb" => "\u{22A1}",
+        b"RightTee" | b"vdash" => "\u{22A2}",
         b"dashv" | b"LeftTee" => "\u{22A3}",
-        b"top" | b"DownTee" => "\u{22A4}",
-        b"bottom" | b"bot" | b"perp" | b"UpTee" => "\u{22A5}",
+        b"DownTee" | b"top" => "\u{22A4}",
+        b"bot" | b"bottom" | b"perp" | b"UpTee" => "\u{22A5}",
         b"models" => "\u{22A7}",
-        b"vDash" | b"DoubleRightTee" => "\u{22A8}",
+        b"DoubleRightTee" | b"vDash" => "\u{22A8}",
         b"Vdash" => "\u{22A9}",
         b"Vvdash" => "\u{22AA}",
         b"VDash" => "\u{22AB}",
@@ -955,61 +1773,67 @@ const fn named_entity(name: &[u8]) -> Option<&str> {
         b"nVdash" => "\u{22AE}",
         b"nVDash" => "\u{22AF}",
         b"prurel" => "\u{22B0}",
-        b"vltri" | b"vartriangleleft" | b"LeftTriangle" => "\u{22B2}",
-        b"vrtri" | b"vartriangleright" | b"RightTriangle" => "\u{22B3}",
-        b"ltrie" | b"trianglelefteq" | b"LeftTriangleEqual" => "\u{22B4}",
-        b"rtrie" | b"trianglerighteq" | b"RightTriangleEqual" => "\u{22B5}",
+        b"LeftTriangle" | b"vartriangleleft" | b"vltri" => "\u{22B2}",
+        b"RightTriangle" | b"vartriangleright" | b"vrtri" => "\u{22B3}",
+        b"LeftTriangleEqual" | b"ltrie" | b"trianglelefteq" => "\u{22B4}",
+        b"nvltrie" => "\u{22B4}\u{20D2}",
+        b"RightTriangleEqual" | b"rtrie" | b"trianglerighteq" => "\u{22B5}",
+        b"nvrtrie" => "\u{22B5}\u{20D2}",
         b"origof" => "\u{22B6}",
         b"imof" => "\u{22B7}",
-        b"mumap" | b"multimap" => "\u{22B8}",
+        b"multimap" | b"mumap" => "\u{22B8}",
         b"hercon" => "\u{22B9}",
         b"intcal" | b"intercal" => "\u{22BA}",
         b"veebar" => "\u{22BB}",
         b"barvee" => "\u{22BD}",
         b"angrtvb" => "\u{22BE}",
         b"lrtri" => "\u{22BF}",
-        b"xwedge" | b"Wedge" | b"bigwedge" => "\u{22C0}",
-        b"xvee" | b"Vee" | b"bigvee" => "\u{22C1}",
-        b"xcap" | b"Intersection" | b"bigcap" => "\u{22C2}",
-        b"xcup" | b"Union" | b"bigcup" => "\u{22C3}",
-        b"diam" | b"diamond" | b"Diamond" => "\u{22C4}",
+        b"bigwedge" | b"Wedge" | b"xwedge" => "\u{22C0}",
+        b"bigvee" | b"Vee" | b"xvee" => "\u{22C1}",
+        b"bigcap" | b"Intersection" | b"xcap" => "\u{22C2}",
+        b"bigcup" | b"Union" | b"xcup" => "\u{22C3}",
+        b"diam" | b"Diamond" | b"diamond" => "\u{22C4}",
         b"sdot" => "\u{22C5}",
         b"sstarf" | b"Star" => "\u{22C6}",
-        b"divonx" | b"divideontimes" => "\u{22C7}",
+        b"divideontimes" | b"divonx" => "\u{22C7}",
         b"bowtie" => "\u{22C8}",
         b"ltimes" => "\u{22C9}",
         b"rtimes" => "\u{22CA}",
-        b"lthree" | b"leftthreetimes" => "\u{22CB}",
-        b"rthree" | b"rightthreetimes" => "\u{22CC}",
-        b"bsime" | b"backsimeq" => "\u{22CD}",
-        b"cuvee" | b"curlyvee" => "\u{22CE}",
-        b"cuwed" | b"curlywedge" => "\u{22CF}",
+        b"leftthreetimes" | b"lthree" => "\u{22CB}",
+        b"rightthreetimes" | b"rthree" => "\u{22CC}",
+        b"backsimeq" | b"bsime" => "\u{22CD}",
+        b"curlyvee" | b"cuvee" => "\u{22CE}",
+        b"curlywedge" | b"cuwed" => "\u{22CF}",
         b"Sub" | b"Subset" => "\u{22D0}",
         b"Sup" | b"Supset" => "\u{22D1}",
         b"Cap" => "\u{22D2}",
         b"Cup" => "\u{22D3}",
         b"fork" | b"pitchfork" => "\u{22D4}",
         b"epar" => "\u{22D5}",
-        b"ltdot" | b"lessdot" => "\u{22D6}",
+        b"lessdot" | b"ltdot" => "\u{22D6}",
         b"gtdot" | b"gtrdot" => "\u{22D7}",
         b"Ll" => "\u{22D8}",
+        b"nLl" => "\u{22D8}\u{338}",
         b"Gg" | b"ggg" => "\u{22D9}",
-        b"leg" | b"LessEqualGreater" | b"lesseqgtr" => "\u{22DA}",
-        b"gel" | b"gtreqless" | b"GreaterEqualLess" => "\u{22DB}",
+        b"nGg" => "\u{22D9}\u{338}",
+        b"leg" | b"lesseqgtr" | b"LessEqualGreater" => "\u{22DA}",
+        b"lesg" => "\u{22DA}\u{FE00}",
+        b"gel" | b"GreaterEqualLess" | b"gtreqless" => "\u{22DB}",
+        b"gesl" => "\u{22DB}\u{FE00}",
         b"cuepr" | b"curlyeqprec" => "\u{22DE}",
         b"cuesc" | b"curlyeqsucc" => "\u{22DF}",
-        b"nprcue" | b"NotPrecedesSlantEqual" => "\u{22E0}",
-        b"nsccue" | b"NotSucceedsSlantEqual" => "\u{22E1}",
-        b"nsqsube" | b"NotSquareSubsetEqual" => "\u{22E2}",
-        b"nsqsupe" | b"NotSquareSupersetEqual" => "\u{22E3}",
+        b"NotPrecedesSlantEqual" | b"nprcue" => "\u{22E0}",
+        b"NotSucceedsSlantEqual" | b"nsccue" => "\u{22E1}",
+        b"NotSquareSubsetEqual" | b"nsqsube" => "\u{22E2}",
+        b"NotSquareSupersetEqual" | b"nsqsupe" => "\u{22E3}",
         b"lnsim" => "\u{22E6}",
         b"gnsim" => "\u{22E7}",
-        b"prnsim" | b"precnsim" => "\u{22E8}",
+        b"precnsim" | b"prnsim" => "\u{22E8}",
         b"scnsim" | b"succnsim" => "\u{22E9}",
-        b"nltri" | b"ntriangleleft" | b"NotLeftTriangle" => "\u{22EA}",
-        b"nrtri" | b"ntriangleright" | b"NotRightTriangle" => "\u{22EB}",
-        b"nltrie" | b"ntrianglelefteq" | b"NotLeftTriangleEqual" => "\u{22EC}",
-        b"nrtrie" | b"ntrianglerighteq" | b"NotRightTriangleEqual" => "\u{22ED}",
+        b"nltri" | b"NotLeftTriangle" | b"ntriangleleft" => "\u{22EA}",
+        b"NotRightTriangle" | b"nrtri" | b"ntriangleright" => "\u{22EB}",
+        b"nltrie" | b"NotLeftTriangleEqual" | b"ntrianglelefteq" => "\u{22EC}",
+        b"NotRightTriangleEqual" | b"nrtrie" | b"ntrianglerighteq" => "\u{22ED}",
         b"vellip" => "\u{22EE}",
         b"ctdot" => "\u{22EF}",
         b"utdot" => "\u{22F0}",
@@ -1018,9 +1842,11 @@ const fn named_entity(name: &[u8]) -> Option<&str> {
         b"isinsv" => "\u{22F3}",
         b"isins" => "\u{22F4}",
         b"isindot" => "\u{22F5}",
+        b"notindot" => "\u{22F5}\u{338}",
         b"notinvc" => "\u{22F6}",
         b"notinvb" => "\u{22F7}",
         b"isinE" => "\u{22F9}",
+        b"notinE" => "\u{22F9}\u{338}",
         b"nisd" => "\u{22FA}",
         b"xnis" => "\u{22FB}",
         b"nis" => "\u{22FC}",
@@ -1030,7 +1856,7 @@ const fn named_entity(name: &[u8]) -> Option<&str> {
         b"Barwed" | b"doublebarwedge" => "\u{2306}",
         b"lceil" | b"LeftCeiling" => "\u{2308}",
         b"rceil" | b"RightCeiling" => "\u{2309}",
-        b"lfloor" | b"LeftFloor" => "\u{230A}",
+        b"LeftFloor" | b"lfloor" => "\u{230A}",
         b"rfloor" | b"RightFloor" => "\u{230B}",
         b"drcrop" => "\u{230C}",
         b"dlcrop" => "\u{230D}",
@@ -1055,7 +1881,7 @@ const fn named_entity(name: &[u8]) -> Option<&str> {
         b"angzarr" => "\u{237C}",
         b"lmoust" | b"lmoustache" => "\u{23B0}",
         b"rmoust" | b"rmoustache" => "\u{23B1}",
-        b"tbrk" | b"OverBracket" => "\u{23B4}",
+        b"OverBracket" | b"tbrk" => "\u{23B4}",
         b"bbrk" | b"UnderBracket" => "\u{23B5}",
         b"bbrktbrk" => "\u{23B6}",
         b"OverParenthesis" => "\u{23DC}",
@@ -1065,7 +1891,7 @@ const fn named_entity(name: &[u8]) -> Option<&str> {
         b"trpezium" => "\u{23E2}",
         b"elinters" => "\u{23E7}",
         b"blank" => "\u{2423}",
-        b"oS" | b"circledS" => "\u{24C8}",
+        b"circledS" | b"oS" => "\u{24C8}",
         b"boxh" | b"HorizontalLine" => "\u{2500}",
         b"boxv" => "\u{2502}",
         b"boxdr" => "\u{250C}",
@@ -1112,32 +1938,32 @@ const fn named_entity(name: &[u8]) -> Option<&str> {
         b"blk14" => "\u{2591}",
         b"blk12" => "\u{2592}",
         b"blk34" => "\u{2593}",
-        b"squ" | b"square" | b"Square" => "\u{25A1}",
-        b"squf" | b"squarf" | b"blacksquare" | b"FilledVerySmallSquare" => "\u{25AA}",
+        b"squ" | b"Square" | b"square" => "\u{25A1}",
+        b"blacksquare" | b"FilledVerySmallSquare" | b"squarf" | b"squf" => "\u{25AA}",
         b"EmptyVerySmallSquare" => "\u{25AB}",
         b"rect" => "\u{25AD}",
         b"marker" => "\u{25AE}",
         b"fltns" => "\u{25B1}",
-        b"xutri" | b"bigtriangleup" => "\u{25B3}",
-        b"utrif" | b"blacktriangle" => "\u{25B4}",
-        b"utri" | b"triangle" => "\u{25B5}",
-        b"rtrif" | b"blacktriangleright" => "\u{25B8}",
+        b"bigtriangleup" | b"xutri" => "\u{25B3}",
+        b"blacktriangle" | b"utrif" => "\u{25B4}",
+        b"triangle" | b"utri" => "\u{25B5}",
+        b"blacktriangleright" | b"rtrif" => "\u{25B8}",
         b"rtri" | b"triangleright" => "\u{25B9}",
-        b"xdtri" | b"bigtriangledown" => "\u{25BD}",
-        b"dtrif" | b"blacktriangledown" => "\u{25BE}",
+        b"bigtriangledown" | b"xdtri" => "\u{25BD}",
+        b"blacktriangledown" | b"dtrif" => "\u{25BE}",
         b"dtri" | b"triangledown" => "\u{25BF}",
-        b"ltrif" | b"blacktriangleleft" => "\u{25C2}",
+        b"blacktriangleleft" | b"ltrif" => "\u{25C2}",
         b"ltri" | b"triangleleft" => "\u{25C3}",
         b"loz" | b"lozenge" => "\u{25CA}",
         b"cir" => "\u{25CB}",
         b"tridot" => "\u{25EC}",
-        b"xcirc" | b"bigcirc" => "\u{25EF}",
+        b"bigcirc" | b"xcirc" => "\u{25EF}",
         b"ultri" => "\u{25F8}",
         b"urtri" => "\u{25F9}",
         b"lltri" => "\u{25FA}",
         b"EmptySmallSquare" => "\u{25FB}",
         b"FilledSmallSquare" => "\u{25FC}",
-        b"starf" | b"bigstar" => "\u{2605}",
+        b"bigstar" | b"starf" => "\u{2605}",
         b"star" => "\u{2606}",
         b"phone" => "\u{260E}",
         b"female" => "\u{2640}",
@@ -1145,7 +1971,7 @@ const fn named_entity(name: &[u8]) -> Option<&str> {
         b"spades" | b"spadesuit" => "\u{2660}",
         b"clubs" | b"clubsuit" => "\u{2663}",
         b"hearts" | b"heartsuit" => "\u{2665}",
-        b"diams" | b"diamondsuit" => "\u{2666}",
+        b"diamondsuit" | b"diams" => "\u{2666}",
         b"sung" => "\u{266A}",
         b"flat" => "\u{266D}",
         b"natur" | b"natural" => "\u{266E}",
@@ -1157,31 +1983,33 @@ const fn named_entity(name: &[u8]) -> Option<&str> {
         b"VerticalSeparator" => "\u{2758}",
         b"lbbrk" => "\u{2772}",
         b"rbbrk" => "\u{2773}",
-        b"lobrk" | b"LeftDoubleBracket" => "\u{27E6}",
-        b"robrk" | b"RightDoubleBracket" => "\u{27E7}",
-        b"lang" | b"LeftAngleBracket" | b"langle" => "\u{27E8}",
-        b"rang" | b"RightAngleBracket" | b"rangle" => "\u{27E9}",
+        b"bsolhsub" => "\u{27C8}",
+        b"suphsol" => "\u{27C9}",
+        b"LeftDoubleBracket" | b"lobrk" => "\u{27E6}",
+        b"RightDoubleBracket" | b"robrk" => "\u{27E7}",
+        b"lang" | b"langle" | b"LeftAngleBracket" => "\u{27E8}",
+        b"rang" | b"rangle" | b"RightAngleBracket" => "\u{27E9}",
         b"Lang" => "\u{27EA}",
         b"Rang" => "\u{27EB}",
         b"loang" => "\u{27EC}",
         b"roang" => "\u{27ED}",
-        b"xlarr" | b"longleftarrow" | b"LongLeftArrow" => "\u{27F5}",
-        b"xrarr" | b"longrightarrow" | b"LongRightArrow" => "\u{27F6}",
-        b"xharr" | b"longleftrightarrow" | b"LongLeftRightArrow" => "\u{27F7}",
-        b"xlArr" | b"Longleftarrow" | b"DoubleLongLeftArrow" => "\u{27F8}",
-        b"xrArr" | b"Longrightarrow" | b"DoubleLongRightArrow" => "\u{27F9}",
-        b"xhArr" | b"Longleftrightarrow" | b"DoubleLongLeftRightArrow" => "\u{27FA}",
-        b"xmap" | b"longmapsto" => "\u{27FC}",
+        b"LongLeftArrow" | b"longleftarrow" | b"xlarr" => "\u{27F5}",
+        b"LongRightArrow" | b"longrightarrow" | b"xrarr" => "\u{27F6}",
+        b"LongLeftRightArrow" | b"longleftrightarrow" | b"xharr" => "\u{27F7}",
+        b"DoubleLongLeftArrow" | b"Longleftarrow" | b"xlArr" => "\u{27F8}",
+        b"DoubleLongRightArrow" | b"Longrightarrow" | b"xrArr" => "\u{27F9}",
+        b"DoubleLongLeftRightArrow" | b"Longleftrightarrow" | b"xhArr" => "\u{27FA}",
+        b"longmapsto" | b"xmap" => "\u{27FC}",
         b"dzigrarr" => "\u{27FF}",
         b"nvlArr" => "\u{2902}",
         b"nvrArr" => "\u{2903}",
         b"nvHarr" => "\u{2904}",
         b"Map" => "\u{2905}",
         b"lbarr" => "\u{290C}",
-        b"rbarr" | b"bkarow" => "\u{290D}",
+        b"bkarow" | b"rbarr" => "\u{290D}",
         b"lBarr" => "\u{290E}",
-        b"rBarr" | b"dbkarow" => "\u{290F}",
-        b"RBarr" | b"drbkarow" => "\u{2910}",
+        b"dbkarow" | b"rBarr" => "\u{290F}",
+        b"drbkarow" | b"RBarr" => "\u{2910}",
         b"DDotrahd" => "\u{2911}",
         b"UpArrowBar" => "\u{2912}",
         b"DownArrowBar" => "\u{2913}",
@@ -1196,13 +2024,14 @@ const fn named_entity(name: &[u8]) -> Option<&str> {
         b"rarrbfs" => "\u{2920}",
         b"nwarhk" => "\u{2923}",
         b"nearhk" => "\u{2924}",
-        b"searhk" | b"hksearow" => "\u{2925}",
-        b"swarhk" | b"hkswarow" => "\u{2926}",
+        b"hksearow" | b"searhk" => "\u{2925}",
+        b"hkswarow" | b"swarhk" => "\u{2926}",
         b"nwnear" => "\u{2927}",
         b"nesear" | b"toea" => "\u{2928}",
         b"seswar" | b"tosa" => "\u{2929}",
         b"swnwar" => "\u{292A}",
         b"rarrc" => "\u{2933}",
+        b"nrarrc" => "\u{2933}\u{338}",
         b"cudarrr" => "\u{2935}",
         b"ldca" => "\u{2936}",
         b"rdca" => "\u{2937}",
@@ -1315,23 +2144,24 @@ const fn named_entity(name: &[u8]) -> Option<&str> {
         b"trisb" => "\u{29CD}",
         b"rtriltri" => "\u{29CE}",
         b"LeftTriangleBar" => "\u{29CF}",
+        b"NotLeftTriangleBar" => "\u{29CF}\u{338}",
         b"RightTriangleBar" => "\u{29D0}",
-        b"race" => "\u{29DA}",
+        b"NotRightTriangleBar" => "\u{29D0}\u{338}",
         b"iinfin" => "\u{29DC}",
         b"infintie" => "\u{29DD}",
         b"nvinfin" => "\u{29DE}",
         b"eparsl" => "\u{29E3}",
         b"smeparsl" => "\u{29E4}",
         b"eqvparsl" => "\u{29E5}",
-        b"lozf" | b"blacklozenge" => "\u{29EB}",
+        b"blacklozenge" | b"lozf" => "\u{29EB}",
         b"RuleDelayed" => "\u{29F4}",
         b"dsol" => "\u{29F6}",
-        b"xodot" | b"bigodot" => "\u{2A00}",
-        b"xoplus" | b"bigoplus" => "\u{2A01}",
-        b"xotime" | b"bigotimes" => "\u{2A02}",
-        b"xuplus" | b"biguplus" => "\u{2A04}",
-        b"xsqcup" | b"bigsqcup" => "\u{2A06}",
-        b"qint" | b"iiiint" => "\u{2A0C}",
+        b"bigodot" | b"xodot" => "\u{2A00}",
+        b"bigoplus" | b"xoplus" => "\u{2A01}",
+        b"bigotimes" | b"xotime" => "\u{2A02}",
+        b"biguplus" | b"xuplus" => "\u{2A04}",
+        b"bigsqcup" | b"xsqcup" => "\u{2A06}",
+        b"iiiint" | b"qint" => "\u{2A0C}",
         b"fpartint" => "\u{2A0D}",
         b"cirfnint" => "\u{2A10}",
         b"awint" => "\u{2A11}",
@@ -1363,7 +2193,7 @@ const fn named_entity(name: &[u8]) -> Option<&str> {
         b"triplus" => "\u{2A39}",
         b"triminus" => "\u{2A3A}",
         b"tritime" => "\u{2A3B}",
-        b"iprod" | b"intprod" => "\u{2A3C}",
+        b"intprod" | b"iprod" => "\u{2A3C}",
         b"amalg" => "\u{2A3F}",
         b"capdot" => "\u{2A40}",
         b"ncup" => "\u{2A42}",
@@ -1393,22 +2223,26 @@ const fn named_entity(name: &[u8]) -> Option<&str> {
         b"sdote" => "\u{2A66}",
         b"simdot" => "\u{2A6A}",
         b"congdot" => "\u{2A6D}",
+        b"ncongdot" => "\u{2A6D}\u{338}",
         b"easter" => "\u{2A6E}",
         b"apacir" => "\u{2A6F}",
         b"apE" => "\u{2A70}",
+        b"napE" => "\u{2A70}\u{338}",
         b"eplus" => "\u{2A71}",
         b"pluse" => "\u{2A72}",
         b"Esim" => "\u{2A73}",
         b"Colone" => "\u{2A74}",
         b"Equal" => "\u{2A75}",
-        b"eDDot" | b"ddotseq" => "\u{2A77}",
+        b"ddotseq" | b"eDDot" => "\u{2A77}",
         b"equivDD" => "\u{2A78}",
         b"ltcir" => "\u{2A79}",
         b"gtcir" => "\u{2A7A}",
         b"ltquest" => "\u{2A7B}",
         b"gtquest" => "\u{2A7C}",
-        b"les" | b"LessSlantEqual" | b"leqslant" => "\u{2A7D}",
-        b"ges" | b"GreaterSlantEqual" | b"geqslant" => "\u{2A7E}",
+        b"leqslant" | b"les" | b"LessSlantEqual" => "\u{2A7D}",
+        b"nleqslant" | b"nles" | b"NotLessSlantEqual" => "\u{2A7D}\u{338}",
+        b"geqslant" | b"ges" | b"GreaterSlantEqual" => "\u{2A7E}",
+        b"ngeqslant" | b"nges" | b"NotGreaterSlantEqual" => "\u{2A7E}\u{338}",
         b"lesdot" => "\u{2A7F}",
         b"gesdot" => "\u{2A80}",
         b"lesdoto" => "\u{2A81}",
@@ -1442,7 +2276,9 @@ const fn named_entity(name: &[u8]) -> Option<&str> {
         b"simlE" => "\u{2A9F}",
         b"simgE" => "\u{2AA0}",
         b"LessLess" => "\u{2AA1}",
+        b"NotNestedLessLess" => "\u{2AA1}\u{338}",
         b"GreaterGreater" => "\u{2AA2}",
+        b"NotNestedGreaterGreater" => "\u{2AA2}\u{338}",
         b"glj" => "\u{2AA4}",
         b"gla" => "\u{2AA5}",
         b"ltcc" => "\u{2AA6}",
@@ -1452,17 +2288,21 @@ const fn named_entity(name: &[u8]) -> Option<&str> {
         b"smt" => "\u{2AAA}",
         b"lat" => "\u{2AAB}",
         b"smte" => "\u{2AAC}",
+        b"smtes" => "\u{2AAC}\u{FE00}",
         b"late" => "\u{2AAD}",
+        b"lates" => "\u{2AAD}\u{FE00}",
         b"bumpE" => "\u{2AAE}",
-        b"pre" | b"preceq" | b"PrecedesEqual" => "\u{2AAF}",
-        b"sce" | b"succeq" | b"SucceedsEqual" => "\u{2AB0}",
+        b"pre" | b"PrecedesEqual" | b"preceq" => "\u{2AAF}",
+        b"NotPrecedesEqual" | b"npre" | b"npreceq" => "\u{2AAF}\u{338}",
+        b"sce" | b"SucceedsEqual" | b"succeq" => "\u{2AB0}",
+        b"NotSucceedsEqual" | b"nsce" | b"nsucceq" => "\u{2AB0}\u{338}",
         b"prE" => "\u{2AB3}",
         b"scE" => "\u{2AB4}",
-        b"prnE" | b"precneqq" => "\u{2AB5}",
+        b"precneqq" | b"prnE" => "\u{2AB5}",
         b"scnE" | b"succneqq" => "\u{2AB6}",
         b"prap" | b"precapprox" => "\u{2AB7}",
         b"scap" | b"succapprox" => "\u{2AB8}",
-        b"prnap" | b"precnapprox" => "\u{2AB9}",
+        b"precnapprox" | b"prnap" => "\u{2AB9}",
         b"scnap" | b"succnapprox" => "\u{2ABA}",
         b"Pr" => "\u{2ABB}",
         b"Sc" => "\u{2ABC}",
@@ -1475,11 +2315,15 @@ const fn named_entity(name: &[u8]) -> Option<&str> {
         b"subedot" => "\u{2AC3}",
         b"supedot" => "\u{2AC4}",
         b"subE" | b"subseteqq" => "\u{2AC5}",
+        b"nsubE" | b"nsubseteqq" => "\u{2AC5}\u{338}",
         b"supE" | b"supseteqq" => "\u{2AC6}",
+        b"nsupE" | b"nsupseteqq" => "\u{2AC6}\u{338}",
         b"subsim" => "\u{2AC7}",
         b"supsim" => "\u{2AC8}",
         b"subnE" | b"subsetneqq" => "\u{2ACB}",
+        b"varsubsetneqq" | b"vsubnE" => "\u{2ACB}\u{FE00}",
         b"supnE" | b"supsetneqq" => "\u{2ACC}",
+        b"varsupsetneqq" | b"vsupnE" => "\u{2ACC}\u{FE00}",
         b"csub" => "\u{2ACF}",
         b"csup" => "\u{2AD0}",
         b"csube" => "\u{2AD1}",
@@ -1508,173 +2352,180 @@ const fn named_entity(name: &[u8]) -> Option<&str> {
         b"nhpar" => "\u{2AF2}",
         b"parsim" => "\u{2AF3}",
         b"parsl" => "\u{2AFD}",
+        b"nparsl" => "\u{2AFD}\u{20E5}",
         b"fflig" => "\u{FB00}",
         b"filig" => "\u{FB01}",
         b"fllig" => "\u{FB02}",
         b"ffilig" => "\u{FB03}",
         b"ffllig" => "\u{FB04}",
-        b"Ascr" => "\u{1D49}",
-        b"Cscr" => "\u{1D49}",
-        b"Dscr" => "\u{1D49}",
-        b"Gscr" => "\u{1D4A}",
-        b"Jscr" => "\u{1D4A}",
-        b"Kscr" => "\u{1D4A}",
-        b"Nscr" => "\u{1D4A}",
-        b"Oscr" => "\u{1D4A}",
-        b"Pscr" => "\u{1D4A}",
-        b"Qscr" => "\u{1D4A}",
-        b"Sscr" => "\u{1D4A}",
-        b"Tscr" => "\u{1D4A}",
-        b"Uscr" => "\u{1D4B}",
-        b"Vscr" => "\u{1D4B}",
-        b"Wscr" => "\u{1D4B}",
-        b"Xscr" => "\u{1D4B}",
-        b"Yscr" => "\u{1D4B}",
-        b"Zscr" => "\u{1D4B}",
-        b"ascr" => "\u{1D4B}",
-        b"bscr" => "\u{1D4B}",
-        b"cscr" => "\u{1D4B}",
-        b"dscr" => "\u{1D4B}",
-        b"fscr" => "\u{1D4B}",
-        b"hscr" => "\u{1D4B}",
-        b"iscr" => "\u{1D4B}",
-        b"jscr" => "\u{1D4B}",
-        b"kscr" => "\u{1D4C}",
-        b"lscr" => "\u{1D4C}",
-        b"mscr" => "\u{1D4C}",
-        b"nscr" => "\u{1D4C}",
-        b"pscr" => "\u{1D4C}",
-        b"qscr" => "\u{1D4C}",
-        b"rscr" => "\u{1D4C}",
-        b"sscr" => "\u{1D4C}",
-        b"tscr" => "\u{1D4C}",
-        b"uscr" => "\u{1D4C}",
-        b"vscr" => "\u{1D4C}",
-        b"wscr" => "\u{1D4C}",
-        b"xscr" => "\u{1D4C}",
-        b"yscr" => "\u{1D4C}",
-        b"zscr" => "\u{1D4C}",
-        b"Afr" => "\u{1D50}",
-        b"Bfr" => "\u{1D50}",
-        b"Dfr" => "\u{1D50}",
-        b"Efr" => "\u{1D50}",
-        b"Ffr" => "\u{1D50}",
-        b"Gfr" => "\u{1D50}",
-        b"Jfr" => "\u{1D50}",
-        b"Kfr" => "\u{1D50}",
-        b"Lfr" => "\u{1D50}",
-        b"Mfr" => "\u{1D51}",
-        b"Nfr" => "\u{1D51}",
-        b"Ofr" => "\u{1D51}",
-        b"Pfr" => "\u{1D51}",
-        b"Qfr" => "\u{1D51}",
-        b"Sfr" => "\u{1D51}",
-        b"Tfr" => "\u{1D51}",
-        b"Ufr" => "\u{1D51}",
-        b"Vfr" => "\u{1D51}",
-        b"Wfr" => "\u{1D51}",
-        b"Xfr" => "\u{1D51}",
-        b"Yfr" => "\u{1D51}",
-        b"afr" => "\u{1D51}",
-        b"bfr" => "\u{1D51}",
-        b"cfr" => "\u{1D52}",
-        b"dfr" => "\u{1D52}",
-        b"efr" => "\u{1D52}",
-        b"ffr" => "\u{1D52}",
-        b"gfr" => "\u{1D52}",
-        b"hfr" => "\u{1D52}",
-        b"ifr" => "\u{1D52}",
-        b"jfr" => "\u{1D52}",
-        b"kfr" => "\u{1D52}",
-        b"lfr" => "\u{1D52}",
-        b"mfr" => "\u{1D52}",
-        b"nfr" => "\u{1D52}",
-        b"ofr" => "\u{1D52}",
-        b"pfr" => "\u{1D52}",
-        b"qfr" => "\u{1D52}",
-        b"rfr" => "\u{1D52}",
-        b"sfr" => "\u{1D53}",
-        b"tfr" => "\u{1D53}",
-        b"ufr" => "\u{1D53}",
-        b"vfr" => "\u{1D53}",
-        b"wfr" => "\u{1D53}",
-        b"xfr" => "\u{1D53}",
-        b"yfr" => "\u{1D53}",
-        b"zfr" => "\u{1D53}",
-        b"Aopf" => "\u{1D53}",
-        b"Bopf" => "\u{1D53}",
-        b"Dopf" => "\u{1D53}",
-        b"Eopf" => "\u{1D53}",
-        b"Fopf" => "\u{1D53}",
-        b"Gopf" => "\u{1D53}",
-        b"Iopf" => "\u{1D54}",
-        b"Jopf" => "\u{1D54}",
-        b"Kopf" => "\u{1D54}",
-        b"Lopf" => "\u{1D54}",
-        b"Mopf" => "\u{1D54}",
-        b"Oopf" => "\u{1D54}",
-        b"Sopf" => "\u{1D54}",
-        b"Topf" => "\u{1D54}",
-        b"Uopf" => "\u{1D54}",
-        b"Vopf" => "\u{1D54}",
-        b"Wopf" => "\u{1D54}",
-        b"Xopf" => "\u{1D54}",
-        b"Yopf" => "\u{1D55}",
-        b"aopf" => "\u{1D55}",
-        b"bopf" => "\u{1D55}",
-        b"copf" => "\u{1D55}",
-        b"dopf" => "\u{1D55}",
-        b"eopf" => "\u{1D55}",
-        b"fopf" => "\u{1D55}",
-        b"gopf" => "\u{1D55}",
-        b"hopf" => "\u{1D55}",
-        b"iopf" => "\u{1D55}",
-        b"jopf" => "\u{1D55}",
-        b"kopf" => "\u{1D55}",
-        b"lopf" => "\u{1D55}",
-        b"mopf" => "\u{1D55}",
-        b"nopf" => "\u{1D55}",
-        b"oopf" => "\u{1D56}",
-        b"popf" => "\u{1D56}",
-        b"qopf" => "\u{1D56}",
-        b"ropf" => "\u{1D56}",
-        b"sopf" => "\u{1D56}",
-        b"topf" => "\u{1D56}",
-        b"uopf" => "\u{1D56}",
-        b"vopf" => "\u{1D56}",
-        b"wopf" => "\u{1D56}",
-        b"xopf" => "\u{1D56}",
-        b"yopf" => "\u{1D56}",
-        b"zopf" => "\u{1D56}",
-        _ => return None
+        b"Ascr" => "\u{1D49C}",
+        b"Cscr" => "\u{1D49E}",
+        b"Dscr" => "\u{1D49F}",
+        b"Gscr" => "\u{1D4A2}",
+        b"Jscr" => "\u{1D4A5}",
+        b"Kscr" => "\u{1D4A6}",
+        b"Nscr" => "\u{1D4A9}",
+        b"Oscr" => "\u{1D4AA}",
+        b"Pscr" => "\u{1D4AB}",
+        b"Qscr" => "\u{1D4AC}",
+        b"Sscr" => "\u{1D4AE}",
+        b"Tscr" => "\u{1D4AF}",
+        b"Uscr" => "\u{1D4B0}",
+        b"Vscr" => "\u{1D4B1}",
+        b"Wscr" => "\u{1D4B2}",
+        b"Xscr" => "\u{1D4B3}",
+        b"Yscr" => "\u{1D4B4}",
+        b"Zscr" => "\u{1D4B5}",
+        b"ascr" => "\u{1D4B6}",
+        b"bscr" => "\u{1D4B7}",
+        b"cscr" => "\u{1D4B8}",
+        b"dscr" => "\u{1D4B9}",
+        b"fscr" => "\u{1D4BB}",
+        b"hscr" => "\u{1D4BD}",
+        b"iscr" => "\u{1D4BE}",
+        b"jscr" => "\u{1D4BF}",
+        b"kscr" => "\u{1D4C0}",
+        b"lscr" => "\u{1D4C1}",
+        b"mscr" => "\u{1D4C2}",
+        b"nscr" => "\u{1D4C3}",
+        b"pscr" => "\u{1D4C5}",
+        b"qscr" => "\u{1D4C6}",
+        b"rscr" => "\u{1D4C7}",
+        b"sscr" => "\u{1D4C8}",
+        b"tscr" => "\u{1D4C9}",
+        b"uscr" => "\u{1D4CA}",
+        b"vscr" => "\u{1D4CB}",
+        b"wscr" => "\u{1D4CC}",
+        b"xscr" => "\u{1D4CD}",
+        b"yscr" => "\u{1D4CE}",
+        b"zscr" => "\u{1D4CF}",
+        b"Afr" => "\u{1D504}",
+        b"Bfr" => "\u{1D505}",
+        b"Dfr" => "\u{1D507}",
+        b"Efr" => "\u{1D508}",
+        b"Ffr" => "\u{1D509}",
+        b"Gfr" => "\u{1D50A}",
+        b"Jfr" => "\u{1D50D}",
+        b"Kfr" => "\u{1D50E}",
+        b"Lfr" => "\u{1D50F}",
+        b"Mfr" => "\u{1D510}",
+        b"Nfr" => "\u{1D511}",
+        b"Ofr" => "\u{1D512}",
+        b"Pfr" => "\u{1D513}",
+        b"Qfr" => "\u{1D514}",
+        b"Sfr" => "\u{1D516}",
+        b"Tfr" => "\u{1D517}",
+        b"Ufr" => "\u{1D518}",
+        b"Vfr" => "\u{1D519}",
+        b"Wfr" => "\u{1D51A}",
+        b"Xfr" => "\u{1D51B}",
+        b"Yfr" => "\u{1D51C}",
+        b"afr" => "\u{1D51E}",
+        b"bfr" => "\u{1D51F}",
+        b"cfr" => "\u{1D520}",
+        b"dfr" => "\u{1D521}",
+        b"efr" => "\u{1D522}",
+        b"ffr" => "\u{1D523}",
+        b"gfr" => "\u{1D524}",
+        b"hfr" => "\u{1D525}",
+        b"ifr" => "\u{1D526}",
+        b"jfr" => "\u{1D527}",
+        b"kfr" => "\u{1D528}",
+        b"lfr" => "\u{1D529}",
+        b"mfr" => "\u{1D52A}",
+        b"nfr" => "\u{1D52B}",
+        b"ofr" => "\u{1D52C}",
+        b"pfr" => "\u{1D52D}",
+        b"qfr" => "\u{1D52E}",
+        b"rfr" => "\u{1D52F}",
+        b"sfr" => "\u{1D530}",
+        b"tfr" => "\u{1D531}",
+        b"ufr" => "\u{1D532}",
+        b"vfr" => "\u{1D533}",
+        b"wfr" => "\u{1D534}",
+        b"xfr" => "\u{1D535}",
+        b"yfr" => "\u{1D536}",
+        b"zfr" => "\u{1D537}",
+        b"Aopf" => "\u{1D538}",
+        b"Bopf" => "\u{1D539}",
+        b"Dopf" => "\u{1D53B}",
+        b"Eopf" => "\u{1D53C}",
+        b"Fopf" => "\u{1D53D}",
+        b"Gopf" => "\u{1D53E}",
+        b"Iopf" => "\u{1D540}",
+        b"Jopf" => "\u{1D541}",
+        b"Kopf" => "\u{1D542}",
+        b"Lopf" => "\u{1D543}",
+        b"Mopf" => "\u{1D544}",
+        b"Oopf" => "\u{1D546}",
+        b"Sopf" => "\u{1D54A}",
+        b"Topf" => "\u{1D54B}",
+        b"Uopf" => "\u{1D54C}",
+        b"Vopf" => "\u{1D54D}",
+        b"Wopf" => "\u{1D54E}",
+        b"Xopf" => "\u{1D54F}",
+        b"Yopf" => "\u{1D550}",
+        b"aopf" => "\u{1D552}",
+        b"bopf" => "\u{1D553}",
+        b"copf" => "\u{1D554}",
+        b"dopf" => "\u{1D555}",
+        b"eopf" => "\u{1D556}",
+        b"fopf" => "\u{1D557}",
+        b"gopf" => "\u{1D558}",
+        b"hopf" => "\u{1D559}",
+        b"iopf" => "\u{1D55A}",
+        b"jopf" => "\u{1D55B}",
+        b"kopf" => "\u{1D55C}",
+        b"lopf" => "\u{1D55D}",
+        b"mopf" => "\u{1D55E}",
+        b"nopf" => "\u{1D55F}",
+        b"oopf" => "\u{1D560}",
+        b"popf" => "\u{1D561}",
+        b"qopf" => "\u{1D562}",
+        b"ropf" => "\u{1D563}",
+        b"sopf" => "\u{1D564}",
+        b"topf" => "\u{1D565}",
+        b"uopf" => "\u{1D566}",
+        b"vopf" => "\u{1D567}",
+        b"wopf" => "\u{1D568}",
+        b"xopf" => "\u{1D569}",
+        b"yopf" => "\u{1D56A}",
+        b"zopf" => "\u{1D56B}",
+        _ => return None,
     };
     Some(s)
 }
+#[cfg(not(feature = "escape-html"))]
+const fn html_entity(_name: &[u8]) -> Option<&str> {
+    None
+}
 
 fn push_utf8(out: &mut Vec<u8>, code: char) {
     let mut buf = [0u8; 4];
     out.extend_from_slice(code.encode_utf8(&mut buf).as_bytes());
 }
 
+/// Parses the number of the character reference `&#...;` at the `range` of the input,
+/// `bytes` being the part between `#` and `;`
 fn parse_number(bytes: &[u8], range: Range<usize>) -> Result<char, EscapeError> {
     let code = if bytes.starts_with(b"x") {
-        parse_hexadecimal(&bytes[1..])
+        parse_hexadecimal(&bytes[1..], range.clone())
     } else {
-        parse_decimal(&bytes)
+        parse_decimal(bytes, range.clone())
     }?;
     if code == 0 {
         return Err(EscapeError::EntityWithNull(range));
     }
     match std::char::from_u32(code) {
         Some(c) => Ok(c),
-        None => Err(EscapeError::InvalidCodepoint(code)),
+        None => Err(EscapeError::InvalidCodepoint(range, code)),
     }
 }
 
-fn parse_hexadecimal(bytes: &[u8]) -> Result<u32, EscapeError> {
+fn parse_hexadecimal(bytes: &[u8], range: Range<usize>) -> Result<u32, EscapeError> {
     // maximum code is 0x10FFFF => 6 characters
     if bytes.len() > 6 {
-        return Err(EscapeError::TooLongHexadecimal);
+        return Err(EscapeError::TooLongHexadecimal(range));
     }
     let mut code = 0;
     for &b in bytes {
@@ -1683,23 +2534,23 @@ fn parse_hexadecimal(bytes: &[u8]) -> Result<u32, EscapeError> {
             b'0'..=b'9' => b - b'0',
             b'a'..=b'f' => b - b'a' + 10,
             b'A'..=b'F' => b - b'A' + 10,
-            b => return Err(EscapeError::InvalidHexadecimal(b as char)),
+            b => return Err(EscapeError::InvalidHexadecimal(range, b as char)),
         } as u32;
     }
     Ok(code)
 }
 
-fn parse_decimal(bytes: &[u8]) -> Result<u32, EscapeError> {
+fn parse_decimal(bytes: &[u8], range: Range<usize>) -> Result<u32, EscapeError> {
     // maximum code is 0x10FFFF = 1114111 => 7 characters
     if bytes.len() > 7 {
-        return Err(EscapeError::TooLongDecimal);
+        return Err(EscapeError::TooLongDecimal(range));
     }
     let mut code = 0;
     for &b in bytes {
         code *= 10;
         code += match b {
             b'0'..=b'9' => b - b'0',
-            b => return Err(EscapeError::InvalidDecimal(b as char)),
+            b => return Err(EscapeError::InvalidDecimal(range, b as char)),
         } as u32;
     }
     Ok(code)
@@ -1714,6 +2565,56 @@ fn test_unescape() {
     assert!(unescape(b"&foo;").is_err());
 }
 
+#[test]
+fn test_unescape_error_ranges() {
+    fn error_at(raw: &str) -> (&str, String) {
+        let e = unescape(raw.as_bytes()).unwrap_err();
+        (&raw[e.range()], e.to_string())
+    }
+    assert_eq!(error_at("a &lt; &foo; b").0, "&foo;");
+    assert_eq!(error_at("a &lt; &amp b").0, "&amp b");
+    assert_eq!(error_at("&lt;&#0;").0, "&#0;");
+    assert_eq!(error_at("&lt;&#x1234567;").0, "&#x1234567;");
+    assert_eq!(error_at("&lt;&#x12G;").0, "&#x12G;");
+    assert_eq!(error_at("&lt;&#12345678;").0, "&#12345678;");
+    assert_eq!(error_at("&lt;&#1x;").0, "&#1x;");
+    assert_eq!(error_at("&lt;&#xD800;").0, "&#xD800;");
+    assert!(error_at("&lt;&#1x;").1.contains("4..9"));
+
+    match unescape(b"&lt;&foo;") {
+        Err(EscapeError::UnrecognizedSymbol(range, Ok(name))) => {
+            assert_eq!(range, 4..9);
+            assert_eq!(name, "foo");
+        }
+        x => panic!("expected UnrecognizedSymbol, got {:?}", x),
+    }
+
+    let e = unescape(b"&foo;").unwrap_err().with_offset(10);
+    assert_eq!(e.range(), 10..15);
+}
+
+#[test]
+fn test_unescape_lossy() {
+    let mut errors = Vec::new();
+    assert!(matches!(
+        unescape_lossy(b"plain", |e| errors.push(e)),
+        Cow::Borrowed(b"plain")
+    ));
+    assert_eq!(
+        &*unescape_lossy(b"&lt;&foo;&#0;&gt;", |e| errors.push(e)),
+        b"<&foo;&#0;>"
+    );
+    assert_eq!(
+        &*unescape_lossy(b"a &amp b & c &amp; d&", |e| errors.push(e)),
+        b"a &amp b & c & d&"
+    );
+    let ranges: Vec<_> = errors.iter().map(EscapeError::range).collect();
+    assert_eq!(ranges, vec![4..9, 9..13, 2..9, 9..13, 20..21]);
+    assert!(matches!(errors[0], EscapeError::UnrecognizedSymbol(..)));
+    assert!(matches!(errors[1], EscapeError::EntityWithNull(..)));
+    assert!(matches!(errors[2], EscapeError::UnterminatedEntity(..)));
+}
+
 #[test]
 fn test_unescape_with() {
     let custom_entities = vec![(b"foo".to_vec(), b"BAR".to_vec())]
@@ -1741,3 +2642,315 @@ fn test_escape() {
         "prefix_&quot;a&quot;b&amp;&lt;&gt;c".as_bytes()
     );
 }
+
+#[test]
+fn test_escape_unescaped() {
+    assert_eq!(&*escape_unescaped(b"test"), b"test");
+    assert_eq!(&*escape_unescaped(b"<test>"), b"&lt;test&gt;");
+    assert_eq!(&*escape_unescaped(b"&lt;test&gt;"), b"&lt;test&gt;");
+    assert_eq!(&*escape_unescaped(b"a & b"), b"a &amp; b");
+    assert_eq!(&*escape_unescaped(b"&#x30;&#48;&amp;"), b"&#x30;&#48;&amp;");
+    assert_eq!(&*escape_unescaped(b"&#xZZ;&#;&;"), b"&amp;#xZZ;&amp;#;&amp;;");
+    assert_eq!(&*escape_unescaped(b"\"&quot;'"), b"&quot;&quot;'");
+}
+
+#[test]
+fn test_escape_str() {
+    assert!(matches!(escape_str("test"), Cow::Borrowed("test")));
+    assert_eq!(escape_str("<test>"), "&lt;test&gt;");
+    assert_eq!(
+        escape_str("'a' & \"b\" < \u{444} >"),
+        "&apos;a&apos; &amp; &quot;b&quot; &lt; \u{444} &gt;"
+    );
+
+    let mut out = String::from("<a>");
+    escape_str_into("'&\"<>", &mut out);
+    assert_eq!(out, "<a>&apos;&amp;&quot;&lt;&gt;");
+}
+
+#[test]
+fn test_escape_into() {
+    let mut out = b"<a>".to_vec();
+    assert!(escape_into(b"'&\"<>", &mut out));
+    assert_eq!(out, b"<a>&apos;&amp;&quot;&lt;&gt;");
+    assert!(!escape_into(b"test", &mut out));
+    assert_eq!(out, b"<a>&apos;&amp;&quot;&lt;&gt;test");
+    assert!(!escape_into(b"", &mut out));
+
+    assert!(needs_escaping(b"a>"));
+    assert!(!needs_escaping(b"test \xd1\x84"));
+}
+
+#[test]
+fn test_escape_with_level() {
+    let raw = "<&>'\" \u{444}".as_bytes();
+    assert_eq!(
+        &*escape_with_level(raw, EscapeLevel::Minimal),
+        "&lt;&amp;>'\" \u{444}".as_bytes()
+    );
+    assert_eq!(&*partial_escape(raw), "&lt;&amp;>'\" \u{444}".as_bytes());
+    assert_eq!(
+        &*escape_with_level(raw, EscapeLevel::Standard),
+        "&lt;&amp;&gt;&apos;&quot; \u{444}".as_bytes()
+    );
+    assert_eq!(
+        &*escape_with_level(raw, EscapeLevel::Aggressive),
+        b"&lt;&amp;&gt;&apos;&quot; &#x444;"
+    );
+    // Characters outside of the BMP and invalid UTF-8
+    assert_eq!(
+        &*escape_with_level(b"\xF0\x9F\x98\x80 \xFF\xD1", EscapeLevel::Aggressive),
+        b"&#x1F600; \xFF\xD1"
+    );
+
+    assert!(matches!(
+        escape_with_level(b">'\"", EscapeLevel::Minimal),
+        Cow::Borrowed(_)
+    ));
+    assert!(matches!(
+        escape_with_level("\u{444}".as_bytes(), EscapeLevel::Standard),
+        Cow::Borrowed(_)
+    ));
+}
+
+#[test]
+fn test_escape_non_ascii() {
+    use self::CharRefFormat::*;
+
+    assert!(matches!(
+        escape_non_ascii(b"&lt;ascii&gt;", Decimal),
+        Cow::Borrowed(b"&lt;ascii&gt;")
+    ));
+    let raw = "&amp;\u{7F}\u{80}\u{7FF}\u{800}\u{FFFF}\u{10000}\u{10FFFF}".as_bytes();
+    assert_eq!(
+        &*escape_non_ascii(raw, Decimal),
+        b"&amp;\x7F&#128;&#2047;&#2048;&#65535;&#65536;&#1114111;"
+    );
+    assert_eq!(
+        &*escape_non_ascii(raw, Hexadecimal),
+        b"&amp;\x7F&#x80;&#x7FF;&#x800;&#xFFFF;&#x10000;&#x10FFFF;"
+    );
+    // Bytes which are not valid UTF-8 are kept as is
+    assert_eq!(
+        &*escape_non_ascii(b"\xFF\xD1\x84\xD1", Decimal),
+        b"\xFF&#1092;\xD1"
+    );
+    assert!(matches!(
+        escape_non_ascii(b"a\xFF", Decimal),
+        Cow::Borrowed(b"a\xFF")
+    ));
+}
+
+#[test]
+fn test_unescape_into() {
+    let mut out = b"<a>".to_vec();
+    assert!(unescape_into(b"&apos;&amp;&quot;&lt;&gt;", &mut out).unwrap());
+    assert_eq!(out, b"<a>'&\"<>");
+    assert!(!unescape_into(b"test", &mut out).unwrap());
+    assert_eq!(out, b"<a>'&\"<>test");
+    assert!(unescape_into(b"&#x30;", &mut out).unwrap());
+    assert_eq!(out, b"<a>'&\"<>test0");
+    assert!(unescape_into(b"&foo;", &mut out).is_err());
+}
+
+#[test]
+fn test_unescape_str() {
+    assert!(matches!(unescape_str("test").unwrap(), Cow::Borrowed("test")));
+    assert_eq!(
+        unescape_str("&apos;&amp;&quot;&lt;&gt; \u{444}").unwrap(),
+        "'&\"<> \u{444}"
+    );
+    assert!(unescape_str("&foo;").is_err());
+}
+
+#[test]
+fn test_unescape_str_with() {
+    let resolve = |entity: &str| match entity {
+        "lt" => Some("less"),
+        "nested" => Some("&lt;&nested;"),
+        "x" => Some("X"),
+        _ => None,
+    };
+    assert!(matches!(
+        unescape_str_with("test", resolve).unwrap(),
+        Cow::Borrowed("test")
+    ));
+    // Predefined entities cannot be overridden
+    assert_eq!(unescape_str_with("&lt;&x;&#x20;", resolve).unwrap(), "<X ");
+    // Values are not unescaped
+    assert_eq!(
+        unescape_str_with("&nested;", resolve).unwrap(),
+        "&lt;&nested;"
+    );
+    assert!(unescape_str_with("&unknown;", resolve).is_err());
+    assert!(unescape_str_with("&x", resolve).is_err());
+}
+
+#[test]
+fn test_normalize_attribute_value() {
+    assert!(matches!(
+        normalize_attribute_value(b"a b", false, None).unwrap(),
+        Cow::Borrowed(b"a b")
+    ));
+    assert_eq!(
+        &*normalize_attribute_value(b"a\tb\nc\r\nd\re", false, None).unwrap(),
+        b"a b c d e"
+    );
+    assert_eq!(
+        &*normalize_attribute_value(b"&lt;&#x20;&#32;&#xA;&#9;", false, None).unwrap(),
+        b"<  \n\t"
+    );
+    assert!(normalize_attribute_value(b"&foo;", false, None).is_err());
+    assert!(normalize_attribute_value(b"&lt", false, None).is_err());
+}
+
+#[test]
+fn test_escape_attribute() {
+    assert!(matches!(escape_attribute(b"a b"), Cow::Borrowed(b"a b")));
+    let raw = b"\"a\" <b>\r\n\tc & 'd'\r";
+    let escaped = escape_attribute(raw);
+    assert_eq!(
+        &*escaped,
+        b"&quot;a&quot; &lt;b&gt;&#13;&#10;&#9;c &amp; &apos;d&apos;&#13;"
+    );
+    // Whitespaces survive the normalization of a read value
+    assert_eq!(
+        &*normalize_attribute_value(&escaped, false, None).unwrap(),
+        raw
+    );
+}
+
+#[cfg(feature = "escape-html")]
+#[test]
+fn test_unescape_html() {
+    assert_eq!(&*unescape(b"a&nbsp;b").unwrap(), "a\u{A0}b".as_bytes());
+    assert_eq!(
+        &*unescape(b"&CounterClockwiseContourIntegral;").unwrap(),
+        "\u{2233}".as_bytes()
+    );
+    // Names with code points outside of the Basic Multilingual Plane
+    assert_eq!(&*unescape(b"&iopf;").unwrap(), "\u{1D55A}".as_bytes());
+    // Names resolved to several code points
+    assert_eq!(&*unescape(b"&nvlt;").unwrap(), "<\u{20D2}".as_bytes());
+    assert!(unescape(b"&nope;").is_err());
+
+    assert!(do_unescape_with_html(b"&nbsp;", None, false).is_err());
+    assert_eq!(
+        &*do_unescape_with_html(b"&lt;&#32;&amp;", None, false).unwrap(),
+        b"< &"
+    );
+    let mut custom_entities = HashMap::new();
+    custom_entities.insert(b"nbsp".to_vec(), b"custom".to_vec());
+    // HTML entities are resolved before the custom ones
+    assert_eq!(
+        &*unescape_with(b"&nbsp;", &custom_entities).unwrap(),
+        "\u{A0}".as_bytes()
+    );
+    assert_eq!(
+        &*do_unescape_with_html(b"&nbsp;", Some(&custom_entities), false).unwrap(),
+        b"custom"
+    );
+}
+
+/// Byte-by-byte implementations of the escaping functions, which were used before
+/// the `memchr`-based ones. Kept as a reference for the differential tests
+#[cfg(test)]
+mod scalar {
+    use super::{entity, is_special, starts_with_reference};
+
+    pub fn escape<F: Fn(u8) -> bool>(raw: &[u8], escape_chars: F) -> Vec<u8> {
+        let mut escaped = Vec::new();
+        for &b in raw {
+            if escape_chars(b) {
+                escaped.extend_from_slice(entity(b).as_bytes());
+            } else {
+                escaped.push(b);
+            }
+        }
+        escaped
+    }
+
+    pub fn escape_standard(raw: &[u8]) -> Vec<u8> {
+        escape(raw, is_special)
+    }
+
+    pub fn escape_minimal(raw: &[u8]) -> Vec<u8> {
+        escape(raw, |b| b == b'&' || b == b'<')
+    }
+
+    pub fn escape_unescaped(raw: &[u8]) -> Vec<u8> {
+        let mut escaped = Vec::new();
+        for (i, &b) in raw.iter().enumerate() {
+            match b {
+                b'<' | b'>' | b'"' => escaped.extend_from_slice(entity(b).as_bytes()),
+                b'&' if !starts_with_reference(&raw[i..]) => escaped.extend_from_slice(b"&amp;"),
+                b => escaped.push(b),
+            }
+        }
+        escaped
+    }
+}
+
+/// Generates random documents, mostly made of markup characters, with a xorshift
+/// generator, so the tests are reproducible
+#[cfg(test)]
+fn random_documents() -> impl Iterator<Item = Vec<u8>> {
+    const ALPHABET: &[u8] = b"<>&'\";#xa1 \n\xD1\x84";
+    let mut state = 0x2545_F491_4F6C_DD1Du64;
+    let mut next = move || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    };
+    (0..2000).map(move |n| {
+        // Long documents exercise the vectorized search of `memchr`
+        let len = if n % 10 == 0 {
+            next() % 5000
+        } else {
+            next() % 100
+        } as usize;
+        // Some documents have no special characters at all, and some are almost only them
+        let density = next() % 8;
+        (0..len)
+            .map(|_| {
+                let r = next();
+                if r % 8 < density {
+                    ALPHABET[(r >> 8) as usize % ALPHABET.len()]
+                } else {
+                    b'a' + (r >> 8) as u8 % 26
+                }
+            })
+            .collect()
+    })
+}
+
+#[test]
+fn test_escape_differential() {
+    for raw in random_documents() {
+        let expected = scalar::escape_standard(&raw);
+        assert_eq!(&*escape(&raw), &*expected, "{:?}", raw);
+        assert_eq!(needs_escaping(&raw), expected != raw, "{:?}", raw);
+
+        let mut out = b"prefix".to_vec();
+        assert_eq!(escape_into(&raw, &mut out), expected != raw);
+        assert_eq!(&out[6..], &*expected);
+
+        if let Ok(s) = std::str::from_utf8(&raw) {
+            assert_eq!(escape_str(s).as_bytes(), &*expected);
+        }
+
+        let expected = scalar::escape_minimal(&raw);
+        assert_eq!(&*partial_escape(&raw), &*expected, "{:?}", raw);
+        assert_eq!(
+            needs_escaping_with_level(&raw, EscapeLevel::Minimal),
+            expected != raw
+        );
+
+        let expected = scalar::escape_unescaped(&raw);
+        assert_eq!(&*escape_unescaped(&raw), &*expected, "{:?}", raw);
+
+        let expected = scalar::escape(&raw, |b| b == b'"');
+        assert_eq!(&*_escape(&raw, memchr::memchr_iter(b'"', &raw)), &*expected);
+    }
+}
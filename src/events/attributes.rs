@@ -3,17 +3,24 @@
 //! Provides an iterator over attributes key/value pairs
 
 use errors::{Error, Result};
-use escape::{do_unescape, escape};
-use reader::{is_whitespace, Reader};
+use escape::{do_unescape, do_unescape_with_html, escape_attribute, normalize_attribute_value};
+use name::QName;
+use reader::{is_whitespace, Decoder, Reader};
 use std::borrow::Cow;
 use std::collections::HashMap;
+use std::fmt;
 use std::io::BufRead;
 use std::ops::Range;
+use std::str::FromStr;
 
 /// Iterator over XML attributes.
 ///
 /// Yields `Result<Attribute>`. An `Err` will be yielded if an attribute is malformed or duplicated.
-/// The duplicate check can be turned off by calling [`with_checks(false)`].
+/// Most of the checks can be turned off by calling [`with_checks(false)`].
+///
+/// Parsing is lazy: creating the iterator costs nothing, and each call to `next` parses
+/// only the next attribute, so tags which attributes are never inspected are not parsed
+/// at all, and a search for a single attribute stops as soon as it is found.
 ///
 /// [`with_checks(false)`]: #method.with_checks
 #[derive(Clone)]
@@ -54,12 +61,71 @@ impl<'a> Attributes<'a> {
         }
     }
 
-    /// Changes whether attributes should be checked for uniqueness.
+    /// Converts this iterator into an iterator, which also yields positions of the keys and
+    /// the values of the attributes and their quoting style.
+    ///
+    /// Positions are relative to the buffer this iterator was created from. For the iterator,
+    /// returned by [`BytesStart::attributes`], this is the content of the tag without `<` and
+    /// `>` (or `/>`), so the position of an attribute in the document can be calculated from
+    /// [`Reader::buffer_position`], which is right after the tag, when the tag was read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use quick_xml::events::attributes::AttrQuote;
+    /// use quick_xml::events::Event;
+    /// use quick_xml::Reader;
+    ///
+    /// let xml = r#"<root><tag a='"' b=""/></root>"#;
+    /// let mut reader = Reader::from_str(xml);
+    /// reader.trim_text(true);
+    /// let mut buf = Vec::new();
+    /// reader.read_event(&mut buf).unwrap();
+    /// let tag = match reader.read_event(&mut buf).unwrap() {
+    ///     Event::Empty(e) => e.into_owned(),
+    ///     e => panic!("Expected `Empty`, found {:?}", e),
+    /// };
+    /// // The tag ends with `/>`
+    /// let tag_start = reader.buffer_position() - tag.len() - 2;
+    ///
+    /// let spans: Vec<_> = tag.attributes().with_spans().map(|a| a.unwrap()).collect();
+    /// assert_eq!(spans[0].quote, AttrQuote::Single);
+    /// assert_eq!(&xml[tag_start + spans[0].key_span.start..][..1], "a");
+    /// assert_eq!(&xml[tag_start + spans[0].value_span.start..][..1], "\"");
+    /// assert_eq!(spans[1].quote, AttrQuote::Double);
+    /// assert!(spans[1].value_span.is_empty());
+    /// ```
+    ///
+    /// [`BytesStart::attributes`]: ../struct.BytesStart.html#method.attributes
+    /// [`Reader::buffer_position`]: ../../struct.Reader.html#method.buffer_position
+    pub fn with_spans(self) -> SpannedAttributes<'a> {
+        SpannedAttributes { inner: self }
+    }
+
+    /// Changes whether attributes should be fully checked for well-formedness.
+    ///
+    /// When checks are enabled, the iterator yields an error with the position of the problem
+    /// in the buffer if:
+    /// - the attribute key is not unique in the element, which is required by the XML
+    ///   specification ([`Error::DuplicatedAttribute`])
+    /// - the attribute key contains a quote ([`Error::NameWithQuote`]) or another character,
+    ///   that is not allowed in XML names ([`Error::InvalidAttributeName`])
+    /// - the last key has no `=` ([`Error::NoEqAfterName`]) or the last `=` has no value
+    ///   ([`Error::UnquotedValue`]). Without checks iteration just stops there
+    ///
+    /// Missing `=` between keys and values and unbalanced quotes are reported in both modes.
+    /// Disabling the checks gives a faster path for trusted documents.
     ///
-    /// The XML specification requires attribute keys in the same element to be unique. This check
-    /// can be disabled to improve performance slightly.
+    /// In the [`html`] mode keys without values are allowed regardless of this setting.
     ///
     /// (`true` by default)
+    ///
+    /// [`Error::DuplicatedAttribute`]: ../../errors/enum.Error.html#variant.DuplicatedAttribute
+    /// [`Error::NameWithQuote`]: ../../errors/enum.Error.html#variant.NameWithQuote
+    /// [`Error::InvalidAttributeName`]: ../../errors/enum.Error.html#variant.InvalidAttributeName
+    /// [`Error::NoEqAfterName`]: ../../errors/enum.Error.html#variant.NoEqAfterName
+    /// [`Error::UnquotedValue`]: ../../errors/enum.Error.html#variant.UnquotedValue
+    /// [`html`]: #method.html
     pub fn with_checks(&mut self, val: bool) -> &mut Attributes<'a> {
         self.with_checks = val;
         self
@@ -81,7 +147,7 @@ pub struct Attribute<'a> {
     /// If [`Attributes::with_checks`] is turned off, the key might not be unique.
     ///
     /// [`Attributes::with_checks`]: struct.Attributes.html#method.with_checks
-    pub key: &'a [u8],
+    pub key: QName<'a>,
     /// The raw value of the attribute.
     pub value: Cow<'a, [u8]>,
 }
@@ -126,6 +192,99 @@ impl<'a> Attribute<'a> {
         do_unescape(&*self.value, custom_entities).map_err(Error::EscapeError)
     }
 
+    /// Decodes the value and normalizes it as required by the [XML specification] for
+    /// attributes of the `CDATA` type.
+    ///
+    /// Entity and character references are replaced by their values, and the literal tabs,
+    /// newlines and carriage returns are replaced by spaces. Whitespaces produced by character
+    /// references, such as `&#xA;`, are kept as is, so they can be used to preserve a newline
+    /// in a value.
+    ///
+    /// This will allocate only if the value has to be decoded or contains any references or
+    /// whitespaces other than spaces.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use quick_xml::events::attributes::Attribute;
+    /// use quick_xml::Reader;
+    ///
+    /// let reader = Reader::from_str("");
+    /// let attr = Attribute::from(("a".as_bytes(), "line\r\nnext&#xA;&lt;last&gt;".as_bytes()));
+    /// assert_eq!(
+    ///     attr.normalized_value(reader.decoder()).unwrap(),
+    ///     "line next\n<last>"
+    /// );
+    /// ```
+    ///
+    /// [XML specification]: https://www.w3.org/TR/xml/#AVNormalize
+    pub fn normalized_value(&self, decoder: Decoder) -> Result<Cow<'_, str>> {
+        #[cfg(not(feature = "encoding"))]
+        let decoded = Cow::Borrowed(decoder.decode(&self.value)?);
+
+        #[cfg(feature = "encoding")]
+        let decoded = decoder.decode(&self.value);
+
+        let normalized =
+            normalize_attribute_value(decoded.as_bytes(), cfg!(feature = "escape-html"), None)
+                .map_err(Error::EscapeError)?;
+        // Release the borrow of `decoded` so it can be returned if nothing was changed
+        let normalized = match normalized {
+            Cow::Borrowed(_) => None,
+            Cow::Owned(normalized) => Some(normalized),
+        };
+        match normalized {
+            Some(normalized) => Ok(Cow::Owned(
+                String::from_utf8(normalized).map_err(|e| Error::Utf8(e.utf8_error()))?,
+            )),
+            None => Ok(decoded),
+        }
+    }
+
+    /// Decodes and normalizes the value as [`normalized_value`] does, trims the leading and
+    /// trailing XML whitespaces and parses the result into `T`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use quick_xml::events::BytesStart;
+    /// use quick_xml::Reader;
+    ///
+    /// let reader = Reader::from_str("");
+    /// let tag = BytesStart::borrowed(b"tag count=' 42 ' ratio='0.5' visible='nope'", 3);
+    ///
+    /// let count = tag.try_get_attribute("count").unwrap().unwrap();
+    /// assert_eq!(count.decode_and_parse::<u64>(reader.decoder()).unwrap(), 42);
+    ///
+    /// let ratio = tag.try_get_attribute("ratio").unwrap().unwrap();
+    /// assert_eq!(ratio.decode_and_parse::<f64>(reader.decoder()).unwrap(), 0.5);
+    ///
+    /// let visible = tag.try_get_attribute("visible").unwrap().unwrap();
+    /// let error = visible.decode_and_parse::<bool>(reader.decoder()).unwrap_err();
+    /// assert_eq!(
+    ///     error.to_string(),
+    ///     "cannot parse value 'nope' of attribute 'visible': provided string was not `true` or `false`"
+    /// );
+    /// ```
+    ///
+    /// [`normalized_value`]: #method.normalized_value
+    pub fn decode_and_parse<T: FromStr>(
+        &self,
+        decoder: Decoder,
+    ) -> ::std::result::Result<T, AttrParseError<T::Err>> {
+        let value = self
+            .normalized_value(decoder)
+            .map_err(AttrParseError::Xml)?;
+        value
+            .trim_matches(|c: char| c.is_ascii() && is_whitespace(c as u8))
+            .parse()
+            .map_err(|error| AttrParseError::Parse {
+                key: String::from_utf8_lossy(&self.key).into_owned(),
+                value: String::from_utf8_lossy(&self.value).into_owned(),
+                error,
+            })
+    }
+
     /// Decode then unescapes the value
     ///
     /// This allocates a `String` in all cases. For performance reasons it might be a better idea to
@@ -171,7 +330,8 @@ impl<'a> Attribute<'a> {
     ) -> Result<String> {
         let decoded = reader.decode(&*self.value);
         let unescaped =
-            do_unescape(decoded.as_bytes(), custom_entities).map_err(Error::EscapeError)?;
+            do_unescape_with_html(decoded.as_bytes(), custom_entities, reader.html_entities())
+                .map_err(Error::EscapeError)?;
         String::from_utf8(unescaped.into_owned()).map_err(|e| Error::Utf8(e.utf8_error()))
     }
 
@@ -183,7 +343,8 @@ impl<'a> Attribute<'a> {
     ) -> Result<String> {
         let decoded = reader.decode(&*self.value)?;
         let unescaped =
-            do_unescape(decoded.as_bytes(), custom_entities).map_err(Error::EscapeError)?;
+            do_unescape_with_html(decoded.as_bytes(), custom_entities, reader.html_entities())
+                .map_err(Error::EscapeError)?;
         String::from_utf8(unescaped.into_owned()).map_err(|e| Error::Utf8(e.utf8_error()))
     }
 
@@ -265,7 +426,8 @@ impl<'a> Attribute<'a> {
     ) -> Result<String> {
         let decoded = reader.decode_without_bom(&*self.value);
         let unescaped =
-            do_unescape(decoded.as_bytes(), custom_entities).map_err(Error::EscapeError)?;
+            do_unescape_with_html(decoded.as_bytes(), custom_entities, reader.html_entities())
+                .map_err(Error::EscapeError)?;
         String::from_utf8(unescaped.into_owned()).map_err(|e| Error::Utf8(e.utf8_error()))
     }
 
@@ -277,7 +439,8 @@ impl<'a> Attribute<'a> {
     ) -> Result<String> {
         let decoded = reader.decode_without_bom(&*self.value)?;
         let unescaped =
-            do_unescape(decoded.as_bytes(), custom_entities).map_err(Error::EscapeError)?;
+            do_unescape_with_html(decoded.as_bytes(), custom_entities, reader.html_entities())
+                .map_err(Error::EscapeError)?;
         String::from_utf8(unescaped.into_owned()).map_err(|e| Error::Utf8(e.utf8_error()))
     }
 }
@@ -287,13 +450,53 @@ impl<'a> std::fmt::Debug for Attribute<'a> {
         use crate::utils::write_byte_string;
 
         write!(f, "Attribute {{ key: ")?;
-        write_byte_string(f, self.key)?;
+        write_byte_string(f, &self.key)?;
         write!(f, ", value: ")?;
         write_byte_string(f, &self.value)?;
         write!(f, " }}")
     }
 }
 
+/// An error returned by [`Attribute::decode_and_parse`].
+///
+/// [`Attribute::decode_and_parse`]: struct.Attribute.html#method.decode_and_parse
+#[derive(Debug)]
+pub enum AttrParseError<E> {
+    /// The value cannot be decoded or unescaped
+    Xml(Error),
+    /// The value cannot be parsed
+    Parse {
+        /// The name of the attribute
+        key: String,
+        /// The raw value of the attribute, as it is present in the input
+        value: String,
+        /// The error of the `FromStr` implementation
+        error: E,
+    },
+}
+
+impl<E: fmt::Display> fmt::Display for AttrParseError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AttrParseError::Xml(e) => write!(f, "{}", e),
+            AttrParseError::Parse { key, value, error } => write!(
+                f,
+                "cannot parse value '{}' of attribute '{}': {}",
+                value, key, error
+            ),
+        }
+    }
+}
+
+impl<E: std::error::Error + 'static> std::error::Error for AttrParseError<E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            AttrParseError::Xml(e) => Some(e),
+            AttrParseError::Parse { error, .. } => Some(error),
+        }
+    }
+}
+
 impl<'a> From<(&'a [u8], &'a [u8])> for Attribute<'a> {
     /// Creates new attribute from raw bytes.
     /// Does not apply any transformation to both key and value.
@@ -308,7 +511,7 @@ impl<'a> From<(&'a [u8], &'a [u8])> for Attribute<'a> {
     /// ```
     fn from(val: (&'a [u8], &'a [u8])) -> Attribute<'a> {
         Attribute {
-            key: val.0,
+            key: QName(val.0),
             value: Cow::from(val.1),
         }
     }
@@ -316,27 +519,109 @@ impl<'a> From<(&'a [u8], &'a [u8])> for Attribute<'a> {
 
 impl<'a> From<(&'a str, &'a str)> for Attribute<'a> {
     /// Creates new attribute from text representation.
-    /// Key is stored as-is, but the value will be escaped.
+    /// Key is stored as-is, but the value will be escaped with [`escape_attribute`], so its
+    /// tabs and line breaks are kept, when the value is read back.
     ///
     /// # Examples
     ///
     /// ```
     /// use quick_xml::events::attributes::Attribute;
     ///
-    /// let features = Attribute::from(("features", "Bells & whistles"));
-    /// assert_eq!(features.value, "Bells &amp; whistles".as_bytes());
+    /// let features = Attribute::from(("features", "Bells & whistles\n"));
+    /// assert_eq!(features.value, "Bells &amp; whistles&#10;".as_bytes());
     /// ```
+    ///
+    /// [`escape_attribute`]: ../../escape/fn.escape_attribute.html
     fn from(val: (&'a str, &'a str)) -> Attribute<'a> {
         Attribute {
-            key: val.0.as_bytes(),
-            value: escape(val.1.as_bytes()),
+            key: QName(val.0.as_bytes()),
+            value: escape_attribute(val.1.as_bytes()),
         }
     }
 }
 
+/// The quotes around an attribute value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AttrQuote {
+    /// The value is surrounded by `'`
+    Single,
+    /// The value is surrounded by `"`
+    Double,
+    /// The value is not quoted or missing. Possible only in HTML attributes
+    Unquoted,
+}
+
+/// An attribute together with its positions, yielded by [`SpannedAttributes`].
+///
+/// [`SpannedAttributes`]: struct.SpannedAttributes.html
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpannedAttribute<'a> {
+    /// The attribute itself
+    pub attribute: Attribute<'a>,
+    /// The position of the key
+    pub key_span: Range<usize>,
+    /// The position of the raw value, excluding quotes
+    pub value_span: Range<usize>,
+    /// The quotes around the value
+    pub quote: AttrQuote,
+}
+
+/// Iterator over XML attributes with their positions, created by [`Attributes::with_spans`].
+///
+/// [`Attributes::with_spans`]: struct.Attributes.html#method.with_spans
+#[derive(Clone)]
+pub struct SpannedAttributes<'a> {
+    inner: Attributes<'a>,
+}
+
+impl<'a> SpannedAttributes<'a> {
+    /// Returns the position of the next attribute to parse
+    #[cfg(feature = "serialize")]
+    pub(crate) fn position(&self) -> usize {
+        self.inner.position
+    }
+}
+
+impl<'a> Iterator for SpannedAttributes<'a> {
+    type Item = Result<SpannedAttribute<'a>>;
+    fn next(&mut self) -> Option<Self::Item> {
+        let bytes = self.inner.bytes;
+        self.inner.next_spans().map(|spans| {
+            spans.map(|spans| SpannedAttribute {
+                attribute: Attribute {
+                    key: QName(&bytes[spans.key.clone()]),
+                    value: Cow::Borrowed(&bytes[spans.value.clone()]),
+                },
+                key_span: spans.key,
+                value_span: spans.value,
+                quote: spans.quote,
+            })
+        })
+    }
+}
+
+/// Positions of the key and the value of an attribute in the attributes buffer
+struct AttrSpans {
+    key: Range<usize>,
+    value: Range<usize>,
+    quote: AttrQuote,
+}
+
 impl<'a> Iterator for Attributes<'a> {
     type Item = Result<Attribute<'a>>;
     fn next(&mut self) -> Option<Self::Item> {
+        self.next_spans().map(|spans| {
+            spans.map(|spans| Attribute {
+                key: QName(&self.bytes[spans.key]),
+                value: Cow::Borrowed(&self.bytes[spans.value]),
+            })
+        })
+    }
+}
+
+impl<'a> Attributes<'a> {
+    /// Parses the next attribute, returning its positions in `bytes`
+    fn next_spans(&mut self) -> Option<Result<AttrSpans>> {
         let len = self.bytes.len();
 
         macro_rules! err {
@@ -347,19 +632,23 @@ impl<'a> Iterator for Attributes<'a> {
         }
 
         macro_rules! attr {
-            ($key:expr) => {{
+            ($key:expr, $err:expr) => {{
                 self.position = len;
                 if self.html {
-                    attr!($key, 0..0)
+                    let key = $key;
+                    attr!(key.clone(), key.end..key.end, AttrQuote::Unquoted)
+                } else if self.with_checks {
+                    err!($err);
                 } else {
                     return None;
                 };
             }};
-            ($key:expr, $val:expr) => {
-                return Some(Ok(Attribute {
-                    key: &self.bytes[$key],
-                    value: Cow::Borrowed(&self.bytes[$val]),
-                }));
+            ($key:expr, $val:expr, $quote:expr) => {
+                return Some(Ok(AttrSpans {
+                    key: $key,
+                    value: $val,
+                    quote: $quote,
+                }))
             };
         }
 
@@ -376,7 +665,14 @@ impl<'a> Iterator for Attributes<'a> {
             .find(|&(_, &b)| !is_whitespace(b))
         {
             Some((i, _)) => i,
-            None => attr!(self.position..len),
+            None => {
+                // only whitespaces left
+                if self.html {
+                    attr!(self.position..len, Error::NoEqAfterName(len));
+                }
+                self.position = len;
+                return None;
+            }
         };
 
         // key ends with either whitespace or =
@@ -385,29 +681,29 @@ impl<'a> Iterator for Attributes<'a> {
             .find(|&(_, &b)| b == b'=' || is_whitespace(b))
         {
             Some((i, &b'=')) => i,
-            Some((i, &b'\'')) | Some((i, &b'"')) if self.with_checks => {
-                err!(Error::NameWithQuote(i));
-            }
             Some((i, _)) => {
                 // consume until `=` or return if html
                 match bytes.by_ref().find(|&(_, &b)| !is_whitespace(b)) {
                     Some((_, &b'=')) => i,
                     Some((j, _)) if self.html => {
                         self.position = j - 1;
-                        attr!(start_key..i, 0..0);
+                        attr!(start_key..i, i..i, AttrQuote::Unquoted);
                     }
                     Some((j, _)) => err!(Error::NoEqAfterName(j)),
                     None if self.html => {
                         self.position = len;
-                        attr!(start_key..len, 0..0);
+                        attr!(start_key..len, len..len, AttrQuote::Unquoted);
                     }
                     None => err!(Error::NoEqAfterName(len)),
                 }
             }
-            None => attr!(start_key..len),
+            None => attr!(start_key..len, Error::NoEqAfterName(len)),
         };
 
         if self.with_checks {
+            if let Err(e) = check_name(&self.bytes[start_key..end_key], start_key) {
+                err!(e);
+            }
             if let Some(start) = self
                 .consumed
                 .iter()
@@ -423,10 +719,16 @@ impl<'a> Iterator for Attributes<'a> {
         // value has quote if not html
         match bytes.by_ref().find(|&(_, &b)| !is_whitespace(b)) {
             Some((i, quote @ &b'\'')) | Some((i, quote @ &b'"')) => {
-                match bytes.by_ref().find(|&(_, &b)| b == *quote) {
-                    Some((j, _)) => {
+                match memchr::memchr(*quote, &self.bytes[i + 1..]) {
+                    Some(j) => {
+                        let j = i + 1 + j;
                         self.position = j + 1;
-                        attr!(start_key..end_key, i + 1..j)
+                        let quote = if *quote == b'"' {
+                            AttrQuote::Double
+                        } else {
+                            AttrQuote::Single
+                        };
+                        attr!(start_key..end_key, i + 1..j, quote)
                     }
                     None => err!(Error::UnquotedValue(i)),
                 }
@@ -437,12 +739,31 @@ impl<'a> Iterator for Attributes<'a> {
                     .find(|&(_, &b)| is_whitespace(b))
                     .map_or(len, |(j, _)| j);
                 self.position = j;
-                attr!(start_key..end_key, i..j)
+                attr!(start_key..end_key, i..j, AttrQuote::Unquoted)
             }
             Some((i, _)) => err!(Error::UnquotedValue(i)),
-            None => attr!(start_key..end_key),
+            None => attr!(start_key..end_key, Error::UnquotedValue(len)),
+        }
+    }
+}
+
+/// Checks that `name` contains only characters allowed in XML names. Non-ASCII bytes are
+/// always accepted. `offset` is the position of `name` used in the returned error.
+fn check_name(name: &[u8], offset: usize) -> Result<()> {
+    for (i, &b) in name.iter().enumerate() {
+        let valid = match b {
+            b'a'..=b'z' | b'A'..=b'Z' | b'_' | b':' | 0x80..=0xFF => true,
+            b'0'..=b'9' | b'-' | b'.' => i > 0,
+            _ => false,
+        };
+        if !valid {
+            return Err(match b {
+                b'\'' | b'"' => Error::NameWithQuote(offset + i),
+                _ => Error::InvalidAttributeName(offset + i),
+            });
         }
     }
+    Ok(())
 }
 
 #[cfg(test)]
@@ -455,10 +776,10 @@ mod tests {
         let mut attributes = Attributes::new(event, 0);
         attributes.with_checks(true);
         let a = attributes.next().unwrap().unwrap();
-        assert_eq!(a.key, b"a");
+        assert_eq!(a.key.as_ref(), b"a");
         assert_eq!(&*a.value, b"a");
         let a = attributes.next().unwrap().unwrap();
-        assert_eq!(a.key, b"b");
+        assert_eq!(a.key.as_ref(), b"b");
         assert_eq!(&*a.value, b"b");
         assert!(attributes.next().is_none());
     }
@@ -469,13 +790,13 @@ mod tests {
         let mut attributes = Attributes::new(event, 0);
         attributes.with_checks(true);
         let a = attributes.next().unwrap().unwrap();
-        assert_eq!(a.key, b"a");
+        assert_eq!(a.key.as_ref(), b"a");
         assert_eq!(&*a.value, b"a");
         let a = attributes.next().unwrap().unwrap();
-        assert_eq!(a.key, b"b");
+        assert_eq!(a.key.as_ref(), b"b");
         assert_eq!(&*a.value, b"b");
         let a = attributes.next().unwrap().unwrap();
-        assert_eq!(a.key, b"c");
+        assert_eq!(a.key.as_ref(), b"c");
         assert_eq!(&*a.value, b"cc\"cc");
         assert!(attributes.next().is_none());
     }
@@ -486,7 +807,7 @@ mod tests {
         let mut attributes = Attributes::new(event, 0);
         attributes.with_checks(true);
         let a = attributes.next().unwrap().unwrap();
-        assert_eq!(a.key, b"a");
+        assert_eq!(a.key.as_ref(), b"a");
         assert_eq!(&*a.value, b"a");
         assert!(attributes.next().unwrap().is_err());
     }
@@ -497,23 +818,193 @@ mod tests {
         let mut attributes = Attributes::html(event, 0);
         attributes.with_checks(true);
         let a = attributes.next().unwrap().unwrap();
-        assert_eq!(a.key, b"a");
+        assert_eq!(a.key.as_ref(), b"a");
         assert_eq!(&*a.value, b"a");
         let a = attributes.next().unwrap().unwrap();
-        assert_eq!(a.key, b"e");
+        assert_eq!(a.key.as_ref(), b"e");
         assert_eq!(&*a.value, b"");
         let a = attributes.next().unwrap().unwrap();
-        assert_eq!(a.key, b"b");
+        assert_eq!(a.key.as_ref(), b"b");
         assert_eq!(&*a.value, b"b");
         let a = attributes.next().unwrap().unwrap();
-        assert_eq!(a.key, b"c");
+        assert_eq!(a.key.as_ref(), b"c");
         assert_eq!(&*a.value, b"");
         let a = attributes.next().unwrap().unwrap();
-        assert_eq!(a.key, b"d");
+        assert_eq!(a.key.as_ref(), b"d");
         assert_eq!(&*a.value, b"");
         let a = attributes.next().unwrap().unwrap();
-        assert_eq!(a.key, b"ee");
+        assert_eq!(a.key.as_ref(), b"ee");
         assert_eq!(&*a.value, b"ee");
         assert!(attributes.next().is_none());
     }
+
+    #[test]
+    fn decode_and_parse() {
+        #[derive(Debug, PartialEq)]
+        enum Color {
+            Red,
+            Green,
+        }
+
+        impl FromStr for Color {
+            type Err = String;
+
+            fn from_str(s: &str) -> ::std::result::Result<Self, String> {
+                match s {
+                    "red" => Ok(Color::Red),
+                    "green" => Ok(Color::Green),
+                    _ => Err(format!("unknown color '{}'", s)),
+                }
+            }
+        }
+
+        let decoder = Reader::from_str("").decoder();
+        let attr = |value: &'static str| Attribute::from(("a".as_bytes(), value.as_bytes()));
+
+        assert_eq!(
+            attr("18446744073709551615")
+                .decode_and_parse::<u64>(decoder)
+                .unwrap(),
+            u64::max_value()
+        );
+        assert_eq!(
+            attr(" -1.5e3\n").decode_and_parse::<f64>(decoder).unwrap(),
+            -1500.0
+        );
+        assert_eq!(
+            attr("\ttrue ").decode_and_parse::<bool>(decoder).unwrap(),
+            true
+        );
+        assert_eq!(
+            attr("&#x72;ed").decode_and_parse::<Color>(decoder).unwrap(),
+            Color::Red
+        );
+        assert_eq!(
+            attr("green").decode_and_parse::<Color>(decoder).unwrap(),
+            Color::Green
+        );
+
+        match attr("&lt;blue&gt;").decode_and_parse::<Color>(decoder) {
+            Err(AttrParseError::Parse { key, value, error }) => {
+                assert_eq!(key, "a");
+                assert_eq!(value, "&lt;blue&gt;");
+                assert_eq!(error, "unknown color '<blue>'");
+            }
+            x => panic!("Expected `Parse` error, got {:?}", x),
+        }
+        assert_eq!(
+            attr("-1")
+                .decode_and_parse::<u64>(decoder)
+                .unwrap_err()
+                .to_string(),
+            "cannot parse value '-1' of attribute 'a': invalid digit found in string"
+        );
+        match attr("&unknown;").decode_and_parse::<u64>(decoder) {
+            Err(AttrParseError::Xml(Error::EscapeError(_))) => {}
+            x => panic!("Expected `Xml` error, got {:?}", x),
+        }
+    }
+
+    #[test]
+    fn spans() {
+        let event = br#"name a='"x"' bb = "it's" c='' d="""#;
+        let mut attributes = Attributes::new(event, 4).with_spans();
+
+        let a = attributes.next().unwrap().unwrap();
+        assert_eq!(a.attribute.key.as_ref(), b"a");
+        assert_eq!(&*a.attribute.value, br#""x""#);
+        assert_eq!(a.key_span, 5..6);
+        assert_eq!(a.value_span, 8..11);
+        assert_eq!(a.quote, AttrQuote::Single);
+
+        let a = attributes.next().unwrap().unwrap();
+        assert_eq!(&event[a.key_span.clone()], b"bb");
+        assert_eq!(&event[a.value_span.clone()], b"it's");
+        assert_eq!(a.quote, AttrQuote::Double);
+
+        let a = attributes.next().unwrap().unwrap();
+        assert_eq!(&event[a.key_span.clone()], b"c");
+        assert_eq!(a.value_span, 28..28);
+        assert_eq!(a.quote, AttrQuote::Single);
+
+        let a = attributes.next().unwrap().unwrap();
+        assert_eq!(&event[a.key_span.clone()], b"d");
+        assert_eq!(a.value_span, 33..33);
+        assert_eq!(a.quote, AttrQuote::Double);
+
+        assert!(attributes.next().is_none());
+    }
+
+    #[test]
+    fn html_spans() {
+        let event = b"name a=b c d='e'";
+        let mut attributes = Attributes::html(event, 4).with_spans();
+
+        let a = attributes.next().unwrap().unwrap();
+        assert_eq!(&event[a.key_span.clone()], b"a");
+        assert_eq!(&event[a.value_span.clone()], b"b");
+        assert_eq!(a.quote, AttrQuote::Unquoted);
+
+        let a = attributes.next().unwrap().unwrap();
+        assert_eq!(&event[a.key_span.clone()], b"c");
+        assert_eq!(a.value_span, 10..10);
+        assert_eq!(a.quote, AttrQuote::Unquoted);
+
+        let a = attributes.next().unwrap().unwrap();
+        assert_eq!(&event[a.key_span.clone()], b"d");
+        assert_eq!(&event[a.value_span.clone()], b"e");
+        assert_eq!(a.quote, AttrQuote::Single);
+
+        assert!(attributes.next().is_none());
+    }
+
+    #[test]
+    fn checked() {
+        let mut attributes = Attributes::new(b"name a='1' 1b='2'", 4);
+        match attributes.next() {
+            Some(Ok(a)) => assert_eq!(a.key.as_ref(), b"a"),
+            x => panic!("Expected `Some(Ok(_))`, but got `{:?}`", x),
+        }
+        match attributes.next() {
+            Some(Err(Error::InvalidAttributeName(11))) => {}
+            x => panic!("Expected `InvalidAttributeName(11)`, but got `{:?}`", x),
+        }
+        assert!(attributes.next().is_none());
+
+        match Attributes::new(b"name a<b='1'", 4).next() {
+            Some(Err(Error::InvalidAttributeName(6))) => {}
+            x => panic!("Expected `InvalidAttributeName(6)`, but got `{:?}`", x),
+        }
+        match Attributes::new(b"name a\"b='1'", 4).next() {
+            Some(Err(Error::NameWithQuote(6))) => {}
+            x => panic!("Expected `NameWithQuote(6)`, but got `{:?}`", x),
+        }
+        match Attributes::new(b"name a", 4).next() {
+            Some(Err(Error::NoEqAfterName(6))) => {}
+            x => panic!("Expected `NoEqAfterName(6)`, but got `{:?}`", x),
+        }
+        match Attributes::new(b"name a=", 4).next() {
+            Some(Err(Error::UnquotedValue(7))) => {}
+            x => panic!("Expected `UnquotedValue(7)`, but got `{:?}`", x),
+        }
+        assert!(Attributes::new(b"name a='1' ", 4).nth(1).is_none());
+    }
+
+    #[test]
+    fn unchecked() {
+        let mut attributes = Attributes::new(b"name 1b='1' a<b='2' a='3' a='4' c", 4);
+        attributes.with_checks(false);
+        let keys: Vec<_> = attributes
+            .map(|a| a.unwrap().key.as_ref().to_vec())
+            .collect();
+        assert_eq!(keys, vec![&b"1b"[..], b"a<b", b"a", b"a"]);
+
+        // Unbalanced quotes are always reported
+        let mut attributes = Attributes::new(b"name a='1", 4);
+        attributes.with_checks(false);
+        match attributes.next() {
+            Some(Err(Error::UnquotedValue(7))) => {}
+            x => panic!("Expected `UnquotedValue(7)`, but got `{:?}`", x),
+        }
+    }
 }
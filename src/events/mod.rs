@@ -42,15 +42,18 @@ use encoding_rs::Encoding;
 use std::borrow::Cow;
 use std::collections::HashMap;
 use std::io::BufRead;
-use std::ops::Deref;
+use std::ops::{Deref, Range};
 use std::str::from_utf8;
 
-use self::attributes::{Attribute, Attributes};
+#[cfg(feature = "serde-types")]
+use serde::{de, Deserialize, Deserializer, Serialize};
+
+use self::attributes::{AttrQuote, Attribute, Attributes};
 use errors::{Error, Result};
-use escape::{do_unescape, escape};
+use escape::{do_unescape, do_unescape_with_html, escape, unescape_with_resolver};
+use name::{LocalName, QName};
 use reader::Reader;
-
-use memchr;
+use writer::{FmtSink, Writer};
 
 /// Opening tag data (`Event::Start`), with optional attributes.
 ///
@@ -64,6 +67,7 @@ use memchr;
 /// [`unescaped`]: #method.unescaped
 /// [`attributes`]: #method.attributes
 #[derive(Clone)]
+#[cfg_attr(feature = "serde-types", derive(Serialize))]
 pub struct BytesStart<'a> {
     /// content of the element, before any utf8 conversion
     buf: Cow<'a, [u8]>,
@@ -71,6 +75,28 @@ pub struct BytesStart<'a> {
     name_len: usize,
 }
 
+/// Checks that the name is within the content, so `name()` cannot panic
+#[cfg(feature = "serde-types")]
+impl<'de, 'a> Deserialize<'de> for BytesStart<'a> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> ::std::result::Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(rename = "BytesStart")]
+        struct Fields<'a> {
+            buf: Cow<'a, [u8]>,
+            name_len: usize,
+        }
+
+        let Fields { buf, name_len } = Fields::deserialize(deserializer)?;
+        if name_len > buf.len() {
+            return Err(de::Error::invalid_value(
+                de::Unexpected::Unsigned(name_len as u64),
+                &"`name_len` not greater than the length of `buf`",
+            ));
+        }
+        Ok(BytesStart { buf, name_len })
+    }
+}
+
 impl<'a> BytesStart<'a> {
     /// Creates a new `BytesStart` from the given content (name + attributes).
     ///
@@ -118,6 +144,53 @@ impl<'a> BytesStart<'a> {
         }
     }
 
+    /// Creates a new `BytesStart` from the given name, with space for at least `capacity`
+    /// bytes of the name and attributes, so attributes can be added without reallocations.
+    ///
+    /// Owns its contents.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use quick_xml::events::BytesStart;
+    /// let mut tag = BytesStart::with_capacity("point", 64);
+    /// tag.push_attribute(("x", "1"));
+    /// tag.push_attribute(("y", "2"));
+    /// assert_eq!(&*tag, br#"point x="1" y="2""#.as_ref());
+    /// ```
+    pub fn with_capacity<N: AsRef<[u8]>>(name: N, capacity: usize) -> BytesStart<'static> {
+        let name = name.as_ref();
+        let mut content = Vec::with_capacity(capacity.max(name.len()));
+        content.extend_from_slice(name);
+        Self::owned(content, name.len())
+    }
+
+    /// Creates a new `BytesStart` from the given name, with space for at least `n_bytes`
+    /// bytes of attributes after the name, so they can be added without reallocations.
+    ///
+    /// Owns its contents.
+    ///
+    /// # Examples
+    ///
+    /// Copying attributes of a tag into a new tag allocates the content only once:
+    ///
+    /// ```
+    /// # use quick_xml::events::BytesStart;
+    /// let source = BytesStart::borrowed(br#"rect width="10" height="20""#, 4);
+    /// let attributes = source.len() - source.name().len();
+    ///
+    /// let mut tag = BytesStart::with_attributes_capacity("svg:rect", attributes);
+    /// tag.extend_attributes(source.attributes().map(|a| a.unwrap()));
+    /// assert_eq!(&*tag, br#"svg:rect width="10" height="20""#.as_ref());
+    /// ```
+    pub fn with_attributes_capacity<N: AsRef<[u8]>>(
+        name: N,
+        n_bytes: usize,
+    ) -> BytesStart<'static> {
+        let name = name.as_ref();
+        Self::with_capacity(name, name.len() + n_bytes)
+    }
+
     /// Converts the event into an owned event.
     pub fn into_owned(self) -> BytesStart<'static> {
         Self::owned(self.buf.into_owned(), self.name_len)
@@ -128,6 +201,31 @@ impl<'a> BytesStart<'a> {
         Self::owned(self.buf.to_owned(), self.name_len)
     }
 
+    /// Converts the event into an owned event without taking ownership of Event, with space
+    /// for at least `additional` more bytes of attributes.
+    pub fn to_owned_with_capacity(&self, additional: usize) -> BytesStart<'static> {
+        let mut content = Vec::with_capacity(self.buf.len() + additional);
+        content.extend_from_slice(&self.buf);
+        Self::owned(content, self.name_len)
+    }
+
+    /// Reserves space for at least `additional` more bytes of attributes, so that
+    /// [`push_attribute`] does not reallocate the content for each attribute.
+    ///
+    /// A borrowed content is copied into an owned buffer of the required size.
+    ///
+    /// [`push_attribute`]: #method.push_attribute
+    pub fn reserve(&mut self, additional: usize) {
+        match self.buf {
+            Cow::Borrowed(content) => {
+                let mut owned = Vec::with_capacity(content.len() + additional);
+                owned.extend_from_slice(content);
+                self.buf = Cow::Owned(owned);
+            }
+            Cow::Owned(ref mut content) => content.reserve(additional),
+        }
+    }
+
     /// Converts the event into a borrowed event. Most useful when paired with [`to_end`].
     ///
     /// # Example
@@ -158,7 +256,7 @@ impl<'a> BytesStart<'a> {
 
     /// Creates new paired close tag
     pub fn to_end(&self) -> BytesEnd {
-        BytesEnd::borrowed(self.name())
+        BytesEnd::borrowed(self.name().into_inner())
     }
 
     /// Consumes `self` and yield a new `BytesStart` with additional attributes from an iterator.
@@ -175,19 +273,52 @@ impl<'a> BytesStart<'a> {
         self
     }
 
-    /// Gets the undecoded raw tag name as a `&[u8]`.
+    /// Extracts the inner `Cow` with the name and attributes.
+    #[inline]
+    pub(crate) fn into_inner(self) -> Cow<'a, [u8]> {
+        self.buf
+    }
+
+    /// Returns the name and attributes, if they are borrowed from the input
+    #[cfg(feature = "serialize")]
+    #[inline]
+    pub(crate) fn borrowed_buf(&self) -> Option<&'a [u8]> {
+        match self.buf {
+            Cow::Borrowed(buf) => Some(buf),
+            Cow::Owned(_) => None,
+        }
+    }
+
+    /// Gets the undecoded raw tag name, as present in the input stream.
     #[inline]
-    pub fn name(&self) -> &[u8] {
-        &self.buf[..self.name_len]
+    pub fn name(&self) -> QName<'_> {
+        QName(&self.buf[..self.name_len])
     }
 
-    /// Gets the undecoded raw local tag name (excluding namespace) as a `&[u8]`.
+    /// Gets the undecoded raw local tag name (excluding namespace) as present
+    /// in the input stream.
     ///
     /// All content up to and including the first `:` character is removed from the tag name.
     #[inline]
-    pub fn local_name(&self) -> &[u8] {
-        let name = self.name();
-        memchr::memchr(b':', name).map_or(name, |i| &name[i + 1..])
+    pub fn local_name(&self) -> LocalName<'_> {
+        self.name().local_name()
+    }
+
+    /// Checks if the local name of this tag is equal to the local name of `other`,
+    /// ignoring the namespace prefixes of both names.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use quick_xml::events::{BytesEnd, BytesStart};
+    /// let start = BytesStart::borrowed_name(b"ns1:item");
+    /// assert!(start.name_eq_ignore_prefix(BytesEnd::borrowed(b"item").name()));
+    /// assert!(start.name_eq_ignore_prefix(BytesEnd::borrowed(b"ns2:item").name()));
+    /// assert!(!start.name_eq_ignore_prefix(BytesEnd::borrowed(b"ns1:other").name()));
+    /// ```
+    #[inline]
+    pub fn name_eq_ignore_prefix(&self, other: QName) -> bool {
+        self.local_name() == other.local_name()
     }
 
     /// Gets the unescaped tag name.
@@ -238,8 +369,85 @@ impl<'a> BytesStart<'a> {
         Attributes::html(self, self.name_len)
     }
 
+    /// Returns the first attribute with the `attr_name` key, or `None` if there is no such
+    /// attribute.
+    ///
+    /// Attributes are parsed only up to the first match, so errors in the attributes after it,
+    /// including duplicates of the matched attribute, are not reported.
+    pub fn try_get_attribute<N: AsRef<[u8]>>(&self, attr_name: N) -> Result<Option<Attribute<'_>>> {
+        for attr in self.attributes() {
+            let attr = attr?;
+            if attr.key.as_ref() == attr_name.as_ref() {
+                return Ok(Some(attr));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Returns the first attribute with the `local_name` in the `namespace`, or `None` if there
+    /// is no such attribute. The namespace of each attribute is resolved by the
+    /// [`Reader::attribute_namespace`], so `namespace_buffer` should be the same buffer, which
+    /// was passed to the [`Reader::read_namespaced_event`] returned this tag.
+    ///
+    /// Unqualified attributes are matched with `namespace` equal to `None`.
+    ///
+    /// Attributes are parsed only up to the first match, so errors in the attributes after it,
+    /// including duplicates of the matched attribute, are not reported.
+    ///
+    /// [`Reader::attribute_namespace`]: ../reader/struct.Reader.html#method.attribute_namespace
+    /// [`Reader::read_namespaced_event`]: ../reader/struct.Reader.html#method.read_namespaced_event
+    pub fn try_get_attribute_ns<B: BufRead, N: AsRef<[u8]>>(
+        &self,
+        reader: &Reader<B>,
+        namespace_buffer: &[u8],
+        namespace: Option<&[u8]>,
+        local_name: N,
+    ) -> Result<Option<Attribute<'_>>> {
+        for attr in self.attributes() {
+            let attr = attr?;
+            let (ns, local) = reader.attribute_namespace(attr.key, namespace_buffer);
+            if ns == namespace && local.as_ref() == local_name.as_ref() {
+                return Ok(Some(attr));
+            }
+        }
+        Ok(None)
+    }
+
     /// Gets the undecoded raw string with the attributes of this tag as a `&[u8]`,
     /// including the whitespace after the tag name if there is any.
+    ///
+    /// This is everything after the tag name up to the closing `>` (or `/>` for
+    /// empty elements). The bytes are returned as they are in the document: they
+    /// are not validated, and values are neither unescaped nor normalized. This
+    /// allows to cheaply filter tags, for example, by searching for a substring,
+    /// before parsing their [`attributes`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use quick_xml::events::Event;
+    /// use quick_xml::Reader;
+    ///
+    /// let mut reader = Reader::from_str(r#"<a id="1"/><a id="2" class="x &amp; y"/>"#);
+    /// let mut buf = Vec::new();
+    /// let mut ids = Vec::new();
+    /// loop {
+    ///     match reader.read_event(&mut buf).unwrap() {
+    ///         // Parse attributes only of the tags, that mention `class`
+    ///         Event::Empty(e) if e.attributes_raw().windows(5).any(|w| w == b"class") => {
+    ///             assert_eq!(e.attributes_raw(), br#" id="2" class="x &amp; y""#);
+    ///             let id = e.try_get_attribute("id").unwrap().unwrap();
+    ///             ids.push(id.value.into_owned());
+    ///         }
+    ///         Event::Eof => break,
+    ///         _ => {}
+    ///     }
+    ///     buf.clear();
+    /// }
+    /// assert_eq!(ids, vec![b"2".to_vec()]);
+    /// ```
+    ///
+    /// [`attributes`]: #method.attributes
     #[inline]
     pub fn attributes_raw(&self) -> &[u8] {
         &self.buf[self.name_len..]
@@ -308,7 +516,8 @@ impl<'a> BytesStart<'a> {
     ) -> Result<String> {
         let decoded = reader.decode(&*self);
         let unescaped =
-            do_unescape(decoded.as_bytes(), custom_entities).map_err(Error::EscapeError)?;
+            do_unescape_with_html(decoded.as_bytes(), custom_entities, reader.html_entities())
+                .map_err(Error::EscapeError)?;
         String::from_utf8(unescaped.into_owned()).map_err(|e| Error::Utf8(e.utf8_error()))
     }
 
@@ -321,21 +530,165 @@ impl<'a> BytesStart<'a> {
     ) -> Result<String> {
         let decoded = reader.decode(&*self)?;
         let unescaped =
-            do_unescape(decoded.as_bytes(), custom_entities).map_err(Error::EscapeError)?;
+            do_unescape_with_html(decoded.as_bytes(), custom_entities, reader.html_entities())
+                .map_err(Error::EscapeError)?;
         String::from_utf8(unescaped.into_owned()).map_err(|e| Error::Utf8(e.utf8_error()))
     }
 
     /// Adds an attribute to this element.
+    ///
+    /// A value of a `(&str, &str)` pair is a plain text, which is escaped, while a value of
+    /// a `(&[u8], &[u8])` pair is written as is. Use [`push_escaped_attribute`] to add
+    /// an already escaped string value.
+    ///
+    /// Tabs and line breaks of a plain text are written as character references, because
+    /// the whitespace characters, written as is, are replaced by spaces, when the value is
+    /// read. To write them as is, escape the value with [`escape_str`] and add it with
+    /// [`push_escaped_attribute`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use quick_xml::events::BytesStart;
+    /// use quick_xml::escape::escape_str;
+    ///
+    /// let mut tag = BytesStart::borrowed_name(b"tag");
+    /// tag.push_attribute(("escaped", "a\nb"));
+    /// tag.push_escaped_attribute("literal", &escape_str("a\nb"));
+    /// assert_eq!(&*tag, b"tag escaped=\"a&#10;b\" literal=\"a\nb\"".as_ref());
+    /// ```
+    ///
+    /// [`push_escaped_attribute`]: #method.push_escaped_attribute
+    /// [`escape_str`]: ../escape/fn.escape_str.html
     pub fn push_attribute<'b, A: Into<Attribute<'b>>>(&mut self, attr: A) {
         let a = attr.into();
         let bytes = self.buf.to_mut();
+        // ` key="value"`
+        bytes.reserve(a.key.len() + a.value.len() + 4);
         bytes.push(b' ');
-        bytes.extend_from_slice(a.key);
+        bytes.extend_from_slice(&a.key);
         bytes.extend_from_slice(b"=\"");
         bytes.extend_from_slice(&*a.value);
         bytes.push(b'"');
     }
 
+    /// Adds an attribute to this element, which value is quoted with `'` if `quote` is
+    /// [`AttrQuote::Single`], and with `"` otherwise, for example, to keep the quotes of
+    /// the attributes copied from another element.
+    ///
+    /// The value is converted in the same way as by [`push_attribute`] and must not contain
+    /// the quote character.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use quick_xml::events::BytesStart;
+    /// let source = BytesStart::borrowed(br#"tag a='"' b="'""#, 3);
+    /// let mut tag = BytesStart::borrowed_name(b"copy");
+    /// for attr in source.attributes().with_spans() {
+    ///     let attr = attr.unwrap();
+    ///     tag.push_attribute_with_quote(attr.attribute, attr.quote);
+    /// }
+    /// assert_eq!(&*tag, br#"copy a='"' b="'""#.as_ref());
+    /// ```
+    ///
+    /// [`AttrQuote::Single`]: attributes/enum.AttrQuote.html#variant.Single
+    /// [`push_attribute`]: #method.push_attribute
+    pub fn push_attribute_with_quote<'b, A: Into<Attribute<'b>>>(
+        &mut self,
+        attr: A,
+        quote: AttrQuote,
+    ) {
+        let a = attr.into();
+        let quote = match quote {
+            AttrQuote::Single => b'\'',
+            AttrQuote::Double | AttrQuote::Unquoted => b'"',
+        };
+        let bytes = self.buf.to_mut();
+        bytes.reserve(a.key.len() + a.value.len() + 4);
+        bytes.push(b' ');
+        bytes.extend_from_slice(&a.key);
+        bytes.push(b'=');
+        bytes.push(quote);
+        bytes.extend_from_slice(&a.value);
+        bytes.push(quote);
+    }
+
+    /// Adds an attribute with an already escaped value to this element.
+    ///
+    /// The value is written as is, so it must not contain markup characters
+    /// or `&` which does not start an entity or character reference.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use quick_xml::events::BytesStart;
+    /// let mut tag = BytesStart::borrowed_name(b"tag");
+    /// tag.push_attribute(("plain", "a & b"));
+    /// tag.push_escaped_attribute("escaped", "a &amp; b");
+    /// assert_eq!(&*tag, br#"tag plain="a &amp; b" escaped="a &amp; b""#.as_ref());
+    /// ```
+    pub fn push_escaped_attribute(&mut self, key: &str, value: &str) {
+        self.push_attribute((key.as_bytes(), value.as_bytes()));
+    }
+
+    /// Removes the first attribute with the `attr_name` key from this element.
+    ///
+    /// Returns `true` if the attribute was found and removed. Attributes are searched only up to
+    /// the first malformed one, so an attribute after it is never removed.
+    pub fn remove_attribute<N: AsRef<[u8]>>(&mut self, attr_name: N) -> bool {
+        match self.find_attribute(attr_name.as_ref()) {
+            Some(range) => {
+                self.buf.to_mut().drain(range);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Replaces the value of the first attribute with the same key as `attr`, or adds `attr`
+    /// to this element if there is no such attribute.
+    ///
+    /// The replaced attribute is written in the same form as by [`push_attribute`], at the
+    /// place of the old one.
+    ///
+    /// [`push_attribute`]: #method.push_attribute
+    pub fn replace_attribute<'b, A: Into<Attribute<'b>>>(
+        &mut self,
+        attr: A,
+    ) -> &mut BytesStart<'a> {
+        let a = attr.into();
+        match self.find_attribute(&a.key) {
+            Some(range) => {
+                let mut bytes = Vec::with_capacity(a.key.len() + a.value.len() + 4);
+                bytes.push(b' ');
+                bytes.extend_from_slice(&a.key);
+                bytes.extend_from_slice(b"=\"");
+                bytes.extend_from_slice(&a.value);
+                bytes.push(b'"');
+                self.buf.to_mut().splice(range, bytes);
+            }
+            None => self.push_attribute(a),
+        }
+        self
+    }
+
+    /// Returns the range of `buf` occupied by the first attribute with the `attr_name` key,
+    /// including the whitespace before it.
+    fn find_attribute(&self, attr_name: &[u8]) -> Option<Range<usize>> {
+        let mut attributes = self.attributes();
+        loop {
+            let start = attributes.position;
+            match attributes.next() {
+                Some(Ok(a)) if a.key.as_ref() == attr_name => {
+                    return Some(start..attributes.position)
+                }
+                Some(Ok(_)) => {}
+                _ => return None,
+            }
+        }
+    }
+
     /// Edit the name of the BytesStart in-place
     ///
     /// # Warning
@@ -369,6 +722,7 @@ impl<'a> std::fmt::Debug for BytesStart<'a> {
 ///
 /// [W3C XML 1.1 Prolog and Document Type Declaration](http://w3.org/TR/xml11/#sec-prolog-dtd)
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde-types", derive(Deserialize, Serialize))]
 pub struct BytesDecl<'a> {
     element: BytesStart<'a>,
 }
@@ -385,11 +739,11 @@ impl<'a> BytesDecl<'a> {
         match self.element.attributes().next() {
             Some(Err(e)) => Err(e),
             Some(Ok(Attribute {
-                key: b"version",
+                key: QName(b"version"),
                 value: v,
             })) => Ok(v),
             Some(Ok(a)) => {
-                let found = from_utf8(a.key).map_err(Error::Utf8)?.to_string();
+                let found = from_utf8(&a.key).map_err(Error::Utf8)?.to_string();
                 Err(Error::XmlDeclWithoutVersion(Some(found)))
             }
             None => Err(Error::XmlDeclWithoutVersion(None)),
@@ -402,7 +756,7 @@ impl<'a> BytesDecl<'a> {
             match a {
                 Err(e) => return Some(Err(e)),
                 Ok(Attribute {
-                    key: b"encoding",
+                    key: QName(b"encoding"),
                     value: v,
                 }) => return Some(Ok(v)),
                 _ => (),
@@ -417,7 +771,7 @@ impl<'a> BytesDecl<'a> {
             match a {
                 Err(e) => return Some(Err(e)),
                 Ok(Attribute {
-                    key: b"standalone",
+                    key: QName(b"standalone"),
                     value: v,
                 }) => return Some(Ok(v)),
                 _ => (),
@@ -491,8 +845,34 @@ impl<'a> BytesDecl<'a> {
 
 /// A struct to manage `Event::End` events
 #[derive(Clone)]
+#[cfg_attr(feature = "serde-types", derive(Serialize))]
 pub struct BytesEnd<'a> {
+    /// content of the closing tag: the name, followed by the eventual whitespaces
     name: Cow<'a, [u8]>,
+    /// end of the element name, the name starts at the start of `name`
+    name_len: usize,
+}
+
+/// Checks that the name is within the content, so `name()` cannot panic
+#[cfg(feature = "serde-types")]
+impl<'de, 'a> Deserialize<'de> for BytesEnd<'a> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> ::std::result::Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(rename = "BytesEnd")]
+        struct Fields<'a> {
+            name: Cow<'a, [u8]>,
+            name_len: usize,
+        }
+
+        let Fields { name, name_len } = Fields::deserialize(deserializer)?;
+        if name_len > name.len() {
+            return Err(de::Error::invalid_value(
+                de::Unexpected::Unsigned(name_len as u64),
+                &"`name_len` not greater than the length of `name`",
+            ));
+        }
+        Ok(BytesEnd { name, name_len })
+    }
 }
 
 impl<'a> BytesEnd<'a> {
@@ -501,6 +881,7 @@ impl<'a> BytesEnd<'a> {
     pub fn borrowed(name: &'a [u8]) -> BytesEnd<'a> {
         BytesEnd {
             name: Cow::Borrowed(name),
+            name_len: name.len(),
         }
     }
 
@@ -508,33 +889,63 @@ impl<'a> BytesEnd<'a> {
     #[inline]
     pub fn owned(name: Vec<u8>) -> BytesEnd<'static> {
         BytesEnd {
+            name_len: name.len(),
             name: Cow::Owned(name),
         }
     }
 
+    /// Creates a new `BytesEnd` from the content of a closing tag `</name  >`: the name
+    /// followed by whitespaces, which are written back by the [`Writer`].
+    ///
+    /// # Warning
+    ///
+    /// `&content[name_len..]` is not checked to contain only whitespaces
+    ///
+    /// [`Writer`]: ../writer/struct.Writer.html
+    #[inline]
+    pub fn borrowed_content(content: &'a [u8], name_len: usize) -> BytesEnd<'a> {
+        BytesEnd {
+            name: Cow::Borrowed(content),
+            name_len,
+        }
+    }
+
     /// Converts the event into an owned event.
     pub fn into_owned(self) -> BytesEnd<'static> {
         BytesEnd {
             name: Cow::Owned(self.name.into_owned()),
+            name_len: self.name_len,
         }
     }
 
     /// Gets `BytesEnd` event name
     #[inline]
-    pub fn name(&self) -> &[u8] {
-        &*self.name
+    pub fn name(&self) -> QName<'_> {
+        QName(&self.name[..self.name_len])
     }
 
-    /// local name (excluding namespace) as &[u8] (without eventual attributes)
+    /// Gets the whitespaces after the name, which are empty unless the event was created
+    /// with [`borrowed_content`].
+    ///
+    /// [`borrowed_content`]: #method.borrowed_content
+    #[inline]
+    pub fn trailing_whitespaces(&self) -> &[u8] {
+        &self.name[self.name_len..]
+    }
+
+    /// local name (excluding namespace) (without eventual attributes)
     /// returns the name() with any leading namespace removed (all content up to
     /// and including the first ':' character)
     #[inline]
-    pub fn local_name(&self) -> &[u8] {
-        if let Some(i) = self.name().iter().position(|b| *b == b':') {
-            &self.name()[i + 1..]
-        } else {
-            self.name()
-        }
+    pub fn local_name(&self) -> LocalName<'_> {
+        self.name().local_name()
+    }
+
+    /// Checks if the local name of this tag is equal to the local name of `other`,
+    /// ignoring the namespace prefixes of both names.
+    #[inline]
+    pub fn name_eq_ignore_prefix(&self, other: QName) -> bool {
+        self.local_name() == other.local_name()
     }
 }
 
@@ -543,19 +954,39 @@ impl<'a> std::fmt::Debug for BytesEnd<'a> {
         use crate::utils::write_byte_string;
 
         write!(f, "BytesEnd {{ name: ")?;
-        write_byte_string(f, &self.name)?;
+        write_byte_string(f, self.name().into_inner())?;
         write!(f, " }}")
     }
 }
 
 /// Data from various events (most notably, `Event::Text`).
 #[derive(Clone)]
+#[cfg_attr(feature = "serde-types", derive(Deserialize, Serialize))]
 pub struct BytesText<'a> {
     // Invariant: The content is always escaped.
     content: Cow<'a, [u8]>,
 }
 
 impl<'a> BytesText<'a> {
+    /// Creates a new `BytesText` from a plain text, which is escaped when
+    /// the event is written.
+    ///
+    /// Use [`from_escaped_str`] for the text which is already escaped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use quick_xml::events::BytesText;
+    /// let text = BytesText::new("Bells & whistles");
+    /// assert_eq!(&*text, b"Bells &amp; whistles");
+    /// ```
+    ///
+    /// [`from_escaped_str`]: #method.from_escaped_str
+    #[inline]
+    pub fn new(content: &'a str) -> BytesText<'a> {
+        Self::from_plain_str(content)
+    }
+
     /// Creates a new `BytesText` from an escaped byte sequence.
     #[inline]
     pub fn from_escaped<C: Into<Cow<'a, [u8]>>>(content: C) -> BytesText<'a> {
@@ -633,6 +1064,37 @@ impl<'a> BytesText<'a> {
         self.make_unescaped(Some(custom_entities))
     }
 
+    /// Gets unescaped content, using `resolve_entity` to get values of the entities,
+    /// that are not predefined.
+    ///
+    /// `resolve_entity` receives the name of an entity without `&` and `;` and returns
+    /// its value, or `None` if the entity is unknown. Values are not scanned for other
+    /// entities. See [`unescape_str_with`] for the order, in which entities are resolved.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use quick_xml::events::BytesText;
+    ///
+    /// let text = BytesText::from_escaped_str("&lt;&nbsp;&gt;");
+    /// let unescaped = text.unescaped_with(|entity| match entity {
+    ///     "nbsp" => Some("\u{A0}"),
+    ///     _ => None,
+    /// });
+    /// assert_eq!(&*unescaped.unwrap(), "<\u{A0}>".as_bytes());
+    /// ```
+    ///
+    /// [`unescape_str_with`]: ../escape/fn.unescape_str_with.html
+    pub fn unescaped_with<'s, 'e, F>(&'s self, mut resolve_entity: F) -> Result<Cow<'s, [u8]>>
+    where
+        F: FnMut(&str) -> Option<&'e str>,
+    {
+        unescape_with_resolver(self, cfg!(feature = "escape-html"), |name| {
+            resolve_entity(from_utf8(name).ok()?).map(str::as_bytes)
+        })
+        .map_err(Error::EscapeError)
+    }
+
     fn make_unescaped<'s>(
         &'s self,
         custom_entities: Option<&HashMap<Vec<u8>, Vec<u8>>>,
@@ -718,7 +1180,8 @@ impl<'a> BytesText<'a> {
     ) -> Result<String> {
         let decoded = reader.decode_without_bom(&*self);
         let unescaped =
-            do_unescape(decoded.as_bytes(), custom_entities).map_err(Error::EscapeError)?;
+            do_unescape_with_html(decoded.as_bytes(), custom_entities, reader.html_entities())
+                .map_err(Error::EscapeError)?;
         String::from_utf8(unescaped.into_owned()).map_err(|e| Error::Utf8(e.utf8_error()))
     }
 
@@ -730,7 +1193,8 @@ impl<'a> BytesText<'a> {
     ) -> Result<String> {
         let decoded = reader.decode_without_bom(&*self)?;
         let unescaped =
-            do_unescape(decoded.as_bytes(), custom_entities).map_err(Error::EscapeError)?;
+            do_unescape_with_html(decoded.as_bytes(), custom_entities, reader.html_entities())
+                .map_err(Error::EscapeError)?;
         String::from_utf8(unescaped.into_owned()).map_err(|e| Error::Utf8(e.utf8_error()))
     }
 
@@ -770,7 +1234,8 @@ impl<'a> BytesText<'a> {
     ) -> Result<String> {
         let decoded = reader.decode(&*self);
         let unescaped =
-            do_unescape(decoded.as_bytes(), custom_entities).map_err(Error::EscapeError)?;
+            do_unescape_with_html(decoded.as_bytes(), custom_entities, reader.html_entities())
+                .map_err(Error::EscapeError)?;
         String::from_utf8(unescaped.into_owned()).map_err(|e| Error::Utf8(e.utf8_error()))
     }
 
@@ -782,7 +1247,8 @@ impl<'a> BytesText<'a> {
     ) -> Result<String> {
         let decoded = reader.decode(&*self)?;
         let unescaped =
-            do_unescape(decoded.as_bytes(), custom_entities).map_err(Error::EscapeError)?;
+            do_unescape_with_html(decoded.as_bytes(), custom_entities, reader.html_entities())
+                .map_err(Error::EscapeError)?;
         String::from_utf8(unescaped.into_owned()).map_err(|e| Error::Utf8(e.utf8_error()))
     }
 
@@ -806,6 +1272,7 @@ impl<'a> std::fmt::Debug for BytesText<'a> {
 ///
 /// [`Reader::read_event`]: ../reader/struct.Reader.html#method.read_event
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde-types", derive(Deserialize, Serialize))]
 pub enum Event<'a> {
     /// Start tag (with attributes) `<tag attr="value">`.
     Start(BytesStart<'a>),
@@ -893,6 +1360,37 @@ impl<'a> Deref for Event<'a> {
     }
 }
 
+/// Renders the event as XML text, exactly as a [`Writer`] with the default configuration
+/// would write it. Unlike the `Debug` output, this is the markup of the event: a tag with
+/// its attributes, an escaped text, a comment and so on. [`Event::Eof`] renders nothing.
+///
+/// Intended for logging and debugging, use a [`Writer`] to produce documents.
+///
+/// # Examples
+///
+/// ```rust
+/// # extern crate quick_xml;
+/// # fn main() {
+/// use quick_xml::events::{BytesStart, BytesText, Event};
+///
+/// let start = BytesStart::borrowed_name(b"tag").with_attributes(vec![("attr", "a&b")]);
+/// assert_eq!(Event::Start(start).to_string(), r#"<tag attr="a&amp;b">"#);
+///
+/// let text = BytesText::from_plain_str("1 < 2");
+/// assert_eq!(format!("{}", Event::Text(text)), "1 &lt; 2");
+/// # }
+/// ```
+///
+/// [`Writer`]: ../writer/struct.Writer.html
+/// [`Event::Eof`]: enum.Event.html#variant.Eof
+impl<'a> std::fmt::Display for Event<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        Writer::new(FmtSink::new(f))
+            .write_event(self)
+            .map_err(|_| std::fmt::Error)
+    }
+}
+
 impl<'a> AsRef<Event<'a>> for Event<'a> {
     fn as_ref(&self) -> &Event<'a> {
         self
@@ -918,12 +1416,12 @@ mod test {
         loop {
             match rdr.read_event(&mut buf).expect("unable to read xml event") {
                 Event::Start(ref e) => parsed_local_names.push(
-                    from_utf8(e.local_name())
+                    from_utf8(&e.local_name())
                         .expect("unable to build str from local_name")
                         .to_string(),
                 ),
                 Event::End(ref e) => parsed_local_names.push(
-                    from_utf8(e.local_name())
+                    from_utf8(&e.local_name())
                         .expect("unable to build str from local_name")
                         .to_string(),
                 ),
@@ -941,25 +1439,74 @@ mod test {
         assert_eq!(parsed_local_names[7], "bus:baz".to_string());
     }
 
+    #[test]
+    fn try_get_attribute() {
+        let tag = BytesStart::borrowed(br#"tag ab="1" a="2" a="3" b"#, 3);
+
+        let attr = tag.try_get_attribute("a").unwrap().unwrap();
+        assert_eq!(&*attr.value, b"2");
+        let attr = tag.try_get_attribute(b"ab").unwrap().unwrap();
+        assert_eq!(&*attr.value, b"1");
+        assert!(tag.try_get_attribute("b").is_err());
+        assert!(tag.try_get_attribute("c").is_err());
+
+        let tag = BytesStart::borrowed(br#"tag a="1""#, 3);
+        assert_eq!(tag.try_get_attribute("b").unwrap(), None);
+    }
+
+    #[test]
+    fn try_get_attribute_ns() {
+        let xml = r#"<tag xmlns:n="urn:n" a="1" n:a="2" n:ab="3">"#;
+        let mut reader = Reader::from_str(xml);
+        reader.trim_text(true);
+        let mut buf = Vec::new();
+        let mut ns_buf = Vec::new();
+        let tag = match reader.read_namespaced_event(&mut buf, &mut ns_buf).unwrap() {
+            (_, Event::Start(e)) => e.into_owned(),
+            e => panic!("Expected `Start`, but found {:?}", e),
+        };
+
+        let attr = tag
+            .try_get_attribute_ns(&reader, &ns_buf, Some(b"urn:n"), "a")
+            .unwrap()
+            .unwrap();
+        assert_eq!(&*attr.value, b"2");
+        let attr = tag
+            .try_get_attribute_ns(&reader, &ns_buf, None, "a")
+            .unwrap()
+            .unwrap();
+        assert_eq!(&*attr.value, b"1");
+        let attr = tag
+            .try_get_attribute_ns(&reader, &ns_buf, Some(b"urn:n"), "ab")
+            .unwrap()
+            .unwrap();
+        assert_eq!(&*attr.value, b"3");
+        assert_eq!(
+            tag.try_get_attribute_ns(&reader, &ns_buf, Some(b"urn:x"), "a")
+                .unwrap(),
+            None
+        );
+    }
+
     #[test]
     fn bytestart_create() {
         let b = BytesStart::owned_name("test");
         assert_eq!(b.len(), 4);
-        assert_eq!(b.name(), b"test");
+        assert_eq!(b.name().as_ref(), b"test");
     }
 
     #[test]
     fn bytestart_set_name() {
         let mut b = BytesStart::owned_name("test");
         assert_eq!(b.len(), 4);
-        assert_eq!(b.name(), b"test");
+        assert_eq!(b.name().as_ref(), b"test");
         assert_eq!(b.attributes_raw(), b"");
         b.push_attribute(("x", "a"));
         assert_eq!(b.len(), 10);
         assert_eq!(b.attributes_raw(), b" x=\"a\"");
         b.set_name(b"g");
         assert_eq!(b.len(), 7);
-        assert_eq!(b.name(), b"g");
+        assert_eq!(b.name().as_ref(), b"g");
     }
 
     #[test]
@@ -970,6 +1517,171 @@ mod test {
         b.clear_attributes();
         assert!(b.attributes().next().is_none());
         assert_eq!(b.len(), 4);
-        assert_eq!(b.name(), b"test");
+        assert_eq!(b.name().as_ref(), b"test");
+    }
+
+    #[test]
+    fn bytestart_reserve() {
+        // The content is not moved, when attributes fit into the reserved space
+        let mut b = BytesStart::with_capacity("test", 40);
+        assert_eq!(b.name().as_ref(), b"test");
+        let ptr = b.as_ptr();
+        b.push_attribute(("x", "1"));
+        b.push_attribute(("y", "2"));
+        b.push_attribute(("z", "3"));
+        assert_eq!(b.as_ptr(), ptr);
+        assert_eq!(&*b, br#"test x="1" y="2" z="3""#.as_ref());
+
+        let mut b = BytesStart::borrowed(b"test a=\"0\"", 4);
+        b.reserve(18);
+        let ptr = b.as_ptr();
+        b.push_attribute(("x", "1"));
+        b.push_attribute(("y", "2"));
+        b.push_attribute(("z", "3"));
+        assert_eq!(b.as_ptr(), ptr);
+        assert_eq!(&*b, br#"test a="0" x="1" y="2" z="3""#.as_ref());
+
+        let mut b = b.to_owned_with_capacity(6);
+        let ptr = b.as_ptr();
+        b.push_attribute(("w", "4"));
+        assert_eq!(b.as_ptr(), ptr);
+        assert_eq!(b.name().as_ref(), b"test");
+        assert_eq!(b.len(), 34);
+    }
+
+    /// Parses `<tag>` from `xml` and returns the key-value pairs of its attributes
+    fn parse_attributes(xml: &[u8]) -> Vec<(String, String)> {
+        let mut reader = Reader::from_reader(xml);
+        reader.trim_text(true);
+        let mut buf = Vec::new();
+        match reader.read_event(&mut buf).unwrap() {
+            Event::Start(e) => e
+                .attributes()
+                .map(|a| {
+                    let a = a.unwrap();
+                    (
+                        String::from_utf8(a.key.to_vec()).unwrap(),
+                        String::from_utf8(a.value.into_owned()).unwrap(),
+                    )
+                })
+                .collect(),
+            e => panic!("Expected `Start`, but found {:?}", e),
+        }
+    }
+
+    #[test]
+    fn bytestart_modify_attributes() {
+        let xml = b"<tag a = 'x>y'\tversion=\"1\" b=\"2\"\n>";
+        let mut reader = Reader::from_reader(&xml[..]);
+        reader.trim_text(true);
+        let mut buf = Vec::new();
+        let mut tag = match reader.read_event(&mut buf).unwrap() {
+            Event::Start(e) => e.into_owned(),
+            e => panic!("Expected `Start`, but found {:?}", e),
+        };
+
+        assert!(tag.remove_attribute("a"));
+        assert!(!tag.remove_attribute("a"));
+        tag.replace_attribute(("version", "2"));
+        tag.replace_attribute(("c", "3"));
+        assert_eq!(tag.name().as_ref(), b"tag");
+
+        let mut writer = Writer::new(Vec::new());
+        writer.write_event(Event::Start(tag.to_borrowed())).unwrap();
+        assert_eq!(
+            parse_attributes(&writer.into_inner()),
+            vec![
+                ("version".to_string(), "2".to_string()),
+                ("b".to_string(), "2".to_string()),
+                ("c".to_string(), "3".to_string()),
+            ]
+        );
+
+        tag.clear_attributes();
+        let mut writer = Writer::new(Vec::new());
+        writer.write_event(Event::Start(tag.to_borrowed())).unwrap();
+        assert_eq!(writer.into_inner(), b"<tag>");
+    }
+
+    #[test]
+    fn escape_once_roundtrip() {
+        let mut tag = BytesStart::borrowed_name(b"tag");
+        tag.push_attribute(("plain", "a & b"));
+        tag.push_escaped_attribute("escaped", "a &amp; b");
+
+        let mut writer = Writer::new(Vec::new());
+        writer.write_event(Event::Start(tag)).unwrap();
+        writer
+            .write_event(Event::Text(BytesText::new("a & b")))
+            .unwrap();
+        writer
+            .write_event(Event::Text(BytesText::from_escaped_str(" | a &amp; b")))
+            .unwrap();
+        writer
+            .write_event(Event::End(BytesEnd::borrowed(b"tag")))
+            .unwrap();
+        let xml = writer.into_inner();
+        assert_eq!(
+            from_utf8(&xml).unwrap(),
+            r#"<tag plain="a &amp; b" escaped="a &amp; b">a &amp; b | a &amp; b</tag>"#
+        );
+
+        let mut reader = Reader::from_reader(&xml[..]);
+        reader.trim_text(true);
+        let mut buf = Vec::new();
+        match reader.read_event(&mut buf).unwrap() {
+            Event::Start(e) => {
+                for attr in e.attributes() {
+                    let value = attr.unwrap().unescape_and_decode_value(&reader).unwrap();
+                    assert_eq!(value, "a & b");
+                }
+            }
+            e => panic!("Expected `Start`, but found {:?}", e),
+        }
+        match reader.read_event(&mut buf).unwrap() {
+            Event::Text(e) => {
+                assert_eq!(e.unescape_and_decode(&reader).unwrap(), "a & b | a & b");
+            }
+            e => panic!("Expected `Text`, but found {:?}", e),
+        }
+    }
+
+    #[test]
+    fn display() {
+        let start = BytesStart::borrowed_name(b"tag").with_attributes(vec![("attr", "\"x\"")]);
+        assert_eq!(
+            Event::Start(start.clone()).to_string(),
+            r#"<tag attr="&quot;x&quot;">"#
+        );
+        assert_eq!(
+            Event::Empty(start).to_string(),
+            r#"<tag attr="&quot;x&quot;"/>"#
+        );
+        assert_eq!(Event::End(BytesEnd::borrowed(b"tag")).to_string(), "</tag>");
+        assert_eq!(
+            Event::Text(BytesText::from_plain_str("<a & b>")).to_string(),
+            "&lt;a &amp; b&gt;"
+        );
+        assert_eq!(
+            Event::Comment(BytesText::from_escaped_str(" comment ")).to_string(),
+            "<!-- comment -->"
+        );
+        assert_eq!(
+            Event::CData(BytesText::from_escaped_str("<raw>")).to_string(),
+            "<![CDATA[<raw>]]>"
+        );
+        assert_eq!(
+            Event::Decl(BytesDecl::new(b"1.0", Some(b"UTF-8"), None)).to_string(),
+            r#"<?xml version="1.0" encoding="UTF-8"?>"#
+        );
+        assert_eq!(
+            Event::PI(BytesText::from_escaped_str("target data")).to_string(),
+            "<?target data?>"
+        );
+        assert_eq!(
+            Event::DocType(BytesText::from_escaped_str(" html")).to_string(),
+            "<!DOCTYPE html>"
+        );
+        assert_eq!(Event::Eof.to_string(), "");
     }
 }
@@ -0,0 +1,286 @@
+//! Streaming reformatting of XML documents.
+//!
+//! [`pretty`] re-indents a document and [`minify`] removes insignificant whitespace and
+//! comments. Both pass events from a [`Reader`] to a [`Writer`] one by one, so documents
+//! of any size are processed in memory proportional only to the size of the largest event.
+//! Texts, CDATA sections, attributes, the declaration, processing instructions and the
+//! doctype are written exactly as they are in the input.
+//!
+//! Text events, which consist only of whitespace, are considered insignificant and are
+//! replaced by the indentation or removed, except:
+//! - inside elements with the `xml:space="preserve"` attribute, which content is written
+//!   as is;
+//! - after a text with other characters or a CDATA section in the same element: such
+//!   element has mixed content, so the rest of its content is written as is.
+//!
+//! Because the input is not buffered, whitespace in a mixed content element before its
+//! first significant text is still treated as insignificant. Use `xml:space="preserve"`
+//! when such whitespace is important.
+//!
+//! [`pretty`]: fn.pretty.html
+//! [`minify`]: fn.minify.html
+//! [`Reader`]: ../struct.Reader.html
+//! [`Writer`]: ../struct.Writer.html
+
+use std::io::BufRead;
+
+use errors::Result;
+use events::{BytesStart, BytesText, Event};
+use reader::{is_whitespace, Reader};
+use writer::{Sink, Writer};
+
+/// Re-indents the XML document read from `input` and writes it to `output`, putting each
+/// tag, comment, processing instruction, declaration and doctype on its own line, indented
+/// with `indent` spaces per nesting level. See the [module documentation](index.html)
+/// for which whitespace is kept.
+///
+/// The output is not changed by formatting it again.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate quick_xml;
+/// # fn main() {
+/// use quick_xml::format::pretty;
+///
+/// let xml = "<root><item id='1'>text</item><item><empty/></item></root>";
+/// let mut output = Vec::new();
+/// pretty(xml.as_bytes(), &mut output, 2).unwrap();
+/// assert_eq!(
+///     String::from_utf8(output).unwrap(),
+///     "<root>\n  <item id='1'>text</item>\n  <item>\n    <empty/>\n  </item>\n</root>"
+/// );
+/// # }
+/// ```
+pub fn pretty<R: BufRead, W: Sink>(input: R, output: W, indent: usize) -> Result<()> {
+    Formatter::new(output, Some(indent)).run(input)
+}
+
+/// Removes insignificant whitespace and comments from the XML document read from `input`
+/// and writes it to `output`. See the [module documentation](index.html) for which
+/// whitespace is kept.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate quick_xml;
+/// # fn main() {
+/// use quick_xml::format::minify;
+///
+/// let xml = "<root>\n  <!-- items -->\n  <item> text </item>\n  <item/>\n</root>\n";
+/// let mut output = Vec::new();
+/// minify(xml.as_bytes(), &mut output).unwrap();
+/// assert_eq!(output, b"<root><item> text </item><item/></root>");
+/// # }
+/// ```
+pub fn minify<R: BufRead, W: Sink>(input: R, output: W) -> Result<()> {
+    Formatter::new(output, None).run(input)
+}
+
+struct Formatter<W: Sink> {
+    writer: Writer<W>,
+    /// Number of spaces per nesting level, `None` to minify
+    indent: Option<usize>,
+    /// Number of currently opened elements
+    depth: usize,
+    /// Depth of the element, which content is written as is
+    verbatim: Option<usize>,
+    /// Whether the last written event is a start tag
+    after_start: bool,
+    /// Whether anything is written
+    started: bool,
+}
+
+impl<W: Sink> Formatter<W> {
+    fn new(output: W, indent: Option<usize>) -> Formatter<W> {
+        Formatter {
+            writer: Writer::new(output),
+            indent,
+            depth: 0,
+            verbatim: None,
+            after_start: false,
+            started: false,
+        }
+    }
+
+    fn run<R: BufRead>(mut self, input: R) -> Result<()> {
+        let mut reader = Reader::from_reader(input);
+        let mut buf = Vec::new();
+        loop {
+            match reader.read_event(&mut buf)? {
+                Event::Eof => break,
+                event => self.format(&event)?,
+            }
+            buf.clear();
+        }
+        self.writer.flush()
+    }
+
+    fn format(&mut self, event: &Event) -> Result<()> {
+        match event {
+            Event::Text(e) if self.verbatim.is_none() => {
+                if e.iter().all(|b| is_whitespace(*b)) {
+                    return Ok(());
+                }
+                self.verbatim = Some(self.depth);
+            }
+            Event::CData(_) if self.verbatim.is_none() => self.verbatim = Some(self.depth),
+            Event::Comment(_) if self.indent.is_none() => return Ok(()),
+            Event::End(_) => {
+                if !self.after_start {
+                    self.write_indent(self.depth.saturating_sub(1))?;
+                }
+                if self.verbatim == Some(self.depth) {
+                    self.verbatim = None;
+                }
+                self.depth = self.depth.saturating_sub(1);
+            }
+            Event::Start(e) => {
+                self.write_indent(self.depth)?;
+                self.depth += 1;
+                if self.verbatim.is_none() && preserves_space(e)? {
+                    self.verbatim = Some(self.depth);
+                }
+            }
+            Event::Text(_) | Event::CData(_) => {}
+            _ => self.write_indent(self.depth)?,
+        }
+        self.after_start = matches!(event, Event::Start(_));
+        self.started = true;
+        match event {
+            Event::CData(e) => {
+                let content = BytesText::from_escaped(e.unescaped()?);
+                self.writer.write_event(Event::CData(content))
+            }
+            _ => self.writer.write_event(event),
+        }
+    }
+
+    /// Starts a new line, indented to the `level`, unless the content is written as is
+    fn write_indent(&mut self, level: usize) -> Result<()> {
+        if let (Some(indent), None, true) = (self.indent, self.verbatim, self.started) {
+            self.writer.write_raw(b"\n")?;
+            for _ in 0..indent * level {
+                self.writer.write_raw(b" ")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Whether the element has the `xml:space="preserve"` attribute
+fn preserves_space(e: &BytesStart) -> Result<bool> {
+    for attr in e.attributes() {
+        let attr = attr?;
+        if attr.key.as_ref() == b"xml:space" {
+            return Ok(attr.value.as_ref() == b"preserve");
+        }
+    }
+    Ok(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::from_utf8;
+
+    const MESSY: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE doc SYSTEM "doc.dtd">
+  <?style type="text/css"?>
+<doc   lang="en"><!-- header -->
+        <title>A  &amp;  B</title>
+   <empty></empty><self-closed />
+<para>Mixed <b>bold</b>   and <i>italic <u>under</u></i>
+  text.</para>
+  <code xml:space="preserve">
+    fn main() {}
+  <line>  x  </line></code>
+    <data><![CDATA[  <raw>  ]]></data>
+    <list>   <item>1</item>
+
+    <item>2</item>   </list>
+</doc>
+"#;
+
+    const PRETTY: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE doc SYSTEM "doc.dtd">
+<?style type="text/css"?>
+<doc   lang="en">
+  <!-- header -->
+  <title>A  &amp;  B</title>
+  <empty></empty>
+  <self-closed />
+  <para>Mixed <b>bold</b>   and <i>italic <u>under</u></i>
+  text.</para>
+  <code xml:space="preserve">
+    fn main() {}
+  <line>  x  </line></code>
+  <data><![CDATA[  <raw>  ]]></data>
+  <list>
+    <item>1</item>
+    <item>2</item>
+  </list>
+</doc>"#;
+
+    const MINIFIED: &str = concat!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>"#,
+        r#"<!DOCTYPE doc SYSTEM "doc.dtd">"#,
+        r#"<?style type="text/css"?>"#,
+        r#"<doc   lang="en">"#,
+        r#"<title>A  &amp;  B</title>"#,
+        r#"<empty></empty><self-closed />"#,
+        "<para>Mixed <b>bold</b>   and <i>italic <u>under</u></i>\n  text.</para>",
+        "<code xml:space=\"preserve\">\n    fn main() {}\n  <line>  x  </line></code>",
+        r#"<data><![CDATA[  <raw>  ]]></data>"#,
+        r#"<list><item>1</item><item>2</item></list>"#,
+        r#"</doc>"#,
+    );
+
+    fn format_pretty(xml: &str) -> String {
+        let mut output = Vec::new();
+        pretty(xml.as_bytes(), &mut output, 2).unwrap();
+        from_utf8(&output).unwrap().to_string()
+    }
+
+    fn format_minify(xml: &str) -> String {
+        let mut output = Vec::new();
+        minify(xml.as_bytes(), &mut output).unwrap();
+        from_utf8(&output).unwrap().to_string()
+    }
+
+    #[test]
+    fn pretty_golden() {
+        assert_eq!(format_pretty(MESSY), PRETTY);
+    }
+
+    #[test]
+    fn minify_golden() {
+        assert_eq!(format_minify(MESSY), MINIFIED);
+    }
+
+    #[test]
+    fn idempotence() {
+        assert_eq!(format_pretty(PRETTY), PRETTY);
+        assert_eq!(
+            format_pretty(MINIFIED),
+            PRETTY.replace("\n  <!-- header -->", "")
+        );
+        assert_eq!(format_minify(MINIFIED), MINIFIED);
+        assert_eq!(format_minify(PRETTY), MINIFIED);
+    }
+
+    #[test]
+    fn mixed_content_in_nested_element() {
+        assert_eq!(
+            format_pretty("<a><b><c/>text<d/></b><e/></a>"),
+            "<a>\n  <b>\n    <c/>text<d/></b>\n  <e/>\n</a>"
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let mut output = Vec::new();
+        assert!(pretty("<a></b>".as_bytes(), &mut output, 2).is_err());
+        assert!(minify("<a><b></a>".as_bytes(), &mut output).is_err());
+    }
+}
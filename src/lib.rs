@@ -50,7 +50,7 @@
 //!         Ok(Event::Start(ref e)) => {
 //!         // for namespaced:
 //!         // Ok((ref namespace_value, Event::Start(ref e)))
-//!             match e.name() {
+//!             match e.name().as_ref() {
 //!                 b"tag1" => println!("attributes values: {:?}",
 //!                                     e.attributes().map(|a| a.unwrap().value)
 //!                                     .collect::<Vec<_>>()),
@@ -86,7 +86,7 @@
 //! let mut buf = Vec::new();
 //! loop {
 //!     match reader.read_event(&mut buf) {
-//!         Ok(Event::Start(ref e)) if e.name() == b"this_tag" => {
+//!         Ok(Event::Start(ref e)) if e.name().as_ref() == b"this_tag" => {
 //!
 //!             // crates a new element ... alternatively we could reuse `e` by calling
 //!             // `e.into_owned()`
@@ -101,7 +101,7 @@
 //!             // writes the event to the writer
 //!             assert!(writer.write_event(Event::Start(elem)).is_ok());
 //!         },
-//!         Ok(Event::End(ref e)) if e.name() == b"this_tag" => {
+//!         Ok(Event::End(ref e)) if e.name().as_ref() == b"this_tag" => {
 //!             assert!(writer.write_event(Event::End(BytesEnd::borrowed(b"my_elem"))).is_ok());
 //!         },
 //!         Ok(Event::Eof) => break,
@@ -120,9 +120,15 @@
 //!
 //! # Features
 //!
-//! quick-xml supports 2 additional features, non activated by default:
+//! quick-xml supports 4 additional features, non activated by default:
 //! - `encoding`: support non utf8 XMLs
 //! - `serialize`: support serde `Serialize`/`Deserialize`
+//! - `serde-types`: implement serde `Serialize`/`Deserialize` for the [`Event`] types
+//! - `escape-html`: resolve HTML5 named character references, like `&nbsp;`, when unescaping.
+//!   Can be turned off for a reader with [`Reader::resolve_html_entities`]
+//!
+//! [`Event`]: events/enum.Event.html
+//! [`Reader::resolve_html_entities`]: struct.Reader.html#method.resolve_html_entities
 //!
 //! [StAX]: https://en.wikipedia.org/wiki/StAX
 //! [Serde]: https://serde.rs/
@@ -133,30 +139,56 @@
 #[cfg(feature = "encoding_rs")]
 extern crate encoding_rs;
 extern crate memchr;
-#[cfg(feature = "serialize")]
+#[cfg(any(feature = "serialize", feature = "serde-types"))]
 extern crate serde;
+#[cfg(feature = "json")]
+extern crate serde_json;
 #[cfg(all(test, feature = "serialize"))]
 extern crate serde_value;
 
+pub mod compare;
+#[cfg(feature = "json")]
+pub mod convert;
 #[cfg(feature = "serialize")]
 pub mod de;
+pub mod digest;
+#[cfg(feature = "dom")]
+pub mod dom;
 mod errors;
 mod escapei;
 pub mod escape {
     //! Manage xml character escapes
-    pub(crate) use escapei::do_unescape;
-    pub use escapei::{escape, unescape, unescape_with, EscapeError};
+    pub(crate) use escapei::{
+        do_unescape, do_unescape_with_html, escape_bare_ampersands, escape_control_chars,
+        escape_unescaped, is_control_char, normalize_attribute_value, unescape_with_resolver,
+    };
+    pub use escapei::{
+        escape, escape_attribute, escape_into, escape_non_ascii, escape_str, escape_str_into,
+        escape_with_level, needs_escaping, partial_escape, unescape, unescape_into, unescape_lossy,
+        unescape_str, unescape_str_with, unescape_with, CharRefFormat, EscapeError, EscapeLevel,
+    };
+    #[cfg(feature = "serialize")]
+    pub(crate) use escapei::{
+        escape_attribute_chars, escape_with_char_refs_into, escape_with_level_into,
+        needs_escaping_with_level,
+    };
 }
 pub mod events;
+pub mod format;
+pub mod name;
 mod reader;
+pub mod sax;
 #[cfg(feature = "serialize")]
 pub mod se;
+mod select;
 mod utils;
+pub mod validate;
 mod writer;
 
 // reexports
 #[cfg(feature = "serialize")]
 pub use errors::serialize::DeError;
 pub use errors::{Error, Result};
-pub use reader::Reader;
-pub use writer::Writer;
+pub use reader::{Decoder, Reader, ReaderConfig};
+pub use select::{Select, Selected};
+pub use writer::{ControlCharPolicy, EscapePolicy, FmtSink, Sink, Writer};
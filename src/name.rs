@@ -0,0 +1,277 @@
+//! Module for handling names according to the W3C [Namespaces in XML 1.1 (Second Edition)][spec]
+//! specification
+//!
+//! [spec]: https://www.w3.org/TR/xml-names11
+
+use memchr::memchr;
+use std::fmt;
+use std::ops::Deref;
+use utils::write_byte_string;
+
+/// A [qualified name] of an element or an attribute, including an optional
+/// namespace [prefix] and a [local name].
+///
+/// The name is split at the first `:`, so in the malformed name `a:b:c` the
+/// prefix is `a` and the local name is `b:c`.
+///
+/// [qualified name]: https://www.w3.org/TR/xml-names11/#dt-qualname
+/// [prefix]: struct.Prefix.html
+/// [local name]: struct.LocalName.html
+#[derive(Clone, Copy, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct QName<'a>(pub &'a [u8]);
+
+impl<'a> QName<'a> {
+    /// Converts this name to an internal slice representation.
+    #[inline]
+    pub fn into_inner(self) -> &'a [u8] {
+        self.0
+    }
+
+    /// Returns the local part of the name: the whole name if it has no prefix,
+    /// or the part after the first `:` otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use quick_xml::name::QName;
+    /// assert_eq!(QName(b"p:e").local_name().as_ref(), b"e");
+    /// assert_eq!(QName(b"e").local_name().as_ref(), b"e");
+    /// assert_eq!(QName(b"p:").local_name().as_ref(), b"");
+    /// ```
+    pub fn local_name(&self) -> LocalName<'a> {
+        LocalName(self.index().map_or(self.0, |i| &self.0[i + 1..]))
+    }
+
+    /// Returns the namespace prefix of the name: the part before the first `:`,
+    /// or `None` if the name has no `:`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use quick_xml::name::QName;
+    /// assert_eq!(QName(b"p:e").prefix().unwrap().as_ref(), b"p");
+    /// assert_eq!(QName(b":e").prefix().unwrap().as_ref(), b"");
+    /// assert!(QName(b"e").prefix().is_none());
+    /// ```
+    pub fn prefix(&self) -> Option<Prefix<'a>> {
+        self.index().map(|i| Prefix(&self.0[..i]))
+    }
+
+    /// Returns the local name and the prefix of the name at once.
+    pub fn decompose(&self) -> (LocalName<'a>, Option<Prefix<'a>>) {
+        match self.index() {
+            None => (LocalName(self.0), None),
+            Some(i) => (LocalName(&self.0[i + 1..]), Some(Prefix(&self.0[..i]))),
+        }
+    }
+
+    /// If the name is the name of an attribute that declares a namespace,
+    /// returns the kind of the declaration:
+    ///
+    /// - `xmlns` declares the default namespace
+    /// - `xmlns:prefix` binds a namespace to the `prefix`
+    ///
+    /// Returns `None` for all other names.
+    pub fn as_namespace_binding(&self) -> Option<PrefixDeclaration<'a>> {
+        if self.0.starts_with(b"xmlns") {
+            match self.0.get(5) {
+                None => Some(PrefixDeclaration::Default),
+                Some(&b':') => Some(PrefixDeclaration::Named(&self.0[6..])),
+                _ => None,
+            }
+        } else {
+            None
+        }
+    }
+
+    /// The position of the `:` separating the prefix and the local name
+    #[inline]
+    fn index(&self) -> Option<usize> {
+        memchr(b':', self.0)
+    }
+}
+
+impl<'a> fmt::Debug for QName<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "QName(")?;
+        write_byte_string(f, self.0)?;
+        write!(f, ")")
+    }
+}
+
+impl<'a> AsRef<[u8]> for QName<'a> {
+    #[inline]
+    fn as_ref(&self) -> &[u8] {
+        self.0
+    }
+}
+
+impl<'a> Deref for QName<'a> {
+    type Target = [u8];
+
+    #[inline]
+    fn deref(&self) -> &[u8] {
+        self.0
+    }
+}
+
+/// A [local name] of an element or an attribute: the part of a [qualified name]
+/// without a namespace prefix.
+///
+/// [local name]: https://www.w3.org/TR/xml-names11/#dt-localname
+/// [qualified name]: struct.QName.html
+#[derive(Clone, Copy, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct LocalName<'a>(pub(crate) &'a [u8]);
+
+impl<'a> LocalName<'a> {
+    /// Converts this name to an internal slice representation.
+    #[inline]
+    pub fn into_inner(self) -> &'a [u8] {
+        self.0
+    }
+}
+
+impl<'a> fmt::Debug for LocalName<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "LocalName(")?;
+        write_byte_string(f, self.0)?;
+        write!(f, ")")
+    }
+}
+
+impl<'a> AsRef<[u8]> for LocalName<'a> {
+    #[inline]
+    fn as_ref(&self) -> &[u8] {
+        self.0
+    }
+}
+
+impl<'a> Deref for LocalName<'a> {
+    type Target = [u8];
+
+    #[inline]
+    fn deref(&self) -> &[u8] {
+        self.0
+    }
+}
+
+impl<'a> From<QName<'a>> for LocalName<'a> {
+    /// Returns the local part of the qualified name
+    #[inline]
+    fn from(name: QName<'a>) -> Self {
+        name.local_name()
+    }
+}
+
+/// A [namespace prefix] of an element or an attribute: the part of a
+/// [qualified name] before the `:`.
+///
+/// [namespace prefix]: https://www.w3.org/TR/xml-names11/#dt-prefix
+/// [qualified name]: struct.QName.html
+#[derive(Clone, Copy, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Prefix<'a>(pub(crate) &'a [u8]);
+
+impl<'a> Prefix<'a> {
+    /// Converts this prefix to an internal slice representation.
+    #[inline]
+    pub fn into_inner(self) -> &'a [u8] {
+        self.0
+    }
+}
+
+impl<'a> fmt::Debug for Prefix<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Prefix(")?;
+        write_byte_string(f, self.0)?;
+        write!(f, ")")
+    }
+}
+
+impl<'a> AsRef<[u8]> for Prefix<'a> {
+    #[inline]
+    fn as_ref(&self) -> &[u8] {
+        self.0
+    }
+}
+
+impl<'a> Deref for Prefix<'a> {
+    type Target = [u8];
+
+    #[inline]
+    fn deref(&self) -> &[u8] {
+        self.0
+    }
+}
+
+/// A namespace declaration, made by an `xmlns` or an `xmlns:prefix` attribute.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PrefixDeclaration<'a> {
+    /// `xmlns` attribute, which declares the default namespace
+    Default,
+    /// `xmlns:prefix` attribute, which binds a namespace to the contained prefix
+    Named(&'a [u8]),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unprefixed() {
+        let name = QName(b"e");
+        assert_eq!(name.prefix(), None);
+        assert_eq!(name.local_name().as_ref(), b"e");
+        assert_eq!(name.as_namespace_binding(), None);
+    }
+
+    #[test]
+    fn prefixed() {
+        let name = QName(b"p:e");
+        assert_eq!(name.prefix().unwrap().as_ref(), b"p");
+        assert_eq!(name.local_name().as_ref(), b"e");
+        assert_eq!(name.decompose(), (name.local_name(), name.prefix()));
+        assert_eq!(name.as_namespace_binding(), None);
+    }
+
+    #[test]
+    fn multiple_colons() {
+        let name = QName(b"p:e:x");
+        assert_eq!(name.prefix().unwrap().as_ref(), b"p");
+        assert_eq!(name.local_name().as_ref(), b"e:x");
+    }
+
+    #[test]
+    fn empty_prefix() {
+        let name = QName(b":x");
+        assert_eq!(name.prefix().unwrap().as_ref(), b"");
+        assert_eq!(name.local_name().as_ref(), b"x");
+    }
+
+    #[test]
+    fn empty_local_name() {
+        let name = QName(b"x:");
+        assert_eq!(name.prefix().unwrap().as_ref(), b"x");
+        assert_eq!(name.local_name().as_ref(), b"");
+    }
+
+    #[test]
+    fn xmlns() {
+        let name = QName(b"xmlns");
+        assert_eq!(
+            name.as_namespace_binding(),
+            Some(PrefixDeclaration::Default)
+        );
+        assert_eq!(name.prefix(), None);
+
+        let name = QName(b"xmlns:p");
+        assert_eq!(
+            name.as_namespace_binding(),
+            Some(PrefixDeclaration::Named(b"p"))
+        );
+        assert_eq!(name.prefix().unwrap().as_ref(), b"xmlns");
+        assert_eq!(name.local_name().as_ref(), b"p");
+
+        assert_eq!(QName(b"xmlnsx").as_namespace_binding(), None);
+        assert_eq!(QName(b"p:xmlns").as_namespace_binding(), None);
+    }
+}
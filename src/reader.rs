@@ -1,9 +1,9 @@
 //! A module to handle `Reader`
 
-#[cfg(feature = "encoding")]
 use std::borrow::Cow;
 use std::fs::File;
 use std::io::{self, BufRead, BufReader};
+use std::ops::Range;
 use std::path::Path;
 use std::str::from_utf8;
 
@@ -11,9 +11,12 @@ use std::str::from_utf8;
 use encoding_rs::{Encoding, UTF_16BE, UTF_16LE};
 
 use errors::{Error, Result};
+use escape::escape_bare_ampersands;
 use events::{attributes::Attribute, BytesDecl, BytesEnd, BytesStart, BytesText, Event};
 
 use memchr;
+use name::{LocalName, Prefix, PrefixDeclaration, QName};
+use select::Select;
 
 #[derive(Clone)]
 enum TagState {
@@ -24,6 +27,335 @@ enum TagState {
     Exit,
 }
 
+/// Markup, in which `Reader::copy_content_raw` is, between the chunks of the input
+#[derive(Clone, Copy)]
+enum RawState {
+    /// A text
+    Text,
+    /// Right after `<` of an inner markup
+    Open,
+    /// A start tag. `quote` is the quote of the attribute value, in which the byte is,
+    /// `slash` is set after `/`, and `in_name` while the name of the tag is read
+    StartTag {
+        quote: Option<u8>,
+        slash: bool,
+        in_name: bool,
+    },
+    /// An end tag of an inner element
+    EndTag,
+    /// After `<!` and the first `len` bytes of `prefix`, which is `--` or `[CDATA[`
+    Bang(&'static [u8], usize),
+    /// A comment after the number of `-`, which may close it
+    Comment(usize),
+    /// A CDATA section after the number of `]`, which may close it
+    CData(usize),
+    /// A processing instruction, flagged after `?`, which may close it
+    PI(bool),
+    /// A declaration, like `<!DOCTYPE>`, inside of which `>` is not expected
+    Decl,
+}
+
+/// Part of the document, where the reader is, tracked by `strict_prolog`
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum DocumentPart {
+    /// Nothing except a byte order mark was read yet
+    Start,
+    /// Before the root element
+    Prolog,
+    /// Inside the root element, with the number of opened elements
+    Root(usize),
+    /// After the root element
+    Epilog,
+}
+
+/// Options of a [`Reader`], collected in one value, which can be set up once and shared,
+/// for example to read all documents from untrusted sources with the same vetted options.
+///
+/// The methods have the same meaning and defaults as the setters of [`Reader`] with the
+/// same names, which change the configuration of an existing reader.
+///
+/// # Examples
+///
+/// ```
+/// use quick_xml::events::Event;
+/// use quick_xml::{Error, ReaderConfig};
+///
+/// let config = ReaderConfig::default()
+///     .trim_text(true)
+///     .max_depth(2)
+///     .forbid_doctype(true);
+///
+/// let mut reader = config.build_from_str("<root> <a/> </root>");
+/// let mut buf = Vec::new();
+/// assert!(matches!(reader.read_event(&mut buf), Ok(Event::Start(_))));
+/// assert!(matches!(reader.read_event(&mut buf), Ok(Event::Empty(_))));
+///
+/// let mut reader = config.build_from_str("<root><a><b/></a></root>");
+/// assert!(matches!(reader.read_event(&mut buf), Ok(Event::Start(_))));
+/// assert!(matches!(reader.read_event(&mut buf), Ok(Event::Start(_))));
+/// assert!(matches!(reader.read_event(&mut buf), Err(Error::TooDeep(2))));
+/// ```
+///
+/// [`Reader`]: struct.Reader.html
+#[derive(Clone, Debug)]
+pub struct ReaderConfig {
+    /// expand empty element into an opening and closing element
+    expand_empty_elements: bool,
+    /// skips Text events, which contain only whitespaces
+    drop_whitespace_only_text: bool,
+    /// trims leading whitespace in Text events
+    trim_text_start: bool,
+    /// trims trailing whitespace in Text events.
+    trim_text_end: bool,
+    /// trims trailing whitespaces from markup names in closing tags `</a >`
+    trim_markup_names_in_closing_tags: bool,
+    /// keep the markup as written, so the events are written back unchanged
+    preserve_formatting: bool,
+    /// check if End nodes match last Start node
+    check_end_names: bool,
+    /// ignore namespace prefixes when checking End nodes
+    lenient_end_name_prefixes: bool,
+    /// ignore ASCII case when checking End nodes
+    lenient_end_name_case: bool,
+    /// accept attribute values without quotes
+    lenient_attribute_values: bool,
+    /// escape `&` which do not start a reference
+    lenient_ampersands: bool,
+    /// names of elements which never have content, like `<br>` in HTML
+    void_elements: Vec<Vec<u8>>,
+    /// check if comments contains `--` (false per default)
+    check_comments: bool,
+    /// check the placement of the declarations, texts and elements before and after the
+    /// root element
+    strict_prolog: bool,
+    /// the maximum number of nested elements
+    max_depth: usize,
+    /// reject DOCTYPE declarations
+    forbid_doctype: bool,
+    #[cfg(feature = "escape-html")]
+    /// resolve HTML named entities when unescaping
+    resolve_html_entities: bool,
+}
+
+impl Default for ReaderConfig {
+    fn default() -> Self {
+        ReaderConfig {
+            expand_empty_elements: false,
+            drop_whitespace_only_text: false,
+            trim_text_start: false,
+            trim_text_end: false,
+            trim_markup_names_in_closing_tags: true,
+            preserve_formatting: false,
+            check_end_names: true,
+            lenient_end_name_prefixes: false,
+            lenient_end_name_case: false,
+            lenient_attribute_values: false,
+            lenient_ampersands: false,
+            void_elements: Vec::new(),
+            check_comments: false,
+            strict_prolog: false,
+            max_depth: usize::MAX,
+            forbid_doctype: false,
+            #[cfg(feature = "escape-html")]
+            resolve_html_entities: true,
+        }
+    }
+}
+
+impl ReaderConfig {
+    /// Creates a configuration for documents from untrusted sources.
+    ///
+    /// Unlike the default configuration, elements can be nested at most 64 levels deep,
+    /// DOCTYPE declarations, which could declare entities, are rejected, and comments
+    /// are checked. Closing tags are checked to match the opening tags, and nothing is
+    /// relaxed, as by default. Other options can be changed on the returned value.
+    pub fn secure() -> Self {
+        ReaderConfig::default()
+            .max_depth(64)
+            .forbid_doctype(true)
+            .check_comments(true)
+    }
+
+    /// Creates a reader from a reader implementing `BufRead` with this configuration.
+    pub fn build_from_reader<B: BufRead>(&self, reader: B) -> Reader<B> {
+        let mut reader = Reader::from_reader(reader);
+        reader.config = self.clone();
+        reader
+    }
+
+    /// Creates a reader from a string slice with this configuration.
+    pub fn build_from_str<'a>(&self, s: &'a str) -> Reader<&'a [u8]> {
+        self.build_from_reader(s.as_bytes())
+    }
+
+    /// Creates a reader from a byte slice with this configuration.
+    pub fn build_from_bytes<'a>(&self, s: &'a [u8]) -> Reader<&'a [u8]> {
+        self.build_from_reader(s)
+    }
+
+    /// Changes whether empty elements should be split into an `Open` and a `Close` event.
+    /// See [`Reader::expand_empty_elements`].
+    ///
+    /// [`Reader::expand_empty_elements`]: struct.Reader.html#method.expand_empty_elements
+    pub fn expand_empty_elements(mut self, val: bool) -> Self {
+        self.expand_empty_elements = val;
+        self
+    }
+
+    /// Changes whether whitespace before and after character data should be removed, and
+    /// texts, which contain only whitespaces, are skipped. See [`Reader::trim_text`].
+    ///
+    /// [`Reader::trim_text`]: struct.Reader.html#method.trim_text
+    pub fn trim_text(mut self, val: bool) -> Self {
+        self.drop_whitespace_only_text = val;
+        self.trim_text_start = val;
+        self.trim_text_end = val;
+        self
+    }
+
+    /// Changes whether texts, which contain only whitespaces, are skipped.
+    /// See [`Reader::drop_whitespace_only_text`].
+    ///
+    /// [`Reader::drop_whitespace_only_text`]: struct.Reader.html#method.drop_whitespace_only_text
+    pub fn drop_whitespace_only_text(mut self, val: bool) -> Self {
+        self.drop_whitespace_only_text = val;
+        self
+    }
+
+    /// Changes whether whitespace before and after character data should be removed.
+    /// See [`Reader::trim_text_edges`].
+    ///
+    /// [`Reader::trim_text_edges`]: struct.Reader.html#method.trim_text_edges
+    pub fn trim_text_edges(mut self, val: bool) -> Self {
+        self.trim_text_start = val;
+        self.trim_text_end = val;
+        self
+    }
+
+    /// Changes whether whitespace after character data should be removed.
+    /// See [`Reader::trim_text_end`].
+    ///
+    /// [`Reader::trim_text_end`]: struct.Reader.html#method.trim_text_end
+    pub fn trim_text_end(mut self, val: bool) -> Self {
+        self.trim_text_end = val;
+        self
+    }
+
+    /// Changes whether trailing whitespaces after the markup name are trimmed in closing tags.
+    /// See [`Reader::trim_markup_names_in_closing_tags`].
+    ///
+    /// [`Reader::trim_markup_names_in_closing_tags`]: struct.Reader.html#method.trim_markup_names_in_closing_tags
+    pub fn trim_markup_names_in_closing_tags(mut self, val: bool) -> Self {
+        self.trim_markup_names_in_closing_tags = val;
+        self
+    }
+
+    /// Changes whether the events keep the markup as written.
+    /// See [`Reader::preserve_formatting`].
+    ///
+    /// [`Reader::preserve_formatting`]: struct.Reader.html#method.preserve_formatting
+    pub fn preserve_formatting(mut self, val: bool) -> Self {
+        self.preserve_formatting = val;
+        self
+    }
+
+    /// Changes whether mismatched closing tag names should be detected.
+    /// See [`Reader::check_end_names`].
+    ///
+    /// [`Reader::check_end_names`]: struct.Reader.html#method.check_end_names
+    pub fn check_end_names(mut self, val: bool) -> Self {
+        self.check_end_names = val;
+        self
+    }
+
+    /// Changes whether only local names are compared when checking closing tags.
+    /// See [`Reader::lenient_end_name_prefixes`].
+    ///
+    /// [`Reader::lenient_end_name_prefixes`]: struct.Reader.html#method.lenient_end_name_prefixes
+    pub fn lenient_end_name_prefixes(mut self, val: bool) -> Self {
+        self.lenient_end_name_prefixes = val;
+        self
+    }
+
+    /// Changes whether names are compared ignoring ASCII case when checking closing tags.
+    /// See [`Reader::lenient_end_name_case`].
+    ///
+    /// [`Reader::lenient_end_name_case`]: struct.Reader.html#method.lenient_end_name_case
+    pub fn lenient_end_name_case(mut self, val: bool) -> Self {
+        self.lenient_end_name_case = val;
+        self
+    }
+
+    /// Changes whether attribute values without quotes are accepted.
+    /// See [`Reader::lenient_attribute_values`].
+    ///
+    /// [`Reader::lenient_attribute_values`]: struct.Reader.html#method.lenient_attribute_values
+    pub fn lenient_attribute_values(mut self, val: bool) -> Self {
+        self.lenient_attribute_values = val;
+        self
+    }
+
+    /// Changes whether `&` characters, which do not start a reference, are accepted.
+    /// See [`Reader::lenient_ampersands`].
+    ///
+    /// [`Reader::lenient_ampersands`]: struct.Reader.html#method.lenient_ampersands
+    pub fn lenient_ampersands(mut self, val: bool) -> Self {
+        self.lenient_ampersands = val;
+        self
+    }
+
+    /// Sets the names of elements, which never have content. See [`Reader::void_elements`].
+    ///
+    /// [`Reader::void_elements`]: struct.Reader.html#method.void_elements
+    pub fn void_elements(mut self, names: &[&str]) -> Self {
+        self.void_elements = names.iter().map(|n| n.as_bytes().to_vec()).collect();
+        self
+    }
+
+    /// Changes whether HTML5 named character references are resolved.
+    /// See [`Reader::resolve_html_entities`].
+    ///
+    /// [`Reader::resolve_html_entities`]: struct.Reader.html#method.resolve_html_entities
+    #[cfg(feature = "escape-html")]
+    pub fn resolve_html_entities(mut self, val: bool) -> Self {
+        self.resolve_html_entities = val;
+        self
+    }
+
+    /// Changes whether comments should be validated. See [`Reader::check_comments`].
+    ///
+    /// [`Reader::check_comments`]: struct.Reader.html#method.check_comments
+    pub fn check_comments(mut self, val: bool) -> Self {
+        self.check_comments = val;
+        self
+    }
+
+    /// Changes whether the prolog and the epilog of the document are checked.
+    /// See [`Reader::strict_prolog`].
+    ///
+    /// [`Reader::strict_prolog`]: struct.Reader.html#method.strict_prolog
+    pub fn strict_prolog(mut self, val: bool) -> Self {
+        self.strict_prolog = val;
+        self
+    }
+
+    /// Sets the maximum number of nested elements. See [`Reader::max_depth`].
+    ///
+    /// [`Reader::max_depth`]: struct.Reader.html#method.max_depth
+    pub fn max_depth(mut self, val: usize) -> Self {
+        self.max_depth = val;
+        self
+    }
+
+    /// Changes whether DOCTYPE declarations are rejected. See [`Reader::forbid_doctype`].
+    ///
+    /// [`Reader::forbid_doctype`]: struct.Reader.html#method.forbid_doctype
+    pub fn forbid_doctype(mut self, val: bool) -> Self {
+        self.forbid_doctype = val;
+        self
+    }
+}
+
 /// A low level encoding-agnostic XML event reader.
 ///
 /// Consumes a `BufRead` and streams XML `Event`s.
@@ -46,7 +378,7 @@ enum TagState {
 /// loop {
 ///     match reader.read_event(&mut buf) {
 ///         Ok(Event::Start(ref e)) => {
-///             match e.name() {
+///             match e.name().as_ref() {
 ///                 b"tag1" => println!("attributes values: {:?}",
 ///                                     e.attributes().map(|a| a.unwrap().value)
 ///                                     .collect::<Vec<_>>()),
@@ -64,24 +396,26 @@ enum TagState {
 /// ```
 #[derive(Clone)]
 pub struct Reader<B: BufRead> {
-    /// reader
-    reader: B,
+    /// reader, which transcodes UTF-16 input to UTF-8 with the `encoding` feature
+    reader: Source<B>,
     /// current buffer position, useful for debuging errors
     buf_position: usize,
     /// current state Open/Close
     tag_state: TagState,
-    /// expand empty element into an opening and closing element
-    expand_empty_elements: bool,
-    /// trims leading whitespace in Text events, skip the element if text is empty
-    trim_text_start: bool,
-    /// trims trailing whitespace in Text events.
-    trim_text_end: bool,
-    /// trims trailing whitespaces from markup names in closing tags `</a >`
-    trim_markup_names_in_closing_tags: bool,
-    /// check if End nodes match last Start node
-    check_end_names: bool,
-    /// check if comments contains `--` (false per default)
-    check_comments: bool,
+    /// options of the reader
+    config: ReaderConfig,
+    /// number of the opened elements
+    depth: usize,
+    /// part of the document, where the reader is, if `strict_prolog` is enabled
+    document_part: DocumentPart,
+    /// position of the `<` of the last markup
+    markup_start: usize,
+    /// whether an XML declaration was read
+    has_declaration: bool,
+    /// whether a DOCTYPE was read
+    has_doctype: bool,
+    /// number of `INCLUDE` marked sections, which are not closed yet
+    included_sections: usize,
     /// all currently Started elements which didn't have a matching
     /// End element yet
     opened_buffer: Vec<u8>,
@@ -101,17 +435,18 @@ impl<B: BufRead> Reader<B> {
     /// Creates a `Reader` that reads from a reader implementing `BufRead`.
     pub fn from_reader(reader: B) -> Reader<B> {
         Reader {
-            reader,
+            reader: Source::new(reader),
             opened_buffer: Vec::new(),
             opened_starts: Vec::new(),
             tag_state: TagState::Closed,
-            expand_empty_elements: false,
-            trim_text_start: false,
-            trim_text_end: false,
-            trim_markup_names_in_closing_tags: true,
-            check_end_names: true,
+            config: ReaderConfig::default(),
+            depth: 0,
             buf_position: 0,
-            check_comments: false,
+            document_part: DocumentPart::Start,
+            markup_start: 0,
+            has_declaration: false,
+            has_doctype: false,
+            included_sections: 0,
             ns_buffer: NamespaceBufferIndex::default(),
             #[cfg(feature = "encoding")]
             encoding: ::encoding_rs::UTF_8,
@@ -132,7 +467,7 @@ impl<B: BufRead> Reader<B> {
     /// [`Start`]: events/enum.Event.html#variant.Start
     /// [`End`]: events/enum.Event.html#variant.End
     pub fn expand_empty_elements(&mut self, val: bool) -> &mut Reader<B> {
-        self.expand_empty_elements = val;
+        self.config.expand_empty_elements = val;
         self
     }
 
@@ -141,12 +476,48 @@ impl<B: BufRead> Reader<B> {
     /// When set to `true`, all [`Text`] events are trimmed. If they are empty, no event will be
     /// pushed.
     ///
+    /// This is a shortcut for [`drop_whitespace_only_text`] and [`trim_text_edges`].
+    ///
     /// (`false` by default)
     ///
     /// [`Text`]: events/enum.Event.html#variant.Text
+    /// [`drop_whitespace_only_text`]: #method.drop_whitespace_only_text
+    /// [`trim_text_edges`]: #method.trim_text_edges
     pub fn trim_text(&mut self, val: bool) -> &mut Reader<B> {
-        self.trim_text_start = val;
-        self.trim_text_end = val;
+        self.config.drop_whitespace_only_text = val;
+        self.config.trim_text_start = val;
+        self.config.trim_text_end = val;
+        self
+    }
+
+    /// Changes whether [`Text`] events, which contain only whitespaces, are skipped.
+    ///
+    /// Such texts are usually the formatting between tags. Other texts are returned
+    /// unchanged, unless [`trim_text_edges`] is set, so the whitespaces in mixed content,
+    /// like the space before `<b>` in `<p>hello <b>world</b></p>`, are kept.
+    ///
+    /// (`false` by default)
+    ///
+    /// [`Text`]: events/enum.Event.html#variant.Text
+    /// [`trim_text_edges`]: #method.trim_text_edges
+    pub fn drop_whitespace_only_text(&mut self, val: bool) -> &mut Reader<B> {
+        self.config.drop_whitespace_only_text = val;
+        self
+    }
+
+    /// Changes whether whitespace before and after character data should be removed.
+    ///
+    /// Unlike [`trim_text`], texts, which contain only whitespaces, are returned as empty
+    /// [`Text`] events, unless [`drop_whitespace_only_text`] is set.
+    ///
+    /// (`false` by default)
+    ///
+    /// [`Text`]: events/enum.Event.html#variant.Text
+    /// [`trim_text`]: #method.trim_text
+    /// [`drop_whitespace_only_text`]: #method.drop_whitespace_only_text
+    pub fn trim_text_edges(&mut self, val: bool) -> &mut Reader<B> {
+        self.config.trim_text_start = val;
+        self.config.trim_text_end = val;
         self
     }
 
@@ -158,7 +529,7 @@ impl<B: BufRead> Reader<B> {
     ///
     /// [`Text`]: events/enum.Event.html#variant.Text
     pub fn trim_text_end(&mut self, val: bool) -> &mut Reader<B> {
-        self.trim_text_end = val;
+        self.config.trim_text_end = val;
         self
     }
 
@@ -174,7 +545,33 @@ impl<B: BufRead> Reader<B> {
     ///
     /// [`End`]: events/enum.Event.html#variant.End
     pub fn trim_markup_names_in_closing_tags(&mut self, val: bool) -> &mut Reader<B> {
-        self.trim_markup_names_in_closing_tags = val;
+        self.config.trim_markup_names_in_closing_tags = val;
+        self
+    }
+
+    /// Changes whether the events keep the markup as written, so a [`Writer`] writes the
+    /// document back unchanged.
+    ///
+    /// The quotes of attribute values, the whitespaces inside start tags and the style of
+    /// empty elements (`<a/>` or `<a></a>`) are always kept. When set to `true`:
+    /// - the whitespaces after the name in closing tags `</a >` are kept in the [`End`]
+    ///   event, available with [`BytesEnd::trailing_whitespaces`]. The name itself is
+    ///   trimmed, regardless of [`trim_markup_names_in_closing_tags`];
+    /// - the content of [`CData`] events is kept as is, instead of being escaped like the
+    ///   content of [`Text`] events. Use its bytes directly instead of unescaping them.
+    ///
+    /// The keyword of a `<!doctype>` declaration is still written in uppercase.
+    ///
+    /// (`false` by default)
+    ///
+    /// [`Writer`]: writer/struct.Writer.html
+    /// [`End`]: events/enum.Event.html#variant.End
+    /// [`CData`]: events/enum.Event.html#variant.CData
+    /// [`Text`]: events/enum.Event.html#variant.Text
+    /// [`BytesEnd::trailing_whitespaces`]: events/struct.BytesEnd.html#method.trailing_whitespaces
+    /// [`trim_markup_names_in_closing_tags`]: #method.trim_markup_names_in_closing_tags
+    pub fn preserve_formatting(&mut self, val: bool) -> &mut Reader<B> {
+        self.config.preserve_formatting = val;
         self
     }
 
@@ -192,7 +589,130 @@ impl<B: BufRead> Reader<B> {
     ///
     /// [`End`]: events/enum.Event.html#variant.End
     pub fn check_end_names(&mut self, val: bool) -> &mut Reader<B> {
-        self.check_end_names = val;
+        self.config.check_end_names = val;
+        self
+    }
+
+    /// Changes whether only local names are compared when checking closing tags with
+    /// [`check_end_names`], so a closing tag can use another namespace prefix than the opening
+    /// tag or omit it. For example, `<ns1:item></item>` will be permitted.
+    ///
+    /// This is useful to read sloppy documents, which do not follow the namespace rules.
+    ///
+    /// (`false` by default)
+    ///
+    /// [`check_end_names`]: #method.check_end_names
+    pub fn lenient_end_name_prefixes(&mut self, val: bool) -> &mut Reader<B> {
+        self.config.lenient_end_name_prefixes = val;
+        self
+    }
+
+    /// Changes whether names are compared ignoring ASCII case when checking closing tags with
+    /// [`check_end_names`]. For example, `<DIV></div>` will be permitted.
+    ///
+    /// This can be combined with [`lenient_end_name_prefixes`].
+    ///
+    /// (`false` by default)
+    ///
+    /// [`check_end_names`]: #method.check_end_names
+    /// [`lenient_end_name_prefixes`]: #method.lenient_end_name_prefixes
+    pub fn lenient_end_name_case(&mut self, val: bool) -> &mut Reader<B> {
+        self.config.lenient_end_name_case = val;
+        self
+    }
+
+    /// Changes whether attribute values without quotes, like in `<td colspan=2>`, are
+    /// accepted. Such value ends at the first whitespace or at the end of the tag.
+    ///
+    /// The reader quotes such values in the returned [`BytesStart`], so they can be read
+    /// with [`BytesStart::attributes`] and by the deserializer as usual, unlike
+    /// [`BytesStart::html_attributes`], which only relaxes the parsing of attributes. Note
+    /// that a value cannot end with `/`, because `/` before `>` closes an empty element.
+    ///
+    /// Attributes without values, like in `<option selected>`, are kept as is and can be
+    /// read with [`BytesStart::html_attributes`]. The deserializer reads the attributes in
+    /// this mode as well, and such attributes are deserialized as `true` into `bool` fields
+    /// and as empty strings into string fields.
+    ///
+    /// (`false` by default)
+    ///
+    /// [`BytesStart`]: events/struct.BytesStart.html
+    /// [`BytesStart::attributes`]: events/struct.BytesStart.html#method.attributes
+    /// [`BytesStart::html_attributes`]: events/struct.BytesStart.html#method.html_attributes
+    pub fn lenient_attribute_values(&mut self, val: bool) -> &mut Reader<B> {
+        self.config.lenient_attribute_values = val;
+        self
+    }
+
+    /// Changes whether `&` characters, which do not start an entity or a character reference,
+    /// like in `Fish & Chips`, are accepted as literal text.
+    ///
+    /// The reader escapes such characters in texts and attribute values, so they can be
+    /// unescaped as usual.
+    ///
+    /// (`false` by default)
+    pub fn lenient_ampersands(&mut self, val: bool) -> &mut Reader<B> {
+        self.config.lenient_ampersands = val;
+        self
+    }
+
+    /// Sets the names of elements, which never have content, like `br` or `img` in HTML.
+    /// Start tags of such elements are read as empty elements, so `<br>` is read as `<br/>`.
+    /// Names are compared ignoring ASCII case.
+    ///
+    /// A closing tag of such element, like `</br>`, is still reported as a mismatch, if
+    /// [`check_end_names`] is enabled.
+    ///
+    /// (empty by default)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate quick_xml;
+    /// # fn main() {
+    /// use quick_xml::events::Event;
+    /// use quick_xml::Reader;
+    ///
+    /// let mut reader = Reader::from_str("<p>line<BR>line</p>");
+    /// reader.void_elements(&["br", "hr", "img"]);
+    /// let mut buf = Vec::new();
+    /// let mut events = Vec::new();
+    /// loop {
+    ///     match reader.read_event(&mut buf).unwrap() {
+    ///         Event::Eof => break,
+    ///         Event::Empty(e) => events.push(e.name().as_ref().to_vec()),
+    ///         _ => (),
+    ///     }
+    ///     buf.clear();
+    /// }
+    /// assert_eq!(events, vec![b"BR".to_vec()]);
+    /// # }
+    /// ```
+    ///
+    /// [`check_end_names`]: #method.check_end_names
+    pub fn void_elements(&mut self, names: &[&str]) -> &mut Reader<B> {
+        self.config.void_elements = names.iter().map(|n| n.as_bytes().to_vec()).collect();
+        self
+    }
+
+    /// Changes whether HTML5 named character references, such as `&nbsp;` or `&copy;`, are
+    /// resolved by the unescaping methods, which take this reader, like
+    /// [`BytesText::unescape_and_decode`], and by the [`Deserializer`] using this reader.
+    ///
+    /// They are looked up after the predefined XML entities and character references, but before
+    /// the custom entities. Unknown entities are still reported as errors.
+    ///
+    /// The methods without a reader, like [`BytesText::unescaped`], always resolve them,
+    /// when the `escape-html` feature is enabled.
+    ///
+    /// (`true` by default)
+    ///
+    /// [`BytesText::unescape_and_decode`]: events/struct.BytesText.html#method.unescape_and_decode
+    /// [`Deserializer`]: de/struct.Deserializer.html
+    /// [`BytesText::unescaped`]: events/struct.BytesText.html#method.unescaped
+    #[cfg(feature = "escape-html")]
+    pub fn resolve_html_entities(&mut self, val: bool) -> &mut Reader<B> {
+        self.config.resolve_html_entities = val;
         self
     }
 
@@ -207,10 +727,91 @@ impl<B: BufRead> Reader<B> {
     ///
     /// [`Comment`]: events/enum.Event.html#variant.Comment
     pub fn check_comments(&mut self, val: bool) -> &mut Reader<B> {
-        self.check_comments = val;
+        self.config.check_comments = val;
         self
     }
 
+    /// Changes whether the prolog and the epilog of the document, the parts before and after
+    /// the root element, are checked.
+    ///
+    /// When set to `true`, [`Error::InvalidProlog`] with the position of the problem is
+    /// returned if:
+    /// - the XML declaration is not at the very start of the document. Only a byte order mark
+    ///   can precede it;
+    /// - there are several DOCTYPE declarations, or the DOCTYPE follows the root element;
+    /// - there is a text other than whitespaces, a CDATA section, a closing tag or a second
+    ///   root element outside the root element.
+    ///
+    /// Comments and processing instructions are allowed everywhere. Whether the declarations
+    /// were read is available with [`has_declaration`] and [`has_doctype`] regardless of this
+    /// setting.
+    ///
+    /// (`false` by default)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use quick_xml::{Error, Reader};
+    ///
+    /// let mut reader = Reader::from_str(" <?xml version='1.0'?><root/>");
+    /// reader.strict_prolog(true).trim_text(true);
+    /// match reader.read_event(&mut Vec::new()) {
+    ///     Err(Error::InvalidProlog(position, _)) => assert_eq!(position, 1),
+    ///     e => panic!("Expected `InvalidProlog`, found {:?}", e),
+    /// }
+    /// ```
+    ///
+    /// [`Error::InvalidProlog`]: enum.Error.html#variant.InvalidProlog
+    /// [`has_declaration`]: #method.has_declaration
+    /// [`has_doctype`]: #method.has_doctype
+    pub fn strict_prolog(&mut self, val: bool) -> &mut Reader<B> {
+        self.config.strict_prolog = val;
+        self
+    }
+
+    /// Sets the maximum number of nested elements, so a malicious document cannot make
+    /// the code, which builds a tree of the elements, exhaust the memory or the stack.
+    ///
+    /// [`Error::TooDeep`] with the limit is returned, when a start tag or an empty element
+    /// tag would open more elements.
+    ///
+    /// (unlimited by default)
+    ///
+    /// [`Error::TooDeep`]: enum.Error.html#variant.TooDeep
+    pub fn max_depth(&mut self, val: usize) -> &mut Reader<B> {
+        self.config.max_depth = val;
+        self
+    }
+
+    /// Changes whether DOCTYPE declarations are rejected with [`Error::ForbiddenDocType`].
+    ///
+    /// Documents from untrusted sources usually have no reason to declare a DOCTYPE, which
+    /// could declare entities and point to external resources.
+    ///
+    /// (`false` by default)
+    ///
+    /// [`Error::ForbiddenDocType`]: enum.Error.html#variant.ForbiddenDocType
+    pub fn forbid_doctype(&mut self, val: bool) -> &mut Reader<B> {
+        self.config.forbid_doctype = val;
+        self
+    }
+
+    /// Returns the configuration of the reader, which can be used to create other readers
+    /// with the same options.
+    pub fn config(&self) -> &ReaderConfig {
+        &self.config
+    }
+
+    /// Returns `true` if an XML declaration `<?xml ...?>` has been read.
+    pub fn has_declaration(&self) -> bool {
+        self.has_declaration
+    }
+
+    /// Returns `true` if a DOCTYPE declaration has been read.
+    pub fn has_doctype(&self) -> bool {
+        self.has_doctype
+    }
+
     /// Gets the current byte position in the input data.
     ///
     /// Useful when debugging errors.
@@ -224,75 +825,164 @@ impl<B: BufRead> Reader<B> {
         }
     }
 
-    /// private function to read until '<' is found
-    /// return a `Text` event
-    fn read_until_open<'a, 'b>(&'a mut self, buf: &'b mut Vec<u8>) -> Result<Event<'b>> {
-        self.tag_state = TagState::Opened;
-        let buf_start = buf.len();
-        match read_until(&mut self.reader, b'<', buf, &mut self.buf_position) {
-            Ok(0) => Ok(Event::Eof),
-            Ok(_) => {
-                let (start, len) = (
-                    buf_start
-                        + if self.trim_text_start {
-                            match buf.iter().skip(buf_start).position(|&b| !is_whitespace(b)) {
-                                Some(start) => start,
-                                None => return self.read_event(buf),
-                            }
-                        } else {
-                            0
-                        },
-                    if self.trim_text_end {
-                        buf.iter()
-                            .rposition(|&b| !is_whitespace(b))
-                            .map_or_else(|| buf.len(), |p| p + 1)
-                    } else {
-                        buf.len()
-                    },
-                );
-                Ok(Event::Text(BytesText::from_escaped(&buf[start..len])))
-            }
-            Err(e) => Err(e),
+    /// Returns the next byte of the input without consuming it, or `None` at the end of input
+    fn peek_byte(&mut self) -> Result<Option<u8>> {
+        loop {
+            match self.reader.fill_buf() {
+                // We intentionally don't `consume()` the byte, otherwise we would have to
+                // handle things like '<>' here already.
+                Ok(n) => return Ok(n.first().cloned()),
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(Error::Io(e)),
+            }
+        }
+    }
+
+    /// private function to read until '<' is found
+    /// return a `Text` event
+    fn read_until_open<'b, E: EventBytes<'b, Source<B>>>(
+        &mut self,
+        mut bytes: E,
+    ) -> Result<Event<'b>> {
+        match bytes.read_until(&mut self.reader, b'<', &mut self.buf_position) {
+            Ok((0, _)) if self.included_sections > 0 => {
+                Err(Error::UnexpectedEof("INCLUDE".to_string()))
+            }
+            Ok((0, _)) => Ok(Event::Eof),
+            Ok((_, found)) => {
+                // text at the end of input is not followed by a markup
+                if found {
+                    self.tag_state = TagState::Opened;
+                }
+                if self.config.strict_prolog {
+                    let text = bytes.bytes();
+                    let start = self.buf_position - text.len() - found as usize;
+                    self.check_prolog_text(text, start)?;
+                }
+                // `]]>`, which closes `INCLUDE` marked sections, is not a part of the text
+                let closed = if self.included_sections > 0 {
+                    self.close_included_sections(bytes.bytes())
+                } else {
+                    None
+                };
+                let range = {
+                    let buf = closed
+                        .as_ref()
+                        .map_or_else(|| bytes.bytes(), |c| c.as_slice());
+                    self.trimmed_range(buf)
+                };
+                let (start, len) = match range {
+                    Some(range) => range,
+                    None => {
+                        let bytes = bytes.next(&self.reader);
+                        return self.read_event_inner(bytes);
+                    }
+                };
+                let content = match closed {
+                    Some(mut closed) => {
+                        closed.truncate(len);
+                        closed.drain(..start);
+                        Cow::Owned(closed)
+                    }
+                    None => Cow::Borrowed(&bytes.into_bytes()[start..len]),
+                };
+                if self.config.lenient_ampersands {
+                    let content = match content {
+                        Cow::Borrowed(c) => escape_bare_ampersands(c),
+                        Cow::Owned(c) => Cow::Owned(escape_bare_ampersands(&c).into_owned()),
+                    };
+                    Ok(Event::Text(BytesText::from_escaped(content)))
+                } else {
+                    Ok(Event::Text(BytesText::from_escaped(content)))
+                }
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Returns the range of the text `buf` without the whitespaces, which should be trimmed,
+    /// or `None` if the text should be skipped, because it has only whitespaces
+    fn trimmed_range(&self, buf: &[u8]) -> Option<(usize, usize)> {
+        let first = buf.iter().position(|&b| !is_whitespace(b));
+        if first.is_none() && self.config.drop_whitespace_only_text {
+            return None;
+        }
+        let start = if self.config.trim_text_start {
+            first.unwrap_or(buf.len())
+        } else {
+            0
+        };
+        let len = if self.config.trim_text_end {
+            buf.iter()
+                .rposition(|&b| !is_whitespace(b))
+                .map_or_else(|| buf.len(), |p| p + 1)
+        } else {
+            buf.len()
+        };
+        Some((start, len))
+    }
+
+    /// Removes `]]>`, which close the opened `INCLUDE` marked sections, from the `text`.
+    /// Returns `None` if the text does not close any section
+    fn close_included_sections(&mut self, text: &[u8]) -> Option<Vec<u8>> {
+        let mut closed: Option<Vec<u8>> = None;
+        let mut rest = text;
+        while self.included_sections > 0 {
+            let end = match rest.windows(3).position(|w| w == b"]]>") {
+                Some(end) => end,
+                None => break,
+            };
+            let out = closed.get_or_insert_with(|| Vec::with_capacity(text.len()));
+            out.extend_from_slice(&rest[..end]);
+            rest = &rest[end + 3..];
+            self.included_sections -= 1;
         }
+        closed.map(|mut out| {
+            out.extend_from_slice(rest);
+            out
+        })
     }
 
     /// private function to read until '>' is found
-    fn read_until_close<'a, 'b>(&'a mut self, buf: &'b mut Vec<u8>) -> Result<Event<'b>> {
+    fn read_until_close<'b, E: EventBytes<'b, Source<B>>>(
+        &mut self,
+        mut bytes: E,
+    ) -> Result<Event<'b>> {
         self.tag_state = TagState::Closed;
+        self.markup_start = self.buf_position - 1;
 
         // need to read 1 character to decide whether pay special attention to attribute values
-        let buf_start = buf.len();
-        let start = loop {
-            match self.reader.fill_buf() {
-                Ok(n) if n.is_empty() => return Ok(Event::Eof),
-                Ok(n) => {
-                    // We intentionally don't `consume()` the byte, otherwise we would have to
-                    // handle things like '<>' here already.
-                    break n[0];
-                }
-                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
-                Err(e) => return Err(Error::Io(e)),
-            }
+        let start = match self.peek_byte()? {
+            Some(start) => start,
+            None => return Err(Error::UnexpectedEof("Start".to_string())),
         };
 
-        if start != b'/' && start != b'!' && start != b'?' {
-            match read_elem_until(&mut self.reader, b'>', buf, &mut self.buf_position) {
-                Ok(0) => Ok(Event::Eof),
-                Ok(_) => {
+        if start == b'!' {
+            self.read_bang(bytes)
+        } else if start != b'/' && start != b'?' {
+            match bytes.read_elem_until(&mut self.reader, b'>', &mut self.buf_position) {
+                Ok((_, true)) => {
                     // we already *know* that we are in this case
-                    self.read_start(&buf[buf_start..])
+                    self.read_start(bytes.into_bytes())
+                }
+                Ok((_, false)) => {
+                    self.buf_position -= bytes.bytes().len();
+                    Err(Error::UnexpectedEof("Start".to_string()))
                 }
                 Err(e) => Err(e),
             }
         } else {
-            match read_until(&mut self.reader, b'>', buf, &mut self.buf_position) {
-                Ok(0) => Ok(Event::Eof),
-                Ok(_) => match start {
-                    b'/' => self.read_end(&buf[buf_start..]),
-                    b'!' => self.read_bang(buf_start, buf),
-                    b'?' => self.read_question_mark(&buf[buf_start..]),
+            match bytes.read_until(&mut self.reader, b'>', &mut self.buf_position) {
+                Ok((_, found)) => match start {
+                    _ if !found => {
+                        self.buf_position -= bytes.bytes().len();
+                        let kind = if start == b'/' { "End" } else { "XmlDecl" };
+                        Err(Error::UnexpectedEof(kind.to_string()))
+                    }
+                    b'/' => self.read_end(bytes.into_bytes()),
+                    b'?' => self.read_question_mark(bytes.into_bytes()),
                     _ => unreachable!(
-                        "We checked that `start` must be one of [/!?], was {:?} \
+                        "We checked that `start` must be one of [/?], was {:?} \
                              instead.",
                         start
                     ),
@@ -303,12 +993,21 @@ impl<B: BufRead> Reader<B> {
     }
 
     /// reads `BytesElement` starting with a `/`,
-    /// if `self.check_end_names`, checks that element matches last opened element
+    /// if `self.config.check_end_names`, checks that element matches last opened element
     /// return `End` event
     fn read_end<'a, 'b>(&'a mut self, buf: &'b [u8]) -> Result<Event<'b>> {
+        let end = |name: &'b [u8], preserve: bool| {
+            if preserve {
+                BytesEnd::borrowed_content(&buf[1..], name.len())
+            } else {
+                BytesEnd::borrowed(name)
+            }
+        };
         // XML standard permits whitespaces after the markup name in closing tags.
         // Let's strip them from the buffer before comparing tag names.
-        let name = if self.trim_markup_names_in_closing_tags {
+        let name = if self.config.trim_markup_names_in_closing_tags
+            || self.config.preserve_formatting
+        {
             if let Some(pos_end_name) = buf[1..].iter().rposition(|&b| !b.is_ascii_whitespace()) {
                 let (name, _) = buf[1..].split_at(pos_end_name + 1);
                 name
@@ -318,7 +1017,7 @@ impl<B: BufRead> Reader<B> {
         } else {
             &buf[1..]
         };
-        if self.check_end_names {
+        if self.config.check_end_names {
             let mismatch_err = |expected: &[u8], found: &[u8], buf_position: &mut usize| {
                 *buf_position -= buf.len();
                 Err(Error::EndEventMismatch {
@@ -328,100 +1027,218 @@ impl<B: BufRead> Reader<B> {
             };
             match self.opened_starts.pop() {
                 Some(start) => {
-                    if name != &self.opened_buffer[start..] {
+                    let (expected, found) = if self.config.lenient_end_name_prefixes {
+                        (
+                            QName(&self.opened_buffer[start..])
+                                .local_name()
+                                .into_inner(),
+                            QName(name).local_name().into_inner(),
+                        )
+                    } else {
+                        (&self.opened_buffer[start..], name)
+                    };
+                    let matches = if self.config.lenient_end_name_case {
+                        expected.eq_ignore_ascii_case(found)
+                    } else {
+                        expected == found
+                    };
+                    if !matches {
                         let expected = &self.opened_buffer[start..];
                         mismatch_err(expected, name, &mut self.buf_position)
                     } else {
                         self.opened_buffer.truncate(start);
-                        Ok(Event::End(BytesEnd::borrowed(name)))
+                        Ok(Event::End(end(name, self.config.preserve_formatting)))
                     }
                 }
                 None => mismatch_err(b"", &buf[1..], &mut self.buf_position),
             }
         } else {
-            Ok(Event::End(BytesEnd::borrowed(name)))
+            Ok(Event::End(end(name, self.config.preserve_formatting)))
         }
     }
 
     /// reads `BytesElement` starting with a `!`,
-    /// return `Comment`, `CData` or `DocType` event
+    /// return `Comment`, `CData` or `DocType` event.
+    ///
+    /// `INCLUDE` and `IGNORE` marked sections are processed transparently: the content
+    /// of an `INCLUDE` section is read as usual, and an `IGNORE` section is skipped
+    fn read_bang<'b, E: EventBytes<'b, Source<B>>>(&mut self, mut bytes: E) -> Result<Event<'b>> {
+        bytes.read_until(&mut self.reader, b'!', &mut self.buf_position)?;
+        bytes.push_delimiter(b'!');
+        if self.peek_byte()? == Some(b'[') {
+            // Only the keyword of a marked section is read, as the content of an `INCLUDE`
+            // section should be read as usual markup
+            bytes.read_until(&mut self.reader, b'[', &mut self.buf_position)?;
+            bytes.push_delimiter(b'[');
+            let (_, found) = bytes.read_until(&mut self.reader, b'[', &mut self.buf_position)?;
+            if found {
+                bytes.push_delimiter(b'[');
+                let section = {
+                    let buf = bytes.bytes();
+                    MarkedSection::from_keyword(&buf[2..buf.len() - 1])
+                };
+                match section {
+                    Some(MarkedSection::CData) => {}
+                    Some(MarkedSection::Include) => {
+                        self.included_sections += 1;
+                        let bytes = bytes.next(&self.reader);
+                        return self.read_event_inner(bytes);
+                    }
+                    Some(MarkedSection::Ignore) => {
+                        self.skip_ignored_section(&mut bytes)?;
+                        let bytes = bytes.next(&self.reader);
+                        return self.read_event_inner(bytes);
+                    }
+                    None => return Err(Error::UnexpectedBang),
+                }
+            } else {
+                return self.read_bang_content(bytes, false);
+            }
+        }
+        let (_, found) = bytes.read_until(&mut self.reader, b'>', &mut self.buf_position)?;
+        self.read_bang_content(bytes, found)
+    }
+
+    /// Skips the content of an `IGNORE` marked section, which header `<![IGNORE[` is
+    /// already read into `bytes`, including nested marked sections
+    fn skip_ignored_section<'b, E: EventBytes<'b, Source<B>>>(
+        &mut self,
+        bytes: &mut E,
+    ) -> Result<()> {
+        let mut depth = 1;
+        // Position of the first byte, which is not checked for `<![` and `]]>` yet
+        let mut checked = bytes.bytes().len();
+        loop {
+            let (_, found) = bytes.read_until(&mut self.reader, b'>', &mut self.buf_position)?;
+            if !found {
+                self.buf_position -= bytes.bytes().len();
+                return Err(Error::UnexpectedEof("IGNORE".to_string()));
+            }
+            bytes.push_delimiter(b'>');
+            let buf = bytes.bytes();
+            while checked + 3 <= buf.len() {
+                match &buf[checked..checked + 3] {
+                    b"<![" => {
+                        depth += 1;
+                        checked += 3;
+                    }
+                    b"]]>" => {
+                        depth -= 1;
+                        checked += 3;
+                        if depth == 0 {
+                            return Ok(());
+                        }
+                    }
+                    _ => checked += 1,
+                }
+            }
+        }
+    }
+
+    /// reads `BytesElement` starting with a `!`, which is read until the first `>`
+    /// into `bytes`, return `Comment`, `CData` or `DocType` event
     ///
     /// Note: depending on the start of the Event, we may need to read more
-    /// data, thus we need a mutable buffer
-    fn read_bang<'a, 'b>(
-        &'a mut self,
-        buf_start: usize,
-        buf: &'b mut Vec<u8>,
+    /// data, thus we need mutable bytes. `found` is `false` if the end of input
+    /// was reached before the `>`
+    fn read_bang_content<'b, E: EventBytes<'b, Source<B>>>(
+        &mut self,
+        mut bytes: E,
+        mut found: bool,
     ) -> Result<Event<'b>> {
-        if buf[buf_start..].starts_with(b"!--") {
-            while buf.len() < buf_start + 5 || !buf.ends_with(b"--") {
-                buf.push(b'>');
-                match read_until(&mut self.reader, b'>', buf, &mut self.buf_position) {
-                    Ok(0) => {
-                        self.buf_position -= buf.len() - buf_start;
+        if bytes.bytes().starts_with(b"!--") {
+            while !found || bytes.bytes().len() < 5 || !bytes.bytes().ends_with(b"--") {
+                if found {
+                    bytes.push_delimiter(b'>');
+                }
+                match bytes.read_until(&mut self.reader, b'>', &mut self.buf_position) {
+                    Ok((0, _)) => {
+                        self.buf_position -= bytes.bytes().len();
                         return Err(Error::UnexpectedEof("Comment".to_string()));
                     }
-                    Ok(_) => (),
+                    Ok((_, f)) => found = f,
                     Err(e) => return Err(e),
                 }
             }
+            let buf = bytes.into_bytes();
             let len = buf.len();
-            if self.check_comments {
+            if self.config.check_comments {
                 // search if '--' not in comments
-                if let Some(p) = memchr::memchr_iter(b'-', &buf[buf_start + 3..len - 2])
-                    .position(|p| buf[buf_start + 3 + p + 1] == b'-')
+                if let Some(p) =
+                    memchr::memchr_iter(b'-', &buf[3..len - 2]).position(|p| buf[3 + p + 1] == b'-')
                 {
-                    self.buf_position -= buf.len() - buf_start + p;
+                    self.buf_position -= buf.len() + p;
                     return Err(Error::UnexpectedToken("--".to_string()));
                 }
             }
-            Ok(Event::Comment(BytesText::from_escaped(
-                &buf[buf_start + 3..len - 2],
-            )))
-        } else if buf.len() >= buf_start + 8 {
-            match &buf[buf_start + 1..buf_start + 8] {
+            Ok(Event::Comment(BytesText::from_escaped(&buf[3..len - 2])))
+        } else if bytes.bytes().len() >= 8 {
+            match &bytes.bytes()[1..8] {
                 b"[CDATA[" => {
-                    while buf.len() < 10 || !buf.ends_with(b"]]") {
-                        buf.push(b'>');
-                        match read_until(&mut self.reader, b'>', buf, &mut self.buf_position) {
-                            Ok(0) => {
-                                self.buf_position -= buf.len() - buf_start;
+                    while !found || bytes.bytes().len() < 10 || !bytes.bytes().ends_with(b"]]") {
+                        if found {
+                            bytes.push_delimiter(b'>');
+                        }
+                        match bytes.read_until(&mut self.reader, b'>', &mut self.buf_position) {
+                            Ok((0, _)) => {
+                                self.buf_position -= bytes.bytes().len();
                                 return Err(Error::UnexpectedEof("CData".to_string()));
                             }
-                            Ok(_) => (),
+                            Ok((_, f)) => found = f,
                             Err(e) => return Err(e),
                         }
                     }
-                    Ok(Event::CData(BytesText::from_plain(
-                        &buf[buf_start + 8..buf.len() - 2],
-                    )))
+                    let buf = bytes.into_bytes();
+                    let content = &buf[8..buf.len() - 2];
+                    // The content is escaped as the content of texts, so users unescape
+                    // `Text` and `CData` events alike
+                    if self.config.preserve_formatting {
+                        Ok(Event::CData(BytesText::from_escaped(content)))
+                    } else {
+                        Ok(Event::CData(BytesText::from_plain(content)))
+                    }
                 }
                 x if x.eq_ignore_ascii_case(b"DOCTYPE") => {
-                    let mut count = buf.iter().skip(buf_start).filter(|&&b| b == b'<').count();
-                    while count > 0 {
-                        buf.push(b'>');
-                        match read_until(&mut self.reader, b'>', buf, &mut self.buf_position) {
-                            Ok(0) => {
-                                self.buf_position -= buf.len() - buf_start;
+                    let mut count = memchr::memchr_iter(b'<', bytes.bytes()).count();
+                    while !found || count > 0 {
+                        if found {
+                            bytes.push_delimiter(b'>');
+                        }
+                        match bytes.read_until(&mut self.reader, b'>', &mut self.buf_position) {
+                            Ok((0, _)) => {
+                                self.buf_position -= bytes.bytes().len();
                                 return Err(Error::UnexpectedEof("DOCTYPE".to_string()));
                             }
-                            Ok(n) => {
+                            Ok((n, f)) => {
+                                let buf = bytes.bytes();
                                 let start = buf.len() - n;
-                                count += buf.iter().skip(start).filter(|&&b| b == b'<').count();
+                                count += memchr::memchr_iter(b'<', &buf[start..]).count();
                                 count -= 1;
+                                found = f;
                             }
                             Err(e) => return Err(e),
                         }
                     }
-                    Ok(Event::DocType(BytesText::from_escaped(
-                        &buf[buf_start + 8..buf.len()],
-                    )))
+                    let buf = bytes.into_bytes();
+                    Ok(Event::DocType(BytesText::from_escaped(&buf[8..])))
                 }
                 _ => Err(Error::UnexpectedBang),
             }
         } else {
-            self.buf_position -= buf.len() - buf_start;
-            Err(Error::UnexpectedBang)
+            let buf = bytes.bytes();
+            self.buf_position -= buf.len();
+            if found {
+                return Err(Error::UnexpectedBang);
+            }
+            // `<!` and the beginning of a comment, CDATA or DOCTYPE at the end of input
+            let kind = match buf.get(1) {
+                None => "Comment, CData or DOCTYPE",
+                Some(b'-') => "Comment",
+                Some(b'[') => "CData",
+                Some(b'D') | Some(b'd') => "DOCTYPE",
+                Some(_) => return Err(Error::UnexpectedBang),
+            };
+            Err(Error::UnexpectedEof(kind.to_string()))
         }
     }
 
@@ -433,9 +1250,13 @@ impl<B: BufRead> Reader<B> {
         if len > 2 && buf[len - 1] == b'?' {
             if len > 5 && &buf[1..4] == b"xml" && is_whitespace(buf[4]) {
                 let event = BytesDecl::from_start(BytesStart::borrowed(&buf[1..len - 1], 3));
-                // Try getting encoding from the declaration event
+                // Try getting encoding from the declaration event. UTF-16 input is transcoded
+                // to UTF-8, and UTF-16 declared in an input which is not UTF-16 is wrong, so in
+                // both cases the content is kept decoded as UTF-8
                 if let Some(enc) = event.encoder() {
-                    self.encoding = enc;
+                    if !self.reader.is_transcoded() && enc != UTF_16LE && enc != UTF_16BE {
+                        self.encoding = enc;
+                    }
                     self.is_encoding_set = true;
                 }
                 Ok(Event::Decl(event))
@@ -479,24 +1300,64 @@ impl<B: BufRead> Reader<B> {
     /// return `Start` or `Empty` event
     fn read_start<'a, 'b>(&'a mut self, buf: &'b [u8]) -> Result<Event<'b>> {
         // TODO: do this directly when reading bufreader ...
-        let len = buf.len();
-        let name_end = buf.iter().position(|&b| is_whitespace(b)).unwrap_or(len);
-        if let Some(&b'/') = buf.last() {
-            let end = if name_end < len { name_end } else { len - 1 };
-            if self.expand_empty_elements {
+        let self_closed = buf.last() == Some(&b'/');
+        let content = if self_closed {
+            &buf[..buf.len() - 1]
+        } else {
+            buf
+        };
+        let name_end = content
+            .iter()
+            .position(|&b| is_whitespace(b))
+            .unwrap_or(content.len());
+        let name = &content[..name_end];
+        let empty = self_closed
+            || self
+                .config
+                .void_elements
+                .iter()
+                .any(|v| v.eq_ignore_ascii_case(name));
+        let start = self.relax_start(content, name_end);
+        if empty {
+            if self.config.expand_empty_elements {
                 self.tag_state = TagState::Empty;
                 self.opened_starts.push(self.opened_buffer.len());
-                self.opened_buffer.extend(&buf[..end]);
-                Ok(Event::Start(BytesStart::borrowed(&buf[..len - 1], end)))
+                self.opened_buffer.extend(name);
+                Ok(Event::Start(start))
             } else {
-                Ok(Event::Empty(BytesStart::borrowed(&buf[..len - 1], end)))
+                Ok(Event::Empty(start))
             }
         } else {
-            if self.check_end_names {
+            if self.config.check_end_names {
                 self.opened_starts.push(self.opened_buffer.len());
-                self.opened_buffer.extend(&buf[..name_end]);
+                self.opened_buffer.extend(name);
+            }
+            Ok(Event::Start(start))
+        }
+    }
+
+    /// Applies the enabled relaxations to the attributes of the start tag `content`
+    fn relax_start<'b>(&self, content: &'b [u8], name_end: usize) -> BytesStart<'b> {
+        let attributes = &content[name_end..];
+        let quoted = if self.config.lenient_attribute_values {
+            quote_attribute_values(attributes)
+        } else {
+            Cow::Borrowed(attributes)
+        };
+        let relaxed = match quoted {
+            Cow::Borrowed(a) if self.config.lenient_ampersands => escape_bare_ampersands(a),
+            Cow::Owned(a) if self.config.lenient_ampersands => {
+                Cow::Owned(escape_bare_ampersands(&a).into_owned())
+            }
+            quoted => quoted,
+        };
+        match relaxed {
+            Cow::Borrowed(_) => BytesStart::borrowed(content, name_end),
+            Cow::Owned(attributes) => {
+                let mut content = content[..name_end].to_vec();
+                content.extend(attributes);
+                BytesStart::owned(content, name_end)
             }
-            Ok(Event::Start(BytesStart::borrowed(buf, name_end)))
         }
     }
 
@@ -544,9 +1405,123 @@ impl<B: BufRead> Reader<B> {
     /// println!("Text events: {:?}", txt);
     /// ```
     pub fn read_event<'a, 'b>(&'a mut self, buf: &'b mut Vec<u8>) -> Result<Event<'b>> {
+        let start = buf.len();
+        self.read_event_impl(Buffered { buf, start })
+    }
+
+    /// Reads the next `Event` into `bytes`
+    fn read_event_impl<'b, E: EventBytes<'b, Source<B>>>(&mut self, bytes: E) -> Result<Event<'b>> {
+        let mut event = self.read_event_inner(bytes);
+        if let Ok(ref e) = event {
+            let mut checked = self.check_limits(e);
+            if checked.is_ok() && self.config.strict_prolog {
+                checked = self.check_prolog_markup(e);
+            }
+            if let Err(err) = checked {
+                self.tag_state = TagState::Exit;
+                event = Err(err);
+            }
+        }
+        match event {
+            Ok(Event::Decl(_)) => self.has_declaration = true,
+            Ok(Event::DocType(_)) => self.has_doctype = true,
+            _ => {}
+        }
+        event
+    }
+
+    /// Checks that the `event` does not exceed the `max_depth` and is not a forbidden DOCTYPE
+    fn check_limits(&mut self, event: &Event) -> Result<()> {
+        match *event {
+            Event::Start(_) | Event::Empty(_) if self.depth >= self.config.max_depth => {
+                Err(Error::TooDeep(self.config.max_depth))
+            }
+            Event::Start(_) => {
+                self.depth += 1;
+                Ok(())
+            }
+            Event::End(_) => {
+                self.depth = self.depth.saturating_sub(1);
+                Ok(())
+            }
+            Event::DocType(_) if self.config.forbid_doctype => {
+                Err(Error::ForbiddenDocType(self.markup_start))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks that the raw `text`, which starts at the position `start`, is allowed in the
+    /// current part of the document
+    fn check_prolog_text(&mut self, text: &[u8], start: usize) -> Result<()> {
+        let content = match self.document_part {
+            DocumentPart::Root(_) => return Ok(()),
+            DocumentPart::Start => text.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(text),
+            _ => text,
+        };
+        if let Some(i) = content.iter().position(|&b| !is_whitespace(b)) {
+            let position = start + text.len() - content.len() + i;
+            return Err(Error::InvalidProlog(
+                position,
+                "text is not allowed outside the root element",
+            ));
+        }
+        if !content.is_empty() && self.document_part == DocumentPart::Start {
+            self.document_part = DocumentPart::Prolog;
+        }
+        Ok(())
+    }
+
+    /// Checks that the markup `event` is allowed in the current part of the document and
+    /// moves to the next part
+    fn check_prolog_markup(&mut self, event: &Event) -> Result<()> {
+        use self::DocumentPart::*;
+
+        let message = match (event, self.document_part) {
+            (Event::Text(_), _) | (Event::Eof, _) => return Ok(()),
+            (_, Root(depth)) => {
+                self.document_part = match *event {
+                    Event::Start(_) => Root(depth + 1),
+                    Event::End(_) if depth == 1 => Epilog,
+                    Event::End(_) => Root(depth - 1),
+                    _ => Root(depth),
+                };
+                return Ok(());
+            }
+            (Event::Decl(_), Start) => None,
+            (Event::Decl(_), _) => Some("XML declaration must be at the start of the document"),
+            (Event::DocType(_), Start) | (Event::DocType(_), Prolog) if !self.has_doctype => None,
+            (Event::DocType(_), _) => {
+                Some("only one DOCTYPE is allowed, and only before the root element")
+            }
+            (Event::Comment(_), _) | (Event::PI(_), _) => None,
+            (Event::Start(_), Epilog) | (Event::Empty(_), Epilog) => {
+                Some("only one root element is allowed")
+            }
+            (Event::Start(_), _) | (Event::Empty(_), _) => None,
+            (Event::CData(_), _) => Some("CDATA is not allowed outside the root element"),
+            (Event::End(_), _) => Some("closing tag without the opening tag"),
+        };
+        if let Some(message) = message {
+            return Err(Error::InvalidProlog(self.markup_start, message));
+        }
+        self.document_part = match (event, self.document_part) {
+            (Event::Start(_), _) => Root(1),
+            (Event::Empty(_), _) => Epilog,
+            (_, Start) => Prolog,
+            (_, part) => part,
+        };
+        Ok(())
+    }
+
+    /// Reads the next `Event` into `bytes`, without the checks of `strict_prolog`
+    fn read_event_inner<'b, E: EventBytes<'b, Source<B>>>(
+        &mut self,
+        bytes: E,
+    ) -> Result<Event<'b>> {
         let event = match self.tag_state {
-            TagState::Opened => self.read_until_close(buf),
-            TagState::Closed => self.read_until_open(buf),
+            TagState::Opened => self.read_until_close(bytes),
+            TagState::Closed => self.read_until_open(bytes),
             TagState::Empty => self.close_expanded_empty(),
             TagState::Exit => return Ok(Event::Eof),
         };
@@ -564,12 +1539,14 @@ impl<B: BufRead> Reader<B> {
     /// can be defined on the same element as the attribute in question.
     ///
     /// *Unqualified* event inherits the current *default namespace*.
+    ///
+    /// If the prefix of the name is not bound to any namespace, the name is returned as is.
     #[inline]
     pub fn event_namespace<'a, 'b, 'c>(
         &'a self,
-        qname: &'b [u8],
+        qname: QName<'b>,
         namespace_buffer: &'c [u8],
-    ) -> (Option<&'c [u8]>, &'b [u8]) {
+    ) -> (Option<&'c [u8]>, LocalName<'b>) {
         self.ns_buffer
             .resolve_namespace(qname, namespace_buffer, true)
     }
@@ -581,18 +1558,124 @@ impl<B: BufRead> Reader<B> {
     /// can be defined on the same element as the attribute in question.
     ///
     /// *Unqualified* attribute names do *not* inherit the current *default namespace*.
+    ///
+    /// If the prefix of the name is not bound to any namespace, the name is returned as is.
     #[inline]
     pub fn attribute_namespace<'a, 'b, 'c>(
         &'a self,
-        qname: &'b [u8],
+        qname: QName<'b>,
         namespace_buffer: &'c [u8],
-    ) -> (Option<&'c [u8]>, &'b [u8]) {
+    ) -> (Option<&'c [u8]>, LocalName<'b>) {
         self.ns_buffer
             .resolve_namespace(qname, namespace_buffer, false)
     }
 
+    /// Returns all namespace bindings in scope of the last element, read by
+    /// [`read_namespaced_event`], as `(prefix, namespace)` pairs.
+    ///
+    /// The default namespace is returned with the `None` prefix. A prefix, bound again by
+    /// a nested element, is returned only once with the most recent namespace. Bindings,
+    /// removed by an empty namespace, such as `xmlns=""`, are not returned.
+    ///
+    /// The most recently declared bindings are returned first. `namespace_buffer` should be
+    /// the same buffer, which was passed to [`read_namespaced_event`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use quick_xml::Reader;
+    ///
+    /// let mut reader = Reader::from_str(r#"<a xmlns="urn:a" xmlns:p="urn:p"><b xmlns:p="urn:q">"#);
+    /// reader.trim_text(true);
+    /// let mut buf = Vec::new();
+    /// let mut ns_buf = Vec::new();
+    /// reader.read_namespaced_event(&mut buf, &mut ns_buf).unwrap();
+    /// reader.read_namespaced_event(&mut buf, &mut ns_buf).unwrap();
+    ///
+    /// let bindings: Vec<_> = reader
+    ///     .in_scope_namespaces(&ns_buf)
+    ///     .map(|(prefix, ns)| (prefix.map(|p| p.into_inner()), ns))
+    ///     .collect();
+    /// assert_eq!(
+    ///     bindings,
+    ///     vec![(Some(b"p".as_ref()), b"urn:q".as_ref()), (None, b"urn:a".as_ref())]
+    /// );
+    /// ```
+    ///
+    /// [`read_namespaced_event`]: #method.read_namespaced_event
+    pub fn in_scope_namespaces<'a>(
+        &'a self,
+        namespace_buffer: &'a [u8],
+    ) -> impl Iterator<Item = (Option<Prefix<'a>>, &'a [u8])> + 'a {
+        self.ns_buffer.in_scope(namespace_buffer)
+    }
+
+    /// Adds the namespace declarations, that are in scope of the last element, read by
+    /// [`read_namespaced_event`], and are not declared by `start`, to `start` as `xmlns`
+    /// attributes.
+    ///
+    /// This makes the element self-contained, so it can be copied to another document with
+    /// its content, which uses these bindings. Shadowed bindings are added with the innermost
+    /// namespace, and prefixes, removed by an empty namespace, such as `xmlns:p=""`, are not
+    /// added. `namespace_buffer` should be the same buffer, which was passed to
+    /// [`read_namespaced_event`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use quick_xml::events::Event;
+    /// use quick_xml::Reader;
+    ///
+    /// let mut reader = Reader::from_str(r#"<a xmlns="urn:a" xmlns:p="urn:p"><p:b x="1">"#);
+    /// reader.trim_text(true);
+    /// let mut buf = Vec::new();
+    /// let mut ns_buf = Vec::new();
+    /// reader.read_namespaced_event(&mut buf, &mut ns_buf).unwrap();
+    /// match reader.read_namespaced_event(&mut buf, &mut ns_buf).unwrap() {
+    ///     (_, Event::Start(e)) => {
+    ///         let mut e = e.into_owned();
+    ///         reader.materialize_bindings(&mut e, &ns_buf);
+    ///         assert_eq!(&*e, br#"p:b x="1" xmlns:p="urn:p" xmlns="urn:a""#.as_ref());
+    ///     }
+    ///     e => panic!("Expected Start, found {:?}", e),
+    /// }
+    /// ```
+    ///
+    /// [`read_namespaced_event`]: #method.read_namespaced_event
+    pub fn materialize_bindings(&self, start: &mut BytesStart, namespace_buffer: &[u8]) {
+        let mut declared = Vec::new();
+        for a in start.attributes().with_checks(false) {
+            match a {
+                Ok(a) => match a.key.as_namespace_binding() {
+                    Some(PrefixDeclaration::Default) => declared.push(None),
+                    Some(PrefixDeclaration::Named(prefix)) => declared.push(Some(prefix.to_vec())),
+                    None => {}
+                },
+                Err(_) => break,
+            }
+        }
+        for (prefix, ns) in self.in_scope_namespaces(namespace_buffer) {
+            let prefix = prefix.map(|p| p.into_inner());
+            if declared.iter().any(|d| d.as_deref() == prefix) {
+                continue;
+            }
+            match prefix {
+                Some(prefix) => {
+                    let mut key = b"xmlns:".to_vec();
+                    key.extend_from_slice(prefix);
+                    start.push_attribute((&*key, ns));
+                }
+                None => start.push_attribute((b"xmlns".as_ref(), ns)),
+            }
+        }
+    }
+
     /// Reads the next event and resolves its namespace (if applicable).
     ///
+    /// The namespace of `Start`, `Empty` and `End` events is resolved like with
+    /// [`event_namespace`], so it doesn't need to be resolved separately. `None` is returned
+    /// for other events and for elements, which prefix is not bound to a namespace.
+    ///
     /// # Examples
     ///
     /// ```
@@ -614,7 +1697,7 @@ impl<B: BufRead> Reader<B> {
     ///     match reader.read_namespaced_event(&mut buf, &mut ns_buf) {
     ///         Ok((ref ns, Event::Start(ref e))) => {
     ///             count += 1;
-    ///             match (*ns, e.local_name()) {
+    ///             match (*ns, e.local_name().as_ref()) {
     ///                 (Some(b"www.xxxx"), b"tag1") => (),
     ///                 (Some(b"www.yyyy"), b"tag2") => (),
     ///                 (ns, n) => panic!("Namespace and local name mismatch"),
@@ -633,6 +1716,8 @@ impl<B: BufRead> Reader<B> {
     /// println!("Found {} start events", count);
     /// println!("Text events: {:?}", txt);
     /// ```
+    ///
+    /// [`event_namespace`]: #method.event_namespace
     pub fn read_namespaced_event<'a, 'b, 'c>(
         &'a mut self,
         buf: &'b mut Vec<u8>,
@@ -686,6 +1771,18 @@ impl<B: BufRead> Reader<B> {
     ///
     /// This encoding will be used by [`decode`].
     ///
+    /// UTF-16 documents, recognized by a byte order mark or by the `<?` of the XML declaration
+    /// as described in the [XML specification], are transcoded to UTF-8 while read, so the
+    /// encoding of their events, and the returned value, is UTF-8. Positions, returned by
+    /// [`buffer_position`], are counted in the transcoded content.
+    ///
+    /// Any other encoding, which label is known to `encoding_rs`, can be declared, if it is
+    /// ASCII-compatible, such as Shift_JIS, EUC-JP, GBK, Big5 or windows-1251: the bytes of
+    /// `<`, `>`, `&`, `=` and quotes never occur inside their multibyte sequences, so events
+    /// are read as is and their content is decoded with [`decode`].
+    ///
+    /// [XML specification]: https://www.w3.org/TR/xml/#sec-guessing-no-ext-info
+    /// [`buffer_position`]: #method.buffer_position
     /// [`decode`]: #method.decode
     #[cfg(feature = "encoding")]
     pub fn encoding(&self) -> &'static Encoding {
@@ -704,6 +1801,54 @@ impl<B: BufRead> Reader<B> {
         self.encoding.decode(bytes).0
     }
 
+    /// Decodes `bytes` with [`decode`](#method.decode), which fails only without
+    /// the `encoding` feature, so callers have the same result type with and without it
+    #[inline]
+    #[cfg(feature = "encoding")]
+    pub(crate) fn decode_cow<'c>(&self, bytes: &'c [u8]) -> Result<Cow<'c, str>> {
+        Ok(self.decode(bytes))
+    }
+
+    /// Decodes `bytes` with [`decode`](#method.decode), which fails only without
+    /// the `encoding` feature, so callers have the same result type with and without it
+    #[inline]
+    #[cfg(not(feature = "encoding"))]
+    pub(crate) fn decode_cow<'c>(&self, bytes: &'c [u8]) -> Result<Cow<'c, str>> {
+        self.decode(bytes).map(Cow::Borrowed)
+    }
+
+    /// Decodes a slice using the encoding specified in the XML declaration, without BOM
+    /// sniffing, and fails on malformed sequences instead of replacing them.
+    ///
+    /// Returns [`Error::NonDecodable`] with the name of the encoding, if `bytes` are not valid
+    /// in it. If no encoding is specified, defaults to UTF-8.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate quick_xml;
+    /// # fn main() {
+    /// use quick_xml::Reader;
+    ///
+    /// // "表" in Shift_JIS
+    /// let mut reader = Reader::from_reader(&b"<?xml version='1.0' encoding='Shift_JIS'?>"[..]);
+    /// reader.trim_text(true);
+    /// reader.read_event(&mut Vec::new()).unwrap();
+    /// assert_eq!(reader.decode_strict(b"\x95\x5C").unwrap(), "\u{8868}");
+    /// // A lead byte without a trail byte
+    /// assert!(reader.decode_strict(b"\x95").is_err());
+    /// assert_eq!(reader.decode(b"\x95"), "\u{FFFD}");
+    /// # }
+    /// ```
+    ///
+    /// [`Error::NonDecodable`]: ../enum.Error.html#variant.NonDecodable
+    #[cfg(feature = "encoding")]
+    pub fn decode_strict<'c>(&self, bytes: &'c [u8]) -> Result<Cow<'c, str>> {
+        self.encoding
+            .decode_without_bom_handling_and_without_replacement(bytes)
+            .ok_or_else(|| Error::NonDecodable(self.encoding.name()))
+    }
+
     /// Decodes a UTF8 slice without BOM (Byte order mark) regardless of XML declaration.
     ///
     /// Decode `bytes` without BOM and with malformed sequences replaced with the
@@ -744,7 +1889,7 @@ impl<B: BufRead> Reader<B> {
         } else if bytes.starts_with(b"\xFE\xFF") {
             self.is_encoding_set = true;
             self.encoding = UTF_16BE;
-            bytes = &bytes[3..];
+            bytes = &bytes[2..];
         };
         self.encoding.decode_without_bom_handling(bytes).0
     }
@@ -763,6 +1908,17 @@ impl<B: BufRead> Reader<B> {
         from_utf8(bytes).map_err(Error::Utf8)
     }
 
+    /// Decodes a UTF8 slice regardless of XML declaration, failing on malformed sequences.
+    ///
+    /// # Note
+    ///
+    /// If you instead want to use XML declared encoding, use the `encoding` feature
+    #[inline]
+    #[cfg(not(feature = "encoding"))]
+    pub fn decode_strict<'c>(&self, bytes: &'c [u8]) -> Result<Cow<'c, str>> {
+        from_utf8(bytes).map(Cow::Borrowed).map_err(Error::Utf8)
+    }
+
     /// Get utf8 decoder
     #[cfg(feature = "encoding")]
     pub fn decoder(&self) -> Decoder {
@@ -771,37 +1927,399 @@ impl<B: BufRead> Reader<B> {
         }
     }
 
-    /// Get utf8 decoder
-    #[cfg(not(feature = "encoding"))]
-    pub fn decoder(&self) -> Decoder {
-        Decoder
+    /// Get utf8 decoder
+    #[cfg(not(feature = "encoding"))]
+    pub fn decoder(&self) -> Decoder {
+        Decoder
+    }
+
+    /// Returns whether whitespaces are trimmed at the start and at the end of texts
+    #[cfg(feature = "serialize")]
+    pub(crate) fn trims_text(&self) -> (bool, bool) {
+        (self.config.trim_text_start, self.config.trim_text_end)
+    }
+
+    /// Returns the position of the `<` of the last read markup
+    pub(crate) fn markup_start(&self) -> usize {
+        self.markup_start
+    }
+
+    /// Returns whether texts, which contain only whitespaces, are skipped
+    #[cfg(feature = "serialize")]
+    pub(crate) fn drops_whitespace_only_text(&self) -> bool {
+        self.config.drop_whitespace_only_text
+    }
+
+    /// Returns `true` if attributes are read leniently, so the deserializer reads them in
+    /// the HTML mode
+    #[cfg(feature = "serialize")]
+    pub(crate) fn lenient_attributes(&self) -> bool {
+        self.config.lenient_attribute_values
+    }
+
+    /// Returns `true` if HTML named entities should be resolved when unescaping
+    /// with this reader
+    #[cfg(feature = "escape-html")]
+    pub(crate) fn html_entities(&self) -> bool {
+        self.config.resolve_html_entities
+    }
+
+    /// Returns `true` if HTML named entities should be resolved when unescaping
+    /// with this reader
+    #[cfg(not(feature = "escape-html"))]
+    pub(crate) fn html_entities(&self) -> bool {
+        false
+    }
+
+    /// Reads until end element is found
+    ///
+    /// Manages nested cases where parent and child elements have the same name
+    pub fn read_to_end<K: AsRef<[u8]>>(&mut self, end: K, buf: &mut Vec<u8>) -> Result<()> {
+        self.read_to_end_spanned(end, buf).map(|_| ())
+    }
+
+    /// Reads until end element is found, as [`read_to_end`] does, and returns the range of
+    /// bytes between the start and the end tags, which can be used to get the raw content
+    /// of the element without parsing it.
+    ///
+    /// Positions are the same as returned by [`buffer_position`], that is the numbers of bytes
+    /// consumed from the underlying reader. For a reader created with [`from_str`] or
+    /// [`from_reader`] over a slice, these are positions in that slice. Other readers do not
+    /// keep the input, so the content should be extracted from another copy of it.
+    ///
+    /// Should be called right after the [`Start`] event of the element.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use quick_xml::Reader;
+    /// use quick_xml::events::Event;
+    ///
+    /// let xml = "<root><inner><a>1</a><inner/></inner><b/></root>";
+    /// let mut reader = Reader::from_str(xml);
+    /// let mut buf = Vec::new();
+    /// loop {
+    ///     match reader.read_event(&mut buf).unwrap() {
+    ///         Event::Start(ref e) if e.name().as_ref() == b"inner" => break,
+    ///         _ => (),
+    ///     }
+    /// }
+    /// let span = reader.read_to_end_spanned(b"inner", &mut buf).unwrap();
+    /// assert_eq!(&xml[span], "<a>1</a><inner/>");
+    /// ```
+    ///
+    /// [`read_to_end`]: #method.read_to_end
+    /// [`buffer_position`]: #method.buffer_position
+    /// [`from_str`]: #method.from_str
+    /// [`from_reader`]: #method.from_reader
+    /// [`Start`]: events/enum.Event.html#variant.Start
+    pub fn read_to_end_spanned<K: AsRef<[u8]>>(
+        &mut self,
+        end: K,
+        buf: &mut Vec<u8>,
+    ) -> Result<Range<usize>> {
+        let start = self.buffer_position();
+        let mut depth = 0;
+        let end = end.as_ref();
+        loop {
+            let position = self.buffer_position();
+            match self.read_event(buf) {
+                Ok(Event::End(ref e)) if e.name().as_ref() == end => {
+                    if depth == 0 {
+                        return Ok(start..position);
+                    }
+                    depth -= 1;
+                }
+                Ok(Event::Start(ref e)) if e.name().as_ref() == end => depth += 1,
+                Err(e) => return Err(e),
+                Ok(Event::Eof) => {
+                    return Err(Error::UnexpectedEof(format!("</{:?}>", from_utf8(end))));
+                }
+                _ => (),
+            }
+            buf.clear();
+        }
+    }
+
+    /// Reads events until a [`Start`] or [`Empty`] event of an element with the qualified
+    /// `name` at any depth, and returns its start tag, or `None` at the end of the document.
+    ///
+    /// This is a quick way to locate a known section of a large document: the events before
+    /// the element are read, as they must be to find the nesting of the elements, but not
+    /// returned. The events are read into `buf` after its current content, and the returned
+    /// start tag borrows it, as if it would be returned by [`read_event`]. The next event is
+    /// the content of the found element, or its `End` event, if it is empty and
+    /// [`expand_empty_elements`] is enabled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use quick_xml::name::QName;
+    /// use quick_xml::Reader;
+    ///
+    /// let xml = r#"<config><server><port>80</port></server><db><port value="5432"/></db></config>"#;
+    /// let mut reader = Reader::from_str(xml);
+    /// let mut buf = Vec::new();
+    ///
+    /// let db = reader.seek_to_element(QName(b"db"), &mut buf).unwrap().unwrap();
+    /// assert_eq!(db.name(), QName(b"db"));
+    ///
+    /// let port = reader.seek_to_element(QName(b"port"), &mut buf).unwrap().unwrap();
+    /// let value = port.try_get_attribute("value").unwrap().unwrap();
+    /// assert_eq!(&*value.value, b"5432");
+    ///
+    /// assert!(reader.seek_to_element(QName(b"port"), &mut buf).unwrap().is_none());
+    /// ```
+    ///
+    /// [`Start`]: events/enum.Event.html#variant.Start
+    /// [`Empty`]: events/enum.Event.html#variant.Empty
+    /// [`read_event`]: #method.read_event
+    /// [`expand_empty_elements`]: #method.expand_empty_elements
+    pub fn seek_to_element<'b>(
+        &mut self,
+        name: QName,
+        buf: &'b mut Vec<u8>,
+    ) -> Result<Option<BytesStart<'b>>> {
+        let start = buf.len();
+        // The event cannot be returned from the loop, which reuses the buffer, so only
+        // the content of the tag, if it is not borrowed from the buffer, is kept
+        let (len, name_len, owned) = loop {
+            buf.truncate(start);
+            let e = match self.read_event(buf)? {
+                Event::Start(e) | Event::Empty(e) => e,
+                Event::Eof => return Ok(None),
+                _ => continue,
+            };
+            if e.name() == name {
+                let (len, name_len) = (e.len(), e.name().as_ref().len());
+                match e.into_inner() {
+                    Cow::Borrowed(_) => break (len, name_len, None),
+                    Cow::Owned(content) => break (len, name_len, Some(content)),
+                }
+            }
+        };
+        Ok(Some(match owned {
+            Some(content) => BytesStart::owned(content, name_len),
+            // The tag is the last bytes in the buffer, except for `/` of an empty element
+            None => {
+                let end = buf.len() - buf.ends_with(b"/") as usize;
+                BytesStart::borrowed(&buf[end - len..end], name_len)
+            }
+        }))
+    }
+
+    /// Whether the last event is the `Start` event of an empty element, expanded by
+    /// [`expand_empty_elements`], so the next event is its `End` event
+    ///
+    /// [`expand_empty_elements`]: #method.expand_empty_elements
+    pub(crate) fn is_expanded_empty(&self) -> bool {
+        matches!(self.tag_state, TagState::Empty)
     }
 
-    /// Reads until end element is found
-    ///
-    /// Manages nested cases where parent and child elements have the same name
-    pub fn read_to_end<K: AsRef<[u8]>>(&mut self, end: K, buf: &mut Vec<u8>) -> Result<()> {
-        let mut depth = 0;
-        let end = end.as_ref();
+    /// Passes the raw content of the element, which `Start` event is the last event read,
+    /// to `out` in chunks, without reading it as events, and stops right before the end tag
+    /// of the element, so the next event is that `End` event. Only the nesting of the inner
+    /// elements is tracked: their names are not checked and namespaces are not resolved.
+    ///
+    /// `end` is the name of the element, which is reported if the input ends before it
+    pub(crate) fn copy_content_raw<F>(&mut self, end: &[u8], mut out: F) -> Result<()>
+    where
+        F: FnMut(&[u8]) -> Result<()>,
+    {
+        let mut state = RawState::Text;
+        let mut depth = 0usize;
+        // Name of the current start tag, collected only to recognize void elements
+        let mut name = Vec::new();
         loop {
-            match self.read_event(buf) {
-                Ok(Event::End(ref e)) if e.name() == end => {
-                    if depth == 0 {
-                        return Ok(());
+            let (used, open) = {
+                let available = match self.reader.fill_buf() {
+                    Ok([]) => {
+                        return Err(Error::UnexpectedEof(format!("</{:?}>", from_utf8(end))));
                     }
-                    depth -= 1;
+                    Ok(n) => n,
+                    Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                    Err(e) => return Err(Error::Io(e)),
+                };
+                let mut used = available.len();
+                let mut open = false;
+                let mut i = 0;
+                while i < available.len() {
+                    // Bytes, which do not change the state, are skipped at once
+                    let rest = &available[i..];
+                    let skip = match state {
+                        RawState::Text => memchr::memchr(b'<', rest),
+                        RawState::EndTag | RawState::Decl => memchr::memchr(b'>', rest),
+                        RawState::StartTag { quote: Some(q), .. } => memchr::memchr(q, rest),
+                        RawState::StartTag {
+                            quote: None,
+                            in_name: false,
+                            slash,
+                        } => {
+                            let skip = memchr::memchr3(b'>', b'"', b'\'', rest);
+                            let last = skip.unwrap_or(rest.len());
+                            state = RawState::StartTag {
+                                quote: None,
+                                in_name: false,
+                                slash: if last > 0 {
+                                    rest[last - 1] == b'/'
+                                } else {
+                                    slash
+                                },
+                            };
+                            skip
+                        }
+                        RawState::Comment(0) => memchr::memchr(b'-', rest),
+                        RawState::CData(0) => memchr::memchr(b']', rest),
+                        RawState::PI(false) => memchr::memchr(b'?', rest),
+                        _ => Some(0),
+                    };
+                    i = match skip {
+                        Some(skip) => i + skip,
+                        None => break,
+                    };
+                    let b = available[i];
+                    i += 1;
+                    state = match state {
+                        // `<` of the end tag of the element can only be found here
+                        RawState::Text if b == b'<' && depth == 0 => {
+                            used = i;
+                            open = true;
+                            break;
+                        }
+                        RawState::Text if b == b'<' => RawState::Open,
+                        RawState::Text => RawState::Text,
+                        RawState::Open => match b {
+                            b'/' => RawState::EndTag,
+                            b'!' => RawState::Bang(b"", 0),
+                            b'?' => RawState::PI(false),
+                            _ => {
+                                name.clear();
+                                if !self.config.void_elements.is_empty() {
+                                    name.push(b);
+                                }
+                                RawState::StartTag {
+                                    quote: None,
+                                    slash: false,
+                                    in_name: true,
+                                }
+                            }
+                        },
+                        RawState::StartTag {
+                            quote: Some(q),
+                            in_name,
+                            ..
+                        } => RawState::StartTag {
+                            quote: if b == q { None } else { Some(q) },
+                            slash: false,
+                            in_name,
+                        },
+                        RawState::StartTag { slash, .. } if b == b'>' => {
+                            let void = self
+                                .config
+                                .void_elements
+                                .iter()
+                                .any(|v| v.eq_ignore_ascii_case(&name));
+                            if !slash && !void {
+                                depth += 1;
+                            }
+                            RawState::Text
+                        }
+                        RawState::StartTag { in_name, .. } => {
+                            let in_name = in_name && !is_whitespace(b) && b != b'/';
+                            if in_name && !self.config.void_elements.is_empty() {
+                                name.push(b);
+                            }
+                            RawState::StartTag {
+                                quote: if b == b'"' || b == b'\'' {
+                                    Some(b)
+                                } else {
+                                    None
+                                },
+                                slash: b == b'/',
+                                in_name,
+                            }
+                        }
+                        RawState::EndTag if b == b'>' => {
+                            depth -= 1;
+                            RawState::Text
+                        }
+                        RawState::EndTag => RawState::EndTag,
+                        RawState::Bang(_, 0) if b == b'>' => RawState::Text,
+                        RawState::Bang(_, 0) if b == b'-' => RawState::Bang(b"--", 1),
+                        RawState::Bang(_, 0) if b == b'[' => RawState::Bang(b"[CDATA[", 1),
+                        RawState::Bang(prefix, len) if len > 0 && prefix[len] == b => {
+                            match (prefix.len() == len + 1, prefix[0]) {
+                                (true, b'-') => RawState::Comment(0),
+                                (true, _) => RawState::CData(0),
+                                (false, _) => RawState::Bang(prefix, len + 1),
+                            }
+                        }
+                        RawState::Bang(..) | RawState::Decl if b == b'>' => RawState::Text,
+                        RawState::Bang(..) | RawState::Decl => RawState::Decl,
+                        RawState::Comment(dashes) if b == b'>' && dashes >= 2 => RawState::Text,
+                        RawState::Comment(dashes) if b == b'-' => RawState::Comment(dashes + 1),
+                        RawState::Comment(_) => RawState::Comment(0),
+                        RawState::CData(brackets) if b == b'>' && brackets >= 2 => RawState::Text,
+                        RawState::CData(brackets) if b == b']' => RawState::CData(brackets + 1),
+                        RawState::CData(_) => RawState::CData(0),
+                        RawState::PI(true) if b == b'>' => RawState::Text,
+                        RawState::PI(_) => RawState::PI(b == b'?'),
+                    };
                 }
-                Ok(Event::Start(ref e)) if e.name() == end => depth += 1,
-                Err(e) => return Err(e),
-                Ok(Event::Eof) => {
-                    return Err(Error::UnexpectedEof(format!("</{:?}>", from_utf8(end))));
+                // `<` is not a part of the content, if it opens the end tag of the element
+                out(&available[..used - open as usize])?;
+                (used, open)
+            };
+            self.reader.consume(used);
+            self.buf_position += used;
+            if open {
+                if self.peek_byte()? == Some(b'/') {
+                    self.tag_state = TagState::Opened;
+                    return Ok(());
                 }
-                _ => (),
+                out(b"<")?;
+                state = RawState::Open;
             }
-            buf.clear();
         }
     }
 
+    /// Returns an iterator over the elements or attributes matching the absolute `path`,
+    /// read from the remaining events.
+    ///
+    /// The path is a tiny subset of XPath: `/`-separated qualified names of elements,
+    /// starting from the root element, where `*` matches any element, and an optional
+    /// final `@name` step, which selects an attribute of the matched elements. Elements
+    /// are yielded with their text and their position in the input, see [`Selected`].
+    ///
+    /// Returns [`Error::InvalidPath`] if the path is malformed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use quick_xml::Reader;
+    ///
+    /// let xml = r#"<catalog>
+    ///     <book id="1"><title>XML &amp; you</title></book>
+    ///     <book id="2"><title>Streaming</title></book>
+    /// </catalog>"#;
+    /// let mut reader = Reader::from_str(xml);
+    /// reader.trim_text(true);
+    ///
+    /// let titles = reader
+    ///     .select("/catalog/book/title")
+    ///     .unwrap()
+    ///     .map(|selected| selected.map(|s| s.text().to_string()))
+    ///     .collect::<Result<Vec<_>, _>>()
+    ///     .unwrap();
+    /// assert_eq!(titles, vec!["XML & you", "Streaming"]);
+    /// ```
+    ///
+    /// [`Selected`]: enum.Selected.html
+    /// [`Error::InvalidPath`]: enum.Error.html#variant.InvalidPath
+    pub fn select(&mut self, path: &str) -> Result<Select<'_, B>> {
+        Select::new(self, path)
+    }
+
     /// Reads optional text between start and end tags.
     ///
     /// If the next event is a [`Text`] event, returns the decoded and unescaped content as a
@@ -839,7 +2357,7 @@ impl<B: BufRead> Reader<B> {
     pub fn read_text<K: AsRef<[u8]>>(&mut self, end: K, buf: &mut Vec<u8>) -> Result<String> {
         let s = match self.read_event(buf) {
             Ok(Event::Text(e)) => e.unescape_and_decode(self),
-            Ok(Event::End(ref e)) if e.name() == end.as_ref() => return Ok("".to_string()),
+            Ok(Event::End(ref e)) if e.name().as_ref() == end.as_ref() => return Ok("".to_string()),
             Err(e) => return Err(e),
             Ok(Event::Eof) => return Err(Error::UnexpectedEof("Text".to_string())),
             _ => return Err(Error::TextNotFound),
@@ -886,7 +2404,7 @@ impl<B: BufRead> Reader<B> {
     ///
     /// loop {
     ///     match reader.read_event(&mut buf) {
-    ///         Ok(Event::Start(ref e)) => match e.name() {
+    ///         Ok(Event::Start(ref e)) => match e.name().as_ref() {
     ///             b"tag1" | b"tag2" => (),
     ///             tag => {
     ///                 assert_eq!(b"tag3", tag);
@@ -901,7 +2419,7 @@ impl<B: BufRead> Reader<B> {
     /// }
     /// ```
     pub fn into_underlying_reader(self) -> B {
-        self.reader
+        self.reader.inner
     }
 }
 
@@ -919,17 +2437,456 @@ impl<'a> Reader<&'a [u8]> {
     pub fn from_str(s: &'a str) -> Reader<&'a [u8]> {
         Reader::from_reader(s.as_bytes())
     }
+
+    /// Creates an XML reader from a byte slice.
+    pub fn from_bytes(s: &'a [u8]) -> Reader<&'a [u8]> {
+        Reader::from_reader(s)
+    }
+
+    /// Reads the next `Event` without a buffer.
+    ///
+    /// Events, including their attributes and texts, borrow the input slice, so unlike
+    /// [`read_event`] the bytes of events are not copied anywhere, and events can be kept
+    /// while the next ones are read. The events are the same, as [`read_event`] returns.
+    ///
+    /// With the `encoding` feature UTF-16 input is transcoded to UTF-8, so events of such
+    /// input own their content.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use quick_xml::Reader;
+    /// use quick_xml::events::Event;
+    ///
+    /// let xml = "<tag1 att1='test'><tag2>Test</tag2><tag2>Test 2</tag2></tag1>";
+    /// let mut reader = Reader::from_str(xml);
+    /// reader.trim_text(true);
+    /// let mut texts = Vec::new();
+    /// loop {
+    ///     match reader.read_event_unbuffered() {
+    ///         Ok(Event::Text(e)) => texts.push(e),
+    ///         Ok(Event::Eof) => break,
+    ///         Ok(_) => (),
+    ///         Err(e) => panic!("Error at position {}: {:?}", reader.buffer_position(), e),
+    ///     }
+    /// }
+    /// assert_eq!(texts.len(), 2);
+    /// assert_eq!(&*texts[1], b"Test 2");
+    /// ```
+    ///
+    /// [`read_event`]: #method.read_event
+    pub fn read_event_unbuffered(&mut self) -> Result<Event<'a>> {
+        match self.reader.as_slice() {
+            Some(input) => self.read_event_impl(Borrowed { input, len: 0 }),
+            None => {
+                let mut buf = Vec::new();
+                self.read_event(&mut buf).map(Event::into_owned)
+            }
+        }
+    }
+
+    /// Returns the rest of the input, if events borrow it
+    #[cfg(feature = "serialize")]
+    pub(crate) fn remaining(&mut self) -> Option<&'a [u8]> {
+        self.reader.as_slice()
+    }
+}
+
+/// The input of a [`Reader`]. With the `encoding` feature UTF-16 input is detected and
+/// transcoded to UTF-8, so the parser only has to deal with ASCII-compatible bytes.
+///
+/// [`Reader`]: struct.Reader.html
+#[derive(Clone)]
+struct Source<B> {
+    inner: B,
+    /// Whether the input was checked for UTF-16
+    #[cfg(feature = "encoding")]
+    detected: bool,
+    #[cfg(feature = "encoding")]
+    transcoder: Option<Transcoder>,
+    /// The first bytes of the input, consumed to detect UTF-16 when they were not
+    /// available at once, and which should be read before the rest of the input
+    #[cfg(feature = "encoding")]
+    prefix: Vec<u8>,
+}
+
+impl<B: BufRead> Source<B> {
+    fn new(inner: B) -> Self {
+        Source {
+            inner,
+            #[cfg(feature = "encoding")]
+            detected: false,
+            #[cfg(feature = "encoding")]
+            transcoder: None,
+            #[cfg(feature = "encoding")]
+            prefix: Vec::new(),
+        }
+    }
+
+    /// Returns `true` if the input is UTF-16, transcoded to UTF-8
+    #[cfg(feature = "encoding")]
+    fn is_transcoded(&self) -> bool {
+        self.transcoder.is_some()
+    }
+
+    /// Detects UTF-16 input by a byte order mark or by the `<?` of the XML declaration
+    #[cfg(feature = "encoding")]
+    fn detect(&mut self) -> io::Result<()> {
+        // The first 4 bytes are enough to detect UTF-16. Usually they are available
+        // at once, otherwise they are collected to the `prefix` first
+        let start = self.inner.fill_buf()?;
+        if start.len() < 4 {
+            while self.prefix.len() < 4 {
+                let len = {
+                    let available = self.inner.fill_buf()?;
+                    let len = available.len().min(4 - self.prefix.len());
+                    self.prefix.extend_from_slice(&available[..len]);
+                    len
+                };
+                if len == 0 {
+                    break;
+                }
+                self.inner.consume(len);
+            }
+        }
+        let start = if self.prefix.is_empty() {
+            self.inner.fill_buf()?
+        } else {
+            &self.prefix
+        };
+        let (encoding, bom) = match start {
+            [0xFF, 0xFE, ..] => (UTF_16LE, 2),
+            [0xFE, 0xFF, ..] => (UTF_16BE, 2),
+            [b'<', 0, b'?', 0, ..] => (UTF_16LE, 0),
+            [0, b'<', 0, b'?', ..] => (UTF_16BE, 0),
+            _ => {
+                self.detected = true;
+                return Ok(());
+            }
+        };
+        let pending = if self.prefix.is_empty() {
+            self.inner.consume(bom);
+            Vec::new()
+        } else {
+            self.prefix.split_off(bom)
+        };
+        self.prefix.clear();
+        self.transcoder = Some(Transcoder {
+            encoding,
+            buf: Vec::new(),
+            pos: 0,
+            pending,
+        });
+        self.detected = true;
+        Ok(())
+    }
+}
+
+impl<'a> Source<&'a [u8]> {
+    /// Returns the rest of the input, if the bytes of events can be borrowed from it,
+    /// that is, the input is not transcoded
+    #[cfg(feature = "encoding")]
+    fn as_slice(&mut self) -> Option<&'a [u8]> {
+        if !self.detected {
+            // Reading from a slice never fails
+            self.detect().ok()?;
+        }
+        if self.transcoder.is_none() && self.prefix.is_empty() {
+            Some(self.inner)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the rest of the input, if the bytes of events can be borrowed from it
+    #[cfg(not(feature = "encoding"))]
+    fn as_slice(&mut self) -> Option<&'a [u8]> {
+        Some(self.inner)
+    }
+}
+
+impl<B: BufRead> io::Read for Source<B> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = {
+            let available = self.fill_buf()?;
+            let len = available.len().min(buf.len());
+            buf[..len].copy_from_slice(&available[..len]);
+            len
+        };
+        self.consume(len);
+        Ok(len)
+    }
+}
+
+impl<B: BufRead> BufRead for Source<B> {
+    #[cfg(not(feature = "encoding"))]
+    #[inline]
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.inner.fill_buf()
+    }
+
+    #[cfg(feature = "encoding")]
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if !self.detected {
+            self.detect()?;
+        }
+        match self.transcoder {
+            Some(ref mut transcoder) => transcoder.fill_buf(&mut self.inner),
+            None if !self.prefix.is_empty() => Ok(&self.prefix),
+            None => self.inner.fill_buf(),
+        }
+    }
+
+    #[cfg(not(feature = "encoding"))]
+    #[inline]
+    fn consume(&mut self, amt: usize) {
+        self.inner.consume(amt)
+    }
+
+    #[cfg(feature = "encoding")]
+    fn consume(&mut self, amt: usize) {
+        match self.transcoder {
+            Some(ref mut transcoder) => transcoder.pos += amt,
+            None if !self.prefix.is_empty() => {
+                self.prefix.drain(..amt);
+            }
+            None => self.inner.consume(amt),
+        }
+    }
+}
+
+/// Transcodes UTF-16 input to UTF-8 chunk by chunk.
+///
+/// Only complete characters are decoded, so no decoder state is kept between chunks
+/// and the `Reader` stays `Clone`.
+#[cfg(feature = "encoding")]
+#[derive(Clone)]
+struct Transcoder {
+    /// `UTF_16LE` or `UTF_16BE`
+    encoding: &'static Encoding,
+    /// Transcoded content of the last chunk
+    buf: Vec<u8>,
+    /// Position of the first not consumed byte in `buf`
+    pos: usize,
+    /// Input which is not decoded yet: an incomplete code unit or a high surrogate
+    /// at the end of the previous chunk, followed by the current chunk
+    pending: Vec<u8>,
+}
+
+#[cfg(feature = "encoding")]
+impl Transcoder {
+    fn fill_buf<B: BufRead>(&mut self, inner: &mut B) -> io::Result<&[u8]> {
+        while self.pos == self.buf.len() {
+            self.buf.clear();
+            self.pos = 0;
+            let read = {
+                let input = inner.fill_buf()?;
+                self.pending.extend_from_slice(input);
+                input.len()
+            };
+            inner.consume(read);
+            // At the end of input the incomplete characters are decoded as malformed ones
+            let complete = if read == 0 {
+                self.pending.len()
+            } else {
+                self.complete_len()
+            };
+            if complete == 0 {
+                if read == 0 {
+                    break;
+                }
+                continue;
+            }
+            let mut decoder = self.encoding.new_decoder_without_bom_handling();
+            let max_len = decoder
+                .max_utf8_buffer_length(complete)
+                .expect("the length of a chunk does not overflow");
+            self.buf.resize(max_len, 0);
+            let (_, _, written, _) =
+                decoder.decode_to_utf8(&self.pending[..complete], &mut self.buf, true);
+            self.buf.truncate(written);
+            self.pending.drain(..complete);
+        }
+        Ok(&self.buf[self.pos..])
+    }
+
+    /// Returns the length of the pending input which contains only complete characters
+    fn complete_len(&self) -> usize {
+        let len = self.pending.len() & !1;
+        if len < 2 {
+            return len;
+        }
+        let last = [self.pending[len - 2], self.pending[len - 1]];
+        let unit = if self.encoding == UTF_16BE {
+            u16::from_be_bytes(last)
+        } else {
+            u16::from_le_bytes(last)
+        };
+        // A high surrogate should be decoded together with the following low surrogate
+        if (0xD800..0xDC00).contains(&unit) {
+            len - 2
+        } else {
+            len
+        }
+    }
+}
+
+/// The bytes of an event, which is being read: either copied into a buffer, provided
+/// by the user, or borrowed from the input slice without copying.
+///
+/// `R` is the input, from which the bytes are read.
+trait EventBytes<'b, R: BufRead>: Sized {
+    /// Reads the input until `byte` is found, appends the bytes before it to the event
+    /// and consumes `byte`. See `read_until`
+    fn read_until(&mut self, r: &mut R, byte: u8, position: &mut usize) -> Result<(usize, bool)>;
+
+    /// Reads the input until `byte` is found outside of attribute values. See `read_elem_until`
+    fn read_elem_until(
+        &mut self,
+        r: &mut R,
+        byte: u8,
+        position: &mut usize,
+    ) -> Result<(usize, bool)>;
+
+    /// Appends `byte`, which was found and consumed by the last `read_until`, to the event.
+    /// Should not be called, if the input ended before `byte`
+    fn push_delimiter(&mut self, byte: u8);
+
+    /// Returns the bytes of the event, read so far
+    fn bytes(&self) -> &[u8];
+
+    /// Returns the bytes of the event with the lifetime of the event
+    fn into_bytes(self) -> &'b [u8];
+
+    /// Discards the bytes of this event and starts the next event
+    fn next(self, r: &R) -> Self;
+}
+
+/// The bytes of an event, copied into the buffer after `start`
+struct Buffered<'b> {
+    buf: &'b mut Vec<u8>,
+    start: usize,
+}
+
+impl<'b, R: BufRead> EventBytes<'b, R> for Buffered<'b> {
+    #[inline]
+    fn read_until(&mut self, r: &mut R, byte: u8, position: &mut usize) -> Result<(usize, bool)> {
+        read_until(r, byte, self.buf, position)
+    }
+
+    #[inline]
+    fn read_elem_until(
+        &mut self,
+        r: &mut R,
+        byte: u8,
+        position: &mut usize,
+    ) -> Result<(usize, bool)> {
+        read_elem_until(r, byte, self.buf, position)
+    }
+
+    fn push_delimiter(&mut self, byte: u8) {
+        self.buf.push(byte);
+    }
+
+    fn bytes(&self) -> &[u8] {
+        &self.buf[self.start..]
+    }
+
+    fn into_bytes(self) -> &'b [u8] {
+        let buf: &'b Vec<u8> = self.buf;
+        &buf[self.start..]
+    }
+
+    fn next(self, _r: &R) -> Self {
+        // The bytes are kept in the buffer, as the user may expect them there
+        let start = self.buf.len();
+        Buffered {
+            buf: self.buf,
+            start,
+        }
+    }
+}
+
+/// The bytes of an event, which are the first `len` bytes of the `input`. The delimiters
+/// between parts of the event, which are consumed by `read_until`, are the same bytes, that
+/// are pushed back by `push_delimiter`, so the event is always a contiguous part of the input
+struct Borrowed<'b> {
+    input: &'b [u8],
+    len: usize,
+}
+
+impl<'b> EventBytes<'b, Source<&'b [u8]>> for Borrowed<'b> {
+    #[inline]
+    fn read_until(
+        &mut self,
+        r: &mut Source<&'b [u8]>,
+        byte: u8,
+        position: &mut usize,
+    ) -> Result<(usize, bool)> {
+        read_until(r, byte, &mut self.len, position)
+    }
+
+    #[inline]
+    fn read_elem_until(
+        &mut self,
+        r: &mut Source<&'b [u8]>,
+        byte: u8,
+        position: &mut usize,
+    ) -> Result<(usize, bool)> {
+        read_elem_until(r, byte, &mut self.len, position)
+    }
+
+    fn push_delimiter(&mut self, byte: u8) {
+        debug_assert_eq!(self.input[self.len], byte);
+        self.len += 1;
+    }
+
+    fn bytes(&self) -> &[u8] {
+        &self.input[..self.len]
+    }
+
+    fn into_bytes(self) -> &'b [u8] {
+        &self.input[..self.len]
+    }
+
+    fn next(self, r: &Source<&'b [u8]>) -> Self {
+        Borrowed {
+            input: r.inner,
+            len: 0,
+        }
+    }
+}
+
+/// Receives the bytes, read by `read_until` and `read_elem_until`
+trait ReadSink {
+    /// Appends the `bytes`, read from the input
+    fn append(&mut self, bytes: &[u8]);
+}
+
+impl ReadSink for Vec<u8> {
+    #[inline]
+    fn append(&mut self, bytes: &[u8]) {
+        self.extend_from_slice(bytes);
+    }
+}
+
+/// Counts the bytes, which are borrowed from the input slice instead of copying them
+impl ReadSink for usize {
+    #[inline]
+    fn append(&mut self, bytes: &[u8]) {
+        *self += bytes.len();
+    }
 }
 
 /// read until `byte` is found or end of file
-/// return the position of byte
+/// return the number of consumed bytes and whether `byte` was found
 #[inline]
-fn read_until<R: BufRead>(
+fn read_until<R: BufRead, S: ReadSink>(
     r: &mut R,
     byte: u8,
-    buf: &mut Vec<u8>,
+    buf: &mut S,
     position: &mut usize,
-) -> Result<usize> {
+) -> Result<(usize, bool)> {
     let mut read = 0;
     let mut done = false;
     while !done {
@@ -946,12 +2903,12 @@ fn read_until<R: BufRead>(
 
             match memchr::memchr(byte, available) {
                 Some(i) => {
-                    buf.extend_from_slice(&available[..i]);
+                    buf.append(&available[..i]);
                     done = true;
                     i + 1
                 }
                 None => {
-                    buf.extend_from_slice(available);
+                    buf.append(available);
                     available.len()
                 }
             }
@@ -960,7 +2917,7 @@ fn read_until<R: BufRead>(
         read += used;
     }
     *position += read;
-    Ok(read)
+    Ok((read, done))
 }
 
 /// Derived from `read_until`, but modified to handle XML attributes using a minimal state machine.
@@ -974,12 +2931,12 @@ fn read_until<R: BufRead>(
 /// (`Reference` is something like `&quot;`, but we don't care about escaped characters at this
 /// level)
 #[inline]
-fn read_elem_until<R: BufRead>(
+fn read_elem_until<R: BufRead, S: ReadSink>(
     r: &mut R,
     end_byte: u8,
-    buf: &mut Vec<u8>,
+    buf: &mut S,
     position: &mut usize,
-) -> Result<usize> {
+) -> Result<(usize, bool)> {
     #[derive(Clone, Copy)]
     enum State {
         /// The initial state (inside element, but outside of attribute value)
@@ -995,7 +2952,7 @@ fn read_elem_until<R: BufRead>(
     while !done {
         let used = {
             let available = match r.fill_buf() {
-                Ok(n) if n.is_empty() => return Ok(read),
+                Ok(n) if n.is_empty() => break,
                 Ok(n) => n,
                 Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
                 Err(e) => {
@@ -1012,7 +2969,7 @@ fn read_elem_until<R: BufRead>(
                         state = match (state, available[i]) {
                             (State::Elem, b) if b == end_byte => {
                                 // only allowed to match `end_byte` while we are in state `Elem`
-                                buf.extend_from_slice(&available[..i]);
+                                buf.append(&available[..i]);
                                 done = true;
                                 used = i + 1;
                                 break;
@@ -1028,7 +2985,7 @@ fn read_elem_until<R: BufRead>(
                         };
                     }
                     None => {
-                        buf.extend_from_slice(available);
+                        buf.append(available);
                         used = available.len();
                         break;
                     }
@@ -1040,7 +2997,89 @@ fn read_elem_until<R: BufRead>(
         read += used;
     }
     *position += read;
-    Ok(read)
+    Ok((read, done))
+}
+
+/// Adds quotes around attribute values in `attributes`, which are not quoted. Such values end
+/// at the first whitespace, quotes inside them are escaped.
+fn quote_attribute_values(attributes: &[u8]) -> Cow<'_, [u8]> {
+    let mut quoted: Option<Vec<u8>> = None;
+    let mut pos = 0;
+    let mut i = 0;
+    while i < attributes.len() {
+        match attributes[i] {
+            q @ b'"' | q @ b'\'' => {
+                i = match memchr::memchr(q, &attributes[i + 1..]) {
+                    Some(end) => i + end + 2,
+                    None => attributes.len(),
+                };
+            }
+            b'=' => {
+                let start = i
+                    + 1
+                    + attributes[i + 1..]
+                        .iter()
+                        .take_while(|&&b| is_whitespace(b))
+                        .count();
+                i = start;
+                match attributes.get(start) {
+                    None | Some(b'"') | Some(b'\'') => continue,
+                    _ => {}
+                }
+                let end = attributes[start..]
+                    .iter()
+                    .position(|&b| is_whitespace(b))
+                    .map_or(attributes.len(), |p| start + p);
+                let out = quoted.get_or_insert_with(|| Vec::with_capacity(attributes.len() + 8));
+                out.extend_from_slice(&attributes[pos..start]);
+                out.push(b'"');
+                for &b in &attributes[start..end] {
+                    match b {
+                        b'"' => out.extend_from_slice(b"&quot;"),
+                        _ => out.push(b),
+                    }
+                }
+                out.push(b'"');
+                pos = end;
+                i = end;
+            }
+            _ => i += 1,
+        }
+    }
+    match quoted {
+        Some(mut out) => {
+            out.extend_from_slice(&attributes[pos..]);
+            Cow::Owned(out)
+        }
+        None => Cow::Borrowed(attributes),
+    }
+}
+
+/// Kind of a marked section `<![keyword[...]]>`
+enum MarkedSection {
+    /// `<![CDATA[...]]>`
+    CData,
+    /// `<![INCLUDE[...]]>`, which content is read as usual
+    Include,
+    /// `<![IGNORE[...]]>`, which is skipped
+    Ignore,
+}
+
+impl MarkedSection {
+    /// Returns the kind of a section with the `keyword` between `<![` and `[`
+    fn from_keyword(keyword: &[u8]) -> Option<MarkedSection> {
+        if keyword == b"CDATA" {
+            return Some(MarkedSection::CData);
+        }
+        // Whitespaces are allowed around keywords other than `CDATA`
+        let start = keyword.iter().position(|&b| !is_whitespace(b))?;
+        let end = keyword.iter().rposition(|&b| !is_whitespace(b))? + 1;
+        match &keyword[start..end] {
+            b"INCLUDE" => Some(MarkedSection::Include),
+            b"IGNORE" => Some(MarkedSection::Ignore),
+            _ => None,
+        }
+    }
 }
 
 /// A function to check whether the byte is a whitespace (blank, new line, carriage return or tab)
@@ -1089,14 +3128,27 @@ impl Namespace {
         }
     }
 
-    /// Check if the namespace matches the potentially qualified name
+    /// Gets the prefix slice out of namespace buffer
+    ///
+    /// Returns `None` for the default namespace declaration
     #[inline]
-    fn is_match(&self, ns_buffer: &[u8], qname: &[u8]) -> bool {
+    fn prefix<'b>(&self, ns_buffer: &'b [u8]) -> Option<Prefix<'b>> {
         if self.prefix_len == 0 {
-            !qname.contains(&b':')
+            None
         } else {
-            qname.get(self.prefix_len).map_or(false, |n| *n == b':')
-                && qname.starts_with(&ns_buffer[self.start..self.start + self.prefix_len])
+            Some(Prefix(&ns_buffer[self.start..self.start + self.prefix_len]))
+        }
+    }
+
+    /// Check if the namespace matches the `prefix` of a potentially qualified name
+    #[inline]
+    fn is_match(&self, ns_buffer: &[u8], prefix: Option<Prefix>) -> bool {
+        match prefix {
+            None => self.prefix_len == 0,
+            Some(prefix) => {
+                self.prefix_len > 0
+                    && *prefix == ns_buffer[self.start..self.start + self.prefix_len]
+            }
         }
     }
 }
@@ -1121,12 +3173,13 @@ impl NamespaceBufferIndex {
     #[inline]
     fn find_namespace_value<'a, 'b, 'c>(
         &'a self,
-        element_name: &'b [u8],
+        element_name: QName<'b>,
         buffer: &'c [u8],
     ) -> Option<&'c [u8]> {
+        let prefix = element_name.prefix();
         self.slices
             .iter()
-            .rfind(|n| n.is_match(buffer, element_name))
+            .rfind(|n| n.is_match(buffer, prefix))
             .and_then(|n| n.opt_value(buffer))
     }
 
@@ -1161,31 +3214,29 @@ impl NamespaceBufferIndex {
         // (default namespace) attribute.
         for a in e.attributes().with_checks(false) {
             if let Ok(Attribute { key: k, value: v }) = a {
-                if k.starts_with(b"xmlns") {
-                    match k.get(5) {
-                        None => {
-                            let start = buffer.len();
-                            buffer.extend_from_slice(&*v);
-                            self.slices.push(Namespace {
-                                start,
-                                prefix_len: 0,
-                                value_len: v.len(),
-                                level,
-                            });
-                        }
-                        Some(&b':') => {
-                            let start = buffer.len();
-                            buffer.extend_from_slice(&k[6..]);
-                            buffer.extend_from_slice(&*v);
-                            self.slices.push(Namespace {
-                                start,
-                                prefix_len: k.len() - 6,
-                                value_len: v.len(),
-                                level,
-                            });
-                        }
-                        _ => break,
+                match k.as_namespace_binding() {
+                    Some(PrefixDeclaration::Default) => {
+                        let start = buffer.len();
+                        buffer.extend_from_slice(&*v);
+                        self.slices.push(Namespace {
+                            start,
+                            prefix_len: 0,
+                            value_len: v.len(),
+                            level,
+                        });
                     }
+                    Some(PrefixDeclaration::Named(prefix)) => {
+                        let start = buffer.len();
+                        buffer.extend_from_slice(prefix);
+                        buffer.extend_from_slice(&*v);
+                        self.slices.push(Namespace {
+                            start,
+                            prefix_len: prefix.len(),
+                            value_len: v.len(),
+                            level,
+                        });
+                    }
+                    None => {}
                 }
             } else {
                 break;
@@ -1193,6 +3244,25 @@ impl NamespaceBufferIndex {
         }
     }
 
+    /// Iterates over the bindings, which are not shadowed by the later declarations
+    /// of the same prefix, from the most recent one
+    fn in_scope<'a>(
+        &'a self,
+        buffer: &'a [u8],
+    ) -> impl Iterator<Item = (Option<Prefix<'a>>, &'a [u8])> + 'a {
+        self.slices
+            .iter()
+            .enumerate()
+            .rev()
+            .filter(move |&(i, n)| {
+                let prefix = n.prefix(buffer);
+                !self.slices[i + 1..]
+                    .iter()
+                    .any(|later| later.is_match(buffer, prefix))
+            })
+            .filter_map(move |(_, n)| n.opt_value(buffer).map(|value| (n.prefix(buffer), value)))
+    }
+
     /// Resolves a potentially qualified **attribute name** into (namespace name, local name).
     ///
     /// *Qualified* attribute names have the form `prefix:local-name` where the`prefix` is defined
@@ -1203,21 +3273,19 @@ impl NamespaceBufferIndex {
     #[inline]
     fn resolve_namespace<'a, 'b, 'c>(
         &'a self,
-        qname: &'b [u8],
+        qname: QName<'b>,
         buffer: &'c [u8],
         use_default: bool,
-    ) -> (Option<&'c [u8]>, &'b [u8]) {
+    ) -> (Option<&'c [u8]>, LocalName<'b>) {
+        let (local_name, prefix) = qname.decompose();
         self.slices
             .iter()
-            .rfind(|n| n.is_match(buffer, qname))
-            .map_or((None, qname), |n| {
-                let len = n.prefix_len;
-                if len > 0 {
-                    (n.opt_value(buffer), &qname[len + 1..])
-                } else if use_default {
-                    (n.opt_value(buffer), qname)
+            .rfind(|n| n.is_match(buffer, prefix))
+            .map_or((None, LocalName(qname.into_inner())), |n| {
+                if prefix.is_some() || use_default {
+                    (n.opt_value(buffer), local_name)
                 } else {
-                    (None, qname)
+                    (None, local_name)
                 }
             })
     }
@@ -1236,11 +3304,18 @@ pub struct Decoder {
 }
 
 impl Decoder {
+    /// Decodes a UTF-8 slice regardless of XML declaration.
+    ///
+    /// Returns an error in case of malformed sequences in the `bytes`.
     #[cfg(not(feature = "encoding"))]
     pub fn decode<'c>(&self, bytes: &'c [u8]) -> Result<&'c str> {
         from_utf8(bytes).map_err(Error::Utf8)
     }
 
+    /// Decodes a slice using the encoding specified in the XML declaration.
+    ///
+    /// Decoding is performed in the lossy mode: malformed sequences are replaced
+    /// by `U+FFFD REPLACEMENT CHARACTER`.
     #[cfg(feature = "encoding")]
     pub fn decode<'c>(&self, bytes: &'c [u8]) -> Cow<'c, str> {
         self.encoding.decode(bytes).0
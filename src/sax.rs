@@ -0,0 +1,398 @@
+//! A callback interface to the [`Reader`], similar to SAX parsers of other languages.
+//!
+//! Implement [`Handler`] and pass it to [`run`] or, to get the namespaces of the names,
+//! to [`run_with_namespaces`]. The driver reads events one by one, reusing one buffer, and
+//! calls the handler for each of them. Every callback returns a [`ControlFlow`], so
+//! the handler can stop the reading at any event.
+//!
+//! See `examples/sax_histogram.rs` for a complete example.
+//!
+//! [`Reader`]: ../struct.Reader.html
+//! [`Handler`]: trait.Handler.html
+//! [`run`]: fn.run.html
+//! [`run_with_namespaces`]: fn.run_with_namespaces.html
+//! [`ControlFlow`]: https://doc.rust-lang.org/std/ops/enum.ControlFlow.html
+
+use std::borrow::Cow;
+use std::io::BufRead;
+use std::ops::ControlFlow;
+
+use errors::Result;
+use events::{BytesStart, Event};
+use name::QName;
+use reader::Reader;
+
+/// A decoded name of an element or an attribute
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Name<'a> {
+    /// The name as written, with the namespace prefix if any
+    pub qname: Cow<'a, str>,
+    /// The name without the namespace prefix
+    pub local_name: Cow<'a, str>,
+    /// The namespace of the name, resolved only by [`run_with_namespaces`]
+    ///
+    /// [`run_with_namespaces`]: fn.run_with_namespaces.html
+    pub namespace: Option<Cow<'a, str>>,
+}
+
+/// A decoded attribute with the unescaped value
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Attribute<'a> {
+    /// The name of the attribute
+    pub name: Name<'a>,
+    /// The unescaped and decoded value of the attribute
+    pub value: Cow<'a, str>,
+}
+
+/// Callbacks, called by [`run`] for the events of a document.
+///
+/// All methods do nothing by default. Return `ControlFlow::Break(())` to stop reading,
+/// then the reader is positioned right after the event, which has been handled.
+///
+/// The XML declaration and the doctype are not reported.
+///
+/// [`run`]: fn.run.html
+pub trait Handler {
+    /// Called for a start tag and for an empty element `<name/>`, which is followed by
+    /// [`end_element`]
+    ///
+    /// [`end_element`]: #method.end_element
+    fn start_element(&mut self, name: &Name, attributes: &[Attribute]) -> ControlFlow<()> {
+        let _ = (name, attributes);
+        ControlFlow::Continue(())
+    }
+
+    /// Called for an end tag and after [`start_element`] for an empty element
+    ///
+    /// [`start_element`]: #method.start_element
+    fn end_element(&mut self, name: &Name) -> ControlFlow<()> {
+        let _ = name;
+        ControlFlow::Continue(())
+    }
+
+    /// Called for unescaped and decoded non-empty texts and CDATA sections. Texts are
+    /// trimmed if it is configured in the reader
+    fn characters(&mut self, text: &str) -> ControlFlow<()> {
+        let _ = text;
+        ControlFlow::Continue(())
+    }
+
+    /// Called for the decoded content of a comment
+    fn comment(&mut self, text: &str) -> ControlFlow<()> {
+        let _ = text;
+        ControlFlow::Continue(())
+    }
+
+    /// Called for a processing instruction `<?target data?>`
+    fn pi(&mut self, target: &str, data: &str) -> ControlFlow<()> {
+        let _ = (target, data);
+        ControlFlow::Continue(())
+    }
+}
+
+/// Reads events from the `reader` and calls the `handler` for each of them until the end
+/// of the document, the first error or until the handler stops the reading.
+///
+/// Returns `ControlFlow::Break(())` if the handler has stopped the reading, and
+/// `ControlFlow::Continue(())` if the whole document has been read.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate quick_xml;
+/// # fn main() {
+/// use quick_xml::sax::{run, Attribute, Handler, Name};
+/// use quick_xml::Reader;
+/// use std::ops::ControlFlow;
+///
+/// /// Finds the `id` of the first `<item>`
+/// struct FirstId(Option<String>);
+///
+/// impl Handler for FirstId {
+///     fn start_element(&mut self, name: &Name, attributes: &[Attribute]) -> ControlFlow<()> {
+///         if name.qname == "item" {
+///             let id = attributes.iter().find(|a| a.name.qname == "id");
+///             self.0 = id.map(|a| a.value.to_string());
+///             return ControlFlow::Break(());
+///         }
+///         ControlFlow::Continue(())
+///     }
+/// }
+///
+/// let mut reader = Reader::from_str(r#"<items><item id="1"/><item id="2"/></items>"#);
+/// let mut handler = FirstId(None);
+/// assert_eq!(run(&mut reader, &mut handler).unwrap(), ControlFlow::Break(()));
+/// assert_eq!(handler.0.as_deref(), Some("1"));
+/// # }
+/// ```
+pub fn run<B: BufRead, H: Handler>(
+    reader: &mut Reader<B>,
+    handler: &mut H,
+) -> Result<ControlFlow<()>> {
+    drive(reader, handler, false)
+}
+
+/// Same as [`run`], but also resolves the namespaces of the names of elements and
+/// attributes with [`Reader::read_namespaced_event`].
+///
+/// [`run`]: fn.run.html
+/// [`Reader::read_namespaced_event`]: ../struct.Reader.html#method.read_namespaced_event
+pub fn run_with_namespaces<B: BufRead, H: Handler>(
+    reader: &mut Reader<B>,
+    handler: &mut H,
+) -> Result<ControlFlow<()>> {
+    drive(reader, handler, true)
+}
+
+fn drive<B: BufRead, H: Handler>(
+    reader: &mut Reader<B>,
+    handler: &mut H,
+    namespaces: bool,
+) -> Result<ControlFlow<()>> {
+    let mut buf = Vec::new();
+    let mut ns_buf = Vec::new();
+    loop {
+        buf.clear();
+        let (namespace, event) = if namespaces {
+            let (namespace, event) = reader.read_namespaced_event(&mut buf, &mut ns_buf)?;
+            (namespace.map(|ns| ns.to_vec()), event)
+        } else {
+            (None, reader.read_event(&mut buf)?)
+        };
+        let namespace = match namespace {
+            Some(ref ns) => Some(reader.decode_cow(ns)?),
+            None => None,
+        };
+        let flow = match event {
+            Event::Start(ref e) | Event::Empty(ref e) => {
+                let name = name(reader, e.name(), namespace)?;
+                let attributes =
+                    attributes(reader, e, if namespaces { Some(&ns_buf) } else { None })?;
+                match handler.start_element(&name, &attributes) {
+                    ControlFlow::Continue(()) if matches!(event, Event::Empty(_)) => {
+                        handler.end_element(&name)
+                    }
+                    flow => flow,
+                }
+            }
+            Event::End(ref e) => handler.end_element(&name(reader, e.name(), namespace)?),
+            Event::Text(ref e) | Event::CData(ref e) => {
+                let text = e.unescape_and_decode(reader)?;
+                if text.is_empty() {
+                    continue;
+                }
+                handler.characters(&text)
+            }
+            Event::Comment(ref e) => handler.comment(&reader.decode_cow(e)?),
+            Event::PI(ref e) => {
+                let content = reader.decode_cow(e)?;
+                let (target, data) = match content.find(|c: char| c.is_ascii_whitespace()) {
+                    Some(i) => (&content[..i], content[i..].trim_start()),
+                    None => (&*content, ""),
+                };
+                handler.pi(target, data)
+            }
+            Event::Decl(_) | Event::DocType(_) => continue,
+            Event::Eof => return Ok(ControlFlow::Continue(())),
+        };
+        if flow.is_break() {
+            return Ok(flow);
+        }
+    }
+}
+
+fn name<'a, B: BufRead>(
+    reader: &Reader<B>,
+    qname: QName<'a>,
+    namespace: Option<Cow<'a, str>>,
+) -> Result<Name<'a>> {
+    Ok(Name {
+        qname: reader.decode_cow(qname.into_inner())?,
+        local_name: reader.decode_cow(qname.local_name().into_inner())?,
+        namespace,
+    })
+}
+
+fn attributes<'a, B: BufRead>(
+    reader: &Reader<B>,
+    e: &'a BytesStart,
+    ns_buf: Option<&[u8]>,
+) -> Result<Vec<Attribute<'a>>> {
+    let mut attributes = Vec::new();
+    for attr in e.attributes() {
+        let attr = attr?;
+        let namespace = match ns_buf {
+            Some(ns_buf) => match reader.attribute_namespace(attr.key, ns_buf).0 {
+                Some(ns) => Some(Cow::Owned(reader.decode_cow(ns)?.into_owned())),
+                None => None,
+            },
+            None => None,
+        };
+        attributes.push(Attribute {
+            value: Cow::Owned(attr.unescape_and_decode_value(reader)?),
+            name: name(reader, attr.key, namespace)?,
+        });
+    }
+    Ok(attributes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Records all callbacks and stops after `stop_after` of them
+    #[derive(Default)]
+    struct Recorder {
+        calls: Vec<String>,
+        stop_after: Option<usize>,
+    }
+
+    impl Recorder {
+        fn record(&mut self, call: String) -> ControlFlow<()> {
+            self.calls.push(call);
+            match self.stop_after {
+                Some(n) if self.calls.len() >= n => ControlFlow::Break(()),
+                _ => ControlFlow::Continue(()),
+            }
+        }
+    }
+
+    fn format_name(name: &Name) -> String {
+        match name.namespace {
+            Some(ref ns) => format!("{{{}}}{}", ns, name.local_name),
+            None => name.qname.to_string(),
+        }
+    }
+
+    impl Handler for Recorder {
+        fn start_element(&mut self, name: &Name, attributes: &[Attribute]) -> ControlFlow<()> {
+            let mut call = format!("start {}", format_name(name));
+            for attr in attributes {
+                call.push_str(&format!(" {}={:?}", format_name(&attr.name), attr.value));
+            }
+            self.record(call)
+        }
+
+        fn end_element(&mut self, name: &Name) -> ControlFlow<()> {
+            self.record(format!("end {}", format_name(name)))
+        }
+
+        fn characters(&mut self, text: &str) -> ControlFlow<()> {
+            self.record(format!("text {:?}", text))
+        }
+
+        fn comment(&mut self, text: &str) -> ControlFlow<()> {
+            self.record(format!("comment {:?}", text))
+        }
+
+        fn pi(&mut self, target: &str, data: &str) -> ControlFlow<()> {
+            self.record(format!("pi {} {:?}", target, data))
+        }
+    }
+
+    const XML: &str = r#"<?xml version="1.0"?>
+<!DOCTYPE root>
+<root xmlns="urn:a" xmlns:b="urn:b">
+    <!-- comment -->
+    <?target some data?>
+    <b:item b:id="1" name="x &amp; y">text <![CDATA[<cdata>]]></b:item>
+    <empty/>
+</root>"#;
+
+    #[test]
+    fn callbacks() {
+        let mut reader = Reader::from_str(XML);
+        reader.trim_text(true);
+        let mut handler = Recorder::default();
+        assert_eq!(
+            run(&mut reader, &mut handler).unwrap(),
+            ControlFlow::Continue(())
+        );
+        assert_eq!(
+            handler.calls,
+            vec![
+                r#"start root xmlns="urn:a" xmlns:b="urn:b""#,
+                r#"comment " comment ""#,
+                r#"pi target "some data""#,
+                r#"start b:item b:id="1" name="x & y""#,
+                r#"text "text""#,
+                r#"text "<cdata>""#,
+                "end b:item",
+                "start empty",
+                "end empty",
+                "end root",
+            ]
+        );
+    }
+
+    #[test]
+    fn namespaces() {
+        let mut reader = Reader::from_str(XML);
+        reader.trim_text(true);
+        let mut handler = Recorder::default();
+        assert_eq!(
+            run_with_namespaces(&mut reader, &mut handler).unwrap(),
+            ControlFlow::Continue(())
+        );
+        let elements: Vec<_> = handler
+            .calls
+            .iter()
+            .filter(|call| call.starts_with("start") || call.starts_with("end"))
+            .collect();
+        assert_eq!(
+            elements,
+            vec![
+                r#"start {urn:a}root xmlns="urn:a" xmlns:b="urn:b""#,
+                r#"start {urn:b}item {urn:b}id="1" name="x & y""#,
+                "end {urn:b}item",
+                "start {urn:a}empty",
+                "end {urn:a}empty",
+                "end {urn:a}root",
+            ]
+        );
+    }
+
+    #[test]
+    fn early_stop() {
+        // The last call and the rest of the input after stopping at each call
+        let expected = [
+            (r#"start root xmlns="urn:a" xmlns:b="urn:b""#, "\n    <!--"),
+            (r#"comment " comment ""#, "\n    <?target"),
+            (r#"pi target "some data""#, "\n    <b:item"),
+            (r#"start b:item b:id="1" name="x & y""#, "text <!["),
+            (r#"text "text""#, "<![CDATA["),
+            (r#"text "<cdata>""#, "</b:item>"),
+            ("end b:item", "\n    <empty/>"),
+            ("start empty", "\n</root>"),
+            ("end empty", "\n</root>"),
+            ("end root", ""),
+        ];
+        for (n, &(call, rest)) in expected.iter().enumerate() {
+            let mut reader = Reader::from_str(XML);
+            reader.trim_text(true);
+            let mut handler = Recorder {
+                calls: Vec::new(),
+                stop_after: Some(n + 1),
+            };
+            assert_eq!(
+                run(&mut reader, &mut handler).unwrap(),
+                ControlFlow::Break(())
+            );
+            assert_eq!(handler.calls.last().unwrap(), call);
+            assert_eq!(
+                &XML[reader.buffer_position()..][..rest.len()],
+                rest,
+                "after {}",
+                call
+            );
+        }
+    }
+
+    #[test]
+    fn errors() {
+        let mut reader = Reader::from_str("<a><b></a>");
+        let mut handler = Recorder::default();
+        assert!(run(&mut reader, &mut handler).is_err());
+        assert_eq!(handler.calls, vec!["start a", "start b"]);
+    }
+}
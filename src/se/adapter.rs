@@ -0,0 +1,515 @@
+use crate::errors::serialize::DeError;
+use serde::de::value::{MapDeserializer, SeqDeserializer};
+use serde::de::{self, DeserializeOwned, IntoDeserializer, Visitor};
+use serde::ser::{self, Impossible, Serialize};
+use std::any::type_name;
+
+/// Functions, registered with `Serializer::register_adapter`, which write values of
+/// concrete types as text.
+///
+/// Serde passes values to a serializer as references of generic types, which are not
+/// `'static`, so their `TypeId` is not available. Types are recognized by the full path,
+/// which `std::any::type_name` returns, with the generic arguments, so types, which only
+/// have the same name in serde, are different. To call the function, a value of the type
+/// is captured as it is serialized and the type is deserialized back from it, because
+/// the value cannot be cast to the type without `unsafe` code.
+#[derive(Default)]
+pub(super) struct Adapters(Vec<Adapter>);
+
+/// A function, registered for the type with the full path `name`
+struct Adapter {
+    name: &'static str,
+    /// Deserializes the type from the captured value and calls the function
+    adapt: Box<dyn Fn(Content) -> Result<String, DeError> + Send + Sync>,
+}
+
+impl Adapters {
+    /// Registers `function` for values of type `T`, replacing the previous function of
+    /// the type
+    pub(super) fn register<T: DeserializeOwned + 'static>(&mut self, function: fn(&T) -> String) {
+        let name = type_name::<T>();
+        let adapter = Adapter {
+            name,
+            adapt: Box::new(move |content| T::deserialize(content).map(|value| function(&value))),
+        };
+        match self.0.iter_mut().find(|a| a.name == name) {
+            Some(a) => *a = adapter,
+            None => self.0.push(adapter),
+        }
+    }
+
+    /// Returns the adapter of values of type `T` or of references to them
+    fn find<T: ?Sized>(&self) -> Option<&Adapter> {
+        if self.0.is_empty() {
+            return None;
+        }
+        let mut name = type_name::<T>();
+        while let Some(referenced) = name.strip_prefix('&') {
+            name = referenced.strip_prefix("mut ").unwrap_or(referenced);
+        }
+        self.0.iter().find(|a| a.name == name)
+    }
+
+    /// Whether an adapter is registered for the type of `value`
+    pub(super) fn is_adapted<T: ?Sized>(&self, _value: &T) -> bool {
+        self.find::<T>().is_some()
+    }
+
+    /// Returns the text of the `value`, if an adapter is registered for its type
+    pub(super) fn adapt<T: ?Sized + Serialize>(
+        &self,
+        value: &T,
+    ) -> Result<Option<String>, DeError> {
+        match self.find::<T>() {
+            Some(adapter) => (adapter.adapt)(value.serialize(Capture)?).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// A value, captured as it is serialized, to deserialize the registered type from it
+#[derive(Debug, PartialEq)]
+enum Content {
+    Bool(bool),
+    I64(i64),
+    U64(u64),
+    F64(f64),
+    Char(char),
+    String(String),
+    Bytes(Vec<u8>),
+    None,
+    Some(Box<Content>),
+    Unit,
+    /// A newtype struct
+    Newtype(Box<Content>),
+    /// A sequence, a tuple or a tuple struct
+    Seq(Vec<Content>),
+    /// A map or a struct
+    Map(Vec<(Content, Content)>),
+}
+
+/// Captures a value as `Content`
+struct Capture;
+
+/// Captures the items of a sequence or the fields of a map or a struct
+#[derive(Default)]
+struct Compound {
+    items: Vec<Content>,
+    entries: Vec<(Content, Content)>,
+}
+
+impl ser::Serializer for Capture {
+    type Ok = Content;
+    type Error = DeError;
+
+    type SerializeSeq = Compound;
+    type SerializeTuple = Compound;
+    type SerializeTupleStruct = Compound;
+    type SerializeTupleVariant = Impossible<Content, DeError>;
+    type SerializeMap = Compound;
+    type SerializeStruct = Compound;
+    type SerializeStructVariant = Impossible<Content, DeError>;
+
+    fn serialize_bool(self, v: bool) -> Result<Content, DeError> {
+        Ok(Content::Bool(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Content, DeError> {
+        Ok(Content::I64(v.into()))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Content, DeError> {
+        Ok(Content::I64(v.into()))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Content, DeError> {
+        Ok(Content::I64(v.into()))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Content, DeError> {
+        Ok(Content::I64(v))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Content, DeError> {
+        Ok(Content::U64(v.into()))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Content, DeError> {
+        Ok(Content::U64(v.into()))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Content, DeError> {
+        Ok(Content::U64(v.into()))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Content, DeError> {
+        Ok(Content::U64(v))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Content, DeError> {
+        Ok(Content::F64(v.into()))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Content, DeError> {
+        Ok(Content::F64(v))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Content, DeError> {
+        Ok(Content::Char(v))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Content, DeError> {
+        Ok(Content::String(v.to_string()))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Content, DeError> {
+        Ok(Content::Bytes(v.to_vec()))
+    }
+
+    fn serialize_none(self) -> Result<Content, DeError> {
+        Ok(Content::None)
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<Content, DeError> {
+        Ok(Content::Some(Box::new(value.serialize(Capture)?)))
+    }
+
+    fn serialize_unit(self) -> Result<Content, DeError> {
+        Ok(Content::Unit)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Content, DeError> {
+        Ok(Content::Unit)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Content, DeError> {
+        Ok(Content::String(variant.to_string()))
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Content, DeError> {
+        Ok(Content::Newtype(Box::new(value.serialize(Capture)?)))
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Content, DeError> {
+        Err(DeError::Unsupported(
+            "adapters do not support enum variants with data",
+        ))
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Compound, DeError> {
+        Ok(Compound::default())
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Compound, DeError> {
+        Ok(Compound::default())
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> Result<Compound, DeError> {
+        Ok(Compound::default())
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, DeError> {
+        Err(DeError::Unsupported(
+            "adapters do not support enum variants with data",
+        ))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Compound, DeError> {
+        Ok(Compound::default())
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Compound, DeError> {
+        Ok(Compound::default())
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, DeError> {
+        Err(DeError::Unsupported(
+            "adapters do not support enum variants with data",
+        ))
+    }
+}
+
+impl ser::SerializeSeq for Compound {
+    type Ok = Content;
+    type Error = DeError;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), DeError> {
+        self.items.push(value.serialize(Capture)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Content, DeError> {
+        Ok(Content::Seq(self.items))
+    }
+}
+
+impl ser::SerializeTuple for Compound {
+    type Ok = Content;
+    type Error = DeError;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), DeError> {
+        <Self as ser::SerializeSeq>::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Content, DeError> {
+        <Self as ser::SerializeSeq>::end(self)
+    }
+}
+
+impl ser::SerializeTupleStruct for Compound {
+    type Ok = Content;
+    type Error = DeError;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), DeError> {
+        <Self as ser::SerializeSeq>::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Content, DeError> {
+        <Self as ser::SerializeSeq>::end(self)
+    }
+}
+
+impl ser::SerializeMap for Compound {
+    type Ok = Content;
+    type Error = DeError;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<(), DeError> {
+        self.items.push(key.serialize(Capture)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), DeError> {
+        let key = self.items.pop().unwrap_or(Content::Unit);
+        self.entries.push((key, value.serialize(Capture)?));
+        Ok(())
+    }
+
+    fn end(self) -> Result<Content, DeError> {
+        Ok(Content::Map(self.entries))
+    }
+}
+
+impl ser::SerializeStruct for Compound {
+    type Ok = Content;
+    type Error = DeError;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), DeError> {
+        let key = Content::String(key.to_string());
+        self.entries.push((key, value.serialize(Capture)?));
+        Ok(())
+    }
+
+    fn end(self) -> Result<Content, DeError> {
+        <Self as ser::SerializeMap>::end(self)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+impl<'de> IntoDeserializer<'de, DeError> for Content {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+impl<'de> de::Deserializer<'de> for Content {
+    type Error = DeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        match self {
+            Content::Bool(v) => visitor.visit_bool(v),
+            Content::I64(v) => visitor.visit_i64(v),
+            Content::U64(v) => visitor.visit_u64(v),
+            Content::F64(v) => visitor.visit_f64(v),
+            Content::Char(v) => visitor.visit_char(v),
+            Content::String(v) => visitor.visit_string(v),
+            Content::Bytes(v) => visitor.visit_byte_buf(v),
+            Content::None => visitor.visit_none(),
+            Content::Some(v) => visitor.visit_some(*v),
+            Content::Unit => visitor.visit_unit(),
+            Content::Newtype(v) => visitor.visit_newtype_struct(*v),
+            Content::Seq(items) => {
+                let mut seq = SeqDeserializer::new(items.into_iter());
+                let value = visitor.visit_seq(&mut seq)?;
+                seq.end()?;
+                Ok(value)
+            }
+            Content::Map(entries) => {
+                let mut map = MapDeserializer::new(entries.into_iter());
+                let value = visitor.visit_map(&mut map)?;
+                map.end()?;
+                Ok(value)
+            }
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        match self {
+            Content::None | Content::Unit => visitor.visit_none(),
+            Content::Some(v) => visitor.visit_some(*v),
+            v => visitor.visit_some(v),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, DeError> {
+        match self {
+            Content::Newtype(v) => visitor.visit_newtype_struct(*v),
+            v => visitor.visit_newtype_struct(v),
+        }
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, DeError> {
+        match self {
+            Content::String(variant) => visitor.visit_enum(variant.into_deserializer()),
+            _ => Err(de::Error::custom(
+                "adapters support only unit enum variants",
+            )),
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct
+        identifier ignored_any
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::{Deserialize, Serialize};
+    use std::time::Duration;
+
+    #[derive(Serialize, Deserialize)]
+    struct Millis(u64);
+
+    #[derive(Serialize, Deserialize)]
+    struct Span {
+        start: u32,
+        end: Option<u32>,
+        unit: Unit,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    enum Unit {
+        Seconds,
+    }
+
+    #[test]
+    fn adapt() {
+        let mut adapters = Adapters::default();
+        assert_eq!(adapters.adapt(&Millis(1)).unwrap(), None);
+
+        adapters.register(|value: &Millis| format!("{}ms", value.0));
+        assert!(adapters.is_adapted(&Millis(15)));
+        assert!(!adapters.is_adapted(&15u64));
+        assert_eq!(adapters.adapt(&Millis(15)).unwrap(), Some("15ms".into()));
+        assert_eq!(adapters.adapt(&&Millis(15)).unwrap(), Some("15ms".into()));
+        assert_eq!(adapters.adapt(&15u64).unwrap(), None);
+        assert_eq!(adapters.adapt(&Some(Millis(15))).unwrap(), None);
+
+        adapters.register(|value: &Millis| format!("{}", value.0));
+        assert_eq!(adapters.adapt(&Millis(15)).unwrap(), Some("15".into()));
+    }
+
+    #[test]
+    fn adapt_structs() {
+        let mut adapters = Adapters::default();
+        adapters.register(|value: &Duration| {
+            format!("{}.{:03}", value.as_secs(), value.subsec_millis())
+        });
+        adapters.register(|value: &Span| {
+            format!(
+                "{}..{:?} {}",
+                value.start,
+                value.end,
+                match value.unit {
+                    Unit::Seconds => "s",
+                }
+            )
+        });
+
+        let duration = Duration::from_millis(1500);
+        assert_eq!(adapters.adapt(&duration).unwrap(), Some("1.500".into()));
+        let span = Span {
+            start: 1,
+            end: Some(2),
+            unit: Unit::Seconds,
+        };
+        assert_eq!(adapters.adapt(&span).unwrap(), Some("1..Some(2) s".into()));
+    }
+
+    #[test]
+    fn adapt_primitives() {
+        let mut adapters = Adapters::default();
+        adapters.register(|value: &u64| format!("{:#x}", value));
+        assert_eq!(adapters.adapt(&255u64).unwrap(), Some("0xff".into()));
+        assert_eq!(adapters.adapt(&255u32).unwrap(), None);
+    }
+
+    /// Types with the same name in serde are different types
+    #[test]
+    fn same_serde_names() {
+        #[derive(Serialize, Deserialize)]
+        struct Duration {
+            secs: u64,
+            nanos: u32,
+        }
+
+        let mut adapters = Adapters::default();
+        adapters.register(|value: &Duration| format!("{}s", value.secs));
+        let std = std::time::Duration::from_secs(5);
+        assert_eq!(adapters.adapt(&std).unwrap(), None);
+        let own = Duration { secs: 5, nanos: 0 };
+        assert_eq!(adapters.adapt(&own).unwrap(), Some("5s".into()));
+
+        let mut adapters = Adapters::default();
+        adapters.register(|value: &std::time::Duration| format!("{}ms", value.as_millis()));
+        assert_eq!(adapters.adapt(&own).unwrap(), None);
+        assert_eq!(adapters.adapt(&std).unwrap(), Some("5000ms".into()));
+    }
+}
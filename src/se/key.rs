@@ -0,0 +1,209 @@
+use crate::{errors::serialize::DeError, se::Config};
+use serde::ser::{self, Impossible, Serialize};
+use serde::serde_if_integer128;
+use std::io::Write;
+
+/// A serializer of keys of map entries, which writes strings and primitive values directly
+/// into a buffer, as the `Serializer` writes them into a text. Newtypes are written as
+/// their values and unit variants as their names.
+///
+/// Other values, such as structs or sequences, cannot be names of elements, so
+/// `DeError::Unsupported` is returned for them.
+pub(super) struct KeySerializer<'a, 'r> {
+    pub(super) out: &'a mut Vec<u8>,
+    pub(super) config: &'a Config<'r>,
+    /// Whether strings are escaped as in a text, or written as is
+    pub(super) escape: bool,
+}
+
+/// The error for keys, which are not strings or primitive values
+fn unsupported() -> DeError {
+    DeError::Unsupported("map key must be a string-like value")
+}
+
+impl<'a, 'r> KeySerializer<'a, 'r> {
+    fn write_primitive<P: std::fmt::Display>(self, value: P) -> Result<(), DeError> {
+        write!(self.out, "{}", value).map_err(crate::errors::Error::Io)?;
+        Ok(())
+    }
+
+    fn write_str(self, value: &str) -> Result<(), DeError> {
+        if self.escape {
+            self.config.escape_into(value.as_bytes(), self.out);
+        } else {
+            self.out.extend_from_slice(value.as_bytes());
+        }
+        Ok(())
+    }
+}
+
+impl<'a, 'r> ser::Serializer for KeySerializer<'a, 'r> {
+    type Ok = ();
+    type Error = DeError;
+
+    type SerializeSeq = Impossible<(), DeError>;
+    type SerializeTuple = Impossible<(), DeError>;
+    type SerializeTupleStruct = Impossible<(), DeError>;
+    type SerializeTupleVariant = Impossible<(), DeError>;
+    type SerializeMap = Impossible<(), DeError>;
+    type SerializeStruct = Impossible<(), DeError>;
+    type SerializeStructVariant = Impossible<(), DeError>;
+
+    fn serialize_bool(self, v: bool) -> Result<(), DeError> {
+        let text = self.config.bool_text(v);
+        self.write_str(text)
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<(), DeError> {
+        self.write_primitive(v)
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<(), DeError> {
+        self.write_primitive(v)
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<(), DeError> {
+        self.write_primitive(v)
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<(), DeError> {
+        self.write_primitive(v)
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<(), DeError> {
+        self.write_primitive(v)
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<(), DeError> {
+        self.write_primitive(v)
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<(), DeError> {
+        self.write_primitive(v)
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<(), DeError> {
+        self.write_primitive(v)
+    }
+
+    serde_if_integer128! {
+        fn serialize_i128(self, v: i128) -> Result<(), DeError> {
+            self.write_primitive(v)
+        }
+
+        fn serialize_u128(self, v: u128) -> Result<(), DeError> {
+            self.write_primitive(v)
+        }
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<(), DeError> {
+        self.write_primitive(v)
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<(), DeError> {
+        self.write_primitive(v)
+    }
+
+    fn serialize_char(self, v: char) -> Result<(), DeError> {
+        self.write_str(v.encode_utf8(&mut [0; 4]))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<(), DeError> {
+        self.write_str(v)
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<(), DeError> {
+        Err(unsupported())
+    }
+
+    fn serialize_none(self) -> Result<(), DeError> {
+        Err(unsupported())
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<(), DeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), DeError> {
+        Err(unsupported())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), DeError> {
+        Err(unsupported())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<(), DeError> {
+        self.write_str(variant)
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<(), DeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<(), DeError> {
+        Err(unsupported())
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Impossible<(), DeError>, DeError> {
+        Err(unsupported())
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Impossible<(), DeError>, DeError> {
+        Err(unsupported())
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Impossible<(), DeError>, DeError> {
+        Err(unsupported())
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Impossible<(), DeError>, DeError> {
+        Err(unsupported())
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Impossible<(), DeError>, DeError> {
+        Err(unsupported())
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Impossible<(), DeError>, DeError> {
+        Err(unsupported())
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Impossible<(), DeError>, DeError> {
+        Err(unsupported())
+    }
+}
@@ -1,13 +1,23 @@
 //! Module to handle custom serde `Serializer`
 
+mod adapter;
+mod key;
+mod probe;
 mod var;
 
+use self::adapter::Adapters;
+use self::probe::{Kind, Probe};
 use self::var::{Map, Seq, Struct, Tuple};
 use crate::{
     errors::serialize::DeError,
-    events::{BytesEnd, BytesStart, BytesText, Event},
+    escape::{
+        escape_with_char_refs_into, escape_with_level_into, needs_escaping_with_level,
+        CharRefFormat, EscapeLevel,
+    },
+    events::{BytesDecl, BytesEnd, BytesStart, BytesText, Event},
     writer::Writer,
 };
+use serde::de::DeserializeOwned;
 use serde::ser::{self, Serialize};
 use serde::serde_if_integer128;
 use std::io::Write;
@@ -15,6 +25,7 @@ use std::io::Write;
 /// Serialize struct into a `Write`r
 pub fn to_writer<W: Write, S: Serialize>(writer: W, value: &S) -> Result<(), DeError> {
     let mut serializer = Serializer::new(writer);
+    serializer.probe_struct(value)?;
     value.serialize(&mut serializer)
 }
 
@@ -26,11 +37,279 @@ pub fn to_string<S: Serialize>(value: &S) -> Result<String, DeError> {
     Ok(s)
 }
 
+/// Serialize struct into a `String` with the XML declaration `decl` before it.
+///
+/// `root` is the tag name of the root element, as in [`Serializer::with_root`]. If it is
+/// not specified, the struct name is used. Use [`Serializer::serialize_with_header`] to
+/// write a document type declaration as well.
+///
+/// # Examples
+///
+/// ```edition2018
+/// # use serde::Serialize;
+/// use quick_xml::events::BytesDecl;
+/// use quick_xml::se::to_string_with_header;
+///
+/// #[derive(Serialize)]
+/// struct Config {
+///     name: &'static str,
+/// }
+///
+/// let decl = BytesDecl::new(b"1.0", Some(b"UTF-8"), None);
+/// assert_eq!(
+///     to_string_with_header(&Config { name: "default" }, Some("config"), decl).unwrap(),
+///     r#"<?xml version="1.0" encoding="UTF-8"?><config name="default"/>"#
+/// );
+/// ```
+///
+/// [`Serializer::with_root`]: struct.Serializer.html#method.with_root
+/// [`Serializer::serialize_with_header`]: struct.Serializer.html#method.serialize_with_header
+pub fn to_string_with_header<S: Serialize>(
+    value: &S,
+    root: Option<&str>,
+    decl: BytesDecl,
+) -> Result<String, DeError> {
+    let mut writer = Vec::new();
+    Serializer::with_root(Writer::new(&mut writer), root)
+        .serialize_with_header(value, decl, None)?;
+    let s = String::from_utf8(writer).map_err(|e| crate::errors::Error::Utf8(e.utf8_error()))?;
+    Ok(s)
+}
+
+/// A document type declaration `<!DOCTYPE name PUBLIC "public_id" "system_id">`, written
+/// by [`Serializer::serialize_with_header`].
+///
+/// See [`Writer::write_doctype`] for how the identifiers are written.
+///
+/// [`Serializer::serialize_with_header`]: struct.Serializer.html#method.serialize_with_header
+/// [`Writer::write_doctype`]: ../struct.Writer.html#method.write_doctype
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DocType<'a> {
+    /// Name of the root element
+    pub name: &'a str,
+    /// Public identifier of the external DTD
+    pub public_id: Option<&'a str>,
+    /// System identifier of the external DTD
+    pub system_id: Option<&'a str>,
+}
+
+/// Defines how a sequence without elements is written by the [`Serializer`].
+///
+/// [`Serializer`]: struct.Serializer.html
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EmptySeqPolicy {
+    /// Nothing is written, as there are no elements to repeat. This is the default
+    #[default]
+    Omit,
+    /// A single empty element `<field/>`, named after the field, is written
+    EmptyElement,
+}
+
+/// Defines how booleans are written by the [`Serializer`].
+///
+/// [`Serializer`]: struct.Serializer.html
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BoolFormat {
+    /// `true` and `false`. This is the default
+    #[default]
+    TrueFalse,
+    /// `1` and `0`, which are valid `xs:boolean` values as well
+    OneZero,
+}
+
+/// Defines how byte arrays, serialized with `serialize_bytes`, for example, by the
+/// `serde_bytes` crate, are written by the [`Serializer`].
+///
+/// [`Serializer`]: struct.Serializer.html
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BytesEncoding {
+    /// Bytes are not supported, and an error is returned. This is the default
+    #[default]
+    Unsupported,
+    /// Each byte is written as two uppercase hexadecimal digits, like `DEADBEEF`
+    Hex,
+}
+
+/// Defines how unit variants, which are values of struct fields, are written by the
+/// [`Serializer`]. Unit variants, which are items of sequences or the root, are always
+/// written as empty elements, like `<Start/>`.
+///
+/// [`Serializer`]: struct.Serializer.html
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum UnitVariantFormat {
+    /// The name of the variant, as an attribute value `command="Start"` or as a text
+    /// of the `$value` field. This is the default
+    #[default]
+    Text,
+    /// An empty element, named after the variant, like `<Start/>`. Such fields are
+    /// deserialized back only as the `$value` fields, because the element is not named
+    /// after the field
+    Element,
+}
+
+/// Defines how sequences of pairs, such as `Vec<(K, V)>`, are written by the [`Serializer`].
+/// Unlike `HashMap`s, such association lists keep the order of the entries.
+///
+/// [`Serializer`]: struct.Serializer.html
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AssocListStyle {
+    /// Pairs are tuples, so both values are written as repeated elements, named after
+    /// the field, like `<field>k</field><field>v</field>`. This is the default
+    #[default]
+    Tuple,
+    /// An element, named after the field, with an `<entry><key>k</key><value>v</value></entry>`
+    /// element for each pair
+    Entries,
+    /// An element, named after the field, with a `<k>v</k>` element for each pair, as a map
+    /// is written. Keys must be strings or primitives, as the keys of maps
+    Elements,
+}
+
+/// Kind of the value, serialized as a value of a map entry
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum EntryValue {
+    /// The value is not a value of a map entry
+    None,
+    /// A value of a map entry, which key is the `root_tag`
+    Any,
+    /// A sequence, which items are written as repeated elements named by the `root_tag`
+    Seq,
+}
+
+/// Options of the `Serializer`, which are inherited by the serializers of nested values
+#[derive(Clone, Copy, Default)]
+struct Config<'r> {
+    /// Names of the elements for each position of a tuple
+    tuple_element_names: Option<&'r [&'r str]>,
+    /// Pairs of names of sequence fields and elements, which wrap their items
+    seq_wrappers: Option<&'r [(&'r str, &'r str)]>,
+    /// Write empty attributes as `name=""` instead of omitting them
+    keep_empty_attributes: bool,
+    /// Write whitespace characters of attributes as is instead of character references
+    keep_attribute_whitespaces: bool,
+    /// How to write sequences without elements
+    empty_seq: EmptySeqPolicy,
+    /// Flush the writer after each element of a sequence
+    flush_each_element: bool,
+    /// Which characters of strings are escaped
+    escape_level: EscapeLevel,
+    /// Replace non-ASCII characters of strings with character references
+    escape_non_ascii: bool,
+    /// Format of the character references for non-ASCII characters
+    char_ref_format: CharRefFormat,
+    /// How to write booleans
+    bool_format: BoolFormat,
+    /// How to write byte arrays
+    bytes_encoding: BytesEncoding,
+    /// Number of bytes in the groups of hexadecimal digits, separated by spaces
+    bytes_hex_grouping: Option<usize>,
+    /// How to write unit variants, which are values of struct fields
+    unit_variant_format: UnitVariantFormat,
+    /// How to write sequences of pairs
+    assoc_list_style: AssocListStyle,
+    /// Name of the element, which wraps the items of a sequence, serialized as the root
+    root_seq_wrapper: Option<&'r str>,
+    /// Write the items of a sequence, serialized as the root, without a root element
+    allow_fragment: bool,
+}
+
+impl<'r> Config<'r> {
+    /// Returns the name of the element, which wraps items of the sequence `field`
+    fn seq_wrapper(&self, field: &str) -> Option<&'r str> {
+        self.seq_wrappers?
+            .iter()
+            .find(|(name, _)| *name == field)
+            .map(|(_, wrapper)| *wrapper)
+    }
+
+    /// Returns the text of the boolean `value`
+    fn bool_text(&self, value: bool) -> &'static str {
+        match (self.bool_format, value) {
+            (BoolFormat::TrueFalse, true) => "true",
+            (BoolFormat::TrueFalse, false) => "false",
+            (BoolFormat::OneZero, true) => "1",
+            (BoolFormat::OneZero, false) => "0",
+        }
+    }
+
+    /// Whether the string `value` is changed by `escape_into`
+    fn needs_escaping(&self, value: &[u8]) -> bool {
+        self.escape_non_ascii && !value.is_ascii()
+            || needs_escaping_with_level(value, self.escape_level)
+    }
+
+    /// Appends the string `value`, escaped according to these options, to `out`
+    fn escape_into(&self, value: &[u8], out: &mut Vec<u8>) {
+        if self.escape_non_ascii && !value.is_ascii() {
+            escape_with_char_refs_into(value, out, self.escape_level, self.char_ref_format);
+        } else {
+            escape_with_level_into(value, out, self.escape_level);
+        }
+    }
+}
+
 /// A Serializer
 pub struct Serializer<'r, W: Write> {
     writer: Writer<W>,
     /// Name of the root tag. If not specified, deduced from the structure name
     root_tag: Option<&'r str>,
+    config: Config<'r>,
+    /// Set when a primitive value, possibly an empty one, was written. Allows to
+    /// distinguish empty strings from `None` and units, which write nothing
+    has_text: bool,
+    /// Set by the map, which value is serialized, to detect sequences, which items
+    /// should be written as repeated elements named by the key
+    entry: EntryValue,
+    /// Set when the next struct is known to serialize the fields, written as attributes,
+    /// before the fields, written as elements, so it can write the elements without
+    /// buffering them. Taken by the struct
+    attributes_first: bool,
+    /// Name of the element, which wraps items of the next sequence. Set by the struct
+    /// for fields, configured with `seq_wrappers`, and taken by the sequence
+    seq_wrapper: Option<&'r str>,
+    /// Set until the serializer starts to write its value, so the value is the root of
+    /// the document. Cleared for the serializers of nested values
+    root: bool,
+    /// Set for the serializers of the values of struct fields, so unit variants are
+    /// written according to `unit_variant_format`. Cleared by the values, written as
+    /// elements, so their items and contents are elements again
+    field: bool,
+    /// Set by the sequence for its items, which are pairs, written as entries according
+    /// to `assoc_list_style`. Taken by the tuple
+    pair: bool,
+    /// Buffers, moved to the serializers of nested values and back, so they are
+    /// allocated only once
+    buffers: Buffers,
+    /// Functions, which write values of the registered types as text. Moved to the
+    /// serializers of nested values and back, as the buffers
+    adapters: Adapters,
+}
+
+/// Buffers, reused by the `Serializer` to avoid allocations for each value
+#[derive(Default)]
+struct Buffers {
+    /// Buffer for formatting and escaping primitive values
+    text: Vec<u8>,
+    /// Empty buffers for structs and maps. Each struct or map takes the buffers it needs
+    /// and gives them back when it ends, so nested structs take their own buffers, and
+    /// after the first value is serialized the buffers are never allocated again.
+    ///
+    /// Buffers in use are lost, if serialization fails or panics, and are allocated anew,
+    /// so partially written buffers never return to the pool
+    free: Vec<Vec<u8>>,
+}
+
+impl Buffers {
+    /// Takes an empty buffer from the pool or creates a new one
+    fn take(&mut self) -> Vec<u8> {
+        self.free.pop().unwrap_or_default()
+    }
+
+    /// Returns the `buffer` to the pool
+    fn give(&mut self, mut buffer: Vec<u8>) {
+        buffer.clear();
+        self.free.push(buffer);
+    }
 }
 
 impl<'r, W: Write> Serializer<'r, W> {
@@ -43,171 +322,923 @@ impl<'r, W: Write> Serializer<'r, W> {
         Self::with_root(Writer::new(writer), None)
     }
 
-    /// Creates a new `Serializer` that uses specified root tag name
+    /// Sets names of the elements for each position of tuples, tuple structs and
+    /// tuple variants. Positions without a name use the tag name of the tuple.
     ///
     /// # Examples
     ///
-    /// When serializing a primitive type, only its representation will be written:
-    ///
     /// ```edition2018
     /// # use serde::Serialize;
-    /// use quick_xml::Writer;
-    /// # use quick_xml::se::Serializer;
+    /// use quick_xml::se::Serializer;
     ///
     /// let mut buffer = Vec::new();
-    /// let mut writer = Writer::new_with_indent(&mut buffer, b' ', 2);
-    /// let mut ser = Serializer::with_root(writer, Some("root"));
+    /// let mut ser = Serializer::with_root(quick_xml::Writer::new(&mut buffer), Some("point"));
+    /// ser.tuple_element_names(&["x", "y"]);
     ///
-    /// "node".serialize(&mut ser).unwrap();
-    /// assert_eq!(String::from_utf8(buffer).unwrap(), "node");
+    /// (1, 2, 3).serialize(&mut ser).unwrap();
+    /// assert_eq!(
+    ///     String::from_utf8(buffer).unwrap(),
+    ///     "<x>1</x><y>2</y><point>3</point>"
+    /// );
     /// ```
+    pub fn tuple_element_names(&mut self, names: &'r [&'r str]) -> &mut Self {
+        self.config.tuple_element_names = Some(names);
+        self
+    }
+
+    /// Sets elements, which wrap items of sequence fields of structs, as pairs of
+    /// a field name and a wrapper name.
     ///
-    /// When serializing a struct, newtype struct, unit struct or tuple `root_tag`
-    /// is used as tag name of root(s) element(s):
+    /// Items of a sequence are written as repeated sibling elements named by the field.
+    /// Items of the listed fields are written inside of a single wrapper element instead,
+    /// and are still named by the field, which can be changed with `#[serde(rename)]`.
+    /// Items, that are not elements by themselves, such as numbers or strings, are
+    /// written as `<field>...</field>`. An empty sequence is written as an empty wrapper
+    /// element regardless of [`empty_seq_element`](#method.empty_seq_element).
+    ///
+    /// # Examples
     ///
     /// ```edition2018
     /// # use serde::Serialize;
-    /// use quick_xml::Writer;
     /// use quick_xml::se::Serializer;
     ///
-    /// #[derive(Debug, PartialEq, Serialize)]
-    /// struct Struct {
-    ///     question: String,
-    ///     answer: u32,
+    /// #[derive(Serialize)]
+    /// struct Order {
+    ///     id: u32,
+    ///     item: Vec<u32>,
     /// }
     ///
     /// let mut buffer = Vec::new();
-    /// let mut writer = Writer::new_with_indent(&mut buffer, b' ', 2);
-    /// let mut ser = Serializer::with_root(writer, Some("root"));
+    /// let mut ser = Serializer::new(&mut buffer);
+    /// ser.seq_wrappers(&[("item", "items")]);
     ///
-    /// Struct {
-    ///     question: "The Ultimate Question of Life, the Universe, and Everything".into(),
-    ///     answer: 42,
-    /// }.serialize(&mut ser).unwrap();
+    /// Order { id: 1, item: vec![2, 3] }.serialize(&mut ser).unwrap();
     /// assert_eq!(
-    ///     String::from_utf8(buffer.clone()).unwrap(),
-    ///     r#"<root question="The Ultimate Question of Life, the Universe, and Everything" answer="42"/>"#
+    ///     String::from_utf8(buffer).unwrap(),
+    ///     r#"<Order id="1"><items><item>2</item><item>3</item></items></Order>"#
     /// );
     /// ```
-    pub fn with_root(writer: Writer<W>, root_tag: Option<&'r str>) -> Self {
-        Self { writer, root_tag }
+    pub fn seq_wrappers(&mut self, wrappers: &'r [(&'r str, &'r str)]) -> &mut Self {
+        self.config.seq_wrappers = Some(wrappers);
+        self
     }
 
-    fn write_primitive<P: std::fmt::Display>(
-        &mut self,
-        value: P,
-        escaped: bool,
-    ) -> Result<(), DeError> {
-        let value = value.to_string().into_bytes();
-        let event = if escaped {
-            BytesText::from_escaped(value)
-        } else {
-            BytesText::from_plain(&value)
-        };
-        self.writer.write_event(Event::Text(event))?;
-        Ok(())
+    /// Changes whether struct fields serialized as empty attributes, such as empty
+    /// strings, are written as `name=""` (`false` by default).
+    ///
+    /// When set to `false`, such attributes are omitted. Fields that write nothing
+    /// at all, such as `None` and units, are omitted regardless of this setting.
+    ///
+    /// # Examples
+    ///
+    /// ```edition2018
+    /// # use serde::Serialize;
+    /// use quick_xml::se::Serializer;
+    ///
+    /// #[derive(Serialize)]
+    /// struct Root {
+    ///     empty: String,
+    ///     none: Option<String>,
+    /// }
+    ///
+    /// let mut buffer = Vec::new();
+    /// let mut ser = Serializer::new(&mut buffer);
+    /// ser.keep_empty_attributes(true);
+    ///
+    /// Root { empty: String::new(), none: None }.serialize(&mut ser).unwrap();
+    /// assert_eq!(String::from_utf8(buffer).unwrap(), r#"<Root empty=""/>"#);
+    /// ```
+    pub fn keep_empty_attributes(&mut self, val: bool) -> &mut Self {
+        self.config.keep_empty_attributes = val;
+        self
     }
 
-    /// Writes self-closed tag `<tag_name/>` into inner writer
-    fn write_self_closed(&mut self, tag_name: &str) -> Result<(), DeError> {
-        self.writer
-            .write_event(Event::Empty(BytesStart::borrowed_name(tag_name.as_bytes())))?;
-        Ok(())
+    /// Changes whether tabs and line breaks of struct fields, serialized as attributes,
+    /// are written as is (`false` by default).
+    ///
+    /// By default they are written as character references, such as `&#10;`, because
+    /// the whitespace characters, written as is, are replaced by spaces, when the value
+    /// is read, so multi-line values would not survive a round-trip.
+    ///
+    /// # Examples
+    ///
+    /// ```edition2018
+    /// # use serde::Serialize;
+    /// use quick_xml::se::Serializer;
+    ///
+    /// #[derive(Serialize)]
+    /// struct Root {
+    ///     title: String,
+    /// }
+    ///
+    /// let root = Root { title: "first\nsecond".into() };
+    ///
+    /// let mut buffer = Vec::new();
+    /// root.serialize(&mut Serializer::new(&mut buffer)).unwrap();
+    /// assert_eq!(String::from_utf8(buffer).unwrap(), r#"<Root title="first&#10;second"/>"#);
+    ///
+    /// let mut buffer = Vec::new();
+    /// let mut ser = Serializer::new(&mut buffer);
+    /// ser.keep_attribute_whitespaces(true);
+    /// root.serialize(&mut ser).unwrap();
+    /// assert_eq!(String::from_utf8(buffer).unwrap(), "<Root title=\"first\nsecond\"/>");
+    /// ```
+    pub fn keep_attribute_whitespaces(&mut self, val: bool) -> &mut Self {
+        self.config.keep_attribute_whitespaces = val;
+        self
     }
 
-    /// Writes a serialized `value` surrounded by `<tag_name>...</tag_name>`
-    fn write_paired<T: ?Sized + Serialize>(
-        &mut self,
-        tag_name: &str,
-        value: &T,
-    ) -> Result<(), DeError> {
-        self.writer
-            .write_event(Event::Start(BytesStart::borrowed_name(tag_name.as_bytes())))?;
-        value.serialize(&mut *self)?;
-        self.writer
-            .write_event(Event::End(BytesEnd::borrowed(tag_name.as_bytes())))?;
-        Ok(())
+    /// Changes how sequences without elements are written (`EmptySeqPolicy::Omit` by default).
+    ///
+    /// Elements of a sequence are written as repeated sibling elements, so an empty sequence
+    /// is omitted by default. With [`EmptySeqPolicy::EmptyElement`] a single empty element is
+    /// written instead, if the sequence has a name, for example, it is a struct field.
+    ///
+    /// # Examples
+    ///
+    /// ```edition2018
+    /// # use serde::Serialize;
+    /// use quick_xml::se::{EmptySeqPolicy, Serializer};
+    ///
+    /// #[derive(Serialize)]
+    /// struct Root {
+    ///     item: Vec<u32>,
+    /// }
+    ///
+    /// let mut buffer = Vec::new();
+    /// let mut ser = Serializer::new(&mut buffer);
+    /// ser.empty_seq_element(EmptySeqPolicy::EmptyElement);
+    ///
+    /// Root { item: Vec::new() }.serialize(&mut ser).unwrap();
+    /// assert_eq!(String::from_utf8(buffer).unwrap(), "<Root><item/></Root>");
+    /// ```
+    ///
+    /// [`EmptySeqPolicy::EmptyElement`]: enum.EmptySeqPolicy.html#variant.EmptyElement
+    pub fn empty_seq_element(&mut self, policy: EmptySeqPolicy) -> &mut Self {
+        self.config.empty_seq = policy;
+        self
     }
-}
-
-impl<'r, 'w, W: Write> ser::Serializer for &'w mut Serializer<'r, W> {
-    type Ok = ();
-    type Error = DeError;
-
-    type SerializeSeq = Seq<'r, 'w, W>;
-    type SerializeTuple = Tuple<'r, 'w, W>;
-    type SerializeTupleStruct = Tuple<'r, 'w, W>;
-    type SerializeTupleVariant = Tuple<'r, 'w, W>;
-    type SerializeMap = Map<'r, 'w, W>;
-    type SerializeStruct = Struct<'r, 'w, W>;
-    type SerializeStructVariant = Struct<'r, 'w, W>;
 
-    fn serialize_bool(self, v: bool) -> Result<Self::Ok, DeError> {
-        self.write_primitive(if v { "true" } else { "false" }, true)
+    /// Changes whether the underlying writer is flushed after each serialized element
+    /// of a sequence (`false` by default).
+    ///
+    /// Elements of a sequence are written as soon as they are serialized, so enabling
+    /// this keeps the memory usage flat when a large sequence is exported through a
+    /// buffered writer, at the cost of a flush per element.
+    pub fn flush_each_element(&mut self, val: bool) -> &mut Self {
+        self.config.flush_each_element = val;
+        self
     }
 
-    fn serialize_i8(self, v: i8) -> Result<Self::Ok, DeError> {
-        self.write_primitive(v, true)
+    /// Changes which characters of strings are escaped (`EscapeLevel::Standard` by default).
+    ///
+    /// Attribute values are always written in double quotes, so with
+    /// [`EscapeLevel::Minimal`] the `"` characters in them are escaped as well.
+    ///
+    /// # Examples
+    ///
+    /// ```edition2018
+    /// # use serde::Serialize;
+    /// use quick_xml::escape::EscapeLevel;
+    /// use quick_xml::se::Serializer;
+    ///
+    /// #[derive(Serialize)]
+    /// struct Root {
+    ///     attribute: &'static str,
+    ///     #[serde(rename = "$value")]
+    ///     text: &'static str,
+    /// }
+    ///
+    /// let mut buffer = Vec::new();
+    /// let mut ser = Serializer::new(&mut buffer);
+    /// ser.escape_level(EscapeLevel::Minimal);
+    ///
+    /// Root { attribute: "\"a\" > 'b'", text: "\"a\" > 'b'" }.serialize(&mut ser).unwrap();
+    /// assert_eq!(
+    ///     String::from_utf8(buffer).unwrap(),
+    ///     r#"<Root attribute="&quot;a&quot; > 'b'">"a" > 'b'</Root>"#
+    /// );
+    /// ```
+    ///
+    /// [`EscapeLevel::Minimal`]: ../escape/enum.EscapeLevel.html#variant.Minimal
+    pub fn escape_level(&mut self, level: EscapeLevel) -> &mut Self {
+        self.config.escape_level = level;
+        self
     }
 
-    fn serialize_i16(self, v: i16) -> Result<Self::Ok, DeError> {
-        self.write_primitive(v, true)
+    /// Changes whether non-ASCII characters of strings are replaced with character references,
+    /// such as `&#233;` for `é`, so the output is pure ASCII. Applied to texts and attribute
+    /// values; names of elements and attributes are never escaped (`false` by default).
+    ///
+    /// The references are decimal, unless changed by [`char_ref_format`]. This takes
+    /// precedence over the hexadecimal references of [`EscapeLevel::Aggressive`].
+    ///
+    /// # Examples
+    ///
+    /// ```edition2018
+    /// # use serde::Serialize;
+    /// use quick_xml::se::Serializer;
+    ///
+    /// #[derive(Serialize)]
+    /// struct Root {
+    ///     attribute: &'static str,
+    ///     #[serde(rename = "$value")]
+    ///     text: &'static str,
+    /// }
+    ///
+    /// let mut buffer = Vec::new();
+    /// let mut ser = Serializer::new(&mut buffer);
+    /// ser.escape_non_ascii(true);
+    ///
+    /// Root { attribute: "caf\u{E9}", text: "\u{1F600} & co" }.serialize(&mut ser).unwrap();
+    /// assert_eq!(
+    ///     String::from_utf8(buffer).unwrap(),
+    ///     r#"<Root attribute="caf&#233;">&#128512; &amp; co</Root>"#
+    /// );
+    /// ```
+    ///
+    /// [`char_ref_format`]: #method.char_ref_format
+    /// [`EscapeLevel::Aggressive`]: ../escape/enum.EscapeLevel.html#variant.Aggressive
+    pub fn escape_non_ascii(&mut self, val: bool) -> &mut Self {
+        self.config.escape_non_ascii = val;
+        self
     }
 
-    fn serialize_i32(self, v: i32) -> Result<Self::Ok, DeError> {
-        self.write_primitive(v, true)
+    /// Changes the format of the character references written by [`escape_non_ascii`]
+    /// (`CharRefFormat::Decimal` by default).
+    ///
+    /// [`escape_non_ascii`]: #method.escape_non_ascii
+    pub fn char_ref_format(&mut self, format: CharRefFormat) -> &mut Self {
+        self.config.char_ref_format = format;
+        self
     }
 
-    fn serialize_i64(self, v: i64) -> Result<Self::Ok, DeError> {
-        self.write_primitive(v, true)
+    /// Changes how booleans are written, in attribute values and in texts alike
+    /// (`BoolFormat::TrueFalse` by default).
+    ///
+    /// # Examples
+    ///
+    /// ```edition2018
+    /// # use serde::Serialize;
+    /// use quick_xml::se::{BoolFormat, Serializer};
+    ///
+    /// #[derive(Serialize)]
+    /// struct Root {
+    ///     enabled: bool,
+    ///     #[serde(rename = "$value")]
+    ///     text: bool,
+    /// }
+    ///
+    /// let mut buffer = Vec::new();
+    /// let mut ser = Serializer::new(&mut buffer);
+    /// ser.bool_format(BoolFormat::OneZero);
+    ///
+    /// Root { enabled: true, text: false }.serialize(&mut ser).unwrap();
+    /// assert_eq!(String::from_utf8(buffer).unwrap(), r#"<Root enabled="1">0</Root>"#);
+    /// ```
+    pub fn bool_format(&mut self, format: BoolFormat) -> &mut Self {
+        self.config.bool_format = format;
+        self
     }
 
-    fn serialize_u8(self, v: u8) -> Result<Self::Ok, DeError> {
-        self.write_primitive(v, true)
+    /// Changes how unit variants, which are values of struct fields, are written
+    /// (`UnitVariantFormat::Text` by default). Unit structs are always written as empty
+    /// elements, named after the field or, as the root, after the `root_tag` or the type.
+    ///
+    /// # Examples
+    ///
+    /// ```edition2018
+    /// # use serde::Serialize;
+    /// use quick_xml::se::{Serializer, UnitVariantFormat};
+    ///
+    /// #[derive(Serialize)]
+    /// enum Command {
+    ///     Start,
+    /// }
+    ///
+    /// #[derive(Serialize)]
+    /// struct Root {
+    ///     command: Command,
+    /// }
+    ///
+    /// let mut buffer = Vec::new();
+    /// Root { command: Command::Start }
+    ///     .serialize(&mut Serializer::new(&mut buffer))
+    ///     .unwrap();
+    /// assert_eq!(String::from_utf8(buffer).unwrap(), r#"<Root command="Start"/>"#);
+    ///
+    /// let mut buffer = Vec::new();
+    /// let mut ser = Serializer::new(&mut buffer);
+    /// ser.unit_variant_format(UnitVariantFormat::Element);
+    ///
+    /// Root { command: Command::Start }.serialize(&mut ser).unwrap();
+    /// assert_eq!(String::from_utf8(buffer).unwrap(), "<Root><Start/></Root>");
+    /// ```
+    pub fn unit_variant_format(&mut self, format: UnitVariantFormat) -> &mut Self {
+        self.config.unit_variant_format = format;
+        self
     }
 
-    fn serialize_u16(self, v: u16) -> Result<Self::Ok, DeError> {
-        self.write_primitive(v, true)
+    /// Changes how sequences of pairs, such as `Vec<(K, V)>`, are written
+    /// (`AssocListStyle::Tuple` by default).
+    ///
+    /// Sequences of pairs are ordered maps, which are written as a map or as a list of
+    /// entries with `Entries` and `Elements` styles. Only tuples are pairs, not tuple
+    /// structs. As the root, the entries are written inside of the root element.
+    ///
+    /// # Examples
+    ///
+    /// ```edition2018
+    /// # use serde::Serialize;
+    /// use quick_xml::se::{AssocListStyle, Serializer};
+    ///
+    /// #[derive(Serialize)]
+    /// struct Root {
+    ///     env: Vec<(String, String)>,
+    /// }
+    ///
+    /// let root = Root {
+    ///     env: vec![("PATH".into(), "/bin".into()), ("HOME".into(), "/root".into())],
+    /// };
+    ///
+    /// let mut buffer = Vec::new();
+    /// let mut ser = Serializer::new(&mut buffer);
+    /// ser.assoc_list_style(AssocListStyle::Entries);
+    /// root.serialize(&mut ser).unwrap();
+    /// assert_eq!(
+    ///     String::from_utf8(buffer).unwrap(),
+    ///     "<Root><env>\
+    ///         <entry><key>PATH</key><value>/bin</value></entry>\
+    ///         <entry><key>HOME</key><value>/root</value></entry>\
+    ///     </env></Root>"
+    /// );
+    ///
+    /// let mut buffer = Vec::new();
+    /// let mut ser = Serializer::new(&mut buffer);
+    /// ser.assoc_list_style(AssocListStyle::Elements);
+    /// root.serialize(&mut ser).unwrap();
+    /// assert_eq!(
+    ///     String::from_utf8(buffer).unwrap(),
+    ///     "<Root><env><PATH>/bin</PATH><HOME>/root</HOME></env></Root>"
+    /// );
+    /// ```
+    pub fn assoc_list_style(&mut self, style: AssocListStyle) -> &mut Self {
+        self.config.assoc_list_style = style;
+        self
     }
 
-    fn serialize_u32(self, v: u32) -> Result<Self::Ok, DeError> {
-        self.write_primitive(v, true)
+    /// Changes how byte arrays are written, in attribute values and in texts alike
+    /// (`BytesEncoding::Unsupported` by default).
+    ///
+    /// Only values, which are serialized as bytes, are affected, such as the fields marked
+    /// with `#[serde(with = "serde_bytes")]`. `Vec<u8>` is serialized as a sequence.
+    pub fn bytes_encoding(&mut self, encoding: BytesEncoding) -> &mut Self {
+        self.config.bytes_encoding = encoding;
+        self
     }
 
-    fn serialize_u64(self, v: u64) -> Result<Self::Ok, DeError> {
-        self.write_primitive(v, true)
+    /// Sets the number of bytes, after which a space is written between the hexadecimal
+    /// digits, when byte arrays are written with `BytesEncoding::Hex`. `Some(1)` writes
+    /// `DE AD BE EF`, and `Some(2)` writes `DEAD BEEF`.
+    ///
+    /// (`None`, no spaces, by default)
+    ///
+    /// # Examples
+    ///
+    /// ```edition2018
+    /// # use serde::{Serialize, Serializer as _};
+    /// use quick_xml::se::{BytesEncoding, Serializer};
+    ///
+    /// // Does the same as `#[serde(with = "serde_bytes")]`
+    /// fn as_bytes<S: serde::Serializer>(bytes: &[u8], s: S) -> Result<S::Ok, S::Error> {
+    ///     s.serialize_bytes(bytes)
+    /// }
+    ///
+    /// #[derive(Serialize)]
+    /// struct Register {
+    ///     #[serde(serialize_with = "as_bytes")]
+    ///     reset: Vec<u8>,
+    ///     #[serde(rename = "$value", serialize_with = "as_bytes")]
+    ///     value: Vec<u8>,
+    /// }
+    ///
+    /// let mut buffer = Vec::new();
+    /// let mut ser = Serializer::new(&mut buffer);
+    /// ser.bytes_encoding(BytesEncoding::Hex).bytes_hex_grouping(Some(2));
+    ///
+    /// let register = Register {
+    ///     reset: vec![0, 0, 0x0F],
+    ///     value: vec![0xDE, 0xAD, 0xBE, 0xEF],
+    /// };
+    /// register.serialize(&mut ser).unwrap();
+    /// assert_eq!(
+    ///     String::from_utf8(buffer).unwrap(),
+    ///     r#"<Register reset="0000 0F">DEAD BEEF</Register>"#
+    /// );
+    /// ```
+    pub fn bytes_hex_grouping(&mut self, group: Option<usize>) -> &mut Self {
+        self.config.bytes_hex_grouping = group.filter(|&n| n > 0);
+        self
     }
 
-    serde_if_integer128! {
-        fn serialize_i128(self, v: i128) -> Result<Self::Ok, DeError> {
-            self.write_primitive(v, true)
-        }
-
-        fn serialize_u128(self, v: u128) -> Result<Self::Ok, DeError> {
-            self.write_primitive(v, true)
+    /// Sets the name of the root element, which wraps the items of a sequence, serialized
+    /// as the root of the document.
+    ///
+    /// A document has only one root element, so a sequence, serialized as the root,
+    /// needs an element around its items. By default it is named by the root tag, given
+    /// to [`with_root`], and the items are named by themselves. Without the name
+    /// serialization fails, unless fragments are allowed with [`allow_fragment`].
+    ///
+    /// If both names are given, the items are named by the root tag.
+    ///
+    /// # Examples
+    ///
+    /// ```edition2018
+    /// # use serde::Serialize;
+    /// use quick_xml::se::Serializer;
+    ///
+    /// #[derive(Serialize)]
+    /// struct Item {
+    ///     id: u32,
+    /// }
+    ///
+    /// let mut buffer = Vec::new();
+    /// let mut ser = Serializer::new(&mut buffer);
+    /// ser.wrap_sequences_in_root("items");
+    ///
+    /// vec![Item { id: 1 }, Item { id: 2 }].serialize(&mut ser).unwrap();
+    /// assert_eq!(
+    ///     String::from_utf8(buffer).unwrap(),
+    ///     r#"<items><Item id="1"/><Item id="2"/></items>"#
+    /// );
+    /// ```
+    ///
+    /// [`with_root`]: #method.with_root
+    /// [`allow_fragment`]: #method.allow_fragment
+    pub fn wrap_sequences_in_root(&mut self, name: &'r str) -> &mut Self {
+        self.config.root_seq_wrapper = Some(name);
+        self
+    }
+
+    /// Changes whether the items of a sequence, serialized as the root of the document,
+    /// are written one after another without a root element (`false` by default).
+    ///
+    /// The result is not a well-formed document, but a fragment, which can be embedded
+    /// into another one. See [`wrap_sequences_in_root`] for the default behavior.
+    ///
+    /// # Examples
+    ///
+    /// ```edition2018
+    /// # use serde::Serialize;
+    /// use quick_xml::se::Serializer;
+    ///
+    /// #[derive(Serialize)]
+    /// struct Item {
+    ///     id: u32,
+    /// }
+    ///
+    /// let mut buffer = Vec::new();
+    /// let mut ser = Serializer::new(&mut buffer);
+    /// ser.allow_fragment(true);
+    ///
+    /// vec![Item { id: 1 }, Item { id: 2 }].serialize(&mut ser).unwrap();
+    /// assert_eq!(
+    ///     String::from_utf8(buffer).unwrap(),
+    ///     r#"<Item id="1"/><Item id="2"/>"#
+    /// );
+    /// ```
+    ///
+    /// [`wrap_sequences_in_root`]: #method.wrap_sequences_in_root
+    pub fn allow_fragment(&mut self, val: bool) -> &mut Self {
+        self.config.allow_fragment = val;
+        self
+    }
+
+    /// Registers a function, which writes values of type `T` as text, in attribute values
+    /// and in texts alike. It replaces the `Serialize` implementation of `T` for values
+    /// of fields, items of sequences and values of maps, but not for the root value.
+    ///
+    /// Values of type `T` and references to them are recognized by the full path of the
+    /// type, as `std::any::type_name` returns it, because serde passes values, which
+    /// are not `'static`, so their `TypeId` is not available. Values of other types, like
+    /// `Option<T>` or other types with the same name in serde, are serialized as usual.
+    ///
+    /// `T` must implement `Deserialize`: serde does not give the value of `T` itself, so
+    /// it is deserialized back from the serialized value to call `adapter`.
+    ///
+    /// # Examples
+    ///
+    /// ```edition2018
+    /// # use serde::{Deserialize, Serialize};
+    /// use quick_xml::se::Serializer;
+    /// use std::time::Duration;
+    ///
+    /// #[derive(Serialize)]
+    /// struct Task {
+    ///     timeout: Duration,
+    /// }
+    ///
+    /// let mut buffer = Vec::new();
+    /// let mut ser = Serializer::new(&mut buffer);
+    /// ser.register_adapter(|d: &Duration| format!("PT{}S", d.as_secs_f64()));
+    ///
+    /// let task = Task { timeout: Duration::from_millis(2500) };
+    /// task.serialize(&mut ser).unwrap();
+    /// assert_eq!(String::from_utf8(buffer).unwrap(), r#"<Task timeout="PT2.5S"/>"#);
+    /// ```
+    pub fn register_adapter<T: DeserializeOwned + 'static>(
+        &mut self,
+        adapter: fn(&T) -> String,
+    ) -> &mut Self {
+        self.adapters.register(adapter);
+        self
+    }
+
+    /// Creates a serializer of a nested value, which inherits options of this serializer.
+    /// The buffers and adapters are moved to the nested serializer and should be returned
+    /// back with [`restore`](#method.restore)
+    fn nested<'k, V: Write>(
+        &mut self,
+        writer: Writer<V>,
+        root_tag: Option<&'k str>,
+    ) -> Serializer<'k, V>
+    where
+        'r: 'k,
+    {
+        Serializer {
+            writer,
+            root_tag,
+            config: self.config,
+            has_text: false,
+            entry: EntryValue::None,
+            attributes_first: false,
+            seq_wrapper: None,
+            root: false,
+            field: false,
+            pair: false,
+            buffers: std::mem::take(&mut self.buffers),
+            adapters: std::mem::take(&mut self.adapters),
+        }
+    }
+
+    /// Finds out what `value` is serialized as and, if it is a struct, whether it can
+    /// write its fields without buffering them. Sets `attributes_first` accordingly
+    fn probe_struct<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<Kind, DeError> {
+        let kind = Probe {
+            config: &self.config,
+            adapters: &self.adapters,
+            fields: true,
+            wrapped: false,
+            field: false,
+            pairs: false,
+        }
+        .kind_of(value)?;
+        self.attributes_first = kind.attributes_first();
+        Ok(kind)
+    }
+
+    /// Whether `value` is a pair, which is written as an entry of an association list
+    fn is_pair<T: ?Sized + Serialize>(&self, value: &T) -> Result<bool, DeError> {
+        let kind = Probe {
+            config: &self.config,
+            adapters: &self.adapters,
+            fields: false,
+            wrapped: false,
+            field: false,
+            pairs: true,
+        }
+        .kind_of(value)?;
+        Ok(kind == Kind::Pair)
+    }
+
+    /// Takes back the buffers and adapters, moved to the `nested` serializer
+    fn restore<V: Write>(&mut self, nested: &mut Serializer<V>) {
+        self.buffers = std::mem::take(&mut nested.buffers);
+        self.adapters = std::mem::take(&mut nested.adapters);
+    }
+
+    /// Serializes `value`, or the text of it, if an adapter is registered for its type
+    fn serialize_adapted<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), DeError> {
+        match self.adapters.adapt(value)? {
+            Some(text) => self.write_str(&text),
+            None => value.serialize(self),
+        }
+    }
+
+    /// Creates a new `Serializer` that uses specified root tag name
+    ///
+    /// # Examples
+    ///
+    /// When serializing a primitive type, only its representation will be written:
+    ///
+    /// ```edition2018
+    /// # use serde::Serialize;
+    /// use quick_xml::Writer;
+    /// # use quick_xml::se::Serializer;
+    ///
+    /// let mut buffer = Vec::new();
+    /// let mut writer = Writer::new_with_indent(&mut buffer, b' ', 2);
+    /// let mut ser = Serializer::with_root(writer, Some("root"));
+    ///
+    /// "node".serialize(&mut ser).unwrap();
+    /// assert_eq!(String::from_utf8(buffer).unwrap(), "node");
+    /// ```
+    ///
+    /// When serializing a struct, newtype struct, unit struct or tuple `root_tag`
+    /// is used as tag name of root(s) element(s):
+    ///
+    /// ```edition2018
+    /// # use serde::Serialize;
+    /// use quick_xml::Writer;
+    /// use quick_xml::se::Serializer;
+    ///
+    /// #[derive(Debug, PartialEq, Serialize)]
+    /// struct Struct {
+    ///     question: String,
+    ///     answer: u32,
+    /// }
+    ///
+    /// let mut buffer = Vec::new();
+    /// let mut writer = Writer::new_with_indent(&mut buffer, b' ', 2);
+    /// let mut ser = Serializer::with_root(writer, Some("root"));
+    ///
+    /// Struct {
+    ///     question: "The Ultimate Question of Life, the Universe, and Everything".into(),
+    ///     answer: 42,
+    /// }.serialize(&mut ser).unwrap();
+    /// assert_eq!(
+    ///     String::from_utf8(buffer.clone()).unwrap(),
+    ///     r#"<root question="The Ultimate Question of Life, the Universe, and Everything" answer="42"/>"#
+    /// );
+    /// ```
+    ///
+    /// The writer is used as is: its indentation and escaping settings are applied to all
+    /// emitted events, including events of the nested elements, and it can be taken back
+    /// with [`into_inner`] to write more events, which continue its indentation:
+    ///
+    /// ```edition2018
+    /// # use serde::Serialize;
+    /// use quick_xml::events::{BytesEnd, BytesStart, Event};
+    /// use quick_xml::se::Serializer;
+    /// use quick_xml::Writer;
+    ///
+    /// #[derive(Serialize)]
+    /// struct Item {
+    ///     id: u32,
+    /// }
+    ///
+    /// let mut buffer = Vec::new();
+    /// let mut writer = Writer::new_with_indent(&mut buffer, b' ', 2);
+    /// writer.write_event(Event::Start(BytesStart::borrowed_name(b"items"))).unwrap();
+    ///
+    /// let mut ser = Serializer::with_root(writer, Some("item"));
+    /// Item { id: 1 }.serialize(&mut ser).unwrap();
+    /// let mut writer = ser.into_inner();
+    ///
+    /// writer.write_event(Event::End(BytesEnd::borrowed(b"items"))).unwrap();
+    /// assert_eq!(
+    ///     String::from_utf8(buffer).unwrap(),
+    ///     "<items>\n  <item id=\"1\"/>\n</items>"
+    /// );
+    /// ```
+    ///
+    /// [`into_inner`]: #method.into_inner
+    pub fn with_root(writer: Writer<W>, root_tag: Option<&'r str>) -> Self {
+        Self {
+            writer,
+            root_tag,
+            config: Config::default(),
+            has_text: false,
+            entry: EntryValue::None,
+            attributes_first: false,
+            seq_wrapper: None,
+            root: true,
+            field: false,
+            pair: false,
+            buffers: Buffers::default(),
+            adapters: Adapters::default(),
+        }
+    }
+
+    /// Creates a new `Serializer` that writes into an already configured `writer`
+    /// and uses specified root tag name, or struct name if `root_tag` is `None`.
+    #[deprecated(note = "use `Serializer::with_root`, which does the same")]
+    pub fn with_writer(writer: Writer<W>, root_tag: Option<&'r str>) -> Self {
+        Self::with_root(writer, root_tag)
+    }
+
+    /// Consumes this serializer and returns the underlying writer, so more events can be
+    /// written after the serialized values with the same indentation.
+    pub fn into_inner(self) -> Writer<W> {
+        self.writer
+    }
+
+    /// Serializes `value` as a complete document: the XML declaration `decl`,
+    /// an optional document type declaration, and then the root element.
+    ///
+    /// # Examples
+    ///
+    /// ```edition2018
+    /// # use serde::Serialize;
+    /// use quick_xml::events::BytesDecl;
+    /// use quick_xml::se::{DocType, Serializer};
+    ///
+    /// #[derive(Serialize)]
+    /// struct Note {
+    ///     #[serde(rename = "$value")]
+    ///     text: &'static str,
+    /// }
+    ///
+    /// let mut buffer = Vec::new();
+    /// let mut ser = Serializer::with_root(quick_xml::Writer::new(&mut buffer), Some("note"));
+    /// let doctype = DocType {
+    ///     name: "note",
+    ///     public_id: None,
+    ///     system_id: Some("note.dtd"),
+    /// };
+    /// ser.serialize_with_header(
+    ///     &Note { text: "Hello" },
+    ///     BytesDecl::new(b"1.0", None, None),
+    ///     Some(doctype),
+    /// ).unwrap();
+    /// assert_eq!(
+    ///     String::from_utf8(buffer).unwrap(),
+    ///     r#"<?xml version="1.0"?><!DOCTYPE note SYSTEM "note.dtd"><note>Hello</note>"#
+    /// );
+    /// ```
+    pub fn serialize_with_header<T: ?Sized + Serialize>(
+        &mut self,
+        value: &T,
+        decl: BytesDecl,
+        doctype: Option<DocType>,
+    ) -> Result<(), DeError> {
+        self.writer.write_event(Event::Decl(decl))?;
+        if let Some(doctype) = doctype {
+            self.writer
+                .write_doctype(doctype.name, doctype.public_id, doctype.system_id)?;
+        }
+        value.serialize(self)
+    }
+
+    /// Writes a primitive value, which representation never needs escaping
+    fn write_primitive<P: std::fmt::Display>(&mut self, value: P) -> Result<(), DeError> {
+        let text = &mut self.buffers.text;
+        text.clear();
+        write!(text, "{}", value).map_err(crate::errors::Error::Io)?;
+        self.writer
+            .write_event(Event::Text(BytesText::from_escaped(&text[..])))?;
+        self.has_text = true;
+        Ok(())
+    }
+
+    /// Writes a string value, escaping it only if it contains special characters
+    fn write_str(&mut self, value: &str) -> Result<(), DeError> {
+        let value = value.as_bytes();
+        let escaped = if self.config.needs_escaping(value) {
+            let text = &mut self.buffers.text;
+            text.clear();
+            self.config.escape_into(value, text);
+            &text[..]
+        } else {
+            value
+        };
+        self.writer
+            .write_event(Event::Text(BytesText::from_escaped(escaped)))?;
+        self.has_text = true;
+        Ok(())
+    }
+
+    /// Writes bytes as hexadecimal digits, grouped according to `bytes_hex_grouping`
+    fn write_hex(&mut self, value: &[u8]) -> Result<(), DeError> {
+        const DIGITS: &[u8; 16] = b"0123456789ABCDEF";
+
+        let text = &mut self.buffers.text;
+        text.clear();
+        for (i, byte) in value.iter().enumerate() {
+            match self.config.bytes_hex_grouping {
+                Some(group) if i > 0 && i % group == 0 => text.push(b' '),
+                _ => {}
+            }
+            text.push(DIGITS[usize::from(byte >> 4)]);
+            text.push(DIGITS[usize::from(byte & 0xF)]);
+        }
+        self.writer
+            .write_event(Event::Text(BytesText::from_escaped(&text[..])))?;
+        self.has_text = true;
+        Ok(())
+    }
+
+    /// Writes self-closed tag `<tag_name/>` into inner writer
+    fn write_self_closed(&mut self, tag_name: &str) -> Result<(), DeError> {
+        self.writer
+            .write_event(Event::Empty(BytesStart::borrowed_name(tag_name.as_bytes())))?;
+        Ok(())
+    }
+
+    /// Writes a serialized `value` surrounded by `<tag_name>...</tag_name>`
+    fn write_paired<T: ?Sized + Serialize>(
+        &mut self,
+        tag_name: &str,
+        value: &T,
+    ) -> Result<(), DeError> {
+        self.field = false;
+        self.writer
+            .write_event(Event::Start(BytesStart::borrowed_name(tag_name.as_bytes())))?;
+        self.serialize_inner(value)?;
+        self.writer
+            .write_event(Event::End(BytesEnd::borrowed(tag_name.as_bytes())))?;
+        Ok(())
+    }
+
+    /// Serializes `value` inside of an element, written by this serializer, so the value
+    /// is not the root of the document
+    fn serialize_inner<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), DeError> {
+        let root = std::mem::replace(&mut self.root, false);
+        let result = self.serialize_adapted(value);
+        self.root = root;
+        result
+    }
+}
+
+impl<'r, 'w, W: Write> ser::Serializer for &'w mut Serializer<'r, W> {
+    type Ok = ();
+    type Error = DeError;
+
+    type SerializeSeq = Seq<'r, 'w, W>;
+    type SerializeTuple = Tuple<'r, 'w, W>;
+    type SerializeTupleStruct = Tuple<'r, 'w, W>;
+    type SerializeTupleVariant = Tuple<'r, 'w, W>;
+    type SerializeMap = Map<'r, 'w, W>;
+    type SerializeStruct = Struct<'r, 'w, W>;
+    type SerializeStructVariant = Struct<'r, 'w, W>;
+
+    fn serialize_bool(self, v: bool) -> Result<Self::Ok, DeError> {
+        self.write_str(self.config.bool_text(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Self::Ok, DeError> {
+        self.write_primitive(v)
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Self::Ok, DeError> {
+        self.write_primitive(v)
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Self::Ok, DeError> {
+        self.write_primitive(v)
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Self::Ok, DeError> {
+        self.write_primitive(v)
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Self::Ok, DeError> {
+        self.write_primitive(v)
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Self::Ok, DeError> {
+        self.write_primitive(v)
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Self::Ok, DeError> {
+        self.write_primitive(v)
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Self::Ok, DeError> {
+        self.write_primitive(v)
+    }
+
+    serde_if_integer128! {
+        fn serialize_i128(self, v: i128) -> Result<Self::Ok, DeError> {
+            self.write_primitive(v)
+        }
+
+        fn serialize_u128(self, v: u128) -> Result<Self::Ok, DeError> {
+            self.write_primitive(v)
         }
     }
 
     fn serialize_f32(self, v: f32) -> Result<Self::Ok, DeError> {
-        self.write_primitive(v, true)
+        self.write_primitive(v)
     }
 
     fn serialize_f64(self, v: f64) -> Result<Self::Ok, DeError> {
-        self.write_primitive(v, true)
+        self.write_primitive(v)
     }
 
     fn serialize_char(self, v: char) -> Result<Self::Ok, DeError> {
-        self.write_primitive(v, false)
+        self.write_str(v.encode_utf8(&mut [0; 4]))
     }
 
     fn serialize_str(self, value: &str) -> Result<Self::Ok, DeError> {
-        self.write_primitive(value, false)
+        self.write_str(value)
     }
 
-    fn serialize_bytes(self, _value: &[u8]) -> Result<Self::Ok, DeError> {
-        // TODO: I imagine you'd want to use base64 here.
-        // Not sure how to roundtrip effectively though...
-        Err(DeError::Unsupported("serialize_bytes"))
+    fn serialize_bytes(self, value: &[u8]) -> Result<Self::Ok, DeError> {
+        match self.config.bytes_encoding {
+            BytesEncoding::Unsupported => Err(DeError::Unsupported("serialize_bytes")),
+            BytesEncoding::Hex => self.write_hex(value),
+        }
     }
 
     fn serialize_none(self) -> Result<Self::Ok, DeError> {
@@ -215,7 +1246,7 @@ impl<'r, 'w, W: Write> ser::Serializer for &'w mut Serializer<'r, W> {
     }
 
     fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<Self::Ok, DeError> {
-        value.serialize(self)
+        self.serialize_adapted(value)
     }
 
     fn serialize_unit(self) -> Result<Self::Ok, DeError> {
@@ -232,7 +1263,11 @@ impl<'r, 'w, W: Write> ser::Serializer for &'w mut Serializer<'r, W> {
         _variant_index: u32,
         variant: &'static str,
     ) -> Result<Self::Ok, DeError> {
-        self.write_self_closed(variant)
+        if self.field && self.config.unit_variant_format == UnitVariantFormat::Text {
+            self.write_str(variant)
+        } else {
+            self.write_self_closed(variant)
+        }
     }
 
     fn serialize_newtype_struct<T: ?Sized + Serialize>(
@@ -262,10 +1297,43 @@ impl<'r, 'w, W: Write> ser::Serializer for &'w mut Serializer<'r, W> {
     }
 
     fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, DeError> {
-        Ok(Seq::new(self))
+        let mut wrapper = self.seq_wrapper.take();
+        // Items of a sequence would be the roots of the document, so write them inside of
+        // the root element, unless a fragment without one is explicitly allowed
+        let mut root_tag = None;
+        if self.root && !self.config.allow_fragment {
+            match (self.config.root_seq_wrapper, self.root_tag) {
+                (Some(name), _) => wrapper = Some(name),
+                (None, Some(tag)) => {
+                    wrapper = Some(tag);
+                    root_tag = self.root_tag.take();
+                }
+                (None, None) => {
+                    return Err(DeError::Custom(
+                        "root element name must be specified when serialize a sequence: \
+                         use `Serializer::with_root` or `Serializer::wrap_sequences_in_root`, \
+                         or `Serializer::allow_fragment` to write the items without it"
+                            .into(),
+                    ))
+                }
+            }
+        }
+        if self.entry == EntryValue::Any {
+            // Items of a map value are written as the entries instead of inside of one
+            self.writer.unnest();
+            self.entry = EntryValue::Seq;
+        } else if wrapper.is_some() {
+            self.entry = EntryValue::Seq;
+        }
+        let root = std::mem::replace(&mut self.root, false);
+        self.field = false;
+        Ok(Seq::new(self, wrapper).root(root, root_tag))
     }
 
     fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, DeError> {
+        if std::mem::replace(&mut self.pair, false) {
+            return Ok(Tuple::pair(self));
+        }
         let tag = match self.root_tag {
             Some(tag) => tag,
             None => {
@@ -274,6 +1342,7 @@ impl<'r, 'w, W: Write> ser::Serializer for &'w mut Serializer<'r, W> {
                 ))
             }
         };
+        self.field = false;
         Ok(Tuple::new(self, tag))
     }
 
@@ -282,6 +1351,7 @@ impl<'r, 'w, W: Write> ser::Serializer for &'w mut Serializer<'r, W> {
         name: &'static str,
         _len: usize,
     ) -> Result<Self::SerializeTupleStruct, DeError> {
+        self.field = false;
         Ok(Tuple::new(self, self.root_tag.unwrap_or(name)))
     }
 
@@ -292,10 +1362,12 @@ impl<'r, 'w, W: Write> ser::Serializer for &'w mut Serializer<'r, W> {
         variant: &'static str,
         _len: usize,
     ) -> Result<Self::SerializeTupleVariant, DeError> {
+        self.field = false;
         Ok(Tuple::new(self, variant))
     }
 
     fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, DeError> {
+        self.field = false;
         if let Some(tag) = self.root_tag {
             // TODO: Write self-closed tag if map is empty
             self.writer
@@ -307,9 +1379,9 @@ impl<'r, 'w, W: Write> ser::Serializer for &'w mut Serializer<'r, W> {
     fn serialize_struct(
         self,
         name: &'static str,
-        _len: usize,
+        len: usize,
     ) -> Result<Self::SerializeStruct, DeError> {
-        Ok(Struct::new(self, self.root_tag.unwrap_or(name)))
+        Ok(Struct::new(self, self.root_tag.unwrap_or(name), len))
     }
 
     fn serialize_struct_variant(
@@ -317,249 +1389,1688 @@ impl<'r, 'w, W: Write> ser::Serializer for &'w mut Serializer<'r, W> {
         _name: &'static str,
         _variant_index: u32,
         variant: &'static str,
-        _len: usize,
+        len: usize,
     ) -> Result<Self::SerializeStructVariant, DeError> {
-        Ok(Struct::new(self, variant))
+        Ok(Struct::new(self, variant, len))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::ser::SerializeMap;
+    use serde::{Deserialize, Serialize, Serializer as SerSerializer};
+
+    #[test]
+    fn test_serialize_bool() {
+        let inputs = vec![(true, "true"), (false, "false")];
+
+        for (src, should_be) in inputs {
+            let mut buffer = Vec::new();
+
+            {
+                let mut ser = Serializer::new(&mut buffer);
+                ser.serialize_bool(src).unwrap();
+            }
+
+            let got = String::from_utf8(buffer).unwrap();
+            assert_eq!(got, should_be);
+        }
+    }
+
+    #[test]
+    fn test_serialize_struct() {
+        #[derive(Serialize)]
+        struct Person {
+            name: String,
+            age: u32,
+        }
+
+        let bob = Person {
+            name: "Bob".to_string(),
+            age: 42,
+        };
+        let should_be = "<Person name=\"Bob\" age=\"42\"/>";
+        let mut buffer = Vec::new();
+
+        {
+            let mut ser = Serializer::new(&mut buffer);
+            bob.serialize(&mut ser).unwrap();
+        }
+
+        let got = String::from_utf8(buffer).unwrap();
+        assert_eq!(got, should_be);
+    }
+
+    #[test]
+    fn test_serialize_struct_value_number() {
+        #[derive(Serialize)]
+        struct Person {
+            name: String,
+            #[serde(rename = "$value")]
+            age: u32,
+        }
+
+        let bob = Person {
+            name: "Bob".to_string(),
+            age: 42,
+        };
+        let should_be = "<Person name=\"Bob\">42</Person>";
+        let got = to_string(&bob).unwrap();
+        assert_eq!(got, should_be);
+    }
+
+    #[test]
+    fn test_serialize_struct_value_string() {
+        #[derive(Serialize)]
+        struct Person {
+            name: String,
+            #[serde(rename = "$value")]
+            age: String,
+        }
+
+        let bob = Person {
+            name: "Bob".to_string(),
+            age: "42".to_string(),
+        };
+        let should_be = "<Person name=\"Bob\">42</Person>";
+        let got = to_string(&bob).unwrap();
+        assert_eq!(got, should_be);
+    }
+
+    #[test]
+    fn test_serialize_map_entries() {
+        let should_be = "<name>Bob</name><age>5</age>";
+        let mut buffer = Vec::new();
+
+        {
+            let mut ser = Serializer::new(&mut buffer);
+            let mut map = Map::new(&mut ser);
+            map.serialize_entry("name", "Bob").unwrap();
+            map.serialize_entry("age", "5").unwrap();
+        }
+
+        let got = String::from_utf8(buffer).unwrap();
+        assert_eq!(got, should_be);
+    }
+
+    #[test]
+    fn test_serialize_map_of_sequences() {
+        use std::collections::BTreeMap;
+
+        let mut map = BTreeMap::new();
+        map.insert("host", vec!["alpha", "beta"]);
+        map.insert("port", vec!["80", "443", "8080"]);
+
+        let should_be = "<config>\
+            <host>alpha</host><host>beta</host>\
+            <port>80</port><port>443</port><port>8080</port>\
+            </config>";
+        let mut buffer = Vec::new();
+
+        {
+            let mut ser = Serializer::with_root(Writer::new(&mut buffer), Some("config"));
+            map.serialize(&mut ser).unwrap();
+        }
+
+        let got = String::from_utf8(buffer).unwrap();
+        assert_eq!(got, should_be);
+    }
+
+    #[test]
+    fn map_keys() {
+        use std::collections::BTreeMap;
+
+        fn serialize<K: Serialize + Ord>(
+            key: K,
+            checks: bool,
+            config: fn(&mut Serializer<&mut Vec<u8>>),
+        ) -> String {
+            let mut map = BTreeMap::new();
+            map.insert(key, 1);
+            let mut buffer = Vec::new();
+            {
+                let mut writer = Writer::new(&mut buffer);
+                // Keys are serialized by a nested serializer when the writer checks events
+                writer.with_checks(checks);
+                let mut ser = Serializer::with_root(writer, Some("map"));
+                config(&mut ser);
+                map.serialize(&mut ser).unwrap();
+            }
+            String::from_utf8(buffer).unwrap()
+        }
+        fn check<K: Serialize + Ord + Copy>(
+            key: K,
+            expected: &str,
+            config: fn(&mut Serializer<&mut Vec<u8>>),
+        ) {
+            let expected = format!("<map><{0}>1</{0}></map>", expected);
+            assert_eq!(serialize(key, false, config), expected);
+            assert_eq!(serialize(key, true, config), expected);
+        }
+
+        check("name", "name", |_| {});
+        check('c', "c", |_| {});
+        check(42u32, "42", |_| {});
+        check(-7i64, "-7", |_| {});
+        check(true, "true", |_| {});
+        check(false, "0", |ser| {
+            ser.bool_format(BoolFormat::OneZero);
+        });
+        check(Some("name"), "name", |_| {});
+        check("caf\u{E9}", "caf\u{E9}", |_| {});
+        check("caf\u{E9}", "caf&#233;", |ser| {
+            ser.escape_non_ascii(true);
+        });
+    }
+
+    #[test]
+    fn map_newtype_and_variant_keys() {
+        use std::collections::BTreeMap;
+
+        #[derive(Serialize, PartialEq, Eq, PartialOrd, Ord)]
+        struct Id(String);
+
+        #[derive(Serialize, PartialEq, Eq, PartialOrd, Ord)]
+        enum Color {
+            Red,
+            #[serde(rename = "dark-blue")]
+            DarkBlue,
+        }
+
+        fn serialize<K: Serialize + Ord>(keys: Vec<K>, checks: bool) -> String {
+            let map: BTreeMap<_, _> = keys.into_iter().zip(1..).collect();
+            let mut buffer = Vec::new();
+            let mut writer = Writer::new(&mut buffer);
+            writer.with_checks(checks);
+            let mut ser = Serializer::with_root(writer, Some("map"));
+            map.serialize(&mut ser).unwrap();
+            String::from_utf8(buffer).unwrap()
+        }
+
+        for &checks in &[false, true] {
+            assert_eq!(
+                serialize(vec![Id("a".into()), Id("b".into())], checks),
+                "<map><a>1</a><b>2</b></map>"
+            );
+            assert_eq!(
+                serialize(vec![Color::Red, Color::DarkBlue], checks),
+                "<map><Red>1</Red><dark-blue>2</dark-blue></map>"
+            );
+        }
+    }
+
+    #[test]
+    fn map_struct_keys() {
+        use std::collections::HashMap;
+
+        #[derive(Serialize, PartialEq, Eq, Hash)]
+        struct StructKey {
+            id: u32,
+        }
+
+        fn serialize<K: Serialize + Eq + std::hash::Hash>(key: K, checks: bool) -> DeError {
+            let mut map = HashMap::new();
+            map.insert(key, 1);
+            let mut buffer = Vec::new();
+            let mut writer = Writer::new(&mut buffer);
+            writer.with_checks(checks);
+            let mut ser = Serializer::with_root(writer, Some("map"));
+            map.serialize(&mut ser).unwrap_err()
+        }
+
+        for &checks in &[false, true] {
+            match serialize(StructKey { id: 1 }, checks) {
+                DeError::Unsupported(message) => {
+                    assert_eq!(message, "map key must be a string-like value")
+                }
+                e => panic!("Expected `Unsupported`, found {:?}", e),
+            }
+            match serialize((1, 2), checks) {
+                DeError::Unsupported(_) => {}
+                e => panic!("Expected `Unsupported`, found {:?}", e),
+            }
+            match serialize(None::<&str>, checks) {
+                DeError::Unsupported(_) => {}
+                e => panic!("Expected `Unsupported`, found {:?}", e),
+            }
+        }
+    }
+
+    #[test]
+    fn test_serialize_enum() {
+        #[derive(Serialize)]
+        #[allow(dead_code)]
+        enum Node {
+            Boolean(bool),
+            Number(f64),
+            String(String),
+        }
+
+        let mut buffer = Vec::new();
+        let should_be = "<Boolean>true</Boolean>";
+
+        {
+            let mut ser = Serializer::new(&mut buffer);
+            let node = Node::Boolean(true);
+            node.serialize(&mut ser).unwrap();
+        }
+
+        let got = String::from_utf8(buffer).unwrap();
+        assert_eq!(got, should_be);
+    }
+
+    #[test]
+    #[ignore]
+    fn serialize_a_list() {
+        let inputs = vec![1, 2, 3, 4];
+
+        let mut buffer = Vec::new();
+
+        {
+            let mut ser = Serializer::new(&mut buffer);
+            inputs.serialize(&mut ser).unwrap();
+        }
+
+        let got = String::from_utf8(buffer).unwrap();
+        println!("{}", got);
+        panic!();
+    }
+
+    #[test]
+    fn unit() {
+        #[derive(Serialize)]
+        struct Unit;
+
+        let data = Unit;
+        let should_be = "<root/>";
+        let mut buffer = Vec::new();
+
+        {
+            let mut ser = Serializer::with_root(Writer::new(&mut buffer), Some("root"));
+            data.serialize(&mut ser).unwrap();
+        }
+
+        let got = String::from_utf8(buffer).unwrap();
+        assert_eq!(got, should_be);
+    }
+
+    #[test]
+    fn newtype() {
+        #[derive(Serialize)]
+        struct Newtype(bool);
+
+        let data = Newtype(true);
+        let should_be = "<root>true</root>";
+        let mut buffer = Vec::new();
+
+        {
+            let mut ser = Serializer::with_root(Writer::new(&mut buffer), Some("root"));
+            data.serialize(&mut ser).unwrap();
+        }
+
+        let got = String::from_utf8(buffer).unwrap();
+        assert_eq!(got, should_be);
+    }
+
+    #[test]
+    fn tuple() {
+        let data = (42.0, "answer");
+        let should_be = "<root>42</root>\n<root>answer</root>";
+        let mut buffer = Vec::new();
+
+        {
+            let mut ser =
+                Serializer::with_root(Writer::new_with_indent(&mut buffer, b' ', 4), Some("root"));
+            data.serialize(&mut ser).unwrap();
+        }
+
+        let got = String::from_utf8(buffer).unwrap();
+        assert_eq!(got, should_be);
+    }
+
+    #[test]
+    fn indentation_after_empty_elements() {
+        use std::collections::BTreeMap;
+
+        #[derive(Serialize)]
+        struct Empty {}
+
+        #[derive(Serialize)]
+        struct Text {
+            #[serde(rename = "$value")]
+            text: &'static str,
+        }
+
+        #[derive(Serialize)]
+        struct Inner {
+            first: Empty,
+            text: Text,
+            last: Empty,
+        }
+
+        #[derive(Serialize)]
+        struct Root {
+            empty: Empty,
+            inner: Inner,
+            map: BTreeMap<&'static str, Empty>,
+            seqs: BTreeMap<&'static str, Vec<u32>>,
+            tuple: (u32, &'static str),
+            text: Text,
+        }
+
+        let mut map = BTreeMap::new();
+        map.insert("key", Empty {});
+        let mut seqs = BTreeMap::new();
+        seqs.insert("item", vec![1, 2]);
+        let root = Root {
+            empty: Empty {},
+            inner: Inner {
+                first: Empty {},
+                text: Text { text: "text" },
+                last: Empty {},
+            },
+            map,
+            seqs,
+            tuple: (1, "text"),
+            text: Text { text: "" },
+        };
+        let should_be = r#"<Root>
+  <empty/>
+  <inner>
+    <first/>
+    <text>text</text>
+    <last/>
+  </inner>
+  <map>
+    <key>
+      <key/>
+    </key>
+  </map>
+  <seqs>
+    <item>1</item>
+    <item>2</item>
+  </seqs>
+  <tuple>1</tuple>
+  <tuple>text</tuple>
+  <text/>
+</Root>"#;
+
+        for minimize in &[false, true] {
+            let mut buffer = Vec::new();
+            {
+                let mut writer = Writer::new_with_indent(&mut buffer, b' ', 2);
+                writer.minimize_empty_elements(*minimize);
+                root.serialize(&mut Serializer::with_writer(writer, None))
+                    .unwrap();
+            }
+            assert_eq!(String::from_utf8(buffer).unwrap(), should_be);
+        }
+    }
+
+    #[test]
+    fn tuple_element_names() {
+        #[derive(Serialize)]
+        struct Struct {
+            name: &'static str,
+            point: (i32, i32, i32),
+        }
+
+        let mut buffer = Vec::new();
+        {
+            let mut ser = Serializer::with_root(Writer::new(&mut buffer), Some("point"));
+            ser.tuple_element_names(&["x", "y", "z"]);
+            (1, 2, 3).serialize(&mut ser).unwrap();
+        }
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            "<x>1</x><y>2</y><z>3</z>"
+        );
+
+        // Names are inherited by serializers of the fields
+        let mut buffer = Vec::new();
+        {
+            let mut ser = Serializer::with_root(Writer::new(&mut buffer), Some("root"));
+            ser.tuple_element_names(&["x", "y"]);
+            Struct {
+                name: "origin",
+                point: (0, 0, 0),
+            }
+            .serialize(&mut ser)
+            .unwrap();
+        }
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            r#"<root name="origin"><x>0</x><y>0</y><point>0</point></root>"#
+        );
+    }
+
+    #[test]
+    fn keep_empty_attributes() {
+        #[derive(Serialize)]
+        struct Struct {
+            empty: &'static str,
+            none: Option<&'static str>,
+            float: f64,
+        }
+
+        let value = Struct {
+            empty: "",
+            none: None,
+            float: 42.0,
+        };
+
+        let mut buffer = Vec::new();
+        {
+            let mut ser = Serializer::with_root(Writer::new(&mut buffer), Some("root"));
+            value.serialize(&mut ser).unwrap();
+        }
+        assert_eq!(String::from_utf8(buffer).unwrap(), r#"<root float="42"/>"#);
+
+        let mut buffer = Vec::new();
+        {
+            let mut ser = Serializer::with_root(Writer::new(&mut buffer), Some("root"));
+            ser.keep_empty_attributes(true);
+            value.serialize(&mut ser).unwrap();
+        }
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            r#"<root empty="" float="42"/>"#
+        );
+    }
+
+    #[test]
+    fn attribute_whitespaces() {
+        #[derive(Debug, Serialize, Deserialize, PartialEq)]
+        struct Struct {
+            text: String,
+            #[serde(rename = "$value")]
+            content: String,
+        }
+
+        let value = Struct {
+            text: "line\n\tindented\r\nlast\r".into(),
+            content: "a\nb".into(),
+        };
+
+        let xml = to_string(&value).unwrap();
+        assert_eq!(
+            xml,
+            "<Struct text=\"line&#10;&#9;indented&#13;&#10;last&#13;\">a\nb</Struct>"
+        );
+        assert_eq!(crate::de::from_str::<Struct>(&xml).unwrap(), value);
+        // The value is escaped after all other characters
+        let mut buffer = Vec::new();
+        {
+            let mut ser = Serializer::new(&mut buffer);
+            ser.escape_level(EscapeLevel::Minimal);
+            Struct {
+                text: "\"\n>".into(),
+                content: String::new(),
+            }
+            .serialize(&mut ser)
+            .unwrap();
+        }
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            r#"<Struct text="&quot;&#10;>"/>"#
+        );
+
+        let mut buffer = Vec::new();
+        {
+            let mut ser = Serializer::new(&mut buffer);
+            ser.keep_attribute_whitespaces(true);
+            value.serialize(&mut ser).unwrap();
+        }
+        let xml = String::from_utf8(buffer).unwrap();
+        assert_eq!(
+            xml,
+            "<Struct text=\"line\n\tindented\r\nlast\r\">a\nb</Struct>"
+        );
+        let read = crate::de::from_str::<Struct>(&xml).unwrap();
+        assert_eq!(read.text, "line  indented last ");
+    }
+
+    #[test]
+    fn bool_format() {
+        #[derive(Serialize)]
+        struct Child {
+            #[serde(rename = "$value")]
+            text: bool,
+        }
+
+        #[derive(Serialize)]
+        struct Struct {
+            yes: bool,
+            no: bool,
+            child: Child,
+        }
+
+        let value = Struct {
+            yes: true,
+            no: false,
+            child: Child { text: true },
+        };
+        let serialize = |format| {
+            let mut buffer = Vec::new();
+            {
+                let mut ser = Serializer::with_root(Writer::new(&mut buffer), Some("root"));
+                ser.bool_format(format);
+                value.serialize(&mut ser).unwrap();
+            }
+            String::from_utf8(buffer).unwrap()
+        };
+
+        assert_eq!(
+            serialize(BoolFormat::TrueFalse),
+            r#"<root yes="true" no="false"><child>true</child></root>"#
+        );
+        assert_eq!(
+            serialize(BoolFormat::OneZero),
+            r#"<root yes="1" no="0"><child>1</child></root>"#
+        );
+    }
+
+    #[test]
+    fn bytes_hex_grouping() {
+        struct Bytes<'a>(&'a [u8]);
+
+        impl<'a> Serialize for Bytes<'a> {
+            fn serialize<S: ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_bytes(self.0)
+            }
+        }
+
+        #[derive(Serialize)]
+        struct Child<'a> {
+            #[serde(rename = "$value")]
+            data: Bytes<'a>,
+        }
+
+        #[derive(Serialize)]
+        struct Struct<'a> {
+            mask: Bytes<'a>,
+            empty: Bytes<'a>,
+            child: Child<'a>,
+        }
+
+        let value = Struct {
+            mask: Bytes(&[0x00, 0xFF, 0x0A]),
+            empty: Bytes(&[]),
+            child: Child {
+                data: Bytes(&[0xDE, 0xAD, 0xBE, 0xEF, 0x01]),
+            },
+        };
+        let serialize = |encoding, group| {
+            let mut buffer = Vec::new();
+            let result = {
+                let mut ser = Serializer::with_root(Writer::new(&mut buffer), Some("root"));
+                ser.bytes_encoding(encoding).bytes_hex_grouping(group);
+                value.serialize(&mut ser)
+            };
+            result.map(|_| String::from_utf8(buffer).unwrap())
+        };
+
+        assert_eq!(
+            serialize(BytesEncoding::Hex, None).unwrap(),
+            r#"<root mask="00FF0A"><child>DEADBEEF01</child></root>"#
+        );
+        assert_eq!(
+            serialize(BytesEncoding::Hex, Some(1)).unwrap(),
+            r#"<root mask="00 FF 0A"><child>DE AD BE EF 01</child></root>"#
+        );
+        assert_eq!(
+            serialize(BytesEncoding::Hex, Some(2)).unwrap(),
+            r#"<root mask="00FF 0A"><child>DEAD BEEF 01</child></root>"#
+        );
+        assert_eq!(
+            serialize(BytesEncoding::Hex, Some(0)).unwrap(),
+            r#"<root mask="00FF0A"><child>DEADBEEF01</child></root>"#
+        );
+        match serialize(BytesEncoding::Unsupported, Some(1)) {
+            Err(DeError::Unsupported("serialize_bytes")) => {}
+            e => panic!("Expected `Unsupported`, found {:?}", e),
+        }
+    }
+
+    #[test]
+    fn adapters() {
+        use std::collections::BTreeMap;
+
+        #[derive(Serialize, Deserialize)]
+        struct Millis(u64);
+
+        /// Writes milliseconds as an `xs:duration`
+        fn duration(value: &Millis) -> String {
+            format!("PT{}.{:03}S", value.0 / 1000, value.0 % 1000)
+        }
+
+        #[derive(Serialize)]
+        struct Child {
+            #[serde(rename = "$value")]
+            elapsed: Millis,
+        }
+
+        #[derive(Serialize)]
+        struct Struct {
+            timeout: Millis,
+            retry: Option<Millis>,
+            child: Child,
+            limits: BTreeMap<&'static str, Millis>,
+        }
+
+        let mut limits = BTreeMap::new();
+        limits.insert("read", Millis(250));
+        let value = Struct {
+            timeout: Millis(1500),
+            retry: Some(Millis(20)),
+            child: Child {
+                elapsed: Millis(61_000),
+            },
+            limits,
+        };
+        let mut buffer = Vec::new();
+        {
+            let mut ser = Serializer::with_root(Writer::new(&mut buffer), Some("root"));
+            ser.register_adapter(duration);
+            value.serialize(&mut ser).unwrap();
+        }
+
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            r#"<root timeout="PT1.500S" retry="PT0.020S"><child>PT61.000S</child><limits><read>PT0.250S</read></limits></root>"#
+        );
+    }
+
+    #[test]
+    fn empty_seq_element() {
+        #[derive(Serialize)]
+        struct Item {
+            id: u32,
+        }
+
+        #[derive(Serialize)]
+        struct Struct {
+            item: Vec<Item>,
+        }
+
+        let serialize = |value: &Struct, policy| {
+            let mut buffer = Vec::new();
+            {
+                let mut ser = Serializer::with_root(Writer::new(&mut buffer), Some("root"));
+                ser.empty_seq_element(policy);
+                value.serialize(&mut ser).unwrap();
+            }
+            String::from_utf8(buffer).unwrap()
+        };
+
+        let empty = Struct { item: Vec::new() };
+        assert_eq!(serialize(&empty, EmptySeqPolicy::Omit), "<root/>");
+        assert_eq!(
+            serialize(&empty, EmptySeqPolicy::EmptyElement),
+            "<root><item/></root>"
+        );
+
+        let filled = Struct {
+            item: vec![Item { id: 1 }, Item { id: 2 }],
+        };
+        let expected = r#"<root><item id="1"/><item id="2"/></root>"#;
+        assert_eq!(serialize(&filled, EmptySeqPolicy::Omit), expected);
+        assert_eq!(serialize(&filled, EmptySeqPolicy::EmptyElement), expected);
+    }
+
+    #[test]
+    fn flush_each_element() {
+        /// Writer that counts calls to `flush`
+        #[derive(Default)]
+        struct Flushes {
+            buffer: Vec<u8>,
+            flushes: usize,
+        }
+
+        impl Write for Flushes {
+            fn write(&mut self, buf: &[u8]) -> ::std::io::Result<usize> {
+                self.buffer.write(buf)
+            }
+
+            fn flush(&mut self) -> ::std::io::Result<()> {
+                self.flushes += 1;
+                Ok(())
+            }
+        }
+
+        #[derive(Serialize)]
+        struct Item {
+            id: u32,
+        }
+
+        let items = vec![Item { id: 1 }, Item { id: 2 }, Item { id: 3 }];
+        let serialize = |flush| {
+            let mut writer = Flushes::default();
+            {
+                let mut ser = Serializer::with_root(Writer::new(&mut writer), Some("item"));
+                ser.flush_each_element(flush).allow_fragment(true);
+                items.serialize(&mut ser).unwrap();
+            }
+            writer
+        };
+
+        let writer = serialize(true);
+        assert_eq!(writer.flushes, 3);
+        assert_eq!(
+            String::from_utf8(writer.buffer).unwrap(),
+            r#"<item id="1"/><item id="2"/><item id="3"/>"#
+        );
+        assert_eq!(serialize(false).flushes, 0);
+    }
+
+    #[test]
+    fn root_sequence() {
+        #[derive(Serialize)]
+        struct Item {
+            id: u32,
+        }
+
+        let items = vec![Item { id: 1 }, Item { id: 2 }];
+        let empty: Vec<Item> = Vec::new();
+        let serialize = |items: &Vec<Item>, root, configure: fn(&mut Serializer<&mut Vec<u8>>)| {
+            let mut buffer = Vec::new();
+            let result = {
+                let mut ser = Serializer::with_root(Writer::new(&mut buffer), root);
+                configure(&mut ser);
+                items.serialize(&mut ser)
+            };
+            result.map(|_| String::from_utf8(buffer).unwrap())
+        };
+
+        // Without the name of the root element
+        for items in &[&items, &empty] {
+            match serialize(items, None, |_| {}) {
+                Err(DeError::Custom(message)) => {
+                    assert!(message.contains("Serializer::with_root"), "{}", message)
+                }
+                e => panic!("Expected `Err(Custom(_))`, but got `{:?}`", e),
+            }
+        }
+
+        // Wrapped into the root element
+        let wrap: fn(&mut Serializer<&mut Vec<u8>>) = |ser| {
+            ser.wrap_sequences_in_root("items");
+        };
+        assert_eq!(
+            serialize(&items, None, wrap).unwrap(),
+            r#"<items><Item id="1"/><Item id="2"/></items>"#
+        );
+        assert_eq!(
+            serialize(&items, Some("item"), wrap).unwrap(),
+            r#"<items><item id="1"/><item id="2"/></items>"#
+        );
+        assert_eq!(
+            serialize(&items, Some("items"), |_| {}).unwrap(),
+            r#"<items><Item id="1"/><Item id="2"/></items>"#
+        );
+        assert_eq!(serialize(&empty, None, wrap).unwrap(), "<items/>");
+        assert_eq!(
+            serialize(&empty, Some("items"), |_| {}).unwrap(),
+            "<items/>"
+        );
+
+        // A fragment without the root element
+        let fragment: fn(&mut Serializer<&mut Vec<u8>>) = |ser| {
+            ser.wrap_sequences_in_root("items").allow_fragment(true);
+        };
+        assert_eq!(
+            serialize(&items, None, fragment).unwrap(),
+            r#"<Item id="1"/><Item id="2"/>"#
+        );
+        assert_eq!(
+            serialize(&items, Some("item"), fragment).unwrap(),
+            r#"<item id="1"/><item id="2"/>"#
+        );
+        assert_eq!(serialize(&empty, None, fragment).unwrap(), "");
+
+        // Sequences inside of the root element are not affected
+        #[derive(Serialize)]
+        struct Root {
+            item: Vec<Item>,
+        }
+        let root = Root { item: items };
+        let mut buffer = Vec::new();
+        root.serialize(&mut Serializer::new(&mut buffer)).unwrap();
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            r#"<Root><item id="1"/><item id="2"/></Root>"#
+        );
+    }
+
+    #[test]
+    fn tuple_struct() {
+        #[derive(Serialize)]
+        struct Tuple(f32, &'static str);
+
+        let data = Tuple(42.0, "answer");
+        let should_be = "<root>42</root>\n<root>answer</root>";
+        let mut buffer = Vec::new();
+
+        {
+            let mut ser =
+                Serializer::with_root(Writer::new_with_indent(&mut buffer, b' ', 4), Some("root"));
+            data.serialize(&mut ser).unwrap();
+        }
+
+        let got = String::from_utf8(buffer).unwrap();
+        assert_eq!(got, should_be);
+    }
+
+    #[test]
+    fn struct_() {
+        #[derive(Serialize)]
+        struct Struct {
+            float: f64,
+            string: String,
+        }
+
+        let mut buffer = Vec::new();
+        let should_be = r#"<root float="42" string="answer"/>"#;
+
+        {
+            let mut ser =
+                Serializer::with_root(Writer::new_with_indent(&mut buffer, b' ', 4), Some("root"));
+            let node = Struct {
+                float: 42.0,
+                string: "answer".to_string(),
+            };
+            node.serialize(&mut ser).unwrap();
+        }
+
+        let got = String::from_utf8(buffer).unwrap();
+        assert_eq!(got, should_be);
+    }
+
+    #[test]
+    fn nested_struct() {
+        #[derive(Serialize)]
+        struct Struct {
+            nested: Nested,
+            string: String,
+        }
+
+        #[derive(Serialize)]
+        struct Nested {
+            float: f64,
+        }
+
+        let mut buffer = Vec::new();
+        let should_be = r#"<root string="answer">
+    <nested float="42"/>
+</root>"#;
+
+        {
+            let mut ser =
+                Serializer::with_root(Writer::new_with_indent(&mut buffer, b' ', 4), Some("root"));
+            let node = Struct {
+                nested: Nested { float: 42.0 },
+                string: "answer".to_string(),
+            };
+            node.serialize(&mut ser).unwrap();
+        }
+
+        let got = String::from_utf8(buffer).unwrap();
+        assert_eq!(got, should_be);
+    }
+
+    /// Structs, which fields written as attributes come first, write their children without
+    /// buffering them. The result should be the same, as with buffering
+    #[test]
+    fn attributes_first() {
+        #[derive(Serialize)]
+        struct Leaf {
+            id: u32,
+            #[serde(rename = "$value")]
+            text: &'static str,
+        }
+
+        #[derive(Serialize)]
+        struct Branch {
+            name: &'static str,
+            empty: Option<u32>,
+            #[serde(rename = "$unflatten=title")]
+            title: &'static str,
+            leaf: Vec<Leaf>,
+            child: Option<Box<Branch>>,
+            none: Option<Leaf>,
+            #[serde(rename = "$value")]
+            text: &'static str,
+        }
+
+        /// The attribute after the child element, so the children are buffered
+        #[derive(Serialize)]
+        struct Mixed {
+            branch: Branch,
+            attribute: bool,
+        }
+
+        let branch = Branch {
+            name: "outer",
+            empty: None,
+            title: "Outer",
+            leaf: vec![
+                Leaf {
+                    id: 1,
+                    text: "first",
+                },
+                Leaf { id: 2, text: "" },
+            ],
+            child: Some(Box::new(Branch {
+                name: "inner",
+                empty: None,
+                title: "Inner",
+                leaf: vec![],
+                child: None,
+                none: None,
+                text: "",
+            })),
+            none: None,
+            text: "text",
+        };
+        let expected = r#"<root name="outer">
+  <title>Outer</title>
+  <leaf id="1">first</leaf>
+  <leaf id="2"/>
+  <child name="inner">
+    <title>Inner</title>
+  </child>text</root>"#;
+
+        let mut buffer = Vec::new();
+        {
+            let writer = Writer::new_with_indent(&mut buffer, b' ', 2);
+            let mut ser = Serializer::with_root(writer, Some("root"));
+            ser.probe_struct(&branch).unwrap();
+            assert!(ser.attributes_first);
+            branch.serialize(&mut ser).unwrap();
+        }
+        assert_eq!(String::from_utf8(buffer).unwrap(), expected);
+
+        // The same without streaming of the top-level struct
+        let mut buffer = Vec::new();
+        {
+            let writer = Writer::new_with_indent(&mut buffer, b' ', 2);
+            let mut ser = Serializer::with_root(writer, Some("root"));
+            branch.serialize(&mut ser).unwrap();
+        }
+        assert_eq!(String::from_utf8(buffer).unwrap(), expected);
+
+        let mixed = Mixed {
+            branch,
+            attribute: true,
+        };
+        let mut buffer = Vec::new();
+        {
+            let mut ser = Serializer::with_root(Writer::new(&mut buffer), Some("root"));
+            ser.probe_struct(&mixed).unwrap();
+            assert!(!ser.attributes_first);
+            mixed.serialize(&mut ser).unwrap();
+        }
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            "<root attribute=\"true\">\
+                <branch name=\"outer\">\
+                    <title>Outer</title>\
+                    <leaf id=\"1\">first</leaf>\
+                    <leaf id=\"2\"/>\
+                    <child name=\"inner\"><title>Inner</title></child>\
+                    text\
+                </branch>\
+            </root>"
+        );
+    }
+
+    mod escape_level {
+        use super::*;
+        use crate::escape::EscapeLevel;
+
+        #[derive(Serialize)]
+        struct Root {
+            attribute: &'static str,
+            #[serde(rename = "$value")]
+            text: &'static str,
+        }
+
+        fn serialize(level: EscapeLevel) -> String {
+            let mut buffer = Vec::new();
+            let mut ser = Serializer::new(&mut buffer);
+            ser.escape_level(level);
+            let specials = "<&>'\" \u{444}";
+            Root {
+                attribute: specials,
+                text: specials,
+            }
+            .serialize(&mut ser)
+            .unwrap();
+            String::from_utf8(buffer).unwrap()
+        }
+
+        #[test]
+        fn minimal() {
+            assert_eq!(
+                serialize(EscapeLevel::Minimal),
+                "<Root attribute=\"&lt;&amp;>'&quot; \u{444}\">&lt;&amp;>'\" \u{444}</Root>"
+            );
+        }
+
+        #[test]
+        fn standard() {
+            assert_eq!(
+                serialize(EscapeLevel::Standard),
+                "<Root attribute=\"&lt;&amp;&gt;&apos;&quot; \u{444}\">\
+                    &lt;&amp;&gt;&apos;&quot; \u{444}\
+                </Root>"
+            );
+        }
+
+        #[test]
+        fn aggressive() {
+            assert_eq!(
+                serialize(EscapeLevel::Aggressive),
+                "<Root attribute=\"&lt;&amp;&gt;&apos;&quot; &#x444;\">\
+                    &lt;&amp;&gt;&apos;&quot; &#x444;\
+                </Root>"
+            );
+        }
+    }
+
+    mod escape_non_ascii {
+        use super::*;
+        use crate::de::from_str;
+        use crate::escape::{CharRefFormat, EscapeLevel};
+        use serde::Deserialize;
+
+        #[derive(Debug, Serialize, Deserialize, PartialEq)]
+        struct Root {
+            attribute: String,
+            symbol: char,
+            item: Item,
+        }
+
+        #[derive(Debug, Serialize, Deserialize, PartialEq)]
+        struct Item {
+            name: String,
+            #[serde(rename = "$value")]
+            text: String,
+        }
+
+        fn root() -> Root {
+            Root {
+                attribute: "caf\u{E9} \"au lait\"".into(),
+                symbol: '\u{1F600}',
+                item: Item {
+                    name: "\u{65E5}\u{672C}".into(),
+                    text: "<\u{1D11E}> & \u{444}".into(),
+                },
+            }
+        }
+
+        fn serialize(level: EscapeLevel, format: CharRefFormat) -> String {
+            let mut buffer = Vec::new();
+            let mut ser = Serializer::new(&mut buffer);
+            ser.escape_level(level)
+                .escape_non_ascii(true)
+                .char_ref_format(format);
+            root().serialize(&mut ser).unwrap();
+            String::from_utf8(buffer).unwrap()
+        }
+
+        #[test]
+        fn decimal() {
+            assert_eq!(
+                serialize(EscapeLevel::Standard, CharRefFormat::Decimal),
+                "<Root attribute=\"caf&#233; &quot;au lait&quot;\" symbol=\"&#128512;\">\
+                    <item name=\"&#26085;&#26412;\">&lt;&#119070;&gt; &amp; &#1092;</item>\
+                </Root>"
+            );
+        }
+
+        #[test]
+        fn hexadecimal() {
+            assert_eq!(
+                serialize(EscapeLevel::Minimal, CharRefFormat::Hexadecimal),
+                "<Root attribute=\"caf&#xE9; &quot;au lait&quot;\" symbol=\"&#x1F600;\">\
+                    <item name=\"&#x65E5;&#x672C;\">&lt;&#x1D11E;> &amp; &#x444;</item>\
+                </Root>"
+            );
+        }
+
+        #[test]
+        fn round_trip() {
+            for &level in &[
+                EscapeLevel::Minimal,
+                EscapeLevel::Standard,
+                EscapeLevel::Aggressive,
+            ] {
+                for &format in &[CharRefFormat::Decimal, CharRefFormat::Hexadecimal] {
+                    let xml = serialize(level, format);
+                    assert!(xml.is_ascii(), "{}", xml);
+                    assert_eq!(from_str::<Root>(&xml).unwrap(), root());
+                }
+            }
+        }
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use serde::ser::SerializeMap;
-    use serde::{Serialize, Serializer as SerSerializer};
+    mod seq_wrappers {
+        use super::*;
+        use crate::de::from_str;
+        use serde::{Deserialize, Deserializer};
+
+        #[derive(Debug, Serialize, Deserialize, PartialEq)]
+        struct Order {
+            id: u32,
+            #[serde(alias = "items", deserialize_with = "unwrap_items")]
+            item: Vec<Item>,
+            #[serde(rename = "code", default)]
+            codes: Vec<u32>,
+            note: Option<Vec<u32>>,
+        }
 
-    #[test]
-    fn test_serialize_bool() {
-        let inputs = vec![(true, "true"), (false, "false")];
+        #[derive(Debug, Serialize, Deserialize, PartialEq)]
+        struct Item {
+            name: String,
+            #[serde(rename = "$value", default)]
+            text: String,
+        }
 
-        for (src, should_be) in inputs {
-            let mut buffer = Vec::new();
+        /// Reads items, written inside of the `<items>` wrapper
+        fn unwrap_items<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Item>, D::Error> {
+            #[derive(Deserialize)]
+            struct Items {
+                #[serde(default)]
+                item: Vec<Item>,
+            }
+            Ok(Items::deserialize(deserializer)?.item)
+        }
 
-            {
-                let mut ser = Serializer::new(&mut buffer);
-                ser.serialize_bool(src).unwrap();
+        const WRAPPERS: &[(&str, &str)] = &[("item", "items"), ("code", "codes")];
+
+        fn order() -> Order {
+            Order {
+                id: 1,
+                item: vec![
+                    Item {
+                        name: "first".into(),
+                        text: "text".into(),
+                    },
+                    Item {
+                        name: "second".into(),
+                        text: String::new(),
+                    },
+                ],
+                codes: vec![2, 3],
+                note: None,
             }
+        }
 
-            let got = String::from_utf8(buffer).unwrap();
-            assert_eq!(got, should_be);
+        /// Serializes the `order` twice, with and without streaming of the children,
+        /// and checks, that the results are the same
+        fn serialize(order: &Order, indent: bool) -> String {
+            let results: Vec<_> = [false, true]
+                .iter()
+                .map(|&stream| {
+                    let mut buffer = Vec::new();
+                    {
+                        let writer = if indent {
+                            Writer::new_with_indent(&mut buffer, b' ', 2)
+                        } else {
+                            Writer::new(&mut buffer)
+                        };
+                        let mut ser = Serializer::with_writer(writer, None);
+                        ser.seq_wrappers(WRAPPERS);
+                        if stream {
+                            ser.probe_struct(order).unwrap();
+                            assert!(ser.attributes_first);
+                        }
+                        order.serialize(&mut ser).unwrap();
+                    }
+                    String::from_utf8(buffer).unwrap()
+                })
+                .collect();
+            assert_eq!(results[0], results[1]);
+            results[1].clone()
         }
-    }
 
-    #[test]
-    fn test_serialize_struct() {
-        #[derive(Serialize)]
-        struct Person {
-            name: String,
-            age: u32,
+        #[test]
+        fn wrapped() {
+            assert_eq!(
+                serialize(&order(), false),
+                "<Order id=\"1\">\
+                    <items><item name=\"first\">text</item><item name=\"second\"/></items>\
+                    <codes><code>2</code><code>3</code></codes>\
+                </Order>"
+            );
+            assert_eq!(
+                serialize(&order(), true),
+                r#"<Order id="1">
+  <items>
+    <item name="first">text</item>
+    <item name="second"/>
+  </items>
+  <codes>
+    <code>2</code>
+    <code>3</code>
+  </codes>
+</Order>"#
+            );
         }
 
-        let bob = Person {
-            name: "Bob".to_string(),
-            age: 42,
-        };
-        let should_be = "<Person name=\"Bob\" age=\"42\"/>";
-        let mut buffer = Vec::new();
+        #[test]
+        fn empty() {
+            let order = Order {
+                id: 1,
+                item: vec![],
+                codes: vec![],
+                note: None,
+            };
+            assert_eq!(
+                serialize(&order, false),
+                r#"<Order id="1"><items/><codes/></Order>"#
+            );
+        }
 
-        {
+        /// Fields without a wrapper are still written as repeated elements
+        #[test]
+        fn not_wrapped() {
+            let order = Order {
+                id: 1,
+                item: vec![],
+                codes: vec![],
+                note: Some(vec![4, 5]),
+            };
+            let mut buffer = Vec::new();
             let mut ser = Serializer::new(&mut buffer);
-            bob.serialize(&mut ser).unwrap();
+            ser.seq_wrappers(&[("item", "items")]);
+            order.serialize(&mut ser).unwrap();
+            assert_eq!(
+                String::from_utf8(buffer).unwrap(),
+                r#"<Order id="1" note="45"><items/></Order>"#
+            );
         }
 
-        let got = String::from_utf8(buffer).unwrap();
-        assert_eq!(got, should_be);
+        #[test]
+        fn round_trip() {
+            for &indent in &[false, true] {
+                let xml = serialize(&order(), indent);
+                // `codes` are not unwrapped, so they are not read back
+                let mut expected = order();
+                expected.codes = vec![];
+                assert_eq!(from_str::<Order>(&xml).unwrap(), expected);
+            }
+        }
     }
 
-    #[test]
-    fn test_serialize_struct_value_number() {
-        #[derive(Serialize)]
-        struct Person {
-            name: String,
-            #[serde(rename = "$value")]
-            age: u32,
+    /// Unit structs and unit variants in each position, where they can be written
+    mod units {
+        use super::*;
+        use crate::de::from_str;
+
+        #[derive(Debug, Serialize, Deserialize, PartialEq)]
+        struct Ping;
+
+        #[derive(Debug, Serialize, Deserialize, PartialEq)]
+        enum Command {
+            Start,
+            Stop,
         }
 
-        let bob = Person {
-            name: "Bob".to_string(),
-            age: 42,
-        };
-        let should_be = "<Person name=\"Bob\">42</Person>";
-        let got = to_string(&bob).unwrap();
-        assert_eq!(got, should_be);
-    }
+        #[derive(Debug, Serialize, Deserialize, PartialEq)]
+        struct Message {
+            ping: Ping,
+            command: Command,
+            next: Option<Command>,
+        }
 
-    #[test]
-    fn test_serialize_struct_value_string() {
-        #[derive(Serialize)]
-        struct Person {
-            name: String,
+        #[derive(Debug, Serialize, Deserialize, PartialEq)]
+        struct Value {
             #[serde(rename = "$value")]
-            age: String,
+            command: Command,
         }
 
-        let bob = Person {
-            name: "Bob".to_string(),
-            age: "42".to_string(),
-        };
-        let should_be = "<Person name=\"Bob\">42</Person>";
-        let got = to_string(&bob).unwrap();
-        assert_eq!(got, should_be);
-    }
-
-    #[test]
-    fn test_serialize_map_entries() {
-        let should_be = "<name>Bob</name><age>5</age>";
-        let mut buffer = Vec::new();
+        #[derive(Debug, Serialize, Deserialize, PartialEq)]
+        struct Script {
+            #[serde(rename = "$value")]
+            commands: Vec<Command>,
+        }
 
-        {
-            let mut ser = Serializer::new(&mut buffer);
-            let mut map = Map::new(&mut ser);
-            map.serialize_entry("name", "Bob").unwrap();
-            map.serialize_entry("age", "5").unwrap();
+        fn serialize<T: Serialize>(value: &T, format: UnitVariantFormat) -> String {
+            let mut buffer = Vec::new();
+            {
+                let mut ser = Serializer::new(&mut buffer);
+                ser.unit_variant_format(format);
+                value.serialize(&mut ser).unwrap();
+            }
+            String::from_utf8(buffer).unwrap()
         }
 
-        let got = String::from_utf8(buffer).unwrap();
-        assert_eq!(got, should_be);
-    }
+        #[test]
+        fn root() {
+            for format in [UnitVariantFormat::Text, UnitVariantFormat::Element] {
+                assert_eq!(serialize(&Ping, format), "<Ping/>");
+                assert_eq!(serialize(&Command::Start, format), "<Start/>");
+            }
 
-    #[test]
-    fn test_serialize_enum() {
-        #[derive(Serialize)]
-        #[allow(dead_code)]
-        enum Node {
-            Boolean(bool),
-            Number(f64),
-            String(String),
+            let mut buffer = Vec::new();
+            {
+                let mut ser = Serializer::with_root(Writer::new(&mut buffer), Some("ping"));
+                Ping.serialize(&mut ser).unwrap();
+            }
+            assert_eq!(String::from_utf8(buffer).unwrap(), "<ping/>");
+
+            assert_eq!(from_str::<Ping>("<Ping/>").unwrap(), Ping);
+            assert_eq!(from_str::<Command>("<Start/>").unwrap(), Command::Start);
         }
 
-        let mut buffer = Vec::new();
-        let should_be = "<Boolean>true</Boolean>";
+        #[test]
+        fn field() {
+            let message = Message {
+                ping: Ping,
+                command: Command::Start,
+                next: Some(Command::Stop),
+            };
 
-        {
-            let mut ser = Serializer::new(&mut buffer);
-            let node = Node::Boolean(true);
-            node.serialize(&mut ser).unwrap();
+            let xml = serialize(&message, UnitVariantFormat::Text);
+            assert_eq!(
+                xml,
+                r#"<Message command="Start" next="Stop"><ping/></Message>"#
+            );
+            assert_eq!(from_str::<Message>(&xml).unwrap(), message);
+
+            assert_eq!(
+                serialize(&message, UnitVariantFormat::Element),
+                "<Message><ping/><Start/><Stop/></Message>"
+            );
         }
 
-        let got = String::from_utf8(buffer).unwrap();
-        assert_eq!(got, should_be);
-    }
-
-    #[test]
-    #[ignore]
-    fn serialize_a_list() {
-        let inputs = vec![1, 2, 3, 4];
+        #[test]
+        fn value() {
+            let value = Value {
+                command: Command::Stop,
+            };
 
-        let mut buffer = Vec::new();
+            let xml = serialize(&value, UnitVariantFormat::Text);
+            assert_eq!(xml, "<Value>Stop</Value>");
+            assert_eq!(from_str::<Value>(&xml).unwrap(), value);
 
-        {
-            let mut ser = Serializer::new(&mut buffer);
-            inputs.serialize(&mut ser).unwrap();
+            let xml = serialize(&value, UnitVariantFormat::Element);
+            assert_eq!(xml, "<Value><Stop/></Value>");
+            assert_eq!(from_str::<Value>(&xml).unwrap(), value);
         }
 
-        let got = String::from_utf8(buffer).unwrap();
-        println!("{}", got);
-        panic!();
+        /// Items of sequences are always elements, because texts would be merged
+        #[test]
+        fn sequence() {
+            let script = Script {
+                commands: vec![Command::Start, Command::Stop],
+            };
+
+            for format in [UnitVariantFormat::Text, UnitVariantFormat::Element] {
+                let xml = serialize(&script, format);
+                assert_eq!(xml, "<Script><Start/><Stop/></Script>");
+                assert_eq!(from_str::<Script>(&xml).unwrap(), script);
+            }
+        }
     }
 
-    #[test]
-    fn unit() {
+    /// Sequences of pairs in each style
+    mod assoc_lists {
+        use super::*;
+        use crate::de::from_str;
+        use std::collections::BTreeMap;
+
         #[derive(Serialize)]
-        struct Unit;
+        struct Config {
+            name: String,
+            env: Vec<(String, String)>,
+            ports: Vec<(u16, Option<&'static str>)>,
+        }
 
-        let data = Unit;
-        let should_be = "<root/>";
-        let mut buffer = Vec::new();
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Env {
+            name: String,
+            env: BTreeMap<String, String>,
+        }
 
-        {
-            let mut ser = Serializer::with_root(Writer::new(&mut buffer), Some("root"));
-            data.serialize(&mut ser).unwrap();
+        fn config() -> Config {
+            Config {
+                name: "app".into(),
+                env: vec![("PATH".into(), "/bin".into()), ("HOME".into(), "/".into())],
+                ports: vec![(80, Some("http")), (443, None)],
+            }
         }
 
-        let got = String::from_utf8(buffer).unwrap();
-        assert_eq!(got, should_be);
-    }
+        fn serialize<T: Serialize>(value: &T, style: AssocListStyle) -> String {
+            let mut buffer = Vec::new();
+            {
+                let mut ser = Serializer::with_root(Writer::new(&mut buffer), Some("root"));
+                ser.assoc_list_style(style);
+                value.serialize(&mut ser).unwrap();
+            }
+            String::from_utf8(buffer).unwrap()
+        }
 
-    #[test]
-    fn newtype() {
-        #[derive(Serialize)]
-        struct Newtype(bool);
+        /// Pairs are written as tuples by default
+        #[test]
+        fn tuple() {
+            let config = Config {
+                ports: vec![],
+                ..config()
+            };
+            assert_eq!(
+                serialize(&config, AssocListStyle::Tuple),
+                "<root name=\"app\">\
+                    <env>PATH</env><env>/bin</env><env>HOME</env><env>/</env>\
+                </root>"
+            );
+        }
 
-        let data = Newtype(true);
-        let should_be = "<root>true</root>";
-        let mut buffer = Vec::new();
+        #[test]
+        fn entries() {
+            assert_eq!(
+                serialize(&config(), AssocListStyle::Entries),
+                "<root name=\"app\">\
+                    <env>\
+                        <entry><key>PATH</key><value>/bin</value></entry>\
+                        <entry><key>HOME</key><value>/</value></entry>\
+                    </env>\
+                    <ports>\
+                        <entry><key>80</key><value>http</value></entry>\
+                        <entry><key>443</key><value></value></entry>\
+                    </ports>\
+                </root>"
+            );
+        }
 
-        {
+        #[test]
+        fn elements() {
+            let config = Config {
+                ports: vec![],
+                ..config()
+            };
+            let xml = serialize(&config, AssocListStyle::Elements);
+            assert_eq!(
+                xml,
+                r#"<root name="app"><env><PATH>/bin</PATH><HOME>/</HOME></env></root>"#
+            );
+            let env = Env {
+                name: "app".into(),
+                env: config.env.into_iter().collect(),
+            };
+            assert_eq!(from_str::<Env>(&xml).unwrap(), env);
+
+            // Keys must be names of elements
+            let mut buffer = Vec::new();
             let mut ser = Serializer::with_root(Writer::new(&mut buffer), Some("root"));
-            data.serialize(&mut ser).unwrap();
+            ser.assoc_list_style(AssocListStyle::Elements);
+            assert!(vec![((1, 2), 3)].serialize(&mut ser).is_err());
         }
 
-        let got = String::from_utf8(buffer).unwrap();
-        assert_eq!(got, should_be);
-    }
+        #[test]
+        fn root() {
+            let env = vec![("a", 1), ("b", 2)];
+            assert_eq!(
+                serialize(&env, AssocListStyle::Elements),
+                "<root><a>1</a><b>2</b></root>"
+            );
+            assert_eq!(
+                serialize(&env, AssocListStyle::Entries),
+                "<root>\
+                    <entry><key>a</key><value>1</value></entry>\
+                    <entry><key>b</key><value>2</value></entry>\
+                </root>"
+            );
+        }
 
-    #[test]
-    fn tuple() {
-        let data = (42.0, "answer");
-        let should_be = "<root>42</root><root>answer</root>";
-        let mut buffer = Vec::new();
+        /// Tuples of other sizes are written as tuples
+        #[test]
+        fn not_pairs() {
+            #[derive(Serialize)]
+            struct Triples {
+                list: Vec<(u8, u8, u8)>,
+            }
 
-        {
-            let mut ser =
-                Serializer::with_root(Writer::new_with_indent(&mut buffer, b' ', 4), Some("root"));
-            data.serialize(&mut ser).unwrap();
+            let triples = Triples {
+                list: vec![(1, 2, 3)],
+            };
+            assert_eq!(
+                serialize(&triples, AssocListStyle::Elements),
+                "<root><list>1</list><list>2</list><list>3</list></root>"
+            );
         }
-
-        let got = String::from_utf8(buffer).unwrap();
-        assert_eq!(got, should_be);
     }
 
-    #[test]
-    fn tuple_struct() {
+    mod with_header {
+        use super::*;
+
         #[derive(Serialize)]
-        struct Tuple(f32, &'static str);
+        struct Html {
+            lang: &'static str,
+            body: Body,
+        }
 
-        let data = Tuple(42.0, "answer");
-        let should_be = "<root>42</root><root>answer</root>";
-        let mut buffer = Vec::new();
+        #[derive(Serialize)]
+        struct Body {
+            #[serde(rename = "$value")]
+            text: &'static str,
+        }
 
-        {
+        const HTML: Html = Html {
+            lang: "en",
+            body: Body { text: "Hello" },
+        };
+
+        #[test]
+        fn to_string() {
+            assert_eq!(
+                to_string_with_header(
+                    &HTML,
+                    Some("html"),
+                    BytesDecl::new(b"1.0", Some(b"UTF-8"), Some(b"yes"))
+                )
+                .unwrap(),
+                r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?><html lang="en"><body>Hello</body></html>"#
+            );
+        }
+
+        #[test]
+        fn doctype() {
+            let mut buffer = Vec::new();
             let mut ser =
-                Serializer::with_root(Writer::new_with_indent(&mut buffer, b' ', 4), Some("root"));
-            data.serialize(&mut ser).unwrap();
+                Serializer::with_root(Writer::new_with_indent(&mut buffer, b' ', 2), Some("html"));
+            ser.serialize_with_header(
+                &HTML,
+                BytesDecl::new(b"1.0", None, None),
+                Some(DocType {
+                    name: "html",
+                    public_id: Some("-//W3C//DTD XHTML 1.0 Strict//EN"),
+                    system_id: Some("http://www.w3.org/TR/xhtml1/DTD/xhtml1-strict.dtd"),
+                }),
+            )
+            .unwrap();
+            assert_eq!(
+                String::from_utf8(buffer).unwrap(),
+                r#"<?xml version="1.0"?>
+<!DOCTYPE html PUBLIC "-//W3C//DTD XHTML 1.0 Strict//EN" "http://www.w3.org/TR/xhtml1/DTD/xhtml1-strict.dtd">
+<html lang="en">
+  <body>Hello</body>
+</html>"#
+            );
         }
 
-        let got = String::from_utf8(buffer).unwrap();
-        assert_eq!(got, should_be);
+        #[test]
+        fn invalid_doctype() {
+            let mut buffer = Vec::new();
+            let mut ser = Serializer::with_root(Writer::new(&mut buffer), Some("html"));
+            let doctype = DocType {
+                name: "html",
+                public_id: Some("-//W3C//DTD XHTML 1.0 Strict//EN"),
+                system_id: None,
+            };
+            match ser.serialize_with_header(
+                &HTML,
+                BytesDecl::new(b"1.0", None, None),
+                Some(doctype),
+            ) {
+                Err(DeError::Xml(crate::errors::Error::InvalidDocType(_))) => {}
+                e => panic!("Expected `InvalidDocType`, found {:?}", e),
+            }
+        }
     }
 
+    /// Buffers are reused between structs, so values of the previous structs
+    /// should not leak into the next ones
     #[test]
-    fn struct_() {
+    fn reused_buffers() {
         #[derive(Serialize)]
-        struct Struct {
-            float: f64,
-            string: String,
+        struct Root {
+            item: Vec<Item>,
         }
 
-        let mut buffer = Vec::new();
-        let should_be = r#"<root float="42" string="answer"/>"#;
+        #[derive(Serialize)]
+        struct Item {
+            text: &'static str,
+            nested: Option<Nested>,
+            #[serde(rename = "$unflatten=char")]
+            ch: char,
+        }
 
-        {
-            let mut ser =
-                Serializer::with_root(Writer::new_with_indent(&mut buffer, b' ', 4), Some("root"));
-            let node = Struct {
-                float: 42.0,
-                string: "answer".to_string(),
-            };
-            node.serialize(&mut ser).unwrap();
+        #[derive(Serialize)]
+        struct Nested {
+            number: u32,
         }
 
-        let got = String::from_utf8(buffer).unwrap();
-        assert_eq!(got, should_be);
+        let root = Root {
+            item: vec![
+                Item {
+                    text: "a long <escaped> text",
+                    nested: Some(Nested { number: 123456 }),
+                    ch: '&',
+                },
+                Item {
+                    text: "short",
+                    nested: None,
+                    ch: '\u{444}',
+                },
+            ],
+        };
+
+        assert_eq!(
+            to_string(&root).unwrap(),
+            "<Root>\
+                <item text=\"a long &lt;escaped&gt; text\"><nested number=\"123456\"/><char>&amp;</char></item>\
+                <item text=\"short\"><char>\u{444}</char></item>\
+            </Root>"
+        );
     }
 
+    /// Serializer continues from the current state of the writer
     #[test]
-    fn nested_struct() {
+    fn into_inner() {
         #[derive(Serialize)]
         struct Struct {
             nested: Nested,
@@ -572,17 +3083,28 @@ mod tests {
         }
 
         let mut buffer = Vec::new();
-        let should_be = r#"<root string="answer"><nested float="42"/>
-</root>"#;
+        let should_be = r#"<document>
+  <root string="answer">
+    <nested float="42"/>
+  </root>
+</document>"#;
 
         {
-            let mut ser =
-                Serializer::with_root(Writer::new_with_indent(&mut buffer, b' ', 4), Some("root"));
+            let mut writer = Writer::new_with_indent(&mut buffer, b' ', 2);
+            writer
+                .write_event(Event::Start(BytesStart::borrowed_name(b"document")))
+                .unwrap();
+
+            let mut ser = Serializer::with_root(writer, Some("root"));
             let node = Struct {
                 nested: Nested { float: 42.0 },
                 string: "answer".to_string(),
             };
             node.serialize(&mut ser).unwrap();
+
+            ser.into_inner()
+                .write_event(Event::End(BytesEnd::borrowed(b"document")))
+                .unwrap();
         }
 
         let got = String::from_utf8(buffer).unwrap();
@@ -604,7 +3126,10 @@ mod tests {
         }
 
         let mut buffer = Vec::new();
-        let should_be = r#"<root><float>42</float><string>answer</string></root>"#;
+        let should_be = r#"<root>
+    <float>42</float>
+    <string>answer</string>
+</root>"#;
 
         {
             let mut ser =
@@ -705,7 +3230,7 @@ mod tests {
             #[test]
             fn tuple_struct() {
                 let mut buffer = Vec::new();
-                let should_be = "<Tuple>42</Tuple><Tuple>answer</Tuple>";
+                let should_be = "<Tuple>42</Tuple>\n<Tuple>answer</Tuple>";
 
                 {
                     let mut ser = Serializer::with_root(
@@ -723,7 +3248,8 @@ mod tests {
             #[test]
             fn nested_struct() {
                 let mut buffer = Vec::new();
-                let should_be = r#"<Holder string="answer"><nested float="42"/>
+                let should_be = r#"<Holder string="answer">
+    <nested float="42"/>
 </Holder>"#;
 
                 {
@@ -745,7 +3271,10 @@ mod tests {
             #[test]
             fn flatten_struct() {
                 let mut buffer = Vec::new();
-                let should_be = r#"<Flatten><float>42</float><string>answer</string></Flatten>"#;
+                let should_be = r#"<Flatten>
+    <float>42</float>
+    <string>answer</string>
+</Flatten>"#;
 
                 {
                     let mut ser = Serializer::with_root(
@@ -848,7 +3377,8 @@ mod tests {
             #[test]
             fn nested_struct() {
                 let mut buffer = Vec::new();
-                let should_be = r#"<root tag="Holder" string="answer"><nested float="42"/>
+                let should_be = r#"<root tag="Holder" string="answer">
+    <nested float="42"/>
 </root>"#;
 
                 {
@@ -870,8 +3400,11 @@ mod tests {
             #[test]
             fn flatten_struct() {
                 let mut buffer = Vec::new();
-                let should_be =
-                    r#"<root><tag>Flatten</tag><float>42</float><string>answer</string></root>"#;
+                let should_be = r#"<root>
+    <tag>Flatten</tag>
+    <float>42</float>
+    <string>answer</string>
+</root>"#;
 
                 {
                     let mut ser = Serializer::with_root(
@@ -1089,7 +3622,7 @@ mod tests {
             #[test]
             fn tuple_struct() {
                 let mut buffer = Vec::new();
-                let should_be = "<root>42</root><root>answer</root>";
+                let should_be = "<root>42</root>\n<root>answer</root>";
 
                 {
                     let mut ser = Serializer::with_root(
@@ -1128,7 +3661,8 @@ mod tests {
             #[test]
             fn nested_struct() {
                 let mut buffer = Vec::new();
-                let should_be = r#"<root string="answer"><nested float="42"/>
+                let should_be = r#"<root string="answer">
+    <nested float="42"/>
 </root>"#;
 
                 {
@@ -1150,7 +3684,10 @@ mod tests {
             #[test]
             fn flatten_struct() {
                 let mut buffer = Vec::new();
-                let should_be = r#"<root><float>42</float><string>answer</string></root>"#;
+                let should_be = r#"<root>
+    <float>42</float>
+    <string>answer</string>
+</root>"#;
 
                 {
                     let mut ser = Serializer::with_root(
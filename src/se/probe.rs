@@ -0,0 +1,450 @@
+use crate::{
+    errors::serialize::DeError,
+    se::{adapter::Adapters, BytesEncoding, Config, EmptySeqPolicy, UnitVariantFormat},
+};
+use de::{INNER_VALUE, UNFLATTEN_PREFIX};
+use serde::ser::{self, Serialize};
+use serde::serde_if_integer128;
+
+/// What the `Serializer` writes for a value, as a field of a struct
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum Kind {
+    /// Nothing, as for `None` and units
+    Nothing,
+    /// A text, which is written as an attribute
+    Text,
+    /// Elements, which are written as children
+    Markup,
+    /// An element of a struct. `attributes_first` is set, when the fields of the struct,
+    /// which are written as attributes, are serialized before the fields, which are
+    /// written as children, so the children can be written right after the start tag
+    Struct { attributes_first: bool },
+    /// A pair, which is written as an entry of an association list. Only found, when
+    /// the items of a sequence are probed
+    Pair,
+}
+
+impl Kind {
+    /// Whether the value is written as elements
+    pub(super) fn is_markup(self) -> bool {
+        match self {
+            Kind::Markup | Kind::Struct { .. } | Kind::Pair => true,
+            Kind::Nothing | Kind::Text => false,
+        }
+    }
+
+    /// Whether the value is a struct with the attributes before the children
+    pub(super) fn attributes_first(self) -> bool {
+        self == Kind::Struct {
+            attributes_first: true,
+        }
+    }
+}
+
+/// Finds out what the `Serializer` would write for a value without serializing it.
+/// Only the fields of the top-level struct are looked at, and the nested values are
+/// not serialized.
+pub(super) struct Probe<'a, 'r> {
+    pub(super) config: &'a Config<'r>,
+    /// Types, which are written as text by the registered adapters
+    pub(super) adapters: &'a Adapters,
+    /// Look at the kinds of fields of a struct to find out `attributes_first`
+    pub(super) fields: bool,
+    /// The value is a struct field, which items are written inside of a wrapper element,
+    /// if it is a sequence
+    pub(super) wrapped: bool,
+    /// The value is a struct field, so unit variants are written according to
+    /// `unit_variant_format`
+    pub(super) field: bool,
+    /// The value is an item of a sequence, so a tuple of two values is a pair
+    pub(super) pairs: bool,
+}
+
+impl<'a, 'r> Probe<'a, 'r> {
+    fn nested(&self) -> Probe<'a, 'r> {
+        Probe {
+            config: self.config,
+            adapters: self.adapters,
+            fields: false,
+            wrapped: false,
+            field: false,
+            pairs: false,
+        }
+    }
+
+    /// Finds out what the `Serializer` writes for `value`, which is a text, if an adapter
+    /// is registered for its type
+    pub(super) fn kind_of<T: ?Sized + Serialize>(self, value: &T) -> Result<Kind, DeError> {
+        if self.adapters.is_adapted(value) {
+            Ok(Kind::Text)
+        } else {
+            value.serialize(self)
+        }
+    }
+
+    fn compound(self, kind: Kind) -> Compound<'a, 'r> {
+        Compound {
+            probe: self.nested(),
+            fields: self.fields,
+            kind,
+            content_seen: false,
+            has_items: false,
+        }
+    }
+}
+
+impl<'a, 'r> ser::Serializer for Probe<'a, 'r> {
+    type Ok = Kind;
+    type Error = DeError;
+
+    type SerializeSeq = Compound<'a, 'r>;
+    type SerializeTuple = Compound<'a, 'r>;
+    type SerializeTupleStruct = Compound<'a, 'r>;
+    type SerializeTupleVariant = Compound<'a, 'r>;
+    type SerializeMap = Compound<'a, 'r>;
+    type SerializeStruct = Compound<'a, 'r>;
+    type SerializeStructVariant = Compound<'a, 'r>;
+
+    fn serialize_bool(self, _v: bool) -> Result<Kind, DeError> {
+        Ok(Kind::Text)
+    }
+
+    fn serialize_i8(self, _v: i8) -> Result<Kind, DeError> {
+        Ok(Kind::Text)
+    }
+
+    fn serialize_i16(self, _v: i16) -> Result<Kind, DeError> {
+        Ok(Kind::Text)
+    }
+
+    fn serialize_i32(self, _v: i32) -> Result<Kind, DeError> {
+        Ok(Kind::Text)
+    }
+
+    fn serialize_i64(self, _v: i64) -> Result<Kind, DeError> {
+        Ok(Kind::Text)
+    }
+
+    fn serialize_u8(self, _v: u8) -> Result<Kind, DeError> {
+        Ok(Kind::Text)
+    }
+
+    fn serialize_u16(self, _v: u16) -> Result<Kind, DeError> {
+        Ok(Kind::Text)
+    }
+
+    fn serialize_u32(self, _v: u32) -> Result<Kind, DeError> {
+        Ok(Kind::Text)
+    }
+
+    fn serialize_u64(self, _v: u64) -> Result<Kind, DeError> {
+        Ok(Kind::Text)
+    }
+
+    serde_if_integer128! {
+        fn serialize_i128(self, _v: i128) -> Result<Kind, DeError> {
+            Ok(Kind::Text)
+        }
+
+        fn serialize_u128(self, _v: u128) -> Result<Kind, DeError> {
+            Ok(Kind::Text)
+        }
+    }
+
+    fn serialize_f32(self, _v: f32) -> Result<Kind, DeError> {
+        Ok(Kind::Text)
+    }
+
+    fn serialize_f64(self, _v: f64) -> Result<Kind, DeError> {
+        Ok(Kind::Text)
+    }
+
+    fn serialize_char(self, _v: char) -> Result<Kind, DeError> {
+        Ok(Kind::Text)
+    }
+
+    fn serialize_str(self, _v: &str) -> Result<Kind, DeError> {
+        Ok(Kind::Text)
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<Kind, DeError> {
+        match self.config.bytes_encoding {
+            BytesEncoding::Hex => Ok(Kind::Text),
+            // The `Serializer` fails on bytes
+            BytesEncoding::Unsupported => Ok(Kind::Nothing),
+        }
+    }
+
+    fn serialize_none(self) -> Result<Kind, DeError> {
+        Ok(Kind::Nothing)
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<Kind, DeError> {
+        self.kind_of(value)
+    }
+
+    fn serialize_unit(self) -> Result<Kind, DeError> {
+        Ok(Kind::Nothing)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Kind, DeError> {
+        Ok(Kind::Markup)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<Kind, DeError> {
+        match self.config.unit_variant_format {
+            UnitVariantFormat::Text if self.field => Ok(Kind::Text),
+            _ => Ok(Kind::Markup),
+        }
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Kind, DeError> {
+        // The value is wrapped into an element
+        match self.kind_of(value)? {
+            kind @ Kind::Struct { .. } => Ok(kind),
+            _ => Ok(Kind::Markup),
+        }
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        value: &T,
+    ) -> Result<Kind, DeError> {
+        self.serialize_newtype_struct(name, value)
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Compound<'a, 'r>, DeError> {
+        if self.wrapped {
+            // The wrapper element is written even without items, and items do not change it
+            let mut compound = self.compound(Kind::Markup);
+            compound.has_items = true;
+            return Ok(compound);
+        }
+        let kind = match self.config.empty_seq {
+            EmptySeqPolicy::EmptyElement => Kind::Markup,
+            EmptySeqPolicy::Omit => Kind::Nothing,
+        };
+        Ok(self.compound(kind))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Compound<'a, 'r>, DeError> {
+        if self.pairs && len == 2 {
+            return Ok(self.compound(Kind::Pair));
+        }
+        Ok(self.compound(Kind::Nothing))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Compound<'a, 'r>, DeError> {
+        Ok(self.compound(Kind::Nothing))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Compound<'a, 'r>, DeError> {
+        Ok(self.compound(Kind::Nothing))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Compound<'a, 'r>, DeError> {
+        Ok(self.compound(Kind::Markup))
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Compound<'a, 'r>, DeError> {
+        let attributes_first = self.fields;
+        Ok(self.compound(Kind::Struct { attributes_first }))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        len: usize,
+    ) -> Result<Compound<'a, 'r>, DeError> {
+        self.serialize_struct(name, len)
+    }
+}
+
+/// Probes the parts of a compound value
+pub(super) struct Compound<'a, 'r> {
+    probe: Probe<'a, 'r>,
+    /// Look at the kinds of fields of a struct
+    fields: bool,
+    kind: Kind,
+    /// Whether a struct field, written as children, was seen
+    content_seen: bool,
+    /// Whether a sequence has items
+    has_items: bool,
+}
+
+impl<'a, 'r> Compound<'a, 'r> {
+    /// Any element makes a tuple written as elements
+    fn element(&mut self) {
+        if self.kind != Kind::Pair {
+            self.kind = Kind::Markup;
+        }
+    }
+}
+
+impl<'a, 'r> ser::SerializeSeq for Compound<'a, 'r> {
+    type Ok = Kind;
+    type Error = DeError;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), DeError> {
+        if !self.has_items {
+            // A sequence with items does not write an empty element
+            self.has_items = true;
+            self.kind = Kind::Nothing;
+        }
+        // Items are written one after another, so the first item, which writes something,
+        // defines the kind
+        if self.kind == Kind::Nothing {
+            self.kind = match self.probe.nested().kind_of(value)? {
+                Kind::Struct { .. } => Kind::Markup,
+                kind => kind,
+            };
+        }
+        Ok(())
+    }
+
+    fn end(self) -> Result<Kind, DeError> {
+        Ok(self.kind)
+    }
+}
+
+impl<'a, 'r> ser::SerializeTuple for Compound<'a, 'r> {
+    type Ok = Kind;
+    type Error = DeError;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, _value: &T) -> Result<(), DeError> {
+        self.element();
+        Ok(())
+    }
+
+    fn end(self) -> Result<Kind, DeError> {
+        Ok(self.kind)
+    }
+}
+
+impl<'a, 'r> ser::SerializeTupleStruct for Compound<'a, 'r> {
+    type Ok = Kind;
+    type Error = DeError;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, _value: &T) -> Result<(), DeError> {
+        self.element();
+        Ok(())
+    }
+
+    fn end(self) -> Result<Kind, DeError> {
+        Ok(self.kind)
+    }
+}
+
+impl<'a, 'r> ser::SerializeTupleVariant for Compound<'a, 'r> {
+    type Ok = Kind;
+    type Error = DeError;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, _value: &T) -> Result<(), DeError> {
+        self.element();
+        Ok(())
+    }
+
+    fn end(self) -> Result<Kind, DeError> {
+        Ok(self.kind)
+    }
+}
+
+impl<'a, 'r> ser::SerializeMap for Compound<'a, 'r> {
+    type Ok = Kind;
+    type Error = DeError;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, _key: &T) -> Result<(), DeError> {
+        Ok(())
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, _value: &T) -> Result<(), DeError> {
+        Ok(())
+    }
+
+    fn end(self) -> Result<Kind, DeError> {
+        Ok(self.kind)
+    }
+}
+
+impl<'a, 'r> ser::SerializeStruct for Compound<'a, 'r> {
+    type Ok = Kind;
+    type Error = DeError;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), DeError> {
+        if !self.fields || !self.kind.attributes_first() {
+            return Ok(());
+        }
+        let kind = Probe {
+            wrapped: self.probe.config.seq_wrapper(key).is_some(),
+            field: true,
+            ..self.probe.nested()
+        }
+        .kind_of(value)?;
+        if key.starts_with(UNFLATTEN_PREFIX) || kind.is_markup() {
+            self.content_seen = true;
+        } else if kind == Kind::Text {
+            if key == INNER_VALUE {
+                self.content_seen = true;
+            } else if self.content_seen {
+                // The attribute cannot be added after the start tag is written
+                self.kind = Kind::Struct {
+                    attributes_first: false,
+                };
+            }
+        }
+        Ok(())
+    }
+
+    fn end(self) -> Result<Kind, DeError> {
+        Ok(self.kind)
+    }
+}
+
+impl<'a, 'r> ser::SerializeStructVariant for Compound<'a, 'r> {
+    type Ok = Kind;
+    type Error = DeError;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), DeError> {
+        <Self as ser::SerializeStruct>::serialize_field(self, key, value)
+    }
+
+    fn end(self) -> Result<Kind, DeError> {
+        Ok(self.kind)
+    }
+}
@@ -1,10 +1,16 @@
 use crate::{
-    errors::{serialize::DeError, Error},
+    errors::serialize::DeError,
+    escape::{escape_attribute_chars, EscapeLevel},
     events::{BytesEnd, BytesStart, Event},
-    se::Serializer,
+    se::{
+        key::KeySerializer,
+        probe::{Kind, Probe},
+        AssocListStyle, EmptySeqPolicy, EntryValue, Serializer,
+    },
     writer::Writer,
 };
-use de::{INNER_VALUE, UNFLATTEN_PREFIX};
+use de::{ATTRIBUTE_PREFIX, INNER_VALUE, UNFLATTEN_PREFIX};
+use reader::is_whitespace;
 use serde::ser::{self, Serialize, SerializeMap};
 use serde::Serializer as _;
 use std::io::Write;
@@ -15,6 +21,8 @@ where
     W: 'w + Write,
 {
     parent: &'w mut Serializer<'r, W>,
+    /// Buffer for the serialized value of an entry
+    value: Vec<u8>,
 }
 
 impl<'r, 'w, W> Map<'r, 'w, W>
@@ -23,7 +31,71 @@ where
 {
     /// Create a new Map
     pub fn new(parent: &'w mut Serializer<'r, W>) -> Self {
-        Map { parent }
+        let value = parent.buffers.take();
+        Map { parent, value }
+    }
+
+    /// Serializes the `key` of an entry into the `buffer`, as it is written by the
+    /// `parent` writer
+    fn serialize_key_into<K: ?Sized + Serialize>(
+        parent: &mut Serializer<'r, W>,
+        key: &K,
+        buffer: &mut Vec<u8>,
+    ) -> Result<(), DeError> {
+        // Only strings and primitives can be names of elements, other keys are rejected
+        let as_is = parent.writer.writes_text_as_is();
+        key.serialize(KeySerializer {
+            out: buffer,
+            config: &parent.config,
+            escape: as_is,
+        })?;
+        // The text of the key is written once more, if the writer would change it
+        if !as_is {
+            let text = String::from_utf8(std::mem::take(buffer))
+                .map_err(|e| crate::errors::Error::Utf8(e.utf8_error()))?;
+            let writer = parent.writer.fork(&mut *buffer);
+            let mut serializer = parent.nested(writer, None);
+            let result = serializer.serialize_str(&text);
+            parent.restore(&mut serializer);
+            result?;
+        }
+        Ok(())
+    }
+
+    /// Writes the `value` of an entry as an element named by the serialized `key`
+    fn serialize_value_of<V: ?Sized + Serialize>(
+        &mut self,
+        key: &[u8],
+        value: &V,
+    ) -> Result<(), DeError> {
+        let key = std::str::from_utf8(key).map_err(crate::errors::Error::Utf8)?;
+        if key == INNER_VALUE {
+            // The content of the element, as the `$value` field of a flattened struct
+            return self.parent.serialize_inner(value);
+        }
+
+        // The value is written inside of an element, unless it is a sequence
+        let writer = self.parent.writer.nested(&mut self.value);
+        let mut serializer = self.parent.nested(writer, Some(key));
+        serializer.entry = EntryValue::Any;
+        let result = serializer
+            .probe_struct(value)
+            .and_then(|_| serializer.serialize_adapted(value));
+        self.parent.restore(&mut serializer);
+        result?;
+
+        if serializer.entry == EntryValue::Seq {
+            // Items are already written as repeated elements named by the key
+            self.parent.writer.append(&mut serializer.writer)?;
+        } else {
+            let name = BytesStart::borrowed_name(key.as_bytes());
+            self.parent.writer.write_event(Event::Start(name))?;
+            self.parent.writer.append(&mut serializer.writer)?;
+            self.parent
+                .writer
+                .write_event(Event::End(BytesEnd::borrowed(key.as_bytes())))?;
+        }
+        Ok(())
     }
 }
 
@@ -41,10 +113,11 @@ where
     }
 
     fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), DeError> {
-        value.serialize(&mut *self.parent)
+        self.parent.serialize_adapted(value)
     }
 
     fn end(self) -> Result<Self::Ok, DeError> {
+        self.parent.buffers.give(self.value);
         if let Some(tag) = self.parent.root_tag {
             self.parent
                 .writer
@@ -58,21 +131,20 @@ where
         key: &K,
         value: &V,
     ) -> Result<(), DeError> {
-        // TODO: Is it possible to ensure our key is never a composite type?
-        // Anything which isn't a "primitive" would lead to malformed XML here...
-        write!(self.parent.writer.inner(), "<").map_err(Error::Io)?;
-        key.serialize(&mut *self.parent)?;
-        write!(self.parent.writer.inner(), ">").map_err(Error::Io)?;
-
-        value.serialize(&mut *self.parent)?;
-
-        write!(self.parent.writer.inner(), "</").map_err(Error::Io)?;
-        key.serialize(&mut *self.parent)?;
-        write!(self.parent.writer.inner(), ">").map_err(Error::Io)?;
-        Ok(())
+        let mut key_buffer = self.parent.buffers.take();
+        if let Err(e) = Self::serialize_key_into(self.parent, key, &mut key_buffer) {
+            self.parent.buffers.give(key_buffer);
+            return Err(e);
+        }
+        let result = self.serialize_value_of(&key_buffer, value);
+        self.parent.buffers.give(key_buffer);
+        result
     }
 }
 
+/// Estimated size of an attribute, such as ` name="value"`, in bytes
+const ATTRIBUTE_SIZE: usize = 16;
+
 /// An implementation of `SerializeStruct` for serializing to XML.
 pub struct Struct<'r, 'w, W>
 where
@@ -82,26 +154,88 @@ where
     /// Buffer for holding fields, serialized as attributes. Doesn't allocate
     /// if there are no fields represented as attributes
     attrs: BytesStart<'w>,
-    /// Buffer for holding fields, serialized as elements
-    children: Vec<u8>,
+    /// Buffer for holding fields, serialized as elements. The writer continues
+    /// indentation of the parent writer
+    children: Writer<Vec<u8>>,
     /// Buffer for serializing one field. Cleared after serialize each field
     buffer: Vec<u8>,
+    /// Whether all fields, serialized as attributes, come before the fields, serialized
+    /// as elements. Then elements are written directly into the parent writer after the
+    /// start tag instead of the `children` buffer
+    attributes_first: bool,
+    /// Whether the start tag is already written into the parent writer
+    started: bool,
 }
 
 impl<'r, 'w, W> Struct<'r, 'w, W>
 where
     W: 'w + Write,
 {
-    /// Create a new `Struct`
-    pub fn new(parent: &'w mut Serializer<'r, W>, name: &'r str) -> Self {
+    /// Create a new `Struct` with `len` fields
+    pub fn new(parent: &'w mut Serializer<'r, W>, name: &'r str, len: usize) -> Self {
         let name = name.as_bytes();
+        // Reuse buffers of the previous structs, they are returned back in `end`
+        let children = parent.writer.nested(parent.buffers.take());
+        let mut attrs = parent.buffers.take();
+        // Any field can be an attribute, so reserve space for all of them at once
+        attrs.reserve(name.len() + len * ATTRIBUTE_SIZE);
+        attrs.extend_from_slice(name);
+        let buffer = parent.buffers.take();
+        let attributes_first = std::mem::replace(&mut parent.attributes_first, false);
         Struct {
             parent,
-            attrs: BytesStart::borrowed_name(name),
-            children: Vec::new(),
-            buffer: Vec::new(),
+            attrs: BytesStart::owned(attrs, name.len()),
+            children,
+            buffer,
+            attributes_first,
+            started: false,
         }
     }
+
+    /// Writes the start tag with the attributes, collected so far, if it is not written yet
+    fn start(
+        writer: &mut Writer<W>,
+        attrs: &BytesStart,
+        started: &mut bool,
+    ) -> Result<(), DeError> {
+        if !*started {
+            writer.write_event(Event::Start(attrs.to_borrowed()))?;
+            *started = true;
+        }
+        Ok(())
+    }
+
+    /// Serializes a field, which is written as elements, directly into the parent writer
+    fn serialize_child<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+        kind: Kind,
+    ) -> Result<(), DeError> {
+        Self::start(&mut self.parent.writer, &self.attrs, &mut self.started)?;
+        let parent = &mut *self.parent;
+        let root_tag = parent.root_tag;
+        let has_text = parent.has_text;
+        let entry = std::mem::replace(&mut parent.entry, EntryValue::None);
+        let root = std::mem::replace(&mut parent.root, false);
+        parent.attributes_first = kind.attributes_first();
+        parent.seq_wrapper = parent.config.seq_wrapper(key);
+        let result = if key.starts_with(UNFLATTEN_PREFIX) {
+            let key = key.split_at(UNFLATTEN_PREFIX.len()).1;
+            parent.root_tag = Some(key);
+            parent.serialize_newtype_struct(key, value)
+        } else {
+            parent.root_tag = Some(key);
+            parent.serialize_adapted(value)
+        };
+        parent.root_tag = root_tag;
+        parent.has_text = has_text;
+        parent.entry = entry;
+        parent.root = root;
+        parent.attributes_first = false;
+        parent.seq_wrapper = None;
+        result
+    }
 }
 
 impl<'r, 'w, W> ser::SerializeStruct for Struct<'r, 'w, W>
@@ -116,44 +250,94 @@ where
         key: &'static str,
         value: &T,
     ) -> Result<(), DeError> {
-        // TODO: Inherit indentation state from self.parent.writer
-        let writer = Writer::new(&mut self.buffer);
-        if key.starts_with(UNFLATTEN_PREFIX) {
+        let key = key.strip_prefix(ATTRIBUTE_PREFIX).unwrap_or(key);
+        let kind = Probe {
+            config: &self.parent.config,
+            adapters: &self.parent.adapters,
+            fields: true,
+            wrapped: self.parent.config.seq_wrapper(key).is_some(),
+            field: true,
+            pairs: false,
+        }
+        .kind_of(value)?;
+        if self.attributes_first && (key.starts_with(UNFLATTEN_PREFIX) || kind.is_markup()) {
+            return self.serialize_child(key, value, kind);
+        }
+
+        // Fields after the start tag continue the indentation of the parent writer
+        let writer = if self.started {
+            self.parent.writer.fork(&mut self.buffer)
+        } else {
+            self.children.fork(&mut self.buffer)
+        };
+        let mut writer = if key.starts_with(UNFLATTEN_PREFIX) {
             let key = key.split_at(UNFLATTEN_PREFIX.len()).1;
-            let mut serializer = Serializer::with_root(writer, Some(key));
-            serializer.serialize_newtype_struct(key, value);
-            self.children.append(&mut self.buffer);
+            let mut serializer = self.parent.nested(writer, Some(key));
+            serializer.attributes_first = kind.attributes_first();
+            let result = serializer.serialize_newtype_struct(key, value);
+            self.parent.restore(&mut serializer);
+            result?;
+            serializer.writer
         } else {
-            let mut serializer = Serializer::with_root(writer, Some(key));
-            value.serialize(&mut serializer)?;
-
-            if !self.buffer.is_empty() {
-                if self.buffer[0] == b'<' || key == INNER_VALUE {
-                    // Drains buffer, moves it to children
-                    self.children.append(&mut self.buffer);
-                } else {
-                    self.attrs
-                        .push_attribute((key.as_bytes(), self.buffer.as_ref()));
+            let mut serializer = self.parent.nested(writer, Some(key));
+            serializer.attributes_first = kind.attributes_first();
+            serializer.seq_wrapper = serializer.config.seq_wrapper(key);
+            serializer.field = true;
+            let result = serializer.serialize_adapted(value);
+            self.parent.restore(&mut serializer);
+            result?;
+
+            let keep_empty = serializer.config.keep_empty_attributes && serializer.has_text;
+            let minimal = serializer.config.escape_level == EscapeLevel::Minimal;
+            let escape_whitespaces = !serializer.config.keep_attribute_whitespaces;
+            let mut writer = serializer.writer;
+            // Content of elements can be preceded by an indentation
+            let first = writer.inner().iter().find(|b| !is_whitespace(**b)).cloned();
+            if first == Some(b'<') || (key == INNER_VALUE && !writer.inner().is_empty()) {
+                writer
+            } else {
+                if !writer.inner().is_empty() || (keep_empty && key != INNER_VALUE) {
+                    let value = escape_attribute_chars(&self.buffer, minimal, escape_whitespaces);
+                    self.attrs.push_attribute((key.as_bytes(), value.as_ref()));
                     self.buffer.clear();
                 }
+                return Ok(());
             }
-        }
+        };
 
+        // Drains buffer, moves it to children
+        if self.attributes_first {
+            Self::start(&mut self.parent.writer, &self.attrs, &mut self.started)?;
+            self.parent.writer.append(&mut writer)?;
+        } else {
+            self.children.append(&mut writer)?;
+        }
         Ok(())
     }
 
-    fn end(self) -> Result<Self::Ok, DeError> {
-        if self.children.is_empty() {
-            self.parent.writer.write_event(Event::Empty(self.attrs))?;
+    fn end(mut self) -> Result<Self::Ok, DeError> {
+        if self.started {
+            self.parent
+                .writer
+                .write_event(Event::End(self.attrs.to_end()))?;
+        } else if self.children.inner().is_empty() {
+            self.parent
+                .writer
+                .write_event(Event::Empty(self.attrs.to_borrowed()))?;
         } else {
             self.parent
                 .writer
                 .write_event(Event::Start(self.attrs.to_borrowed()))?;
-            self.parent.writer.write(&self.children)?;
+            self.parent.writer.append(&mut self.children)?;
             self.parent
                 .writer
                 .write_event(Event::End(self.attrs.to_end()))?;
         }
+        // Given back in the reverse order, so each buffer is taken for the same purpose again
+        let buffers = &mut self.parent.buffers;
+        buffers.give(self.buffer);
+        buffers.give(self.attrs.into_inner().into_owned());
+        buffers.give(self.children.into_inner());
         Ok(())
     }
 }
@@ -186,6 +370,18 @@ where
     W: 'w + Write,
 {
     parent: &'w mut Serializer<'r, W>,
+    /// Whether at least one element was serialized
+    has_elements: bool,
+    /// Name of the element, which wraps the items
+    wrapper: Option<&'r str>,
+    /// Whether the sequence is the root of the document
+    root: bool,
+    /// Root tag of the parent, taken when the sequence is wrapped into the root element
+    /// named by it, so the items are named by themselves
+    root_tag: Option<&'r str>,
+    /// Whether the items are pairs, which are written as entries of an association list
+    /// inside of an element named by the root tag of the parent
+    pairs: bool,
 }
 
 impl<'r, 'w, W> Seq<'r, 'w, W>
@@ -193,8 +389,75 @@ where
     W: 'w + Write,
 {
     /// Create a new `Seq`
-    pub fn new(parent: &'w mut Serializer<'r, W>) -> Self {
-        Seq { parent }
+    pub fn new(parent: &'w mut Serializer<'r, W>, wrapper: Option<&'r str>) -> Self {
+        Seq {
+            parent,
+            has_elements: false,
+            wrapper,
+            root: false,
+            root_tag: None,
+            pairs: false,
+        }
+    }
+
+    /// Marks the sequence as the `root` of the document, which took the `root_tag` of
+    /// the parent. Both are returned to the parent at the end
+    pub(super) fn root(mut self, root: bool, root_tag: Option<&'r str>) -> Self {
+        self.root = root;
+        self.root_tag = root_tag;
+        self
+    }
+
+    /// Serializes an item of a sequence, which is a value of a map entry, as an
+    /// element named by the key. Items, that are not written as elements by
+    /// themselves, are wrapped into `<tag>...</tag>`
+    fn serialize_entry_item<T: ?Sized + Serialize>(
+        &mut self,
+        tag: &str,
+        value: &T,
+    ) -> Result<(), DeError> {
+        let mut buffer = self.parent.buffers.take();
+        let writer = self.parent.writer.fork(&mut buffer);
+        let mut serializer = self.parent.nested(writer, Some(tag));
+        let result = serializer
+            .probe_struct(value)
+            .and_then(|_| serializer.serialize_adapted(value));
+        self.parent.restore(&mut serializer);
+        result?;
+
+        let has_text = serializer.has_text;
+        let mut writer = serializer.writer;
+        let first = writer.inner().iter().find(|b| !is_whitespace(**b)).cloned();
+        if first == Some(b'<') {
+            self.parent.writer.append(&mut writer)?;
+        } else if has_text || !writer.inner().is_empty() {
+            let name = BytesStart::borrowed_name(tag.as_bytes());
+            self.parent.writer.write_event(Event::Start(name))?;
+            self.parent.writer.append(&mut writer)?;
+            self.parent
+                .writer
+                .write_event(Event::End(BytesEnd::borrowed(tag.as_bytes())))?;
+        }
+        self.parent.buffers.give(buffer);
+        Ok(())
+    }
+
+    /// Serializes a pair as an entry of an association list. The entries are written
+    /// inside of an element named by the root tag of the parent, started before the first
+    /// entry, or, as the root, inside of the root element
+    fn serialize_pair<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), DeError> {
+        if !self.pairs {
+            if let Some(tag) = self.parent.root_tag {
+                let start = BytesStart::borrowed_name(tag.as_bytes());
+                self.parent.writer.write_event(Event::Start(start))?;
+            }
+            self.pairs = true;
+        }
+        let entry = std::mem::replace(&mut self.parent.entry, EntryValue::None);
+        self.parent.pair = true;
+        let result = self.parent.serialize_adapted(value);
+        self.parent.entry = entry;
+        result
     }
 }
 
@@ -209,11 +472,50 @@ where
     where
         T: Serialize,
     {
-        value.serialize(&mut *self.parent)?;
+        if let (false, Some(wrapper)) = (self.has_elements, self.wrapper) {
+            let start = BytesStart::borrowed_name(wrapper.as_bytes());
+            self.parent.writer.write_event(Event::Start(start))?;
+        }
+        let pairs = self.parent.config.assoc_list_style != AssocListStyle::Tuple;
+        match (self.parent.entry, self.parent.root_tag) {
+            _ if pairs && self.parent.is_pair(value)? => self.serialize_pair(value)?,
+            (EntryValue::Seq, Some(tag)) => self.serialize_entry_item(tag, value)?,
+            _ => {
+                self.parent.probe_struct(value)?;
+                self.parent.serialize_adapted(value)?
+            }
+        }
+        self.has_elements = true;
+        if self.parent.config.flush_each_element {
+            self.parent.writer.flush()?;
+        }
         Ok(())
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
+        if let (true, Some(tag)) = (self.pairs, self.parent.root_tag) {
+            self.parent
+                .writer
+                .write_event(Event::End(BytesEnd::borrowed(tag.as_bytes())))?;
+        }
+        if let Some(wrapper) = self.wrapper {
+            if self.has_elements {
+                self.parent
+                    .writer
+                    .write_event(Event::End(BytesEnd::borrowed(wrapper.as_bytes())))?;
+            } else {
+                self.parent.write_self_closed(wrapper)?;
+            }
+        } else if !self.has_elements && self.parent.config.empty_seq == EmptySeqPolicy::EmptyElement
+        {
+            if let Some(tag) = self.parent.root_tag {
+                self.parent.write_self_closed(tag)?;
+            }
+        }
+        if self.root {
+            self.parent.root = true;
+            self.parent.root_tag = self.root_tag.or(self.parent.root_tag);
+        }
         Ok(())
     }
 }
@@ -227,6 +529,12 @@ where
     parent: &'w mut Serializer<'r, W>,
     /// Possible qualified name of XML tag surrounding each element
     name: &'r str,
+    /// Position of the next element
+    index: usize,
+    /// Whether the tuple is a pair, written as an entry of an association list
+    pair: bool,
+    /// Serialized key of the pair, written as the name of the entry element
+    key: Vec<u8>,
 }
 
 impl<'r, 'w, W> Tuple<'r, 'w, W>
@@ -235,7 +543,52 @@ where
 {
     /// Create a new `Tuple`
     pub fn new(parent: &'w mut Serializer<'r, W>, name: &'r str) -> Self {
-        Tuple { parent, name }
+        Tuple {
+            parent,
+            name,
+            index: 0,
+            pair: false,
+            key: Vec::new(),
+        }
+    }
+
+    /// Create a new `Tuple` for a pair, written as an entry of an association list
+    pub fn pair(parent: &'w mut Serializer<'r, W>) -> Self {
+        Tuple {
+            pair: true,
+            ..Tuple::new(parent, "")
+        }
+    }
+
+    /// Writes the key, which is the first element of a pair, or the value, which is
+    /// the second one, as a part of an entry according to `assoc_list_style`
+    fn serialize_pair_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), DeError> {
+        match (self.parent.config.assoc_list_style, self.index) {
+            (AssocListStyle::Entries, 0) => {
+                let start = BytesStart::borrowed_name(b"entry");
+                self.parent.writer.write_event(Event::Start(start))?;
+                self.parent.write_paired("key", value)
+            }
+            (AssocListStyle::Entries, _) => {
+                self.parent.write_paired("value", value)?;
+                self.parent
+                    .writer
+                    .write_event(Event::End(BytesEnd::borrowed(b"entry")))?;
+                Ok(())
+            }
+            (_, 0) => {
+                self.key = self.parent.buffers.take();
+                Map::serialize_key_into(self.parent, value, &mut self.key)
+            }
+            (_, _) => {
+                let mut map = Map::new(&mut *self.parent);
+                let result = map.serialize_value_of(&self.key, value);
+                let buffer = map.value;
+                self.parent.buffers.give(buffer);
+                self.parent.buffers.give(std::mem::take(&mut self.key));
+                result
+            }
+        }
     }
 }
 
@@ -250,10 +603,21 @@ where
     where
         T: Serialize,
     {
-        write!(self.parent.writer.inner(), "<{}>", self.name).map_err(Error::Io)?;
-        value.serialize(&mut *self.parent)?;
-        write!(self.parent.writer.inner(), "</{}>", self.name).map_err(Error::Io)?;
-        Ok(())
+        if self.pair {
+            let result = self.serialize_pair_element(value);
+            self.index += 1;
+            return result;
+        }
+        let name = self
+            .parent
+            .config
+            .tuple_element_names
+            .and_then(|names| names.get(self.index))
+            .cloned()
+            .unwrap_or(self.name);
+        self.index += 1;
+
+        self.parent.write_paired(name, value)
     }
 
     #[inline]
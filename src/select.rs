@@ -0,0 +1,358 @@
+//! Streaming selection of elements and attributes by a simple absolute path
+
+use std::io::BufRead;
+use std::ops::Range;
+
+use errors::{Error, Result};
+use events::{BytesStart, Event};
+use reader::Reader;
+
+/// A step of a path
+#[derive(Clone, Debug)]
+enum Step {
+    /// An element with the given qualified name
+    Element(Vec<u8>),
+    /// Any element, `*`
+    Any,
+}
+
+impl Step {
+    fn matches(&self, e: &BytesStart) -> bool {
+        match self {
+            Step::Element(name) => e.name().as_ref() == &name[..],
+            Step::Any => true,
+        }
+    }
+}
+
+/// A match of a path, returned by [`Select`].
+///
+/// [`Select`]: struct.Select.html
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Selected {
+    /// The unescaped and decoded value of an attribute, selected by a `@name` step
+    Attribute(String),
+    /// A selected element
+    Element {
+        /// The unescaped and decoded texts and CDATA sections of the element and of all
+        /// its descendants, concatenated
+        text: String,
+        /// The position of the element in the input, from the `<` of the start tag to the
+        /// `>` of the end tag. Positions are the same as returned by
+        /// [`Reader::buffer_position`], so for a reader over a slice `&xml[span]` can be
+        /// read again by another `Reader` to get all events of the element.
+        ///
+        /// [`Reader::buffer_position`]: struct.Reader.html#method.buffer_position
+        span: Range<usize>,
+    },
+}
+
+impl Selected {
+    /// Returns the value of the attribute or the text of the element
+    pub fn text(&self) -> &str {
+        match self {
+            Selected::Attribute(value) => value,
+            Selected::Element { text, .. } => text,
+        }
+    }
+}
+
+/// An iterator over the matches of a path in the events of a [`Reader`], created by
+/// [`Reader::select`].
+///
+/// Only the elements of the current path are tracked and subtrees, which cannot match,
+/// are skipped with [`Reader::read_to_end`], so documents of any size are processed in
+/// constant memory. Only the text of a matched element is accumulated.
+///
+/// Iteration stops at the end of the document or after the first error.
+///
+/// [`Reader`]: struct.Reader.html
+/// [`Reader::select`]: struct.Reader.html#method.select
+/// [`Reader::read_to_end`]: struct.Reader.html#method.read_to_end
+pub struct Select<'r, B: BufRead + 'r> {
+    reader: &'r mut Reader<B>,
+    /// Steps for the elements of the path
+    steps: Vec<Step>,
+    /// Name of the attribute, if the path ends with a `@name` step
+    attribute: Option<Vec<u8>>,
+    /// Number of the steps, matched by the currently opened elements
+    matched: usize,
+    /// Buffer for events, which are matched against the path
+    buf: Vec<u8>,
+    /// Buffer for events inside a selected element or a skipped subtree
+    inner: Vec<u8>,
+    /// Set when the end of the document or an error was reached
+    done: bool,
+}
+
+impl<'r, B: BufRead> Select<'r, B> {
+    /// Parses the path and creates an iterator over its matches in the `reader`
+    pub(crate) fn new(reader: &'r mut Reader<B>, path: &str) -> Result<Select<'r, B>> {
+        let invalid = |reason: &str| Err(Error::InvalidPath(format!("{}: {:?}", reason, path)));
+        if !path.starts_with('/') {
+            return invalid("path must start with `/`");
+        }
+        let mut steps = Vec::new();
+        let mut attribute = None;
+        for step in path[1..].split('/') {
+            if attribute.is_some() {
+                return invalid("attribute must be the last step");
+            }
+            match step {
+                "" => return invalid("empty step"),
+                "*" => steps.push(Step::Any),
+                _ if step.starts_with('@') && step.len() > 1 && !steps.is_empty() => {
+                    attribute = Some(step.as_bytes()[1..].to_vec());
+                }
+                _ if step.starts_with('@') => return invalid("invalid attribute step"),
+                _ => steps.push(Step::Element(step.as_bytes().to_vec())),
+            }
+        }
+        Ok(Select {
+            reader,
+            steps,
+            attribute,
+            matched: 0,
+            buf: Vec::new(),
+            inner: Vec::new(),
+            done: false,
+        })
+    }
+
+    /// Reads events until the next match
+    fn read_match(&mut self) -> Result<Option<Selected>> {
+        loop {
+            self.buf.clear();
+            let (e, empty) = match self.reader.read_event(&mut self.buf)? {
+                Event::Start(e) => (e, false),
+                Event::Empty(e) => (e, true),
+                Event::End(_) => {
+                    self.matched = self.matched.saturating_sub(1);
+                    continue;
+                }
+                Event::Eof => return Ok(None),
+                _ => continue,
+            };
+            if !self.steps[self.matched].matches(&e) {
+                if !empty {
+                    self.reader.read_to_end(e.name(), &mut self.inner)?;
+                }
+                continue;
+            }
+            if self.matched + 1 < self.steps.len() {
+                if !empty {
+                    self.matched += 1;
+                }
+                continue;
+            }
+
+            // The last element of the path. The event may differ from the markup in the
+            // input, when the reader relaxes attributes or expands empty elements
+            let start = self.reader.markup_start();
+            let end = self.reader.buffer_position();
+            if let Some(ref name) = self.attribute {
+                let mut value = None;
+                for attr in e.attributes() {
+                    let attr = attr?;
+                    if attr.key.as_ref() == &name[..] {
+                        value = Some(attr.unescape_and_decode_value(self.reader)?);
+                        break;
+                    }
+                }
+                if !empty {
+                    self.reader.read_to_end(e.name(), &mut self.inner)?;
+                }
+                match value {
+                    Some(value) => return Ok(Some(Selected::Attribute(value))),
+                    None => continue,
+                }
+            }
+            if empty {
+                return Ok(Some(Selected::Element {
+                    text: String::new(),
+                    span: start..end,
+                }));
+            }
+            let text = self.read_text()?;
+            return Ok(Some(Selected::Element {
+                text,
+                span: start..self.reader.buffer_position(),
+            }));
+        }
+    }
+
+    /// Reads the content of the element after its start tag and concatenates its texts
+    fn read_text(&mut self) -> Result<String> {
+        let mut text = String::new();
+        let mut depth = 0;
+        loop {
+            self.inner.clear();
+            match self.reader.read_event(&mut self.inner)? {
+                Event::Start(_) => depth += 1,
+                Event::End(_) if depth == 0 => return Ok(text),
+                Event::End(_) => depth -= 1,
+                Event::Text(e) | Event::CData(e) => {
+                    text.push_str(&e.unescape_and_decode(self.reader)?)
+                }
+                Event::Eof => return Err(Error::UnexpectedEof("Element".to_string())),
+                _ => {}
+            }
+        }
+    }
+}
+
+impl<'r, B: BufRead> Iterator for Select<'r, B> {
+    type Item = Result<Selected>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        match self.read_match() {
+            Ok(Some(selected)) => Some(Ok(selected)),
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn select(xml: &str, path: &str) -> Vec<Selected> {
+        let mut reader = Reader::from_str(xml);
+        reader.trim_text(true);
+        let selected = reader.select(path).unwrap().collect::<Result<Vec<_>>>();
+        selected.unwrap()
+    }
+
+    fn texts(xml: &str, path: &str) -> Vec<String> {
+        select(xml, path)
+            .iter()
+            .map(|s| s.text().to_string())
+            .collect()
+    }
+
+    const XML: &str = r#"<catalog>
+        <book id="1"><title>First &amp; best</title><author>A</author></book>
+        <magazine id="2"><title>Monthly</title></magazine>
+        <book id="3"><title><![CDATA[<Second>]]></title><title lang="en"/></book>
+        <book><extra><title>nested</title></extra></book>
+    </catalog>"#;
+
+    #[test]
+    fn child_path() {
+        assert_eq!(
+            texts(XML, "/catalog/book/title"),
+            vec!["First & best", "<Second>", ""]
+        );
+        assert_eq!(texts(XML, "/catalog/book/missing"), Vec::<String>::new());
+        assert_eq!(texts(XML, "/other/book/title"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn wildcard() {
+        assert_eq!(
+            texts(XML, "/catalog/*/title"),
+            vec!["First & best", "Monthly", "<Second>", ""]
+        );
+        assert_eq!(texts(XML, "/*/*/*/title"), vec!["nested"]);
+    }
+
+    #[test]
+    fn attribute() {
+        assert_eq!(texts(XML, "/catalog/book/@id"), vec!["1", "3"]);
+        assert_eq!(texts(XML, "/catalog/*/@id"), vec!["1", "2", "3"]);
+        assert_eq!(texts(XML, "/catalog/book/title/@lang"), vec!["en"]);
+    }
+
+    #[test]
+    fn element_text_and_span() {
+        let selected = select(XML, "/catalog/book");
+        assert_eq!(selected.len(), 3);
+        match &selected[0] {
+            Selected::Element { text, span } => {
+                assert_eq!(text, "First & bestA");
+                assert_eq!(
+                    &XML[span.clone()],
+                    r#"<book id="1"><title>First &amp; best</title><author>A</author></book>"#
+                );
+            }
+            s => panic!("Expected an element, but got {:?}", s),
+        }
+        match &selected[1] {
+            Selected::Element { span, .. } => {
+                let mut reader = Reader::from_str(&XML[span.clone()]);
+                reader.trim_text(true);
+                let mut buf = Vec::new();
+                match reader.read_event(&mut buf).unwrap() {
+                    Event::Start(e) => assert_eq!(e.name().as_ref(), b"book"),
+                    e => panic!("Expected `Start`, but got {:?}", e),
+                }
+            }
+            s => panic!("Expected an element, but got {:?}", s),
+        }
+
+        let empty = select(XML, "/catalog/book/title");
+        match &empty[2] {
+            Selected::Element { span, .. } => {
+                assert_eq!(&XML[span.clone()], r#"<title lang="en"/>"#)
+            }
+            s => panic!("Expected an element, but got {:?}", s),
+        }
+    }
+
+    #[test]
+    fn span_of_relaxed_markup() {
+        let xml = "<a b=1>t</a>";
+        let mut reader = Reader::from_str(xml);
+        reader.lenient_attribute_values(true);
+        let selected = reader.select("/a").unwrap().collect::<Result<Vec<_>>>();
+        match &selected.unwrap()[..] {
+            [Selected::Element { text, span }] => {
+                assert_eq!(text, "t");
+                assert_eq!(&xml[span.clone()], xml);
+            }
+            s => panic!("Expected an element, but got {:?}", s),
+        }
+
+        let xml = "<r><a x='1'/><a>t</a></r>";
+        let mut reader = Reader::from_str(xml);
+        reader.expand_empty_elements(true);
+        let selected = reader.select("/r/a").unwrap().collect::<Result<Vec<_>>>();
+        match &selected.unwrap()[..] {
+            [Selected::Element { span: first, .. }, Selected::Element { span: second, .. }] => {
+                assert_eq!(first.clone(), 3..13);
+                assert_eq!(&xml[first.clone()], "<a x='1'/>");
+                assert_eq!(&xml[second.clone()], "<a>t</a>");
+            }
+            s => panic!("Expected two elements, but got {:?}", s),
+        }
+    }
+
+    #[test]
+    fn invalid_path() {
+        let mut reader = Reader::from_str(XML);
+        for path in &[
+            "",
+            "catalog",
+            "/catalog//book",
+            "/@id",
+            "/catalog/@id/book",
+            "/a/@",
+        ] {
+            match reader.select(path) {
+                Err(Error::InvalidPath(_)) => {}
+                Err(e) => panic!("Expected `InvalidPath` for {:?}, but got {:?}", path, e),
+                Ok(_) => panic!("Expected `InvalidPath` for {:?}, but got a match", path),
+            }
+        }
+    }
+}
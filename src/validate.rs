@@ -0,0 +1,657 @@
+//! Well-formedness checking of XML documents, for example, to lint XML resources in CI.
+//!
+//! [`check`] reads a document in the strictest configuration and collects all found
+//! [`Issue`]s with their positions instead of stopping at the first one:
+//! - syntax errors reported by the [`Reader`];
+//! - closing tags, which do not match the opening tags, and unclosed elements;
+//! - malformed and duplicated attributes, and invalid names of elements;
+//! - unknown entities and invalid character references in texts and attribute values;
+//! - characters, which are not allowed in XML documents, like control characters, and
+//!   invalid UTF-8 in documents without a declared encoding or declared as UTF-8;
+//! - comments containing `--` or ending with `-`;
+//! - a missing root element, several root elements and texts outside of the root element;
+//! - XML declarations and doctypes at wrong places, and processing instructions with
+//!   the reserved `xml` target.
+//!
+//! Only the predefined XML entities are known, so entities declared in a DTD are reported.
+//!
+//! The document is read event by event, so the memory used does not depend on the size of
+//! the document, only on the size of the largest event and the depth of nesting.
+//!
+//! [`check`]: fn.check.html
+//! [`Issue`]: struct.Issue.html
+//! [`Reader`]: ../struct.Reader.html
+
+use std::fmt;
+use std::io::BufRead;
+use std::str::from_utf8;
+
+use errors::Error;
+use escape::do_unescape_with_html;
+use events::{BytesStart, BytesText, Event};
+use reader::{is_whitespace, Reader};
+
+/// The maximum number of issues reported by [`check`].
+///
+/// [`check`]: fn.check.html
+pub const DEFAULT_MAX_ISSUES: usize = 100;
+
+/// Severity of an [`Issue`].
+///
+/// [`Issue`]: struct.Issue.html
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// The document is not well-formed, but the rest of it is checked
+    Recoverable,
+    /// The document cannot be read after this issue, so checking is stopped
+    Fatal,
+}
+
+/// What is wrong in an [`Issue`].
+///
+/// [`Issue`]: struct.Issue.html
+#[derive(Debug)]
+pub enum IssueKind {
+    /// An error of the reader or of the parsing of attributes and texts
+    Xml(Error),
+    /// The name of an element is empty or starts with a character, which cannot start names
+    InvalidName(String),
+    /// A character, which is not allowed in XML documents
+    InvalidCharacter(char),
+    /// The document does not have a root element
+    MissingRoot,
+    /// An element after the end of the root element
+    MultipleRoots(String),
+    /// A text or a CDATA section outside of the root element
+    TextOutsideRoot,
+    /// An XML declaration, which is not at the start of the document
+    MisplacedDeclaration,
+    /// A doctype after another doctype or after the start of the root element
+    MisplacedDocType,
+    /// A processing instruction with the reserved target `xml` in any case, which is not
+    /// an XML declaration
+    ReservedTarget(String),
+}
+
+impl fmt::Display for IssueKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            IssueKind::Xml(e) => write!(f, "{}", e),
+            IssueKind::InvalidName(name) => write!(f, "Invalid element name '{}'", name),
+            IssueKind::InvalidCharacter(c) => {
+                write!(f, "Character U+{:04X} is not allowed", *c as u32)
+            }
+            IssueKind::MissingRoot => write!(f, "Document has no root element"),
+            IssueKind::MultipleRoots(name) => {
+                write!(f, "Element <{}> is after the root element", name)
+            }
+            IssueKind::TextOutsideRoot => write!(f, "Text is outside of the root element"),
+            IssueKind::MisplacedDeclaration => {
+                write!(f, "XML declaration is not at the start of the document")
+            }
+            IssueKind::MisplacedDocType => {
+                write!(f, "DOCTYPE is not before the root element or is repeated")
+            }
+            IssueKind::ReservedTarget(target) => {
+                write!(f, "Processing instruction target '{}' is reserved", target)
+            }
+        }
+    }
+}
+
+/// A problem in a document, found by [`check`].
+///
+/// [`check`]: fn.check.html
+#[derive(Debug)]
+pub struct Issue {
+    /// Whether the rest of the document was checked
+    pub severity: Severity,
+    /// Position of the problem in bytes from the start of the document. It is the position
+    /// of the markup or the text containing the problem, or of the problem itself where
+    /// it is known
+    pub position: usize,
+    /// The problem
+    pub kind: IssueKind,
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Recoverable => "error",
+            Severity::Fatal => "fatal error",
+        };
+        write!(
+            f,
+            "{} at position {}: {}",
+            severity, self.position, self.kind
+        )
+    }
+}
+
+/// Checks that the document read from `input` is well-formed and returns all found issues,
+/// but no more than [`DEFAULT_MAX_ISSUES`]. See the [module documentation](index.html)
+/// for what is checked.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate quick_xml;
+/// # fn main() {
+/// use quick_xml::validate::{check, Severity};
+///
+/// assert!(check(r#"<?xml version="1.0"?><root a="1"/>"#.as_bytes()).is_empty());
+///
+/// let issues = check(r#"<root a="1" a="2"><b></c></b></root><root/>"#.as_bytes());
+/// let messages: Vec<_> = issues.iter().map(|i| i.to_string()).collect();
+/// assert_eq!(
+///     messages,
+///     vec![
+///         "error at position 12: error while parsing attribute at position 11: \
+///          Duplicate attribute at position 5 and 11",
+///         "error at position 23: Expecting </b> found </c>",
+///         "error at position 36: Element <root> is after the root element",
+///     ]
+/// );
+/// assert!(issues.iter().all(|i| i.severity == Severity::Recoverable));
+/// # }
+/// ```
+///
+/// [`DEFAULT_MAX_ISSUES`]: constant.DEFAULT_MAX_ISSUES.html
+pub fn check<R: BufRead>(input: R) -> Vec<Issue> {
+    check_with_limit(input, DEFAULT_MAX_ISSUES)
+}
+
+/// Checks that the document read from `input` is well-formed as [`check`] does, but
+/// returns no more than `max_issues` issues. Checking is stopped when the limit is reached.
+///
+/// [`check`]: fn.check.html
+pub fn check_with_limit<R: BufRead>(input: R, max_issues: usize) -> Vec<Issue> {
+    let mut checker = Checker {
+        issues: Vec::new(),
+        max_issues,
+        opened_buffer: Vec::new(),
+        opened_starts: Vec::new(),
+        has_root: false,
+        started: false,
+        has_doctype: false,
+        utf8: true,
+        bom_len: 0,
+    };
+    checker.run(input);
+    checker.issues
+}
+
+struct Checker {
+    issues: Vec<Issue>,
+    max_issues: usize,
+    /// Names of the currently opened elements
+    opened_buffer: Vec<u8>,
+    /// Start indexes of the names in `opened_buffer`
+    opened_starts: Vec<usize>,
+    /// Whether the root element was started
+    has_root: bool,
+    /// Whether anything except the XML declaration was read
+    started: bool,
+    has_doctype: bool,
+    /// Whether the document is checked to be valid UTF-8
+    utf8: bool,
+    /// Length of the UTF-8 byte order mark at the start of the document, after which
+    /// the XML declaration is allowed
+    bom_len: usize,
+}
+
+impl Checker {
+    fn run<R: BufRead>(&mut self, input: R) {
+        let mut reader = Reader::from_reader(input);
+        reader
+            .check_end_names(false)
+            .expand_empty_elements(false)
+            .trim_text(false);
+        let mut buf = Vec::new();
+        while self.issues.len() < self.max_issues {
+            let position = reader.buffer_position();
+            match reader.read_event(&mut buf) {
+                Ok(Event::Eof) => {
+                    self.finish(reader.buffer_position());
+                    break;
+                }
+                Ok(event) => self.event(&event, position),
+                Err(e) => {
+                    self.report(Severity::Fatal, reader.buffer_position(), IssueKind::Xml(e));
+                    break;
+                }
+            }
+            buf.clear();
+        }
+        self.issues.truncate(self.max_issues);
+    }
+
+    fn report(&mut self, severity: Severity, position: usize, kind: IssueKind) {
+        self.issues.push(Issue {
+            severity,
+            position,
+            kind,
+        });
+    }
+
+    fn recoverable(&mut self, position: usize, kind: IssueKind) {
+        self.report(Severity::Recoverable, position, kind);
+    }
+
+    fn event(&mut self, event: &Event, position: usize) {
+        match event {
+            // The reader starts with an empty text
+            Event::Text(e) if e.is_empty() => return,
+            // The byte order mark is returned as a part of the first text
+            Event::Text(e) if position == 0 && e.starts_with(b"\xEF\xBB\xBF") => {
+                self.bom_len = 3;
+                let text = BytesText::from_escaped(&e[3..]);
+                return self.event(&Event::Text(text), 3);
+            }
+            Event::Decl(e) => {
+                if self.started || position != self.bom_len {
+                    self.recoverable(position, IssueKind::MisplacedDeclaration);
+                }
+                if let Err(e) = e.version() {
+                    self.recoverable(position, IssueKind::Xml(e));
+                }
+                if let Some(Ok(encoding)) = e.encoding() {
+                    self.utf8 = encoding.eq_ignore_ascii_case(b"utf-8")
+                        || encoding.eq_ignore_ascii_case(b"utf8");
+                }
+                self.started = true;
+                return;
+            }
+            Event::Start(e) | Event::Empty(e) => {
+                self.start(e, position);
+                if let Event::Start(_) = event {
+                    self.opened_starts.push(self.opened_buffer.len());
+                    self.opened_buffer.extend_from_slice(e.name().as_ref());
+                }
+            }
+            Event::End(e) => self.end(e.name().as_ref(), position),
+            Event::Text(e) => {
+                if self.opened_starts.is_empty() {
+                    if !e.iter().all(|&b| is_whitespace(b)) {
+                        self.recoverable(position, IssueKind::TextOutsideRoot);
+                    }
+                } else {
+                    self.characters(e, position);
+                    if let Err(e) = do_unescape_with_html(e, None, false) {
+                        let e = Error::EscapeError(e.with_offset(position));
+                        self.recoverable(position, IssueKind::Xml(e));
+                    }
+                }
+            }
+            Event::CData(e) => {
+                if self.opened_starts.is_empty() {
+                    self.recoverable(position, IssueKind::TextOutsideRoot);
+                }
+                // `<![CDATA[`
+                self.characters(e, position + 9);
+            }
+            Event::DocType(e) => {
+                if self.has_doctype || self.has_root {
+                    self.recoverable(position, IssueKind::MisplacedDocType);
+                }
+                self.has_doctype = true;
+                // `<!DOCTYPE`
+                self.characters(e, position + 9);
+            }
+            Event::Comment(e) => {
+                if e.windows(2).any(|w| w == b"--") || e.ends_with(b"-") {
+                    let text = String::from_utf8_lossy(e).into_owned();
+                    self.recoverable(position, IssueKind::Xml(Error::InvalidComment(text)));
+                }
+                // `<!--`
+                self.characters(e, position + 4);
+            }
+            Event::PI(e) => {
+                let target = e.split(|&b| is_whitespace(b)).next().unwrap_or(&[]);
+                if target.eq_ignore_ascii_case(b"xml") {
+                    let target = String::from_utf8_lossy(target).into_owned();
+                    self.recoverable(position, IssueKind::ReservedTarget(target));
+                }
+                // `<?`
+                self.characters(e, position + 2);
+            }
+            Event::Eof => {}
+        }
+        self.started = true;
+    }
+
+    fn start(&mut self, e: &BytesStart, position: usize) {
+        if self.opened_starts.is_empty() && self.has_root {
+            let name = String::from_utf8_lossy(e.name().as_ref()).into_owned();
+            self.recoverable(position, IssueKind::MultipleRoots(name));
+        }
+        self.has_root = true;
+        match e.name().as_ref().first() {
+            Some(b) if !b.is_ascii_digit() && *b != b'-' && *b != b'.' => {}
+            _ => {
+                let name = String::from_utf8_lossy(e.name().as_ref()).into_owned();
+                self.recoverable(position, IssueKind::InvalidName(name));
+            }
+        }
+        // `<`
+        let content = position + 1;
+        self.characters(e, content);
+        for attr in e.attributes().with_spans() {
+            match attr {
+                Ok(attr) => {
+                    let value = content + attr.value_span.start;
+                    if let Err(e) = do_unescape_with_html(&attr.attribute.value, None, false) {
+                        let e = Error::EscapeError(e.with_offset(value));
+                        self.recoverable(value, IssueKind::Xml(e));
+                    }
+                }
+                Err(e) => {
+                    let offset = match e {
+                        Error::NameWithQuote(p)
+                        | Error::InvalidAttributeName(p)
+                        | Error::NoEqAfterName(p)
+                        | Error::UnquotedValue(p)
+                        | Error::DuplicatedAttribute(p, _) => p,
+                        _ => 0,
+                    };
+                    self.recoverable(content + offset, IssueKind::Xml(e));
+                    // Positions of the following attributes are unreliable
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Checks the closing tag `name` against the opened elements. If the name matches
+    /// an outer element, the inner elements are considered closed, otherwise the closing
+    /// tag is ignored
+    fn end(&mut self, name: &[u8], position: usize) {
+        let found = self
+            .opened_starts
+            .iter()
+            .rposition(|&start| &self.opened_buffer[start..self.end_of(start)] == name);
+        let expected = match self.opened_starts.last() {
+            Some(&start) => &self.opened_buffer[start..],
+            None => &[],
+        };
+        if expected != name {
+            let e = Error::EndEventMismatch {
+                expected: String::from_utf8_lossy(expected).into_owned(),
+                found: String::from_utf8_lossy(name).into_owned(),
+            };
+            // `</`
+            self.recoverable(position + 2, IssueKind::Xml(e));
+        }
+        if let Some(index) = found {
+            let start = self.opened_starts[index];
+            self.opened_starts.truncate(index);
+            self.opened_buffer.truncate(start);
+        }
+    }
+
+    /// Returns the end index in `opened_buffer` of the name, which starts at `start`
+    fn end_of(&self, start: usize) -> usize {
+        self.opened_starts
+            .iter()
+            .find(|&&s| s > start)
+            .cloned()
+            .unwrap_or(self.opened_buffer.len())
+    }
+
+    fn finish(&mut self, position: usize) {
+        while let Some(start) = self.opened_starts.pop() {
+            let name = String::from_utf8_lossy(&self.opened_buffer[start..]).into_owned();
+            self.opened_buffer.truncate(start);
+            self.recoverable(position, IssueKind::Xml(Error::UnclosedElement(name)));
+        }
+        if !self.has_root {
+            self.recoverable(position, IssueKind::MissingRoot);
+        }
+    }
+
+    /// Checks that `raw` content, which starts at the `position`, contains only characters
+    /// allowed in XML documents
+    fn characters(&mut self, raw: &[u8], position: usize) {
+        if self.utf8 {
+            if let Err(e) = from_utf8(raw) {
+                let position = position + e.valid_up_to();
+                self.recoverable(position, IssueKind::Xml(Error::Utf8(e)));
+            }
+        }
+        if let Some((offset, c)) = invalid_char(raw) {
+            self.recoverable(position + offset, IssueKind::InvalidCharacter(c));
+        }
+    }
+}
+
+/// Returns the offset of the first character in `raw`, which is not allowed in XML
+/// documents: control characters except tab, line feed and carriage return, and the
+/// noncharacters U+FFFE and U+FFFF. Only the first such character is returned
+fn invalid_char(raw: &[u8]) -> Option<(usize, char)> {
+    raw.iter().enumerate().find_map(|(i, &b)| match b {
+        b'\t' | b'\n' | b'\r' => None,
+        0..=0x1F => Some((i, b as char)),
+        0xEF => match raw.get(i + 1..i + 3) {
+            Some([0xBF, 0xBE]) => Some((i, '\u{FFFE}')),
+            Some([0xBF, 0xBF]) => Some((i, '\u{FFFF}')),
+            _ => None,
+        },
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the severity, the position and the message of each issue of the document
+    fn issues(xml: &str) -> Vec<(Severity, usize, String)> {
+        check(xml.as_bytes())
+            .into_iter()
+            .map(|i| (i.severity, i.position, i.kind.to_string()))
+            .collect()
+    }
+
+    fn recoverable(position: usize, message: &str) -> (Severity, usize, String) {
+        (Severity::Recoverable, position, message.to_string())
+    }
+
+    #[test]
+    fn well_formed() {
+        let corpus = [
+            "<root/>",
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE root>
+<!-- comment -->
+<?xml-stylesheet href="style.css"?>
+<root xmlns:a="urn:a" a:b="&lt;&#x41;&#66;">
+  text &amp; more <![CDATA[ <raw> & ]]>
+  <a:child/>
+</root>
+<!-- trailing comment -->
+"#,
+        ];
+        for xml in corpus.iter() {
+            assert_eq!(issues(xml), vec![], "{}", xml);
+            // With the byte order mark
+            assert_eq!(issues(&format!("\u{FEFF}{}", xml)), vec![], "{}", xml);
+        }
+    }
+
+    #[test]
+    fn end_names() {
+        assert_eq!(
+            issues("<a><b></a>"),
+            vec![recoverable(8, "Expecting </b> found </a>")]
+        );
+        // A stray closing tag is ignored
+        assert_eq!(
+            issues("<a><b></c></b></a>"),
+            vec![recoverable(8, "Expecting </b> found </c>")]
+        );
+        assert_eq!(
+            issues("<a></a></b>"),
+            vec![recoverable(9, "Expecting </> found </b>")]
+        );
+        assert_eq!(
+            issues("<a><b>"),
+            vec![
+                recoverable(6, "Element <b> is not closed"),
+                recoverable(6, "Element <a> is not closed"),
+            ]
+        );
+    }
+
+    #[test]
+    fn attributes() {
+        assert_eq!(
+            issues(r#"<a x="1" x="2"/>"#),
+            vec![recoverable(
+                9,
+                "error while parsing attribute at position 8: \
+                 Duplicate attribute at position 2 and 8"
+            )]
+        );
+        assert_eq!(
+            issues(r#"<a x=1/>"#),
+            vec![recoverable(
+                5,
+                "error while parsing attribute at position 4: \
+                 Attribute value must start with a quote."
+            )]
+        );
+        assert_eq!(
+            issues(r#"<a x="&unknown;"/>"#),
+            vec![recoverable(
+                6,
+                "Error while escaping character at range 6..15: Unrecognized escape symbol: Ok(\"unknown\")"
+            )]
+        );
+    }
+
+    #[test]
+    fn names_and_characters() {
+        assert_eq!(
+            issues("<1a/>"),
+            vec![recoverable(0, "Invalid element name '1a'")]
+        );
+        assert_eq!(
+            issues("<a>x\u{1}y</a>"),
+            vec![recoverable(4, "Character U+0001 is not allowed")]
+        );
+        assert_eq!(
+            issues("<a b='\u{FFFF}'/>"),
+            vec![recoverable(6, "Character U+FFFF is not allowed")]
+        );
+        assert_eq!(
+            issues("<a><!-- a -- b --></a>"),
+            vec![recoverable(
+                3,
+                "Comment cannot contain '--' or end with '-': \" a -- b \""
+            )]
+        );
+        assert_eq!(
+            issues("<a>&nbsp;</a>"),
+            vec![recoverable(
+                3,
+                "Error while escaping character at range 3..9: Unrecognized escape symbol: Ok(\"nbsp\")"
+            )]
+        );
+
+        let issues = check(&b"<a>\xFF</a>"[..]);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].position, 3);
+        assert!(matches!(issues[0].kind, IssueKind::Xml(Error::Utf8(_))));
+        // Other encodings are not checked to be UTF-8
+        assert!(check(&b"<?xml version='1.0' encoding='latin1'?><a>\xFF</a>"[..]).is_empty());
+    }
+
+    #[test]
+    fn prologue_and_root() {
+        assert_eq!(
+            issues(" <?xml version='1.0'?><a/>"),
+            vec![recoverable(
+                1,
+                "XML declaration is not at the start of the document"
+            )]
+        );
+        assert_eq!(
+            issues("\u{FEFF} <?xml version='1.0'?><a/>"),
+            vec![recoverable(
+                4,
+                "XML declaration is not at the start of the document"
+            )]
+        );
+        assert_eq!(
+            issues("<a/><!DOCTYPE a>"),
+            vec![recoverable(
+                4,
+                "DOCTYPE is not before the root element or is repeated"
+            )]
+        );
+        assert_eq!(
+            issues("<a/>text<b/><![CDATA[x]]>"),
+            vec![
+                recoverable(4, "Text is outside of the root element"),
+                recoverable(8, "Element <b> is after the root element"),
+                recoverable(12, "Text is outside of the root element"),
+            ]
+        );
+        assert_eq!(
+            issues("<!-- only a comment -->"),
+            vec![recoverable(23, "Document has no root element")]
+        );
+    }
+
+    #[test]
+    fn fatal() {
+        let issues = check("<a x='1'><b></c><!-- unclosed".as_bytes());
+        let severities: Vec<_> = issues.iter().map(|i| i.severity).collect();
+        assert_eq!(severities, vec![Severity::Recoverable, Severity::Fatal]);
+        assert!(matches!(
+            issues[1].kind,
+            IssueKind::Xml(Error::UnexpectedEof(_))
+        ));
+    }
+
+    #[test]
+    fn broken_corpus() {
+        use self::Severity::*;
+
+        let corpus = [
+            ("<a>", Recoverable),
+            ("<a></b>", Recoverable),
+            ("</a>", Recoverable),
+            ("<a b='1' b='2'/>", Recoverable),
+            ("<a b=1/>", Recoverable),
+            ("<a>&foo;</a>", Recoverable),
+            ("<a>&#xZZ;</a>", Recoverable),
+            ("<a>\u{8}</a>", Recoverable),
+            ("<a><!-- a ---></a>", Recoverable),
+            ("<a/><b/>", Recoverable),
+            ("text<a/>", Recoverable),
+            ("<a/><?xml version='1.0'?>", Recoverable),
+            ("<?xml?><a/>", Recoverable),
+            ("<!DOCTYPE a><!DOCTYPE a><a/>", Recoverable),
+            ("", Recoverable),
+            ("<a", Fatal),
+            ("<a><!-- </a>", Fatal),
+            ("<a><![CDATA[ </a>", Fatal),
+            ("<a><!x></a>", Fatal),
+        ];
+        for &(xml, severity) in corpus.iter() {
+            let issues = check(xml.as_bytes());
+            assert!(!issues.is_empty(), "{:?}", xml);
+            let worst = issues.iter().map(|i| i.severity).max();
+            assert_eq!(worst, Some(severity), "{:?}: {:?}", xml, issues);
+        }
+    }
+
+    #[test]
+    fn limit() {
+        let xml = "<a>".to_string() + &"</b>".repeat(1000) + "</a>";
+        assert_eq!(check(xml.as_bytes()).len(), DEFAULT_MAX_ISSUES);
+        assert_eq!(check_with_limit(xml.as_bytes(), 3).len(), 3);
+        assert_eq!(check_with_limit(xml.as_bytes(), 0).len(), 0);
+    }
+}
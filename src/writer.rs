@@ -1,13 +1,111 @@
 //! A module to handle `Writer`
 
-use std::io::Write;
+use std::borrow::Cow;
+use std::fmt;
+use std::io::{self, BufRead};
+use std::sync::Arc;
 
 use errors::{Error, Result};
-use events::Event;
+use escape::{
+    escape_control_chars, escape_non_ascii, escape_unescaped, is_control_char, CharRefFormat,
+};
+use events::attributes::{AttrQuote, SpannedAttribute};
+use events::{BytesStart, BytesText, Event};
+use name::{PrefixDeclaration, QName};
+use reader::Reader;
+
+/// A destination of the [`Writer`] output.
+///
+/// Implemented for all [`std::io::Write`] implementors and for [`FmtSink`], which
+/// adapts a [`std::fmt::Write`] implementor, such as `String`.
+///
+/// [`Writer`]: struct.Writer.html
+/// [`std::io::Write`]: https://doc.rust-lang.org/std/io/trait.Write.html
+/// [`FmtSink`]: struct.FmtSink.html
+/// [`std::fmt::Write`]: https://doc.rust-lang.org/std/fmt/trait.Write.html
+pub trait Sink {
+    /// Writes all bytes to the destination
+    fn write_bytes(&mut self, bytes: &[u8]) -> Result<()>;
+
+    /// Flushes the written bytes to the destination, if it is buffered.
+    /// Does nothing by default
+    fn flush_bytes(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl<W: io::Write> Sink for W {
+    #[inline]
+    fn write_bytes(&mut self, bytes: &[u8]) -> Result<()> {
+        self.write_all(bytes).map_err(Error::Io)
+    }
+
+    #[inline]
+    fn flush_bytes(&mut self) -> Result<()> {
+        self.flush().map_err(Error::Io)
+    }
+}
+
+/// An adapter to use a [`std::fmt::Write`] implementor as a destination of the [`Writer`].
+///
+/// Bytes written by the `Writer` must be a valid UTF-8, otherwise [`Error::Utf8`] is returned.
+/// Errors of the underlying writer are returned as [`Error::Fmt`].
+///
+/// # Examples
+///
+/// ```rust
+/// # extern crate quick_xml;
+/// # fn main() {
+/// use quick_xml::{FmtSink, Writer};
+/// use quick_xml::events::{Event, BytesEnd, BytesStart, BytesText};
+///
+/// let mut writer = Writer::new(FmtSink::new(String::new()));
+/// writer.write_event(Event::Start(BytesStart::borrowed_name(b"tag"))).unwrap();
+/// writer.write_event(Event::Text(BytesText::from_plain_str("text"))).unwrap();
+/// writer.write_event(Event::End(BytesEnd::borrowed(b"tag"))).unwrap();
+///
+/// assert_eq!(writer.into_inner().into_inner(), "<tag>text</tag>");
+/// # }
+/// ```
+///
+/// [`std::fmt::Write`]: https://doc.rust-lang.org/std/fmt/trait.Write.html
+/// [`Writer`]: struct.Writer.html
+/// [`Error::Utf8`]: enum.Error.html#variant.Utf8
+/// [`Error::Fmt`]: enum.Error.html#variant.Fmt
+#[derive(Clone, Debug)]
+pub struct FmtSink<W: fmt::Write>(W);
+
+impl<W: fmt::Write> FmtSink<W> {
+    /// Creates a new adapter of a `fmt::Write` implementor
+    pub fn new(inner: W) -> FmtSink<W> {
+        FmtSink(inner)
+    }
+
+    /// Get inner writer, keeping ownership
+    pub fn inner(&mut self) -> &mut W {
+        &mut self.0
+    }
+
+    /// Consumes this adapter, returning the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.0
+    }
+}
+
+impl<W: fmt::Write> Sink for FmtSink<W> {
+    #[inline]
+    fn write_bytes(&mut self, bytes: &[u8]) -> Result<()> {
+        let s = ::std::str::from_utf8(bytes)?;
+        self.0.write_str(s).map_err(Error::Fmt)
+    }
+}
 
 /// XML writer.
 ///
-/// Writes XML `Event`s to a `Write` implementor.
+/// Writes XML `Event`s to a [`Sink`]: a `Write` implementor or a [`FmtSink`].
+///
+/// [`Sink`]: trait.Sink.html
+/// [`FmtSink`]: struct.FmtSink.html
 ///
 /// # Examples
 ///
@@ -25,7 +123,7 @@ use events::Event;
 /// let mut buf = Vec::new();
 /// loop {
 ///     match reader.read_event(&mut buf) {
-///         Ok(Event::Start(ref e)) if e.name() == b"this_tag" => {
+///         Ok(Event::Start(ref e)) if e.name().as_ref() == b"this_tag" => {
 ///
 ///             // crates a new element ... alternatively we could reuse `e` by calling
 ///             // `e.into_owned()`
@@ -40,7 +138,7 @@ use events::Event;
 ///             // writes the event to the writer
 ///             assert!(writer.write_event(Event::Start(elem)).is_ok());
 ///         },
-///         Ok(Event::End(ref e)) if e.name() == b"this_tag" => {
+///         Ok(Event::End(ref e)) if e.name().as_ref() == b"this_tag" => {
 ///             assert!(writer.write_event(Event::End(BytesEnd::borrowed(b"my_elem"))).is_ok());
 ///         },
 ///         Ok(Event::Eof) => break,
@@ -57,45 +155,702 @@ use events::Event;
 /// # }
 /// ```
 #[derive(Clone)]
-pub struct Writer<W: Write> {
+pub struct Writer<W: Sink> {
     /// underlying writer
     writer: W,
     indent: Option<Indentation>,
+    /// which content of the written events should be escaped
+    escape_policy: EscapePolicy,
+    /// whether non-ASCII characters of texts and attribute values are written as
+    /// character references
+    escape_non_ascii: bool,
+    /// format of the character references for non-ASCII characters
+    char_ref_format: CharRefFormat,
+    /// how control characters of texts and attribute values are written
+    control_char_policy: ControlCharPolicy,
+    /// whether rewritten attribute values keep their original quotes
+    preserve_attribute_quotes: bool,
+    /// whether `<tag></tag>` should be written as `<tag/>`
+    minimize_empty_elements: bool,
+    /// start tag, written only when the next event is known
+    pending_start: Option<BytesStart<'static>>,
+    /// whether the root element has been started
+    root_started: bool,
+    /// namespace prefixes to rewrite, `(from, to)`
+    prefix_renames: Vec<(Vec<u8>, Vec<u8>)>,
+    /// names of the elements which are always written as `<tag></tag>`
+    always_expand: Vec<Vec<u8>>,
+    /// names of the elements which are always written as `<tag/>` when empty
+    never_expand: Vec<Vec<u8>>,
+    /// number of bytes written to the underlying writer
+    bytes_written: usize,
+    /// check that written start and end tags are balanced
+    with_checks: bool,
+    /// check that every written chunk is valid UTF-8
+    check_utf8: bool,
+    /// flush the underlying writer after each top-level element
+    auto_flush: bool,
+    /// number of the started elements, which are not ended yet
+    depth: usize,
+    /// names of all currently started elements which didn't have a matching
+    /// end element yet, if `with_checks` is enabled
+    opened_buffer: Vec<u8>,
+    /// opened name start indexes
+    opened_starts: Vec<usize>,
+}
+
+/// Defines which content of the events passed to [`Writer::write_event`] is escaped on write.
+///
+/// [`Writer::write_event`]: struct.Writer.html#method.write_event
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EscapePolicy {
+    /// Content of the events is written as is. The caller is responsible for providing properly
+    /// escaped text and attribute values.
+    Trust,
+    /// Content of [`Text`] events is checked on write: `<`, `>`, `"` and any `&` which does not
+    /// start an entity or character reference are escaped. Already escaped content is kept as is.
+    ///
+    /// [`Text`]: events/enum.Event.html#variant.Text
+    EscapeText,
+    /// Same as [`EscapeText`], but attribute values of [`Start`] and [`Empty`] events are checked
+    /// as well.
+    ///
+    /// [`EscapeText`]: #variant.EscapeText
+    /// [`Start`]: events/enum.Event.html#variant.Start
+    /// [`Empty`]: events/enum.Event.html#variant.Empty
+    EscapeAll,
+}
+
+/// Defines how [`Writer`] writes the C0 control characters of texts and attribute values,
+/// other than tab, line feed and carriage return. XML 1.0 does not allow them in documents,
+/// even as character references.
+///
+/// [`Writer`]: struct.Writer.html
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ControlCharPolicy {
+    /// The event is not written, and [`Error::ControlCharacter`] is returned
+    ///
+    /// [`Error::ControlCharacter`]: enum.Error.html#variant.ControlCharacter
+    Reject,
+    /// Control characters are removed
+    Strip,
+    /// Control characters are written as character references, such as `&#8;`, which are
+    /// allowed in XML 1.1 documents. The references are in the format, set by
+    /// [`Writer::char_ref_format`].
+    ///
+    /// The NUL character is not allowed even as a reference, so an event with it is not
+    /// written, and [`Error::ControlCharacter`] is returned
+    ///
+    /// [`Writer::char_ref_format`]: struct.Writer.html#method.char_ref_format
+    /// [`Error::ControlCharacter`]: enum.Error.html#variant.ControlCharacter
+    NumericEscape,
 }
 
-impl<W: Write> Writer<W> {
+impl<W: Sink> Writer<W> {
     /// Creates a Writer from a generic Write
     pub fn new(inner: W) -> Writer<W> {
         Writer {
             writer: inner,
             indent: None,
+            escape_policy: EscapePolicy::Trust,
+            escape_non_ascii: false,
+            char_ref_format: CharRefFormat::Decimal,
+            control_char_policy: ControlCharPolicy::Reject,
+            preserve_attribute_quotes: false,
+            minimize_empty_elements: false,
+            pending_start: None,
+            root_started: false,
+            prefix_renames: Vec::new(),
+            always_expand: Vec::new(),
+            never_expand: Vec::new(),
+            bytes_written: 0,
+            with_checks: false,
+            check_utf8: false,
+            auto_flush: false,
+            depth: 0,
+            opened_buffer: Vec::new(),
+            opened_starts: Vec::new(),
         }
     }
 
     /// Creates a Writer with configured whitespace indents from a generic Write
+    ///
+    /// Each tag starts on a new indented line, except the tags after a text or a CDATA
+    /// section, so elements with only a text, like `<name>value</name>`, are kept on one line.
     pub fn new_with_indent(inner: W, indent_char: u8, indent_size: usize) -> Writer<W> {
         Writer {
             writer: inner,
             indent: Some(Indentation::new(indent_char, indent_size)),
+            escape_policy: EscapePolicy::Trust,
+            escape_non_ascii: false,
+            char_ref_format: CharRefFormat::Decimal,
+            control_char_policy: ControlCharPolicy::Reject,
+            preserve_attribute_quotes: false,
+            minimize_empty_elements: false,
+            pending_start: None,
+            root_started: false,
+            prefix_renames: Vec::new(),
+            always_expand: Vec::new(),
+            never_expand: Vec::new(),
+            bytes_written: 0,
+            with_checks: false,
+            check_utf8: false,
+            auto_flush: false,
+            depth: 0,
+            opened_buffer: Vec::new(),
+            opened_starts: Vec::new(),
+        }
+    }
+
+    /// Changes which content of the written events is escaped.
+    ///
+    /// Escaping is idempotent for already escaped content, so with [`EscapeText`] or
+    /// [`EscapeAll`] a markup character can never reach the output unescaped, even if the event
+    /// was created from a wrongly escaped string. Use [`write_raw`] to deliberately bypass
+    /// escaping.
+    ///
+    /// (`EscapePolicy::Trust` by default)
+    ///
+    /// [`EscapeText`]: enum.EscapePolicy.html#variant.EscapeText
+    /// [`EscapeAll`]: enum.EscapePolicy.html#variant.EscapeAll
+    /// [`write_raw`]: #method.write_raw
+    pub fn escape_policy(&mut self, policy: EscapePolicy) -> &mut Writer<W> {
+        self.escape_policy = policy;
+        self
+    }
+
+    /// Changes whether non-ASCII characters in [`Text`] events and in attribute values of
+    /// [`Start`] and [`Empty`] events are written as character references, such as `&#233;`
+    /// for `é`, for consumers which cannot handle UTF-8. Names, comments, CDATA sections and
+    /// processing instructions are written as is, because references are not allowed there.
+    ///
+    /// The references are decimal, unless changed by [`char_ref_format`]. Together with
+    /// `CharRefFormat::Hexadecimal` this gives ASCII-only output with references like
+    /// `&#x1F600;`, as long as names, comments and CDATA sections are ASCII.
+    ///
+    /// (`false` by default)
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use quick_xml::events::{BytesStart, BytesText, Event};
+    /// use quick_xml::Writer;
+    ///
+    /// let mut writer = Writer::new(Vec::new());
+    /// writer.escape_non_ascii(true);
+    ///
+    /// let mut start = BytesStart::borrowed_name(b"p");
+    /// start.push_attribute(("title", "caf\u{E9}"));
+    /// writer.write_event(Event::Start(start)).unwrap();
+    /// writer.write_event(Event::Text(BytesText::from_plain_str("\u{1F600}"))).unwrap();
+    /// writer.write_event(Event::End(BytesStart::borrowed_name(b"p").to_end())).unwrap();
+    /// assert_eq!(writer.into_inner(), br#"<p title="caf&#233;">&#128512;</p>"#);
+    /// ```
+    ///
+    /// [`Text`]: events/enum.Event.html#variant.Text
+    /// [`Start`]: events/enum.Event.html#variant.Start
+    /// [`Empty`]: events/enum.Event.html#variant.Empty
+    /// [`char_ref_format`]: #method.char_ref_format
+    pub fn escape_non_ascii(&mut self, val: bool) -> &mut Writer<W> {
+        self.escape_non_ascii = val;
+        self
+    }
+
+    /// Changes the format of the character references written by [`escape_non_ascii`]
+    /// and by [`ControlCharPolicy::NumericEscape`].
+    ///
+    /// (`CharRefFormat::Decimal` by default)
+    ///
+    /// [`escape_non_ascii`]: #method.escape_non_ascii
+    /// [`ControlCharPolicy::NumericEscape`]: enum.ControlCharPolicy.html#variant.NumericEscape
+    pub fn char_ref_format(&mut self, format: CharRefFormat) -> &mut Writer<W> {
+        self.char_ref_format = format;
+        self
+    }
+
+    /// Changes how the control characters in [`Text`] events and in attribute values of
+    /// [`Start`] and [`Empty`] events are written. Such characters, other than tab, line feed
+    /// and carriage return, make an XML 1.0 document not well-formed, so they are rejected
+    /// by default. Names, comments, CDATA sections and processing instructions are written
+    /// as is.
+    ///
+    /// (`ControlCharPolicy::Reject` by default)
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use quick_xml::events::{BytesText, Event};
+    /// use quick_xml::{ControlCharPolicy, Error, Writer};
+    ///
+    /// let text = BytesText::from_plain_str("bell\u{7}");
+    ///
+    /// let mut writer = Writer::new(Vec::new());
+    /// match writer.write_event(Event::Text(text.clone())) {
+    ///     Err(Error::ControlCharacter(7)) => {}
+    ///     e => panic!("Expected `ControlCharacter`, found {:?}", e),
+    /// }
+    ///
+    /// writer.control_char_policy(ControlCharPolicy::NumericEscape);
+    /// writer.write_event(Event::Text(text)).unwrap();
+    /// assert_eq!(writer.into_inner(), b"bell&#7;");
+    /// ```
+    ///
+    /// [`Text`]: events/enum.Event.html#variant.Text
+    /// [`Start`]: events/enum.Event.html#variant.Start
+    /// [`Empty`]: events/enum.Event.html#variant.Empty
+    pub fn control_char_policy(&mut self, policy: ControlCharPolicy) -> &mut Writer<W> {
+        self.control_char_policy = policy;
+        self
+    }
+
+    /// Changes whether attribute values keep their original quotes, when the attributes of
+    /// a [`Start`] or [`Empty`] event are rewritten because of [`EscapePolicy::EscapeAll`],
+    /// [`escape_non_ascii`] or [`rename_prefix`]. Tags, which are not rewritten, are always
+    /// written as is.
+    ///
+    /// When set to `false`, rewritten values are quoted with `"`, and the `"` inside values,
+    /// which were quoted with `'`, are escaped.
+    ///
+    /// (`false` by default)
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use quick_xml::events::Event;
+    /// use quick_xml::{Reader, Writer};
+    ///
+    /// let xml = "<p title='caf\u{E9}' lang=\"fr\" note='\"quoted\"'/>";
+    /// let mut reader = Reader::from_str(xml);
+    /// let mut writer = Writer::new(Vec::new());
+    /// writer.escape_non_ascii(true).preserve_attribute_quotes(true);
+    ///
+    /// let mut buf = Vec::new();
+    /// loop {
+    ///     match reader.read_event(&mut buf).unwrap() {
+    ///         Event::Eof => break,
+    ///         e => writer.write_event(e).unwrap(),
+    ///     }
+    /// }
+    /// assert_eq!(
+    ///     writer.into_inner(),
+    ///     br#"<p title='caf&#233;' lang="fr" note='"quoted"'/>"#.as_ref()
+    /// );
+    /// ```
+    ///
+    /// [`Start`]: events/enum.Event.html#variant.Start
+    /// [`Empty`]: events/enum.Event.html#variant.Empty
+    /// [`EscapePolicy::EscapeAll`]: enum.EscapePolicy.html#variant.EscapeAll
+    /// [`escape_non_ascii`]: #method.escape_non_ascii
+    /// [`rename_prefix`]: #method.rename_prefix
+    pub fn preserve_attribute_quotes(&mut self, val: bool) -> &mut Writer<W> {
+        self.preserve_attribute_quotes = val;
+        self
+    }
+
+    /// Changes whether an [`Event::Start`] immediately followed by the matching [`Event::End`]
+    /// should be written as a single [`Event::Empty`].
+    ///
+    /// When set to `true`, a start tag is not written until the next event is known. Any event
+    /// in between, including whitespace text, but excluding empty text, keeps both tags as is.
+    /// The buffered start tag is written on any other write, on [`Event::Eof`] and in
+    /// [`into_inner`].
+    ///
+    /// (`false` by default)
+    ///
+    /// [`Event::Start`]: events/enum.Event.html#variant.Start
+    /// [`Event::End`]: events/enum.Event.html#variant.End
+    /// [`Event::Empty`]: events/enum.Event.html#variant.Empty
+    /// [`Event::Eof`]: events/enum.Event.html#variant.Eof
+    /// [`into_inner`]: #method.into_inner
+    pub fn minimize_empty_elements(&mut self, val: bool) -> &mut Writer<W> {
+        self.minimize_empty_elements = val;
+        self
+    }
+
+    /// Changes whether written start and end tags are checked to be balanced.
+    ///
+    /// When set to `true`, the writer keeps the names of all opened elements and
+    /// [`write_event`] returns [`Error::EndEventMismatch`] without writing anything,
+    /// if an [`Event::End`] does not match the most recent [`Event::Start`].
+    /// [`Event::Eof`] and [`finish`] return [`Error::UnclosedElement`] if some elements
+    /// are still not closed.
+    ///
+    /// (`false` by default)
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # extern crate quick_xml;
+    /// # fn main() {
+    /// use quick_xml::{Error, Writer};
+    /// use quick_xml::events::{BytesEnd, BytesStart, Event};
+    ///
+    /// let mut writer = Writer::new(Vec::new());
+    /// writer.with_checks(true);
+    /// writer.write_event(Event::Start(BytesStart::borrowed_name(b"a"))).unwrap();
+    /// writer.write_event(Event::Start(BytesStart::borrowed_name(b"b"))).unwrap();
+    /// match writer.write_event(Event::End(BytesEnd::borrowed(b"a"))) {
+    ///     Err(Error::EndEventMismatch { expected, found }) => {
+    ///         assert_eq!(expected, "b");
+    ///         assert_eq!(found, "a");
+    ///     }
+    ///     e => panic!("Expected `EndEventMismatch`, found {:?}", e),
+    /// }
+    /// writer.write_event(Event::End(BytesEnd::borrowed(b"b"))).unwrap();
+    /// match writer.finish() {
+    ///     Err(Error::UnclosedElement(name)) => assert_eq!(name, "a"),
+    ///     e => panic!("Expected `UnclosedElement`, found {:?}", e),
+    /// }
+    /// # }
+    /// ```
+    ///
+    /// [`write_event`]: #method.write_event
+    /// [`finish`]: #method.finish
+    /// [`Error::EndEventMismatch`]: enum.Error.html#variant.EndEventMismatch
+    /// [`Error::UnclosedElement`]: enum.Error.html#variant.UnclosedElement
+    /// [`Event::Start`]: events/enum.Event.html#variant.Start
+    /// [`Event::End`]: events/enum.Event.html#variant.End
+    /// [`Event::Eof`]: events/enum.Event.html#variant.Eof
+    pub fn with_checks(&mut self, val: bool) -> &mut Writer<W> {
+        self.with_checks = val;
+        self
+    }
+
+    /// Checks that every chunk of bytes, written to the underlying writer, is valid UTF-8.
+    ///
+    /// The output of the writer can only become invalid UTF-8 through the bytes, written
+    /// as is: with [`write_raw`], or in the events, created from raw bytes, for example with
+    /// [`BytesText::from_escaped`], or copied by [`copy_subtree_raw`] from a document in
+    /// another encoding. With this check enabled such a write fails with [`Error::Utf8`]
+    /// and nothing is written, so invalid output is detected where it is introduced.
+    ///
+    /// (`false` by default)
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # extern crate quick_xml;
+    /// # fn main() {
+    /// use quick_xml::{Error, Writer};
+    ///
+    /// let mut writer = Writer::new(Vec::new());
+    /// writer.check_utf8(true);
+    /// writer.write_raw(b"<a>").unwrap();
+    /// match writer.write_raw(b"\xFF") {
+    ///     Err(Error::Utf8(_)) => {}
+    ///     e => panic!("Expected `Utf8`, found {:?}", e),
+    /// }
+    /// assert_eq!(writer.into_string().unwrap(), "<a>");
+    /// # }
+    /// ```
+    ///
+    /// [`write_raw`]: #method.write_raw
+    /// [`BytesText::from_escaped`]: events/struct.BytesText.html#method.from_escaped
+    /// [`copy_subtree_raw`]: #method.copy_subtree_raw
+    /// [`Error::Utf8`]: enum.Error.html#variant.Utf8
+    pub fn check_utf8(&mut self, val: bool) -> &mut Writer<W> {
+        self.check_utf8 = val;
+        self
+    }
+
+    /// Changes whether the underlying writer is flushed after each top-level element
+    /// (`false` by default).
+    ///
+    /// The writer is flushed right after the end tag of an element, which is not inside of
+    /// another element, is written. This is useful for the streams of elements, for example,
+    /// in interactive protocols, where each element should reach the peer as soon as it is
+    /// written, even if the underlying writer is buffered. Values, serialized into such
+    /// writer with the [`Serializer`], are top-level elements, so each one is flushed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # extern crate quick_xml;
+    /// # fn main() {
+    /// use std::io::BufWriter;
+    /// use quick_xml::events::{BytesEnd, BytesStart, Event};
+    /// use quick_xml::Writer;
+    ///
+    /// let mut writer = Writer::new(BufWriter::new(Vec::new()));
+    /// writer.auto_flush(true);
+    /// writer.write_event(Event::Start(BytesStart::borrowed_name(b"message"))).unwrap();
+    /// assert!(writer.inner().get_ref().is_empty());
+    /// writer.write_event(Event::End(BytesEnd::borrowed(b"message"))).unwrap();
+    /// assert_eq!(writer.inner().get_ref(), b"<message></message>");
+    /// # }
+    /// ```
+    ///
+    /// [`Serializer`]: se/struct.Serializer.html
+    pub fn auto_flush(&mut self, val: bool) -> &mut Writer<W> {
+        self.auto_flush = val;
+        self
+    }
+
+    /// Rewrites the namespace prefix `from` to `to` in all subsequently written [`Event::Start`],
+    /// [`Event::Empty`] and [`Event::End`] events: in the element names, in the prefixed
+    /// attribute names and in the `xmlns:from` namespace declarations.
+    ///
+    /// Renaming the same prefix again replaces the previous rename, so documents which use
+    /// the same prefix for different namespaces can be merged by changing the rename between
+    /// them. `to` should be a valid non-empty prefix.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # extern crate quick_xml;
+    /// # fn main() {
+    /// use quick_xml::Writer;
+    /// use quick_xml::events::{BytesEnd, BytesStart, Event};
+    ///
+    /// let mut writer = Writer::new(Vec::new());
+    /// writer.rename_prefix("ns1", "soap");
+    ///
+    /// let start = BytesStart::borrowed_name(b"ns1:Envelope")
+    ///     .with_attributes(vec![("xmlns:ns1", "urn:soap"), ("ns1:mustUnderstand", "1")]);
+    /// writer.write_event(Event::Start(start)).unwrap();
+    /// writer.write_event(Event::End(BytesEnd::borrowed(b"ns1:Envelope"))).unwrap();
+    ///
+    /// assert_eq!(
+    ///     writer.into_inner(),
+    ///     br#"<soap:Envelope xmlns:soap="urn:soap" soap:mustUnderstand="1"></soap:Envelope>"#.as_ref()
+    /// );
+    /// # }
+    /// ```
+    ///
+    /// [`Event::Start`]: events/enum.Event.html#variant.Start
+    /// [`Event::Empty`]: events/enum.Event.html#variant.Empty
+    /// [`Event::End`]: events/enum.Event.html#variant.End
+    pub fn rename_prefix(&mut self, from: &str, to: &str) -> &mut Writer<W> {
+        let to = to.as_bytes().to_vec();
+        match self
+            .prefix_renames
+            .iter_mut()
+            .find(|(f, _)| f.as_slice() == from.as_bytes())
+        {
+            Some(rename) => rename.1 = to,
+            None => self.prefix_renames.push((from.as_bytes().to_vec(), to)),
         }
+        self
+    }
+
+    /// Sets names of the elements which are always written with a start and an end tag, such as
+    /// `<p></p>`, even if they are empty. An [`Event::Empty`] with one of these names is expanded
+    /// and [`minimize_empty_elements`] does not apply to them.
+    ///
+    /// Names are compared with the qualified names of the elements as is.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # extern crate quick_xml;
+    /// # fn main() {
+    /// use quick_xml::Writer;
+    /// use quick_xml::events::{BytesStart, Event};
+    ///
+    /// let mut writer = Writer::new(Vec::new());
+    /// writer.always_expand(&["p", "script"]);
+    ///
+    /// writer.write_event(Event::Empty(BytesStart::borrowed_name(b"p"))).unwrap();
+    /// writer.write_event(Event::Empty(BytesStart::borrowed_name(b"br"))).unwrap();
+    ///
+    /// assert_eq!(writer.into_inner(), b"<p></p><br/>".as_ref());
+    /// # }
+    /// ```
+    ///
+    /// [`Event::Empty`]: events/enum.Event.html#variant.Empty
+    /// [`minimize_empty_elements`]: #method.minimize_empty_elements
+    pub fn always_expand(&mut self, names: &[&str]) -> &mut Writer<W> {
+        self.always_expand = names.iter().map(|n| n.as_bytes().to_vec()).collect();
+        self
+    }
+
+    /// Sets names of the elements which are always written as a single self-closed tag, such as
+    /// `<br/>`, when they are empty: an [`Event::Start`] with one of these names, immediately
+    /// followed by the matching [`Event::End`], is written as [`Event::Empty`], as if
+    /// [`minimize_empty_elements`] is set for them.
+    ///
+    /// Names are compared with the qualified names of the elements as is. [`always_expand`] takes
+    /// precedence, if a name is present in both lists.
+    ///
+    /// [`Event::Start`]: events/enum.Event.html#variant.Start
+    /// [`Event::End`]: events/enum.Event.html#variant.End
+    /// [`Event::Empty`]: events/enum.Event.html#variant.Empty
+    /// [`minimize_empty_elements`]: #method.minimize_empty_elements
+    /// [`always_expand`]: #method.always_expand
+    pub fn never_expand(&mut self, names: &[&str]) -> &mut Writer<W> {
+        self.never_expand = names.iter().map(|n| n.as_bytes().to_vec()).collect();
+        self
     }
 
     /// Consumes this `Writer`, returning the underlying writer.
-    pub fn into_inner(self) -> W {
+    ///
+    /// A start tag, buffered because of [`minimize_empty_elements`], is written first.
+    /// Errors of that write are not reported; write [`Event::Eof`] before to get them.
+    ///
+    /// [`minimize_empty_elements`]: #method.minimize_empty_elements
+    /// [`Event::Eof`]: events/enum.Event.html#variant.Eof
+    pub fn into_inner(mut self) -> W {
+        let _ = self.write_pending_start();
         self.writer
     }
 
     /// Get inner writer, keeping ownership
+    ///
+    /// Note, that a start tag, buffered because of [`minimize_empty_elements`],
+    /// is not written yet. Use [`write_raw`] to write raw content after it.
+    ///
+    /// [`minimize_empty_elements`]: #method.minimize_empty_elements
+    /// [`write_raw`]: #method.write_raw
     pub fn inner(&mut self) -> &mut W {
         &mut self.writer
     }
 
+    /// Returns the number of bytes written to the underlying writer so far.
+    ///
+    /// All written bytes are counted, including indentation and characters added by
+    /// escaping. A start tag, buffered because of [`minimize_empty_elements`], is not
+    /// counted until it is written.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # extern crate quick_xml;
+    /// # fn main() {
+    /// use quick_xml::Writer;
+    /// use quick_xml::events::{BytesEnd, BytesStart, Event};
+    ///
+    /// let mut writer = Writer::new(Vec::new());
+    /// writer.write_event(Event::Start(BytesStart::borrowed_name(b"tag"))).unwrap();
+    /// assert_eq!(writer.bytes_written(), 5);
+    ///
+    /// writer.write_event(Event::End(BytesEnd::borrowed(b"tag"))).unwrap();
+    /// assert_eq!(writer.bytes_written(), 11);
+    /// # }
+    /// ```
+    ///
+    /// [`minimize_empty_elements`]: #method.minimize_empty_elements
+    pub fn bytes_written(&self) -> usize {
+        self.bytes_written
+    }
+
+    /// Flushes the underlying writer. Does nothing for writers without buffering,
+    /// such as [`FmtSink`].
+    ///
+    /// Note, that a start tag, buffered because of [`minimize_empty_elements`],
+    /// is not written yet and therefore is not flushed.
+    ///
+    /// [`FmtSink`]: struct.FmtSink.html
+    /// [`minimize_empty_elements`]: #method.minimize_empty_elements
+    pub fn flush(&mut self) -> Result<()> {
+        self.writer.flush_bytes()
+    }
+
+    /// Finishes the document: writes a start tag, buffered because of
+    /// [`minimize_empty_elements`], and, if [`with_checks`] is enabled, checks
+    /// that all elements are closed.
+    ///
+    /// This is the same as writing [`Event::Eof`].
+    ///
+    /// [`minimize_empty_elements`]: #method.minimize_empty_elements
+    /// [`with_checks`]: #method.with_checks
+    /// [`Event::Eof`]: events/enum.Event.html#variant.Eof
+    pub fn finish(&mut self) -> Result<()> {
+        self.write_event(Event::Eof)
+    }
+
     /// Writes the given event to the underlying writer.
     pub fn write_event<'a, E: AsRef<Event<'a>>>(&mut self, event: E) -> Result<()> {
+        let event = event.as_ref();
+        if self.with_checks {
+            self.check_balance(event)?;
+        }
+        if self.pending_start.is_some() {
+            // empty text, for example, from `Reader` between `<tag>` and `</tag>`, is not a content
+            if let Event::Text(ref e) = *event {
+                if e.is_empty() {
+                    return Ok(());
+                }
+            }
+        }
+        if let Some(start) = self.pending_start.take() {
+            if let Event::End(ref e) = *event {
+                if e.name() == start.name() {
+                    return self.write_event_now(&Event::Empty(start));
+                }
+            }
+            self.write_event_now(&Event::Start(start))?;
+        }
+        match *event {
+            Event::Start(ref e) if self.should_minimize(e) => {
+                self.pending_start = Some(e.to_owned());
+                Ok(())
+            }
+            _ => self.write_event_now(event),
+        }
+    }
+
+    /// Updates the stack of opened elements with the `event`, if it is a start or an end tag.
+    /// Returns an error if an end tag does not match the last opened element, or if the
+    /// document ends when some elements are not closed
+    fn check_balance(&mut self, event: &Event) -> Result<()> {
+        match *event {
+            Event::Start(ref e) => {
+                self.opened_starts.push(self.opened_buffer.len());
+                self.opened_buffer.extend_from_slice(e.name().as_ref());
+            }
+            Event::End(ref e) => {
+                let start = self.opened_starts.last().map_or(0, |start| *start);
+                let expected = &self.opened_buffer[start..];
+                if self.opened_starts.is_empty() || expected != e.name().as_ref() {
+                    return Err(Error::EndEventMismatch {
+                        expected: String::from_utf8_lossy(expected).into_owned(),
+                        found: String::from_utf8_lossy(e.name().as_ref()).into_owned(),
+                    });
+                }
+                self.opened_starts.pop();
+                self.opened_buffer.truncate(start);
+            }
+            Event::Eof => {
+                if let Some(start) = self.opened_starts.last() {
+                    let name = &self.opened_buffer[*start..];
+                    return Err(Error::UnclosedElement(
+                        String::from_utf8_lossy(name).into_owned(),
+                    ));
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Whether a `<tag></tag>` pair, started by `start`, should be written as `<tag/>`
+    fn should_minimize(&self, start: &BytesStart) -> bool {
+        let name = start.name();
+        if contains_name(&self.always_expand, name) {
+            false
+        } else {
+            self.minimize_empty_elements || contains_name(&self.never_expand, name)
+        }
+    }
+
+    /// Writes the start tag, buffered because of `minimize_empty_elements`
+    fn write_pending_start(&mut self) -> Result<()> {
+        match self.pending_start.take() {
+            Some(start) => self.write_event_now(&Event::Start(start)),
+            None => Ok(()),
+        }
+    }
+
+    /// Writes the given event to the underlying writer without buffering.
+    fn write_event_now(&mut self, event: &Event) -> Result<()> {
         let mut next_should_line_break = true;
-        let result = match *event.as_ref() {
+        if let Event::Start(_) | Event::Empty(_) = *event {
+            self.root_started = true;
+        }
+        let result = match *event {
             Event::Start(ref e) => {
-                let result = self.write_wrapped(b"<", e, b">");
+                let e = self.rewrite_start(e)?;
+                let result = self.write_wrapped(b"<", &e, b">");
                 if let Some(i) = self.indent.as_mut() {
                     i.grow();
                 }
@@ -105,12 +860,36 @@ impl<W: Write> Writer<W> {
                 if let Some(i) = self.indent.as_mut() {
                     i.shrink();
                 }
-                self.write_wrapped(b"</", e, b">")
+                let name = self.rename_qname(e.name());
+                self.write_wrapped(b"</", &name, b"")?;
+                self.write(e.trailing_whitespaces())?;
+                self.write(b">")
+            }
+            Event::Empty(ref e) if contains_name(&self.always_expand, e.name()) => {
+                let start = self.rewrite_start(e)?;
+                let name = self.rename_qname(e.name());
+                self.write_wrapped(b"<", &start, b"></")?;
+                self.write(&name)?;
+                self.write(b">")
+            }
+            Event::Empty(ref e) => {
+                let e = self.rewrite_start(e)?;
+                self.write_wrapped(b"<", &e, b"/>")
             }
-            Event::Empty(ref e) => self.write_wrapped(b"<", e, b"/>"),
             Event::Text(ref e) => {
                 next_should_line_break = false;
-                self.write(&e.escaped())
+                let text = match self.escape_policy {
+                    EscapePolicy::Trust => Cow::Borrowed(e.escaped()),
+                    EscapePolicy::EscapeText | EscapePolicy::EscapeAll => {
+                        escape_unescaped(e.escaped())
+                    }
+                };
+                let text = self.handle_control_chars(text)?;
+                if self.escape_non_ascii {
+                    self.write(&escape_non_ascii(&text, self.char_ref_format))
+                } else {
+                    self.write(&text)
+                }
             }
             Event::Comment(ref e) => self.write_wrapped(b"<!--", e, b"-->"),
             Event::CData(ref e) => {
@@ -127,75 +906,628 @@ impl<W: Write> Writer<W> {
         if let Some(i) = self.indent.as_mut() {
             i.should_line_break = next_should_line_break;
         }
-        result
-    }
-
-    /// Writes bytes
-    #[inline]
-    pub fn write(&mut self, value: &[u8]) -> Result<()> {
-        self.writer.write_all(value).map_err(Error::Io)
-    }
-
-    #[inline]
-    fn write_wrapped(&mut self, before: &[u8], value: &[u8], after: &[u8]) -> Result<()> {
-        if let Some(ref i) = self.indent {
-            if i.should_line_break {
-                self.writer.write_all(b"\n").map_err(Error::Io)?;
-                self.writer
-                    .write_all(&i.indents[..i.indents_len])
-                    .map_err(Error::Io)?;
+        match *event {
+            Event::Start(_) => self.depth += 1,
+            Event::End(_) => self.depth = self.depth.saturating_sub(1),
+            _ => {}
+        }
+        result?;
+        if self.auto_flush && self.depth == 0 {
+            if let Event::End(_) | Event::Empty(_) = *event {
+                self.flush()?;
             }
         }
-        self.write(before)?;
-        self.write(value)?;
-        self.write(after)?;
         Ok(())
     }
 
-    /// Manually write a newline and indentation at the proper level.
+    /// Writes a document type declaration `<!DOCTYPE name PUBLIC "public_id" "system_id">`.
     ///
-    /// This can be used when the heuristic to line break and indent after any [Event] apart
-    /// from [Text] fails such as when a [Start] occurs directly after [Text].
-    /// This method will do nothing if `Writer` was not constructed with `new_with_indent`.
+    /// The external identifier is written as `PUBLIC` if both identifiers are specified,
+    /// as `SYSTEM` if only `system_id` is specified, and omitted if none of them are
+    /// specified. The system identifier is quoted with `'` if it contains `"`.
     ///
-    /// [Event]: events/enum.Event.html
-    /// [Text]: events/enum.Event.html#variant.Text
-    /// [Start]: events/enum.Event.html#variant.Start
-    pub fn write_indent(&mut self) -> Result<()> {
-        if let Some(ref i) = self.indent {
-            self.writer.write_all(b"\n").map_err(Error::Io)?;
-            self.writer
-                .write_all(&i.indents[..i.indents_len])
-                .map_err(Error::Io)?;
+    /// Returns [`Error::InvalidDocType`] if `name` is not a valid XML name, `public_id`
+    /// contains characters not allowed in a public identifier, `system_id` contains both
+    /// quotes, `public_id` is specified without `system_id`, or the root element is already
+    /// started.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # extern crate quick_xml;
+    /// # fn main() {
+    /// use quick_xml::Writer;
+    ///
+    /// let mut writer = Writer::new(Vec::new());
+    /// writer.write_doctype(
+    ///     "html",
+    ///     Some("-//W3C//DTD XHTML 1.0 Strict//EN"),
+    ///     Some("http://www.w3.org/TR/xhtml1/DTD/xhtml1-strict.dtd"),
+    /// ).unwrap();
+    ///
+    /// assert_eq!(
+    ///     writer.into_inner(),
+    ///     br#"<!DOCTYPE html PUBLIC "-//W3C//DTD XHTML 1.0 Strict//EN" "http://www.w3.org/TR/xhtml1/DTD/xhtml1-strict.dtd">"#.as_ref()
+    /// );
+    /// # }
+    /// ```
+    ///
+    /// [`Error::InvalidDocType`]: ../enum.Error.html#variant.InvalidDocType
+    pub fn write_doctype(
+        &mut self,
+        name: &str,
+        public_id: Option<&str>,
+        system_id: Option<&str>,
+    ) -> Result<()> {
+        if self.root_started || self.pending_start.is_some() {
+            return Err(Error::InvalidDocType(
+                "cannot be written after the root element".to_string(),
+            ));
+        }
+        let mut chars = name.chars();
+        let valid_name = match chars.next() {
+            Some(c) => is_name_start_char(c) && chars.all(is_name_char),
+            None => false,
+        };
+        if !valid_name {
+            return Err(Error::InvalidDocType(format!("invalid name {:?}", name)));
         }
-        Ok(())
-    }
-}
-
-#[derive(Clone)]
-struct Indentation {
-    should_line_break: bool,
-    indent_char: u8,
-    indent_size: usize,
-    indents: Vec<u8>,
-    indents_len: usize,
-}
 
-impl Indentation {
-    fn new(indent_char: u8, indent_size: usize) -> Indentation {
-        Indentation {
-            should_line_break: false,
-            indent_char,
-            indent_size,
-            indents: vec![indent_char; 128],
-            indents_len: 0,
+        let mut content = format!(" {}", name);
+        match (public_id, system_id) {
+            (Some(public_id), Some(system_id)) => {
+                if let Some(c) = public_id.chars().find(|&c| !is_pubid_char(c)) {
+                    return Err(Error::InvalidDocType(format!(
+                        "invalid character {:?} in public identifier",
+                        c
+                    )));
+                }
+                content.push_str(" PUBLIC \"");
+                content.push_str(public_id);
+                content.push('"');
+                push_system_literal(&mut content, system_id)?;
+            }
+            (None, Some(system_id)) => {
+                content.push_str(" SYSTEM");
+                push_system_literal(&mut content, system_id)?;
+            }
+            (Some(_), None) => {
+                return Err(Error::InvalidDocType(
+                    "public identifier requires a system identifier".to_string(),
+                ))
+            }
+            (None, None) => {}
         }
+        self.write_event(Event::DocType(BytesText::from_escaped_str(&content)))
     }
 
-    fn grow(&mut self) {
-        self.indents_len += self.indent_size;
-        if self.indents_len > self.indents.len() {
-            self.indents.resize(self.indents_len, self.indent_char);
+    /// Writes a comment `<!--text-->`. The text is written as is, as comments cannot
+    /// contain references.
+    ///
+    /// Returns [`Error::InvalidComment`] if the text contains `--` or ends with `-`, which
+    /// are not allowed in comments, for example, because `-->` would end the comment early.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # extern crate quick_xml;
+    /// # fn main() {
+    /// use quick_xml::Writer;
+    ///
+    /// let mut writer = Writer::new(Vec::new());
+    /// writer.write_comment(" <generated> ").unwrap();
+    /// assert!(writer.write_comment("a --> b").is_err());
+    ///
+    /// assert_eq!(writer.into_inner(), b"<!-- <generated> -->");
+    /// # }
+    /// ```
+    ///
+    /// [`Error::InvalidComment`]: ../enum.Error.html#variant.InvalidComment
+    pub fn write_comment(&mut self, text: &str) -> Result<()> {
+        if text.contains("--") || text.ends_with('-') {
+            return Err(Error::InvalidComment(text.to_string()));
+        }
+        self.write_event(Event::Comment(BytesText::from_escaped_str(text)))
+    }
+
+    /// Writes a CDATA section `<![CDATA[text]]>`. The text is written as is, without
+    /// escaping.
+    ///
+    /// Returns [`Error::InvalidCData`] if the text contains `]]>`, which would end the
+    /// section early.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # extern crate quick_xml;
+    /// # fn main() {
+    /// use quick_xml::Writer;
+    ///
+    /// let mut writer = Writer::new(Vec::new());
+    /// writer.write_cdata("if (a < b && c) {}").unwrap();
+    /// assert!(writer.write_cdata("a[b[0]]>c").is_err());
+    ///
+    /// assert_eq!(writer.into_inner(), b"<![CDATA[if (a < b && c) {}]]>");
+    /// # }
+    /// ```
+    ///
+    /// [`Error::InvalidCData`]: ../enum.Error.html#variant.InvalidCData
+    pub fn write_cdata(&mut self, text: &str) -> Result<()> {
+        if text.contains("]]>") {
+            return Err(Error::InvalidCData(text.to_string()));
+        }
+        self.write_event(Event::CData(BytesText::from_escaped_str(text)))
+    }
+
+    /// Writes bytes
+    #[inline]
+    pub fn write(&mut self, value: &[u8]) -> Result<()> {
+        self.write_pending_start()?;
+        self.write_inner(value)
+    }
+
+    /// Writes bytes to the underlying writer, counting them
+    #[inline]
+    fn write_inner(&mut self, value: &[u8]) -> Result<()> {
+        if self.check_utf8 {
+            ::std::str::from_utf8(value)?;
+        }
+        self.write_unchecked(value)
+    }
+
+    /// Writes bytes to the underlying writer, counting them, without the UTF-8 check
+    #[inline]
+    fn write_unchecked(&mut self, value: &[u8]) -> Result<()> {
+        self.writer.write_bytes(value)?;
+        self.bytes_written += value.len();
+        Ok(())
+    }
+
+    /// Writes bytes as is, regardless of the configured [`escape_policy`].
+    ///
+    /// This is the way to deliberately output content which must not be escaped. The bytes
+    /// are not checked to be valid UTF-8, unless [`check_utf8`] is enabled, so this is also
+    /// the way to introduce non-UTF-8 content, for example text in another encoding.
+    ///
+    /// [`escape_policy`]: #method.escape_policy
+    /// [`check_utf8`]: #method.check_utf8
+    #[inline]
+    pub fn write_raw(&mut self, value: &[u8]) -> Result<()> {
+        self.write(value)
+    }
+
+    /// Writes the element, which `start` tag was just read by the `reader`, copying its
+    /// content as is from the input of the reader, and reads it up to the end tag inclusive.
+    ///
+    /// The `start` tag is written as an event, so it may be changed before, and the end tag
+    /// is written after the content with the same name. The content is copied in the chunks
+    /// of the input without reading it as events, so unchanged parts of large documents are
+    /// passed through much faster than event by event. Only the nesting of the inner elements
+    /// is tracked to find the end tag: their names are not checked, and the options of this
+    /// writer, like indentation or escaping, are not applied to them. The end tag is read as
+    /// an event, so it is still checked by the reader, if [`check_end_names`] is enabled.
+    ///
+    /// `start` should be read as a [`Start`] event. An empty element, read as a `Start` event
+    /// because of [`expand_empty_elements`], is written as an [`Empty`] event. Elements read
+    /// as `Empty` events have no content and should be written with [`write_event`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # extern crate quick_xml;
+    /// # fn main() {
+    /// use quick_xml::events::Event;
+    /// use quick_xml::{Reader, Writer};
+    ///
+    /// let xml = r#"<root><keep id="1"><a>&amp;<b/></a><!-- > --></keep><drop><x/></drop></root>"#;
+    /// let mut reader = Reader::from_str(xml);
+    /// let mut writer = Writer::new(Vec::new());
+    /// let mut buf = Vec::new();
+    /// loop {
+    ///     match reader.read_event(&mut buf).unwrap() {
+    ///         Event::Start(ref e) if e.name().as_ref() == b"keep" => {
+    ///             writer.copy_subtree_raw(&mut reader, e).unwrap();
+    ///         }
+    ///         Event::Start(ref e) if e.name().as_ref() == b"drop" => {
+    ///             reader.read_to_end(b"drop", &mut Vec::new()).unwrap();
+    ///         }
+    ///         Event::Eof => break,
+    ///         e => writer.write_event(e).unwrap(),
+    ///     }
+    ///     buf.clear();
+    /// }
+    /// assert_eq!(
+    ///     writer.into_inner(),
+    ///     br#"<root><keep id="1"><a>&amp;<b/></a><!-- > --></keep></root>"#.as_ref()
+    /// );
+    /// # }
+    /// ```
+    ///
+    /// [`check_end_names`]: struct.Reader.html#method.check_end_names
+    /// [`expand_empty_elements`]: struct.Reader.html#method.expand_empty_elements
+    /// [`Start`]: events/enum.Event.html#variant.Start
+    /// [`Empty`]: events/enum.Event.html#variant.Empty
+    /// [`write_event`]: #method.write_event
+    pub fn copy_subtree_raw<R: BufRead>(
+        &mut self,
+        reader: &mut Reader<R>,
+        start: &BytesStart,
+    ) -> Result<()> {
+        if reader.is_expanded_empty() {
+            // Reads the expanded end tag
+            reader.read_event(&mut Vec::new())?;
+            return self.write_event(Event::Empty(start.to_borrowed()));
+        }
+        self.write_event(Event::Start(start.to_borrowed()))?;
+        self.write_pending_start()?;
+        // Chunks of the input may split a character, so its start is kept to be checked
+        // with the next chunk
+        let check_utf8 = self.check_utf8;
+        let mut incomplete = Vec::new();
+        reader.copy_content_raw(start.name().as_ref(), |content| {
+            if check_utf8 {
+                check_utf8_chunk(&mut incomplete, content)?;
+            }
+            self.write_unchecked(content)
+        })?;
+        if let Err(e) = ::std::str::from_utf8(&incomplete) {
+            return Err(Error::Utf8(e));
+        }
+        if let Some(i) = self.indent.as_mut() {
+            i.should_line_break = false;
+        }
+        let mut buf = Vec::new();
+        match reader.read_event(&mut buf)? {
+            Event::End(_) => {}
+            e => unreachable!("the end tag of the element is expected, found {:?}", e),
+        }
+        self.write_event(Event::End(start.to_end()))
+    }
+
+    /// Rebuilds the tag with renamed prefixes and escaped attribute values if required by
+    /// the prefix renames, the escape policy and the escaping of non-ASCII characters
+    fn rewrite_start<'b>(&self, e: &'b BytesStart) -> Result<Cow<'b, [u8]>> {
+        let escape = self.escape_policy == EscapePolicy::EscapeAll;
+        // Names cannot contain control characters, so only values can
+        let control_chars = e.iter().any(|&b| is_control_char(b));
+        if !escape && !self.escape_non_ascii && !control_chars && self.prefix_renames.is_empty() {
+            return Ok(Cow::Borrowed(e));
+        }
+        let name = self.rename_qname(e.name());
+        let mut changed = matches!(name, Cow::Owned(_));
+        let mut attributes = Vec::new();
+        let mut iter = e.attributes();
+        iter.with_checks(false);
+        for attr in iter.with_spans() {
+            let SpannedAttribute {
+                attribute: attr,
+                quote,
+                ..
+            } = attr?;
+            let key = self.rename_attribute(attr.key);
+            let value = match attr.value {
+                Cow::Borrowed(v) if escape => escape_unescaped(v),
+                value => value,
+            };
+            let value = self.handle_control_chars(value)?;
+            let value = if self.escape_non_ascii {
+                match escape_non_ascii(&value, self.char_ref_format) {
+                    Cow::Owned(escaped) => Cow::Owned(escaped),
+                    Cow::Borrowed(_) => value,
+                }
+            } else {
+                value
+            };
+            let quote = if self.preserve_attribute_quotes && quote == AttrQuote::Single {
+                b'\''
+            } else {
+                b'"'
+            };
+            changed |= matches!(key, Cow::Owned(_)) || matches!(value, Cow::Owned(_));
+            attributes.push((key, value, quote));
+        }
+        if !changed {
+            return Ok(Cow::Borrowed(e));
+        }
+        let mut tag = name.into_owned();
+        for (key, value, quote) in attributes {
+            tag.push(b' ');
+            tag.extend_from_slice(&key);
+            tag.push(b'=');
+            tag.push(quote);
+            for &b in value.iter() {
+                match b {
+                    b'"' if quote == b'"' => tag.extend_from_slice(b"&quot;"),
+                    b => tag.push(b),
+                }
+            }
+            tag.push(quote);
+        }
+        Ok(Cow::Owned(tag))
+    }
+
+    /// Rejects, removes or escapes the control characters of `value`, a text or
+    /// an attribute value, according to the [`control_char_policy`]
+    ///
+    /// [`control_char_policy`]: #method.control_char_policy
+    fn handle_control_chars<'b>(&self, value: Cow<'b, [u8]>) -> Result<Cow<'b, [u8]>> {
+        let first = match value.iter().position(|&b| is_control_char(b)) {
+            Some(first) => first,
+            None => return Ok(value),
+        };
+        match self.control_char_policy {
+            ControlCharPolicy::Reject => Err(Error::ControlCharacter(value[first])),
+            ControlCharPolicy::NumericEscape if value.contains(&0) => {
+                Err(Error::ControlCharacter(0))
+            }
+            ControlCharPolicy::Strip => Ok(Cow::Owned(
+                value
+                    .iter()
+                    .cloned()
+                    .filter(|&b| !is_control_char(b))
+                    .collect(),
+            )),
+            ControlCharPolicy::NumericEscape => Ok(Cow::Owned(
+                escape_control_chars(&value, self.char_ref_format).into_owned(),
+            )),
+        }
+    }
+
+    /// Returns `name` with the prefix renamed by [`rename_prefix`](#method.rename_prefix)
+    fn rename_qname<'b>(&self, name: QName<'b>) -> Cow<'b, [u8]> {
+        let (local_name, prefix) = name.decompose();
+        match prefix.and_then(|p| self.find_rename(&p)) {
+            Some(to) => {
+                let mut renamed = Vec::with_capacity(to.len() + 1 + local_name.len());
+                renamed.extend_from_slice(to);
+                renamed.push(b':');
+                renamed.extend_from_slice(&local_name);
+                Cow::Owned(renamed)
+            }
+            None => Cow::Borrowed(name.into_inner()),
+        }
+    }
+
+    /// Returns the attribute `key` with the renamed prefix, or with the renamed
+    /// prefix declaration for `xmlns:prefix` attributes
+    fn rename_attribute<'b>(&self, key: QName<'b>) -> Cow<'b, [u8]> {
+        match key.as_namespace_binding() {
+            Some(PrefixDeclaration::Named(prefix)) => match self.find_rename(prefix) {
+                Some(to) => {
+                    let mut renamed = b"xmlns:".to_vec();
+                    renamed.extend_from_slice(to);
+                    Cow::Owned(renamed)
+                }
+                None => Cow::Borrowed(key.into_inner()),
+            },
+            Some(PrefixDeclaration::Default) => Cow::Borrowed(key.into_inner()),
+            None => self.rename_qname(key),
+        }
+    }
+
+    fn find_rename(&self, prefix: &[u8]) -> Option<&[u8]> {
+        self.prefix_renames
+            .iter()
+            .find(|(from, _)| from.as_slice() == prefix)
+            .map(|(_, to)| to.as_slice())
+    }
+
+    #[inline]
+    fn write_wrapped(&mut self, before: &[u8], value: &[u8], after: &[u8]) -> Result<()> {
+        if let Some(ref i) = self.indent {
+            if i.should_line_break {
+                self.writer.write_bytes(b"\n")?;
+                self.writer.write_bytes(&i.indents[..i.indents_len])?;
+                self.bytes_written += 1 + i.indents_len;
+            }
+        }
+        self.write(before)?;
+        self.write(value)?;
+        self.write(after)?;
+        Ok(())
+    }
+
+    /// Creates a writer into `inner` with the same configuration and indentation state.
+    ///
+    /// A start tag, buffered because of [`minimize_empty_elements`], is written before the
+    /// content of the fork is appended, so the fork continues from the state after it.
+    ///
+    /// [`minimize_empty_elements`]: #method.minimize_empty_elements
+    pub(crate) fn fork<V: Sink>(&self, inner: V) -> Writer<V> {
+        let mut indent = self.indent.clone();
+        if let (Some(i), Some(_)) = (indent.as_mut(), self.pending_start.as_ref()) {
+            i.grow();
+            i.should_line_break = true;
+        }
+        Writer {
+            writer: inner,
+            indent,
+            escape_policy: self.escape_policy,
+            escape_non_ascii: self.escape_non_ascii,
+            char_ref_format: self.char_ref_format,
+            control_char_policy: self.control_char_policy,
+            preserve_attribute_quotes: self.preserve_attribute_quotes,
+            minimize_empty_elements: self.minimize_empty_elements,
+            pending_start: None,
+            root_started: self.root_started,
+            prefix_renames: self.prefix_renames.clone(),
+            always_expand: self.always_expand.clone(),
+            never_expand: self.never_expand.clone(),
+            bytes_written: 0,
+            with_checks: self.with_checks,
+            check_utf8: self.check_utf8,
+            auto_flush: self.auto_flush,
+            depth: 0,
+            opened_buffer: Vec::new(),
+            opened_starts: Vec::new(),
+        }
+    }
+
+    /// Creates a writer into `inner` with the same configuration, which continues from the
+    /// state this writer will be in right after writing the next [`Event::Start`].
+    ///
+    /// Used to render content of an element before its start tag is written.
+    /// The rendered content should then be written with [`append`].
+    ///
+    /// [`Event::Start`]: events/enum.Event.html#variant.Start
+    /// [`append`]: #method.append
+    pub(crate) fn nested<V: Sink>(&self, inner: V) -> Writer<V> {
+        let mut nested = self.fork(inner);
+        nested.root_started = true;
+        if let Some(i) = nested.indent.as_mut() {
+            i.grow();
+            i.should_line_break = true;
+        }
+        nested
+    }
+
+    /// Returns to the indentation state of the writer, which created this one with
+    /// [`nested`], for the content, which is written without the element after all.
+    ///
+    /// [`nested`]: #method.nested
+    #[cfg(feature = "serialize")]
+    pub(crate) fn unnest(&mut self) {
+        if let Some(i) = self.indent.as_mut() {
+            i.shrink();
+        }
+    }
+
+    /// Moves everything written into `other` to this writer and continues from the
+    /// indentation state of `other`.
+    ///
+    /// Nothing is changed, if nothing is written into `other`, so a start tag, buffered
+    /// because of [`minimize_empty_elements`], still can be written as an empty element.
+    ///
+    /// [`minimize_empty_elements`]: #method.minimize_empty_elements
+    pub(crate) fn append<V: Sink + AsMut<Vec<u8>>>(&mut self, other: &mut Writer<V>) -> Result<()> {
+        other.write_pending_start()?;
+        let content = other.writer.as_mut();
+        if content.is_empty() {
+            return Ok(());
+        }
+        self.write(content)?;
+        content.clear();
+        if let (Some(i), Some(o)) = (self.indent.as_mut(), other.indent.as_ref()) {
+            i.should_line_break = o.should_line_break;
+        }
+        Ok(())
+    }
+
+    /// Whether the content of [`Event::Text`] is written as is, without escaping or
+    /// checks, so it can be written without a forked writer.
+    ///
+    /// [`Event::Text`]: events/enum.Event.html#variant.Text
+    #[cfg(feature = "serialize")]
+    pub(crate) fn writes_text_as_is(&self) -> bool {
+        self.escape_policy == EscapePolicy::Trust && !self.escape_non_ascii && !self.with_checks
+    }
+
+    /// Manually write a newline and indentation at the proper level.
+    ///
+    /// This can be used when the heuristic to line break and indent after any [Event] apart
+    /// from [Text] fails such as when a [Start] occurs directly after [Text].
+    /// This method will do nothing if `Writer` was not constructed with `new_with_indent`.
+    ///
+    /// [Event]: events/enum.Event.html
+    /// [Text]: events/enum.Event.html#variant.Text
+    /// [Start]: events/enum.Event.html#variant.Start
+    pub fn write_indent(&mut self) -> Result<()> {
+        self.write_pending_start()?;
+        if let Some(ref i) = self.indent {
+            self.writer.write_bytes(b"\n")?;
+            self.writer.write_bytes(&i.indents[..i.indents_len])?;
+            self.bytes_written += 1 + i.indents_len;
+        }
+        Ok(())
+    }
+}
+
+/// Checks if `name` is present in the list of names
+fn contains_name(names: &[Vec<u8>], name: QName) -> bool {
+    names.iter().any(|n| n.as_slice() == name.as_ref())
+}
+
+/// Appends ` "system_id"`, choosing quotes which are not used inside the literal
+fn push_system_literal(content: &mut String, system_id: &str) -> Result<()> {
+    let quote = if !system_id.contains('"') {
+        '"'
+    } else if !system_id.contains('\'') {
+        '\''
+    } else {
+        return Err(Error::InvalidDocType(
+            "system identifier cannot contain both quotes".to_string(),
+        ));
+    };
+    content.push(' ');
+    content.push(quote);
+    content.push_str(system_id);
+    content.push(quote);
+    Ok(())
+}
+
+/// [NameStartChar](https://www.w3.org/TR/xml/#NT-NameStartChar)
+fn is_name_start_char(c: char) -> bool {
+    matches!(
+        c,
+        ':' | 'A'..='Z'
+            | '_'
+            | 'a'..='z'
+            | '\u{C0}'..='\u{D6}'
+            | '\u{D8}'..='\u{F6}'
+            | '\u{F8}'..='\u{2FF}'
+            | '\u{370}'..='\u{37D}'
+            | '\u{37F}'..='\u{1FFF}'
+            | '\u{200C}'..='\u{200D}'
+            | '\u{2070}'..='\u{218F}'
+            | '\u{2C00}'..='\u{2FEF}'
+            | '\u{3001}'..='\u{D7FF}'
+            | '\u{F900}'..='\u{FDCF}'
+            | '\u{FDF0}'..='\u{FFFD}'
+            | '\u{10000}'..='\u{EFFFF}'
+    )
+}
+
+/// [NameChar](https://www.w3.org/TR/xml/#NT-NameChar)
+fn is_name_char(c: char) -> bool {
+    is_name_start_char(c)
+        || matches!(
+            c,
+            '-' | '.' | '0'..='9' | '\u{B7}' | '\u{0300}'..='\u{036F}' | '\u{203F}'..='\u{2040}'
+        )
+}
+
+/// [PubidChar](https://www.w3.org/TR/xml/#NT-PubidChar)
+fn is_pubid_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || " \r\n-'()+,./:=?;!*#@$_%".contains(c)
+}
+
+#[derive(Clone)]
+struct Indentation {
+    should_line_break: bool,
+    indent_char: u8,
+    indent_size: usize,
+    /// Indentation characters, shared by the forked writers, so forking doesn't allocate
+    indents: Arc<[u8]>,
+    indents_len: usize,
+}
+
+impl Indentation {
+    fn new(indent_char: u8, indent_size: usize) -> Indentation {
+        Indentation {
+            should_line_break: false,
+            indent_char,
+            indent_size,
+            indents: vec![indent_char; 128].into(),
+            indents_len: 0,
+        }
+    }
+
+    fn grow(&mut self) {
+        self.indents_len += self.indent_size;
+        if self.indents_len > self.indents.len() {
+            let mut indents = self.indents.to_vec();
+            indents.resize(self.indents_len, self.indent_char);
+            self.indents = indents.into();
         }
     }
 
@@ -207,6 +1539,73 @@ impl Indentation {
     }
 }
 
+impl Writer<Vec<u8>> {
+    /// Consumes this `Writer`, returning the written document as a string.
+    ///
+    /// A start tag, buffered because of [`minimize_empty_elements`], is written first.
+    /// Unlike [`into_inner`], errors of that write are returned. If the output is not valid
+    /// UTF-8, which is only possible when bytes are written as is (see [`check_utf8`]),
+    /// [`Error::Utf8`] is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # extern crate quick_xml;
+    /// # fn main() {
+    /// use quick_xml::events::{BytesStart, Event};
+    /// use quick_xml::{Error, Writer};
+    ///
+    /// let mut writer = Writer::new(Vec::new());
+    /// writer.write_event(Event::Empty(BytesStart::borrowed_name(b"caf\xC3\xA9"))).unwrap();
+    /// assert_eq!(writer.into_string().unwrap(), "<café/>");
+    ///
+    /// let mut writer = Writer::new(Vec::new());
+    /// writer.write_raw(b"caf\xE9").unwrap();
+    /// match writer.into_string() {
+    ///     Err(Error::Utf8(e)) => assert_eq!(e.valid_up_to(), 3),
+    ///     e => panic!("Expected `Utf8`, found {:?}", e),
+    /// }
+    /// # }
+    /// ```
+    ///
+    /// [`minimize_empty_elements`]: #method.minimize_empty_elements
+    /// [`into_inner`]: #method.into_inner
+    /// [`check_utf8`]: #method.check_utf8
+    /// [`Error::Utf8`]: enum.Error.html#variant.Utf8
+    pub fn into_string(mut self) -> Result<String> {
+        self.write_pending_start()?;
+        String::from_utf8(self.writer).map_err(|e| Error::Utf8(e.utf8_error()))
+    }
+}
+
+/// Checks that `chunk`, preceded by the `incomplete` character at the end of the previous
+/// chunk, is valid UTF-8, and keeps the incomplete character at its end in `incomplete`
+fn check_utf8_chunk(incomplete: &mut Vec<u8>, mut chunk: &[u8]) -> Result<()> {
+    if !incomplete.is_empty() {
+        let len = match incomplete[0] {
+            0xC0..=0xDF => 2,
+            0xE0..=0xEF => 3,
+            _ => 4,
+        };
+        let take = (len - incomplete.len()).min(chunk.len());
+        incomplete.extend_from_slice(&chunk[..take]);
+        chunk = &chunk[take..];
+        if incomplete.len() < len {
+            return Ok(());
+        }
+        ::std::str::from_utf8(incomplete)?;
+        incomplete.clear();
+    }
+    match ::std::str::from_utf8(chunk) {
+        Ok(_) => Ok(()),
+        Err(e) if e.error_len().is_none() => {
+            incomplete.extend_from_slice(&chunk[e.valid_up_to()..]);
+            Ok(())
+        }
+        Err(e) => Err(Error::Utf8(e)),
+    }
+}
+
 #[cfg(test)]
 mod indentation {
     use super::*;
@@ -310,6 +1709,39 @@ mod indentation {
         );
     }
 
+    #[test]
+    fn text_leaves_and_containers() {
+        let mut buffer = Vec::new();
+        let mut writer = Writer::new_with_indent(&mut buffer, b' ', 2);
+
+        let start = |name| Event::Start(BytesStart::borrowed_name(name));
+        let end = |name| Event::End(BytesEnd::borrowed(name));
+        let text = |text| Event::Text(BytesText::from_plain(text));
+        let events = vec![
+            start(b"root"),
+            start(b"name"),
+            text(b"value"),
+            end(b"name"),
+            start(b"list"),
+            start(b"item"),
+            text(b"1"),
+            end(b"item"),
+            start(b"item"),
+            text(b"2"),
+            end(b"item"),
+            end(b"list"),
+            end(b"root"),
+        ];
+        for event in events {
+            writer.write_event(event).expect("write event failed");
+        }
+
+        assert_eq!(
+            std::str::from_utf8(&buffer).unwrap(),
+            "<root>\n  <name>value</name>\n  <list>\n    <item>1</item>\n    <item>2</item>\n  </list>\n</root>"
+        );
+    }
+
     #[test]
     fn mixed_content() {
         let mut buffer = Vec::new();
@@ -381,3 +1813,1187 @@ mod indentation {
         );
     }
 }
+
+#[cfg(test)]
+mod escape_policy {
+    use super::*;
+    use events::*;
+
+    #[test]
+    fn trust() {
+        let mut buffer = Vec::new();
+        let mut writer = Writer::new(&mut buffer);
+
+        writer
+            .write_event(Event::Text(BytesText::from_escaped_str("<script>")))
+            .expect("write text failed");
+
+        assert_eq!(buffer, b"<script>".as_ref());
+    }
+
+    #[test]
+    fn escape_text() {
+        let mut buffer = Vec::new();
+        let mut writer = Writer::new(&mut buffer);
+        writer.escape_policy(EscapePolicy::EscapeText);
+
+        let tag = BytesStart::borrowed(br#"tag attr="<a>""#, 3);
+        writer
+            .write_event(Event::Start(tag))
+            .expect("write start tag failed");
+        writer
+            .write_event(Event::Text(BytesText::from_escaped_str("<script> &amp; &")))
+            .expect("write text failed");
+        writer
+            .write_event(Event::Text(BytesText::from_plain_str("<plain>")))
+            .expect("write text failed");
+
+        assert_eq!(
+            buffer,
+            br#"<tag attr="<a>">&lt;script&gt; &amp; &amp;&lt;plain&gt;"#.as_ref()
+        );
+    }
+
+    #[test]
+    fn escape_all() {
+        let mut buffer = Vec::new();
+        let mut writer = Writer::new(&mut buffer);
+        writer.escape_policy(EscapePolicy::EscapeAll);
+
+        let start = BytesStart::borrowed(br#"tag a="<script>" b='"&amp;'"#, 3);
+        let empty = BytesStart::borrowed(br#"empty a="&lt;safe&gt;""#, 5);
+        writer
+            .write_event(Event::Start(start))
+            .expect("write start tag failed");
+        writer
+            .write_event(Event::Text(BytesText::from_escaped_str("<script>")))
+            .expect("write text failed");
+        writer
+            .write_event(Event::Empty(empty))
+            .expect("write empty tag failed");
+        writer.write_raw(b"<raw/>").expect("write raw failed");
+
+        assert_eq!(
+            buffer,
+            br#"<tag a="&lt;script&gt;" b="&quot;&amp;">&lt;script&gt;<empty a="&lt;safe&gt;"/><raw/>"#
+                .as_ref()
+        );
+    }
+
+    #[test]
+    fn escape_non_ascii() {
+        use reader::Reader;
+
+        let strings = ["caf\u{E9}", "\u{65E5}\u{672C} & <\u{1F600}>", "ascii"];
+        for &(format, expected) in &[
+            (CharRefFormat::Decimal, "&#233;"),
+            (CharRefFormat::Hexadecimal, "&#xE9;"),
+        ] {
+            let mut writer = Writer::new(Vec::new());
+            writer.escape_non_ascii(true).char_ref_format(format);
+            for s in strings.iter() {
+                let mut start = BytesStart::borrowed_name(b"item");
+                start.push_attribute(("value", *s));
+                writer.write_event(Event::Start(start)).unwrap();
+                writer
+                    .write_event(Event::Text(BytesText::from_plain_str(s)))
+                    .unwrap();
+                writer
+                    .write_event(Event::End(BytesEnd::borrowed(b"item")))
+                    .unwrap();
+            }
+            // Comments cannot contain references
+            writer
+                .write_event(Event::Comment(BytesText::from_escaped_str("\u{E9}")))
+                .unwrap();
+            let xml = writer.into_inner();
+            let (xml, comment) = xml.split_at(xml.len() - 9);
+            assert!(xml.is_ascii(), "{:?}", String::from_utf8_lossy(xml));
+            assert!(xml
+                .windows(expected.len())
+                .any(|w| w == expected.as_bytes()));
+            assert_eq!(comment, "<!--\u{E9}-->".as_bytes());
+
+            // Round-trip
+            let mut reader = Reader::from_reader(xml);
+            reader.trim_text(true);
+            let mut buf = Vec::new();
+            let mut read = Vec::new();
+            loop {
+                match reader.read_event(&mut buf).unwrap() {
+                    Event::Start(e) => {
+                        let attr = e.try_get_attribute("value").unwrap().unwrap();
+                        read.push(attr.unescape_and_decode_value(&reader).unwrap());
+                    }
+                    Event::Text(e) => read.push(e.unescape_and_decode(&reader).unwrap()),
+                    Event::Eof => break,
+                    _ => (),
+                }
+                buf.clear();
+            }
+            let expected: Vec<_> = strings
+                .iter()
+                .flat_map(|s| vec![s.to_string(), s.to_string()])
+                .collect();
+            assert_eq!(read, expected);
+        }
+    }
+
+    #[test]
+    fn preserve_attribute_quotes() {
+        let tag = br#"a:tag a:x='"&amp;' y="'" z='1'"#;
+        for &(preserve, expected) in &[
+            (false, r#"<b:tag b:x="&quot;&amp;" y="'" z="1"/>"#),
+            (true, r#"<b:tag b:x='"&amp;' y="'" z='1'/>"#),
+        ] {
+            let mut writer = Writer::new(Vec::new());
+            writer
+                .rename_prefix("a", "b")
+                .preserve_attribute_quotes(preserve);
+            writer
+                .write_event(Event::Empty(BytesStart::borrowed(tag, 5)))
+                .unwrap();
+            assert_eq!(std::str::from_utf8(&writer.into_inner()).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn escape_non_ascii_hexadecimal() {
+        let mut writer = Writer::new(Vec::new());
+        writer
+            .escape_policy(EscapePolicy::EscapeAll)
+            .escape_non_ascii(true)
+            .char_ref_format(CharRefFormat::Hexadecimal);
+
+        let mut start = BytesStart::borrowed_name(b"p");
+        start.push_attribute(("title", "Cr\u{E8}me & <br\u{FB}l\u{E9}e>"));
+        writer.write_event(Event::Start(start)).unwrap();
+        writer
+            .write_event(Event::Text(BytesText::from_escaped_str(
+                "\u{1F600} &amp; \u{1F44D}\u{1F3FD} <na\u{EF}ve> &\u{10FFFF}",
+            )))
+            .unwrap();
+        writer
+            .write_event(Event::End(BytesEnd::borrowed(b"p")))
+            .unwrap();
+
+        assert_eq!(
+            std::str::from_utf8(&writer.into_inner()).unwrap(),
+            "<p title=\"Cr&#xE8;me &amp; &lt;br&#xFB;l&#xE9;e&gt;\">\
+             &#x1F600; &amp; &#x1F44D;&#x1F3FD; &lt;na&#xEF;ve&gt; &amp;&#x10FFFF;</p>"
+        );
+    }
+}
+
+#[cfg(test)]
+mod control_char_policy {
+    use super::*;
+    use events::*;
+
+    /// Writes an element with the `value` as an attribute and as a text
+    fn write(policy: Option<ControlCharPolicy>, value: &str) -> Result<String> {
+        let mut writer = Writer::new(Vec::new());
+        if let Some(policy) = policy {
+            writer.control_char_policy(policy);
+        }
+        let mut start = BytesStart::borrowed_name(b"item");
+        start.push_attribute(("value", value));
+        writer.write_event(Event::Start(start))?;
+        writer.write_event(Event::Text(BytesText::from_plain_str(value)))?;
+        writer.write_event(Event::End(BytesEnd::borrowed(b"item")))?;
+        Ok(String::from_utf8(writer.into_inner()).unwrap())
+    }
+
+    #[test]
+    fn reject() {
+        match write(None, "nul\u{0} backspace\u{8}") {
+            Err(Error::ControlCharacter(0)) => {}
+            e => panic!("Expected `ControlCharacter(0)`, found {:?}", e),
+        }
+        // The text is checked as well
+        let mut writer = Writer::new(Vec::new());
+        match writer.write_event(Event::Text(BytesText::from_plain_str("backspace\u{8}"))) {
+            Err(Error::ControlCharacter(8)) => {}
+            e => panic!("Expected `ControlCharacter(8)`, found {:?}", e),
+        }
+        // Tab, line feed and carriage return are allowed
+        assert_eq!(
+            write(None, "\t\n\r").unwrap(),
+            "<item value=\"&#9;&#10;&#13;\">\t\n\r</item>"
+        );
+    }
+
+    #[test]
+    fn strip() {
+        assert_eq!(
+            write(Some(ControlCharPolicy::Strip), "nul\u{0} backspace\u{8}\t").unwrap(),
+            "<item value=\"nul backspace&#9;\">nul backspace\t</item>"
+        );
+    }
+
+    #[test]
+    fn numeric_escape() {
+        assert_eq!(
+            write(
+                Some(ControlCharPolicy::NumericEscape),
+                "start\u{1} backspace\u{8}"
+            )
+            .unwrap(),
+            "<item value=\"start&#1; backspace&#8;\">start&#1; backspace&#8;</item>"
+        );
+        // NUL is not allowed even as a reference
+        match write(Some(ControlCharPolicy::NumericEscape), "nul\u{0}") {
+            Err(Error::ControlCharacter(0)) => {}
+            e => panic!("Expected `ControlCharacter(0)`, found {:?}", e),
+        }
+
+        let mut writer = Writer::new(Vec::new());
+        writer
+            .control_char_policy(ControlCharPolicy::NumericEscape)
+            .char_ref_format(CharRefFormat::Hexadecimal);
+        writer
+            .write_event(Event::Text(BytesText::from_plain_str("\u{1F}")))
+            .unwrap();
+        assert_eq!(writer.into_inner(), b"&#x1F;");
+    }
+
+    #[test]
+    fn other_events() {
+        // Comments and CDATA sections cannot contain references, so they are written as is
+        let mut writer = Writer::new(Vec::new());
+        writer
+            .write_event(Event::Comment(BytesText::from_escaped_str("\u{8}")))
+            .unwrap();
+        writer
+            .write_event(Event::CData(BytesText::from_escaped_str("\u{8}")))
+            .unwrap();
+        assert_eq!(writer.into_inner(), b"<!--\x08--><![CDATA[\x08]]>");
+    }
+}
+
+#[cfg(test)]
+mod minimize_empty_elements {
+    use super::*;
+    use events::*;
+    use Reader;
+
+    /// Copies all events from `xml` to the writer with minimization enabled
+    fn copy(xml: &str, trim_text: bool) -> String {
+        let mut reader = Reader::from_str(xml);
+        reader.trim_text(trim_text);
+        let mut writer = Writer::new(Vec::new());
+        writer.minimize_empty_elements(true);
+
+        let mut buf = Vec::new();
+        loop {
+            match reader.read_event(&mut buf).expect("read event failed") {
+                Event::Eof => break,
+                e => writer.write_event(e).expect("write event failed"),
+            }
+            buf.clear();
+        }
+        String::from_utf8(writer.into_inner()).unwrap()
+    }
+
+    #[test]
+    fn empty() {
+        assert_eq!(copy("<a></a>", false), "<a/>");
+        assert_eq!(
+            copy(r#"<a attr="value"></a>"#, false),
+            r#"<a attr="value"/>"#
+        );
+    }
+
+    #[test]
+    fn whitespace() {
+        assert_eq!(copy("<a> </a>", false), "<a> </a>");
+        assert_eq!(copy("<a> </a>", true), "<a/>");
+    }
+
+    #[test]
+    fn not_empty() {
+        assert_eq!(copy("<a>text</a>", false), "<a>text</a>");
+        assert_eq!(
+            copy("<a><!--comment--></a>", false),
+            "<a><!--comment--></a>"
+        );
+    }
+
+    #[test]
+    fn nested() {
+        assert_eq!(copy("<a><b></b></a>", false), "<a><b/></a>");
+        assert_eq!(copy("<a><b></b><c></c></a>", false), "<a><b/><c/></a>");
+        assert_eq!(copy("<a><b><c></c></b></a>", false), "<a><b><c/></b></a>");
+    }
+
+    #[test]
+    fn flush() {
+        let mut writer = Writer::new(Vec::new());
+        writer.minimize_empty_elements(true);
+        writer
+            .write_event(Event::Start(BytesStart::borrowed_name(b"a")))
+            .expect("write start tag failed");
+        assert_eq!(writer.into_inner(), b"<a>".as_ref());
+
+        let mut buffer = Vec::new();
+        {
+            let mut writer = Writer::new(&mut buffer);
+            writer.minimize_empty_elements(true);
+            writer
+                .write_event(Event::Start(BytesStart::borrowed_name(b"a")))
+                .expect("write start tag failed");
+            writer.write_event(Event::Eof).expect("write eof failed");
+        }
+        assert_eq!(buffer, b"<a>".as_ref());
+    }
+
+    #[test]
+    fn indentation() {
+        let mut buffer = Vec::new();
+        let mut writer = Writer::new_with_indent(&mut buffer, b' ', 2);
+        writer.minimize_empty_elements(true);
+
+        for event in vec![
+            Event::Start(BytesStart::borrowed_name(b"a")),
+            Event::Start(BytesStart::borrowed_name(b"b")),
+            Event::End(BytesEnd::borrowed(b"b")),
+            Event::End(BytesEnd::borrowed(b"a")),
+        ] {
+            writer.write_event(event).expect("write event failed");
+        }
+
+        assert_eq!(std::str::from_utf8(&buffer).unwrap(), "<a>\n  <b/>\n</a>");
+    }
+}
+
+#[cfg(test)]
+mod fmt_sink {
+    use super::*;
+    use events::*;
+
+    fn events() -> Vec<Event<'static>> {
+        vec![
+            Event::Decl(BytesDecl::new(b"1.0", Some(b"UTF-8"), None)),
+            Event::Start(
+                BytesStart::borrowed_name(b"root").with_attributes(vec![("attr", "välue")]),
+            ),
+            Event::Comment(BytesText::from_plain_str("comment")),
+            Event::Empty(BytesStart::borrowed_name(b"empty")),
+            Event::Start(BytesStart::borrowed_name(b"text")),
+            Event::Text(BytesText::from_plain_str("<ünïcödé>")),
+            Event::End(BytesEnd::borrowed(b"text")),
+            Event::CData(BytesText::from_escaped_str("cdata")),
+            Event::End(BytesEnd::borrowed(b"root")),
+        ]
+    }
+
+    /// Output of a writer into `io::Write` and a writer into `fmt::Write` is the same
+    #[test]
+    fn same_output() {
+        let mut io = Writer::new_with_indent(Vec::new(), b' ', 2);
+        let mut fmt = Writer::new_with_indent(FmtSink::new(String::new()), b' ', 2);
+        for event in events() {
+            io.write_event(&event).expect("write to io failed");
+            fmt.write_event(&event).expect("write to fmt failed");
+        }
+
+        let io = io.into_inner();
+        let fmt = fmt.into_inner().into_inner();
+        assert_eq!(io, fmt.as_bytes());
+        assert_eq!(
+            fmt,
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\
+            \n<root attr=\"välue\">\
+            \n  <!--comment-->\
+            \n  <empty/>\
+            \n  <text>&lt;ünïcödé&gt;</text><![CDATA[cdata]]></root>"
+        );
+    }
+
+    #[test]
+    fn error() {
+        struct Failing;
+        impl fmt::Write for Failing {
+            fn write_str(&mut self, _: &str) -> fmt::Result {
+                Err(fmt::Error)
+            }
+        }
+
+        let mut writer = Writer::new(FmtSink::new(Failing));
+        match writer.write_event(Event::Empty(BytesStart::borrowed_name(b"tag"))) {
+            Err(Error::Fmt(_)) => {}
+            e => panic!("Expected `Err(Fmt(_))`, but found {:?}", e),
+        }
+
+        let mut writer = Writer::new(FmtSink::new(String::new()));
+        match writer.write(b"\xFF") {
+            Err(Error::Utf8(_)) => {}
+            e => panic!("Expected `Err(Utf8(_))`, but found {:?}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod doctype {
+    use super::*;
+    use events::*;
+    use Reader;
+
+    /// Writes a doctype, reads it back and splits into name, public and system identifiers
+    fn roundtrip(
+        name: &str,
+        public_id: Option<&str>,
+        system_id: Option<&str>,
+    ) -> (String, Option<String>, Option<String>) {
+        let mut writer = Writer::new(Vec::new());
+        writer
+            .write_doctype(name, public_id, system_id)
+            .expect("write doctype failed");
+        writer
+            .write_event(Event::Empty(BytesStart::borrowed_name(name.as_bytes())))
+            .expect("write root failed");
+        let xml = writer.into_inner();
+
+        let mut reader = Reader::from_reader(xml.as_slice());
+        reader.trim_text(true);
+        let mut buf = Vec::new();
+        let content = match reader.read_event(&mut buf).expect("read doctype failed") {
+            Event::DocType(e) => String::from_utf8(e.to_vec()).unwrap(),
+            e => panic!("Expected `DocType`, but found {:?}", e),
+        };
+
+        let mut parts = content.trim().splitn(3, ' ');
+        let name = parts.next().unwrap().to_string();
+        match (parts.next(), parts.next()) {
+            (None, None) => (name, None, None),
+            (Some("PUBLIC"), Some(rest)) => {
+                let (public_id, rest) = literal(rest);
+                let (system_id, _) = literal(rest);
+                (name, Some(public_id), Some(system_id))
+            }
+            (Some("SYSTEM"), Some(rest)) => (name, None, Some(literal(rest).0)),
+            ids => panic!("Unexpected external identifier {:?}", ids),
+        }
+    }
+
+    /// Splits a quoted literal from the start of `s`
+    fn literal(s: &str) -> (String, &str) {
+        let s = s.trim_start();
+        let quote = s.chars().next().unwrap();
+        let end = s[1..].find(quote).unwrap() + 1;
+        (s[1..end].to_string(), &s[end + 1..])
+    }
+
+    #[test]
+    fn name() {
+        assert_eq!(roundtrip("html", None, None), ("html".into(), None, None));
+    }
+
+    #[test]
+    fn system() {
+        assert_eq!(
+            roundtrip("note", None, Some("note.dtd")),
+            ("note".into(), None, Some("note.dtd".into()))
+        );
+        assert_eq!(
+            roundtrip("note", None, Some(r#"say "hi".dtd"#)),
+            ("note".into(), None, Some(r#"say "hi".dtd"#.into()))
+        );
+    }
+
+    #[test]
+    fn public() {
+        let public_id = "-//W3C//DTD XHTML 1.0 Strict//EN";
+        let system_id = "http://www.w3.org/TR/xhtml1/DTD/xhtml1-strict.dtd";
+        assert_eq!(
+            roundtrip("html", Some(public_id), Some(system_id)),
+            (
+                "html".into(),
+                Some(public_id.into()),
+                Some(system_id.into())
+            )
+        );
+    }
+
+    #[test]
+    fn invalid() {
+        let mut writer = Writer::new(Vec::new());
+        for &(name, public_id, system_id) in &[
+            ("", None, None),
+            ("1html", None, None),
+            ("ht ml", None, None),
+            ("html>", None, None),
+            ("html", Some("\"quoted\""), Some("html.dtd")),
+            ("html", Some("public"), None),
+            ("html", None, Some(r#"both"'"#)),
+        ] {
+            match writer.write_doctype(name, public_id, system_id) {
+                Err(Error::InvalidDocType(_)) => {}
+                e => panic!(
+                    "Expected `Err(InvalidDocType(_))` for {:?}, but found {:?}",
+                    (name, public_id, system_id),
+                    e
+                ),
+            }
+        }
+        assert_eq!(writer.into_inner(), b"".as_ref());
+    }
+
+    #[test]
+    fn after_root() {
+        let mut writer = Writer::new(Vec::new());
+        writer
+            .write_event(Event::Empty(BytesStart::borrowed_name(b"root")))
+            .expect("write root failed");
+        match writer.write_doctype("root", None, None) {
+            Err(Error::InvalidDocType(_)) => {}
+            e => panic!("Expected `Err(InvalidDocType(_))`, but found {:?}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod rename_prefix {
+    use super::*;
+    use events::*;
+    use Reader;
+
+    /// Copies all events of `xml` to the `writer`
+    fn copy(writer: &mut Writer<Vec<u8>>, xml: &str) {
+        let mut reader = Reader::from_str(xml);
+        reader.trim_text(true);
+        let mut buf = Vec::new();
+        loop {
+            match reader.read_event(&mut buf).expect("read failed") {
+                Event::Eof => break,
+                e => writer.write_event(e).expect("write failed"),
+            }
+            buf.clear();
+        }
+    }
+
+    #[test]
+    fn merge() {
+        let mut writer = Writer::new(Vec::new());
+        writer
+            .write_event(Event::Start(BytesStart::borrowed_name(b"root")))
+            .unwrap();
+
+        writer.rename_prefix("ns1", "a");
+        copy(
+            &mut writer,
+            r#"<ns1:a xmlns:ns1="urn:a" xmlns="urn:d"><ns1:x ns1:id="1" id="2"/><ns12:x/></ns1:a>"#,
+        );
+        writer.rename_prefix("ns1", "b");
+        copy(&mut writer, r#"<ns1:b xmlns:ns1="urn:b"><ns1:y/></ns1:b>"#);
+
+        writer
+            .write_event(Event::End(BytesEnd::borrowed(b"root")))
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(writer.into_inner()).unwrap(),
+            concat!(
+                r#"<root>"#,
+                r#"<a:a xmlns:a="urn:a" xmlns="urn:d"><a:x a:id="1" id="2"/><ns12:x/></a:a>"#,
+                r#"<b:b xmlns:b="urn:b"><b:y/></b:b>"#,
+                r#"</root>"#,
+            )
+        );
+    }
+
+    #[test]
+    fn unchanged() {
+        let mut writer = Writer::new(Vec::new());
+        writer.rename_prefix("ns1", "a");
+        copy(
+            &mut writer,
+            r#"<b:b  xmlns:b='urn:b' b:id = '1'><c/></b:b>"#,
+        );
+        assert_eq!(
+            writer.into_inner(),
+            br#"<b:b  xmlns:b='urn:b' b:id = '1'><c/></b:b>"#.as_ref()
+        );
+    }
+}
+
+#[cfg(test)]
+mod bytes_written {
+    use super::*;
+    use events::*;
+
+    #[test]
+    fn counts_indentation_and_escaping() {
+        let mut writer = Writer::new_with_indent(Vec::new(), b' ', 4);
+        writer.escape_policy(EscapePolicy::EscapeAll);
+
+        let start = BytesStart::borrowed(br#"outer attr="<&>""#, 5);
+        writer
+            .write_event(Event::Start(start))
+            .expect("write start tag failed");
+        writer
+            .write_event(Event::Start(BytesStart::borrowed_name(b"inner")))
+            .expect("write start tag failed");
+        writer
+            .write_event(Event::Text(BytesText::from_plain_str("a < b")))
+            .expect("write text failed");
+        writer
+            .write_event(Event::End(BytesEnd::borrowed(b"inner")))
+            .expect("write end tag failed");
+        writer.write_indent().expect("write indent failed");
+        writer
+            .write_event(Event::End(BytesEnd::borrowed(b"outer")))
+            .expect("write end tag failed");
+
+        let count = writer.bytes_written();
+        let result = writer.into_inner();
+        assert_eq!(count, result.len());
+        assert_eq!(
+            std::str::from_utf8(&result).unwrap(),
+            "<outer attr=\"&lt;&amp;&gt;\">\n    <inner>a &lt; b</inner>\n    \n</outer>"
+        );
+    }
+
+    #[test]
+    fn pending_start() {
+        let mut writer = Writer::new(Vec::new());
+        writer.minimize_empty_elements(true);
+
+        writer
+            .write_event(Event::Start(BytesStart::borrowed_name(b"tag")))
+            .expect("write start tag failed");
+        assert_eq!(writer.bytes_written(), 0);
+
+        writer
+            .write_event(Event::End(BytesEnd::borrowed(b"tag")))
+            .expect("write end tag failed");
+        assert_eq!(writer.bytes_written(), 6);
+        assert_eq!(writer.into_inner(), b"<tag/>".as_ref());
+    }
+}
+
+#[cfg(test)]
+mod expand {
+    use super::*;
+    use events::*;
+
+    fn write_xhtml(writer: &mut Writer<Vec<u8>>) {
+        for name in &[&b"div"[..], b"br", b"p"] {
+            writer
+                .write_event(Event::Empty(BytesStart::borrowed_name(name)))
+                .expect("write empty tag failed");
+            writer
+                .write_event(Event::Start(BytesStart::borrowed_name(name)))
+                .expect("write start tag failed");
+            writer
+                .write_event(Event::End(BytesEnd::borrowed(name)))
+                .expect("write end tag failed");
+        }
+    }
+
+    #[test]
+    fn xhtml() {
+        let mut writer = Writer::new(Vec::new());
+        writer.always_expand(&["p", "script"]);
+        writer.never_expand(&["br", "hr"]);
+        write_xhtml(&mut writer);
+
+        assert_eq!(
+            std::str::from_utf8(&writer.into_inner()).unwrap(),
+            "<div/><div></div><br/><br/><p></p><p></p>"
+        );
+    }
+
+    #[test]
+    fn with_minimize_empty_elements() {
+        let mut writer = Writer::new(Vec::new());
+        writer.minimize_empty_elements(true);
+        writer.always_expand(&["p"]);
+        writer.never_expand(&["br", "p"]);
+        write_xhtml(&mut writer);
+
+        assert_eq!(
+            std::str::from_utf8(&writer.into_inner()).unwrap(),
+            "<div/><div/><br/><br/><p></p><p></p>"
+        );
+    }
+
+    #[test]
+    fn with_indent() {
+        let mut writer = Writer::new_with_indent(Vec::new(), b' ', 2);
+        writer.always_expand(&["p"]);
+
+        let attrs = BytesStart::borrowed(br#"p class="x""#, 1);
+        writer
+            .write_event(Event::Start(BytesStart::borrowed_name(b"body")))
+            .expect("write start tag failed");
+        writer
+            .write_event(Event::Empty(attrs))
+            .expect("write empty tag failed");
+        writer
+            .write_event(Event::Empty(BytesStart::borrowed_name(b"br")))
+            .expect("write empty tag failed");
+        writer
+            .write_event(Event::End(BytesEnd::borrowed(b"body")))
+            .expect("write end tag failed");
+
+        assert_eq!(
+            std::str::from_utf8(&writer.into_inner()).unwrap(),
+            "<body>\n  <p class=\"x\"></p>\n  <br/>\n</body>"
+        );
+    }
+}
+
+#[cfg(test)]
+mod checks {
+    use super::*;
+    use events::*;
+
+    fn start(name: &str) -> Event {
+        Event::Start(BytesStart::borrowed_name(name.as_bytes()))
+    }
+
+    fn end(name: &str) -> Event {
+        Event::End(BytesEnd::borrowed(name.as_bytes()))
+    }
+
+    #[test]
+    fn balanced() {
+        let mut writer = Writer::new(Vec::new());
+        writer.with_checks(true);
+        writer.write_event(start("a")).unwrap();
+        writer.write_event(start("b")).unwrap();
+        writer.write_event(end("b")).unwrap();
+        writer.write_event(start("b")).unwrap();
+        writer.write_event(end("b")).unwrap();
+        writer.write_event(end("a")).unwrap();
+        writer.finish().unwrap();
+        assert_eq!(writer.into_inner(), b"<a><b></b><b></b></a>");
+    }
+
+    #[test]
+    fn mismatch() {
+        let mut writer = Writer::new(Vec::new());
+        writer.with_checks(true);
+        writer.write_event(start("a")).unwrap();
+        writer.write_event(start("b")).unwrap();
+        match writer.write_event(end("a")) {
+            Err(Error::EndEventMismatch { expected, found }) => {
+                assert_eq!(expected, "b");
+                assert_eq!(found, "a");
+            }
+            x => panic!("Expected `EndEventMismatch`, found {:?}", x),
+        }
+        // The wrong end tag is not written, so the document can be fixed
+        writer.write_event(end("b")).unwrap();
+        writer.write_event(end("a")).unwrap();
+        match writer.write_event(end("a")) {
+            Err(Error::EndEventMismatch { expected, found }) => {
+                assert_eq!(expected, "");
+                assert_eq!(found, "a");
+            }
+            x => panic!("Expected `EndEventMismatch`, found {:?}", x),
+        }
+        assert_eq!(writer.into_inner(), b"<a><b></b></a>");
+    }
+
+    #[test]
+    fn unclosed() {
+        let mut writer = Writer::new(Vec::new());
+        writer.with_checks(true);
+        writer.write_event(start("a")).unwrap();
+        writer.write_event(start("b")).unwrap();
+        match writer.finish() {
+            Err(Error::UnclosedElement(name)) => assert_eq!(name, "b"),
+            x => panic!("Expected `UnclosedElement`, found {:?}", x),
+        }
+        writer.write_event(end("b")).unwrap();
+        match writer.write_event(Event::Eof) {
+            Err(Error::UnclosedElement(name)) => assert_eq!(name, "a"),
+            x => panic!("Expected `UnclosedElement`, found {:?}", x),
+        }
+        writer.write_event(end("a")).unwrap();
+        writer.finish().unwrap();
+    }
+
+    #[test]
+    fn with_minimize_empty_elements() {
+        let mut writer = Writer::new(Vec::new());
+        writer.with_checks(true).minimize_empty_elements(true);
+        writer.write_event(start("a")).unwrap();
+        writer.write_event(start("b")).unwrap();
+        writer.write_event(end("b")).unwrap();
+        assert!(writer.write_event(end("b")).is_err());
+        writer.write_event(end("a")).unwrap();
+        writer.finish().unwrap();
+        assert_eq!(writer.into_inner(), b"<a><b/></a>");
+    }
+
+    #[test]
+    fn disabled() {
+        let mut writer = Writer::new(Vec::new());
+        writer.write_event(start("a")).unwrap();
+        writer.write_event(end("b")).unwrap();
+        writer.write_event(start("c")).unwrap();
+        writer.finish().unwrap();
+        assert_eq!(writer.into_inner(), b"<a></b><c>");
+    }
+}
+
+#[cfg(test)]
+mod comment_and_cdata {
+    use super::*;
+    use events::*;
+    use std::str::from_utf8;
+    use Reader;
+
+    #[test]
+    fn write() {
+        let mut writer = Writer::new(Vec::new());
+        writer
+            .write_event(Event::Start(BytesStart::borrowed_name(b"root")))
+            .unwrap();
+        writer.write_comment(" a - b <c> & d ").unwrap();
+        writer
+            .write_cdata("<script>if (a && b) x[y[0]] = 1;</script>")
+            .unwrap();
+        writer.write_comment("").unwrap();
+        writer.write_cdata("").unwrap();
+        writer
+            .write_event(Event::End(BytesEnd::borrowed(b"root")))
+            .unwrap();
+
+        let xml = writer.into_inner();
+        assert_eq!(
+            String::from_utf8(xml.clone()).unwrap(),
+            "<root>\
+                <!-- a - b <c> & d -->\
+                <![CDATA[<script>if (a && b) x[y[0]] = 1;</script>]]>\
+                <!---->\
+                <![CDATA[]]>\
+            </root>"
+        );
+
+        let mut reader = Reader::from_reader(&xml[..]);
+        let mut buf = Vec::new();
+        let mut events = Vec::new();
+        loop {
+            match reader.read_event(&mut buf).unwrap() {
+                Event::Comment(e) => events.push(format!("comment: {}", from_utf8(&e).unwrap())),
+                Event::CData(e) => {
+                    let text = e.unescape_and_decode(&reader).unwrap();
+                    events.push(format!("cdata: {}", text));
+                }
+                Event::Eof => break,
+                _ => {}
+            }
+            buf.clear();
+        }
+        assert_eq!(
+            events,
+            vec![
+                "comment:  a - b <c> & d ",
+                "cdata: <script>if (a && b) x[y[0]] = 1;</script>",
+                "comment: ",
+                "cdata: ",
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_comment() {
+        let mut writer = Writer::new(Vec::new());
+        for text in &["a --> b", "a -- b", "ends with -", "-"] {
+            match writer.write_comment(text) {
+                Err(Error::InvalidComment(t)) => assert_eq!(&t, text),
+                x => panic!("Expected `Err(InvalidComment(_))`, but got {:?}", x),
+            }
+        }
+        assert_eq!(writer.into_inner(), b"");
+    }
+
+    #[test]
+    fn invalid_cdata() {
+        let mut writer = Writer::new(Vec::new());
+        for text in &["]]>", "a[b[0]]>c"] {
+            match writer.write_cdata(text) {
+                Err(Error::InvalidCData(t)) => assert_eq!(&t, text),
+                x => panic!("Expected `Err(InvalidCData(_))`, but got {:?}", x),
+            }
+        }
+        assert_eq!(writer.into_inner(), b"");
+    }
+}
+
+#[cfg(test)]
+mod copy_subtree_raw {
+    use super::*;
+    use events::*;
+    use std::io::BufReader;
+
+    /// Copies the `keep` elements of the `xml` as is, and drops the `drop` elements. The
+    /// input is read in chunks of `capacity` bytes to split the markup between the chunks
+    fn filter(xml: &str, capacity: usize, expand: bool) -> Result<String> {
+        let mut reader = Reader::from_reader(BufReader::with_capacity(capacity, xml.as_bytes()));
+        reader.expand_empty_elements(expand);
+        let mut writer = Writer::new(Vec::new());
+        let mut buf = Vec::new();
+        loop {
+            match reader.read_event(&mut buf)? {
+                Event::Start(ref e) if e.name().as_ref() == b"keep" => {
+                    let mut start = e.to_owned();
+                    start.push_attribute(("copied", "yes"));
+                    writer.copy_subtree_raw(&mut reader, &start)?;
+                }
+                Event::Start(ref e) if e.name().as_ref() == b"drop" => {
+                    reader.read_to_end(b"drop", &mut Vec::new())?;
+                }
+                Event::Eof => break,
+                e => writer.write_event(e)?,
+            }
+            buf.clear();
+        }
+        Ok(String::from_utf8(writer.into_inner()).unwrap())
+    }
+
+    #[test]
+    fn markup() {
+        let content = concat!(
+            "text &amp; <keep>nested <keep/></keep>",
+            r#"<a href="/>" title='</keep>'><b/></a>"#,
+            "<!-- </keep> -> --><![CDATA[</keep>]]]>",
+            "<?pi </keep>?><!DOCTYPE x><?pi?>",
+            "<c >&lt;</c >",
+        );
+        let xml = format!("<root><keep>{}</keep><drop><x/></drop></root>", content);
+        let expected = format!(r#"<root><keep copied="yes">{}</keep></root>"#, content);
+        for &capacity in &[1, 2, 3, 7, 8192] {
+            assert_eq!(filter(&xml, capacity, false).unwrap(), expected);
+            assert_eq!(filter(&xml, capacity, true).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn empty() {
+        let xml = "<root><keep/><keep></keep><keep>\n</keep></root>";
+        assert_eq!(
+            filter(xml, 8192, true).unwrap(),
+            r#"<root><keep copied="yes"/><keep copied="yes"></keep><keep copied="yes">
+</keep></root>"#
+        );
+    }
+
+    #[test]
+    fn errors() {
+        match filter("<root><keep><a></a>", 8192, false) {
+            Err(Error::UnexpectedEof(_)) => {}
+            e => panic!("Expected `UnexpectedEof`, found {:?}", e),
+        }
+        // The end tag of the copied element is checked
+        match filter("<root><keep><a></b></x></root>", 8192, false) {
+            Err(Error::EndEventMismatch { expected, found }) => {
+                assert_eq!(expected, "keep");
+                assert_eq!(found, "x");
+            }
+            e => panic!("Expected `EndEventMismatch`, found {:?}", e),
+        }
+    }
+
+    #[test]
+    fn void_elements() {
+        let xml = "<root><keep><br><p>text<br></p></keep></root>";
+        let mut reader = Reader::from_str(xml);
+        reader.void_elements(&["br"]);
+        let mut writer = Writer::new(Vec::new());
+        let mut buf = Vec::new();
+        loop {
+            match reader.read_event(&mut buf).unwrap() {
+                Event::Start(ref e) if e.name().as_ref() == b"keep" => {
+                    writer.copy_subtree_raw(&mut reader, e).unwrap();
+                }
+                Event::Eof => break,
+                e => writer.write_event(e).unwrap(),
+            }
+            buf.clear();
+        }
+        assert_eq!(writer.into_inner(), xml.as_bytes());
+    }
+}
+
+#[cfg(test)]
+mod utf8 {
+    use super::*;
+    use events::*;
+    use std::io::BufReader;
+
+    #[test]
+    fn into_string() {
+        let mut writer = Writer::new(Vec::new());
+        writer.minimize_empty_elements(true);
+        writer
+            .write_event(Event::Start(BytesStart::borrowed_name("žluť".as_bytes())))
+            .unwrap();
+        // The pending start tag is written
+        assert_eq!(writer.into_string().unwrap(), "<žluť>");
+    }
+
+    #[test]
+    fn into_string_invalid() {
+        let mut writer = Writer::new(Vec::new());
+        writer.write_raw(b"<a>\xC5").unwrap();
+        writer
+            .write_event(Event::Text(BytesText::from_escaped(b"\xBEx".as_ref())))
+            .unwrap();
+        // A character, split between writes, is valid
+        let mut valid = writer.clone();
+        valid.write_raw(b"</a>").unwrap();
+        assert_eq!(valid.into_string().unwrap(), "<a>žx</a>");
+
+        writer.write_raw(b"\xFF</a>").unwrap();
+        match writer.into_string() {
+            Err(Error::Utf8(e)) => assert_eq!(e.valid_up_to(), "<a>žx".len()),
+            e => panic!("Expected `Utf8`, found {:?}", e),
+        }
+    }
+
+    #[test]
+    fn check_utf8() {
+        let mut writer = Writer::new(Vec::new());
+        writer.check_utf8(true);
+        writer.write_raw("<ř>".as_bytes()).unwrap();
+        match writer.write_raw(b"\xFF") {
+            Err(Error::Utf8(_)) => {}
+            e => panic!("Expected `Utf8`, found {:?}", e),
+        }
+        match writer.write_event(Event::Text(BytesText::from_escaped(b"a\xC5".as_ref()))) {
+            Err(Error::Utf8(_)) => {}
+            e => panic!("Expected `Utf8`, found {:?}", e),
+        }
+        writer
+            .write_event(Event::End(BytesEnd::borrowed("ř".as_bytes())))
+            .unwrap();
+        assert_eq!(writer.bytes_written(), "<ř></ř>".len());
+        assert_eq!(writer.into_string().unwrap(), "<ř></ř>");
+    }
+
+    /// Copies the `root` element of the `xml`, read in chunks of `capacity` bytes
+    fn copy(xml: &[u8], capacity: usize) -> Result<Vec<u8>> {
+        let mut reader = Reader::from_reader(BufReader::with_capacity(capacity, xml));
+        reader.trim_text(true);
+        let mut writer = Writer::new(Vec::new());
+        writer.check_utf8(true);
+        let mut buf = Vec::new();
+        match reader.read_event(&mut buf)? {
+            Event::Start(ref e) => writer.copy_subtree_raw(&mut reader, e)?,
+            e => panic!("Expected `Start`, found {:?}", e),
+        }
+        Ok(writer.into_inner())
+    }
+
+    #[test]
+    fn check_utf8_copy_subtree_raw() {
+        let xml = "<root>ž<a>€</a>𝄞ё</root>";
+        for &capacity in &[1, 2, 3, 8192] {
+            assert_eq!(copy(xml.as_bytes(), capacity).unwrap(), xml.as_bytes());
+        }
+        for &capacity in &[1, 2, 3, 8192] {
+            match copy(b"<root>\xE2\x82</root>", capacity) {
+                Err(Error::Utf8(_)) => {}
+                e => panic!("Expected `Utf8`, found {:?}", e),
+            }
+            match copy(b"<root>\xE2\x82a\xAC</root>", capacity) {
+                Err(Error::Utf8(_)) => {}
+                e => panic!("Expected `Utf8`, found {:?}", e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod auto_flush {
+    use super::*;
+    use events::*;
+
+    /// A writer, which counts the flushes and the bytes written before the last flush
+    #[derive(Default)]
+    struct Counter {
+        written: usize,
+        flushed: usize,
+        flushes: usize,
+    }
+
+    impl io::Write for Counter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.written += buf.len();
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            self.flushed = self.written;
+            self.flushes += 1;
+            Ok(())
+        }
+    }
+
+    fn write(writer: &mut Writer<Counter>, events: &[Event]) {
+        for event in events {
+            writer.write_event(event).unwrap();
+        }
+    }
+
+    #[test]
+    fn top_level_elements() {
+        let mut writer = Writer::new(Counter::default());
+        writer.auto_flush(true);
+        write(
+            &mut writer,
+            &[
+                Event::Start(BytesStart::borrowed_name(b"a")),
+                Event::Empty(BytesStart::borrowed_name(b"b")),
+                Event::Start(BytesStart::borrowed_name(b"c")),
+                Event::End(BytesEnd::borrowed(b"c")),
+            ],
+        );
+        assert_eq!(writer.inner().flushes, 0);
+        write(&mut writer, &[Event::End(BytesEnd::borrowed(b"a"))]);
+        assert_eq!(writer.inner().flushes, 1);
+        assert_eq!(writer.inner().flushed, "<a><b/><c></c></a>".len());
+
+        write(
+            &mut writer,
+            &[
+                Event::Text(BytesText::from_plain_str("\n")),
+                Event::Empty(BytesStart::borrowed_name(b"d")),
+            ],
+        );
+        assert_eq!(writer.inner().flushes, 2);
+        let counter = writer.into_inner();
+        assert_eq!(counter.flushed, counter.written);
+    }
+
+    /// Elements, minimized with `minimize_empty_elements`, are flushed once written
+    #[test]
+    fn minimized() {
+        let mut writer = Writer::new(Counter::default());
+        writer.auto_flush(true).minimize_empty_elements(true);
+        write(
+            &mut writer,
+            &[Event::Start(BytesStart::borrowed_name(b"a"))],
+        );
+        assert_eq!(writer.inner().written, 0);
+        write(&mut writer, &[Event::End(BytesEnd::borrowed(b"a"))]);
+        assert_eq!(writer.inner().flushes, 1);
+        assert_eq!(writer.inner().flushed, "<a/>".len());
+    }
+
+    #[test]
+    fn disabled() {
+        let mut writer = Writer::new(Counter::default());
+        write(
+            &mut writer,
+            &[Event::Empty(BytesStart::borrowed_name(b"a"))],
+        );
+        assert_eq!(writer.inner().flushes, 0);
+        writer.flush().unwrap();
+        assert_eq!(writer.inner().flushes, 1);
+    }
+}
@@ -0,0 +1,148 @@
+//! Generator of the table of HTML5 named character references in `src/escapei.rs`.
+//!
+//! Download the [`entities.json`] of the specification and run
+//!
+//! ```text
+//! ENTITIES_JSON=path/to/entities.json cargo test --test html_entities -- --ignored
+//! ```
+//!
+//! to rewrite the arms of `html_entity`. The legacy names without the trailing `;` are
+//! skipped, names which resolve to the same string are grouped in one arm in the order,
+//! which ignores ASCII case, and arms are ordered by the code points of the string. The arms
+//! are formatted as `rustfmt` does.
+//!
+//! [`entities.json`]: https://html.spec.whatwg.org/entities.json
+
+extern crate regex;
+
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+
+/// The line, after which the arms start
+const START: &str =
+    "const fn html_entity(name: &[u8]) -> Option<&str> {\n    let s = match name {\n";
+/// The line, which follows the arms
+const END: &str = "        _ => return None,\n";
+/// Indentation of the arms
+const INDENT: &str = "        ";
+const MAX_WIDTH: usize = 100;
+
+/// Parses the names, which end with `;`, and their code points from `entities.json`
+fn parse(json: &str) -> Vec<(String, Vec<u32>)> {
+    let entity =
+        Regex::new(r#""&([0-9A-Za-z]+);"\s*:\s*\{\s*"codepoints"\s*:\s*\[([0-9,\s]+)\]"#).unwrap();
+    entity
+        .captures_iter(json)
+        .map(|c| {
+            let codepoints = c[2]
+                .split(',')
+                .map(|cp| cp.trim().parse().unwrap())
+                .collect();
+            (c[1].to_string(), codepoints)
+        })
+        .collect()
+}
+
+/// Formats the arms of the match
+fn generate(entities: Vec<(String, Vec<u32>)>) -> String {
+    let mut groups: BTreeMap<Vec<u32>, Vec<String>> = BTreeMap::new();
+    for (name, codepoints) in entities {
+        groups.entry(codepoints).or_default().push(name);
+    }
+
+    let mut arms = String::new();
+    for (codepoints, mut names) in groups {
+        names.sort_by_key(|n| (n.to_ascii_lowercase(), n.clone()));
+        names.dedup();
+        let patterns: Vec<_> = names.iter().map(|n| format!("b\"{}\"", n)).collect();
+        let value: String = codepoints
+            .iter()
+            .map(|cp| format!("\\u{{{:02X}}}", cp))
+            .collect();
+        let value = format!("\"{}\"", value);
+
+        let line = format!("{}{} => {},", INDENT, patterns.join(" | "), value);
+        let block = format!("{}{} => {{", INDENT, patterns.join(" | "));
+        if line.len() <= MAX_WIDTH {
+            arms.push_str(&line);
+            arms.push('\n');
+        } else if block.len() <= MAX_WIDTH {
+            arms.push_str(&block);
+            arms.push_str(&format!("\n{}    {}\n{}}}\n", INDENT, value, INDENT));
+        } else {
+            arms.push_str(&format!("{}{}\n", INDENT, patterns[0]));
+            for pattern in &patterns[1..patterns.len() - 1] {
+                arms.push_str(&format!("{}| {}\n", INDENT, pattern));
+            }
+            arms.push_str(&format!(
+                "{}| {} => {},\n",
+                INDENT,
+                patterns[patterns.len() - 1],
+                value
+            ));
+        }
+    }
+    arms
+}
+
+/// Replaces the arms of `html_entity` in `source`
+fn replace(source: &str, arms: &str) -> String {
+    let start = source.find(START).expect("`html_entity` not found") + START.len();
+    let end = start
+        + source[start..]
+            .find(END)
+            .expect("end of `html_entity` not found");
+    format!("{}{}{}", &source[..start], arms, &source[end..])
+}
+
+#[test]
+#[ignore]
+fn generate_html_entities() {
+    let path = env::var("ENTITIES_JSON").expect("set ENTITIES_JSON to the path of entities.json");
+    let json = fs::read_to_string(path).unwrap();
+    let entities = parse(&json);
+    assert!(!entities.is_empty(), "no entities found");
+
+    let file = concat!(env!("CARGO_MANIFEST_DIR"), "/src/escapei.rs");
+    let source = fs::read_to_string(file).unwrap();
+    fs::write(file, replace(&source, &generate(entities))).unwrap();
+}
+
+#[test]
+fn format_arms() {
+    let json = r#"{
+        "&amp;": { "codepoints": [38], "characters": "&" },
+        "&amp": { "codepoints": [38], "characters": "&" },
+        "&AMP;": { "codepoints": [38], "characters": "&" },
+        "&Tab;": { "codepoints": [9], "characters": "\t" },
+        "&nvlt;": { "codepoints": [60, 8402], "characters": "<⃒" },
+        "&NegativeMediumSpace;": { "codepoints": [8203], "characters": "\u200B" },
+        "&NegativeThickSpace;": { "codepoints": [8203], "characters": "\u200B" },
+        "&NegativeThinSpace;": { "codepoints": [8203], "characters": "\u200B" },
+        "&NegativeVeryThinSpace;": { "codepoints": [8203], "characters": "\u200B" },
+        "&ZeroWidthSpace;": { "codepoints": [8203], "characters": "\u200B" },
+        "&NotDoubleVerticalBar;": { "codepoints": [8742], "characters": "∦" },
+        "&npar;": { "codepoints": [8742], "characters": "∦" },
+        "&nparallel;": { "codepoints": [8742], "characters": "∦" },
+        "&nshortparallel;": { "codepoints": [8742], "characters": "∦" },
+        "&nspar;": { "codepoints": [8742], "characters": "∦" }
+    }"#;
+    let arms = generate(parse(json));
+    assert_eq!(
+        arms,
+        r#"        b"Tab" => "\u{09}",
+        b"AMP" | b"amp" => "\u{26}",
+        b"nvlt" => "\u{3C}\u{20D2}",
+        b"NegativeMediumSpace"
+        | b"NegativeThickSpace"
+        | b"NegativeThinSpace"
+        | b"NegativeVeryThinSpace"
+        | b"ZeroWidthSpace" => "\u{200B}",
+        b"NotDoubleVerticalBar" | b"npar" | b"nparallel" | b"nshortparallel" | b"nspar" => {
+            "\u{2226}"
+        }
+"#
+    );
+}
@@ -0,0 +1,188 @@
+#![cfg(feature = "serialize")]
+
+extern crate quick_xml;
+extern crate serde;
+
+use quick_xml::se::Serializer;
+use quick_xml::Writer;
+use serde::Serialize;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::{Cell, RefCell};
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+/// Counts allocations of the current thread, so tests running in parallel do not
+/// affect each other
+struct Counting;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|a| a.set(a.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|a| a.set(a.get() + 1));
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+/// Returns the number of allocations made by `f`
+fn allocations<F: FnOnce()>(f: F) -> usize {
+    let before = ALLOCATIONS.with(|a| a.get());
+    f();
+    ALLOCATIONS.with(|a| a.get()) - before
+}
+
+/// A writer, which content can be looked at while a `Serializer` writes into it
+struct Shared<'a>(&'a RefCell<Vec<u8>>);
+
+impl<'a> Write for Shared<'a> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Serialize)]
+struct Item {
+    id: u32,
+    name: &'static str,
+    #[serde(rename = "$value")]
+    text: &'static str,
+}
+
+/// Fails, or panics, when serialized, if `panic` is set
+struct Faulty {
+    panic: bool,
+}
+
+impl Serialize for Faulty {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if self.panic {
+            panic!("serialization panics");
+        }
+        serializer.serialize_bytes(b"bytes are not supported")
+    }
+}
+
+#[derive(Serialize)]
+struct Document {
+    id: u32,
+    title: &'static str,
+    item: Item,
+    items: Vec<Item>,
+    counts: BTreeMap<&'static str, u32>,
+    optional: Option<Item>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    faulty: Option<Faulty>,
+}
+
+fn document(faulty: Option<Faulty>) -> Document {
+    let mut counts = BTreeMap::new();
+    counts.insert("first", 1);
+    counts.insert("second", 2);
+    Document {
+        id: 1,
+        title: "title",
+        item: Item {
+            id: 2,
+            name: "single",
+            text: "text & more",
+        },
+        items: vec![
+            Item {
+                id: 3,
+                name: "first",
+                text: "",
+            },
+            Item {
+                id: 4,
+                name: "second",
+                text: "text",
+            },
+        ],
+        counts,
+        optional: Some(Item {
+            id: 5,
+            name: "optional",
+            text: "optional",
+        }),
+        faulty,
+    }
+}
+
+#[test]
+fn reuse_buffers() {
+    let value = document(None);
+    let writers = vec![
+        Writer::new(io::sink()),
+        Writer::new_with_indent(io::sink(), b' ', 2),
+    ];
+    for writer in writers {
+        let mut ser = Serializer::with_root(writer, None);
+        value.serialize(&mut ser).unwrap();
+        assert_eq!(
+            allocations(|| {
+                value.serialize(&mut ser).unwrap();
+                value.serialize(&mut ser).unwrap();
+            }),
+            0
+        );
+    }
+}
+
+#[test]
+fn reuse_buffers_after_failure() {
+    let value = document(None);
+    let expected = {
+        let mut buffer = Vec::new();
+        value.serialize(&mut Serializer::new(&mut buffer)).unwrap();
+        buffer
+    };
+
+    let output = RefCell::new(Vec::new());
+    let mut ser = Serializer::new(Shared(&output));
+    value.serialize(&mut ser).unwrap();
+
+    // The nested struct fails in the middle of the document
+    assert!(document(Some(Faulty { panic: false }))
+        .serialize(&mut ser)
+        .is_err());
+    output.borrow_mut().clear();
+    value.serialize(&mut ser).unwrap();
+    assert_eq!(*output.borrow(), expected);
+
+    // The nested struct panics in the middle of the document
+    let faulty = document(Some(Faulty { panic: true }));
+    assert!(catch_unwind(AssertUnwindSafe(|| faulty.serialize(&mut ser))).is_err());
+    output.borrow_mut().clear();
+    value.serialize(&mut ser).unwrap();
+    assert_eq!(*output.borrow(), expected);
+
+    // Lost buffers are allocated anew only once
+    assert_eq!(
+        allocations(|| {
+            output.borrow_mut().clear();
+            value.serialize(&mut ser).unwrap();
+            output.borrow_mut().clear();
+            value.serialize(&mut ser).unwrap();
+        }),
+        0
+    );
+}
@@ -129,11 +129,13 @@ fn test_parse_unflatten_field() {
     #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
     struct Unflatten {
         #[serde(rename = "$unflatten=NewKey")]
-        field: String
+        field: String,
     }
 
     let source = "<Unflatten><NewKey>Foo</NewKey></Unflatten>";
-    let expected = Unflatten { field: "Foo".to_string() };
+    let expected = Unflatten {
+        field: "Foo".to_string(),
+    };
 
     let parsed: Unflatten = ::quick_xml::de::from_str(source).unwrap();
     assert_eq!(&parsed, &expected);
@@ -181,3 +183,86 @@ fn escapes_in_cdata() {
         r#"{37.3k,268,msb}<-1,1|1,-1>(T=1,(7,-6,3,D:4,1:1,T:1,1:2,0:8,F:8,15:4,C:4,-79m,T=0)+){C =(D:4+4*T+9+F:4+F:4:4+15)&15} [D:0..15,F:0..255]"#
     );
 }
+
+#[test]
+fn text_content_with_attributes() {
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    #[serde(rename = "measurement")]
+    struct Measurement {
+        unit: String,
+        precision: Option<u8>,
+        #[serde(rename = "$value")]
+        value: f64,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Sample {
+        name: String,
+        measurement: Vec<Measurement>,
+    }
+
+    let src = r#"<Sample name="apples"><measurement unit="kg" precision="2">42.25</measurement><measurement unit="g">42250</measurement></Sample>"#;
+    let should_be = Sample {
+        name: "apples".to_string(),
+        measurement: vec![
+            Measurement {
+                unit: "kg".to_string(),
+                precision: Some(2),
+                value: 42.25,
+            },
+            Measurement {
+                unit: "g".to_string(),
+                precision: None,
+                value: 42250.0,
+            },
+        ],
+    };
+
+    let sample: Sample = from_str(src).unwrap();
+    assert_eq!(sample, should_be);
+    assert_eq!(to_string(&sample).unwrap(), src);
+
+    // The text can be a CDATA section as well
+    let cdata = r#"<measurement unit="kg" precision="2"><![CDATA[42.25]]></measurement>"#;
+    let measurement: Measurement = from_str(cdata).unwrap();
+    assert_eq!(measurement, sample.measurement[0]);
+}
+
+#[test]
+fn text_content_with_flattened_attributes() {
+    use std::collections::BTreeMap;
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Label {
+        #[serde(flatten)]
+        attributes: BTreeMap<String, String>,
+        #[serde(rename = "$value")]
+        text: String,
+    }
+
+    let label: Label =
+        from_str(r#"<label lang="en" dir="ltr">Apples &amp; pears</label>"#).unwrap();
+    let mut attributes = BTreeMap::new();
+    attributes.insert("dir".to_string(), "ltr".to_string());
+    attributes.insert("lang".to_string(), "en".to_string());
+    assert_eq!(
+        label,
+        Label {
+            attributes,
+            text: "Apples & pears".to_string(),
+        }
+    );
+
+    // Flattened structs are serialized as maps, which entries are written as elements,
+    // and the `$value` entry as the text
+    let mut buffer = Vec::new();
+    {
+        let writer = quick_xml::Writer::new(&mut buffer);
+        let mut ser = quick_xml::se::Serializer::with_root(writer, Some("label"));
+        label.serialize(&mut ser).unwrap();
+    }
+    assert_eq!(
+        String::from_utf8(buffer).unwrap(),
+        "<label><dir>ltr</dir><lang>en</lang>Apples &amp; pears</label>"
+    );
+}
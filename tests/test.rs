@@ -3,8 +3,10 @@ extern crate quick_xml;
 extern crate serde;
 
 use quick_xml::events::attributes::Attribute;
+use quick_xml::events::BytesStart;
 use quick_xml::events::Event::*;
-use quick_xml::Reader;
+use quick_xml::name::QName;
+use quick_xml::{Reader, Writer};
 use std::borrow::Cow;
 use std::io::Cursor;
 
@@ -29,6 +31,45 @@ fn test_sample() {
     println!("{}", count);
 }
 
+#[test]
+fn test_select_sample() {
+    fn select(path: &str) -> Vec<String> {
+        let src: &[u8] = include_bytes!("sample_rss.xml");
+        let mut r = Reader::from_reader(src);
+        r.trim_text(true);
+        r.select(path)
+            .unwrap()
+            .map(|selected| selected.unwrap().text().to_string())
+            .collect()
+    }
+
+    let titles = select("/rss/channel/item/title");
+    assert_eq!(titles.len(), 99);
+    assert_eq!(titles[0], "What Is KDE Neon? | LAS 409");
+    assert_eq!(titles[98], "Linux Gaming for Everyone | LAS 311");
+
+    let links = select("/rss/channel/item/link");
+    assert_eq!(links.len(), 99);
+    assert_eq!(
+        links[0],
+        "http://www.jupiterbroadcasting.com/97801/what-is-kde-neon-las-409/"
+    );
+
+    let enclosures = select("/rss/channel/item/enclosure/@url");
+    assert_eq!(enclosures.len(), 99);
+    assert_eq!(
+        enclosures[0],
+        "http://www.podtrac.com/pts/redirect.ogg/traffic.libsyn.com/jbmirror/linuxactionshowep409.ogg"
+    );
+    assert_eq!(
+        enclosures[98],
+        "http://traffic.libsyn.com/jupiterbroadcasting/linuxactionshowep311.ogg"
+    );
+
+    // The channel title, the title of the channel image and the titles of all items
+    assert_eq!(select("/rss/channel/*/title").len(), 100);
+}
+
 #[test]
 fn test_attributes_empty() {
     let src = b"<a att1='a' att2='b'/>";
@@ -40,14 +81,14 @@ fn test_attributes_empty() {
             let mut atts = e.attributes();
             match atts.next() {
                 Some(Ok(Attribute {
-                    key: b"att1",
+                    key: QName(b"att1"),
                     value: Cow::Borrowed(b"a"),
                 })) => (),
                 e => panic!("Expecting att1='a' attribute, found {:?}", e),
             }
             match atts.next() {
                 Some(Ok(Attribute {
-                    key: b"att2",
+                    key: QName(b"att2"),
                     value: Cow::Borrowed(b"b"),
                 })) => (),
                 e => panic!("Expecting att2='b' attribute, found {:?}", e),
@@ -72,7 +113,7 @@ fn test_attribute_equal() {
             let mut atts = e.attributes();
             match atts.next() {
                 Some(Ok(Attribute {
-                    key: b"att1",
+                    key: QName(b"att1"),
                     value: Cow::Borrowed(b"a=b"),
                 })) => (),
                 e => panic!("Expecting att1=\"a=b\" attribute, found {:?}", e),
@@ -125,7 +166,7 @@ fn test_attributes_empty_ns() {
         .filter(|kv| !kv.key.starts_with(b"xmlns"))
         .map(|Attribute { key: name, value }| {
             let (opt_ns, local_name) = r.attribute_namespace(name, &ns_buf);
-            (opt_ns, local_name, value)
+            (opt_ns, local_name.into_inner(), value)
         });
     match atts.next() {
         Some((None, b"att1", Cow::Borrowed(b"a"))) => (),
@@ -170,7 +211,7 @@ fn test_attributes_empty_ns_expanded() {
             .filter(|kv| !kv.key.starts_with(b"xmlns"))
             .map(|Attribute { key: name, value }| {
                 let (opt_ns, local_name) = r.attribute_namespace(name, &ns_buf);
-                (opt_ns, local_name, value)
+                (opt_ns, local_name.into_inner(), value)
             });
         match atts.next() {
             Some((None, b"att1", Cow::Borrowed(b"a"))) => (),
@@ -192,7 +233,7 @@ fn test_attributes_empty_ns_expanded() {
     }
 
     match r.read_namespaced_event(&mut buf, &mut ns_buf) {
-        Ok((None, End(e))) => assert_eq!(b"a", e.name()),
+        Ok((None, End(e))) => assert_eq!(b"a", e.name().as_ref()),
         e => panic!("Expecting End event, got {:?}", e),
     }
 }
@@ -211,7 +252,7 @@ fn test_default_ns_shadowing_empty() {
         match r.read_namespaced_event(&mut buf, &mut ns_buf) {
             Ok((Some(ns), Start(e))) => {
                 assert_eq!(&ns[..], b"urn:example:o");
-                assert_eq!(e.name(), b"e");
+                assert_eq!(e.name().as_ref(), b"e");
             }
             e => panic!("Expected Start event (<outer>), got {:?}", e),
         }
@@ -222,7 +263,7 @@ fn test_default_ns_shadowing_empty() {
         let e = match r.read_namespaced_event(&mut buf, &mut ns_buf) {
             Ok((Some(ns), Empty(e))) => {
                 assert_eq!(::std::str::from_utf8(ns).unwrap(), "urn:example:i");
-                assert_eq!(e.name(), b"e");
+                assert_eq!(e.name().as_ref(), b"e");
                 e
             }
             e => panic!("Expecting Empty event, got {:?}", e),
@@ -235,7 +276,7 @@ fn test_default_ns_shadowing_empty() {
             .filter(|kv| !kv.key.starts_with(b"xmlns"))
             .map(|Attribute { key: name, value }| {
                 let (opt_ns, local_name) = r.attribute_namespace(name, &ns_buf);
-                (opt_ns, local_name, value)
+                (opt_ns, local_name.into_inner(), value)
             });
         // the attribute should _not_ have a namespace name. The default namespace does not
         // apply to attributes.
@@ -253,7 +294,7 @@ fn test_default_ns_shadowing_empty() {
     match r.read_namespaced_event(&mut buf, &mut ns_buf) {
         Ok((Some(ns), End(e))) => {
             assert_eq!(&ns[..], b"urn:example:o");
-            assert_eq!(e.name(), b"e");
+            assert_eq!(e.name().as_ref(), b"e");
         }
         e => panic!("Expected End event (<outer>), got {:?}", e),
     }
@@ -273,7 +314,7 @@ fn test_default_ns_shadowing_expanded() {
         match r.read_namespaced_event(&mut buf, &mut ns_buf) {
             Ok((Some(ns), Start(e))) => {
                 assert_eq!(&ns[..], b"urn:example:o");
-                assert_eq!(e.name(), b"e");
+                assert_eq!(e.name().as_ref(), b"e");
             }
             e => panic!("Expected Start event (<outer>), got {:?}", e),
         }
@@ -285,7 +326,7 @@ fn test_default_ns_shadowing_expanded() {
         let e = match r.read_namespaced_event(&mut buf, &mut ns_buf) {
             Ok((Some(ns), Start(e))) => {
                 assert_eq!(&ns[..], b"urn:example:i");
-                assert_eq!(e.name(), b"e");
+                assert_eq!(e.name().as_ref(), b"e");
                 e
             }
             e => panic!("Expecting Start event (<inner>), got {:?}", e),
@@ -297,7 +338,7 @@ fn test_default_ns_shadowing_expanded() {
             .filter(|kv| !kv.key.starts_with(b"xmlns"))
             .map(|Attribute { key: name, value }| {
                 let (opt_ns, local_name) = r.attribute_namespace(name, &ns_buf);
-                (opt_ns, local_name, value)
+                (opt_ns, local_name.into_inner(), value)
             });
         // the attribute should _not_ have a namespace name. The default namespace does not
         // apply to attributes.
@@ -315,7 +356,7 @@ fn test_default_ns_shadowing_expanded() {
     match r.read_namespaced_event(&mut buf, &mut ns_buf) {
         Ok((Some(ns), End(e))) => {
             assert_eq!(&ns[..], b"urn:example:i");
-            assert_eq!(e.name(), b"e");
+            assert_eq!(e.name().as_ref(), b"e");
         }
         e => panic!("Expected End event (</inner>), got {:?}", e),
     }
@@ -323,7 +364,7 @@ fn test_default_ns_shadowing_expanded() {
     match r.read_namespaced_event(&mut buf, &mut ns_buf) {
         Ok((Some(ns), End(e))) => {
             assert_eq!(&ns[..], b"urn:example:o");
-            assert_eq!(e.name(), b"e");
+            assert_eq!(e.name().as_ref(), b"e");
         }
         e => panic!("Expected End event (</outer>), got {:?}", e),
     }
@@ -459,6 +500,210 @@ fn test_default_namespace() {
     }
 }
 
+/// `read_namespaced_event` returns the resolved namespace of each element together with the event
+#[test]
+fn test_default_and_prefixed_namespaces() {
+    let mut r = Reader::from_str(
+        r#"<feed xmlns="urn:atom" xmlns:m="urn:media">
+            <m:group><m:thumb url="a.png"/><title>x</title></m:group>
+            <x:unbound/>
+        </feed>"#,
+    );
+    r.trim_text(true);
+    let mut buf = Vec::new();
+    let mut ns_buf = Vec::new();
+    let mut events = Vec::new();
+    loop {
+        let (ns, event) = r.read_namespaced_event(&mut buf, &mut ns_buf).unwrap();
+        let ns = ns.map(|ns| String::from_utf8(ns.to_vec()).unwrap());
+        let (kind, name) = match event {
+            Start(e) => ("start", e.local_name().to_vec()),
+            Empty(e) => ("empty", e.local_name().to_vec()),
+            End(e) => ("end", e.local_name().to_vec()),
+            Text(e) => ("text", e.to_vec()),
+            Eof => break,
+            e => panic!("Unexpected event {:?}", e),
+        };
+        events.push((kind, String::from_utf8(name).unwrap(), ns));
+        buf.clear();
+    }
+    let ns = |ns: &str| Some(ns.to_string());
+    assert_eq!(
+        events,
+        vec![
+            ("start", "feed".to_string(), ns("urn:atom")),
+            ("start", "group".to_string(), ns("urn:media")),
+            ("empty", "thumb".to_string(), ns("urn:media")),
+            ("start", "title".to_string(), ns("urn:atom")),
+            // Texts are not resolved
+            ("text", "x".to_string(), None),
+            ("end", "title".to_string(), ns("urn:atom")),
+            ("end", "group".to_string(), ns("urn:media")),
+            // Unbound prefixes are not resolved
+            ("empty", "unbound".to_string(), None),
+            ("end", "feed".to_string(), ns("urn:atom")),
+        ]
+    );
+}
+
+#[test]
+fn test_in_scope_namespaces() {
+    let mut r = Reader::from_str(
+        r#"<a xmlns="urn:a" xmlns:p="urn:p1" xmlns:q="urn:q">
+            <b xmlns:p="urn:p2" xmlns="">
+                <c xmlns:p="urn:p3"/>
+            </b>
+            <d/>
+        </a>"#,
+    );
+    r.trim_text(true);
+    let mut buf = Vec::new();
+    let mut ns_buf = Vec::new();
+
+    let mut next = |r: &mut Reader<&[u8]>| {
+        r.read_namespaced_event(&mut buf, &mut ns_buf).unwrap();
+        r.in_scope_namespaces(&ns_buf)
+            .map(|(prefix, ns)| {
+                (
+                    prefix.map(|p| String::from_utf8(p.to_vec()).unwrap()),
+                    String::from_utf8(ns.to_vec()).unwrap(),
+                )
+            })
+            .collect::<Vec<_>>()
+    };
+    let binding = |prefix: Option<&str>, ns: &str| (prefix.map(String::from), ns.to_string());
+
+    // <a>
+    assert_eq!(
+        next(&mut r),
+        vec![
+            binding(Some("q"), "urn:q"),
+            binding(Some("p"), "urn:p1"),
+            binding(None, "urn:a"),
+        ]
+    );
+    // <b>: `p` is re-declared and the default namespace is removed
+    assert_eq!(
+        next(&mut r),
+        vec![binding(Some("p"), "urn:p2"), binding(Some("q"), "urn:q")]
+    );
+    // <c/>
+    assert_eq!(
+        next(&mut r),
+        vec![binding(Some("p"), "urn:p3"), binding(Some("q"), "urn:q")]
+    );
+    // </b>
+    assert_eq!(
+        next(&mut r),
+        vec![binding(Some("p"), "urn:p2"), binding(Some("q"), "urn:q")]
+    );
+    // <d/>
+    assert_eq!(
+        next(&mut r),
+        vec![
+            binding(Some("q"), "urn:q"),
+            binding(Some("p"), "urn:p1"),
+            binding(None, "urn:a"),
+        ]
+    );
+}
+
+/// Resolved namespaces and local names of the elements in the document
+fn resolved_names(xml: &[u8]) -> Vec<(Option<String>, String)> {
+    let mut r = Reader::from_reader(xml);
+    r.trim_text(true);
+    let mut buf = Vec::new();
+    let mut ns_buf = Vec::new();
+    let mut names = Vec::new();
+    loop {
+        match r.read_namespaced_event(&mut buf, &mut ns_buf).unwrap() {
+            (ns, Start(e)) | (ns, Empty(e)) => names.push((
+                ns.map(|ns| String::from_utf8(ns.to_vec()).unwrap()),
+                String::from_utf8(e.local_name().to_vec()).unwrap(),
+            )),
+            (_, Eof) => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+    names
+}
+
+#[test]
+fn test_materialize_bindings() {
+    let xml = br#"<root xmlns="urn:root" xmlns:p="urn:p1" xmlns:q="urn:q" xmlns:r="urn:r">
+            <p:outer xmlns:p="urn:p2" xmlns:r="">
+                <p:item q:attr="1"><inner/><q:leaf/></p:item>
+            </p:outer>
+        </root>"#;
+    let mut r = Reader::from_reader(xml.as_ref());
+    r.trim_text(true);
+    let mut buf = Vec::new();
+    let mut ns_buf = Vec::new();
+    let mut writer = Writer::new(Vec::new());
+    let mut depth = 0;
+    loop {
+        match r.read_namespaced_event(&mut buf, &mut ns_buf).unwrap() {
+            (_, Start(e)) if depth == 0 && e.local_name().as_ref() == b"item" => {
+                let mut e = e.into_owned();
+                r.materialize_bindings(&mut e, &ns_buf);
+                writer.write_event(Start(e)).unwrap();
+                depth = 1;
+            }
+            (_, Start(e)) if depth > 0 => {
+                writer.write_event(Start(e)).unwrap();
+                depth += 1;
+            }
+            (_, End(e)) if depth > 0 => {
+                writer.write_event(End(e)).unwrap();
+                depth -= 1;
+                if depth == 0 {
+                    break;
+                }
+            }
+            (_, e) if depth > 0 => writer.write_event(e).unwrap(),
+            (_, Eof) => panic!("the subtree was not found"),
+            _ => {}
+        }
+        buf.clear();
+    }
+    let fragment = writer.into_inner();
+
+    // `p` is shadowed and `r` is removed
+    assert_eq!(
+        String::from_utf8(fragment.clone()).unwrap(),
+        r#"<p:item q:attr="1" xmlns:p="urn:p2" xmlns:q="urn:q" xmlns="urn:root"><inner/><q:leaf/></p:item>"#
+    );
+    let original = resolved_names(xml);
+    assert_eq!(resolved_names(&fragment), original[2..].to_vec());
+}
+
+#[test]
+fn test_resolve_qualified_names() {
+    let mut r = Reader::from_str(r#"<r xmlns="urn:d" xmlns:p="urn:p"><p:e/><e/><:x/><x:/></r>"#);
+    r.trim_text(true);
+    let mut buf = Vec::new();
+    let mut ns_buf = Vec::new();
+
+    // (namespace, local name) of each element
+    let mut resolve = |r: &mut Reader<&[u8]>| -> (Option<Vec<u8>>, Vec<u8>) {
+        match r.read_namespaced_event(&mut buf, &mut ns_buf).unwrap() {
+            (_, Start(e)) | (_, Empty(e)) => {
+                let (ns, local) = r.event_namespace(e.name(), &ns_buf);
+                (ns.map(|ns| ns.to_vec()), local.to_vec())
+            }
+            e => panic!("expecting an element, found {:?}", e),
+        }
+    };
+
+    assert_eq!(resolve(&mut r), (Some(b"urn:d".to_vec()), b"r".to_vec()));
+    assert_eq!(resolve(&mut r), (Some(b"urn:p".to_vec()), b"e".to_vec()));
+    assert_eq!(resolve(&mut r), (Some(b"urn:d".to_vec()), b"e".to_vec()));
+    // Names with unbound prefixes are returned as is
+    assert_eq!(resolve(&mut r), (None, b":x".to_vec()));
+    assert_eq!(resolve(&mut r), (None, b"x:".to_vec()));
+}
+
 #[cfg(feature = "serialize")]
 #[test]
 fn line_score() {
@@ -1141,3 +1386,111 @@ fn players() {
 
     assert_eq!(res, expected);
 }
+
+/// Reads the document with `preserve_formatting` and writes all its events back
+fn roundtrip(src: &[u8]) -> Vec<u8> {
+    let mut reader = Reader::from_reader(src);
+    reader.preserve_formatting(true);
+    let mut writer = Writer::new(Vec::new());
+    let mut buf = Vec::new();
+    loop {
+        match reader.read_event(&mut buf) {
+            Ok(Eof) => break,
+            Ok(e) => writer.write_event(e).unwrap(),
+            Err(e) => panic!("Error at position {}: {:?}", reader.buffer_position(), e),
+        }
+        buf.clear();
+    }
+    writer.into_inner()
+}
+
+#[test]
+fn roundtrip_formatting() {
+    let src = "<?xml version='1.0'?>\r\n\
+               <a x = 'y'  z=\"w\" ><b/><c /><d\n/><e></e>\
+               t &amp; &#x41; &lt;<![CDATA[<&>]]></a >";
+    assert_eq!(String::from_utf8(roundtrip(src.as_bytes())).unwrap(), src);
+}
+
+#[test]
+fn roundtrip_corpus() {
+    let documents: &[(&str, &[u8])] = &[
+        ("document.xml", include_bytes!("documents/document.xml")),
+        ("gbk.xml", include_bytes!("documents/gbk.xml")),
+        (
+            "opennews_all.rss",
+            include_bytes!("documents/opennews_all.rss"),
+        ),
+        ("sample_1.xml", include_bytes!("documents/sample_1.xml")),
+        ("sample_2.xml", include_bytes!("documents/sample_2.xml")),
+        ("sample_3.xml", include_bytes!("documents/sample_3.xml")),
+        ("sample_4.xml", include_bytes!("documents/sample_4.xml")),
+        ("sample_ns.xml", include_bytes!("documents/sample_ns.xml")),
+        ("shift_jis.xml", include_bytes!("documents/shift_jis.xml")),
+        (
+            "test_writer.xml",
+            include_bytes!("documents/test_writer.xml"),
+        ),
+        (
+            "test_writer_indent.xml",
+            include_bytes!("documents/test_writer_indent.xml"),
+        ),
+        (
+            "test_writer_indent_cdata.xml",
+            include_bytes!("documents/test_writer_indent_cdata.xml"),
+        ),
+        ("linescore.xml", include_bytes!("linescore.xml")),
+        ("players.xml", include_bytes!("players.xml")),
+        ("sample_rss.xml", include_bytes!("sample_rss.xml")),
+    ];
+    for &(name, src) in documents {
+        assert!(
+            roundtrip(src) == src,
+            "{} is changed by the round-trip",
+            name
+        );
+    }
+}
+
+/// Reads the document and writes its events back with the `from` namespace prefix renamed
+/// to `to`, rebuilding the tags from their attributes, if `rebuild` is set
+fn rename_prefix(src: &[u8], from: &str, to: &str, rebuild: bool) -> Vec<u8> {
+    let mut reader = Reader::from_reader(src);
+    let mut writer = Writer::new(Vec::new());
+    writer
+        .rename_prefix(from, to)
+        .preserve_attribute_quotes(true);
+    let mut buf = Vec::new();
+    loop {
+        let event = match reader.read_event(&mut buf).unwrap() {
+            Empty(ref e) if rebuild => {
+                let mut tag = BytesStart::owned_name(e.name().as_ref());
+                for attr in e.attributes().with_spans() {
+                    let attr = attr.unwrap();
+                    tag.push_attribute_with_quote(attr.attribute, attr.quote);
+                }
+                Empty(tag)
+            }
+            Eof => break,
+            e => e.into_owned(),
+        };
+        writer.write_event(event).unwrap();
+        buf.clear();
+    }
+    writer.into_inner()
+}
+
+#[test]
+fn roundtrip_attribute_quotes() {
+    let src = "<a:root xmlns:a='urn:a' a:x='1' y=\"2\">\
+               <a:item a:x='\"' y=\"'\" z='caf&#233;'/>\
+               <a:item a:x=\"&lt;\" y='&amp;'>text</a:item>\
+               </a:root>";
+    let renamed = rename_prefix(src.as_bytes(), "a", "b", true);
+    assert_eq!(
+        String::from_utf8(renamed.clone()).unwrap(),
+        src.replace("a:", "b:").replace("xmlns:a", "xmlns:b")
+    );
+    let restored = rename_prefix(&renamed, "b", "a", false);
+    assert_eq!(String::from_utf8(restored).unwrap(), src);
+}
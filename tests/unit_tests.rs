@@ -1,17 +1,20 @@
 extern crate quick_xml;
+#[cfg(feature = "serde-types")]
+extern crate serde_json;
 
 use std::io::Cursor;
 use std::str::from_utf8;
 
 use quick_xml::events::Event::*;
 use quick_xml::events::{BytesDecl, BytesEnd, BytesStart, BytesText, Event};
-use quick_xml::{Reader, Result, Writer};
+use quick_xml::name::QName;
+use quick_xml::{Error, Reader, ReaderConfig, Result, Writer};
 
 macro_rules! next_eq_name {
     ($r:expr, $t:tt, $bytes:expr) => {
         let mut buf = Vec::new();
         match $r.read_event(&mut buf).unwrap() {
-            $t(ref e) if e.name() == $bytes => (),
+            $t(ref e) if e.name().as_ref() == $bytes => (),
             e => panic!(
                 "expecting {}({:?}), found {:?}",
                 stringify!($t),
@@ -94,6 +97,106 @@ fn test_empty_can_be_expanded() {
     next_eq!(r, Start, b"a", End, b"a");
 }
 
+#[test]
+fn test_empty_can_be_expanded_with_attributes() {
+    let mut r = Reader::from_str("<root><a x=\"1\" y='&lt;2'/><b\n  z=\"3\"\n/><c/></root>");
+    r.trim_text(true).expand_empty_elements(true);
+    // The same buffer is reused without clearing, so the synthesized `End`
+    // must not borrow the name from it
+    let mut buf = Vec::new();
+    let mut events = Vec::new();
+    loop {
+        let event = match r.read_event(&mut buf).unwrap() {
+            Start(e) => {
+                let attributes: Vec<_> = e
+                    .attributes()
+                    .map(|a| {
+                        let a = a.unwrap();
+                        let value = a.unescape_and_decode_value(&r).unwrap();
+                        format!("{}={}", from_utf8(a.key.as_ref()).unwrap(), value)
+                    })
+                    .collect();
+                let name = from_utf8(e.name().as_ref()).unwrap().to_string();
+                format!("<{} {}>", name, attributes.join(" "))
+            }
+            End(e) => format!("</{}>", from_utf8(e.name().as_ref()).unwrap()),
+            Eof => break,
+            e => panic!("Unexpected event {:?}", e),
+        };
+        events.push(event);
+    }
+    assert_eq!(
+        events,
+        vec![
+            "<root >",
+            "<a x=1 y=<2>",
+            "</a>",
+            "<b z=3>",
+            "</b>",
+            "<c >",
+            "</c>",
+            "</root>",
+        ]
+    );
+}
+
+#[test]
+fn test_empty_can_be_expanded_without_end_name_checks() {
+    let mut r = Reader::from_str("<root><a x=\"1\"/><b/></root>");
+    r.trim_text(true)
+        .expand_empty_elements(true)
+        .check_end_names(false);
+    next_eq!(r, Start, b"root", Start, b"a", End, b"a", Start, b"b", End, b"b", End, b"root");
+}
+
+#[test]
+fn test_empty_can_be_expanded_namespaced() {
+    let mut r = Reader::from_str(
+        "<root xmlns='urn:root'><p:a xmlns:p='urn:p' p:x='1' y='2'/><p:b/></root>",
+    );
+    r.trim_text(true).expand_empty_elements(true);
+    let mut buf = Vec::new();
+    let mut ns_buf = Vec::new();
+    let mut events = Vec::new();
+    loop {
+        let (ns, event) = r.read_namespaced_event(&mut buf, &mut ns_buf).unwrap();
+        let ns = ns.map(|ns| from_utf8(ns).unwrap().to_string());
+        let event = match event {
+            Start(e) => {
+                let mut event = format!("<{{{:?}}}{}", ns, from_utf8(e.name().as_ref()).unwrap());
+                for a in e.attributes() {
+                    let a = a.unwrap();
+                    let (attr_ns, _) = r.attribute_namespace(a.key, &ns_buf);
+                    event.push_str(&format!(
+                        " {{{:?}}}{}={}",
+                        attr_ns.map(|ns| from_utf8(ns).unwrap()),
+                        from_utf8(a.key.as_ref()).unwrap(),
+                        from_utf8(&a.value).unwrap()
+                    ));
+                }
+                event + ">"
+            }
+            End(e) => format!("</{{{:?}}}{}>", ns, from_utf8(e.name().as_ref()).unwrap()),
+            Eof => break,
+            e => panic!("Unexpected event {:?}", e),
+        };
+        events.push(event);
+        buf.clear();
+    }
+    assert_eq!(
+        events,
+        vec![
+            r#"<{Some("urn:root")}root {None}xmlns=urn:root>"#,
+            r#"<{Some("urn:p")}p:a {None}xmlns:p=urn:p {Some("urn:p")}p:x=1 {None}y=2>"#,
+            r#"</{Some("urn:p")}p:a>"#,
+            // The prefix is not in scope after the end of `p:a`
+            r#"<{None}p:b>"#,
+            r#"</{None}p:b>"#,
+            r#"</{Some("urn:root")}root>"#,
+        ]
+    );
+}
+
 #[test]
 fn test_empty_attr() {
     let mut r = Reader::from_str("<a b=\"test\" />");
@@ -172,6 +275,33 @@ fn test_trim_test() {
     );
 }
 
+#[test]
+fn test_drop_whitespace_only_text() {
+    let txt = "<div>\n  <p>hello <b>world</b> !</p>\n</div>";
+    let mut r = Reader::from_str(txt);
+    r.drop_whitespace_only_text(true);
+    next_eq!(
+        r, Start, b"div", Start, b"p", Text, b"hello ", Start, b"b", Text, b"world", End, b"b",
+        Text, b" !", End, b"p", End, b"div"
+    );
+
+    // Edges of texts are trimmed, but whitespace-only texts are kept as empty texts
+    let mut r = Reader::from_str(txt);
+    r.trim_text_edges(true);
+    next_eq!(
+        r, Text, b"", Start, b"div", Text, b"", Start, b"p", Text, b"hello", Start, b"b", Text,
+        b"world", End, b"b", Text, b"!", End, b"p", Text, b"", End, b"div"
+    );
+
+    // `trim_text` does both
+    let mut r = Reader::from_str(txt);
+    r.trim_text(true);
+    next_eq!(
+        r, Start, b"div", Start, b"p", Text, b"hello", Start, b"b", Text, b"world", End, b"b",
+        Text, b"!", End, b"p", End, b"div"
+    );
+}
+
 #[test]
 fn test_cdata() {
     let mut r = Reader::from_str("<![CDATA[test]]>");
@@ -432,6 +562,256 @@ fn test_buf_position_err_end_element() {
     }
 }
 
+#[test]
+fn test_end_name_prefixes() {
+    let xml = "<ns1:a><b><ns2:c></c></ns2:b></a>";
+
+    let mut r = Reader::from_str(xml);
+    r.trim_text(true);
+    next_eq!(r, Start, b"ns1:a", Start, b"b", Start, b"ns2:c");
+    let mut buf = Vec::new();
+    match r.read_event(&mut buf) {
+        Err(Error::EndEventMismatch { expected, found }) => {
+            assert_eq!(expected, "ns2:c");
+            assert_eq!(found, "c");
+        }
+        e => panic!("expecting end event mismatch, found {:?}", e),
+    }
+
+    let mut r = Reader::from_str(xml);
+    r.trim_text(true).lenient_end_name_prefixes(true);
+    next_eq!(r, Start, b"ns1:a", Start, b"b", Start, b"ns2:c");
+    next_eq!(r, End, b"c", End, b"ns2:b", End, b"a");
+
+    // Local names still have to match
+    let mut r = Reader::from_str("<ns1:a></ns1:b>");
+    r.trim_text(true).lenient_end_name_prefixes(true);
+    next_eq!(r, Start, b"ns1:a");
+    assert!(r.read_event(&mut buf).is_err());
+}
+
+/// Returns the unescaped attributes of a start tag
+fn attrs(e: &BytesStart) -> Vec<(String, String)> {
+    e.attributes()
+        .map(|a| {
+            let a = a.unwrap();
+            (
+                from_utf8(a.key.as_ref()).unwrap().to_string(),
+                from_utf8(&a.unescaped_value().unwrap())
+                    .unwrap()
+                    .to_string(),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn test_lenient_end_name_case() {
+    let mut buf = Vec::new();
+    let mut r = Reader::from_str("<DIV></div>");
+    r.trim_text(true);
+    next_eq!(r, Start, b"DIV");
+    match r.read_event(&mut buf) {
+        Err(Error::EndEventMismatch { expected, found }) => {
+            assert_eq!(expected, "DIV");
+            assert_eq!(found, "div");
+        }
+        e => panic!("expecting end event mismatch, found {:?}", e),
+    }
+
+    let mut r = Reader::from_str("<DIV><p></P></div>");
+    r.trim_text(true).lenient_end_name_case(true);
+    next_eq!(r, Start, b"DIV", Start, b"p", End, b"P", End, b"div");
+
+    // Names still have to match
+    let mut r = Reader::from_str("<DIV></span>");
+    r.trim_text(true).lenient_end_name_case(true);
+    next_eq!(r, Start, b"DIV");
+    assert!(r.read_event(&mut buf).is_err());
+
+    // Combined with ignoring prefixes
+    let mut r = Reader::from_str("<ns1:A></ns2:a>");
+    r.trim_text(true)
+        .lenient_end_name_case(true)
+        .lenient_end_name_prefixes(true);
+    next_eq!(r, Start, b"ns1:A", End, b"ns2:a");
+}
+
+#[test]
+fn test_lenient_attribute_values() {
+    let xml =
+        r#"<td colspan=2 class = wide title="x y" data=a"b"c lang='en'><img src=a.png/></td>"#;
+    let mut buf = Vec::new();
+
+    let mut r = Reader::from_str(xml);
+    r.trim_text(true);
+    match r.read_event(&mut buf).unwrap() {
+        Start(e) => assert!(e.attributes().any(|a| a.is_err())),
+        e => panic!("expecting Start, found {:?}", e),
+    }
+
+    let mut r = Reader::from_str(xml);
+    r.trim_text(true).lenient_attribute_values(true);
+    match r.read_event(&mut buf).unwrap() {
+        Start(e) => {
+            assert_eq!(e.name().as_ref(), b"td");
+            assert_eq!(
+                attrs(&e),
+                vec![
+                    ("colspan".to_string(), "2".to_string()),
+                    ("class".to_string(), "wide".to_string()),
+                    ("title".to_string(), "x y".to_string()),
+                    ("data".to_string(), "a\"b\"c".to_string()),
+                    ("lang".to_string(), "en".to_string()),
+                ]
+            );
+        }
+        e => panic!("expecting Start, found {:?}", e),
+    }
+    match r.read_event(&mut buf).unwrap() {
+        Empty(e) => assert_eq!(attrs(&e), vec![("src".to_string(), "a.png".to_string())]),
+        e => panic!("expecting Empty, found {:?}", e),
+    }
+    next_eq!(r, End, b"td");
+
+    // Quoted values are borrowed unchanged
+    let mut r = Reader::from_str(r#"<a b="1" c='2'>"#);
+    r.trim_text(true).lenient_attribute_values(true);
+    match r.read_event(&mut buf).unwrap() {
+        Start(e) => assert_eq!(&*e, br#"a b="1" c='2'"#),
+        e => panic!("expecting Start, found {:?}", e),
+    }
+}
+
+#[test]
+fn test_lenient_ampersands() {
+    let xml = r#"<a href="?x=1&y=2&amp;z">Fish & Chips &amp; more &#33;</a>"#;
+    let mut buf = Vec::new();
+
+    let mut r = Reader::from_str(xml);
+    r.trim_text(true);
+    match r.read_event(&mut buf).unwrap() {
+        Start(e) => {
+            let href = e.attributes().next().unwrap().unwrap();
+            assert!(href.unescaped_value().is_err());
+        }
+        e => panic!("expecting Start, found {:?}", e),
+    }
+    match r.read_event(&mut buf).unwrap() {
+        Text(e) => assert!(e.unescaped().is_err()),
+        e => panic!("expecting Text, found {:?}", e),
+    }
+
+    let mut r = Reader::from_str(xml);
+    r.trim_text(true).lenient_ampersands(true);
+    match r.read_event(&mut buf).unwrap() {
+        Start(e) => assert_eq!(
+            attrs(&e),
+            vec![("href".to_string(), "?x=1&y=2&z".to_string())]
+        ),
+        e => panic!("expecting Start, found {:?}", e),
+    }
+    match r.read_event(&mut buf).unwrap() {
+        Text(e) => assert_eq!(&*e.unescaped().unwrap(), b"Fish & Chips & more !"),
+        e => panic!("expecting Text, found {:?}", e),
+    }
+    next_eq!(r, End, b"a");
+}
+
+#[test]
+fn test_void_elements() {
+    let xml = "<p>a<br>b<IMG src='x'/><hr></p>";
+    let mut buf = Vec::new();
+
+    let mut r = Reader::from_str(xml);
+    r.trim_text(true);
+    next_eq!(r, Start, b"p", Text, b"a", Start, b"br", Text, b"b", Empty, b"IMG");
+    next_eq!(r, Start, b"hr");
+    assert!(r.read_event(&mut buf).is_err());
+
+    let mut r = Reader::from_str(xml);
+    r.trim_text(true).void_elements(&["br", "hr", "img"]);
+    next_eq!(r, Start, b"p", Text, b"a", Empty, b"br", Text, b"b", Empty, b"IMG");
+    next_eq!(r, Empty, b"hr", End, b"p");
+
+    let mut r = Reader::from_str(xml);
+    r.trim_text(true)
+        .expand_empty_elements(true)
+        .void_elements(&["br", "hr", "img"]);
+    next_eq!(r, Start, b"p", Text, b"a", Start, b"br", End, b"br", Text, b"b");
+    next_eq!(r, Start, b"IMG", End, b"IMG", Start, b"hr", End, b"hr", End, b"p");
+}
+
+#[test]
+fn test_lenient_html() {
+    let html = r#"<html>
+<head><title>Fish & Chips</title>
+<meta charset=utf-8>
+<link rel=stylesheet href="/style.css?v=2&theme=dark"></head>
+<BODY class=main>
+<h1>Menu</H1>
+<p>Cod & chips<br>Served daily<hr>
+<a href=/order?item=1&qty=2>Order</A></p>
+<img src=/cod.png alt="Cod">
+</body>
+</html>"#;
+
+    let mut r = Reader::from_str(html);
+    r.trim_text(true)
+        .lenient_attribute_values(true)
+        .lenient_ampersands(true)
+        .lenient_end_name_case(true)
+        .void_elements(&["br", "hr", "img", "link", "meta"]);
+
+    let mut events = Vec::new();
+    let mut buf = Vec::new();
+    loop {
+        let event = match r.read_event(&mut buf).unwrap() {
+            Start(e) => format!("<{} {:?}", from_utf8(e.name().as_ref()).unwrap(), attrs(&e)),
+            Empty(e) => format!(
+                "<{}/ {:?}",
+                from_utf8(e.name().as_ref()).unwrap(),
+                attrs(&e)
+            ),
+            End(e) => format!("</{}", from_utf8(e.name().as_ref()).unwrap()),
+            Text(e) => from_utf8(&e.unescaped().unwrap()).unwrap().to_string(),
+            Eof => break,
+            e => panic!("unexpected event {:?}", e),
+        };
+        events.push(event);
+        buf.clear();
+    }
+    assert_eq!(
+        events,
+        vec![
+            "<html []",
+            "<head []",
+            "<title []",
+            "Fish & Chips",
+            "</title",
+            r#"<meta/ [("charset", "utf-8")]"#,
+            r#"<link/ [("rel", "stylesheet"), ("href", "/style.css?v=2&theme=dark")]"#,
+            "</head",
+            r#"<BODY [("class", "main")]"#,
+            "<h1 []",
+            "Menu",
+            "</H1",
+            "<p []",
+            "Cod & chips",
+            "<br/ []",
+            "Served daily",
+            "<hr/ []",
+            r#"<a [("href", "/order?item=1&qty=2")]"#,
+            "Order",
+            "</A",
+            "</p",
+            r#"<img/ [("src", "/cod.png"), ("alt", "Cod")]"#,
+            "</body",
+            "</html",
+        ]
+    );
+}
+
 #[test]
 fn test_buf_position_err_comment() {
     let mut r = Reader::from_str("<a><!--b>");
@@ -699,6 +1079,107 @@ fn test_read_write_roundtrip() {
     assert_eq!(String::from_utf8(result).unwrap(), input.to_string());
 }
 
+#[test]
+fn test_read_write_roundtrip_owned_events() {
+    let input = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE section>
+<section ns:label="header">
+    <!-- comment -->
+    <section ns:label="empty element section" />
+    <section ns:label="with text">data &lt;escaped&gt;<![CDATA[cdata]]></section>
+    <?pi data?>
+</section>"#;
+
+    let events: Vec<Event<'static>> = {
+        let owned_input = input.to_string();
+        let mut reader = Reader::from_str(&owned_input);
+        reader.trim_text(false).expand_empty_elements(false);
+        let mut buf = Vec::new();
+        let mut events = Vec::new();
+        loop {
+            match reader.read_event(&mut buf) {
+                Ok(Eof) => break,
+                Ok(e) => events.push(e.into_owned()),
+                Err(e) => panic!("{}", e),
+            }
+            buf.clear();
+        }
+        events
+    };
+
+    // Owned events could be sent to another thread
+    let result = std::thread::spawn(move || {
+        let mut writer = Writer::new(Cursor::new(Vec::new()));
+        for event in events {
+            writer.write_event(event).unwrap();
+        }
+        writer.into_inner().into_inner()
+    })
+    .join()
+    .unwrap();
+    assert_eq!(String::from_utf8(result).unwrap(), input);
+}
+
+#[cfg(feature = "serde-types")]
+#[test]
+fn test_read_write_roundtrip_serde_events() {
+    let input = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE section>
+<section ns:label="header" empty=''>
+    <!-- comment -->
+    <section ns:label="empty element section" />
+    <section ns:label="with text">data &lt;escaped&gt;<![CDATA[cdata]]></section>
+    <?pi data?>
+</section>"#;
+
+    let mut reader = Reader::from_str(input);
+    reader.trim_text(false).expand_empty_elements(false);
+    let mut buf = Vec::new();
+    let mut events = Vec::new();
+    loop {
+        match reader.read_event(&mut buf) {
+            Ok(Eof) => break,
+            Ok(e) => events.push(e.into_owned()),
+            Err(e) => panic!("{}", e),
+        }
+        buf.clear();
+    }
+
+    let json = serde_json::to_string(&events).unwrap();
+    let events: Vec<Event<'static>> = serde_json::from_str(&json).unwrap();
+
+    let mut writer = Writer::new(Vec::new());
+    for event in &events {
+        writer.write_event(event).unwrap();
+    }
+    assert_eq!(String::from_utf8(writer.into_inner()).unwrap(), input);
+
+    // Attributes are parsed from the restored events
+    let attributes: Vec<_> = events
+        .iter()
+        .filter_map(|e| match e {
+            Start(e) | Empty(e) => Some(e.attributes().count()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(attributes, vec![2, 1, 1]);
+}
+
+#[cfg(feature = "serde-types")]
+#[test]
+fn test_deserialize_invalid_name_len() {
+    match serde_json::from_str::<Event>(r#"{"Start":{"buf":[97,32,98],"name_len":1}}"#) {
+        Ok(Start(e)) => assert_eq!(e.name(), QName(b"a")),
+        x => panic!("Expected `Ok(Start(_))`, but got `{:?}`", x),
+    }
+
+    let error = serde_json::from_str::<Event>(r#"{"Start":{"buf":[97],"name_len":5}}"#)
+        .unwrap_err()
+        .to_string();
+    assert!(error.starts_with("invalid value: integer `5`"), "{}", error);
+    assert!(serde_json::from_str::<Event>(r#"{"End":{"name":[97],"name_len":2}}"#).is_err());
+}
+
 #[test]
 fn test_read_write_roundtrip_escape() {
     let input = r#"
@@ -894,7 +1375,9 @@ fn test_unescape_and_decode_without_bom_removes_utf16be_bom() {
             _ => (),
         }
     }
-    assert_eq!(txt[0], "");
+    // UTF-16 input is transcoded to UTF-8 without the BOM, so there is no text before
+    // the declaration
+    assert!(txt.is_empty());
 }
 
 #[test]
@@ -913,7 +1396,152 @@ fn test_unescape_and_decode_without_bom_removes_utf16le_bom() {
             _ => (),
         }
     }
-    assert_eq!(txt[0], "");
+    // UTF-16 input is transcoded to UTF-8 without the BOM, so there is no text before
+    // the declaration
+    assert!(txt.is_empty());
+}
+
+/// Reads all events of a document, decoding names and texts, as a list of strings
+#[cfg(feature = "encoding")]
+fn all_events<B: std::io::BufRead>(reader: &mut Reader<B>) -> Vec<String> {
+    reader.trim_text(true);
+    let mut events = Vec::new();
+    let mut buf = Vec::new();
+    loop {
+        let event = match reader.read_event(&mut buf).unwrap() {
+            Decl(e) => format!(
+                "Decl(encoding={:?})",
+                e.encoding()
+                    .map(|e| reader.decode(&e.unwrap()).into_owned())
+            ),
+            Start(e) | Empty(e) => {
+                let mut event = format!("Start({}", reader.decode(e.name().as_ref()));
+                for a in e.attributes() {
+                    let a = a.unwrap();
+                    event += &format!(
+                        " {}={}",
+                        reader.decode(a.key.as_ref()),
+                        a.unescape_and_decode_value(reader).unwrap()
+                    );
+                }
+                event + ")"
+            }
+            End(e) => format!("End({})", reader.decode(e.name().as_ref())),
+            Text(e) => format!("Text({})", e.unescape_and_decode(reader).unwrap()),
+            CData(e) => format!("CData({})", e.unescape_and_decode(reader).unwrap()),
+            Eof => break,
+            e => panic!("unexpected event {:?}", e),
+        };
+        events.push(event);
+        buf.clear();
+    }
+    events
+}
+
+#[test]
+#[cfg(feature = "encoding")]
+fn test_utf16_with_bom() {
+    let expected = vec![
+        "Decl(encoding=None)",
+        "Start(project name=project-name)",
+        "End(project)",
+    ];
+    let mut reader = Reader::from_file("./tests/documents/utf16le.xml").unwrap();
+    assert_eq!(all_events(&mut reader), expected);
+    assert_eq!(reader.encoding(), encoding_rs::UTF_8);
+
+    let mut reader = Reader::from_file("./tests/documents/utf16be.xml").unwrap();
+    assert_eq!(all_events(&mut reader), expected);
+    assert_eq!(reader.encoding(), encoding_rs::UTF_8);
+}
+
+#[test]
+#[cfg(feature = "encoding")]
+fn test_utf16le_declaration() {
+    let expected = vec![
+        "Decl(encoding=Some(\"UTF-16\"))",
+        "Start(root lang=ru title=\"Привет\" & 😀)",
+        "Start(item)",
+        "Text(Привет, мир! 😀 <ф>)",
+        "End(item)",
+        "Start(empty)",
+        "End(root)",
+    ];
+    let src: &[u8] = include_bytes!("documents/utf16le_decl.xml");
+
+    let mut reader = Reader::from_reader(src);
+    assert_eq!(all_events(&mut reader), expected);
+    assert_eq!(reader.encoding(), encoding_rs::UTF_8);
+
+    // Code units and surrogate pairs are split between chunks of the input
+    for capacity in 1..8 {
+        let mut reader = Reader::from_reader(std::io::BufReader::with_capacity(capacity, src));
+        assert_eq!(all_events(&mut reader), expected, "capacity {}", capacity);
+    }
+}
+
+#[test]
+#[cfg(feature = "encoding")]
+fn test_shift_jis_declaration() {
+    let expected = vec![
+        "Decl(encoding=Some(\"Shift_JIS\"))",
+        "Start(root lang=ja title=\u{8868}\u{793A})",
+        "Start(item)",
+        "Text(\u{30BD}\u{30D5}\u{30C8}\u{30A6}\u{30A7}\u{30A2} & \u{65E5}\u{672C}\u{8A9E})",
+        "End(item)",
+        "Start(item)",
+        "CData(\u{4E88}\u{5B9A}\u{8868})",
+        "End(item)",
+        "End(root)",
+    ];
+    let src: &[u8] = include_bytes!("documents/shift_jis.xml");
+
+    let mut reader = Reader::from_reader(src);
+    assert_eq!(all_events(&mut reader), expected);
+    assert_eq!(reader.encoding(), encoding_rs::SHIFT_JIS);
+
+    // Multibyte characters are split between chunks of the input
+    for capacity in 1..4 {
+        let mut reader = Reader::from_reader(std::io::BufReader::with_capacity(capacity, src));
+        assert_eq!(all_events(&mut reader), expected, "capacity {}", capacity);
+    }
+}
+
+#[test]
+#[cfg(feature = "encoding")]
+fn test_gbk_declaration() {
+    let expected = vec![
+        "Decl(encoding=Some(\"GBK\"))",
+        "Start(root lang=zh title=\u{4F60}\u{597D})",
+        "Start(item)",
+        "Text(\u{4E2D}\u{6587} & \u{4E16}\u{754C})",
+        "End(item)",
+        "End(root)",
+    ];
+    let src: &[u8] = include_bytes!("documents/gbk.xml");
+
+    let mut reader = Reader::from_reader(src);
+    assert_eq!(all_events(&mut reader), expected);
+    assert_eq!(reader.encoding(), encoding_rs::GBK);
+}
+
+#[test]
+#[cfg(feature = "encoding")]
+fn test_decode_strict() {
+    let mut reader = Reader::from_reader(&b"<?xml version='1.0' encoding='Shift_JIS'?><a/>"[..]);
+    reader.trim_text(true);
+    reader.read_event(&mut Vec::new()).unwrap();
+    // The trail byte of the first character is `\`
+    assert_eq!(
+        reader.decode_strict(b"\x83\x5C\x83t\x83g").unwrap(),
+        "\u{30BD}\u{30D5}\u{30C8}"
+    );
+    // The trail byte is missing
+    match reader.decode_strict(b"\x83\x5C\x83") {
+        Err(Error::NonDecodable(name)) => assert_eq!(name, "Shift_JIS"),
+        x => panic!("Expected Err(NonDecodable(_)), got {:?}", x),
+    }
+    assert_eq!(reader.decode(b"\x83\x5C\x83"), "\u{30BD}\u{FFFD}");
 }
 
 #[test]
@@ -936,3 +1564,601 @@ fn test_unescape_and_decode_without_bom_does_nothing_if_no_bom_exists() {
     }
     assert_eq!(txt.is_empty(), true);
 }
+
+/// Reads all events from `xml`, returning the first error
+fn read_until_error(xml: &[u8], clear_buffer: bool) -> Result<()> {
+    let mut reader = Reader::from_reader(xml);
+    let mut buf = Vec::new();
+    // each event consumes at least one byte, so this is enough to detect infinite loops
+    for _ in 0..=xml.len() {
+        if let Event::Eof = reader.read_event(&mut buf)? {
+            return Ok(());
+        }
+        if clear_buffer {
+            buf.clear();
+        }
+    }
+    panic!("reader does not stop on {:?}", from_utf8(xml));
+}
+
+fn assert_unexpected_eof(xml: &str, context: &str) {
+    match read_until_error(xml.as_bytes(), true) {
+        Err(quick_xml::Error::UnexpectedEof(ref c)) if c == context => {}
+        e => panic!(
+            "expecting UnexpectedEof({:?}) for {:?}, found {:?}",
+            context, xml, e
+        ),
+    }
+}
+
+#[test]
+fn test_truncated_start() {
+    assert_unexpected_eof("<", "Start");
+    assert_unexpected_eof("<tag", "Start");
+    assert_unexpected_eof("<tag attr", "Start");
+    assert_unexpected_eof("<tag attr=\"val", "Start");
+    assert_unexpected_eof("<tag attr='v>l'", "Start");
+    assert_unexpected_eof("<tag/", "Start");
+    assert_unexpected_eof("text<", "Start");
+}
+
+#[test]
+fn test_truncated_end() {
+    assert_unexpected_eof("<tag></", "End");
+    assert_unexpected_eof("<tag></tag", "End");
+}
+
+#[test]
+fn test_truncated_bang() {
+    assert_unexpected_eof("<!", "Comment, CData or DOCTYPE");
+    assert_unexpected_eof("<!-", "Comment");
+    assert_unexpected_eof("<!--", "Comment");
+    assert_unexpected_eof("<!-- comment --", "Comment");
+    assert_unexpected_eof("<!-- a > b --", "Comment");
+    assert_unexpected_eof("<![CD", "CData");
+    assert_unexpected_eof("<![CDATA[", "CData");
+    assert_unexpected_eof("<![CDATA[data]]", "CData");
+    assert_unexpected_eof("<!DOC", "DOCTYPE");
+    assert_unexpected_eof("<!DOCTYPE html", "DOCTYPE");
+    assert_unexpected_eof("<!DOCTYPE e [<!ELEMENT e ANY>", "DOCTYPE");
+}
+
+#[test]
+fn test_include_section() {
+    let mut r = Reader::from_str(
+        "<root><![INCLUDE[<a x='1'/>text]]><![ INCLUDE [<![INCLUDE[<b/>]]>\n]]>tail</root>",
+    );
+    r.trim_text(true);
+    next_eq!(
+        r, Start, b"root", Empty, b"a", Text, b"text", Empty, b"b", Text, b"tail", End, b"root"
+    );
+    let mut buf = Vec::new();
+    assert!(matches!(r.read_event(&mut buf).unwrap(), Eof));
+}
+
+#[test]
+fn test_ignore_section() {
+    let mut r = Reader::from_str(
+        "<root><![IGNORE[<a x='>'><![INCLUDE[<b/>]]></a>]]><c/><![IGNORE[]]></root>",
+    );
+    r.trim_text(true);
+    next_eq!(r, Start, b"root", Empty, b"c", End, b"root");
+    let mut buf = Vec::new();
+    assert!(matches!(r.read_event(&mut buf).unwrap(), Eof));
+}
+
+#[test]
+fn test_marked_sections_in_doctype() {
+    let mut r = Reader::from_str(
+        "<!DOCTYPE a [<![INCLUDE[<!ELEMENT a ANY>]]><![IGNORE[<!ELEMENT b ANY>]]>]><a/>",
+    );
+    r.trim_text(true);
+    let mut buf = Vec::new();
+    match r.read_event(&mut buf).unwrap() {
+        DocType(e) => assert_eq!(
+            &*e,
+            b" a [<![INCLUDE[<!ELEMENT a ANY>]]><![IGNORE[<!ELEMENT b ANY>]]>]".as_ref()
+        ),
+        e => panic!("Expected DocType, found {:?}", e),
+    }
+    next_eq!(r, Empty, b"a");
+}
+
+#[test]
+fn test_invalid_marked_sections() {
+    let mut buf = Vec::new();
+    let mut r = Reader::from_str("<![UNKNOWN[data]]>");
+    r.trim_text(true);
+    match r.read_event(&mut buf) {
+        Err(Error::UnexpectedBang) => {}
+        e => panic!("Expected UnexpectedBang, found {:?}", e),
+    }
+    assert_unexpected_eof("<![IGNORE[<a>", "IGNORE");
+    assert_unexpected_eof("<![IGNORE[<![IGNORE[]]>", "IGNORE");
+    assert_unexpected_eof("<a><![INCLUDE[<b/>", "INCLUDE");
+}
+
+#[test]
+fn test_truncated_question_mark() {
+    assert_unexpected_eof("<?", "XmlDecl");
+    assert_unexpected_eof("<?xml version='1.0'", "XmlDecl");
+    assert_unexpected_eof("<?xml version='1.0'?", "XmlDecl");
+    assert_unexpected_eof("<?pi content?", "XmlDecl");
+}
+
+/// Every prefix of a document is either read completely or fails with an error
+#[test]
+fn test_truncated_document() {
+    let xml = br#"<?xml version="1.0"?><!DOCTYPE e [<!ELEMENT e ANY>]><e a="1" b='>'><!-- c -->t<![CDATA[d]]><?pi?><f/></e>"#;
+    for len in 0..=xml.len() {
+        for &clear_buffer in &[true, false] {
+            match read_until_error(&xml[..len], clear_buffer) {
+                Ok(()) | Err(quick_xml::Error::UnexpectedEof(_)) => {}
+                Err(e) => panic!("unexpected error for {:?}: {:?}", from_utf8(&xml[..len]), e),
+            }
+        }
+    }
+}
+
+#[cfg(feature = "escape-html")]
+#[test]
+fn test_resolve_html_entities() {
+    let mut reader = Reader::from_str("<a title='&copy;'>&nbsp;&nope;</a>");
+    reader.trim_text(true);
+    let mut buf = Vec::new();
+
+    let start = match reader.read_event(&mut buf) {
+        Ok(Start(e)) => e.into_owned(),
+        e => panic!("expecting Start, found {:?}", e),
+    };
+    let title = start.try_get_attribute("title").unwrap().unwrap();
+    assert_eq!(title.unescape_and_decode_value(&reader).unwrap(), "\u{A9}");
+    let text = match reader.read_event(&mut buf) {
+        Ok(Text(e)) => e.into_owned(),
+        e => panic!("expecting Text, found {:?}", e),
+    };
+    // Unknown entities are still errors
+    assert!(text.unescape_and_decode(&reader).is_err());
+
+    reader.resolve_html_entities(false);
+    assert!(title.unescape_and_decode_value(&reader).is_err());
+}
+
+#[test]
+fn test_read_to_end_spanned() {
+    let xml = "<root>\n  <item><item>nested</item><other/></item>\n  <item></item>\n</root>";
+    for &trim in &[false, true] {
+        let mut reader = Reader::from_str(xml);
+        reader.trim_text(trim);
+        let mut buf = Vec::new();
+        let mut spans = Vec::new();
+        loop {
+            match reader.read_event(&mut buf) {
+                Ok(Start(ref e)) if e.name().as_ref() == b"root" => (),
+                Ok(Start(ref e)) => {
+                    let name = e.name().as_ref().to_vec();
+                    spans.push(reader.read_to_end_spanned(name, &mut buf).unwrap());
+                }
+                Ok(Eof) => break,
+                Ok(_) => (),
+                Err(e) => panic!("{:?} at {}", e, reader.buffer_position()),
+            }
+            buf.clear();
+        }
+        let contents: Vec<_> = spans.into_iter().map(|span| &xml[span]).collect();
+        assert_eq!(
+            contents,
+            ["<item>nested</item><other/>", ""],
+            "trim: {}",
+            trim
+        );
+    }
+
+    // Positions of a buffered reader count the bytes consumed from it
+    let mut reader = Reader::from_reader(std::io::BufReader::with_capacity(3, xml.as_bytes()));
+    reader.trim_text(true);
+    let mut buf = Vec::new();
+    next_eq_name!(reader, Start, b"root");
+    next_eq_name!(reader, Start, b"item");
+    let span = reader.read_to_end_spanned(b"item", &mut buf).unwrap();
+    assert_eq!(&xml[span], "<item>nested</item><other/>");
+
+    let mut reader = Reader::from_str("<a><b></b>");
+    reader.trim_text(true);
+    next_eq_name!(reader, Start, b"a");
+    match reader.read_to_end_spanned(b"a", &mut buf) {
+        Err(Error::UnexpectedEof(_)) => (),
+        e => panic!("expecting UnexpectedEof, found {:?}", e),
+    }
+}
+
+#[test]
+fn test_seek_to_element() {
+    let xml = r#"<root>
+        <a><b><c id="1"/></b></a>
+        <a><b><c><d>deep</d><d id="2"/></c></b></a>
+        <d id="3">top</d>
+    </root>"#;
+    for &expand in &[false, true] {
+        let mut reader = Reader::from_str(xml);
+        reader.trim_text(true).expand_empty_elements(expand);
+        let mut buf = Vec::new();
+
+        let d = reader
+            .seek_to_element(QName(b"d"), &mut buf)
+            .unwrap()
+            .unwrap();
+        assert_eq!(&*d, b"d");
+        // The next event is the content of the found element
+        match reader.read_event(&mut buf).unwrap() {
+            Text(e) => assert_eq!(&*e, b"deep"),
+            e => panic!("expecting Text, found {:?}", e),
+        }
+
+        let mut ids = Vec::new();
+        let mut buf = Vec::new();
+        while let Some(d) = reader.seek_to_element(QName(b"d"), &mut buf).unwrap() {
+            let id = d.try_get_attribute("id").unwrap().unwrap();
+            ids.push(id.value.into_owned());
+        }
+        assert_eq!(ids, vec![b"2".to_vec(), b"3".to_vec()]);
+    }
+
+    // The content of the buffer before the events is kept
+    let mut reader = Reader::from_reader(Cursor::new(xml.as_bytes()));
+    let mut buf = b"kept".to_vec();
+    let c = reader
+        .seek_to_element(QName(b"c"), &mut buf)
+        .unwrap()
+        .unwrap();
+    assert_eq!(&*c, br#"c id="1""#);
+    assert!(buf.starts_with(b"kept"));
+
+    // Attributes, changed by the lenient mode, are returned as well
+    let mut reader = Reader::from_str("<root><x><a href=link>text</a></x></root>");
+    reader.lenient_attribute_values(true);
+    let a = reader
+        .seek_to_element(QName(b"a"), &mut buf)
+        .unwrap()
+        .unwrap();
+    assert_eq!(&*a, br#"a href="link""#);
+
+    let mut reader = Reader::from_str("<root><a>");
+    match reader.seek_to_element(QName(b"b"), &mut buf) {
+        Ok(None) => {}
+        e => panic!("expecting None, found {:?}", e),
+    }
+}
+
+/// Reads all events of `input` with both `read_event` and `read_event_unbuffered` and checks,
+/// that they are the same, including errors and positions
+fn compare_unbuffered(input: &[u8], name: &str, configure: fn(&mut Reader<&[u8]>)) {
+    let mut buffered = Reader::from_bytes(input);
+    let mut unbuffered = Reader::from_bytes(input);
+    configure(&mut buffered);
+    configure(&mut unbuffered);
+    let mut buf = Vec::new();
+    loop {
+        let expected = buffered.read_event(&mut buf);
+        let event = unbuffered.read_event_unbuffered();
+        assert_eq!(
+            format!("{:?}", event),
+            format!("{:?}", expected),
+            "{} at {}",
+            name,
+            buffered.buffer_position()
+        );
+        assert_eq!(buffered.buffer_position(), unbuffered.buffer_position());
+        match expected {
+            Ok(Eof) | Err(_) => break,
+            _ => buf.clear(),
+        }
+    }
+}
+
+#[test]
+fn test_read_event_unbuffered_corpus() {
+    let mut documents = Vec::new();
+    for dir in &["tests", "tests/documents"] {
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            match path.extension().and_then(|e| e.to_str()) {
+                Some("xml") | Some("html") | Some("rss") => documents.push(path),
+                _ => {}
+            }
+        }
+    }
+    assert!(documents.len() > 10);
+
+    let configs: [fn(&mut Reader<&[u8]>); 4] = [
+        |_| {},
+        |r| {
+            r.trim_text(true);
+        },
+        |r| {
+            r.trim_text_end(true).expand_empty_elements(true);
+        },
+        |r| {
+            r.check_end_names(false)
+                .check_comments(true)
+                .lenient_attribute_values(true)
+                .lenient_ampersands(true);
+        },
+    ];
+    for path in &documents {
+        let input = std::fs::read(path).unwrap();
+        for configure in configs.iter() {
+            compare_unbuffered(&input, &path.display().to_string(), *configure);
+        }
+    }
+}
+
+#[test]
+fn test_read_event_unbuffered_edge_cases() {
+    let documents = [
+        "<a x='>' y=\">\">text<!-- a > b -- c --></a>",
+        "<?xml version='1.0'?><!DOCTYPE a [<!ELEMENT a (#PCDATA)>]><a/>",
+        "<a><![CDATA[x > y ]] > ]]]></a>",
+        "<a>  \n  </a>  text  ",
+        "<a>&amp; & &lt;</a>",
+        "<a b=c d='e'/>",
+        "<a><![INCLUDE[<b/> text ]]>\n<![ IGNORE [<c>]]></a>",
+        "<![INCLUDE[ unclosed",
+        "<![IGNORE[ <![ unclosed ]]>",
+        "<a></b>",
+        "<!-- unclosed -",
+        "<![CDATA[ unclosed ]]",
+        "<!DOCTYPE a [<!ENTITY e 'x'>",
+        "<a",
+        "</a",
+        "<?pi",
+        "<!",
+        "",
+        "text only",
+    ];
+    let configs: [fn(&mut Reader<&[u8]>); 3] = [
+        |_| {},
+        |r| {
+            r.trim_text(true).check_comments(true).strict_prolog(true);
+        },
+        |r| {
+            r.expand_empty_elements(true)
+                .check_end_names(false)
+                .lenient_attribute_values(true)
+                .lenient_ampersands(true);
+        },
+    ];
+    for document in documents.iter() {
+        for configure in configs.iter() {
+            compare_unbuffered(document.as_bytes(), document, *configure);
+        }
+    }
+}
+
+/// Events of `read_event_unbuffered` borrow the input
+#[test]
+fn test_read_event_unbuffered_borrows() {
+    let input = "<a x='1'>text<b/><![CDATA[data]]></a>";
+    let range = input.as_bytes().as_ptr_range();
+    let mut reader = Reader::from_str(input);
+    reader.trim_text(true);
+    let mut events = Vec::new();
+    loop {
+        match reader.read_event_unbuffered().unwrap() {
+            Eof => break,
+            e => events.push(e),
+        }
+    }
+    assert_eq!(events.len(), 5);
+    for event in &events {
+        let bytes: &[u8] = match event {
+            Start(e) | Empty(e) => e,
+            End(e) => e,
+            Text(e) | CData(e) => e,
+            e => panic!("Unexpected event {:?}", e),
+        };
+        assert!(
+            range.contains(&bytes.as_ptr()),
+            "{:?} is not borrowed",
+            event
+        );
+    }
+    match events[0] {
+        Start(ref e) => {
+            let attribute = e.attributes().next().unwrap().unwrap();
+            assert!(range.contains(&attribute.value.as_ptr()));
+        }
+        ref e => panic!("Expected Start, found {:?}", e),
+    }
+}
+
+#[test]
+fn test_strict_prolog() {
+    let valid = [
+        "<?xml version='1.0'?><!DOCTYPE a><!-- c --><?pi?>\n<a>text<![CDATA[x]]></a>\n<!-- c -->",
+        "\u{FEFF}<?xml version='1.0'?><a/>",
+        "<!-- c --><!DOCTYPE a> <a><b/><c></c></a>",
+        " \n<a/>",
+        "",
+    ];
+    for document in valid.iter() {
+        for &trim in &[false, true] {
+            let mut reader = Reader::from_str(document);
+            reader.strict_prolog(true).trim_text(trim);
+            let mut buf = Vec::new();
+            loop {
+                match reader.read_event(&mut buf) {
+                    Ok(Eof) => break,
+                    Ok(_) => {}
+                    Err(e) => panic!("{:?}: {:?}", document, e),
+                }
+            }
+        }
+    }
+
+    let invalid = [
+        (" <?xml version='1.0'?><a/>", 1),
+        ("<!-- c --><?xml version='1.0'?><a/>", 10),
+        ("<a/><?xml version='1.0'?>", 4),
+        ("<!DOCTYPE a><!DOCTYPE a><a/>", 12),
+        ("<a/><!DOCTYPE a>", 4),
+        ("text<a/>", 0),
+        ("<a/> text", 5),
+        ("<a/><b/>", 4),
+        ("<a><b/></a><b/>", 11),
+        ("<![CDATA[x]]><a/>", 0),
+        ("</a>", 0),
+    ];
+    for &(document, position) in invalid.iter() {
+        for &trim in &[false, true] {
+            let mut reader = Reader::from_str(document);
+            reader
+                .strict_prolog(true)
+                .trim_text(trim)
+                .check_end_names(false);
+            let mut buf = Vec::new();
+            let error = loop {
+                match reader.read_event(&mut buf) {
+                    Ok(Eof) => panic!("{:?}: no error", document),
+                    Ok(_) => {}
+                    Err(e) => break e,
+                }
+            };
+            match error {
+                Error::InvalidProlog(p, _) => assert_eq!(p, position, "{:?}", document),
+                e => panic!("{:?}: expected `InvalidProlog`, found {:?}", document, e),
+            }
+            assert!(matches!(reader.read_event(&mut buf), Ok(Eof)));
+        }
+    }
+}
+
+#[test]
+fn test_max_depth() {
+    let valid = ["<a><b><c/></b></a>", "<a><b></b><b><c></c></b></a>"];
+    for document in valid.iter() {
+        for &expand in &[false, true] {
+            let mut reader = Reader::from_str(document);
+            reader.max_depth(3).expand_empty_elements(expand);
+            let mut buf = Vec::new();
+            while !matches!(reader.read_event(&mut buf).unwrap(), Eof) {}
+        }
+    }
+
+    let invalid = [
+        "<a><b><c><d/></c></b></a>",
+        "<a><b/><b><c><d></d></c></b></a>",
+    ];
+    for document in invalid.iter() {
+        for &expand in &[false, true] {
+            let mut reader = Reader::from_str(document);
+            reader.max_depth(3).expand_empty_elements(expand);
+            let mut buf = Vec::new();
+            let error = loop {
+                match reader.read_event(&mut buf) {
+                    Ok(Eof) => panic!("{:?}: no error", document),
+                    Ok(_) => {}
+                    Err(e) => break e,
+                }
+            };
+            match error {
+                Error::TooDeep(3) => {}
+                e => panic!("{:?}: expected `TooDeep(3)`, found {:?}", document, e),
+            }
+            assert!(matches!(reader.read_event(&mut buf), Ok(Eof)));
+        }
+    }
+}
+
+#[test]
+fn test_forbid_doctype() {
+    let mut reader = Reader::from_str("<?xml version='1.0'?> <!DOCTYPE a><a/>");
+    reader.forbid_doctype(true).trim_text(true);
+    let mut buf = Vec::new();
+    assert!(matches!(reader.read_event(&mut buf), Ok(Decl(_))));
+    match reader.read_event(&mut buf) {
+        Err(Error::ForbiddenDocType(22)) => {}
+        e => panic!("Expected `ForbiddenDocType(22)`, found {:?}", e),
+    }
+    assert!(!reader.has_doctype());
+}
+
+#[test]
+fn test_reader_config() {
+    let config = ReaderConfig::default()
+        .trim_text(true)
+        .expand_empty_elements(true);
+    let document = "<a> <b/> </a>";
+    let mut reader = config.build_from_str(document);
+    let mut buf = Vec::new();
+    next_eq!(reader, Start, b"a", Start, b"b", End, b"b", End, b"a");
+
+    // The setters change the configuration of the reader, so it can be shared
+    let mut reader = Reader::from_str(document);
+    reader.trim_text(true).expand_empty_elements(true);
+    let mut reader = reader.config().build_from_reader(document.as_bytes());
+    next_eq!(reader, Start, b"a", Start, b"b", End, b"b", End, b"a");
+
+    let mut reader = config
+        .clone()
+        .expand_empty_elements(false)
+        .build_from_str(document);
+    next_eq!(reader, Start, b"a", Empty, b"b", End, b"a");
+    assert!(matches!(reader.read_event(&mut buf), Ok(Eof)));
+
+    let secure = ReaderConfig::secure().trim_text(true);
+    let mut reader = secure.build_from_str("<a><!-- a -- b --></a>");
+    reader.read_event(&mut buf).unwrap();
+    assert!(matches!(
+        reader.read_event(&mut buf),
+        Err(Error::UnexpectedToken(_))
+    ));
+    let nested = "<a>".repeat(65);
+    let mut reader = secure.build_from_str(&nested);
+    let error = loop {
+        match reader.read_event(&mut buf) {
+            Ok(_) => {}
+            Err(e) => break e,
+        }
+    };
+    assert!(matches!(error, Error::TooDeep(64)));
+}
+
+#[test]
+fn test_has_declaration() {
+    let mut reader = Reader::from_str("<?xml version='1.0'?><!DOCTYPE a><a/>");
+    reader.trim_text(true);
+    let mut buf = Vec::new();
+    assert!(!reader.has_declaration());
+    reader.read_event(&mut buf).unwrap();
+    assert!(reader.has_declaration());
+    assert!(!reader.has_doctype());
+    reader.read_event(&mut buf).unwrap();
+    assert!(reader.has_doctype());
+
+    let mut reader = Reader::from_str("<a/>");
+    while !matches!(reader.read_event(&mut buf).unwrap(), Eof) {}
+    assert!(!reader.has_declaration());
+    assert!(!reader.has_doctype());
+}
+
+#[test]
+fn test_attribute_whitespace_round_trip() {
+    let value = "first line\n\tsecond line\r\nlast\r";
+    let mut start = BytesStart::borrowed_name(b"a");
+    start.push_attribute(("value", value));
+    let mut writer = Writer::new(Vec::new());
+    writer.write_event(Empty(start)).unwrap();
+    let xml = writer.into_inner();
+
+    let mut reader = Reader::from_reader(&xml[..]);
+    reader.trim_text(true);
+    let mut buf = Vec::new();
+    match reader.read_event(&mut buf).unwrap() {
+        Empty(e) => {
+            let attr = e.try_get_attribute("value").unwrap().unwrap();
+            assert_eq!(attr.normalized_value(reader.decoder()).unwrap(), value);
+        }
+        e => panic!("Expected `Empty`, found {:?}", e),
+    }
+}
@@ -180,7 +180,7 @@ fn issue_93_large_characters_in_entity_references() {
         r#"<hello>&𤶼;</hello>"#.as_bytes(),
         r#"
             |StartElement(hello)
-            |1:10 FailedUnescape([38, 240, 164, 182, 188, 59]; Error while escaping character at range 1..5: Unrecognized escape symbol: Ok("𤶼"))
+            |1:10 FailedUnescape([38, 240, 164, 182, 188, 59]; Error while escaping character at range 0..6: Unrecognized escape symbol: Ok("𤶼"))
             |EndElement(hello)
             |EndDocument
         "#
@@ -370,7 +370,7 @@ fn make_attrs(e: &BytesStart) -> ::std::result::Result<String, String> {
                 if a.key.len() < 5 || !a.key.starts_with(b"xmlns") {
                     atts.push(format!(
                         "{}=\"{}\"",
-                        from_utf8(a.key).unwrap(),
+                        from_utf8(&a.key).unwrap(),
                         from_utf8(&*a.unescaped_value().unwrap()).unwrap()
                     ));
                 }
@@ -384,7 +384,7 @@ fn make_attrs(e: &BytesStart) -> ::std::result::Result<String, String> {
 fn xmlrs_display(opt_event: &Result<(Option<&[u8]>, Event)>) -> String {
     match opt_event {
         Ok((ref n, Event::Start(ref e))) => {
-            let name = namespace_name(n, e.name());
+            let name = namespace_name(n, &e.name());
             match make_attrs(e) {
                 Ok(ref attrs) if attrs.is_empty() => format!("StartElement({})", &name),
                 Ok(ref attrs) => format!("StartElement({} [{}])", &name, &attrs),
@@ -392,14 +392,14 @@ fn xmlrs_display(opt_event: &Result<(Option<&[u8]>, Event)>) -> String {
             }
         }
         Ok((ref n, Event::Empty(ref e))) => {
-            let name = namespace_name(n, e.name());
+            let name = namespace_name(n, &e.name());
             match make_attrs(e) {
                 Ok(ref attrs) if attrs.is_empty() => format!("EmptyElement({})", &name),
                 Ok(ref attrs) => format!("EmptyElement({} [{}])", &name, &attrs),
                 Err(e) => format!("EmptyElement({}, attr-error: {})", &name, &e),
             }
         }
-        Ok((ref n, Event::End(ref e))) => format!("EndElement({})", namespace_name(n, e.name())),
+        Ok((ref n, Event::End(ref e))) => format!("EndElement({})", namespace_name(n, &e.name())),
         Ok((_, Event::Comment(ref e))) => format!("Comment({})", from_utf8(e).unwrap()),
         Ok((_, Event::CData(ref e))) => format!("CData({})", from_utf8(e).unwrap()),
         Ok((_, Event::Text(ref e))) => match e.unescaped() {